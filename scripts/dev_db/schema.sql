@@ -182,6 +182,22 @@ CREATE INDEX idx_material_state_path_override_rejected
   ON material_state(path_override_rejected)
   WHERE path_override_rejected = 1;
 
+-- 人工干预时效（锁定/强制放行/人工紧急/优先级提升，到期或重算 N 次后自动清除）
+CREATE TABLE material_intervention_expiry (
+  material_id TEXT NOT NULL REFERENCES material_master(material_id) ON DELETE CASCADE,
+  intervention_type TEXT NOT NULL,  -- LOCK / FORCE_RELEASE / MANUAL_URGENT / PRIORITY_BOOST
+  expires_at TEXT,                  -- YYYY-MM-DD HH:MM:SS（本地时间）
+  expire_after_recalcs INTEGER,     -- 设置后完成 N 次重算即失效
+  operator TEXT NOT NULL,
+  reason TEXT,
+  anchor_action_id TEXT NOT NULL,   -- 设置时效的 action_log（重算计数起点）
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (material_id, intervention_type)
+);
+
+CREATE INDEX idx_material_intervention_expiry_operator
+  ON material_intervention_expiry(operator, expires_at);
+
 -- ==========================================
 -- Plan / version / items
 -- ==========================================
@@ -223,11 +239,25 @@ CREATE TABLE plan_item (
   locked_in_plan INTEGER NOT NULL DEFAULT 0,
   force_release_in_plan INTEGER NOT NULL DEFAULT 0,
   violation_flags TEXT,
+  assign_reason TEXT,
   PRIMARY KEY (version_id, material_id)
 );
 
 CREATE INDEX idx_item_version_machine_date ON plan_item(version_id, machine_code, plan_date, seq_no);
 
+-- 钢种族批量顺延（重算结束仍因最小批量规则未排入的材料）
+CREATE TABLE grade_batching_deferral (
+  version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
+  material_id TEXT NOT NULL,
+  machine_code TEXT NOT NULL,
+  plan_date TEXT NOT NULL,
+  grade_family TEXT NOT NULL,
+  reason TEXT NOT NULL,
+  PRIMARY KEY (version_id, material_id)
+);
+
+CREATE INDEX idx_grade_batching_deferral_family ON grade_batching_deferral(version_id, grade_family);
+
 -- ==========================================
 -- Plan rhythm (daily production rhythm targets)
 -- ==========================================
@@ -260,6 +290,102 @@ CREATE TABLE plan_rhythm_target (
 CREATE INDEX idx_rhythm_target_version_machine_date
   ON plan_rhythm_target(version_id, machine_code, plan_date);
 
+-- ==========================================
+-- Production actuals (plan-vs-actual reconciliation)
+-- ==========================================
+
+-- 生产实绩（事实数据，不随版本变化；version_id 记录导入时的激活版本）
+CREATE TABLE production_actual (
+  material_id TEXT PRIMARY KEY REFERENCES material_master(material_id),
+  batch_id TEXT NOT NULL,
+  version_id TEXT,
+  machine_code TEXT NOT NULL,
+  completed_at TEXT NOT NULL, -- YYYY-MM-DD HH:MM:SS
+  actual_weight_t REAL NOT NULL,
+  created_by TEXT,
+  created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_production_actual_machine_time
+  ON production_actual(machine_code, completed_at);
+
+-- 计划执行对账（按版本 + 机组 + 日期）
+CREATE TABLE plan_adherence_daily (
+  version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
+  machine_code TEXT NOT NULL,
+  plan_date TEXT NOT NULL, -- YYYY-MM-DD
+  planned_count INTEGER NOT NULL DEFAULT 0,
+  planned_weight_t REAL NOT NULL DEFAULT 0,
+  actual_count INTEGER NOT NULL DEFAULT 0,
+  actual_weight_t REAL NOT NULL DEFAULT 0,
+  matched_count INTEGER NOT NULL DEFAULT 0,
+  matched_weight_t REAL NOT NULL DEFAULT 0,
+  unplanned_count INTEGER NOT NULL DEFAULT 0,
+  carry_over_count INTEGER NOT NULL DEFAULT 0,
+  sequence_deviation_count INTEGER NOT NULL DEFAULT 0,
+  max_sequence_displacement INTEGER NOT NULL DEFAULT 0,
+  avg_sequence_displacement REAL NOT NULL DEFAULT 0,
+  adherence_rate REAL,
+  batch_id TEXT,
+  generated_at TEXT NOT NULL,
+  PRIMARY KEY (version_id, machine_code, plan_date)
+);
+
+-- 计划未完工顺延项（后续完工时回填 resolved_at）
+CREATE TABLE plan_carry_over (
+  version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
+  material_id TEXT NOT NULL,
+  machine_code TEXT NOT NULL,
+  plan_date TEXT NOT NULL, -- 原计划日期
+  seq_no INTEGER NOT NULL,
+  weight_t REAL NOT NULL,
+  detected_at TEXT NOT NULL,
+  resolved_at TEXT,
+  PRIMARY KEY (version_id, material_id)
+);
+
+-- ==========================================
+-- Scenario / sandbox plans (isolated what-if evaluation)
+-- ==========================================
+
+-- 场景假设叠加层（产能调整/假设材料/机组停机/配置覆盖，整体 JSON）
+CREATE TABLE scenario_overlay (
+  plan_id TEXT PRIMARY KEY REFERENCES plan(plan_id) ON DELETE CASCADE,
+  overlay_json TEXT NOT NULL,
+  updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_by TEXT
+);
+
+-- 场景评估结果（在数据库快照上试算，promoted_version_id 为提升后的草稿版本）
+CREATE TABLE scenario_run (
+  run_id TEXT PRIMARY KEY,
+  plan_id TEXT NOT NULL REFERENCES plan(plan_id) ON DELETE CASCADE,
+  base_version_id TEXT NOT NULL,
+  run_json TEXT NOT NULL,
+  created_by TEXT NOT NULL,
+  created_at TEXT NOT NULL, -- YYYY-MM-DD HH:MM:SS
+  promoted_version_id TEXT
+);
+
+CREATE INDEX idx_scenario_run_plan ON scenario_run(plan_id, created_at);
+
+-- ==========================================
+-- Version activation approval (submit / approve / reject)
+-- ==========================================
+
+-- 版本审批记录（状态流转见 plan_version.status: SUBMITTED/APPROVED/REJECTED）
+CREATE TABLE plan_version_review (
+  review_id TEXT PRIMARY KEY,
+  version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
+  action TEXT NOT NULL,     -- SUBMIT / APPROVE / REJECT
+  actor TEXT NOT NULL,
+  comment TEXT,             -- 提交说明 / 审批意见
+  checklist_json TEXT,      -- 提交时的激活前检查单
+  created_at TEXT NOT NULL  -- YYYY-MM-DD HH:MM:SS
+);
+
+CREATE INDEX idx_plan_version_review_version ON plan_version_review(version_id, created_at);
+
 -- ==========================================
 -- Capacity / risk / roll
 -- ==========================================
@@ -283,6 +409,36 @@ CREATE TABLE capacity_pool (
 CREATE INDEX idx_pool_version_machine_date ON capacity_pool(version_id, machine_code, plan_date);
 CREATE INDEX idx_pool_machine_date ON capacity_pool(machine_code, plan_date);
 
+-- 产能预留（战略合同/客户）与按版本的预留使用快照
+CREATE TABLE capacity_reservation (
+  reservation_id TEXT PRIMARY KEY,
+  machine_code TEXT NOT NULL,
+  date_from TEXT NOT NULL,
+  date_to TEXT NOT NULL,
+  daily_reserved_t REAL NOT NULL,
+  contract_no TEXT,
+  contract_prefix TEXT,
+  release_days_before INTEGER NOT NULL DEFAULT 0,
+  expires_on TEXT,
+  reason TEXT,
+  created_by TEXT NOT NULL,
+  created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_capacity_reservation_machine_date ON capacity_reservation(machine_code, date_from, date_to);
+
+CREATE TABLE capacity_reservation_usage (
+  version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
+  reservation_id TEXT NOT NULL,
+  machine_code TEXT NOT NULL,
+  plan_date TEXT NOT NULL,
+  reserved_t REAL NOT NULL,
+  used_t REAL NOT NULL,
+  released_t REAL NOT NULL,
+  held INTEGER NOT NULL,
+  PRIMARY KEY (version_id, reservation_id, plan_date)
+);
+
 CREATE TABLE risk_snapshot (
   version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
   machine_code TEXT NOT NULL REFERENCES machine_master(machine_code),
@@ -0,0 +1,415 @@
+// ==========================================
+// 热轧精整排产系统 - 激活前红线校验器
+// ==========================================
+// 职责: 版本激活前系统性扫描工业红线，输出带严重度的违规报告
+// 扫描范围: plan_item / capacity_pool / material_state / roller_campaign / path_override_pending
+// 红线合规:
+// - 红线1 冻结区保护: 基准激活版本的冻结明细不得移动/移出        (HARD)
+// - 红线2 适温约束: 未适温材料必须强制放行才能入计划             (HARD)
+// - 红线4 产能优先: 机组日排产吨位不得超过产能上限               (HARD)
+// - 换辊硬上限: 换辊批次累计吨位不得超过强制换辊阈值             (HARD)
+// - 路径规则: 路径违规材料必须人工确认后才能上线                 (HARD)
+// - 红线5 可解释性: 明细必须带落位原因 assign_reason            (WARNING)
+// ==========================================
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::domain::capacity::CapacityPool;
+use crate::domain::plan::PlanItem;
+use crate::domain::red_line::{RedLineReport, RedLineSeverity, RedLineViolation};
+use crate::domain::roller::RollerCampaign;
+use crate::repository::capacity_repo::CapacityPoolRepository;
+use crate::repository::material_repo::{MaterialStateRepository, MaterialStateSnapshotLite};
+use crate::repository::path_override_pending_repo::{
+    PathOverridePendingRecord, PathOverridePendingRepository,
+};
+use crate::repository::plan_repo::{PlanItemRepository, PlanVersionRepository};
+use crate::repository::roller_repo::RollerCampaignRepository;
+
+/// 超限判定容差（吨）
+const OVERFLOW_EPSILON_T: f64 = 1e-6;
+
+// ==========================================
+// ActivationRedLineValidator - 激活前红线校验器
+// ==========================================
+pub struct ActivationRedLineValidator {
+    plan_version_repo: Arc<PlanVersionRepository>,
+    plan_item_repo: Arc<PlanItemRepository>,
+    material_state_repo: Arc<MaterialStateRepository>,
+    capacity_repo: Arc<CapacityPoolRepository>,
+    roller_campaign_repo: Arc<RollerCampaignRepository>,
+    path_override_pending_repo: Arc<PathOverridePendingRepository>,
+}
+
+impl ActivationRedLineValidator {
+    /// 创建新的ActivationRedLineValidator实例
+    pub fn new(
+        plan_version_repo: Arc<PlanVersionRepository>,
+        plan_item_repo: Arc<PlanItemRepository>,
+        material_state_repo: Arc<MaterialStateRepository>,
+        capacity_repo: Arc<CapacityPoolRepository>,
+        roller_campaign_repo: Arc<RollerCampaignRepository>,
+        path_override_pending_repo: Arc<PathOverridePendingRepository>,
+    ) -> Self {
+        Self {
+            plan_version_repo,
+            plan_item_repo,
+            material_state_repo,
+            capacity_repo,
+            roller_campaign_repo,
+            path_override_pending_repo,
+        }
+    }
+
+    /// 生成版本红线校验报告
+    ///
+    /// # 说明
+    /// - 冻结区对比基准为同方案当前激活版本（版本自身已激活时无基准）
+    /// - 只读扫描，不修改任何数据
+    pub fn validate_version(&self, version_id: &str) -> ApiResult<RedLineReport> {
+        let version = self
+            .plan_version_repo
+            .find_by_id(version_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+
+        let items = self.plan_item_repo.find_by_version(version_id)?;
+        let material_ids: Vec<String> = items.iter().map(|i| i.material_id.clone()).collect();
+        let states: HashMap<String, MaterialStateSnapshotLite> = self
+            .material_state_repo
+            .find_snapshots_by_material_ids(&material_ids)?
+            .into_iter()
+            .map(|s| (s.material_id.clone(), s))
+            .collect();
+        let pools = self.capacity_repo.find_by_version_id(version_id)?;
+        let campaigns = self.roller_campaign_repo.find_by_version_id(version_id)?;
+        let pending = self
+            .path_override_pending_repo
+            .list_unconfirmed_by_version(version_id)?;
+
+        let baseline = self
+            .plan_version_repo
+            .find_active_version(&version.plan_id)?
+            .filter(|v| v.version_id != version.version_id);
+        let baseline_frozen = match &baseline {
+            Some(v) => self.plan_item_repo.find_frozen_items(&v.version_id)?,
+            None => Vec::new(),
+        };
+
+        let violations = collect_violations(&RedLineScanInput {
+            items: &items,
+            states: &states,
+            pools: &pools,
+            campaigns: &campaigns,
+            baseline_frozen: &baseline_frozen,
+            pending_path_overrides: &pending,
+        });
+
+        Ok(RedLineReport::new(
+            version_id,
+            baseline.map(|v| v.version_id),
+            violations,
+            chrono::Local::now().naive_local(),
+        ))
+    }
+}
+
+// ==========================================
+// 红线扫描（纯函数）
+// ==========================================
+
+struct RedLineScanInput<'a> {
+    items: &'a [PlanItem],
+    states: &'a HashMap<String, MaterialStateSnapshotLite>,
+    pools: &'a [CapacityPool],
+    campaigns: &'a [RollerCampaign],
+    baseline_frozen: &'a [PlanItem],
+    pending_path_overrides: &'a [PathOverridePendingRecord],
+}
+
+fn collect_violations(input: &RedLineScanInput) -> Vec<RedLineViolation> {
+    let mut violations = Vec::new();
+    let items_by_material: HashMap<&str, &PlanItem> = input
+        .items
+        .iter()
+        .map(|i| (i.material_id.as_str(), i))
+        .collect();
+
+    // 红线1: 基准版本冻结明细不得移动/移出
+    for frozen in input.baseline_frozen {
+        let message = match items_by_material.get(frozen.material_id.as_str()) {
+            None => format!(
+                "冻结材料{}已被移出计划（原 {}@{}）",
+                frozen.material_id, frozen.machine_code, frozen.plan_date
+            ),
+            Some(item)
+                if item.machine_code != frozen.machine_code
+                    || item.plan_date != frozen.plan_date =>
+            {
+                format!(
+                    "冻结材料{}被移动: {}@{} → {}@{}",
+                    frozen.material_id,
+                    frozen.machine_code,
+                    frozen.plan_date,
+                    item.machine_code,
+                    item.plan_date
+                )
+            }
+            Some(_) => continue,
+        };
+        violations.push(material_violation(
+            "FROZEN_MOVED",
+            RedLineSeverity::Hard,
+            frozen,
+            message,
+        ));
+    }
+
+    // 红线2: 未适温材料必须强制放行（冻结明细沿用既有决策，不重复校验）
+    for item in input.items.iter().filter(|i| !i.locked_in_plan) {
+        let Some(state) = input.states.get(&item.material_id) else {
+            continue;
+        };
+        let released = item.force_release_in_plan || state.force_release_flag.unwrap_or(false);
+        if !released && is_immature_on(state, item.plan_date) {
+            violations.push(material_violation(
+                "IMMATURE_NOT_RELEASED",
+                RedLineSeverity::Hard,
+                item,
+                match state.earliest_sched_date {
+                    Some(d) => format!(
+                        "材料{}最早可排日期为{}，排于{}且未强制放行",
+                        item.material_id, d, item.plan_date
+                    ),
+                    None => format!("材料{}未适温且未强制放行", item.material_id),
+                },
+            ));
+        }
+    }
+
+    // 红线4: 机组日排产吨位不得超过产能上限（以 plan_item 实际吨位为准）
+    let mut used_by_day: BTreeMap<(&str, NaiveDate), f64> = BTreeMap::new();
+    for item in input.items {
+        *used_by_day
+            .entry((item.machine_code.as_str(), item.plan_date))
+            .or_insert(0.0) += item.weight_t;
+    }
+    for pool in input.pools {
+        let used = used_by_day
+            .get(&(pool.machine_code.as_str(), pool.plan_date))
+            .copied()
+            .unwrap_or(0.0);
+        if pool.limit_capacity_t > 0.0 && used > pool.limit_capacity_t + OVERFLOW_EPSILON_T {
+            violations.push(RedLineViolation {
+                code: "CAPACITY_OVER_LIMIT".to_string(),
+                severity: RedLineSeverity::Hard,
+                material_id: None,
+                machine_code: Some(pool.machine_code.clone()),
+                plan_date: Some(pool.plan_date),
+                message: format!(
+                    "{}@{} 排产{:.1}t 超过上限{:.1}t",
+                    pool.machine_code, pool.plan_date, used, pool.limit_capacity_t
+                ),
+            });
+        }
+    }
+
+    // 换辊硬上限
+    for campaign in input.campaigns {
+        if campaign.hard_limit_t > 0.0
+            && campaign.cum_weight_t > campaign.hard_limit_t + OVERFLOW_EPSILON_T
+        {
+            violations.push(RedLineViolation {
+                code: "ROLL_HARD_LIMIT".to_string(),
+                severity: RedLineSeverity::Hard,
+                material_id: None,
+                machine_code: Some(campaign.machine_code.clone()),
+                plan_date: Some(campaign.start_date),
+                message: format!(
+                    "{} 换辊批次#{} 累计{:.1}t 超过强制换辊阈值{:.1}t",
+                    campaign.machine_code,
+                    campaign.campaign_no,
+                    campaign.cum_weight_t,
+                    campaign.hard_limit_t
+                ),
+            });
+        }
+    }
+
+    // 路径规则: 已入计划的路径违规材料必须人工确认
+    for pending in input.pending_path_overrides {
+        let Some(item) = items_by_material.get(pending.material_id.as_str()) else {
+            continue;
+        };
+        if item.machine_code != pending.machine_code {
+            continue;
+        }
+        violations.push(material_violation(
+            "PATH_VIOLATION_UNCONFIRMED",
+            RedLineSeverity::Hard,
+            item,
+            format!(
+                "材料{}路径违规({})未经人工确认",
+                pending.material_id, pending.violation_type
+            ),
+        ));
+    }
+
+    // 红线5: 可解释性
+    for item in input.items {
+        if item
+            .assign_reason
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or("")
+            .is_empty()
+        {
+            violations.push(material_violation(
+                "MISSING_ASSIGN_REASON",
+                RedLineSeverity::Warning,
+                item,
+                format!("材料{}缺少落位原因", item.material_id),
+            ));
+        }
+    }
+
+    violations
+}
+
+/// 材料在指定日期是否仍未适温
+fn is_immature_on(state: &MaterialStateSnapshotLite, plan_date: NaiveDate) -> bool {
+    match state.earliest_sched_date {
+        Some(earliest) => plan_date < earliest,
+        None => state.sched_state.as_deref() == Some("PENDING_MATURE"),
+    }
+}
+
+fn material_violation(
+    code: &str,
+    severity: RedLineSeverity,
+    item: &PlanItem,
+    message: String,
+) -> RedLineViolation {
+    RedLineViolation {
+        code: code.to_string(),
+        severity,
+        material_id: Some(item.material_id.clone()),
+        machine_code: Some(item.machine_code.clone()),
+        plan_date: Some(item.plan_date),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    fn item(material_id: &str, machine_code: &str, day: u32, weight_t: f64) -> PlanItem {
+        PlanItem {
+            version_id: "V1".to_string(),
+            material_id: material_id.to_string(),
+            machine_code: machine_code.to_string(),
+            plan_date: date(day),
+            seq_no: 1,
+            weight_t,
+            source_type: "CALC".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            urgent_level: None,
+            sched_state: None,
+            assign_reason: Some("TARGET_FILL".to_string()),
+            steel_grade: None,
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: None,
+            due_date: None,
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+
+    fn state(material_id: &str, earliest_day: u32) -> MaterialStateSnapshotLite {
+        MaterialStateSnapshotLite {
+            material_id: material_id.to_string(),
+            sched_state: Some("PENDING_MATURE".to_string()),
+            urgent_level: None,
+            rush_level: None,
+            lock_flag: Some(false),
+            force_release_flag: Some(false),
+            manual_urgent_flag: Some(false),
+            in_frozen_zone: Some(false),
+            ready_in_days: Some(2),
+            earliest_sched_date: Some(date(earliest_day)),
+            scheduled_date: None,
+            scheduled_machine_code: None,
+            seq_no: None,
+        }
+    }
+
+    fn scan(
+        items: &[PlanItem],
+        states: &HashMap<String, MaterialStateSnapshotLite>,
+        baseline_frozen: &[PlanItem],
+    ) -> Vec<RedLineViolation> {
+        collect_violations(&RedLineScanInput {
+            items,
+            states,
+            pools: &[],
+            campaigns: &[],
+            baseline_frozen,
+            pending_path_overrides: &[],
+        })
+    }
+
+    #[test]
+    fn test_immature_item_requires_force_release() {
+        let states: HashMap<String, MaterialStateSnapshotLite> =
+            [("M1".to_string(), state("M1", 5))].into_iter().collect();
+
+        let early = vec![item("M1", "H032", 3, 10.0)];
+        let violations = scan(&early, &states, &[]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].code, "IMMATURE_NOT_RELEASED");
+
+        let mut released = early.clone();
+        released[0].force_release_in_plan = true;
+        assert!(scan(&released, &states, &[]).is_empty());
+
+        let on_time = vec![item("M1", "H032", 5, 10.0)];
+        assert!(scan(&on_time, &states, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_frozen_moved_and_missing_reason() {
+        let mut frozen = item("M1", "H032", 3, 10.0);
+        frozen.locked_in_plan = true;
+
+        let mut moved = item("M1", "H033", 3, 10.0);
+        moved.assign_reason = None;
+        let violations = scan(&[moved], &HashMap::new(), &[frozen.clone()]);
+        let codes: Vec<(&str, RedLineSeverity)> = violations
+            .iter()
+            .map(|v| (v.code.as_str(), v.severity))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("FROZEN_MOVED", RedLineSeverity::Hard),
+                ("MISSING_ASSIGN_REASON", RedLineSeverity::Warning),
+            ]
+        );
+
+        // 冻结材料被移出同样视为违规
+        let removed = scan(&[], &HashMap::new(), &[frozen]);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].code, "FROZEN_MOVED");
+    }
+}
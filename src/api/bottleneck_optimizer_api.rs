@@ -0,0 +1,851 @@
+// ==========================================
+// 热轧精整排产系统 - 瓶颈优化建议 API（一键优化后端）
+// ==========================================
+// 职责:
+// - 基于版本排产明细/产能池/换辊计划/路径待确认/未适温紧急材料生成瓶颈调整建议
+// - 为每条建议附加 ImpactSummaryEngine 预计影响（单条建议相对当前计划）
+// - 一次调用执行选中的建议: 移动走 move_items 校验与日志，其余走对应业务接口
+// 说明:
+// - 执行前按当前数据重新生成建议，只执行仍然有效的建议ID（避免按过期建议操作）
+// ==========================================
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::api::plan_api::{MoveItemRequest, MoveItemsResponse};
+use crate::api::{MaterialApi, PathRuleApi, PlanApi, RollerApi, ValidationMode};
+use crate::config::{config_keys, ConfigManager};
+use crate::domain::capacity::CapacityPool;
+use crate::domain::material::MaterialState;
+use crate::domain::plan::PlanItem;
+use crate::domain::types::{SchedState, UrgentLevel};
+use crate::engine::bottleneck_optimizer::{
+    BottleneckActionKind, BottleneckOptimizer, BottleneckOptimizerInput, BottleneckRecommendation,
+    MachineDayLoad, MovableItem, PathOverrideCandidate, ReleaseCandidate, RollChangePlan,
+};
+use crate::engine::ImpactSummaryEngine;
+use crate::repository::capacity_repo::CapacityPoolRepository;
+use crate::repository::material_repo::{MaterialMasterRepository, MaterialStateRepository};
+use crate::repository::path_override_pending_repo::PathOverridePendingRepository;
+use crate::repository::plan_repo::{PlanItemRepository, PlanVersionRepository};
+use crate::repository::roll_campaign_plan_repo::{
+    RollCampaignPlanEntity, RollCampaignPlanRepository,
+};
+
+/// 分析窗口上限（天）
+const MAX_OPTIMIZE_WINDOW_DAYS: i64 = 60;
+/// 换辊停机时长默认值（分钟，与 D5 一致）
+const DEFAULT_ROLL_DOWNTIME_MINUTES: i32 = 45;
+
+/// 瓶颈优化建议列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BottleneckRecommendationResponse {
+    pub version_id: String,
+    pub plan_date_from: NaiveDate,
+    pub plan_date_to: NaiveDate,
+    pub machine_code: Option<String>,
+    /// 当前瓶颈日数 / 超目标吨位（含换辊停机折算）
+    pub bottleneck_day_count: usize,
+    pub overload_t: f64,
+    /// 全部建议执行后的预计瓶颈日数 / 超目标吨位
+    pub remaining_bottleneck_day_count: usize,
+    pub remaining_overload_t: f64,
+    pub recommendations: Vec<BottleneckRecommendation>,
+    pub message: String,
+}
+
+/// 未执行的建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedRecommendation {
+    pub recommendation_id: String,
+    pub reason: String,
+}
+
+/// 一键执行建议结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyBottleneckRecommendationsResponse {
+    pub version_id: String,
+    pub applied_ids: Vec<String>,
+    pub skipped: Vec<SkippedRecommendation>,
+    pub moved_count: usize,
+    pub force_released_count: usize,
+    pub path_override_confirmed_count: usize,
+    pub roll_change_shifted_count: usize,
+    /// move_items 原始结果（含逐条校验信息）
+    pub move_result: Option<MoveItemsResponse>,
+    pub message: String,
+}
+
+/// 生成建议所需的计划快照
+struct PlanSnapshot {
+    items: Vec<PlanItem>,
+    pools: Vec<CapacityPool>,
+    states: HashMap<String, MaterialState>,
+    roll_plans: Vec<RollCampaignPlanEntity>,
+}
+
+pub struct BottleneckOptimizerApi {
+    plan_version_repo: Arc<PlanVersionRepository>,
+    plan_item_repo: Arc<PlanItemRepository>,
+    capacity_pool_repo: Arc<CapacityPoolRepository>,
+    material_master_repo: Arc<MaterialMasterRepository>,
+    material_state_repo: Arc<MaterialStateRepository>,
+    path_override_pending_repo: Arc<PathOverridePendingRepository>,
+    roll_plan_repo: Arc<RollCampaignPlanRepository>,
+    config_manager: Arc<ConfigManager>,
+    plan_api: Arc<PlanApi>,
+    material_api: Arc<MaterialApi>,
+    path_rule_api: Arc<PathRuleApi>,
+    roller_api: Arc<RollerApi>,
+}
+
+impl BottleneckOptimizerApi {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        plan_version_repo: Arc<PlanVersionRepository>,
+        plan_item_repo: Arc<PlanItemRepository>,
+        capacity_pool_repo: Arc<CapacityPoolRepository>,
+        material_master_repo: Arc<MaterialMasterRepository>,
+        material_state_repo: Arc<MaterialStateRepository>,
+        path_override_pending_repo: Arc<PathOverridePendingRepository>,
+        roll_plan_repo: Arc<RollCampaignPlanRepository>,
+        config_manager: Arc<ConfigManager>,
+        plan_api: Arc<PlanApi>,
+        material_api: Arc<MaterialApi>,
+        path_rule_api: Arc<PathRuleApi>,
+        roller_api: Arc<RollerApi>,
+    ) -> Self {
+        Self {
+            plan_version_repo,
+            plan_item_repo,
+            capacity_pool_repo,
+            material_master_repo,
+            material_state_repo,
+            path_override_pending_repo,
+            roll_plan_repo,
+            config_manager,
+            plan_api,
+            material_api,
+            path_rule_api,
+            roller_api,
+        }
+    }
+
+    // ==========================================
+    // 建议生成
+    // ==========================================
+
+    /// 生成瓶颈优化建议（只读）
+    ///
+    /// # 参数
+    /// - version_id: 版本ID
+    /// - plan_date_from / plan_date_to: 分析窗口（最多60天）
+    /// - machine_code: 可选机组过滤
+    pub fn list_bottleneck_recommendations(
+        &self,
+        version_id: &str,
+        plan_date_from: NaiveDate,
+        plan_date_to: NaiveDate,
+        machine_code: Option<&str>,
+    ) -> ApiResult<BottleneckRecommendationResponse> {
+        self.validate_request(version_id, plan_date_from, plan_date_to)?;
+        let machine_code = machine_code.map(str::trim).filter(|m| !m.is_empty());
+
+        let snapshot =
+            self.load_snapshot(version_id, plan_date_from, plan_date_to, machine_code)?;
+        let output = self.run_optimizer(version_id, &snapshot, plan_date_from, plan_date_to)?;
+
+        let mut recommendations = output.recommendations;
+        for rec in &mut recommendations {
+            rec.predicted_impact = Some(self.predict_impact(version_id, rec, &snapshot));
+        }
+
+        let message = if output.bottleneck_days_before == 0 && recommendations.is_empty() {
+            "窗口内无产能瓶颈，暂无调整建议".to_string()
+        } else {
+            format!(
+                "瓶颈{}天(超目标{:.1}t)，{}条建议全部执行后预计剩余{}天(超目标{:.1}t)",
+                output.bottleneck_days_before,
+                output.overload_before_t,
+                recommendations.len(),
+                output.bottleneck_days_after,
+                output.overload_after_t
+            )
+        };
+
+        Ok(BottleneckRecommendationResponse {
+            version_id: version_id.to_string(),
+            plan_date_from,
+            plan_date_to,
+            machine_code: machine_code.map(str::to_string),
+            bottleneck_day_count: output.bottleneck_days_before,
+            overload_t: output.overload_before_t,
+            remaining_bottleneck_day_count: output.bottleneck_days_after,
+            remaining_overload_t: output.overload_after_t,
+            recommendations,
+            message,
+        })
+    }
+
+    // ==========================================
+    // 一键执行
+    // ==========================================
+
+    /// 执行选中的建议
+    ///
+    /// # 说明
+    /// - 按当前数据重新生成建议，已失效的建议ID计入 skipped
+    /// - MOVE_ITEM 合并为一次 move_items（Strict 校验，冻结区红线逐条拦截）
+    /// - FORCE_RELEASE 走 batch_force_release（未适温为建议前提，使用 AutoFix）
+    /// - CONFIRM_PATH_OVERRIDE 走 batch_confirm_path_override
+    /// - SHIFT_ROLL_CHANGE 走 upsert_campaign_plan（保留原起始时间/停机时长）
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_bottleneck_recommendations(
+        &self,
+        version_id: &str,
+        plan_date_from: NaiveDate,
+        plan_date_to: NaiveDate,
+        machine_code: Option<&str>,
+        recommendation_ids: &[String],
+        operator: &str,
+        reason: &str,
+    ) -> ApiResult<ApplyBottleneckRecommendationsResponse> {
+        self.validate_request(version_id, plan_date_from, plan_date_to)?;
+        if recommendation_ids.is_empty() {
+            return Err(ApiError::InvalidInput("建议ID列表不能为空".to_string()));
+        }
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+        if reason.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作原因不能为空".to_string()));
+        }
+        let version = self
+            .plan_version_repo
+            .find_by_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        if !version.is_editable() {
+            return Err(ApiError::BusinessRuleViolation(
+                "只能修改草稿、已驳回或激活状态的版本".to_string(),
+            ));
+        }
+
+        let machine_code = machine_code.map(str::trim).filter(|m| !m.is_empty());
+        let snapshot =
+            self.load_snapshot(version_id, plan_date_from, plan_date_to, machine_code)?;
+        let current = self
+            .run_optimizer(version_id, &snapshot, plan_date_from, plan_date_to)?
+            .recommendations;
+        let by_id: HashMap<&str, &BottleneckRecommendation> = current
+            .iter()
+            .map(|r| (r.recommendation_id.as_str(), r))
+            .collect();
+
+        let mut skipped = Vec::new();
+        let mut selected: Vec<&BottleneckRecommendation> = Vec::new();
+        let mut seen = HashSet::new();
+        for id in recommendation_ids {
+            if !seen.insert(id.as_str()) {
+                continue;
+            }
+            match by_id.get(id.as_str()) {
+                Some(rec) => selected.push(rec),
+                None => skipped.push(SkippedRecommendation {
+                    recommendation_id: id.clone(),
+                    reason: "建议已失效（计划已变化），请重新生成".to_string(),
+                }),
+            }
+        }
+        let of_kind = |kind: BottleneckActionKind| -> Vec<&BottleneckRecommendation> {
+            selected
+                .iter()
+                .copied()
+                .filter(|r| r.kind == kind)
+                .collect()
+        };
+        let op_reason = format!("瓶颈优化建议 | {}", reason.trim());
+        let mut applied_ids = Vec::new();
+
+        // 1. 移动材料（一次 move_items，追加到目标日末尾）
+        let moves = of_kind(BottleneckActionKind::MoveItem);
+        let mut move_result = None;
+        let mut moved_count = 0;
+        if !moves.is_empty() {
+            let mut next_seq: HashMap<(String, NaiveDate), i32> = HashMap::new();
+            for item in &snapshot.items {
+                let seq = next_seq
+                    .entry((item.machine_code.clone(), item.plan_date))
+                    .or_insert(1);
+                *seq = (*seq).max(item.seq_no + 1);
+            }
+            let requests = moves
+                .iter()
+                .filter_map(|r| {
+                    let to_date = r.to_date?;
+                    let seq = next_seq
+                        .entry((r.machine_code.clone(), to_date))
+                        .or_insert(1);
+                    let to_seq = *seq;
+                    *seq += 1;
+                    Some(MoveItemRequest {
+                        material_id: r.material_id.clone()?,
+                        to_date: to_date.format("%Y-%m-%d").to_string(),
+                        to_seq,
+                        to_machine: r.machine_code.clone(),
+                    })
+                })
+                .collect();
+            let resp = self.plan_api.move_items(
+                version_id,
+                requests,
+                ValidationMode::Strict,
+                operator,
+                Some(&op_reason),
+            )?;
+            for rec in &moves {
+                let result = resp
+                    .results
+                    .iter()
+                    .find(|res| Some(&res.material_id) == rec.material_id.as_ref());
+                match result {
+                    Some(res) if res.success => {
+                        moved_count += 1;
+                        applied_ids.push(rec.recommendation_id.clone());
+                    }
+                    other => skipped.push(SkippedRecommendation {
+                        recommendation_id: rec.recommendation_id.clone(),
+                        reason: other
+                            .and_then(|res| res.error.clone())
+                            .unwrap_or_else(|| "移动未执行".to_string()),
+                    }),
+                }
+            }
+            move_result = Some(resp);
+        }
+
+        // 2. 强制放行
+        let releases = of_kind(BottleneckActionKind::ForceRelease);
+        let mut force_released_count = 0;
+        if !releases.is_empty() {
+            let ids: Vec<String> = releases
+                .iter()
+                .filter_map(|r| r.material_id.clone())
+                .collect();
+            let summary = self.material_api.batch_force_release(
+                ids,
+                operator,
+                &op_reason,
+                ValidationMode::AutoFix,
+            )?;
+            force_released_count = summary.success_count;
+            let failed: HashSet<String> = summary
+                .details
+                .as_ref()
+                .and_then(|d| d.get("failed_material_ids"))
+                .and_then(|v| serde_json::from_value::<Vec<String>>(v.clone()).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            for rec in releases {
+                match rec.material_id.as_ref() {
+                    Some(id) if !failed.contains(id) => {
+                        applied_ids.push(rec.recommendation_id.clone());
+                    }
+                    _ => skipped.push(SkippedRecommendation {
+                        recommendation_id: rec.recommendation_id.clone(),
+                        reason: "强制放行失败（材料状态不存在）".to_string(),
+                    }),
+                }
+            }
+        }
+
+        // 3. 确认路径突破
+        let confirms = of_kind(BottleneckActionKind::ConfirmPathOverride);
+        let mut path_override_confirmed_count = 0;
+        if !confirms.is_empty() {
+            let ids: Vec<String> = confirms
+                .iter()
+                .filter_map(|r| r.material_id.clone())
+                .collect();
+            let result = self
+                .path_rule_api
+                .batch_confirm_path_override(version_id, &ids, operator, &op_reason)?;
+            path_override_confirmed_count = result.success_count.max(0) as usize;
+            for rec in confirms {
+                let failed = rec
+                    .material_id
+                    .as_ref()
+                    .is_some_and(|id| result.failed_material_ids.contains(id));
+                if failed {
+                    skipped.push(SkippedRecommendation {
+                        recommendation_id: rec.recommendation_id.clone(),
+                        reason: "路径突破确认失败".to_string(),
+                    });
+                } else {
+                    applied_ids.push(rec.recommendation_id.clone());
+                }
+            }
+        }
+
+        // 4. 平移换辊
+        let mut roll_change_shifted_count = 0;
+        for rec in of_kind(BottleneckActionKind::ShiftRollChange) {
+            let plan = snapshot
+                .roll_plans
+                .iter()
+                .find(|p| p.machine_code == rec.machine_code);
+            let (Some(plan), Some(shift_to)) = (plan, rec.roll_change_to.as_deref()) else {
+                skipped.push(SkippedRecommendation {
+                    recommendation_id: rec.recommendation_id.clone(),
+                    reason: "换辊计划不存在".to_string(),
+                });
+                continue;
+            };
+            self.roller_api.upsert_campaign_plan(
+                version_id,
+                &plan.machine_code,
+                &plan.initial_start_at,
+                Some(shift_to),
+                plan.downtime_minutes,
+                operator,
+                &op_reason,
+            )?;
+            roll_change_shifted_count += 1;
+            applied_ids.push(rec.recommendation_id.clone());
+        }
+
+        let message = format!(
+            "已执行{}条建议（移动{}、放行{}、确认路径{}、平移换辊{}），跳过{}条",
+            applied_ids.len(),
+            moved_count,
+            force_released_count,
+            path_override_confirmed_count,
+            roll_change_shifted_count,
+            skipped.len()
+        );
+
+        Ok(ApplyBottleneckRecommendationsResponse {
+            version_id: version_id.to_string(),
+            applied_ids,
+            skipped,
+            moved_count,
+            force_released_count,
+            path_override_confirmed_count,
+            roll_change_shifted_count,
+            move_result,
+            message,
+        })
+    }
+
+    // ==========================================
+    // 内部实现
+    // ==========================================
+
+    fn validate_request(
+        &self,
+        version_id: &str,
+        plan_date_from: NaiveDate,
+        plan_date_to: NaiveDate,
+    ) -> ApiResult<()> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        if plan_date_from > plan_date_to {
+            return Err(ApiError::InvalidInput(
+                "开始日期不能晚于结束日期".to_string(),
+            ));
+        }
+        if (plan_date_to - plan_date_from).num_days() + 1 > MAX_OPTIMIZE_WINDOW_DAYS {
+            return Err(ApiError::InvalidInput(format!(
+                "分析窗口过大，最多支持{}天",
+                MAX_OPTIMIZE_WINDOW_DAYS
+            )));
+        }
+        Ok(())
+    }
+
+    fn load_snapshot(
+        &self,
+        version_id: &str,
+        plan_date_from: NaiveDate,
+        plan_date_to: NaiveDate,
+        machine_code: Option<&str>,
+    ) -> ApiResult<PlanSnapshot> {
+        self.plan_version_repo
+            .find_by_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+
+        let in_scope = |m: &str| machine_code.is_none_or(|code| code == m);
+        let items: Vec<PlanItem> = self
+            .plan_item_repo
+            .find_by_date_range(version_id, plan_date_from, plan_date_to)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .filter(|i| in_scope(&i.machine_code))
+            .collect();
+        let pools: Vec<CapacityPool> = self
+            .capacity_pool_repo
+            .find_by_version_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .filter(|p| {
+                in_scope(&p.machine_code)
+                    && p.plan_date >= plan_date_from
+                    && p.plan_date <= plan_date_to
+            })
+            .collect();
+
+        let mut states = HashMap::new();
+        for item in &items {
+            if let Some(state) = self
+                .material_state_repo
+                .find_by_id(&item.material_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            {
+                states.insert(item.material_id.clone(), state);
+            }
+        }
+
+        let roll_plans = self
+            .roll_plan_repo
+            .list_by_version_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .filter(|p| in_scope(&p.machine_code))
+            .collect();
+
+        Ok(PlanSnapshot {
+            items,
+            pools,
+            states,
+            roll_plans,
+        })
+    }
+
+    fn run_optimizer(
+        &self,
+        version_id: &str,
+        snapshot: &PlanSnapshot,
+        plan_date_from: NaiveDate,
+        plan_date_to: NaiveDate,
+    ) -> ApiResult<crate::engine::BottleneckOptimizerOutput> {
+        let machines: HashSet<&str> = snapshot
+            .pools
+            .iter()
+            .map(|p| p.machine_code.as_str())
+            .collect();
+
+        // 1. 负荷: 已用吨位按排产明细实时汇总（产能池 used 可能滞后于人工调整）
+        let mut used: HashMap<(&str, NaiveDate), f64> = HashMap::new();
+        for item in &snapshot.items {
+            *used
+                .entry((item.machine_code.as_str(), item.plan_date))
+                .or_insert(0.0) += item.weight_t;
+        }
+        let loads: Vec<MachineDayLoad> = snapshot
+            .pools
+            .iter()
+            .map(|p| MachineDayLoad {
+                machine_code: p.machine_code.clone(),
+                plan_date: p.plan_date,
+                target_capacity_t: p.target_capacity_t,
+                limit_capacity_t: p.limit_capacity_t,
+                used_capacity_t: used
+                    .get(&(p.machine_code.as_str(), p.plan_date))
+                    .copied()
+                    .unwrap_or(0.0),
+            })
+            .collect();
+
+        // 2. 排产项（材料事实取当前状态/主数据）
+        let item_ids: Vec<String> = snapshot
+            .items
+            .iter()
+            .map(|i| i.material_id.clone())
+            .collect();
+        let due_dates: HashMap<String, Option<NaiveDate>> = self
+            .material_master_repo
+            .find_by_ids(&item_ids)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|m| (m.material_id, m.due_date))
+            .collect();
+        let items: Vec<MovableItem> = snapshot
+            .items
+            .iter()
+            .map(|i| {
+                let state = snapshot.states.get(&i.material_id);
+                MovableItem {
+                    material_id: i.material_id.clone(),
+                    machine_code: i.machine_code.clone(),
+                    plan_date: i.plan_date,
+                    weight_t: i.weight_t,
+                    urgent_level: state.map(|s| s.urgent_level).unwrap_or(UrgentLevel::L0),
+                    due_date: due_dates.get(&i.material_id).copied().flatten(),
+                    earliest_sched_date: state.and_then(|s| s.earliest_sched_date),
+                    locked: i.locked_in_plan
+                        || state.is_some_and(|s| {
+                            s.lock_flag || s.in_frozen_zone || s.sched_state == SchedState::Locked
+                        }),
+                }
+            })
+            .collect();
+        let scheduled: HashSet<&str> = snapshot
+            .items
+            .iter()
+            .map(|i| i.material_id.as_str())
+            .collect();
+
+        // 3. 未适温紧急材料（未排入本版本）
+        let immature: Vec<MaterialState> = self
+            .material_state_repo
+            .find_immature_materials(None)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .filter(|s| {
+                s.urgent_level >= UrgentLevel::L2
+                    && !s.force_release_flag
+                    && !s.lock_flag
+                    && !scheduled.contains(s.material_id.as_str())
+            })
+            .collect();
+        let immature_ids: Vec<String> = immature.iter().map(|s| s.material_id.clone()).collect();
+        let immature_masters: HashMap<String, _> = self
+            .material_master_repo
+            .find_by_ids(&immature_ids)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|m| (m.material_id.clone(), m))
+            .collect();
+        let release_candidates: Vec<ReleaseCandidate> = immature
+            .iter()
+            .filter_map(|s| {
+                let master = immature_masters.get(&s.material_id)?;
+                let machine_code = master.current_machine_code.clone()?;
+                if !machines.contains(machine_code.as_str()) {
+                    return None;
+                }
+                Some(ReleaseCandidate {
+                    material_id: s.material_id.clone(),
+                    machine_code,
+                    weight_t: master.weight_t?,
+                    urgent_level: s.urgent_level,
+                    due_date: master.due_date,
+                    ready_in_days: s.ready_in_days,
+                })
+            })
+            .collect();
+
+        // 4. 路径突破待确认
+        let pending: Vec<_> = self
+            .path_override_pending_repo
+            .list_unconfirmed_by_version(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .filter(|p| {
+                machines.contains(p.machine_code.as_str())
+                    && p.plan_date >= plan_date_from
+                    && p.plan_date <= plan_date_to
+                    && !scheduled.contains(p.material_id.as_str())
+            })
+            .collect();
+        let pending_ids: Vec<String> = pending.iter().map(|p| p.material_id.clone()).collect();
+        let pending_weights: HashMap<String, Option<f64>> = self
+            .material_master_repo
+            .find_by_ids(&pending_ids)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|m| (m.material_id, m.weight_t))
+            .collect();
+        let path_overrides: Vec<PathOverrideCandidate> = pending
+            .into_iter()
+            .filter_map(|p| {
+                let weight_t = pending_weights.get(&p.material_id).copied().flatten()?;
+                Some(PathOverrideCandidate {
+                    weight_t,
+                    urgent_level: parse_urgent_level(&p.urgent_level),
+                    material_id: p.material_id,
+                    machine_code: p.machine_code,
+                    plan_date: p.plan_date,
+                    violation_type: p.violation_type,
+                })
+            })
+            .collect();
+
+        // 5. 换辊计划
+        let default_downtime = self
+            .config_manager
+            .get_global_config_value(config_keys::ROLL_CHANGE_DOWNTIME_MINUTES)
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<i32>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(DEFAULT_ROLL_DOWNTIME_MINUTES);
+        let roll_changes: Vec<RollChangePlan> = snapshot
+            .roll_plans
+            .iter()
+            .filter_map(|p| {
+                let next_change_at = p.next_change_at.as_deref().and_then(|s| {
+                    NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%d %H:%M:%S").ok()
+                })?;
+                Some(RollChangePlan {
+                    machine_code: p.machine_code.clone(),
+                    next_change_at,
+                    downtime_minutes: p.downtime_minutes.unwrap_or(default_downtime),
+                })
+            })
+            .collect();
+
+        Ok(BottleneckOptimizer::recommend(&BottleneckOptimizerInput {
+            loads: &loads,
+            items: &items,
+            release_candidates: &release_candidates,
+            path_overrides: &path_overrides,
+            roll_changes: &roll_changes,
+        }))
+    }
+
+    /// 单条建议相对当前计划的预计影响
+    fn predict_impact(
+        &self,
+        version_id: &str,
+        rec: &BottleneckRecommendation,
+        snapshot: &PlanSnapshot,
+    ) -> crate::domain::action_log::ImpactSummary {
+        let pool_of = |date: Option<NaiveDate>| {
+            date.and_then(|d| {
+                snapshot
+                    .pools
+                    .iter()
+                    .find(|p| p.machine_code == rec.machine_code && p.plan_date == d)
+            })
+        };
+        let used_of = |date: NaiveDate| -> f64 {
+            snapshot
+                .items
+                .iter()
+                .filter(|i| i.machine_code == rec.machine_code && i.plan_date == date)
+                .map(|i| i.weight_t)
+                .sum()
+        };
+        let adjusted = |pool: &CapacityPool, delta_t: f64| {
+            let mut pool = pool.clone();
+            pool.used_capacity_t = used_of(pool.plan_date) + delta_t;
+            pool.overflow_t = (pool.used_capacity_t - pool.limit_capacity_t).max(0.0);
+            pool
+        };
+
+        let mut before_items = Vec::new();
+        let mut after_items = Vec::new();
+        let mut before_pools = Vec::new();
+        let mut after_pools = Vec::new();
+        match rec.kind {
+            BottleneckActionKind::MoveItem => {
+                if let Some(item) = snapshot
+                    .items
+                    .iter()
+                    .find(|i| Some(&i.material_id) == rec.material_id.as_ref())
+                {
+                    before_items.push(item.clone());
+                    let mut moved = item.clone();
+                    moved.plan_date = rec.to_date.unwrap_or(item.plan_date);
+                    moved.source_type = "MANUAL".to_string();
+                    after_items.push(moved);
+                }
+                if let (Some(from), Some(to)) = (pool_of(rec.from_date), pool_of(rec.to_date)) {
+                    before_pools.extend([adjusted(from, 0.0), adjusted(to, 0.0)]);
+                    after_pools.extend([adjusted(from, -rec.weight_t), adjusted(to, rec.weight_t)]);
+                }
+            }
+            BottleneckActionKind::ForceRelease | BottleneckActionKind::ConfirmPathOverride => {
+                if let (Some(material_id), Some(to)) = (rec.material_id.as_ref(), rec.to_date) {
+                    after_items.push(predicted_item(version_id, material_id, rec, to));
+                }
+                if let Some(to) = pool_of(rec.to_date) {
+                    before_pools.push(adjusted(to, 0.0));
+                    after_pools.push(adjusted(to, rec.weight_t));
+                }
+            }
+            BottleneckActionKind::ShiftRollChange => {
+                // 换辊停机按折算吨位计入占用
+                if let (Some(from), Some(to)) = (pool_of(rec.from_date), pool_of(rec.to_date)) {
+                    before_pools.extend([adjusted(from, rec.weight_t), adjusted(to, 0.0)]);
+                    after_pools.extend([adjusted(from, 0.0), adjusted(to, rec.weight_t)]);
+                }
+            }
+        }
+
+        let material_weights: HashMap<String, f64> = before_items
+            .iter()
+            .chain(after_items.iter())
+            .map(|i| (i.material_id.clone(), i.weight_t))
+            .collect();
+        let materials: Vec<MaterialState> = rec
+            .material_id
+            .as_ref()
+            .and_then(|id| snapshot.states.get(id).cloned())
+            .into_iter()
+            .collect();
+
+        let mut impact = ImpactSummaryEngine::new().generate_impact(
+            &before_items,
+            &after_items,
+            &before_pools,
+            &after_pools,
+            &[],
+            &[],
+            &materials,
+            &material_weights,
+        );
+        if rec.kind == BottleneckActionKind::ShiftRollChange {
+            impact.roll_campaign_affected = true;
+        }
+        impact
+    }
+}
+
+fn parse_urgent_level(level: &str) -> UrgentLevel {
+    match level.trim() {
+        "L3" => UrgentLevel::L3,
+        "L2" => UrgentLevel::L2,
+        "L1" => UrgentLevel::L1,
+        _ => UrgentLevel::L0,
+    }
+}
+
+/// 放行/确认后预计落位的排产项（仅用于影响预估，不落库）
+fn predicted_item(
+    version_id: &str,
+    material_id: &str,
+    rec: &BottleneckRecommendation,
+    plan_date: NaiveDate,
+) -> PlanItem {
+    PlanItem {
+        version_id: version_id.to_string(),
+        material_id: material_id.to_string(),
+        machine_code: rec.machine_code.clone(),
+        plan_date,
+        seq_no: 0,
+        weight_t: rec.weight_t,
+        source_type: "CALC".to_string(),
+        locked_in_plan: false,
+        force_release_in_plan: rec.kind == BottleneckActionKind::ForceRelease,
+        violation_flags: None,
+        urgent_level: rec.urgent_level.map(|l| l.to_string()),
+        sched_state: None,
+        assign_reason: Some(rec.kind.as_str().to_string()),
+        steel_grade: None,
+        width_mm: None,
+        thickness_mm: None,
+        contract_no: None,
+        due_date: None,
+        scheduled_date: None,
+        scheduled_machine_code: None,
+    }
+}
@@ -0,0 +1,282 @@
+// ==========================================
+// 热轧精整排产系统 - 产能预留 API
+// ==========================================
+// 职责:
+// - 为战略合同/重点客户在机组日期区间内预留每日吨位（合同号或合同号前缀筛选）
+// - 取消预留
+// - 按版本汇报预留利用率（占用 / 释放 / 保留期内空置）
+// 说明:
+// - 预留在重算时由 CapacityFiller 执行，利用率来自重算落库的 capacity_reservation_usage
+// - 预留定义不随版本复制，修改后需重算生效
+// ==========================================
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::domain::action_log::ActionLog;
+use crate::domain::capacity::{CapacityReservation, ReservationUsage};
+use crate::repository::action_log_repo::ActionLogRepository;
+use crate::repository::capacity_repo::CapacityPoolRepository;
+
+/// 单条预留最长覆盖天数
+const MAX_RESERVATION_DAYS: i64 = 366;
+
+// ==========================================
+// DTO 定义
+// ==========================================
+
+/// 创建产能预留请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCapacityReservationRequest {
+    pub machine_code: String,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub daily_reserved_t: f64,
+    pub contract_no: Option<String>,
+    pub contract_prefix: Option<String>,
+    /// 排产日期前 N 天释放未用预留（默认 0：当日才释放）
+    pub release_days_before: Option<i32>,
+    pub expires_on: Option<NaiveDate>,
+    pub reason: Option<String>,
+    pub operator: String,
+}
+
+/// 单条预留的利用率汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReservationUtilizationDto {
+    pub reservation_id: String,
+    pub machine_code: String,
+    /// 预留定义（已取消的预留为 None，仅保留历史快照）
+    pub reservation: Option<CapacityReservation>,
+    pub reserved_t: f64,
+    pub used_t: f64,
+    pub released_t: f64,
+    /// 保留期内未使用（产能空置）
+    pub held_unused_t: f64,
+    /// used / reserved（%）
+    pub utilization_pct: f64,
+    pub daily: Vec<ReservationUsage>,
+}
+
+/// 版本的预留利用率报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReservationUtilizationReport {
+    pub version_id: String,
+    pub total_reserved_t: f64,
+    pub total_used_t: f64,
+    pub total_released_t: f64,
+    pub total_held_unused_t: f64,
+    pub utilization_pct: f64,
+    pub reservations: Vec<ReservationUtilizationDto>,
+}
+
+// ==========================================
+// CapacityReservationApi - 产能预留 API
+// ==========================================
+pub struct CapacityReservationApi {
+    capacity_repo: Arc<CapacityPoolRepository>,
+    action_log_repo: Arc<ActionLogRepository>,
+}
+
+impl CapacityReservationApi {
+    pub fn new(
+        capacity_repo: Arc<CapacityPoolRepository>,
+        action_log_repo: Arc<ActionLogRepository>,
+    ) -> Self {
+        Self {
+            capacity_repo,
+            action_log_repo,
+        }
+    }
+
+    /// 创建产能预留
+    ///
+    /// # 校验
+    /// - 合同号与合同号前缀至少设置一个
+    /// - 每日预留吨位 > 0，日期区间不超过 366 天，释放点 >= 0
+    pub fn create_reservation(
+        &self,
+        request: CreateCapacityReservationRequest,
+    ) -> ApiResult<CapacityReservation> {
+        let machine_code = request.machine_code.trim();
+        if machine_code.is_empty() {
+            return Err(ApiError::InvalidInput("机组代码不能为空".to_string()));
+        }
+        if request.operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+        let contract_no = normalize(request.contract_no);
+        let contract_prefix = normalize(request.contract_prefix);
+        if contract_no.is_none() && contract_prefix.is_none() {
+            return Err(ApiError::InvalidInput(
+                "合同号与合同号前缀至少设置一个".to_string(),
+            ));
+        }
+        if !(request.daily_reserved_t.is_finite() && request.daily_reserved_t > 0.0) {
+            return Err(ApiError::InvalidInput(
+                "每日预留吨位必须大于0".to_string(),
+            ));
+        }
+        if request.date_to < request.date_from {
+            return Err(ApiError::InvalidInput(
+                "结束日期不能早于起始日期".to_string(),
+            ));
+        }
+        if (request.date_to - request.date_from).num_days() >= MAX_RESERVATION_DAYS {
+            return Err(ApiError::InvalidInput(format!(
+                "预留区间不能超过 {} 天",
+                MAX_RESERVATION_DAYS
+            )));
+        }
+        let release_days_before = request.release_days_before.unwrap_or(0);
+        if release_days_before < 0 {
+            return Err(ApiError::InvalidInput("释放点天数不能为负数".to_string()));
+        }
+
+        let now = chrono::Local::now().naive_local();
+        let reservation = CapacityReservation {
+            reservation_id: uuid::Uuid::new_v4().to_string(),
+            machine_code: machine_code.to_string(),
+            date_from: request.date_from,
+            date_to: request.date_to,
+            daily_reserved_t: request.daily_reserved_t,
+            contract_no,
+            contract_prefix,
+            release_days_before,
+            expires_on: request.expires_on,
+            reason: normalize(request.reason),
+            created_by: request.operator.trim().to_string(),
+            created_at: now,
+        };
+        self.capacity_repo.insert_reservation(&reservation)?;
+
+        self.log_action(
+            "CREATE_CAPACITY_RESERVATION",
+            &reservation,
+            &reservation.created_by,
+            format!(
+                "创建产能预留: {} {}~{} 每日{:.1}t",
+                reservation.machine_code,
+                reservation.date_from,
+                reservation.date_to,
+                reservation.daily_reserved_t
+            ),
+        );
+
+        Ok(reservation)
+    }
+
+    /// 取消产能预留（历史版本的利用率快照保留）
+    pub fn cancel_reservation(&self, reservation_id: &str, operator: &str) -> ApiResult<()> {
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+        let reservation = self
+            .capacity_repo
+            .find_reservation(reservation_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("产能预留{}不存在", reservation_id)))?;
+        self.capacity_repo.delete_reservation(reservation_id)?;
+
+        self.log_action(
+            "CANCEL_CAPACITY_RESERVATION",
+            &reservation,
+            operator,
+            format!("取消产能预留: {}", reservation.reservation_id),
+        );
+        Ok(())
+    }
+
+    /// 查询产能预留（可按机组过滤）
+    pub fn list_reservations(
+        &self,
+        machine_code: Option<&str>,
+    ) -> ApiResult<Vec<CapacityReservation>> {
+        let machine_code = machine_code.map(str::trim).filter(|s| !s.is_empty());
+        Ok(self.capacity_repo.list_reservations(machine_code)?)
+    }
+
+    /// 按版本汇报预留利用率
+    pub fn get_reservation_utilization(
+        &self,
+        version_id: &str,
+    ) -> ApiResult<ReservationUtilizationReport> {
+        let usage = self
+            .capacity_repo
+            .find_reservation_usage_by_version(version_id)?;
+
+        let mut grouped: BTreeMap<String, Vec<ReservationUsage>> = BTreeMap::new();
+        for u in usage {
+            grouped.entry(u.reservation_id.clone()).or_default().push(u);
+        }
+
+        let mut reservations = Vec::with_capacity(grouped.len());
+        for (reservation_id, daily) in grouped {
+            let reserved_t: f64 = daily.iter().map(|u| u.reserved_t).sum();
+            let used_t: f64 = daily.iter().map(|u| u.used_t).sum();
+            reservations.push(ReservationUtilizationDto {
+                reservation: self.capacity_repo.find_reservation(&reservation_id)?,
+                machine_code: daily[0].machine_code.clone(),
+                reservation_id,
+                reserved_t,
+                used_t,
+                released_t: daily.iter().map(|u| u.released_t).sum(),
+                held_unused_t: daily.iter().map(ReservationUsage::held_unused_t).sum(),
+                utilization_pct: pct(used_t, reserved_t),
+                daily,
+            });
+        }
+
+        let total_reserved_t: f64 = reservations.iter().map(|r| r.reserved_t).sum();
+        let total_used_t: f64 = reservations.iter().map(|r| r.used_t).sum();
+        Ok(ReservationUtilizationReport {
+            version_id: version_id.to_string(),
+            total_reserved_t,
+            total_used_t,
+            total_released_t: reservations.iter().map(|r| r.released_t).sum(),
+            total_held_unused_t: reservations.iter().map(|r| r.held_unused_t).sum(),
+            utilization_pct: pct(total_used_t, total_reserved_t),
+            reservations,
+        })
+    }
+
+    fn log_action(
+        &self,
+        action_type: &str,
+        reservation: &CapacityReservation,
+        operator: &str,
+        detail: String,
+    ) {
+        let action_log = ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: None,
+            action_type: action_type.to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: serde_json::to_value(reservation).ok(),
+            impact_summary_json: None,
+            machine_code: Some(reservation.machine_code.clone()),
+            date_range_start: Some(reservation.date_from),
+            date_range_end: Some(reservation.date_to),
+            detail: Some(detail),
+        };
+        if let Err(e) = self.action_log_repo.insert(&action_log) {
+            tracing::warn!(action_type = %action_type, "记录产能预留操作日志失败: {}", e);
+        }
+    }
+}
+
+fn normalize(raw: Option<String>) -> Option<String> {
+    raw.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn pct(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        part / total * 100.0
+    } else {
+        0.0
+    }
+}
@@ -8,17 +8,21 @@
 use crate::api::error::ApiError;
 use crate::config::ConfigManager;
 use crate::domain::material::{
-    DqSummary, DqViolation, ImportConflict, MaterialMaster, RawMaterialRecord,
+    DqSummary, DqViolation, ImportConflict, ImportSourceInfo, MaterialMaster, RawMaterialRecord,
 };
 use crate::engine::MaterialStateDerivationService;
 use crate::importer::conflict_handler::ConflictHandler;
+use crate::importer::streaming::DEFAULT_IMPORT_CHUNK_SIZE;
 use crate::importer::{
-    CsvParser, DataCleanerImpl, DerivationServiceImpl, DqValidatorImpl, FieldMapperImpl,
-    MaterialImporter, MaterialImporterImpl,
+    ContractImporter, DataCleanerImpl, DerivationServiceImpl, DqValidatorImpl, FieldMapperImpl,
+    ImportCancelToken, ImportProgressCallback, MaterialImporter, MaterialImporterImpl,
+    ParseOptions, StreamingImportOptions, UniversalFileParser,
 };
 use crate::repository::{MaterialImportRepository, MaterialImportRepositoryImpl};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
 
 /// 导入API响应
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +43,32 @@ pub struct ImportApiResponse {
     pub dq_violations: Vec<DqViolation>,
     /// 导入耗时（毫秒）
     pub elapsed_ms: i64,
+    /// 实际读取的源信息（编码/工作表/表头行）
+    pub source_info: ImportSourceInfo,
+    /// 是否被取消（已提交分块保留，可通过 cancel_import_batch 回滚）
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// 合同导入API响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractImportApiResponse {
+    /// 导入批次ID
+    pub import_batch_id: String,
+    /// 关联到材料的合同数
+    pub matched_contracts: usize,
+    /// 未关联任何材料的合同数
+    pub unmatched_contracts: usize,
+    /// 更新影子字段并重算催料/紧急等级的材料数
+    pub updated_materials: usize,
+    /// DQ 汇总统计（按合同行）
+    pub dq_summary: DqSummary,
+    /// DQ 违规明细
+    pub dq_violations: Vec<DqViolation>,
+    /// 导入耗时（毫秒）
+    pub elapsed_ms: i64,
+    /// 实际读取的源信息（编码/工作表/表头行）
+    pub source_info: ImportSourceInfo,
 }
 
 /// 冲突列表响应（带分页信息）
@@ -87,12 +117,17 @@ pub struct CancelImportBatchResponse {
 /// 导入API
 pub struct ImportApi {
     db_path: String,
+    /// 进行中的导入（source_batch_id → 取消令牌）
+    running_imports: Mutex<HashMap<String, ImportCancelToken>>,
 }
 
 impl ImportApi {
     /// 创建新的ImportApi实例
     pub fn new(db_path: String) -> Self {
-        Self { db_path }
+        Self {
+            db_path,
+            running_imports: Mutex::new(HashMap::new()),
+        }
     }
 
     /// 导入材料数据
@@ -106,24 +141,118 @@ impl ImportApi {
     /// - Ok(ImportApiResponse): 导入结果
     /// - Err(ApiError): 错误信息
     pub async fn import_materials(
+        &self,
+        file_path: &str,
+        source_batch_id: &str,
+        mapping_profile_id: Option<&str>,
+    ) -> Result<ImportApiResponse, ApiError> {
+        self.import_materials_with_options(
+            file_path,
+            source_batch_id,
+            mapping_profile_id,
+            ParseOptions::default(),
+            None,
+        )
+        .await
+    }
+
+    /// 导入材料数据（指定解析选项）
+    ///
+    /// # 参数
+    /// - file_path: 文件路径（.csv/.xlsx/.xls）
+    /// - source_batch_id: 批次ID
+    /// - mapping_profile_id: 映射配置ID（可选）
+    /// - options: 解析选项（CSV 编码 / Excel 工作表，缺省自动探测）
+    /// - progress: 进度回调（每个分块提交后触发，可选）
+    ///
+    /// # 返回
+    /// - Ok(ImportApiResponse): 导入结果（含实际读取的源信息）
+    /// - Err(ApiError): 错误信息
+    ///
+    /// # 说明
+    /// - 按 DEFAULT_IMPORT_CHUNK_SIZE 分块流式导入
+    /// - 导入期间可通过 cancel_running_import(source_batch_id) 取消
+    pub async fn import_materials_with_options(
         &self,
         file_path: &str,
         source_batch_id: &str,
         _mapping_profile_id: Option<&str>,
+        options: ParseOptions,
+        progress: Option<ImportProgressCallback>,
     ) -> Result<ImportApiResponse, ApiError> {
+        self.import_materials_as(
+            file_path,
+            source_batch_id,
+            _mapping_profile_id,
+            options,
+            progress,
+            "system",
+        )
+        .await
+    }
+
+    /// 导入材料数据（指定导入人）
+    ///
+    /// # 参数
+    /// - imported_by: 导入人（写入 import_batch.imported_by，如自动导入使用 "auto-import"）
+    /// - 其余参数同 import_materials_with_options
+    pub async fn import_materials_as(
+        &self,
+        file_path: &str,
+        source_batch_id: &str,
+        _mapping_profile_id: Option<&str>,
+        options: ParseOptions,
+        progress: Option<ImportProgressCallback>,
+        imported_by: &str,
+    ) -> Result<ImportApiResponse, ApiError> {
+        let ext = std::path::Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
         // 创建导入器
         let importer = self
-            .create_importer()
+            .create_importer(options)
             .map_err(|e| ApiError::ImportError(format!("创建导入器失败: {}", e)))?;
 
-        // 执行导入（仅支持CSV）
-        let result = if file_path.ends_with(".csv") {
-            importer.import_from_csv(file_path).await
-        } else {
+        if !matches!(ext.as_str(), "csv" | "xlsx" | "xls") {
             return Err(ApiError::ImportError(
-                "当前仅支持 .csv 格式文件导入".to_string(),
+                "当前仅支持 .csv/.xlsx/.xls 格式文件导入".to_string(),
             ));
+        }
+
+        // 登记取消令牌
+        let cancel_token = ImportCancelToken::new();
+        {
+            let mut running = self
+                .running_imports
+                .lock()
+                .map_err(|e| ApiError::InternalError(format!("锁获取失败: {}", e)))?;
+            if running.contains_key(source_batch_id) {
+                return Err(ApiError::InvalidInput(format!(
+                    "批次 {} 正在导入中",
+                    source_batch_id
+                )));
+            }
+            running.insert(source_batch_id.to_string(), cancel_token.clone());
+        }
+
+        // 执行导入（分块流式）
+        let streaming = StreamingImportOptions {
+            chunk_size: DEFAULT_IMPORT_CHUNK_SIZE,
+            progress,
+            cancel_token: Some(cancel_token),
+            imported_by: Some(imported_by.to_string()),
         };
+        let result = importer
+            .import_streaming(file_path, streaming)
+            .await
+            .map_err(|e| e.to_string());
+
+        if let Ok(mut running) = self.running_imports.lock() {
+            running.remove(source_batch_id);
+        }
 
         // 处理导入结果
         match result {
@@ -137,12 +266,86 @@ impl ImportApi {
                     dq_summary: import_result.summary.clone(),
                     dq_violations: import_result.violations.clone(),
                     elapsed_ms: import_result.elapsed_time.as_millis() as i64,
+                    source_info: import_result.source_info,
+                    cancelled: import_result.cancelled,
                 })
             }
             Err(e) => Err(ApiError::ImportError(format!("导入失败: {}", e))),
         }
     }
 
+    /// 导入合同/订单数据（按合同号更新关联材料的影子字段并重算催料/紧急等级）
+    ///
+    /// # 参数
+    /// - file_path: 合同文件路径（.csv/.xlsx/.xls，表头需包含"合同号"）
+    /// - options: 解析选项（编码/工作表）
+    /// - imported_by: 导入人
+    ///
+    /// # 返回
+    /// - Ok(ContractImportApiResponse): 导入结果
+    pub async fn import_contracts(
+        &self,
+        file_path: &str,
+        options: ParseOptions,
+        imported_by: &str,
+    ) -> Result<ContractImportApiResponse, ApiError> {
+        let ext = std::path::Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if !matches!(ext.as_str(), "csv" | "xlsx" | "xls") {
+            return Err(ApiError::ImportError(
+                "当前仅支持 .csv/.xlsx/.xls 格式文件导入".to_string(),
+            ));
+        }
+
+        let import_repo = MaterialImportRepositoryImpl::new(&self.db_path)
+            .map_err(|e| ApiError::ImportError(format!("创建导入器失败: {}", e)))?;
+        let config = ConfigManager::new(&self.db_path)
+            .map_err(|e| ApiError::ImportError(format!("创建导入器失败: {}", e)))?;
+        let importer = ContractImporter::new(import_repo, config);
+
+        let result = importer
+            .import_from_file(file_path, options, imported_by)
+            .await
+            .map_err(|e| ApiError::ImportError(format!("合同导入失败: {}", e)))?;
+
+        Ok(ContractImportApiResponse {
+            import_batch_id: result.batch.batch_id.clone(),
+            matched_contracts: result.matched_contracts,
+            unmatched_contracts: result.unmatched_contracts,
+            updated_materials: result.updated_materials,
+            dq_summary: result.summary,
+            dq_violations: result.violations,
+            elapsed_ms: result.elapsed_time.as_millis() as i64,
+            source_info: result.source_info,
+        })
+    }
+
+    /// 取消进行中的导入
+    ///
+    /// # 参数
+    /// - source_batch_id: 发起导入时使用的批次ID
+    ///
+    /// # 返回
+    /// - true: 已发出取消请求（在下一个分块边界生效）
+    /// - false: 该批次没有进行中的导入
+    pub fn cancel_running_import(&self, source_batch_id: &str) -> Result<bool, ApiError> {
+        let running = self
+            .running_imports
+            .lock()
+            .map_err(|e| ApiError::InternalError(format!("锁获取失败: {}", e)))?;
+        match running.get(source_batch_id) {
+            Some(token) => {
+                token.cancel();
+                tracing::info!(source_batch_id, "已请求取消导入");
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
     /// 列出导入冲突
     ///
     /// # 参数
@@ -466,6 +669,7 @@ impl ImportApi {
     /// 创建MaterialImporter实例
     fn create_importer(
         &self,
+        options: ParseOptions,
     ) -> Result<
         MaterialImporterImpl<MaterialImportRepositoryImpl, ConfigManager>,
         Box<dyn std::error::Error>,
@@ -473,7 +677,7 @@ impl ImportApi {
         let import_repo = MaterialImportRepositoryImpl::new(&self.db_path)?;
         let config = ConfigManager::new(&self.db_path)?;
 
-        let file_parser = Box::new(CsvParser);
+        let file_parser = Box::new(UniversalFileParser::with_options(options));
         let field_mapper = Box::new(FieldMapperImpl);
         let data_cleaner = Box::new(DataCleanerImpl);
         let derivation_service = Box::new(DerivationServiceImpl);
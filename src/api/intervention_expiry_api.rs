@@ -0,0 +1,605 @@
+// ==========================================
+// 热轧精整排产系统 - 人工干预时效 API
+// ==========================================
+// 职责:
+// - 为当前生效的 锁定 / 强制放行 / 人工紧急 / 优先级提升 设置时效（到期时间或重算次数）
+// - 清扫已失效的干预：清除材料标志并记录 EXPIRE_INTERVENTION 日志（由后台任务定时调用）
+// - 按设置人列出即将失效的干预
+// 说明:
+// - 重算次数以设置时效的日志为起点，统计其后的 RECALC_FULL / RECALC_PARTIAL 日志
+// - 设置之后同一标志被再次手工调整（含撤销/重做）的，时效作废，清扫时仅删除记录
+// ==========================================
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::api::error::{ApiError, ApiResult, ValidationViolation};
+use crate::domain::action_log::ActionLog;
+use crate::domain::intervention::{InterventionExpiry, InterventionType, RECALC_ACTION_TYPES};
+use crate::domain::material::MaterialState;
+use crate::domain::types::SchedState;
+use crate::domain::undo::InversePayload;
+use crate::repository::action_log_repo::ActionLogRepository;
+use crate::repository::intervention_expiry_repo::InterventionExpiryRepository;
+use crate::repository::material_repo::MaterialStateRepository;
+
+/// 自动清除失效干预的操作人（写入 action_log.actor）
+pub const INTERVENTION_EXPIRY_OPERATOR: &str = "intervention-expiry";
+
+/// 重算次数口径上限
+const MAX_EXPIRE_AFTER_RECALCS: i32 = 100;
+/// 作废判定时每个材料检索的后续日志数上限
+const SUPERSEDE_LOOKUP_LIMIT: i32 = 50;
+
+const DATETIME_FMT: &str = "%Y-%m-%d %H:%M:%S";
+
+// ==========================================
+// DTO 定义
+// ==========================================
+
+/// 人工干预时效条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterventionExpiryDto {
+    pub material_id: String,
+    pub intervention_type: String,
+    /// 设置时效的操作人
+    pub operator: String,
+    pub reason: Option<String>,
+    pub expires_at: Option<String>,
+    /// 距到期剩余小时数（已过期为 0）
+    pub hours_remaining: Option<f64>,
+    pub expire_after_recalcs: Option<i32>,
+    pub recalcs_remaining: Option<i64>,
+    /// 已满足失效条件、等待清扫
+    pub expired: bool,
+    pub created_at: String,
+}
+
+/// 单个设置人的即将失效列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperatorExpirationsDto {
+    pub operator: String,
+    pub entries: Vec<InterventionExpiryDto>,
+}
+
+/// 即将失效的人工干预（按设置人分组）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpcomingExpirationsResponse {
+    pub generated_at: String,
+    pub within_hours: i64,
+    pub total: usize,
+    pub operators: Vec<OperatorExpirationsDto>,
+}
+
+/// 清扫清除的单条干预
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiredInterventionDto {
+    pub material_id: String,
+    pub intervention_type: String,
+    /// 设置时效的操作人
+    pub operator: String,
+    /// 失效原因（TIME / RECALCS）
+    pub trigger: String,
+}
+
+/// 清扫结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpirySweepResult {
+    pub swept_at: String,
+    pub expired: Vec<ExpiredInterventionDto>,
+    /// 干预已被手工调整或已不存在、直接删除的时效记录数
+    pub stale_removed: usize,
+    /// 写入的 EXPIRE_INTERVENTION 日志（每种干预类型一条）
+    pub action_ids: Vec<String>,
+}
+
+/// 时效条目的当前判定
+struct EntryEvaluation {
+    recalcs_since: i64,
+    stale: bool,
+}
+
+// ==========================================
+// InterventionExpiryApi - 人工干预时效 API
+// ==========================================
+pub struct InterventionExpiryApi {
+    material_state_repo: Arc<MaterialStateRepository>,
+    expiry_repo: Arc<InterventionExpiryRepository>,
+    action_log_repo: Arc<ActionLogRepository>,
+}
+
+impl InterventionExpiryApi {
+    pub fn new(
+        material_state_repo: Arc<MaterialStateRepository>,
+        expiry_repo: Arc<InterventionExpiryRepository>,
+        action_log_repo: Arc<ActionLogRepository>,
+    ) -> Self {
+        Self {
+            material_state_repo,
+            expiry_repo,
+            action_log_repo,
+        }
+    }
+
+    /// 为当前生效的人工干预设置时效（已有时效则覆盖）
+    ///
+    /// # 参数
+    /// - expires_at: 到期时间（本地时间，须晚于当前时间）
+    /// - expire_after_recalcs: 重算 N 次后失效（1-100）
+    /// - 两者至少设置一个，同时设置时先满足者生效
+    ///
+    /// # 错误
+    /// - `ManualOperationValidationError`: 材料不存在或该干预当前未生效（INTERVENTION_NOT_ACTIVE）
+    pub fn set_intervention_expiry(
+        &self,
+        material_ids: Vec<String>,
+        intervention_type: InterventionType,
+        expires_at: Option<NaiveDateTime>,
+        expire_after_recalcs: Option<i32>,
+        operator: &str,
+        reason: Option<&str>,
+    ) -> ApiResult<Vec<InterventionExpiryDto>> {
+        let material_ids = Self::normalize_ids(material_ids)?;
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+        if expires_at.is_none() && expire_after_recalcs.is_none() {
+            return Err(ApiError::InvalidInput(
+                "到期时间与重算次数至少设置一个".to_string(),
+            ));
+        }
+        let now = chrono::Local::now().naive_local();
+        if expires_at.is_some_and(|t| t <= now) {
+            return Err(ApiError::InvalidInput(
+                "到期时间必须晚于当前时间".to_string(),
+            ));
+        }
+        if let Some(n) = expire_after_recalcs {
+            if !(1..=MAX_EXPIRE_AFTER_RECALCS).contains(&n) {
+                return Err(ApiError::InvalidInput(format!(
+                    "重算次数必须在 1-{} 之间",
+                    MAX_EXPIRE_AFTER_RECALCS
+                )));
+            }
+        }
+        let reason = reason.map(str::trim).filter(|s| !s.is_empty());
+
+        let mut violations = Vec::new();
+        for material_id in &material_ids {
+            if !self.is_intervention_active(material_id, intervention_type)? {
+                violations.push(ValidationViolation {
+                    violation_type: "INTERVENTION_NOT_ACTIVE".to_string(),
+                    material_id: material_id.clone(),
+                    reason: format!("材料{}当前未设置{}", material_id, intervention_type),
+                    details: None,
+                });
+            }
+        }
+        if !violations.is_empty() {
+            return Err(ApiError::ManualOperationValidationError {
+                reason: format!(
+                    "{}个材料的{}未生效，无法设置时效",
+                    violations.len(),
+                    intervention_type
+                ),
+                violations,
+            });
+        }
+
+        // 日志作为重算计数与作废判定的起点，必须写入成功
+        let action_id = uuid::Uuid::new_v4().to_string();
+        let action_log = ActionLog {
+            action_id: action_id.clone(),
+            version_id: None,
+            action_type: "SET_INTERVENTION_EXPIRY".to_string(),
+            action_ts: now,
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "material_ids": material_ids,
+                "intervention_type": intervention_type,
+                "expires_at": expires_at.map(|t| t.format(DATETIME_FMT).to_string()),
+                "expire_after_recalcs": expire_after_recalcs,
+                "reason": reason,
+            })),
+            impact_summary_json: Some(serde_json::json!({
+                "success_count": material_ids.len(),
+            })),
+            machine_code: None,
+            date_range_start: None,
+            date_range_end: None,
+            detail: Some(format!("设置人工干预时效({})", intervention_type)),
+        };
+        self.action_log_repo
+            .insert(&action_log)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let entries: Vec<InterventionExpiry> = material_ids
+            .iter()
+            .map(|material_id| InterventionExpiry {
+                material_id: material_id.clone(),
+                intervention_type,
+                expires_at,
+                expire_after_recalcs,
+                operator: operator.to_string(),
+                reason: reason.map(str::to_string),
+                anchor_action_id: action_id.clone(),
+                created_at: now,
+            })
+            .collect();
+        self.expiry_repo
+            .upsert_batch(&entries)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok(entries.iter().map(|e| Self::to_dto(e, now, 0)).collect())
+    }
+
+    /// 取消人工干预时效（干预恢复为长期有效）
+    ///
+    /// # 返回
+    /// - 实际删除的时效条数
+    pub fn clear_intervention_expiry(
+        &self,
+        material_ids: Vec<String>,
+        intervention_type: InterventionType,
+        operator: &str,
+    ) -> ApiResult<usize> {
+        let material_ids = Self::normalize_ids(material_ids)?;
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+
+        let keys: Vec<(String, InterventionType)> = material_ids
+            .iter()
+            .map(|id| (id.clone(), intervention_type))
+            .collect();
+        let removed = self
+            .expiry_repo
+            .delete_batch(&keys)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let action_log = ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: None,
+            action_type: "CLEAR_INTERVENTION_EXPIRY".to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "material_ids": material_ids,
+                "intervention_type": intervention_type,
+            })),
+            impact_summary_json: Some(serde_json::json!({ "removed_count": removed })),
+            machine_code: None,
+            date_range_start: None,
+            date_range_end: None,
+            detail: Some(format!("取消人工干预时效({})", intervention_type)),
+        };
+        if let Err(e) = self.action_log_repo.insert(&action_log) {
+            warn!(error = %e, "记录操作日志失败");
+        }
+
+        Ok(removed)
+    }
+
+    /// 按设置人列出即将失效的人工干预
+    ///
+    /// # 参数
+    /// - operator: 仅查询该设置人（为空查询全部）
+    /// - within_hours: 到期时间在该窗口内（1-720 小时）
+    ///
+    /// # 说明
+    /// - 按重算次数失效的条目无法折算时间，一律列出并给出剩余次数
+    /// - 已满足失效条件、尚未清扫的条目同样列出（expired = true）
+    /// - 已被手工调整作废的条目不列出
+    pub fn list_upcoming_expirations(
+        &self,
+        operator: Option<&str>,
+        within_hours: i64,
+    ) -> ApiResult<UpcomingExpirationsResponse> {
+        if !(1..=720).contains(&within_hours) {
+            return Err(ApiError::InvalidInput(
+                "within_hours 必须在 1-720 之间".to_string(),
+            ));
+        }
+        let operator = operator.map(str::trim).filter(|s| !s.is_empty());
+        let now = chrono::Local::now().naive_local();
+        let horizon = now + Duration::hours(within_hours);
+
+        let entries = self
+            .expiry_repo
+            .list(operator)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let mut grouped: BTreeMap<String, Vec<InterventionExpiryDto>> = BTreeMap::new();
+        let mut total = 0;
+        for entry in &entries {
+            let eval = self.evaluate(entry)?;
+            if eval.stale {
+                continue;
+            }
+            let upcoming = entry.expire_after_recalcs.is_some()
+                || entry.expires_at.is_some_and(|t| t <= horizon);
+            if !upcoming {
+                continue;
+            }
+            grouped
+                .entry(entry.operator.clone())
+                .or_default()
+                .push(Self::to_dto(entry, now, eval.recalcs_since));
+            total += 1;
+        }
+
+        Ok(UpcomingExpirationsResponse {
+            generated_at: now.format(DATETIME_FMT).to_string(),
+            within_hours,
+            total,
+            operators: grouped
+                .into_iter()
+                .map(|(operator, entries)| OperatorExpirationsDto { operator, entries })
+                .collect(),
+        })
+    }
+
+    /// 清扫已失效的人工干预
+    ///
+    /// # 说明
+    /// - 锁定/强制放行失效：清除标志，状态仍为 LOCKED/FORCE_RELEASE 的回退为 READY
+    /// - 人工紧急失效：清除 manual_urgent_flag（紧急等级由下次重算重新判定）
+    /// - 优先级提升失效：清除路径突破拒绝标记，重排时不再进入提升集合
+    /// - 每种干预类型写一条 EXPIRE_INTERVENTION 日志（操作人 intervention-expiry）
+    pub fn sweep_expired(&self, now: NaiveDateTime) -> ApiResult<ExpirySweepResult> {
+        let entries = self
+            .expiry_repo
+            .list(None)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let mut stale_keys = Vec::new();
+        let mut expired_by_type: BTreeMap<&'static str, Vec<(InterventionExpiry, i64)>> =
+            BTreeMap::new();
+        for entry in entries {
+            let eval = self.evaluate(&entry)?;
+            if eval.stale {
+                stale_keys.push((entry.material_id.clone(), entry.intervention_type));
+            } else if entry.is_expired(now, eval.recalcs_since) {
+                expired_by_type
+                    .entry(entry.intervention_type.to_db_str())
+                    .or_default()
+                    .push((entry, eval.recalcs_since));
+            }
+        }
+
+        let stale_removed = if stale_keys.is_empty() {
+            0
+        } else {
+            self.expiry_repo
+                .delete_batch(&stale_keys)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+        };
+
+        let mut expired = Vec::new();
+        let mut action_ids = Vec::new();
+        for group in expired_by_type.into_values() {
+            let intervention_type = group[0].0.intervention_type;
+            for (entry, _) in &group {
+                self.clear_intervention_flag(&entry.material_id, intervention_type)?;
+            }
+
+            let keys: Vec<(String, InterventionType)> = group
+                .iter()
+                .map(|(e, _)| (e.material_id.clone(), intervention_type))
+                .collect();
+            self.expiry_repo
+                .delete_batch(&keys)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            let details: Vec<serde_json::Value> = group
+                .iter()
+                .map(|(e, recalcs_since)| {
+                    serde_json::json!({
+                        "material_id": e.material_id,
+                        "set_by": e.operator,
+                        "expires_at": e.expires_at.map(|t| t.format(DATETIME_FMT).to_string()),
+                        "expire_after_recalcs": e.expire_after_recalcs,
+                        "recalcs_since": recalcs_since,
+                        "trigger": Self::trigger(e, now),
+                        "reason": e.reason,
+                    })
+                })
+                .collect();
+            let material_ids: Vec<&str> =
+                group.iter().map(|(e, _)| e.material_id.as_str()).collect();
+
+            let action_log = ActionLog {
+                action_id: uuid::Uuid::new_v4().to_string(),
+                version_id: None,
+                action_type: "EXPIRE_INTERVENTION".to_string(),
+                action_ts: now,
+                actor: INTERVENTION_EXPIRY_OPERATOR.to_string(),
+                payload_json: Some(serde_json::json!({
+                    "intervention_type": intervention_type,
+                    "material_ids": material_ids,
+                    "entries": details,
+                })),
+                impact_summary_json: Some(serde_json::json!({
+                    "expired_count": group.len(),
+                })),
+                machine_code: None,
+                date_range_start: None,
+                date_range_end: None,
+                detail: Some(format!("人工干预到期自动清除({})", intervention_type)),
+            };
+            match self.action_log_repo.insert(&action_log) {
+                Ok(_) => action_ids.push(action_log.action_id),
+                Err(e) => warn!(error = %e, "记录操作日志失败"),
+            }
+
+            expired.extend(group.iter().map(|(e, _)| ExpiredInterventionDto {
+                material_id: e.material_id.clone(),
+                intervention_type: intervention_type.to_db_str().to_string(),
+                operator: e.operator.clone(),
+                trigger: Self::trigger(e, now).to_string(),
+            }));
+        }
+
+        if !expired.is_empty() || stale_removed > 0 {
+            tracing::info!(
+                expired_count = expired.len(),
+                stale_removed = stale_removed,
+                "人工干预时效清扫完成"
+            );
+        }
+
+        Ok(ExpirySweepResult {
+            swept_at: now.format(DATETIME_FMT).to_string(),
+            expired,
+            stale_removed,
+            action_ids,
+        })
+    }
+
+    // ==========================================
+    // 辅助方法
+    // ==========================================
+
+    fn normalize_ids(material_ids: Vec<String>) -> ApiResult<Vec<String>> {
+        let mut ids: Vec<String> = Vec::with_capacity(material_ids.len());
+        for id in material_ids {
+            let id = id.trim().to_string();
+            if !id.is_empty() && !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        if ids.is_empty() {
+            return Err(ApiError::InvalidInput("材料ID列表不能为空".to_string()));
+        }
+        Ok(ids)
+    }
+
+    /// 干预当前是否生效（材料不存在视为未生效）
+    fn is_intervention_active(
+        &self,
+        material_id: &str,
+        intervention_type: InterventionType,
+    ) -> ApiResult<bool> {
+        let flag = |f: fn(&MaterialState) -> bool| -> ApiResult<bool> {
+            Ok(self.find_state(material_id)?.as_ref().is_some_and(f))
+        };
+        match intervention_type {
+            InterventionType::Lock => flag(|s| s.lock_flag),
+            InterventionType::ForceRelease => flag(|s| s.force_release_flag),
+            InterventionType::ManualUrgent => flag(|s| s.manual_urgent_flag),
+            InterventionType::PriorityBoost => Ok(!self
+                .material_state_repo
+                .find_path_override_rejections(&[material_id.to_string()])
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                .is_empty()),
+        }
+    }
+
+    fn find_state(&self, material_id: &str) -> ApiResult<Option<MaterialState>> {
+        self.material_state_repo
+            .find_by_id(material_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    /// 判定时效条目：设置后的重算次数、是否已作废
+    fn evaluate(&self, entry: &InterventionExpiry) -> ApiResult<EntryEvaluation> {
+        let recalcs_since = self
+            .action_log_repo
+            .count_by_action_types_after(&entry.anchor_action_id, RECALC_ACTION_TYPES)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let manual_types = entry.intervention_type.manual_action_types();
+        let superseded = self
+            .action_log_repo
+            .find_by_material_id_after_action(
+                &entry.material_id,
+                &entry.anchor_action_id,
+                SUPERSEDE_LOOKUP_LIMIT,
+            )
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .iter()
+            .any(|log| {
+                manual_types.contains(&log.action_type.as_str())
+                    || (matches!(log.action_type.as_str(), "UNDO" | "REDO")
+                        && InversePayload::from_payload(log.payload_json.as_ref()).is_some_and(
+                            |p| p.kind() == entry.intervention_type.undo_payload_kind(),
+                        ))
+            });
+
+        let stale = superseded
+            || !self.is_intervention_active(&entry.material_id, entry.intervention_type)?;
+        Ok(EntryEvaluation {
+            recalcs_since,
+            stale,
+        })
+    }
+
+    fn clear_intervention_flag(
+        &self,
+        material_id: &str,
+        intervention_type: InterventionType,
+    ) -> ApiResult<()> {
+        let update = |f: fn(&mut MaterialState)| -> ApiResult<()> {
+            if let Some(mut state) = self.find_state(material_id)? {
+                f(&mut state);
+                self.material_state_repo
+                    .batch_insert_material_state(vec![state])
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            }
+            Ok(())
+        };
+        match intervention_type {
+            InterventionType::Lock => update(|s| {
+                s.lock_flag = false;
+                if s.sched_state == SchedState::Locked {
+                    s.sched_state = SchedState::Ready;
+                }
+            }),
+            InterventionType::ForceRelease => update(|s| {
+                s.force_release_flag = false;
+                if s.sched_state == SchedState::ForceRelease {
+                    s.sched_state = SchedState::Ready;
+                }
+            }),
+            InterventionType::ManualUrgent => update(|s| s.manual_urgent_flag = false),
+            InterventionType::PriorityBoost => self
+                .material_state_repo
+                .clear_path_override_rejection(material_id, INTERVENTION_EXPIRY_OPERATOR)
+                .map_err(|e| ApiError::DatabaseError(e.to_string())),
+        }
+    }
+
+    fn trigger(entry: &InterventionExpiry, now: NaiveDateTime) -> &'static str {
+        if entry.expires_at.is_some_and(|t| t <= now) {
+            "TIME"
+        } else {
+            "RECALCS"
+        }
+    }
+
+    fn to_dto(
+        entry: &InterventionExpiry,
+        now: NaiveDateTime,
+        recalcs_since: i64,
+    ) -> InterventionExpiryDto {
+        InterventionExpiryDto {
+            material_id: entry.material_id.clone(),
+            intervention_type: entry.intervention_type.to_db_str().to_string(),
+            operator: entry.operator.clone(),
+            reason: entry.reason.clone(),
+            expires_at: entry.expires_at.map(|t| t.format(DATETIME_FMT).to_string()),
+            hours_remaining: entry.expires_at.map(|t| {
+                let minutes = (t - now).num_minutes().max(0) as f64;
+                (minutes / 60.0 * 10.0).round() / 10.0
+            }),
+            expire_after_recalcs: entry.expire_after_recalcs,
+            recalcs_remaining: entry.recalcs_remaining(recalcs_since),
+            expired: entry.is_expired(now, recalcs_since),
+            created_at: entry.created_at.format(DATETIME_FMT).to_string(),
+        }
+    }
+}
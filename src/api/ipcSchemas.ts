@@ -34,11 +34,19 @@ export function zodValidator<T extends z.ZodTypeAny>(schema: T, name: string) {
 export const EmptyOkResponseSchema = z.object({}).passthrough();
 
 export * from './ipcSchemas/importSchemas';
+export * from './ipcSchemas/planEvaluationSchemas';
 export * from './ipcSchemas/strategyDraftSchemas';
 export * from './ipcSchemas/versionComparisonSchemas';
+export * from './ipcSchemas/versionMergeSchemas';
+export * from './ipcSchemas/rushInsertionSchemas';
+export * from './ipcSchemas/undoSchemas';
+export * from './ipcSchemas/scenarioSchemas';
+export * from './ipcSchemas/versionApprovalSchemas';
+export * from './ipcSchemas/versionLineageSchemas';
 export * from './ipcSchemas/decisionRefreshSchemas';
 export * from './ipcSchemas/dashboardSchemas';
 export * from './ipcSchemas/materialSchemas';
+export * from './ipcSchemas/interventionExpirySchemas';
 export * from './ipcSchemas/planSchemas';
 export * from './ipcSchemas/configSchemas';
 export * from './ipcSchemas/capacitySchemas';
@@ -46,5 +54,6 @@ export * from './ipcSchemas/pathRuleSchemas';
 export * from './ipcSchemas/rollSchemas';
 export * from './ipcSchemas/actionLogSchemas';
 export * from './ipcSchemas/rhythmSchemas';
+export * from './ipcSchemas/productionSchemas';
 export * from './ipcSchemas/machineConfigSchemas';
 
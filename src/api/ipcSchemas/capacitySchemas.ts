@@ -33,10 +33,74 @@ export const BatchUpdateCapacityPoolsResponseSchema = z
   })
   .passthrough();
 
+// ==========================================================
+// 产能预留（战略合同/客户）
+// ==========================================================
+
+export const CapacityReservationSchema = z
+  .object({
+    reservation_id: z.string(),
+    machine_code: z.string(),
+    date_from: DateString,
+    date_to: DateString,
+    daily_reserved_t: z.number(),
+    contract_no: z.string().nullable().optional(),
+    contract_prefix: z.string().nullable().optional(),
+    /** 排产日期前 N 天释放未用预留 */
+    release_days_before: z.number(),
+    expires_on: DateString.nullable().optional(),
+    reason: z.string().nullable().optional(),
+    created_by: z.string(),
+    created_at: z.string(),
+  })
+  .passthrough();
+
+export const ReservationUsageSchema = z
+  .object({
+    version_id: z.string(),
+    reservation_id: z.string(),
+    machine_code: z.string(),
+    plan_date: DateString,
+    reserved_t: z.number(),
+    used_t: z.number(),
+    released_t: z.number(),
+    /** 重算时是否仍在保留期 */
+    held: z.boolean(),
+  })
+  .passthrough();
+
+export const ReservationUtilizationReportSchema = z
+  .object({
+    version_id: z.string(),
+    total_reserved_t: z.number(),
+    total_used_t: z.number(),
+    total_released_t: z.number(),
+    total_held_unused_t: z.number(),
+    utilization_pct: z.number(),
+    reservations: z.array(
+      z
+        .object({
+          reservation_id: z.string(),
+          machine_code: z.string(),
+          /** 已取消的预留为 null */
+          reservation: CapacityReservationSchema.nullable().optional(),
+          reserved_t: z.number(),
+          used_t: z.number(),
+          released_t: z.number(),
+          held_unused_t: z.number(),
+          utilization_pct: z.number(),
+          daily: z.array(ReservationUsageSchema),
+        })
+        .passthrough()
+    ),
+  })
+  .passthrough();
+
 // ==========================================================
 // 类型导出
 // ==========================================================
 
 export type CapacityPool = z.infer<typeof CapacityPoolSchema>;
 export type BatchUpdateCapacityPoolsResponse = z.infer<typeof BatchUpdateCapacityPoolsResponseSchema>;
-
+export type CapacityReservation = z.infer<typeof CapacityReservationSchema>;
+export type ReservationUtilizationReport = z.infer<typeof ReservationUtilizationReportSchema>;
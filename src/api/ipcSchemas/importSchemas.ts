@@ -26,6 +26,18 @@ export const DqViolationSchema = z
   })
   .passthrough();
 
+export const ImportSourceInfoSchema = z
+  .object({
+    file_format: z.string(),
+    encoding: z.string().nullable().optional(),
+    encoding_detected: z.boolean(),
+    sheet_name: z.string().nullable().optional(),
+    available_sheets: z.array(z.string()),
+    header_row: z.number().nullable().optional(),
+    header_auto_detected: z.boolean(),
+  })
+  .passthrough();
+
 export const ImportApiResponseSchema = z
   .object({
     imported: z.number(),
@@ -36,9 +48,92 @@ export const ImportApiResponseSchema = z
     dq_summary: DqSummarySchema,
     dq_violations: z.array(DqViolationSchema),
     elapsed_ms: z.number(),
+    source_info: ImportSourceInfoSchema.optional(),
+    cancelled: z.boolean().optional(),
+  })
+  .passthrough();
+
+// 合同/订单导入（按合同号更新关联材料影子字段）
+export const ContractImportResponseSchema = z
+  .object({
+    import_batch_id: z.string(),
+    matched_contracts: z.number(),
+    unmatched_contracts: z.number(),
+    updated_materials: z.number(),
+    dq_summary: DqSummarySchema,
+    dq_violations: z.array(DqViolationSchema),
+    elapsed_ms: z.number(),
+    source_info: ImportSourceInfoSchema.optional(),
+  })
+  .passthrough();
+
+export type ContractImportResponse = z.infer<typeof ContractImportResponseSchema>;
+
+// 分块导入进度（事件 material_import_progress）
+export const ImportProgressSchema = z
+  .object({
+    batch_id: z.string(),
+    stage: z.enum(['PARSING', 'WRITING', 'COMPLETED', 'CANCELLED']),
+    processed_rows: z.number(),
+    total_rows: z.number().nullable().optional(),
+    written_rows: z.number(),
+    conflict_rows: z.number(),
+    chunks_committed: z.number(),
   })
   .passthrough();
 
+export type ImportProgress = z.infer<typeof ImportProgressSchema>;
+
+export const CancelMaterialImportResponseSchema = z
+  .object({
+    cancelled: z.boolean(),
+  })
+  .passthrough();
+
+// 监控目录自动导入
+export const AutoImportConfigSchema = z
+  .object({
+    enabled: z.boolean(),
+    watch_dir: z.string().nullable().optional(),
+    file_pattern: z.string(),
+    mapping_profile_id: z.string().nullable().optional(),
+    stability_secs: z.number(),
+    poll_interval_secs: z.number(),
+    trigger_recalc: z.boolean(),
+    recalc_days: z.number(),
+  })
+  .passthrough();
+
+export const AutoImportFileResultSchema = z
+  .object({
+    file_name: z.string(),
+    outcome: z.enum(['IMPORTED', 'FAILED']),
+    source_batch_id: z.string(),
+    import_batch_id: z.string().nullable().optional(),
+    imported: z.number(),
+    conflicts: z.number(),
+    moved_to: z.string().nullable().optional(),
+    error: z.string().nullable().optional(),
+    recalc_version_id: z.string().nullable().optional(),
+    recalc_error: z.string().nullable().optional(),
+    processed_at: z.string(),
+  })
+  .passthrough();
+
+export type AutoImportFileResult = z.infer<typeof AutoImportFileResultSchema>;
+
+export const AutoImportStatusSchema = z
+  .object({
+    running: z.boolean(),
+    config: AutoImportConfigSchema,
+    last_scan_at: z.string().nullable().optional(),
+    last_scan_error: z.string().nullable().optional(),
+    recent_results: z.array(AutoImportFileResultSchema),
+  })
+  .passthrough();
+
+export type AutoImportStatus = z.infer<typeof AutoImportStatusSchema>;
+
 export const ImportConflictSchema = z
   .object({
     conflict_id: z.string(),
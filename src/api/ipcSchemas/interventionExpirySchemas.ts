@@ -0,0 +1,76 @@
+import { z } from 'zod';
+
+// ==========================================================
+// 人工干预时效（锁定/强制放行/人工紧急/优先级提升 到期或重算 N 次后自动清除）
+// ==========================================================
+
+export const InterventionTypeSchema = z.enum([
+  'LOCK',
+  'FORCE_RELEASE',
+  'MANUAL_URGENT',
+  'PRIORITY_BOOST',
+]);
+
+export const InterventionExpirySchema = z
+  .object({
+    material_id: z.string(),
+    intervention_type: z.string(),
+    /** 设置时效的操作人 */
+    operator: z.string(),
+    reason: z.string().nullable().optional(),
+    expires_at: z.string().nullable().optional(),
+    hours_remaining: z.number().nullable().optional(),
+    expire_after_recalcs: z.number().nullable().optional(),
+    recalcs_remaining: z.number().nullable().optional(),
+    /** 已满足失效条件、等待清扫 */
+    expired: z.boolean(),
+    created_at: z.string(),
+  })
+  .passthrough();
+
+export const UpcomingExpirationsResponseSchema = z
+  .object({
+    generated_at: z.string(),
+    within_hours: z.number(),
+    total: z.number(),
+    operators: z.array(
+      z
+        .object({
+          operator: z.string(),
+          entries: z.array(InterventionExpirySchema),
+        })
+        .passthrough()
+    ),
+  })
+  .passthrough();
+
+export const ExpirySweepResultSchema = z
+  .object({
+    swept_at: z.string(),
+    expired: z.array(
+      z
+        .object({
+          material_id: z.string(),
+          intervention_type: z.string(),
+          operator: z.string(),
+          /** TIME / RECALCS */
+          trigger: z.string(),
+        })
+        .passthrough()
+    ),
+    stale_removed: z.number(),
+    action_ids: z.array(z.string()),
+  })
+  .passthrough();
+
+export const InterventionExpiryStatusSchema = z
+  .object({
+    running: z.boolean(),
+    sweep_interval_secs: z.number(),
+    last_sweep_at: z.string().nullable().optional(),
+    last_sweep_error: z.string().nullable().optional(),
+    last_expired_count: z.number(),
+  })
+  .passthrough();
+
+export type InterventionType = z.infer<typeof InterventionTypeSchema>;
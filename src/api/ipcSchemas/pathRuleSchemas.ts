@@ -62,3 +62,41 @@ export const BatchConfirmPathOverrideResultSchema = z
   })
   .passthrough();
 
+
+export const ResequenceViolationSchema = z
+  .object({
+    material_id: z.string(),
+    plan_date: DateString, // 当日或下一排产日（复核第一块）
+    seq_no: z.number(),
+    status: z.string(), // OVERRIDE_REQUIRED / HARD_VIOLATION
+    violation_type: z.string(),
+    urgent_level: z.string(),
+    width_mm: z.number(),
+    thickness_mm: z.number(),
+    anchor_material_id: z.string().nullable().optional(),
+    anchor_width_mm: z.number(),
+    anchor_thickness_mm: z.number(),
+    width_delta_mm: z.number(),
+    thickness_delta_mm: z.number(),
+  })
+  .passthrough();
+
+export const ResequenceResultSchema = z
+  .object({
+    version_id: z.string(),
+    machine_code: z.string(),
+    plan_date: DateString,
+    old_order: z.array(z.string()),
+    new_order: z.array(z.string()),
+    moved_materials: z.array(z.string()),
+    anchor_source: z.string(),
+    anchor_material_id: z.string().nullable().optional(),
+    anchor_width_mm: z.number().nullable().optional(),
+    anchor_thickness_mm: z.number().nullable().optional(),
+    violations: z.array(ResequenceViolationSchema),
+    override_required_count: z.number(),
+    hard_violation_count: z.number(),
+    applied: z.boolean(),
+    message: z.string(),
+  })
+  .passthrough();
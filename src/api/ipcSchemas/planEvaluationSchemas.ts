@@ -0,0 +1,41 @@
+import { z } from 'zod';
+
+// ==========================================================
+// 方案多目标评价 Schema（策略草案 / 版本 KPI 对比 / 决策层 共用）
+// 对应 Rust: src/engine/plan_evaluation.rs PlanObjectiveVector
+// ==========================================================
+
+export const PlanObjectiveVectorSchema = z
+  .object({
+    plan_items_count: z.number(),
+    total_weight_t: z.number(),
+
+    due_items_count: z.number(),
+    tardy_count: z.number(),
+    total_tardiness_days: z.number(),
+    max_tardiness_days: z.number(),
+    on_time_weight_t: z.number(),
+    due_weight_t: z.number(),
+    on_time_weight_rate: z.number(),
+
+    capacity_used_t: z.number(),
+    capacity_target_t: z.number(),
+    capacity_limit_t: z.number(),
+    capacity_util_pct: z.number(),
+    overflow_t: z.number(),
+    overflow_machine_days: z.number(),
+
+    structure_deviation: z.number(),
+    structure_violation_days: z.number(),
+
+    path_override_count: z.number(),
+    roll_change_count: z.number(),
+    changeover_count: z.number(),
+
+    cold_stock_cleared_count: z.number(),
+    cold_stock_cleared_t: z.number(),
+    cold_stock_age_reduction_days: z.number(),
+  })
+  .passthrough();
+
+export type PlanObjectiveVector = z.infer<typeof PlanObjectiveVectorSchema>;
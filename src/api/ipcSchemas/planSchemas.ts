@@ -58,6 +58,18 @@ export const PlanItemSchema = z
   })
   .passthrough();
 
+// 钢种族批量顺延（重算结束仍因最小批量规则未排入的材料）
+export const BatchingDeferralSchema = z
+  .object({
+    version_id: z.string(),
+    material_id: z.string(),
+    machine_code: z.string(),
+    plan_date: DateString,
+    grade_family: z.string(),
+    reason: z.string(),
+  })
+  .passthrough();
+
 export const PlanItemDateBoundsResponseSchema = z
   .object({
     version_id: z.string(),
@@ -121,6 +133,75 @@ export const MoveItemsResponseSchema = z
   })
   .passthrough();
 
+// ==========================================================
+// 瓶颈优化建议（一键优化）响应 Schema
+// ==========================================================
+
+export const BottleneckActionKindSchema = z.enum([
+  'MOVE_ITEM',
+  'FORCE_RELEASE',
+  'CONFIRM_PATH_OVERRIDE',
+  'SHIFT_ROLL_CHANGE',
+]);
+
+export const BottleneckRecommendationSchema = z
+  .object({
+    recommendation_id: z.string(),
+    rank: z.number(),
+    kind: BottleneckActionKindSchema,
+    machine_code: z.string(),
+    material_id: z.string().nullable().optional(),
+    from_date: DateString.nullable().optional(),
+    to_date: DateString.nullable().optional(),
+    roll_change_from: z.string().nullable().optional(),
+    roll_change_to: z.string().nullable().optional(),
+    weight_t: z.number(),
+    urgent_level: z.string().nullable().optional(),
+    overload_relief_t: z.number(),
+    slack_fill_t: z.number(),
+    benefit_score: z.number(),
+    reason: z.string(),
+    /** ImpactSummaryEngine 预计影响（结构较大，按需读取） */
+    predicted_impact: z.record(z.unknown()).nullable().optional(),
+  })
+  .passthrough();
+
+export const BottleneckRecommendationResponseSchema = z
+  .object({
+    version_id: z.string(),
+    plan_date_from: DateString,
+    plan_date_to: DateString,
+    machine_code: z.string().nullable().optional(),
+    bottleneck_day_count: z.number(),
+    overload_t: z.number(),
+    remaining_bottleneck_day_count: z.number(),
+    remaining_overload_t: z.number(),
+    recommendations: z.array(BottleneckRecommendationSchema),
+    message: z.string(),
+  })
+  .passthrough();
+
+export const ApplyBottleneckRecommendationsResponseSchema = z
+  .object({
+    version_id: z.string(),
+    applied_ids: z.array(z.string()),
+    skipped: z.array(
+      z
+        .object({
+          recommendation_id: z.string(),
+          reason: z.string(),
+        })
+        .passthrough()
+    ),
+    moved_count: z.number(),
+    force_released_count: z.number(),
+    path_override_confirmed_count: z.number(),
+    roll_change_shifted_count: z.number(),
+    move_result: MoveItemsResponseSchema.nullable().optional(),
+    message: z.string(),
+  })
+  .passthrough();
+
 // ==========================================================
 // 一键重算/试算 响应 Schema
 // ==========================================================
@@ -0,0 +1,81 @@
+import { z } from 'zod';
+
+import { DateString, DateTimeString } from './_shared';
+import { DqSummarySchema, DqViolationSchema, ImportSourceInfoSchema } from './importSchemas';
+
+// ==========================================================
+// 生产实绩 / 计划执行对账 Schema
+// ==========================================================
+
+export const PlanAdherenceRecordSchema = z
+  .object({
+    version_id: z.string(),
+    machine_code: z.string(),
+    plan_date: DateString,
+    planned_count: z.number(),
+    planned_weight_t: z.number(),
+    actual_count: z.number(),
+    actual_weight_t: z.number(),
+    matched_count: z.number(),
+    matched_weight_t: z.number(),
+    unplanned_count: z.number(),
+    carry_over_count: z.number(),
+    sequence_deviation_count: z.number(),
+    max_sequence_displacement: z.number(),
+    avg_sequence_displacement: z.number(),
+    adherence_rate: z.number().nullable().optional(),
+    batch_id: z.string().nullable().optional(),
+    generated_at: DateTimeString,
+  })
+  .passthrough();
+
+export type PlanAdherenceRecord = z.infer<typeof PlanAdherenceRecordSchema>;
+
+export const ProductionActualsImportResponseSchema = z
+  .object({
+    batch_id: z.string(),
+    version_id: z.string().nullable().optional(),
+    imported: z.number(),
+    campaigns_updated: z.number(),
+    dq_summary: DqSummarySchema,
+    dq_violations: z.array(DqViolationSchema),
+    adherence: z.array(PlanAdherenceRecordSchema),
+    carry_over_count: z.number(),
+    elapsed_ms: z.number(),
+    source_info: ImportSourceInfoSchema.optional(),
+  })
+  .passthrough();
+
+export type ProductionActualsImportResponse = z.infer<typeof ProductionActualsImportResponseSchema>;
+
+export const PlanAdherenceReportSchema = z
+  .object({
+    version_id: z.string(),
+    rows: z.array(PlanAdherenceRecordSchema),
+    total_planned_weight_t: z.number(),
+    total_actual_weight_t: z.number(),
+    total_matched_weight_t: z.number(),
+    total_carry_over_count: z.number(),
+    total_sequence_deviation_count: z.number(),
+    overall_adherence_rate: z.number().nullable().optional(),
+  })
+  .passthrough();
+
+export type PlanAdherenceReport = z.infer<typeof PlanAdherenceReportSchema>;
+
+export const CarryOverItemSchema = z
+  .object({
+    version_id: z.string(),
+    material_id: z.string(),
+    machine_code: z.string(),
+    plan_date: DateString,
+    seq_no: z.number(),
+    weight_t: z.number(),
+    detected_at: DateTimeString,
+    resolved_at: DateTimeString.nullable().optional(),
+  })
+  .passthrough();
+
+export const CarryOverItemsResponseSchema = z.array(CarryOverItemSchema);
+
+export type CarryOverItem = z.infer<typeof CarryOverItemSchema>;
@@ -0,0 +1,62 @@
+import { z } from 'zod';
+
+import { DateString } from './_shared';
+
+// ==========================================================
+// 紧急插单（最早可行日 + 挤出链，执行后生成派生草稿版本）
+// ==========================================================
+
+export const RushPlacementSchema = z
+  .object({
+    material_id: z.string(),
+    machine_code: z.string(),
+    from_date: DateString.nullable().optional(),
+    to_date: DateString,
+    seq_no: z.number(),
+    weight_t: z.number(),
+    urgent_level: z.string(),
+    due_date: DateString.nullable().optional(),
+    tardiness_days: z.number(),
+    displaced_count: z.number(),
+  })
+  .passthrough();
+
+export const DisplacedItemSchema = z
+  .object({
+    material_id: z.string(),
+    machine_code: z.string(),
+    from_date: DateString,
+    to_date: DateString,
+    weight_t: z.number(),
+    urgent_level: z.string().nullable().optional(),
+    due_date: DateString.nullable().optional(),
+    tardiness_before_days: z.number(),
+    tardiness_after_days: z.number(),
+    caused_by: z.string(),
+    chain_step: z.number(),
+  })
+  .passthrough();
+
+export const RushInfeasibleSchema = z
+  .object({
+    material_id: z.string(),
+    reason: z.string(),
+  })
+  .passthrough();
+
+export const RushInsertionResponseSchema = z
+  .object({
+    base_version_id: z.string(),
+    derived_version_id: z.string().nullable().optional(),
+    first_open_date: DateString,
+    placements: z.array(RushPlacementSchema),
+    displaced: z.array(DisplacedItemSchema),
+    infeasible: z.array(RushInfeasibleSchema),
+    displaced_weight_t: z.number(),
+    added_tardiness_days: z.number(),
+    newly_late_count: z.number(),
+    /** ImpactSummaryEngine 影响摘要（结构较大，按需读取） */
+    impact: z.record(z.unknown()),
+    message: z.string(),
+  })
+  .passthrough();
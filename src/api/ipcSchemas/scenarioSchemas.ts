@@ -0,0 +1,258 @@
+import { z } from 'zod';
+
+import { DateString } from './_shared';
+import { PlanItemSchema } from './planSchemas';
+
+// ==========================================================
+// 场景/沙盘方案（隔离评估 + 提升为草稿版本）
+// ==========================================================
+
+export const CapacityOverrideSchema = z
+  .object({
+    machine_code: z.string(),
+    plan_date: DateString,
+    target_capacity_t: z.number().nullable().optional(),
+    limit_capacity_t: z.number().nullable().optional(),
+  })
+  .passthrough();
+
+export const HypotheticalMaterialSchema = z
+  .object({
+    material_id: z.string(),
+    machine_code: z.string(),
+    weight_t: z.number(),
+    width_mm: z.number().nullable().optional(),
+    thickness_mm: z.number().nullable().optional(),
+    steel_mark: z.string().nullable().optional(),
+    due_date: DateString.nullable().optional(),
+  })
+  .passthrough();
+
+export const MachineOutageSchema = z
+  .object({
+    machine_code: z.string(),
+    date_from: DateString,
+    date_to: DateString,
+    reason: z.string().nullable().optional(),
+  })
+  .passthrough();
+
+export const ScenarioOverlaySchema = z
+  .object({
+    capacity_changes: z.array(CapacityOverrideSchema),
+    hypothetical_materials: z.array(HypotheticalMaterialSchema),
+    machine_outages: z.array(MachineOutageSchema),
+    config_overrides: z.record(z.string()),
+  })
+  .passthrough();
+
+export type ScenarioOverlay = z.infer<typeof ScenarioOverlaySchema>;
+
+export const CreateScenarioPlanResponseSchema = z
+  .object({
+    plan_id: z.string(),
+  })
+  .passthrough();
+
+export const ScenarioEvaluationResponseSchema = z
+  .object({
+    run_id: z.string(),
+    plan_id: z.string(),
+    base_version_id: z.string(),
+    plan_date_from: DateString,
+    plan_date_to: DateString,
+    strategy: z.string(),
+    total_items: z.number(),
+    total_weight_t: z.number(),
+    mature_count: z.number(),
+    immature_count: z.number(),
+    overflow_days: z.number(),
+    hypothetical_scheduled: z.number(),
+    baseline_items: z.number(),
+    baseline_weight_t: z.number(),
+  })
+  .passthrough();
+
+export type ScenarioEvaluationResponse = z.infer<typeof ScenarioEvaluationResponseSchema>;
+
+export const ScenarioRunSchema = z
+  .object({
+    run_id: z.string(),
+    plan_id: z.string(),
+    base_version_id: z.string(),
+    plan_date_from: DateString,
+    plan_date_to: DateString,
+    strategy: z.string(),
+    total_items: z.number(),
+    total_weight_t: z.number(),
+    mature_count: z.number(),
+    immature_count: z.number(),
+    overflow_days: z.number(),
+    hypothetical_scheduled: z.number(),
+    items: z.array(PlanItemSchema),
+    hypothetical_material_ids: z.array(z.string()),
+    overlay: ScenarioOverlaySchema,
+    created_by: z.string(),
+    created_at: z.string(),
+    promoted_version_id: z.string().nullable().optional(),
+  })
+  .passthrough();
+
+export type ScenarioRun = z.infer<typeof ScenarioRunSchema>;
+
+export const PromoteScenarioResponseSchema = z
+  .object({
+    version_id: z.string(),
+    plan_id: z.string(),
+    items_count: z.number(),
+    skipped_hypothetical: z.number(),
+    message: z.string(),
+  })
+  .passthrough();
+
+export type PromoteScenarioResponse = z.infer<typeof PromoteScenarioResponseSchema>;
+
+// ==========================================================
+// 机组故障 what-if 模拟
+// ==========================================================
+
+export const DisplacedMaterialSchema = z
+  .object({
+    material_id: z.string(),
+    contract_no: z.string().nullable().optional(),
+    urgent_level: z.string().nullable().optional(),
+    due_date: z.string().nullable().optional(),
+    weight_t: z.number(),
+    machine_code: z.string(),
+    baseline_date: DateString,
+    whatif_machine_code: z.string().nullable().optional(),
+    whatif_date: DateString.nullable().optional(),
+    delay_days: z.number().nullable().optional(),
+  })
+  .passthrough();
+
+export const ContractAtRiskSchema = z
+  .object({
+    contract_no: z.string(),
+    due_date: DateString,
+    days_to_due: z.number(),
+    urgent_level: z.string(),
+    total_weight_t: z.number(),
+    baseline_completion_rate: z.number(),
+    whatif_completion_rate: z.number(),
+    reason: z.string(),
+  })
+  .passthrough();
+
+export const BreakdownSuggestionSchema = z
+  .object({
+    kind: z.enum(['ALTERNATE_MACHINE', 'CAPACITY_MAKEUP', 'EXPEDITE_CONTRACT']),
+    machine_code: z.string().nullable().optional(),
+    plan_date: DateString.nullable().optional(),
+    contract_no: z.string().nullable().optional(),
+    weight_t: z.number(),
+    detail: z.string(),
+  })
+  .passthrough();
+
+export const BreakdownSimulationResponseSchema = z
+  .object({
+    version_id: z.string(),
+    machine_code: z.string(),
+    outage_start: z.string(),
+    outage_end: z.string(),
+    plan_date_from: DateString,
+    plan_date_to: DateString,
+    capacity_loss: z.array(
+      z
+        .object({
+          plan_date: DateString,
+          loss_ratio: z.number(),
+          lost_capacity_t: z.number(),
+        })
+        .passthrough()
+    ),
+    baseline_items: z.number(),
+    whatif_items: z.number(),
+    squeezed_weight_t: z.number(),
+    squeezed_out: z.array(DisplacedMaterialSchema),
+    delayed: z.array(DisplacedMaterialSchema),
+    newly_at_risk: z.array(ContractAtRiskSchema),
+    suggestions: z.array(BreakdownSuggestionSchema),
+    draft_version_id: z.string().nullable().optional(),
+  })
+  .passthrough();
+
+export type BreakdownSimulationResponse = z.infer<typeof BreakdownSimulationResponseSchema>;
+
+// ==========================================================
+// 历史重算确定性回放
+// ==========================================================
+
+export const RecalcRunParamsSchema = z
+  .object({
+    engine_version: z.string(),
+    plan_id: z.string(),
+    base_version_id: z.string().nullable().optional(),
+    base_date: DateString,
+    end_date: DateString,
+    window_days: z.number(),
+    frozen_from_date: DateString,
+    machine_codes: z.array(z.string()),
+    strategy_key: z.string(),
+    base_strategy: z.string(),
+    strategy_parameters: z.unknown().nullable().optional(),
+  })
+  .passthrough();
+
+export const RecalcInputFingerprintSchema = z
+  .object({
+    run: RecalcRunParamsSchema,
+    material_hash: z.string(),
+    capacity_hash: z.string(),
+    strategy_hash: z.string(),
+    config_hash: z.string(),
+    frozen_hash: z.string(),
+    combined_hash: z.string(),
+    material_count: z.number(),
+    capacity_pool_count: z.number(),
+    frozen_item_count: z.number(),
+    inputs_persisted: z.boolean(),
+  })
+  .passthrough();
+
+export type RecalcInputFingerprint = z.infer<typeof RecalcInputFingerprintSchema>;
+
+export const RecalcReplayDiffSchema = z
+  .object({
+    material_id: z.string(),
+    change_type: z.enum(['ADDED', 'REMOVED', 'MOVED', 'SEQ_CHANGED']),
+    stored_machine_code: z.string().nullable().optional(),
+    stored_plan_date: DateString.nullable().optional(),
+    stored_seq_no: z.number().nullable().optional(),
+    replayed_machine_code: z.string().nullable().optional(),
+    replayed_plan_date: DateString.nullable().optional(),
+    replayed_seq_no: z.number().nullable().optional(),
+  })
+  .passthrough();
+
+export const RecalcReplayReportSchema = z
+  .object({
+    version_id: z.string(),
+    input_source: z.enum(['PERSISTED', 'CURRENT']),
+    recorded_fingerprint: RecalcInputFingerprintSchema,
+    replay_fingerprint: RecalcInputFingerprintSchema,
+    input_mismatches: z.array(z.string()),
+    stored_count: z.number(),
+    replayed_count: z.number(),
+    identical: z.boolean(),
+    added_count: z.number(),
+    removed_count: z.number(),
+    moved_count: z.number(),
+    seq_changed_count: z.number(),
+    diffs: z.array(RecalcReplayDiffSchema),
+    diffs_truncated: z.boolean(),
+  })
+  .passthrough();
+
+export type RecalcReplayReport = z.infer<typeof RecalcReplayReportSchema>;
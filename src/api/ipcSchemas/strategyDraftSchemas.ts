@@ -1,6 +1,8 @@
 import { z } from 'zod';
 
 import { DateString } from './_shared';
+import { CustomStrategyParametersSchema } from './configSchemas';
+import { PlanObjectiveVectorSchema } from './planEvaluationSchemas';
 
 // ==========================================================
 // P0-1: strategy draft (draft persistence)
@@ -23,6 +25,7 @@ export const StrategyDraftSummarySchema = z
     removed_count: z.number(),
     squeezed_out_count: z.number(),
     message: z.string(),
+    objectives: PlanObjectiveVectorSchema.nullable().optional(),
   })
   .passthrough();
 
@@ -33,11 +36,20 @@ export const GenerateStrategyDraftsResponseSchema = z
     plan_date_to: DateString,
     drafts: z.array(StrategyDraftSummarySchema),
     message: z.string(),
+    generation_id: z.string().nullable().optional(),
+    cancelled: z.boolean().nullable().optional(),
   })
   .passthrough();
 
 export const ListStrategyDraftsResponseSchema = GenerateStrategyDraftsResponseSchema;
 
+export const CancelStrategyDraftGenerationResponseSchema = z
+  .object({
+    generation_id: z.string(),
+    cancelled: z.boolean(),
+  })
+  .passthrough();
+
 export const ApplyStrategyDraftResponseSchema = z
   .object({
     version_id: z.string(),
@@ -105,3 +117,103 @@ export const CleanupStrategyDraftsResponseSchema = z
   })
   .passthrough();
 
+
+// ==========================================================
+// 自定义策略权重参数扫描
+// ==========================================================
+
+export const WeightRangeSchema = z
+  .object({
+    min: z.number(),
+    max: z.number(),
+    steps: z.number().optional(),
+  })
+  .passthrough();
+
+export const StrategySweepSpecSchema = z
+  .object({
+    base_strategy: z.string(),
+    mode: z.enum(['GRID', 'RANDOM']),
+    urgent_weight: WeightRangeSchema.nullable().optional(),
+    capacity_weight: WeightRangeSchema.nullable().optional(),
+    cold_stock_weight: WeightRangeSchema.nullable().optional(),
+    due_date_weight: WeightRangeSchema.nullable().optional(),
+    rolling_output_age_weight: WeightRangeSchema.nullable().optional(),
+    sample_count: z.number().nullable().optional(),
+    seed: z.number().nullable().optional(),
+    cold_stock_age_threshold_days: z.number().nullable().optional(),
+    overflow_tolerance_pct: z.number().nullable().optional(),
+  })
+  .passthrough();
+
+export const StrategySweepPointSchema = z
+  .object({
+    index: z.number(),
+    draft_id: z.string(),
+    strategy_key: z.string(),
+    parameters: CustomStrategyParametersSchema,
+    objectives: PlanObjectiveVectorSchema,
+    pareto_optimal: z.boolean(),
+    dominated_by_count: z.number(),
+  })
+  .passthrough();
+
+export const StrategySweepResponseSchema = z
+  .object({
+    sweep_id: z.string(),
+    base_version_id: z.string(),
+    plan_date_from: DateString,
+    plan_date_to: DateString,
+    base_strategy: z.string(),
+    points: z.array(StrategySweepPointSchema),
+    pareto_count: z.number(),
+    message: z.string(),
+  })
+  .passthrough();
+
+// ==========================================================
+// 人工调整反推策略权重（离线分析）
+// ==========================================================
+
+export const AgreementRateSchema = z
+  .object({
+    agreed: z.number(),
+    total: z.number(),
+    rate: z.number(),
+  })
+  .passthrough();
+
+export const UrgencyRuleSuggestionSchema = z
+  .object({
+    config_key: z.string(),
+    current_value: z.number(),
+    suggested_value: z.number(),
+    evidence_count: z.number(),
+    covered_before: z.number(),
+    covered_after: z.number(),
+    reason: z.string(),
+  })
+  .passthrough();
+
+export const StrategyWeightLearningResponseSchema = z
+  .object({
+    analysis_from: DateString,
+    analysis_to: DateString,
+    strategy_key: z.string(),
+    base_strategy: z.string(),
+    move_log_count: z.number(),
+    legacy_move_log_count: z.number(),
+    urgent_log_count: z.number(),
+    force_release_log_count: z.number(),
+    pair_count: z.number(),
+    excluded_pair_count: z.number(),
+    pairs_truncated: z.boolean(),
+    baseline_agreement: AgreementRateSchema,
+    learned_agreement: AgreementRateSchema,
+    learned_parameters: CustomStrategyParametersSchema,
+    urgent_n1_days: z.number(),
+    urgent_n2_days: z.number(),
+    rule_suggestions: z.array(UrgencyRuleSuggestionSchema),
+    message: z.string(),
+  })
+  .passthrough();
@@ -0,0 +1,43 @@
+import { z } from 'zod';
+
+// ==========================================================
+// 人工操作撤销/重做（按操作人 + 版本回放 action_log）
+// ==========================================================
+
+export const UndoEntrySchema = z
+  .object({
+    action_id: z.string(),
+    /** 原始操作类型（MOVE_ITEMS / LOCK_MATERIALS / PathOverrideConfirm 等） */
+    source_action_type: z.string(),
+    /** 逆操作载荷类型（MOVE_ITEMS / MATERIAL_FLAGS / PATH_OVERRIDE） */
+    payload_kind: z.string(),
+    material_ids: z.array(z.string()),
+    action_ts: z.string(),
+    detail: z.string().nullable().optional(),
+  })
+  .passthrough();
+
+export const UndoStatusResponseSchema = z
+  .object({
+    version_id: z.string(),
+    actor: z.string(),
+    undo_depth: z.number(),
+    redo_depth: z.number(),
+    next_undo: UndoEntrySchema.nullable().optional(),
+    next_redo: UndoEntrySchema.nullable().optional(),
+  })
+  .passthrough();
+
+export const UndoRedoResponseSchema = z
+  .object({
+    version_id: z.string(),
+    kind: z.enum(['UNDO', 'REDO']),
+    action_id: z.string(),
+    target_action_id: z.string(),
+    source_action_type: z.string(),
+    payload_kind: z.string(),
+    material_ids: z.array(z.string()),
+    status: UndoStatusResponseSchema,
+    message: z.string(),
+  })
+  .passthrough();
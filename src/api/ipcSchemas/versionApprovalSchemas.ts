@@ -0,0 +1,73 @@
+import { z } from 'zod';
+
+import { DateString, DateTimeString } from './_shared';
+
+// ==========================================================
+// 版本激活审批 Schema (提交 → 审批通过/驳回 → 激活)
+// ==========================================================
+
+export const ChecklistItemSchema = z
+  .object({
+    code: z.string(),
+    label: z.string(),
+    passed: z.boolean(),
+    detail: z.string(),
+  })
+  .passthrough();
+
+export const ActivationChecklistSchema = z
+  .object({
+    items: z.array(ChecklistItemSchema),
+    generated_at: DateTimeString,
+  })
+  .passthrough();
+
+export const VersionReviewSchema = z
+  .object({
+    review_id: z.string(),
+    version_id: z.string(),
+    action: z.enum(['SUBMIT', 'APPROVE', 'REJECT']),
+    actor: z.string(),
+    comment: z.string().nullable().optional(),
+    checklist: ActivationChecklistSchema.nullable().optional(),
+    created_at: DateTimeString,
+  })
+  .passthrough();
+
+export const VersionReviewHistorySchema = z
+  .object({
+    version_id: z.string(),
+    status: z.string(),
+    reviews: z.array(VersionReviewSchema),
+  })
+  .passthrough();
+
+// 激活前红线校验报告（HARD 级违规阻断激活，需填写覆盖原因）
+export const RedLineViolationSchema = z
+  .object({
+    code: z.string(),
+    severity: z.enum(['HARD', 'WARNING']),
+    material_id: z.string().nullable().optional(),
+    machine_code: z.string().nullable().optional(),
+    plan_date: DateString.nullable().optional(),
+    message: z.string(),
+  })
+  .passthrough();
+
+export const RedLineReportSchema = z
+  .object({
+    version_id: z.string(),
+    baseline_version_id: z.string().nullable().optional(),
+    hard_count: z.number(),
+    warning_count: z.number(),
+    violations: z.array(RedLineViolationSchema),
+    generated_at: DateTimeString,
+  })
+  .passthrough();
+
+export type ChecklistItem = z.infer<typeof ChecklistItemSchema>;
+export type ActivationChecklist = z.infer<typeof ActivationChecklistSchema>;
+export type VersionReview = z.infer<typeof VersionReviewSchema>;
+export type VersionReviewHistory = z.infer<typeof VersionReviewHistorySchema>;
+export type RedLineViolation = z.infer<typeof RedLineViolationSchema>;
+export type RedLineReport = z.infer<typeof RedLineReportSchema>;
@@ -1,6 +1,7 @@
 import { z } from 'zod';
 
-import { DateString } from './_shared';
+import { DateString, DateTimeString } from './_shared';
+import { PlanObjectiveVectorSchema } from './planEvaluationSchemas';
 
 // ==========================================================
 // P1-1: version comparison KPI aggregation
@@ -35,6 +36,8 @@ export const VersionKpiSummarySchema = z
     urgent_total_t: z.number().nullable(),
     snapshot_date_from: DateString.nullable(),
     snapshot_date_to: DateString.nullable(),
+
+    objectives: PlanObjectiveVectorSchema.nullable().optional(),
   })
   .passthrough();
 
@@ -76,6 +79,66 @@ export const ConfigChangeSchema = z
   })
   .passthrough();
 
+export const ContractDeliveryDeltaSchema = z
+  .object({
+    contract_no: z.string(),
+    due_date: DateString.nullable().optional(),
+    material_count: z.number(),
+    scheduled_count_a: z.number(),
+    scheduled_count_b: z.number(),
+    on_time_count_a: z.number(),
+    on_time_count_b: z.number(),
+    on_time_rate_a: z.number(),
+    on_time_rate_b: z.number(),
+    on_time_rate_delta: z.number(),
+    finish_date_a: DateString.nullable().optional(),
+    finish_date_b: DateString.nullable().optional(),
+  })
+  .passthrough();
+
+export const GradeMixDeltaSchema = z
+  .object({
+    machine_code: z.string(),
+    plan_date: DateString,
+    steel_grade: z.string(),
+    weight_a: z.number(),
+    weight_b: z.number(),
+    weight_delta: z.number(),
+    count_a: z.number(),
+    count_b: z.number(),
+  })
+  .passthrough();
+
+export const SequenceDeltaSchema = z
+  .object({
+    machine_code: z.string(),
+    plan_date: DateString,
+    common_count: z.number(),
+    discordant_pairs: z.number(),
+    kendall_tau_distance: z.number(),
+  })
+  .passthrough();
+
+export const PathOverrideDeltaSchema = z
+  .object({
+    pending_count_a: z.number(),
+    pending_count_b: z.number(),
+    pending_delta: z.number(),
+    confirmed_count_a: z.number(),
+    confirmed_count_b: z.number(),
+    confirmed_delta: z.number(),
+  })
+  .passthrough();
+
+export const RollChangeDeltaSchema = z
+  .object({
+    machine_code: z.string(),
+    roll_change_count_a: z.number(),
+    roll_change_count_b: z.number(),
+    roll_change_delta: z.number(),
+  })
+  .passthrough();
+
 export const VersionComparisonResultSchema = z
   .object({
     version_id_a: z.string(),
@@ -86,8 +149,101 @@ export const VersionComparisonResultSchema = z
     squeezed_out_count: z.number(),
     risk_delta: z.array(RiskDeltaSchema).nullable().optional(),
     capacity_delta: z.array(CapacityDeltaSchema).nullable().optional(),
+    contract_delta: z.array(ContractDeliveryDeltaSchema).nullable().optional(),
+    grade_mix_delta: z.array(GradeMixDeltaSchema).nullable().optional(),
+    sequence_delta: z.array(SequenceDeltaSchema).nullable().optional(),
+    path_override_delta: PathOverrideDeltaSchema.nullable().optional(),
+    roll_change_delta: z.array(RollChangeDeltaSchema).nullable().optional(),
     config_changes: z.array(ConfigChangeSchema).nullable().optional(),
     message: z.string(),
   })
   .passthrough();
 
+export const VersionDiffItemSchema = z
+  .object({
+    material_id: z.string(),
+    change_type: z.enum(['ADDED', 'REMOVED', 'MOVED', 'SEQ_CHANGED']),
+    machine_code_a: z.string().nullable().optional(),
+    plan_date_a: DateString.nullable().optional(),
+    seq_no_a: z.number().nullable().optional(),
+    machine_code_b: z.string().nullable().optional(),
+    plan_date_b: DateString.nullable().optional(),
+    seq_no_b: z.number().nullable().optional(),
+    weight_t: z.number(),
+  })
+  .passthrough();
+
+export const VersionDiffItemPageSchema = z
+  .object({
+    version_id_a: z.string(),
+    version_id_b: z.string(),
+    total_count: z.number(),
+    offset: z.number(),
+    limit: z.number(),
+    items: z.array(VersionDiffItemSchema),
+  })
+  .passthrough();
+
+
+// ==========================================================
+// 现场变更通知 (相邻两个激活版本，按机组×日期)
+// ==========================================================
+
+export const ChangeNoticeLineSchema = z
+  .object({
+    machine_code: z.string(),
+    plan_date: DateString,
+    material_id: z.string(),
+    change_type: z.enum(['ADDED', 'REMOVED', 'MOVED', 'RESEQUENCED']),
+    from_machine_code: z.string().nullable().optional(),
+    from_plan_date: DateString.nullable().optional(),
+    from_seq_no: z.number().nullable().optional(),
+    to_machine_code: z.string().nullable().optional(),
+    to_plan_date: DateString.nullable().optional(),
+    to_seq_no: z.number().nullable().optional(),
+    weight_t: z.number(),
+    reason: z.string(),
+  })
+  .passthrough();
+
+export const ChangeNoticeMachineDaySchema = z
+  .object({
+    machine_code: z.string(),
+    plan_date: DateString,
+    added_count: z.number(),
+    removed_count: z.number(),
+    moved_in_count: z.number(),
+    moved_out_count: z.number(),
+    resequenced_count: z.number(),
+    weight_delta_t: z.number(),
+  })
+  .passthrough();
+
+export const ChangeNoticeSchema = z
+  .object({
+    plan_id: z.string(),
+    from_version_id: z.string().nullable().optional(),
+    from_version_no: z.number().nullable().optional(),
+    to_version_id: z.string(),
+    to_version_no: z.number(),
+    added_count: z.number(),
+    removed_count: z.number(),
+    moved_count: z.number(),
+    resequenced_count: z.number(),
+    machine_days: z.array(ChangeNoticeMachineDaySchema),
+    lines: z.array(ChangeNoticeLineSchema),
+    generated_at: DateTimeString,
+  })
+  .passthrough();
+
+export const ChangeNoticeExportResponseSchema = z
+  .object({
+    file_path: z.string(),
+    format: z.string(),
+    line_count: z.number(),
+  })
+  .passthrough();
+
+export type ChangeNoticeLine = z.infer<typeof ChangeNoticeLineSchema>;
+export type ChangeNotice = z.infer<typeof ChangeNoticeSchema>;
+export type ChangeNoticeExportResponse = z.infer<typeof ChangeNoticeExportResponseSchema>;
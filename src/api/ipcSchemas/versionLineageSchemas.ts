@@ -0,0 +1,101 @@
+import { z } from 'zod';
+
+import { DateTimeString } from './_shared';
+
+// ==========================================================
+// 版本谱系与保留策略 Schema (谱系树 / 历史版本清理)
+// ==========================================================
+
+export const VersionTriggerSchema = z.enum([
+  'MANUAL',
+  'RECALC',
+  'MERGE',
+  'SCENARIO_PROMOTE',
+  'BREAKDOWN_WHAT_IF',
+  'RUSH_INSERT',
+]);
+
+export const LineageDiffCountsSchema = z
+  .object({
+    moved_count: z.number(),
+    added_count: z.number(),
+    removed_count: z.number(),
+  })
+  .passthrough();
+
+export type VersionLineageNode = {
+  version_id: string;
+  version_no: number;
+  status: string;
+  parent_version_id?: string | null;
+  trigger?: z.infer<typeof VersionTriggerSchema> | null;
+  strategy?: string | null;
+  version_name?: string | null;
+  created_by?: string | null;
+  created_at: string;
+  diff_from_parent?: z.infer<typeof LineageDiffCountsSchema> | null;
+  children: VersionLineageNode[];
+};
+
+export const VersionLineageNodeSchema: z.ZodType<VersionLineageNode> = z.lazy(() =>
+  z
+    .object({
+      version_id: z.string(),
+      version_no: z.number(),
+      status: z.string(),
+      parent_version_id: z.string().nullable().optional(),
+      trigger: VersionTriggerSchema.nullable().optional(),
+      strategy: z.string().nullable().optional(),
+      version_name: z.string().nullable().optional(),
+      created_by: z.string().nullable().optional(),
+      created_at: DateTimeString,
+      diff_from_parent: LineageDiffCountsSchema.nullable().optional(),
+      children: z.array(VersionLineageNodeSchema),
+    })
+    .passthrough()
+);
+
+export const VersionLineageTreeSchema = z
+  .object({
+    plan_id: z.string(),
+    version_count: z.number(),
+    roots: z.array(VersionLineageNodeSchema),
+  })
+  .passthrough();
+
+export const VersionRetentionPolicySchema = z
+  .object({
+    keep_archived_per_plan: z.number(),
+    keep_ever_active: z.boolean(),
+    max_age_days: z.number().nullable().optional(),
+  })
+  .passthrough();
+
+export const VersionPurgeCandidateSchema = z
+  .object({
+    version_id: z.string(),
+    plan_id: z.string(),
+    version_no: z.number(),
+    status: z.string(),
+    created_at: DateTimeString,
+    reason: z.string(),
+  })
+  .passthrough();
+
+export const VersionPurgeReportSchema = z
+  .object({
+    dry_run: z.boolean(),
+    policy: VersionRetentionPolicySchema,
+    scanned_count: z.number(),
+    candidates: z.array(VersionPurgeCandidateSchema),
+    deleted_rows: z.record(z.number()),
+    detached_action_logs: z.number(),
+    executed_at: DateTimeString,
+  })
+  .passthrough();
+
+export type VersionTrigger = z.infer<typeof VersionTriggerSchema>;
+export type VersionLineageTree = z.infer<typeof VersionLineageTreeSchema>;
+export type VersionRetentionPolicy = z.infer<typeof VersionRetentionPolicySchema>;
+export type VersionPurgeCandidate = z.infer<typeof VersionPurgeCandidateSchema>;
+export type VersionPurgeReport = z.infer<typeof VersionPurgeReportSchema>;
@@ -0,0 +1,60 @@
+import { z } from 'zod';
+
+import { DateString } from './_shared';
+
+// ==========================================================
+// 版本三方合并（人工调整回放到重算版本）
+// ==========================================================
+
+export const ManualChangeSchema = z
+  .object({
+    material_id: z.string(),
+    from_machine_code: z.string().nullable().optional(),
+    from_plan_date: DateString.nullable().optional(),
+    from_seq_no: z.number().nullable().optional(),
+    to_machine_code: z.string(),
+    to_plan_date: DateString,
+    to_seq_no: z.number(),
+    weight_t: z.number(),
+  })
+  .passthrough();
+
+export const MergeConflictTypeSchema = z.enum([
+  'MATERIAL_NOT_ELIGIBLE',
+  'CAPACITY_EXCEEDED',
+  'PATH_RULE_VIOLATED',
+]);
+
+export const MergeConflictSchema = z
+  .object({
+    material_id: z.string(),
+    conflict_type: MergeConflictTypeSchema,
+    change: ManualChangeSchema,
+    reason: z.string(),
+  })
+  .passthrough();
+
+export const VersionMergePreviewResponseSchema = z
+  .object({
+    ancestor_version_id: z.string(),
+    adjusted_version_id: z.string(),
+    recalculated_version_id: z.string(),
+    manual_change_count: z.number(),
+    applied: z.array(ManualChangeSchema),
+    conflicts: z.array(MergeConflictSchema),
+    already_matching: z.number(),
+    merged_items_count: z.number(),
+    message: z.string(),
+  })
+  .passthrough();
+
+export const VersionMergeApplyResponseSchema = z
+  .object({
+    merged_version_id: z.string(),
+    applied_count: z.number(),
+    forced_count: z.number(),
+    dropped_count: z.number(),
+    merged_items_count: z.number(),
+    message: z.string(),
+  })
+  .passthrough();
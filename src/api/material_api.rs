@@ -16,6 +16,7 @@ use crate::config::config_manager::ConfigManager;
 use crate::domain::action_log::ActionLog;
 use crate::domain::material::{MaterialMaster, MaterialState};
 use crate::domain::types::{SchedState, UrgentLevel};
+use crate::domain::undo::{InversePayload, MaterialFlagSnapshot};
 use crate::engine::eligibility::EligibilityEngine;
 use crate::engine::urgency::UrgencyEngine;
 use crate::repository::action_log_repo::ActionLogRepository;
@@ -378,6 +379,9 @@ impl MaterialApi {
         self.validator
             .validate_lock_materials(&material_ids, mode)?;
 
+        // 撤销载荷：操作前快照
+        let before = self.material_flag_snapshots(&material_ids)?;
+
         // 执行状态更新
         let mut success_count = 0;
         let mut fail_count = 0;
@@ -422,6 +426,7 @@ impl MaterialApi {
                 "material_ids": material_ids,
                 "lock_flag": lock_flag,
                 "reason": reason,
+                "undo": self.material_flags_inverse(before, mode == ValidationMode::Strict)?,
             })),
             impact_summary_json: Some(serde_json::json!({
                 "success_count": success_count,
@@ -487,8 +492,12 @@ impl MaterialApi {
         // 使用validator进行校验（红线2）
         let violations = self.validator.validate_force_release(&material_ids, mode)?;
 
+        // 撤销载荷：操作前快照
+        let before = self.material_flag_snapshots(&material_ids)?;
+
         // 执行强制放行
         let mut success_count = 0;
+        let mut failed_material_ids = Vec::new();
         for material_id in &material_ids {
             if let Some(mut state) = self
                 .material_state_repo
@@ -504,6 +513,8 @@ impl MaterialApi {
                     .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
 
                 success_count += 1;
+            } else {
+                failed_material_ids.push(material_id.clone());
             }
         }
 
@@ -519,10 +530,12 @@ impl MaterialApi {
                 "immature_count": violations.len(),
                 "violations": violations,
                 "reason": reason,
+                "undo": self.material_flags_inverse(before, mode == ValidationMode::Strict)?,
             })),
             impact_summary_json: Some(serde_json::json!({
                 "success_count": success_count,
                 "fail_count": material_ids.len() - success_count,
+                "failed_material_ids": failed_material_ids,
             })),
             machine_code: None,
             date_range_start: None,
@@ -548,6 +561,7 @@ impl MaterialApi {
             details: Some(serde_json::json!({
                 "immature_count": violations.len(),
                 "violations": violations,
+                "failed_material_ids": failed_material_ids,
             })),
         })
     }
@@ -653,6 +667,9 @@ impl MaterialApi {
             return Err(ApiError::InvalidInput("操作原因不能为空".to_string()));
         }
 
+        // 撤销载荷：操作前快照
+        let before = self.material_flag_snapshots(&material_ids)?;
+
         // 执行状态更新
         let mut success_count = 0;
         for material_id in &material_ids {
@@ -683,6 +700,7 @@ impl MaterialApi {
                 "material_ids": material_ids,
                 "manual_urgent_flag": manual_urgent_flag,
                 "reason": reason,
+                "undo": self.material_flags_inverse(before, true)?,
             })),
             impact_summary_json: Some(serde_json::json!({
                 "success_count": success_count,
@@ -712,6 +730,107 @@ impl MaterialApi {
         })
     }
 
+    // ==========================================
+    // 撤销/重做支持
+    // ==========================================
+
+    /// 材料人工标志快照（不存在的材料跳过）
+    pub(crate) fn material_flag_snapshots(
+        &self,
+        material_ids: &[String],
+    ) -> ApiResult<Vec<MaterialFlagSnapshot>> {
+        let mut snapshots = Vec::with_capacity(material_ids.len());
+        for material_id in material_ids {
+            if let Some(state) = self
+                .material_state_repo
+                .find_by_id(material_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            {
+                snapshots.push(MaterialFlagSnapshot {
+                    material_id: state.material_id,
+                    sched_state: state.sched_state,
+                    lock_flag: state.lock_flag,
+                    force_release_flag: state.force_release_flag,
+                    manual_urgent_flag: state.manual_urgent_flag,
+                });
+            }
+        }
+        Ok(snapshots)
+    }
+
+    /// 由操作前快照与当前状态构造逆操作载荷
+    fn material_flags_inverse(
+        &self,
+        before: Vec<MaterialFlagSnapshot>,
+        strict: bool,
+    ) -> ApiResult<serde_json::Value> {
+        let ids: Vec<String> = before.iter().map(|s| s.material_id.clone()).collect();
+        let expected = self.material_flag_snapshots(&ids)?;
+        Ok(InversePayload::MaterialFlags {
+            restore: before,
+            expected,
+            strict,
+        }
+        .to_json())
+    }
+
+    /// 按快照恢复材料人工标志（撤销/重做）
+    ///
+    /// # 说明
+    /// - 重新锁定的材料走锁定校验（红线1），重新强制放行的材料走放行校验（红线2）
+    /// - 校验模式沿用原操作（strict=true 为 Strict）
+    /// - 不写操作日志（由调用方记录 UNDO/REDO 日志）
+    pub(crate) fn apply_material_flag_snapshots(
+        &self,
+        restore: &[MaterialFlagSnapshot],
+        strict: bool,
+    ) -> ApiResult<()> {
+        let mode = if strict {
+            ValidationMode::Strict
+        } else {
+            ValidationMode::AutoFix
+        };
+
+        let mut states = Vec::with_capacity(restore.len());
+        let mut to_lock = Vec::new();
+        let mut to_release = Vec::new();
+        for snapshot in restore {
+            let state = self
+                .material_state_repo
+                .find_by_id(&snapshot.material_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                .ok_or_else(|| {
+                    ApiError::NotFound(format!("材料状态不存在: {}", snapshot.material_id))
+                })?;
+            if snapshot.lock_flag && !state.lock_flag {
+                to_lock.push(snapshot.material_id.clone());
+            }
+            if snapshot.force_release_flag && !state.force_release_flag {
+                to_release.push(snapshot.material_id.clone());
+            }
+            states.push(state);
+        }
+
+        if !to_lock.is_empty() {
+            self.validator.validate_lock_materials(&to_lock, mode)?;
+        }
+        if !to_release.is_empty() {
+            self.validator.validate_force_release(&to_release, mode)?;
+        }
+
+        for (mut state, snapshot) in states.into_iter().zip(restore) {
+            state.sched_state = snapshot.sched_state;
+            state.lock_flag = snapshot.lock_flag;
+            state.force_release_flag = snapshot.force_release_flag;
+            state.manual_urgent_flag = snapshot.manual_urgent_flag;
+            self.material_state_repo
+                .batch_insert_material_state(vec![state])
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
     /// 查询指定紧急等级的材料
     ///
     /// # 参数
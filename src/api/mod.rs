@@ -4,29 +4,56 @@
 // 职责: 提供业务 API 接口,供 Tauri 命令调用
 // ==========================================
 
+pub mod activation_validator;
+pub mod bottleneck_optimizer_api;
+pub mod capacity_reservation_api;
 pub mod config_api;
 pub mod dashboard_api;
 pub mod error;
 pub mod import_api;
+pub mod intervention_expiry_api;
 pub mod machine_config_api;
 pub mod material_api;
 pub mod path_rule_api;
 pub mod plan_api;
+pub mod production_api;
 pub mod rhythm_api;
 pub mod roller_api;
+pub mod scenario_api;
+pub mod undo_api;
 pub mod validator;
+pub mod version_approval_api;
+pub mod version_lineage_api;
 
 // 重导出核心类型
+pub use activation_validator::ActivationRedLineValidator;
+pub use bottleneck_optimizer_api::{
+    ApplyBottleneckRecommendationsResponse, BottleneckOptimizerApi,
+    BottleneckRecommendationResponse, SkippedRecommendation,
+};
+pub use capacity_reservation_api::{
+    CapacityReservationApi, CreateCapacityReservationRequest, ReservationUtilizationReport,
+};
 pub use config_api::ConfigApi;
 pub use dashboard_api::DashboardApi;
 pub use error::{ApiError, ApiResult, ValidationViolation};
-pub use import_api::{ImportApi, ImportApiResponse};
+pub use import_api::{ContractImportApiResponse, ImportApi, ImportApiResponse};
+pub use intervention_expiry_api::{
+    ExpirySweepResult, InterventionExpiryApi, InterventionExpiryDto, UpcomingExpirationsResponse,
+};
 pub use machine_config_api::MachineConfigApi;
 pub use material_api::MaterialApi;
 pub use path_rule_api::PathRuleApi;
 pub use plan_api::PlanApi;
+pub use production_api::{PlanAdherenceReport, ProductionActualsImportResponse, ProductionApi};
 pub use rhythm_api::RhythmApi;
 pub use roller_api::RollerApi;
+pub use scenario_api::{
+    BreakdownSimulationResponse, PromoteScenarioResponse, ScenarioApi, ScenarioEvaluationResponse,
+};
+pub use undo_api::{UndoApi, UndoEntryDto, UndoRedoResponse, UndoStatusResponse};
 pub use validator::{ManualOperationValidator, ValidationMode};
+pub use version_approval_api::{VersionApprovalApi, VersionReviewHistory};
+pub use version_lineage_api::VersionLineageApi;
 
 // TODO: 添加请求日志记录
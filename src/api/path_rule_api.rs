@@ -7,5 +7,8 @@
 // ==========================================
 
 mod core;
+mod override_history;
+mod resequence;
 
 pub use core::*;
+pub use resequence::{ResequenceResultDto, ResequenceViolationDto};
@@ -6,6 +6,8 @@
 // 2) 查询待人工确认的路径违规材料
 // 3) 人工确认突破（写 material_state.user_confirmed* + action_log）
 // 4) 查询/重置换辊周期锚点（roller_campaign.path_anchor_*）
+// 5) 机组日内顺序调整（见 `path_rule_api/resequence.rs`）
+// 6) 确认/拒绝的撤销快照（见 `path_rule_api/override_history.rs`）
 // ==========================================
 
 use std::sync::{Arc, Mutex};
@@ -83,20 +85,57 @@ pub struct BatchConfirmResultDto {
     pub failed_material_ids: Vec<String>,
 }
 
+// ==========================================
+// 路径规则配置加载（路径规则 API 与版本合并共用）
+// ==========================================
+
+/// 从全局配置读取路径规则参数（缺失/非法时回落默认值）
+pub fn load_path_rule_config(config_manager: &ConfigManager) -> PathRuleConfig {
+    PathRuleConfig {
+        enabled: PathRuleApi::parse_bool(
+            config_manager
+                .get_global_config_value("path_rule_enabled")
+                .ok()
+                .flatten(),
+            true,
+        ),
+        width_tolerance_mm: PathRuleApi::parse_f64(
+            config_manager
+                .get_global_config_value("path_width_tolerance_mm")
+                .ok()
+                .flatten(),
+            50.0,
+        ),
+        thickness_tolerance_mm: PathRuleApi::parse_f64(
+            config_manager
+                .get_global_config_value("path_thickness_tolerance_mm")
+                .ok()
+                .flatten(),
+            1.0,
+        ),
+        override_allowed_urgency_levels: PathRuleApi::parse_urgent_levels(
+            config_manager
+                .get_global_config_value("path_override_allowed_urgency_levels")
+                .ok()
+                .flatten(),
+        ),
+    }
+}
+
 // ==========================================
 // PathRuleApi
 // ==========================================
 
 pub struct PathRuleApi {
-    conn: Arc<Mutex<Connection>>,
-    config_manager: Arc<ConfigManager>,
-    plan_item_repo: Arc<PlanItemRepository>,
-    material_master_repo: Arc<MaterialMasterRepository>,
-    material_state_repo: Arc<MaterialStateRepository>,
-    roller_campaign_repo: Arc<RollerCampaignRepository>,
-    roll_campaign_plan_repo: Arc<RollCampaignPlanRepository>,
-    action_log_repo: Arc<ActionLogRepository>,
-    path_override_pending_repo: Arc<PathOverridePendingRepository>,
+    pub(super) conn: Arc<Mutex<Connection>>,
+    pub(super) config_manager: Arc<ConfigManager>,
+    pub(super) plan_item_repo: Arc<PlanItemRepository>,
+    pub(super) material_master_repo: Arc<MaterialMasterRepository>,
+    pub(super) material_state_repo: Arc<MaterialStateRepository>,
+    pub(super) roller_campaign_repo: Arc<RollerCampaignRepository>,
+    pub(super) roll_campaign_plan_repo: Arc<RollCampaignPlanRepository>,
+    pub(super) action_log_repo: Arc<ActionLogRepository>,
+    pub(super) path_override_pending_repo: Arc<PathOverridePendingRepository>,
 }
 
 impl PathRuleApi {
@@ -133,14 +172,14 @@ impl PathRuleApi {
         }
     }
 
-    fn parse_f64(raw: Option<String>, default: f64) -> f64 {
+    pub(super) fn parse_f64(raw: Option<String>, default: f64) -> f64 {
         raw.as_deref()
             .and_then(|s| s.trim().parse::<f64>().ok())
             .filter(|v| v.is_finite())
             .unwrap_or(default)
     }
 
-    fn parse_i32(raw: Option<String>, default: i32) -> i32 {
+    pub(super) fn parse_i32(raw: Option<String>, default: i32) -> i32 {
         raw.as_deref()
             .and_then(|s| s.trim().parse::<i32>().ok())
             .unwrap_or(default)
@@ -170,36 +209,8 @@ impl PathRuleApi {
         }
     }
 
-    fn load_path_rule_config(&self) -> PathRuleConfig {
-        PathRuleConfig {
-            enabled: Self::parse_bool(
-                self.config_manager
-                    .get_global_config_value("path_rule_enabled")
-                    .ok()
-                    .flatten(),
-                true,
-            ),
-            width_tolerance_mm: Self::parse_f64(
-                self.config_manager
-                    .get_global_config_value("path_width_tolerance_mm")
-                    .ok()
-                    .flatten(),
-                50.0,
-            ),
-            thickness_tolerance_mm: Self::parse_f64(
-                self.config_manager
-                    .get_global_config_value("path_thickness_tolerance_mm")
-                    .ok()
-                    .flatten(),
-                1.0,
-            ),
-            override_allowed_urgency_levels: Self::parse_urgent_levels(
-                self.config_manager
-                    .get_global_config_value("path_override_allowed_urgency_levels")
-                    .ok()
-                    .flatten(),
-            ),
-        }
+    pub(super) fn load_path_rule_config(&self) -> PathRuleConfig {
+        load_path_rule_config(&self.config_manager)
     }
 
     fn reject_path_override_single_internal(
@@ -521,6 +532,9 @@ impl PathRuleApi {
         let engine = PathRuleEngine::new(config);
         let check = engine.check(w, t, state.urgent_level, anchor.as_ref(), false);
 
+        // 撤销载荷：操作前快照
+        let before = self.path_override_snapshots(&[material_id.to_string()])?;
+
         // 先写入 material_state.user_confirmed*
         self.material_state_repo
             .update_user_confirmation(material_id, confirmed_by, reason)
@@ -580,6 +594,7 @@ impl PathRuleApi {
                 "thickness_delta_mm": thickness_delta_mm,
                 "urgent_level": urgent_level,
                 "confirm_reason": reason,
+                "undo": self.path_override_inverse(before, &[])?,
             })),
             impact_summary_json: None,
             machine_code: Some(machine_code.clone()),
@@ -612,6 +627,9 @@ impl PathRuleApi {
             return Err(ApiError::InvalidInput("确认原因不能为空".to_string()));
         }
 
+        // 撤销载荷：操作前快照
+        let before = self.path_override_snapshots(material_ids)?;
+
         let mut failed = Vec::new();
         let mut success_count = 0i32;
 
@@ -639,6 +657,7 @@ impl PathRuleApi {
                 "fail_count": failed_material_ids.len(),
                 "failed_material_ids": failed_material_ids,
                 "confirm_reason": reason,
+                "undo": self.path_override_inverse(before, &failed)?,
             })),
             impact_summary_json: None,
             machine_code: None,
@@ -700,6 +719,9 @@ impl PathRuleApi {
             });
         }
 
+        // 撤销载荷：操作前快照
+        let before = self.path_override_snapshots(&ids)?;
+
         let mut failed = Vec::new();
         let mut success_count = 0i32;
         for id in &ids {
@@ -730,6 +752,7 @@ impl PathRuleApi {
                 "failed_material_ids": failed_material_ids,
                 "material_ids_sample": sample_ids,
                 "confirm_reason": reason,
+                "undo": self.path_override_inverse(before, &failed)?,
             })),
             impact_summary_json: None,
             machine_code: None,
@@ -769,6 +792,9 @@ impl PathRuleApi {
             return Err(ApiError::InvalidInput("拒绝原因不能为空".to_string()));
         }
 
+        // 撤销载荷：操作前快照
+        let before = self.path_override_snapshots(&[material_id.to_string()])?;
+
         let (machine_code, reject_cycle_no, base_sched_state) = self
             .reject_path_override_single_internal(version_id, material_id, rejected_by, reason)?;
 
@@ -786,7 +812,8 @@ impl PathRuleApi {
                 "policy": {
                     "postpone_min_roll_cycle": 1,
                     "boost_urgent_level_by": 1,
-                }
+                },
+                "undo": self.path_override_inverse(before, &[])?,
             })),
             impact_summary_json: None,
             machine_code: Some(machine_code),
@@ -819,6 +846,9 @@ impl PathRuleApi {
             return Err(ApiError::InvalidInput("拒绝原因不能为空".to_string()));
         }
 
+        // 撤销载荷：操作前快照
+        let before = self.path_override_snapshots(material_ids)?;
+
         let mut failed = Vec::new();
         let mut success_count = 0i32;
         let mut sample: Vec<serde_json::Value> = Vec::new();
@@ -857,7 +887,8 @@ impl PathRuleApi {
                 "policy": {
                     "postpone_min_roll_cycle": 1,
                     "boost_urgent_level_by": 1,
-                }
+                },
+                "undo": self.path_override_inverse(before, &failed)?,
             })),
             impact_summary_json: None,
             machine_code: None,
@@ -919,6 +950,9 @@ impl PathRuleApi {
             });
         }
 
+        // 撤销载荷：操作前快照
+        let before = self.path_override_snapshots(&ids)?;
+
         let mut failed = Vec::new();
         let mut success_count = 0i32;
         let mut sample: Vec<serde_json::Value> = Vec::new();
@@ -960,7 +994,8 @@ impl PathRuleApi {
                 "policy": {
                     "postpone_min_roll_cycle": 1,
                     "boost_urgent_level_by": 1,
-                }
+                },
+                "undo": self.path_override_inverse(before, &failed)?,
             })),
             impact_summary_json: None,
             machine_code: None,
@@ -0,0 +1,131 @@
+// ==========================================
+// 热轧精整排产系统 - 路径突破确认/拒绝 撤销支持
+// ==========================================
+// 职责:
+// 1) 采集 material_state.user_confirmed* / path_override_rejected* 快照
+// 2) 构造确认/拒绝操作的逆操作载荷（写入 action_log.payload_json["undo"]）
+// 3) 按快照恢复确认/拒绝标记（撤销/重做）
+// ==========================================
+
+use std::collections::HashMap;
+
+use super::PathRuleApi;
+use crate::api::error::{ApiError, ApiResult};
+use crate::domain::undo::{InversePayload, PathOverrideSnapshot};
+use crate::repository::material_repo::PathOverrideRejectionSummary;
+
+impl PathRuleApi {
+    /// 路径突破确认/拒绝快照（不存在的材料跳过）
+    pub(crate) fn path_override_snapshots(
+        &self,
+        material_ids: &[String],
+    ) -> ApiResult<Vec<PathOverrideSnapshot>> {
+        let rejections: HashMap<String, PathOverrideRejectionSummary> = self
+            .material_state_repo
+            .find_path_override_rejections(material_ids)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|r| (r.material_id.clone(), r))
+            .collect();
+
+        let mut snapshots = Vec::with_capacity(material_ids.len());
+        for material_id in material_ids {
+            let Some(state) = self
+                .material_state_repo
+                .find_by_id(material_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            else {
+                continue;
+            };
+            let rejection = rejections.get(material_id);
+            snapshots.push(PathOverrideSnapshot {
+                material_id: state.material_id,
+                user_confirmed: state.user_confirmed,
+                user_confirmed_at: state.user_confirmed_at,
+                user_confirmed_by: state.user_confirmed_by,
+                user_confirmed_reason: state.user_confirmed_reason,
+                rejected: rejection.is_some(),
+                reject_cycle_no: rejection.and_then(|r| r.reject_cycle_no),
+                reject_base_sched_state: rejection.and_then(|r| r.reject_base_sched_state.clone()),
+            });
+        }
+        Ok(snapshots)
+    }
+
+    /// 由操作前快照与当前状态构造逆操作载荷（仅保留处理成功的材料）
+    pub(super) fn path_override_inverse(
+        &self,
+        mut before: Vec<PathOverrideSnapshot>,
+        failed_material_ids: &[String],
+    ) -> ApiResult<serde_json::Value> {
+        before.retain(|s| !failed_material_ids.contains(&s.material_id));
+        let ids: Vec<String> = before.iter().map(|s| s.material_id.clone()).collect();
+        let expected = self.path_override_snapshots(&ids)?;
+        Ok(InversePayload::PathOverride {
+            restore: before,
+            expected,
+        }
+        .to_json())
+    }
+
+    /// 按快照恢复路径突破确认/拒绝标记（撤销/重做）
+    ///
+    /// # 说明
+    /// - 校验口径同确认/拒绝：材料状态必须存在；恢复为已确认时确认人与原因必填
+    /// - 不写操作日志（由调用方记录 UNDO/REDO 日志）
+    pub(crate) fn apply_path_override_snapshots(
+        &self,
+        restore: &[PathOverrideSnapshot],
+    ) -> ApiResult<()> {
+        let mut states = Vec::with_capacity(restore.len());
+        let mut rejections = Vec::new();
+        for snapshot in restore {
+            let mut state = self
+                .material_state_repo
+                .find_by_id(&snapshot.material_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                .ok_or_else(|| {
+                    ApiError::NotFound(format!("材料状态不存在: {}", snapshot.material_id))
+                })?;
+
+            if snapshot.user_confirmed {
+                let by_missing = snapshot
+                    .user_confirmed_by
+                    .as_deref()
+                    .unwrap_or("")
+                    .trim()
+                    .is_empty();
+                let reason_missing = snapshot
+                    .user_confirmed_reason
+                    .as_deref()
+                    .unwrap_or("")
+                    .trim()
+                    .is_empty();
+                if by_missing || reason_missing {
+                    return Err(ApiError::InvalidInput(format!(
+                        "材料{}的确认快照缺少确认人或原因",
+                        snapshot.material_id
+                    )));
+                }
+            }
+
+            state.user_confirmed = snapshot.user_confirmed;
+            state.user_confirmed_at = snapshot.user_confirmed_at;
+            state.user_confirmed_by = snapshot.user_confirmed_by.clone();
+            state.user_confirmed_reason = snapshot.user_confirmed_reason.clone();
+            states.push(state);
+
+            if snapshot.rejected {
+                rejections.push(PathOverrideRejectionSummary {
+                    material_id: snapshot.material_id.clone(),
+                    reject_cycle_no: snapshot.reject_cycle_no,
+                    reject_base_sched_state: snapshot.reject_base_sched_state.clone(),
+                });
+            }
+        }
+
+        self.material_state_repo
+            .restore_path_override_flags(&states, &rejections)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
@@ -0,0 +1,624 @@
+// ==========================================
+// 热轧精整排产系统 - 机组日内顺序调整（甘特图拖拽）
+// ==========================================
+// 职责:
+// 1) 接收某机组某日的新材料顺序（必须是当日明细的一个排列）
+// 2) 自当日初始锚点起逐块执行 PathRuleEngine 门控，锚点随已排材料推进
+// 3) 当日最后一块变化时，按新的最后一块复核下一排产日第一块
+// 4) OVERRIDE_REQUIRED 生成 path_override_pending 待确认；HARD_VIOLATION 拒绝调整
+// 5) 重写 seq_no、刷新待确认、记录 LocalAdjust 日志（新旧顺序），同一事务落库
+// ==========================================
+// 初始锚点口径（与重算的 RollCycle 锚点一致，换辊即重置）:
+// - 同一换辊周期内：取本机组上一排产日的最后一块
+// - 周期内无前序排产日：取 roller_campaign 持久化锚点（锚点材料未排在当日及之后）
+// - 否则按 AnchorResolver 优先级解析（锁定区最后一块/人工确认队列/SeedS2）
+// ==========================================
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::PathRuleApi;
+use crate::api::error::{ApiError, ApiResult};
+use crate::domain::action_log::{ActionLog, ActionType};
+use crate::domain::material::{MaterialMaster, MaterialState};
+use crate::domain::plan::PlanItem;
+use crate::domain::types::{PathRuleStatus, SchedState};
+use crate::engine::{Anchor, AnchorResolver, MaterialSummary, PathRuleEngine, SeedS2Config};
+use crate::repository::path_override_pending_repo::PathOverridePendingRecord;
+use crate::repository::plan_repo::PlanVersionRepository;
+
+/// 上一排产日最后一块作为初始锚点时的来源标记
+const ANCHOR_SOURCE_PREVIOUS_DAY_LAST: &str = "PREVIOUS_DAY_LAST";
+
+/// 换辊周期持久化锚点作为初始锚点时的来源标记
+const ANCHOR_SOURCE_ROLL_CYCLE: &str = "ROLL_CYCLE";
+
+// ==========================================
+// DTO
+// ==========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResequenceViolationDto {
+    pub material_id: String,
+    pub plan_date: String, // 当日或下一排产日（复核第一块）
+    pub seq_no: i32,
+    pub status: String, // OVERRIDE_REQUIRED / HARD_VIOLATION
+    pub violation_type: String,
+    pub urgent_level: String,
+    pub width_mm: f64,
+    pub thickness_mm: f64,
+    pub anchor_material_id: Option<String>,
+    pub anchor_width_mm: f64,
+    pub anchor_thickness_mm: f64,
+    pub width_delta_mm: f64,
+    pub thickness_delta_mm: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResequenceResultDto {
+    pub version_id: String,
+    pub machine_code: String,
+    pub plan_date: String,
+    pub old_order: Vec<String>,
+    pub new_order: Vec<String>,
+    pub moved_materials: Vec<String>,
+    pub anchor_source: String,
+    pub anchor_material_id: Option<String>,
+    pub anchor_width_mm: Option<f64>,
+    pub anchor_thickness_mm: Option<f64>,
+    pub violations: Vec<ResequenceViolationDto>,
+    pub override_required_count: usize,
+    pub hard_violation_count: usize,
+    pub applied: bool,
+    pub message: String,
+}
+
+/// 校验结果（内部使用）
+struct ResequencePlan {
+    day_items: Vec<PlanItem>,
+    result: ResequenceResultDto,
+    refresh_material_ids: Vec<String>, // 需按新锚点刷新待确认的材料（当日 + 复核的下一日第一块）
+    pending_records: Vec<PathOverridePendingRecord>,
+    revalidated_next_first: Option<String>,
+}
+
+impl PathRuleApi {
+    /// 预览日内顺序调整（仅校验路径规则，不落库）
+    pub fn preview_resequence_machine_day(
+        &self,
+        version_id: &str,
+        machine_code: &str,
+        plan_date: NaiveDate,
+        new_order: &[String],
+    ) -> ApiResult<ResequenceResultDto> {
+        let plan = self.build_resequence(version_id, machine_code, plan_date, new_order)?;
+        Ok(plan.result)
+    }
+
+    /// 执行日内顺序调整
+    ///
+    /// 存在 HARD_VIOLATION 时不落库（applied=false）；OVERRIDE_REQUIRED 照常调整并生成待确认。
+    pub fn resequence_machine_day(
+        &self,
+        version_id: &str,
+        machine_code: &str,
+        plan_date: NaiveDate,
+        new_order: &[String],
+        operator: &str,
+        reason: Option<&str>,
+    ) -> ApiResult<ResequenceResultDto> {
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+
+        let ResequencePlan {
+            day_items,
+            mut result,
+            refresh_material_ids,
+            pending_records,
+            revalidated_next_first,
+        } = self.build_resequence(version_id, machine_code, plan_date, new_order)?;
+
+        if result.hard_violation_count > 0 || result.moved_materials.is_empty() {
+            return Ok(result);
+        }
+
+        // 1) 重写 seq_no
+        let position: HashMap<&str, i32> = result
+            .new_order
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx as i32 + 1))
+            .collect();
+        let updated: Vec<PlanItem> = day_items
+            .into_iter()
+            .map(|mut item| {
+                item.seq_no = position[item.material_id.as_str()];
+                item
+            })
+            .collect();
+
+        // 2) 审计日志
+        let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+        let detail = match reason {
+            Some(r) => format!(
+                "日内顺序调整: {} {} 调整{}块 | {}",
+                machine_code,
+                plan_date,
+                result.moved_materials.len(),
+                r
+            ),
+            None => format!(
+                "日内顺序调整: {} {} 调整{}块",
+                machine_code,
+                plan_date,
+                result.moved_materials.len()
+            ),
+        };
+        let log = ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: Some(version_id.to_string()),
+            action_type: ActionType::LocalAdjust.as_str().to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "operation": "RESEQUENCE",
+                "machine_code": machine_code,
+                "plan_date": result.plan_date,
+                "old_order": result.old_order,
+                "new_order": result.new_order,
+                "moved_materials": result.moved_materials,
+                "anchor": {
+                    "source": result.anchor_source,
+                    "material_id": result.anchor_material_id,
+                    "width_mm": result.anchor_width_mm,
+                    "thickness_mm": result.anchor_thickness_mm,
+                },
+                "override_required": result
+                    .violations
+                    .iter()
+                    .map(|v| &v.material_id)
+                    .collect::<Vec<_>>(),
+                "revalidated_next_first": revalidated_next_first,
+                "reason": reason,
+            })),
+            impact_summary_json: None,
+            machine_code: Some(machine_code.to_string()),
+            date_range_start: Some(plan_date),
+            date_range_end: Some(plan_date),
+            detail: Some(detail),
+        };
+
+        // 3) 明细 seq_no、待确认刷新与日志同一事务写入
+        self.path_override_pending_repo
+            .ensure_schema()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        self.plan_item_repo
+            .apply_resequence(
+                version_id,
+                machine_code,
+                &updated,
+                &refresh_material_ids,
+                &pending_records,
+                &log,
+            )
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        result.applied = true;
+        result.message = if result.override_required_count > 0 {
+            format!(
+                "顺序已调整: {}块位置变化, {}块待人工确认路径突破",
+                result.moved_materials.len(),
+                result.override_required_count
+            )
+        } else {
+            format!("顺序已调整: {}块位置变化", result.moved_materials.len())
+        };
+        Ok(result)
+    }
+
+    fn build_resequence(
+        &self,
+        version_id: &str,
+        machine_code: &str,
+        plan_date: NaiveDate,
+        new_order: &[String],
+    ) -> ApiResult<ResequencePlan> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        if machine_code.trim().is_empty() {
+            return Err(ApiError::InvalidInput("机组代码不能为空".to_string()));
+        }
+        if new_order.is_empty() {
+            return Err(ApiError::InvalidInput("新顺序不能为空".to_string()));
+        }
+
+        let version = PlanVersionRepository::new(self.conn.clone())
+            .find_by_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        if !version.is_editable() {
+            return Err(ApiError::BusinessRuleViolation(
+                "只能修改草稿、已驳回或激活状态的版本".to_string(),
+            ));
+        }
+        if version.frozen_from_date.is_some_and(|f| plan_date < f) {
+            return Err(ApiError::FrozenZoneProtection(format!(
+                "{} 位于冻结区，不可调整顺序",
+                plan_date
+            )));
+        }
+
+        // 1) 当日明细 + 同一换辊周期内上一排产日最后一块 / 下一排产日第一块
+        let campaigns = self
+            .roller_campaign_repo
+            .find_by_machine(version_id, machine_code, i32::MAX)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let campaign = campaigns.iter().find(|c| c.start_date <= plan_date);
+        let machine_items = self
+            .plan_item_repo
+            .find_by_machine(version_id, machine_code)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let previous_last = machine_items
+            .iter()
+            .filter(|i| i.plan_date < plan_date)
+            .filter(|i| campaign.is_none_or(|c| i.plan_date >= c.start_date))
+            .max_by_key(|i| (i.plan_date, i.seq_no))
+            .cloned();
+        let next_first = machine_items
+            .iter()
+            .filter(|i| i.plan_date > plan_date)
+            .min_by_key(|i| (i.plan_date, i.seq_no))
+            .filter(|next| {
+                !campaigns
+                    .iter()
+                    .any(|c| c.start_date > plan_date && c.start_date <= next.plan_date)
+            })
+            .cloned();
+        let scheduled_from_day: HashSet<String> = machine_items
+            .iter()
+            .filter(|i| i.plan_date >= plan_date)
+            .map(|i| i.material_id.clone())
+            .collect();
+        let mut day_items: Vec<PlanItem> = machine_items
+            .into_iter()
+            .filter(|i| i.plan_date == plan_date)
+            .collect();
+        day_items.sort_by_key(|i| i.seq_no);
+        if day_items.is_empty() {
+            return Err(ApiError::NotFound(format!(
+                "{} {} 无排产明细",
+                machine_code, plan_date
+            )));
+        }
+
+        // 2) 新顺序必须是当日明细的一个排列
+        let old_order: Vec<String> = day_items.iter().map(|i| i.material_id.clone()).collect();
+        let day_ids: HashSet<&str> = old_order.iter().map(String::as_str).collect();
+        let mut seen: HashSet<&str> = HashSet::new();
+        for id in new_order {
+            if !day_ids.contains(id.as_str()) {
+                return Err(ApiError::InvalidInput(format!(
+                    "材料{}不在 {} {} 的排产明细中",
+                    id, machine_code, plan_date
+                )));
+            }
+            if !seen.insert(id.as_str()) {
+                return Err(ApiError::InvalidInput(format!("材料{}重复", id)));
+            }
+        }
+        if seen.len() != day_ids.len() {
+            return Err(ApiError::InvalidInput(format!(
+                "新顺序缺少材料: 当日{}块, 提交{}块",
+                day_ids.len(),
+                seen.len()
+            )));
+        }
+
+        // 冻结材料必须保持原位
+        for (idx, item) in day_items.iter().enumerate() {
+            if item.locked_in_plan && new_order[idx] != item.material_id {
+                return Err(ApiError::FrozenZoneProtection(format!(
+                    "冻结区材料{}不可调整顺序",
+                    item.material_id
+                )));
+            }
+        }
+
+        // 3) 材料尺寸与状态
+        let mut lookup_ids = old_order.clone();
+        lookup_ids.extend(
+            previous_last
+                .iter()
+                .chain(next_first.iter())
+                .map(|i| i.material_id.clone()),
+        );
+        let masters: HashMap<String, MaterialMaster> = self
+            .material_master_repo
+            .find_by_ids(&lookup_ids)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|m| (m.material_id.clone(), m))
+            .collect();
+        let mut states: HashMap<String, MaterialState> = HashMap::new();
+        for id in old_order
+            .iter()
+            .chain(next_first.iter().map(|i| &i.material_id))
+        {
+            if let Some(state) = self
+                .material_state_repo
+                .find_by_id(id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            {
+                states.insert(id.clone(), state);
+            }
+        }
+        let dims = |id: &str| -> Option<(f64, f64)> {
+            let m = masters.get(id)?;
+            let w = m.width_mm.unwrap_or(0.0);
+            let t = m.thickness_mm.unwrap_or(0.0);
+            (w.is_finite() && t.is_finite() && w > 0.0 && t > 0.0).then_some((w, t))
+        };
+
+        // 4) 初始锚点
+        let config = self.load_path_rule_config();
+        let rule_enabled = config.enabled;
+        let engine = PathRuleEngine::new(config);
+
+        // 周期锚点若已落在当日及之后（重算推进后的锚点），不能作为当日初始锚点
+        let campaign_anchor = campaign
+            .filter(|c| c.has_valid_anchor())
+            .filter(|c| {
+                c.path_anchor_material_id
+                    .as_ref()
+                    .is_none_or(|id| !scheduled_from_day.contains(id))
+            })
+            .and_then(|c| {
+                let w = c.path_anchor_width_mm.unwrap_or(0.0);
+                let t = c.path_anchor_thickness_mm.unwrap_or(0.0);
+                (w.is_finite() && t.is_finite() && w > 0.0 && t > 0.0)
+                    .then(|| (c.path_anchor_material_id.clone(), (w, t)))
+            });
+        let previous_anchor = previous_last
+            .as_ref()
+            .and_then(|p| dims(&p.material_id).map(|d| (Some(p.material_id.clone()), d)));
+
+        let (mut anchor, mut anchor_material_id, anchor_source) = match previous_anchor
+            .map(|a| (a, ANCHOR_SOURCE_PREVIOUS_DAY_LAST))
+            .or(campaign_anchor.map(|a| (a, ANCHOR_SOURCE_ROLL_CYCLE)))
+        {
+            Some(((id, (w, t)), source)) => (
+                Some(Anchor {
+                    width_mm: w,
+                    thickness_mm: t,
+                }),
+                id,
+                source.to_string(),
+            ),
+            None => {
+                let summaries: Vec<(MaterialSummary, Option<&MaterialState>)> = day_items
+                    .iter()
+                    .filter_map(|item| {
+                        let (w, t) = dims(&item.material_id)?;
+                        let state = states.get(&item.material_id);
+                        Some((
+                            MaterialSummary {
+                                material_id: item.material_id.clone(),
+                                width_mm: w,
+                                thickness_mm: t,
+                                seq_no: item.seq_no,
+                                user_confirmed_at: state
+                                    .filter(|s| s.user_confirmed)
+                                    .and_then(|s| s.user_confirmed_at)
+                                    .map(|ts| ts.to_rfc3339()),
+                            },
+                            state,
+                        ))
+                    })
+                    .collect();
+                let locked: Vec<MaterialSummary> = summaries
+                    .iter()
+                    .filter(|(_, s)| s.is_some_and(|s| s.sched_state == SchedState::Locked))
+                    .map(|(m, _)| m.clone())
+                    .collect();
+                let user_confirmed: Vec<MaterialSummary> = summaries
+                    .iter()
+                    .filter(|(m, _)| m.user_confirmed_at.is_some())
+                    .map(|(m, _)| m.clone())
+                    .collect();
+                let candidates: Vec<MaterialSummary> = summaries
+                    .into_iter()
+                    .map(|(m, _)| MaterialSummary { seq_no: 0, ..m })
+                    .collect();
+
+                let resolved = AnchorResolver::new(self.load_seed_s2_config()).resolve(
+                    &[],
+                    &locked,
+                    &user_confirmed,
+                    &candidates,
+                );
+                (
+                    resolved.anchor,
+                    resolved.material_id,
+                    resolved.source.to_string(),
+                )
+            }
+        };
+        let initial_anchor = anchor;
+        let initial_anchor_material_id = anchor_material_id.clone();
+
+        // 5) 按新顺序逐块门控，锚点随已排材料推进（冻结/锁定材料不做门控）
+        let mut violations = Vec::new();
+        let mut pending_records = Vec::new();
+        let mut gate = |item: &PlanItem,
+                        seq_no: i32,
+                        (w, t): (f64, f64),
+                        anchor: Option<&Anchor>,
+                        anchor_material_id: Option<&String>| {
+            let state = states.get(&item.material_id);
+            let protected =
+                item.locked_in_plan || state.is_some_and(|s| s.sched_state == SchedState::Locked);
+            if !rule_enabled || protected {
+                return;
+            }
+            let Some(state) = state else {
+                return;
+            };
+            let check = engine.check(w, t, state.urgent_level, anchor, state.user_confirmed);
+            if check.status == PathRuleStatus::Ok {
+                return;
+            }
+            let violation_type = check
+                .violation_type
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "UNKNOWN".to_string());
+            let (anchor_width_mm, anchor_thickness_mm) = anchor
+                .map(|a| (a.width_mm, a.thickness_mm))
+                .unwrap_or((0.0, 0.0));
+            if check.status == PathRuleStatus::OverrideRequired {
+                pending_records.push(PathOverridePendingRecord {
+                    version_id: version_id.to_string(),
+                    machine_code: machine_code.to_string(),
+                    plan_date: item.plan_date,
+                    material_id: item.material_id.clone(),
+                    violation_type: violation_type.clone(),
+                    urgent_level: state.urgent_level.to_string(),
+                    width_mm: w,
+                    thickness_mm: t,
+                    anchor_width_mm,
+                    anchor_thickness_mm,
+                    width_delta_mm: check.width_delta_mm,
+                    thickness_delta_mm: check.thickness_delta_mm,
+                });
+            }
+            violations.push(ResequenceViolationDto {
+                material_id: item.material_id.clone(),
+                plan_date: item.plan_date.format("%Y-%m-%d").to_string(),
+                seq_no,
+                status: check.status.to_string(),
+                violation_type,
+                urgent_level: state.urgent_level.to_string(),
+                width_mm: w,
+                thickness_mm: t,
+                anchor_material_id: anchor_material_id.cloned(),
+                anchor_width_mm,
+                anchor_thickness_mm,
+                width_delta_mm: check.width_delta_mm,
+                thickness_delta_mm: check.thickness_delta_mm,
+            });
+        };
+        for (idx, id) in new_order.iter().enumerate() {
+            let item = day_items
+                .iter()
+                .find(|i| &i.material_id == id)
+                .expect("新顺序已校验为当日明细的排列");
+            let Some((w, t)) = dims(id) else {
+                continue;
+            };
+            gate(
+                item,
+                idx as i32 + 1,
+                (w, t),
+                anchor.as_ref(),
+                anchor_material_id.as_ref(),
+            );
+            anchor = Some(Anchor {
+                width_mm: w,
+                thickness_mm: t,
+            });
+            anchor_material_id = Some(id.clone());
+        }
+
+        // 6) 当日最后一块变化：下一排产日第一块以新的最后一块为锚点复核
+        let mut refresh_material_ids = new_order.to_vec();
+        let mut revalidated_next_first = None;
+        if new_order.last() != old_order.last() {
+            if let Some(next) = &next_first {
+                if let Some(d) = dims(&next.material_id) {
+                    gate(
+                        next,
+                        next.seq_no,
+                        d,
+                        anchor.as_ref(),
+                        anchor_material_id.as_ref(),
+                    );
+                    refresh_material_ids.push(next.material_id.clone());
+                    revalidated_next_first = Some(next.material_id.clone());
+                }
+            }
+        }
+
+        let moved_materials: Vec<String> = new_order
+            .iter()
+            .zip(old_order.iter())
+            .filter(|(new, old)| new != old)
+            .map(|(new, _)| new.clone())
+            .collect();
+        let override_required_count = violations
+            .iter()
+            .filter(|v| v.status == "OVERRIDE_REQUIRED")
+            .count();
+        let hard_violation_count = violations.len() - override_required_count;
+        let message = if hard_violation_count > 0 {
+            format!("新顺序存在{}块路径硬违规，不可调整", hard_violation_count)
+        } else if moved_materials.is_empty() {
+            "顺序未变化".to_string()
+        } else if override_required_count > 0 {
+            format!(
+                "{}块位置变化, {}块需人工确认路径突破",
+                moved_materials.len(),
+                override_required_count
+            )
+        } else {
+            format!("{}块位置变化, 路径规则校验通过", moved_materials.len())
+        };
+
+        Ok(ResequencePlan {
+            day_items,
+            result: ResequenceResultDto {
+                version_id: version_id.to_string(),
+                machine_code: machine_code.to_string(),
+                plan_date: plan_date.format("%Y-%m-%d").to_string(),
+                old_order,
+                new_order: new_order.to_vec(),
+                moved_materials,
+                anchor_source,
+                anchor_material_id: initial_anchor_material_id,
+                anchor_width_mm: initial_anchor.as_ref().map(|a| a.width_mm),
+                anchor_thickness_mm: initial_anchor.as_ref().map(|a| a.thickness_mm),
+                violations,
+                override_required_count,
+                hard_violation_count,
+                applied: false,
+                message,
+            },
+            refresh_material_ids,
+            pending_records,
+            revalidated_next_first,
+        })
+    }
+
+    fn load_seed_s2_config(&self) -> SeedS2Config {
+        SeedS2Config {
+            percentile: Self::parse_f64(
+                self.config_manager
+                    .get_global_config_value("seed_s2_percentile")
+                    .ok()
+                    .flatten(),
+                0.95,
+            )
+            .clamp(0.0, 1.0),
+            small_sample_threshold: Self::parse_i32(
+                self.config_manager
+                    .get_global_config_value("seed_s2_small_sample_threshold")
+                    .ok()
+                    .flatten(),
+                10,
+            )
+            .max(1),
+        }
+    }
+}
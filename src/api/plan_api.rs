@@ -10,19 +10,33 @@ use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
 
+use crate::api::activation_validator::ActivationRedLineValidator;
 use crate::api::error::{ApiError, ApiResult};
+use crate::config::strategy_profile::{CustomStrategyParameters, CustomStrategyProfile};
 use crate::config::ConfigManager;
-use crate::domain::action_log::ActionLog;
+use crate::domain::action_log::{ActionLog, ImpactSummary};
 use crate::domain::plan::{Plan, PlanItem, PlanVersion};
+use crate::domain::red_line::RedLineReport;
+use crate::domain::scenario::is_isolated_plan_type;
 use crate::domain::types::PlanVersionStatus;
+use crate::domain::version_lineage::{VersionTrigger, META_PARENT_VERSION_ID, META_TRIGGER};
 use crate::engine::events::{
     OptionalEventPublisher, ScheduleEvent, ScheduleEventPublisher, ScheduleEventType,
 };
-use crate::engine::recalc::{RecalcEngine, ResolvedStrategyProfile};
+use crate::engine::plan_evaluation::{PlanEvaluationContext, PlanEvaluator, PlanObjectiveVector};
+use crate::engine::recalc::{RecalcEngine, RescheduleInputSnapshot, ResolvedStrategyProfile};
 use crate::engine::risk::RiskEngine;
+use crate::engine::strategy_sweep::{StrategySweepEngine, StrategySweepPoint, StrategySweepSpec};
+use crate::engine::weight_learning::{AgreementRate, UrgencyRuleSuggestion};
 use crate::engine::ScheduleStrategy;
+use crate::engine::{
+    ChangeNoticeEngine, ChangeNoticeLine, ChangeNoticeMachineDay, ChangeNoticeType,
+    ContractDeliveryDelta, DisplacedItem, GradeMixDelta, ManualChange, MergeConflict,
+    RushInfeasible, RushPlacement, SequenceDelta, VersionDiffEngine,
+};
 use crate::repository::action_log_repo::ActionLogRepository;
 use crate::repository::capacity_repo::CapacityPoolRepository;
 use crate::repository::material_repo::{
@@ -59,8 +73,11 @@ pub struct PlanApi {
     config_manager: Arc<ConfigManager>,
     recalc_engine: Arc<RecalcEngine>,
     risk_engine: Arc<RiskEngine>,
+    activation_validator: Arc<ActivationRedLineValidator>,
     // 事件发布器（依赖倒置：不再直接依赖 Decision 层的 RefreshQueue）
     event_publisher: OptionalEventPublisher,
+    // 进行中的草案生成任务（generation_id -> 取消标记）
+    draft_generation_cancels: Mutex<HashMap<String, Arc<AtomicBool>>>,
 }
 
 impl PlanApi {
@@ -78,6 +95,7 @@ impl PlanApi {
         config_manager: Arc<ConfigManager>,
         recalc_engine: Arc<RecalcEngine>,
         risk_engine: Arc<RiskEngine>,
+        activation_validator: Arc<ActivationRedLineValidator>,
         event_publisher: Option<Arc<dyn ScheduleEventPublisher>>,
     ) -> Self {
         let event_publisher = match event_publisher {
@@ -98,18 +116,28 @@ impl PlanApi {
             config_manager,
             recalc_engine,
             risk_engine,
+            activation_validator,
             event_publisher,
+            draft_generation_cancels: Mutex::new(HashMap::new()),
         }
     }
 }
 
+mod change_notice;
+mod evaluation;
 mod items_query;
 mod operations;
 mod plan_management;
 mod recalc;
+mod rush_insertion;
 mod strategy_drafts;
+mod strategy_sweep;
 mod version_comparison;
 mod version_management;
+mod version_merge;
+mod weight_learning;
+
+pub(crate) use operations::item_position;
 
 // ==========================================
 // DTO 类型定义
@@ -167,6 +195,26 @@ pub struct VersionComparisonResult {
     /// 产能变化（按机组和日期）
     pub capacity_delta: Option<Vec<CapacityDelta>>,
 
+    /// 合同准时交付变化（仅有变化的合同）
+    #[serde(default)]
+    pub contract_delta: Option<Vec<ContractDeliveryDelta>>,
+
+    /// 钢种结构变化（按机组、日期和钢种）
+    #[serde(default)]
+    pub grade_mix_delta: Option<Vec<GradeMixDelta>>,
+
+    /// 同日顺序变化（Kendall-tau 距离，按机组和日期）
+    #[serde(default)]
+    pub sequence_delta: Option<Vec<SequenceDelta>>,
+
+    /// 路径突破数量变化
+    #[serde(default)]
+    pub path_override_delta: Option<PathOverrideDelta>,
+
+    /// 换辊次数变化（按机组）
+    #[serde(default)]
+    pub roll_change_delta: Option<Vec<RollChangeDelta>>,
+
     /// 配置变化
     pub config_changes: Option<Vec<ConfigChange>>,
 
@@ -215,6 +263,10 @@ pub struct VersionKpiSummary {
     pub urgent_total_t: Option<f64>,
     pub snapshot_date_from: Option<NaiveDate>,
     pub snapshot_date_to: Option<NaiveDate>,
+
+    // ===== 多目标评价（PlanEvaluator 统一口径）=====
+    #[serde(default)]
+    pub objectives: Option<PlanObjectiveVector>,
 }
 
 impl VersionKpiSummary {
@@ -247,6 +299,7 @@ impl VersionKpiSummary {
             } else {
                 None
             },
+            objectives: None,
         }
     }
 }
@@ -336,6 +389,64 @@ pub struct ConfigChange {
     pub value_b: Option<String>,
 }
 
+/// 路径突破数量变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathOverrideDelta {
+    /// 待确认突破数（path_override_pending）
+    pub pending_count_a: usize,
+    pub pending_count_b: usize,
+    pub pending_delta: i64,
+
+    /// 已确认突破并排入版本的材料数
+    pub confirmed_count_a: usize,
+    pub confirmed_count_b: usize,
+    pub confirmed_delta: i64,
+}
+
+/// 换辊次数变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollChangeDelta {
+    /// 机组代码
+    pub machine_code: String,
+
+    /// 版本A的换辊次数
+    pub roll_change_count_a: usize,
+
+    /// 版本B的换辊次数
+    pub roll_change_count_b: usize,
+
+    /// 换辊次数变化
+    pub roll_change_delta: i64,
+}
+
+/// 物料级版本差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionDiffItem {
+    pub material_id: String,
+
+    /// ADDED / REMOVED / MOVED / SEQ_CHANGED
+    pub change_type: String,
+
+    pub machine_code_a: Option<String>,
+    pub plan_date_a: Option<NaiveDate>,
+    pub seq_no_a: Option<i32>,
+    pub machine_code_b: Option<String>,
+    pub plan_date_b: Option<NaiveDate>,
+    pub seq_no_b: Option<i32>,
+    pub weight_t: f64,
+}
+
+/// 物料级版本差异分页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionDiffItemPage {
+    pub version_id_a: String,
+    pub version_id_b: String,
+    pub total_count: i64,
+    pub offset: i64,
+    pub limit: i64,
+    pub items: Vec<VersionDiffItem>,
+}
+
 /// 移动项请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoveItemRequest {
@@ -443,6 +554,23 @@ pub struct StrategyDraftSummary {
     pub removed_count: usize,
     pub squeezed_out_count: usize,
     pub message: String,
+    /// 多目标评价（旧草案摘要无此字段）
+    #[serde(default)]
+    pub objectives: Option<PlanObjectiveVector>,
+}
+
+/// 草案生成上下文（同一基准版本/日期范围下多策略共享的只读快照）
+struct StrategyDraftContext {
+    base_version_id: String,
+    from: NaiveDate,
+    to: NaiveDate,
+    base_items_in_range: Vec<PlanItem>,
+    frozen_items_in_range: Vec<PlanItem>,
+    machine_codes: Vec<String>,
+    /// 重排产只读输入快照（各策略并发试算共享）
+    input_snapshot: RescheduleInputSnapshot,
+    created_at: chrono::NaiveDateTime,
+    expires_at: chrono::NaiveDateTime,
 }
 
 /// 策略草案变更明细项（用于解释对比）
@@ -493,6 +621,103 @@ pub struct GenerateStrategyDraftsResponse {
     pub plan_date_to: NaiveDate,
     pub drafts: Vec<StrategyDraftSummary>,
     pub message: String,
+    /// 本次生成任务ID（进度事件/取消使用）
+    #[serde(default)]
+    pub generation_id: String,
+    /// 是否被取消（已完成的草案仍会返回并保留）
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// 草案生成进度状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StrategyDraftProgressStatus {
+    Started,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// 草案生成进度（按策略推送）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyDraftProgress {
+    pub generation_id: String,
+    pub strategy: String,
+    pub status: StrategyDraftProgressStatus,
+    /// 已结束（完成/失败/取消）的策略数
+    pub finished_count: usize,
+    pub total_count: usize,
+    pub draft_id: Option<String>,
+    pub message: String,
+}
+
+/// 策略参数扫描响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategySweepResponse {
+    pub sweep_id: String,
+    pub base_version_id: String,
+    pub plan_date_from: NaiveDate,
+    pub plan_date_to: NaiveDate,
+    pub base_strategy: String,
+    /// 全部扫描点（按生成顺序）；每个点对应一条策略草案，可直接发布
+    pub points: Vec<StrategySweepPoint>,
+    pub pareto_count: usize,
+    pub message: String,
+}
+
+/// 人工调整反推策略权重报告（离线分析，不修改任何配置）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyWeightLearningResponse {
+    /// 操作日志分析窗口
+    pub analysis_from: NaiveDate,
+    pub analysis_to: NaiveDate,
+    /// 对照策略（预设 key 或 custom:<id>）
+    pub strategy_key: String,
+    pub base_strategy: String,
+
+    pub move_log_count: usize,
+    /// 旧版移动日志（无原位置明细）条数，不参与拟合
+    pub legacy_move_log_count: usize,
+    pub urgent_log_count: usize,
+    pub force_release_log_count: usize,
+
+    /// 偏好样本数（胜者应排在败者之前）
+    pub pair_count: usize,
+    /// 因锁定/强制放行/材料缺失剔除的样本数
+    pub excluded_pair_count: usize,
+    /// 样本超过上限被截断（保留最近的调整）
+    pub pairs_truncated: bool,
+
+    /// 对照策略 / 学习后参数对人工选择的一致率
+    pub baseline_agreement: AgreementRate,
+    pub learned_agreement: AgreementRate,
+    pub learned_parameters: CustomStrategyParameters,
+
+    /// 当前紧急阈值与调整建议
+    pub urgent_n1_days: i64,
+    pub urgent_n2_days: i64,
+    pub rule_suggestions: Vec<UrgencyRuleSuggestion>,
+    pub message: String,
+}
+
+impl StrategyWeightLearningResponse {
+    /// 将学习结果转为可保存的自定义策略
+    pub fn to_custom_profile(&self, strategy_id: &str, title: &str) -> CustomStrategyProfile {
+        CustomStrategyProfile {
+            strategy_id: strategy_id.to_string(),
+            title: title.to_string(),
+            description: Some(format!(
+                "人工调整学习（{}~{}，一致率 {:.1}% → {:.1}%）",
+                self.analysis_from,
+                self.analysis_to,
+                self.baseline_agreement.rate * 100.0,
+                self.learned_agreement.rate * 100.0
+            )),
+            base_strategy: self.base_strategy.clone(),
+            parameters: self.learned_parameters.clone(),
+        }
+    }
 }
 
 /// 列出策略草案响应（用于页面刷新/重启后的恢复）
@@ -540,6 +765,154 @@ pub struct CleanupStrategyDraftsResponse {
     pub message: String,
 }
 
+/// 版本合并冲突处理方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeConflictResolution {
+    /// 材料ID
+    pub material_id: String,
+
+    /// 处理方式：KEEP_RECALC（放弃人工调整，保留重算结果）/ FORCE_MANUAL（人工确认强制回放）
+    pub resolution: String,
+}
+
+/// 版本三方合并预览响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionMergePreviewResponse {
+    pub ancestor_version_id: String,
+    pub adjusted_version_id: String,
+    pub recalculated_version_id: String,
+    /// 识别出的人工变更数
+    pub manual_change_count: usize,
+    /// 可回放的人工变更
+    pub applied: Vec<ManualChange>,
+    /// 需人工处理的冲突
+    pub conflicts: Vec<MergeConflict>,
+    /// 重算结果已与人工落位一致的数量
+    pub already_matching: usize,
+    /// 合并后明细数
+    pub merged_items_count: usize,
+    pub message: String,
+}
+
+/// 版本三方合并保存响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionMergeApplyResponse {
+    /// 合并生成的草稿版本
+    pub merged_version_id: String,
+    pub applied_count: usize,
+    pub forced_count: usize,
+    /// 按 KEEP_RECALC 放弃的人工变更数
+    pub dropped_count: usize,
+    pub merged_items_count: usize,
+    pub message: String,
+}
+
+/// 紧急插单响应（预览与执行共用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RushInsertionResponse {
+    pub base_version_id: String,
+    /// 执行后生成的派生草稿版本（预览时为空）
+    pub derived_version_id: Option<String>,
+    /// 首个可插日期（今日与冻结区结束日取大）
+    pub first_open_date: NaiveDate,
+    pub placements: Vec<RushPlacement>,
+    /// 挤出链（按插单材料、链上位置排序）
+    pub displaced: Vec<DisplacedItem>,
+    pub infeasible: Vec<RushInfeasible>,
+    pub displaced_weight_t: f64,
+    /// 挤出材料新增拖期天数合计
+    pub added_tardiness_days: i64,
+    pub newly_late_count: usize,
+    pub impact: ImpactSummary,
+    pub message: String,
+}
+
+/// 现场变更通知（相邻两个激活版本之间，按机组×日期）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeNotice {
+    pub plan_id: String,
+    /// 上一激活版本（首次激活时为空，全部明细视为新增）
+    pub from_version_id: Option<String>,
+    pub from_version_no: Option<i32>,
+    pub to_version_id: String,
+    pub to_version_no: i32,
+    pub added_count: usize,
+    pub removed_count: usize,
+    pub moved_count: usize,
+    pub resequenced_count: usize,
+    /// 机组日汇总
+    pub machine_days: Vec<ChangeNoticeMachineDay>,
+    /// 变更明细（机组 → 日期 → 序号）
+    pub lines: Vec<ChangeNoticeLine>,
+    pub generated_at: chrono::NaiveDateTime,
+}
+
+impl ChangeNotice {
+    /// 按机组/日期区间筛选（现场按本机组、后续班次查看）
+    pub fn filtered(
+        &self,
+        machine_code: Option<&str>,
+        date_from: Option<NaiveDate>,
+        date_to: Option<NaiveDate>,
+    ) -> ChangeNotice {
+        let lines: Vec<ChangeNoticeLine> = self
+            .lines
+            .iter()
+            .filter(|l| machine_code.is_none_or(|m| l.touches_machine(m)))
+            .filter(|l| {
+                l.touches_dates(
+                    date_from.unwrap_or(NaiveDate::MIN),
+                    date_to.unwrap_or(NaiveDate::MAX),
+                )
+            })
+            .cloned()
+            .collect();
+        ChangeNotice::from_lines(
+            self.plan_id.clone(),
+            self.from_version_id.clone(),
+            self.from_version_no,
+            self.to_version_id.clone(),
+            self.to_version_no,
+            lines,
+            self.generated_at,
+        )
+    }
+
+    fn from_lines(
+        plan_id: String,
+        from_version_id: Option<String>,
+        from_version_no: Option<i32>,
+        to_version_id: String,
+        to_version_no: i32,
+        lines: Vec<ChangeNoticeLine>,
+        generated_at: chrono::NaiveDateTime,
+    ) -> Self {
+        let count = |t: ChangeNoticeType| lines.iter().filter(|l| l.change_type == t).count();
+        Self {
+            plan_id,
+            from_version_id,
+            from_version_no,
+            to_version_id,
+            to_version_no,
+            added_count: count(ChangeNoticeType::Added),
+            removed_count: count(ChangeNoticeType::Removed),
+            moved_count: count(ChangeNoticeType::Moved),
+            resequenced_count: count(ChangeNoticeType::Resequenced),
+            machine_days: ChangeNoticeEngine::summarize(&lines),
+            lines,
+            generated_at,
+        }
+    }
+}
+
+/// 变更通知导出响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeNoticeExportResponse {
+    pub file_path: String,
+    pub format: String,
+    pub line_count: usize,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
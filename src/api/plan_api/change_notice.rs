@@ -0,0 +1,247 @@
+use super::*;
+use crate::domain::types::SchedState;
+use crate::exporter::{ExportCell, ExportFormat, ExportTable};
+use std::path::Path;
+
+/// 随激活日志保存变更通知的动作类型
+const NOTICE_ACTION_TYPES: [&str; 2] = ["ACTIVATE_VERSION", "ROLLBACK_VERSION"];
+
+impl PlanApi {
+    // ==========================================
+    // 现场变更通知
+    // ==========================================
+
+    /// 生成两个版本间的现场变更通知
+    ///
+    /// # 参数
+    /// - from_version_id: 上一激活版本（None 表示首次激活，全部明细视为新增）
+    /// - to_version_id: 新激活版本
+    ///
+    /// # 说明
+    /// - 新增/移出/调整 的汇总口径与版本对比 (compare_versions) 一致
+    /// - 同日换序仅报告相对顺序真正变化的材料
+    pub fn generate_change_notice(
+        &self,
+        from_version_id: Option<&str>,
+        to_version_id: &str,
+    ) -> ApiResult<ChangeNotice> {
+        if to_version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        let to_version = self
+            .plan_version_repo
+            .find_by_id(to_version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", to_version_id)))?;
+
+        let from_version = match from_version_id.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(id) => {
+                let v = self
+                    .plan_version_repo
+                    .find_by_id(id)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                    .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", id)))?;
+                if v.plan_id != to_version.plan_id {
+                    return Err(ApiError::BusinessRuleViolation(format!(
+                        "变更通知只能在同一方案的版本间生成: {} / {}",
+                        v.plan_id, to_version.plan_id
+                    )));
+                }
+                Some(v)
+            }
+            None => None,
+        };
+
+        let to_items = self
+            .plan_item_repo
+            .find_by_version(to_version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let from_items = match &from_version {
+            Some(v) => self
+                .plan_item_repo
+                .find_by_version(&v.version_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?,
+            None => Vec::new(),
+        };
+
+        // 被移出材料的当前状态（解释移出原因）
+        let to_ids: HashSet<&str> = to_items.iter().map(|i| i.material_id.as_str()).collect();
+        let mut removed_states: HashMap<String, SchedState> = HashMap::new();
+        for item in from_items
+            .iter()
+            .filter(|i| !to_ids.contains(i.material_id.as_str()))
+        {
+            if let Some(state) = self
+                .material_state_repo
+                .find_by_id(&item.material_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            {
+                removed_states.insert(item.material_id.clone(), state.sched_state);
+            }
+        }
+
+        let lines = ChangeNoticeEngine::diff(&from_items, &to_items, &removed_states);
+        let mut notice = ChangeNotice::from_lines(
+            to_version.plan_id.clone(),
+            from_version.as_ref().map(|v| v.version_id.clone()),
+            from_version.as_ref().map(|v| v.version_no),
+            to_version.version_id.clone(),
+            to_version.version_no,
+            lines,
+            chrono::Local::now().naive_local(),
+        );
+
+        if let Some(from) = &from_version {
+            let comparison = self.compare_versions(&from.version_id, to_version_id)?;
+            notice.added_count = comparison.added_count;
+            notice.removed_count = comparison.removed_count;
+            notice.moved_count = comparison.moved_count;
+        }
+
+        Ok(notice)
+    }
+
+    /// 查询版本最近一次激活（或回滚激活）时保存的变更通知
+    pub fn get_activation_change_notice(&self, version_id: &str) -> ApiResult<ChangeNotice> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+
+        let logs = self
+            .action_log_repo
+            .find_by_version_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let latest = logs
+            .iter()
+            .filter(|log| NOTICE_ACTION_TYPES.contains(&log.action_type.as_str()))
+            .filter_map(|log| {
+                let notice = log.payload_json.as_ref()?.get("change_notice")?;
+                (!notice.is_null()).then(|| (log.action_ts, notice.clone()))
+            })
+            .max_by_key(|(ts, _)| *ts);
+
+        let (_, notice) = latest
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}没有激活变更通知", version_id)))?;
+
+        serde_json::from_value(notice)
+            .map_err(|e| ApiError::InternalError(format!("变更通知解析失败: {}", e)))
+    }
+
+    /// 导出版本激活变更通知（CSV / XLSX）
+    ///
+    /// # 参数
+    /// - format: csv / xlsx
+    /// - output_path: 导出文件路径（由前端保存对话框选择）
+    /// - machine_code / date_from / date_to: 可选筛选（按本机组、后续班次导出）
+    pub fn export_change_notice(
+        &self,
+        version_id: &str,
+        format: &str,
+        output_path: &str,
+        machine_code: Option<&str>,
+        date_from: Option<NaiveDate>,
+        date_to: Option<NaiveDate>,
+    ) -> ApiResult<ChangeNoticeExportResponse> {
+        if output_path.trim().is_empty() {
+            return Err(ApiError::InvalidInput("导出路径不能为空".to_string()));
+        }
+        let format =
+            ExportFormat::parse(format).map_err(|e| ApiError::InvalidInput(e.to_string()))?;
+
+        let notice = self.get_activation_change_notice(version_id)?.filtered(
+            machine_code,
+            date_from,
+            date_to,
+        );
+        let table = Self::change_notice_table(&notice);
+        let line_count = table
+            .write_to_path(Path::new(output_path), format)
+            .map_err(|e| ApiError::InternalError(e.to_string()))?;
+
+        Ok(ChangeNoticeExportResponse {
+            file_path: output_path.to_string(),
+            format: format.extension().to_string(),
+            line_count,
+        })
+    }
+
+    fn change_notice_table(notice: &ChangeNotice) -> ExportTable {
+        let date = |d: Option<NaiveDate>| d.map(|d| d.format("%Y-%m-%d").to_string());
+        let headers = [
+            "机组",
+            "日期",
+            "变更类型",
+            "材料号",
+            "原机组",
+            "原日期",
+            "原序号",
+            "新机组",
+            "新日期",
+            "新序号",
+            "吨位(t)",
+            "原因",
+        ];
+
+        ExportTable {
+            sheet_name: format!("变更通知V{}", notice.to_version_no),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: notice
+                .lines
+                .iter()
+                .map(|l| {
+                    vec![
+                        ExportCell::from(l.machine_code.as_str()),
+                        ExportCell::from(l.plan_date.format("%Y-%m-%d").to_string()),
+                        ExportCell::from(l.change_type.label()),
+                        ExportCell::from(l.material_id.as_str()),
+                        ExportCell::from(l.from_machine_code.clone()),
+                        ExportCell::from(date(l.from_plan_date)),
+                        ExportCell::from(l.from_seq_no),
+                        ExportCell::from(l.to_machine_code.clone()),
+                        ExportCell::from(date(l.to_plan_date)),
+                        ExportCell::from(l.to_seq_no),
+                        ExportCell::from(l.weight_t),
+                        ExportCell::from(l.reason.as_str()),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    /// 变更通知摘要（写入 impact_summary_json）
+    pub(super) fn change_notice_summary(notice: &ChangeNotice) -> serde_json::Value {
+        serde_json::json!({
+            "from_version_id": notice.from_version_id,
+            "added_count": notice.added_count,
+            "removed_count": notice.removed_count,
+            "moved_count": notice.moved_count,
+            "resequenced_count": notice.resequenced_count,
+            "machine_count": notice
+                .machine_days
+                .iter()
+                .map(|d| d.machine_code.as_str())
+                .collect::<HashSet<_>>()
+                .len(),
+        })
+    }
+
+    /// 生成激活变更通知（失败不阻断激活，仅记录警告）
+    pub(super) fn build_activation_change_notice(
+        &self,
+        from_version_id: Option<&str>,
+        to_version_id: &str,
+    ) -> Option<ChangeNotice> {
+        match self.generate_change_notice(from_version_id, to_version_id) {
+            Ok(notice) => Some(notice),
+            Err(e) => {
+                tracing::warn!(
+                    "生成激活变更通知失败: version_id={}, error={}",
+                    to_version_id,
+                    e
+                );
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use super::*;
+use crate::config::config_keys;
+use crate::engine::plan_evaluation::{
+    DEFAULT_COLD_STOCK_AGE_DAYS, DEFAULT_ROLL_SUGGEST_THRESHOLD_T,
+    DEFAULT_STRUCTURE_DEVIATION_THRESHOLD,
+};
+
+impl PlanApi {
+    // ==========================================
+    // 多目标评价接口
+    // ==========================================
+
+    /// 版本多目标评价
+    ///
+    /// # 说明
+    /// - 评价范围为版本全部明细；产能池取本版本在明细日期范围内的记录
+    /// - 与策略草案摘要、版本 KPI 对比使用同一评价引擎（PlanEvaluator）
+    pub fn evaluate_version(&self, version_id: &str) -> ApiResult<PlanObjectiveVector> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        self.plan_version_repo
+            .find_by_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+
+        let mut items = self
+            .plan_item_repo
+            .find_by_version(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        self.evaluate_plan_items(&mut items, version_id, None)
+    }
+
+    /// 评价一组明细（版本明细或 dry-run 试算结果）
+    ///
+    /// # 参数
+    /// - items: 待评价明细（会就地补充钢种/交期快照）
+    /// - pool_version_id: 提供产能目标/上限的版本（草案取基准版本）
+    /// - date_range: 产能池范围；None 时取明细的最早~最晚排产日期
+    pub(super) fn evaluate_plan_items(
+        &self,
+        items: &mut [PlanItem],
+        pool_version_id: &str,
+        date_range: Option<(NaiveDate, NaiveDate)>,
+    ) -> ApiResult<PlanObjectiveVector> {
+        self.enrich_plan_items(items);
+
+        let date_range = date_range.or_else(|| {
+            let from = items.iter().map(|i| i.plan_date).min()?;
+            let to = items.iter().map(|i| i.plan_date).max()?;
+            Some((from, to))
+        });
+        let pools: Vec<_> = match date_range {
+            Some((from, to)) => self
+                .capacity_repo
+                .find_by_version_id(pool_version_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                .into_iter()
+                .filter(|p| p.plan_date >= from && p.plan_date <= to)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let ctx = self.build_evaluation_context(items)?;
+        Ok(PlanEvaluator::evaluate(items, &pools, &ctx))
+    }
+
+    /// 评价上下文：库龄 / 路径突破 / 结构目标 / 换辊阈值
+    fn build_evaluation_context(&self, items: &[PlanItem]) -> ApiResult<PlanEvaluationContext> {
+        let material_ids: Vec<String> = items.iter().map(|i| i.material_id.clone()).collect();
+
+        let stock_age_days = self
+            .material_master_repo
+            .find_by_ids(&material_ids)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .filter_map(|m| m.stock_age_days.map(|age| (m.material_id, age)))
+            .collect();
+        let path_override_material_ids = self
+            .material_state_repo
+            .find_user_confirmed_ids(&material_ids)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .collect();
+
+        let value = |key: &str| {
+            self.config_manager
+                .get_global_config_value(key)
+                .ok()
+                .flatten()
+        };
+        let target_ratio = value(config_keys::TARGET_RATIO)
+            .and_then(|v| serde_json::from_str::<HashMap<String, f64>>(&v).ok())
+            .unwrap_or_default();
+
+        Ok(PlanEvaluationContext {
+            stock_age_days,
+            path_override_material_ids,
+            target_ratio,
+            deviation_threshold: value(config_keys::DEVIATION_THRESHOLD)
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_STRUCTURE_DEVIATION_THRESHOLD),
+            cold_stock_age_threshold_days: value(config_keys::COLD_STOCK_AGE_THRESHOLD_DAYS)
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|d: &i32| *d > 0)
+                .unwrap_or(DEFAULT_COLD_STOCK_AGE_DAYS),
+            roll_suggest_threshold_t: value(config_keys::ROLL_SUGGEST_THRESHOLD_T)
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_ROLL_SUGGEST_THRESHOLD_T),
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::domain::batching::BatchingDeferral;
 use std::collections::HashMap;
 
 impl PlanApi {
@@ -161,6 +162,17 @@ impl PlanApi {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
+    /// 查询版本的钢种族批量顺延材料（重算结束仍因最小批量规则未排入）
+    pub fn list_batching_deferrals(&self, version_id: &str) -> ApiResult<Vec<BatchingDeferral>> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+
+        self.plan_item_repo
+            .find_batching_deferrals_by_version(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
     /// 查询版本排产日期边界（min/max）及数量
     ///
     /// 用途：
@@ -204,7 +216,7 @@ impl PlanApi {
     /// 补充字段：
     /// - material_state: urgent_level/sched_state/scheduled_date/scheduled_machine_code
     /// - material_master: steel_grade/width_mm/thickness_mm/contract_no/due_date
-    fn enrich_plan_items(&self, items: &mut [PlanItem]) {
+    pub(super) fn enrich_plan_items(&self, items: &mut [PlanItem]) {
         if items.is_empty() {
             return;
         }
@@ -1,4 +1,6 @@
 use super::*;
+use crate::api::error::ValidationViolation;
+use crate::domain::undo::{InversePayload, ItemPosition};
 
 impl PlanApi {
     // ==========================================
@@ -44,9 +46,9 @@ impl PlanApi {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?
             .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
 
-        if !version.is_draft() && !version.is_active() {
+        if !version.is_editable() {
             return Err(ApiError::BusinessRuleViolation(
-                "只能修改草稿或激活状态的版本".to_string(),
+                "只能修改草稿、已驳回或激活状态的版本".to_string(),
             ));
         }
 
@@ -68,6 +70,8 @@ impl PlanApi {
         let mut failed_count = 0;
         let mut has_violations = false;
         let mut items_to_update = Vec::new();
+        // 撤销载荷：原位置（成功移动的排产项）
+        let mut restore_positions = Vec::new();
 
         for move_req in moves {
             // 解析目标日期
@@ -144,29 +148,14 @@ impl PlanApi {
             }
 
             // 创建更新后的排产项
-            let updated_item = PlanItem {
-                version_id: version_id.to_string(),
-                material_id: move_req.material_id.clone(),
-                machine_code: move_req.to_machine.clone(),
-                plan_date: to_date,
-                seq_no: move_req.to_seq,
-                weight_t: original_item.weight_t,
-                source_type: "MANUAL".to_string(), // 手动移动
-                locked_in_plan: original_item.locked_in_plan,
-                force_release_in_plan: original_item.force_release_in_plan,
-                violation_flags: original_item.violation_flags.clone(),
-                urgent_level: original_item.urgent_level.clone(),
-                sched_state: original_item.sched_state.clone(),
-                assign_reason: Some("MANUAL_MOVE".to_string()),
-                steel_grade: original_item.steel_grade.clone(),
-                width_mm: original_item.width_mm,
-                thickness_mm: original_item.thickness_mm,
-                contract_no: original_item.contract_no.clone(),
-                due_date: original_item.due_date.clone(),
-                scheduled_date: original_item.scheduled_date.clone(),
-                scheduled_machine_code: original_item.scheduled_machine_code.clone(),
-            };
+            let updated_item = manually_moved_item(
+                original_item,
+                &move_req.to_machine,
+                to_date,
+                move_req.to_seq,
+            );
 
+            restore_positions.push(item_position(original_item));
             items_to_update.push(updated_item);
             success_count += 1;
             results.push(MoveItemResult {
@@ -212,6 +201,24 @@ impl PlanApi {
                     "has_violations": has_violations,
                     "reason": reason,
                     "moved_materials": items_to_update.iter().map(|i| &i.material_id).collect::<Vec<_>>(),
+                    // 原位置 -> 目标位置（人工调整学习等离线分析使用）
+                    "moves": results
+                        .iter()
+                        .filter(|r| r.success)
+                        .map(|r| serde_json::json!({
+                            "material_id": r.material_id,
+                            "from_date": r.from_date,
+                            "from_machine": r.from_machine,
+                            "to_date": r.to_date,
+                            "to_machine": r.to_machine,
+                        }))
+                        .collect::<Vec<_>>(),
+                    // 逆操作（撤销/重做使用）
+                    "undo": InversePayload::MoveItems {
+                        restore: restore_positions,
+                        expected: items_to_update.iter().map(item_position).collect(),
+                    }
+                    .to_json(),
                 })),
                 impact_summary_json: None,
                 machine_code: None,
@@ -250,4 +257,116 @@ impl PlanApi {
             ),
         })
     }
+
+    /// 按位置快照恢复排产项（撤销/重做移动）
+    ///
+    /// # 说明
+    /// - 校验口径同 move_items（STRICT）：版本可编辑、冻结区材料不可移动
+    /// - 来源与分配原因按快照原样恢复；旧快照无来源字段时按人工移动处理
+    /// - 不写操作日志（由调用方记录 UNDO/REDO 日志）
+    pub(crate) fn apply_item_positions(
+        &self,
+        version_id: &str,
+        positions: &[ItemPosition],
+    ) -> ApiResult<()> {
+        let version = self
+            .plan_version_repo
+            .find_by_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        if !version.is_editable() {
+            return Err(ApiError::BusinessRuleViolation(
+                "只能修改草稿、已驳回或激活状态的版本".to_string(),
+            ));
+        }
+
+        let item_map: HashMap<String, PlanItem> = self
+            .plan_item_repo
+            .find_by_version(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|item| (item.material_id.clone(), item))
+            .collect();
+
+        let mut violations = Vec::new();
+        let mut items_to_update = Vec::new();
+        for pos in positions {
+            let item = item_map.get(&pos.material_id).ok_or_else(|| {
+                ApiError::NotFound(format!("材料{}在版本中不存在", pos.material_id))
+            })?;
+            // 红线1: 冻结区保护
+            if item.locked_in_plan {
+                violations.push(ValidationViolation {
+                    violation_type: "FROZEN_ZONE".to_string(),
+                    material_id: pos.material_id.clone(),
+                    reason: "冻结区材料不可移动".to_string(),
+                    details: None,
+                });
+                continue;
+            }
+            items_to_update.push(match &pos.source_type {
+                // 来源与分配原因原样恢复（撤销后引擎落位仍为引擎落位）
+                Some(source_type) => PlanItem {
+                    machine_code: pos.machine_code.clone(),
+                    plan_date: pos.plan_date,
+                    seq_no: pos.seq_no,
+                    source_type: source_type.clone(),
+                    assign_reason: pos.assign_reason.clone(),
+                    ..item.clone()
+                },
+                None => manually_moved_item(item, &pos.machine_code, pos.plan_date, pos.seq_no),
+            });
+        }
+
+        if !violations.is_empty() {
+            return Err(ApiError::ManualOperationValidationError {
+                reason: format!("{}个材料违反冻结区保护", violations.len()),
+                violations,
+            });
+        }
+
+        self.plan_item_repo
+            .batch_upsert(&items_to_update)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let event = ScheduleEvent::full_scope(
+            version_id.to_string(),
+            ScheduleEventType::PlanItemChanged,
+            Some("undo_redo".to_string()),
+        );
+        if let Err(e) = self.event_publisher.publish(event) {
+            tracing::warn!("发布刷新事件失败: {}", e);
+        }
+
+        Ok(())
+    }
+}
+
+/// 人工移动后的排产项（来源标记为 MANUAL）
+fn manually_moved_item(
+    original: &PlanItem,
+    machine_code: &str,
+    plan_date: NaiveDate,
+    seq_no: i32,
+) -> PlanItem {
+    PlanItem {
+        machine_code: machine_code.to_string(),
+        plan_date,
+        seq_no,
+        source_type: "MANUAL".to_string(), // 手动移动
+        assign_reason: Some("MANUAL_MOVE".to_string()),
+        ..original.clone()
+    }
+}
+
+/// 排产项当前位置
+pub(crate) fn item_position(item: &PlanItem) -> ItemPosition {
+    ItemPosition {
+        material_id: item.material_id.clone(),
+        machine_code: item.machine_code.clone(),
+        plan_date: item.plan_date,
+        seq_no: item.seq_no,
+        source_type: Some(item.source_type.clone()),
+        assign_reason: item.assign_reason.clone(),
+    }
 }
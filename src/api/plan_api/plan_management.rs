@@ -94,6 +94,26 @@ impl PlanApi {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
+    /// 校验方案为生产方案（场景/沙盘方案禁止直接重算）
+    ///
+    /// 说明：重算会写入共享的 material_state / risk_snapshot，
+    /// 场景方案只能通过 ScenarioApi 在隔离沙盘中评估。
+    pub(super) fn ensure_production_plan(&self, plan_id: &str) -> ApiResult<()> {
+        let plan = self
+            .plan_repo
+            .find_by_id(plan_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        match plan {
+            Some(plan) if is_isolated_plan_type(&plan.plan_type) => {
+                Err(ApiError::BusinessRuleViolation(format!(
+                    "方案{}为场景方案({})，请使用场景评估代替重算",
+                    plan.plan_name, plan.plan_type
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// 查询最近创建的激活版本ID（跨方案）
     ///
     /// 用途：前端启动时自动回填工作版本，避免“已有激活版本但界面提示未选择”。
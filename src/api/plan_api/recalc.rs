@@ -205,6 +205,7 @@ impl PlanApi {
             .find_by_id(version_id)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?
             .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        self.ensure_production_plan(&version.plan_id)?;
 
         // 红线1预检: 设置冻结日期
         let _frozen_from_date = frozen_date.or(version.frozen_from_date);
@@ -0,0 +1,444 @@
+use super::*;
+use crate::domain::capacity::CapacityPool;
+use crate::domain::material::MaterialState;
+use crate::domain::types::SchedState;
+use crate::engine::{
+    ImpactSummaryEngine, RushInfeasible, RushInsertionEngine, RushInsertionInput,
+    RushInsertionOutcome, RushMaterial,
+};
+
+/// 单次插单材料数上限
+const MAX_RUSH_MATERIALS: usize = 200;
+
+/// 插单计算结果（预览与执行共用）
+struct RushInsertionPlan {
+    base_version: PlanVersion,
+    first_open_date: NaiveDate,
+    base_pools: Vec<CapacityPool>,
+    outcome: RushInsertionOutcome,
+    impact: ImpactSummary,
+}
+
+impl PlanApi {
+    // ==========================================
+    // 紧急插单接口
+    // ==========================================
+
+    /// 预览紧急插单（不落库）
+    ///
+    /// # 参数
+    /// - version_id: 基准版本
+    /// - material_ids: 插单材料（已排材料提前，未排材料新增）
+    ///
+    /// # 返回
+    /// - Ok(RushInsertionResponse): 插单落位 + 挤出链 + 拖期变化 + 影响摘要
+    ///
+    /// # 红线合规
+    /// - 冻结区（冻结起始日之前）与锁定材料不动
+    /// - 未适温材料不早于最早可排日
+    pub fn preview_rush_insertion(
+        &self,
+        version_id: &str,
+        material_ids: &[String],
+    ) -> ApiResult<RushInsertionResponse> {
+        let plan = self.build_rush_insertion(version_id, material_ids)?;
+        Ok(Self::rush_insertion_response(plan, None))
+    }
+
+    /// 执行紧急插单，结果保存为基准版本的派生草稿版本
+    ///
+    /// # 说明
+    /// - 基准版本不修改；派生版本、明细、产能池与日志同一事务写入
+    /// - 派生版本记录 RUSH_INSERT 日志（含完整影响摘要）
+    pub fn apply_rush_insertion(
+        &self,
+        version_id: &str,
+        material_ids: &[String],
+        operator: &str,
+        reason: &str,
+    ) -> ApiResult<RushInsertionResponse> {
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+        if reason.trim().is_empty() {
+            return Err(ApiError::InvalidInput("插单原因不能为空".to_string()));
+        }
+
+        let plan = self.build_rush_insertion(version_id, material_ids)?;
+        if plan.outcome.placements.is_empty() {
+            let reasons: Vec<String> = plan
+                .outcome
+                .infeasible
+                .iter()
+                .map(|i| format!("{}({})", i.material_id, i.reason))
+                .collect();
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "没有可插单的材料: {}",
+                reasons.join(", ")
+            )));
+        }
+
+        let base = &plan.base_version;
+        let mut derived_version = self.build_lineage_version(
+            base.plan_id.clone(),
+            base.recalc_window_days.unwrap_or(30),
+            base.frozen_from_date,
+            Some(format!("紧急插单(V{})", base.version_no)),
+            operator.to_string(),
+            Some(&base.version_id),
+            VersionTrigger::RushInsert,
+        )?;
+        let derived_version_id = derived_version.version_id.clone();
+
+        // 派生版本明细与产能池（已用吨位按插单后明细重算）
+        let items: Vec<PlanItem> = plan
+            .outcome
+            .items
+            .iter()
+            .cloned()
+            .map(|mut i| {
+                i.version_id = derived_version_id.clone();
+                i
+            })
+            .collect();
+        let pools: Vec<CapacityPool> = pools_with_used(&plan.base_pools, &plan.outcome.items)
+            .into_iter()
+            .map(|mut p| {
+                p.version_id = derived_version_id.clone();
+                p
+            })
+            .collect();
+
+        let outcome = &plan.outcome;
+        let dates = outcome
+            .placements
+            .iter()
+            .map(|p| p.to_date)
+            .chain(outcome.displaced.iter().map(|d| d.to_date));
+        let log = ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: Some(derived_version_id.clone()),
+            action_type: "RUSH_INSERT".to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "base_version_id": base.version_id,
+                "reason": reason.trim(),
+                "inserted_materials": outcome.placements.iter().map(|p| &p.material_id).collect::<Vec<_>>(),
+                "displaced_materials": outcome.displaced.iter().map(|d| &d.material_id).collect::<Vec<_>>(),
+                "infeasible_materials": outcome.infeasible.iter().map(|i| &i.material_id).collect::<Vec<_>>(),
+                "added_tardiness_days": outcome.added_tardiness_days,
+            })),
+            impact_summary_json: serde_json::to_value(&plan.impact).ok(),
+            machine_code: None,
+            date_range_start: dates.clone().min(),
+            date_range_end: dates.max(),
+            detail: Some(format!(
+                "紧急插单{}个, 挤出{}个(新增拖期{}天) | {}",
+                outcome.placements.len(),
+                outcome.displaced.len(),
+                outcome.added_tardiness_days,
+                reason.trim()
+            )),
+        };
+
+        // 版本、明细、产能池与日志同一事务写入，任一步失败整体回滚
+        self.plan_version_repo
+            .create_with_contents(&mut derived_version, &items, &pools, |v| {
+                vec![Self::create_version_log(v), log]
+            })
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok(Self::rush_insertion_response(
+            plan,
+            Some(derived_version_id),
+        ))
+    }
+
+    // ==========================================
+    // 内部: 插单计算
+    // ==========================================
+
+    fn build_rush_insertion(
+        &self,
+        version_id: &str,
+        material_ids: &[String],
+    ) -> ApiResult<RushInsertionPlan> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        let mut ids: Vec<String> = material_ids
+            .iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        if ids.is_empty() {
+            return Err(ApiError::InvalidInput("插单材料不能为空".to_string()));
+        }
+        if ids.len() > MAX_RUSH_MATERIALS {
+            return Err(ApiError::InvalidInput(format!(
+                "单次插单材料不能超过{}个",
+                MAX_RUSH_MATERIALS
+            )));
+        }
+
+        let base_version = self
+            .plan_version_repo
+            .find_by_id(version_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        let base_items = self.plan_item_repo.find_by_version(version_id)?;
+        let base_pools = self.capacity_repo.find_by_version_id(version_id)?;
+
+        let today = chrono::Local::now().date_naive();
+        let first_open_date = base_version
+            .frozen_from_date
+            .map_or(today, |d| d.max(today));
+
+        let masters: HashMap<String, _> = self
+            .material_master_repo
+            .find_by_ids(&ids)?
+            .into_iter()
+            .map(|m| (m.material_id.clone(), m))
+            .collect();
+        let item_by_id: HashMap<&str, &PlanItem> = base_items
+            .iter()
+            .map(|i| (i.material_id.as_str(), i))
+            .collect();
+
+        let mut rush = Vec::new();
+        let mut states = Vec::new();
+        let mut infeasible = Vec::new();
+        let mut reject = |material_id: &str, reason: String| {
+            infeasible.push(RushInfeasible {
+                material_id: material_id.to_string(),
+                reason,
+            })
+        };
+        for id in &ids {
+            let Some(master) = masters.get(id) else {
+                reject(id, "材料不存在".to_string());
+                continue;
+            };
+            let Some(state) = self.material_state_repo.find_by_id(id)? else {
+                reject(id, "材料状态缺失".to_string());
+                continue;
+            };
+            if matches!(
+                state.sched_state,
+                SchedState::Blocked | SchedState::Completed
+            ) {
+                reject(id, format!("材料状态{}不可排", state.sched_state));
+                continue;
+            }
+
+            let earliest_sched_date =
+                if state.force_release_flag || state.sched_state == SchedState::ForceRelease {
+                    None
+                } else if state.sched_state == SchedState::PendingMature {
+                    Some(
+                        state
+                            .earliest_sched_date
+                            .unwrap_or(today + chrono::Duration::days(state.ready_in_days as i64)),
+                    )
+                } else {
+                    None
+                };
+
+            let (mut item, scheduled) = match item_by_id.get(id.as_str()) {
+                Some(existing) if existing.locked_in_plan => {
+                    reject(id, "冻结区材料不可移动".to_string());
+                    continue;
+                }
+                Some(existing) => {
+                    let mut item = (*existing).clone();
+                    item.source_type = "MANUAL".to_string();
+                    item.assign_reason = Some("RUSH_INSERT".to_string());
+                    (item, true)
+                }
+                None => {
+                    if state.lock_flag {
+                        reject(id, "材料已锁定".to_string());
+                        continue;
+                    }
+                    let Some(machine_code) = master.current_machine_code.clone() else {
+                        reject(id, "材料缺少当前机组".to_string());
+                        continue;
+                    };
+                    let weight_t = master.weight_t.unwrap_or(0.0);
+                    if weight_t <= 0.0 {
+                        reject(id, "材料重量缺失".to_string());
+                        continue;
+                    }
+                    let item = PlanItem {
+                        version_id: version_id.to_string(),
+                        material_id: id.clone(),
+                        machine_code,
+                        plan_date: first_open_date,
+                        seq_no: 0,
+                        weight_t,
+                        source_type: "MANUAL".to_string(),
+                        locked_in_plan: false,
+                        force_release_in_plan: state.force_release_flag,
+                        violation_flags: None,
+                        urgent_level: None,
+                        sched_state: Some(state.sched_state.to_string()),
+                        assign_reason: Some("RUSH_INSERT".to_string()),
+                        steel_grade: master.steel_mark.clone(),
+                        width_mm: master.width_mm,
+                        thickness_mm: master.thickness_mm,
+                        contract_no: master.contract_no.clone(),
+                        due_date: master.due_date.map(|d| d.format("%Y-%m-%d").to_string()),
+                        scheduled_date: None,
+                        scheduled_machine_code: None,
+                    };
+                    (item, false)
+                }
+            };
+            item.urgent_level = Some(state.urgent_level.to_string());
+            rush.push(RushMaterial {
+                item,
+                scheduled,
+                urgent_level: state.urgent_level,
+                earliest_sched_date,
+                due_date: master.due_date,
+            });
+            states.push(state);
+        }
+
+        // 被挤出材料的交期（插单机组、开放日之后）
+        let rush_machines: HashSet<&str> =
+            rush.iter().map(|r| r.item.machine_code.as_str()).collect();
+        let affected_ids: Vec<String> = base_items
+            .iter()
+            .filter(|i| rush_machines.contains(i.machine_code.as_str()))
+            .filter(|i| i.plan_date >= first_open_date)
+            .map(|i| i.material_id.clone())
+            .collect();
+        let due_dates: HashMap<String, NaiveDate> = if affected_ids.is_empty() {
+            HashMap::new()
+        } else {
+            self.material_master_repo
+                .find_by_ids(&affected_ids)?
+                .into_iter()
+                .filter_map(|m| m.due_date.map(|d| (m.material_id, d)))
+                .collect()
+        };
+
+        let limits: HashMap<(String, NaiveDate), f64> = base_pools
+            .iter()
+            .map(|p| ((p.machine_code.clone(), p.plan_date), p.limit_capacity_t))
+            .collect();
+        let mut outcome = RushInsertionEngine::new().insert(&RushInsertionInput {
+            items: &base_items,
+            limits: &limits,
+            first_open_date,
+            due_dates: &due_dates,
+            rush: &rush,
+        });
+        outcome.infeasible.extend(infeasible);
+        outcome
+            .infeasible
+            .sort_by(|a, b| a.material_id.cmp(&b.material_id));
+
+        let displaced_ids: HashSet<&str> = outcome
+            .displaced
+            .iter()
+            .map(|d| d.material_id.as_str())
+            .collect();
+        for item in outcome.items.iter_mut() {
+            if displaced_ids.contains(item.material_id.as_str()) {
+                item.assign_reason = Some("RUSH_DISPLACED".to_string());
+            }
+        }
+
+        let impact = Self::rush_insertion_impact(&base_items, &outcome.items, &base_pools, &states);
+        Ok(RushInsertionPlan {
+            base_version,
+            first_open_date,
+            base_pools,
+            outcome,
+            impact,
+        })
+    }
+
+    fn rush_insertion_impact(
+        base_items: &[PlanItem],
+        items: &[PlanItem],
+        pools: &[CapacityPool],
+        states: &[MaterialState],
+    ) -> ImpactSummary {
+        let material_weights: HashMap<String, f64> = base_items
+            .iter()
+            .chain(items.iter())
+            .map(|i| (i.material_id.clone(), i.weight_t))
+            .collect();
+        ImpactSummaryEngine::new().generate_impact(
+            base_items,
+            items,
+            &pools_with_used(pools, base_items),
+            &pools_with_used(pools, items),
+            &[],
+            &[],
+            states,
+            &material_weights,
+        )
+    }
+
+    fn rush_insertion_response(
+        plan: RushInsertionPlan,
+        derived_version_id: Option<String>,
+    ) -> RushInsertionResponse {
+        let outcome = plan.outcome;
+        let message = format!(
+            "插单{}个, 无法插单{}个; 挤出{}个({:.1}t), 新增拖期{}天, 新增拖期材料{}个{}",
+            outcome.placements.len(),
+            outcome.infeasible.len(),
+            outcome.displaced.len(),
+            outcome.displaced_weight_t,
+            outcome.added_tardiness_days,
+            outcome.newly_late_count,
+            if derived_version_id.is_some() {
+                "，已生成草稿版本"
+            } else {
+                ""
+            }
+        );
+        RushInsertionResponse {
+            base_version_id: plan.base_version.version_id,
+            derived_version_id,
+            first_open_date: plan.first_open_date,
+            placements: outcome.placements,
+            displaced: outcome.displaced,
+            infeasible: outcome.infeasible,
+            displaced_weight_t: outcome.displaced_weight_t,
+            added_tardiness_days: outcome.added_tardiness_days,
+            newly_late_count: outcome.newly_late_count,
+            impact: plan.impact,
+            message,
+        }
+    }
+}
+
+/// 按明细重算产能池已用吨位/超限吨位
+fn pools_with_used(pools: &[CapacityPool], items: &[PlanItem]) -> Vec<CapacityPool> {
+    let mut used: HashMap<(&str, NaiveDate), f64> = HashMap::new();
+    for item in items {
+        *used
+            .entry((item.machine_code.as_str(), item.plan_date))
+            .or_insert(0.0) += item.weight_t;
+    }
+    pools
+        .iter()
+        .cloned()
+        .map(|mut p| {
+            p.used_capacity_t = used
+                .get(&(p.machine_code.as_str(), p.plan_date))
+                .copied()
+                .unwrap_or(0.0);
+            p.overflow_t = (p.used_capacity_t - p.limit_capacity_t).max(0.0);
+            p
+        })
+        .collect()
+}
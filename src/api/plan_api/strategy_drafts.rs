@@ -1,4 +1,16 @@
 use super::*;
+use crate::config::config_keys;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 草案时间跨度默认上限（天），可由全局配置 strategy_draft_max_days 覆盖
+pub const DEFAULT_STRATEGY_DRAFT_MAX_DAYS: i64 = 60;
+/// 草案时间跨度配置允许的最大值（天）
+const STRATEGY_DRAFT_MAX_DAYS_CEILING: i64 = 366;
+/// 草案并发试算线程上限（实际取 min(CPU 核数, 策略数, 上限)）
+const MAX_DRAFT_WORKERS: usize = 4;
+
+/// 草案生成进度回调
+pub type StrategyDraftProgressFn<'a> = &'a (dyn Fn(StrategyDraftProgress) + Sync);
 
 impl PlanApi {
     // ==========================================
@@ -19,15 +31,298 @@ impl PlanApi {
         strategies: Vec<String>,
         operator: &str,
     ) -> ApiResult<GenerateStrategyDraftsResponse> {
+        self.generate_strategy_drafts_with_progress(
+            base_version_id,
+            plan_date_from,
+            plan_date_to,
+            strategies,
+            operator,
+            None,
+            &|_| {},
+        )
+    }
+
+    /// 生成多策略草案（并发试算 + 进度回调 + 可取消）
+    ///
+    /// # 说明
+    /// - 各策略共享同一份只读输入快照，在工作线程中并发试算
+    /// - 每个策略开始/结束时回调 on_progress（由 Tauri 层转为前端事件）
+    /// - generation_id 为空时自动生成；生成期间可通过 cancel_strategy_draft_generation 取消，
+    ///   已完成的草案保留并返回，response.cancelled = true
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_strategy_drafts_with_progress(
+        &self,
+        base_version_id: &str,
+        plan_date_from: NaiveDate,
+        plan_date_to: NaiveDate,
+        strategies: Vec<String>,
+        operator: &str,
+        generation_id: Option<&str>,
+        on_progress: StrategyDraftProgressFn<'_>,
+    ) -> ApiResult<GenerateStrategyDraftsResponse> {
+        if strategies.is_empty() {
+            return Err(ApiError::InvalidInput("策略列表不能为空".to_string()));
+        }
+
+        let mut profiles = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        for raw_strategy_key in strategies {
+            let raw_strategy_key = raw_strategy_key.trim().to_string();
+            if raw_strategy_key.is_empty() {
+                continue;
+            }
+            if !seen.insert(raw_strategy_key.clone()) {
+                continue;
+            }
+
+            let profile = self
+                .recalc_engine
+                .resolve_strategy_profile(&raw_strategy_key)
+                .map_err(|e| {
+                    ApiError::InvalidInput(format!("策略解析失败（{}）: {}", raw_strategy_key, e))
+                })?;
+            profiles.push(profile);
+        }
+
+        let ctx = self.prepare_strategy_draft_context(
+            base_version_id,
+            plan_date_from,
+            plan_date_to,
+            operator,
+        )?;
+
+        let generation_id = generation_id
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let cancel = self.register_draft_generation(&generation_id)?;
+        let results = self.run_strategy_draft_jobs(
+            &ctx,
+            &profiles,
+            operator,
+            &generation_id,
+            &cancel,
+            on_progress,
+        );
+        self.unregister_draft_generation(&generation_id);
+
+        let cancelled = cancel.load(Ordering::Relaxed);
+        let mut summaries = Vec::with_capacity(results.len());
+        for result in results {
+            match result {
+                Ok(summary) => summaries.push(summary),
+                Err(_) if cancelled => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let draft_count = summaries.len();
+
+        Ok(GenerateStrategyDraftsResponse {
+            base_version_id: ctx.base_version_id,
+            plan_date_from: ctx.from,
+            plan_date_to: ctx.to,
+            drafts: summaries,
+            message: if cancelled {
+                format!("草案生成已取消，已完成{}个策略草案", draft_count)
+            } else {
+                format!("已生成{}个策略草案", draft_count)
+            },
+            generation_id,
+            cancelled,
+        })
+    }
+
+    /// 取消进行中的草案生成
+    ///
+    /// # 返回
+    /// - true: 已发出取消信号（正在试算的策略在下一个排产日边界停止，未开始的策略不再执行）
+    /// - false: 任务不存在或已结束
+    pub fn cancel_strategy_draft_generation(&self, generation_id: &str) -> ApiResult<bool> {
+        if generation_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("生成任务ID不能为空".to_string()));
+        }
+        let cancels = self
+            .draft_generation_cancels
+            .lock()
+            .map_err(|e| ApiError::InternalError(format!("锁获取失败: {}", e)))?;
+        match cancels.get(generation_id.trim()) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn register_draft_generation(&self, generation_id: &str) -> ApiResult<Arc<AtomicBool>> {
+        let mut cancels = self
+            .draft_generation_cancels
+            .lock()
+            .map_err(|e| ApiError::InternalError(format!("锁获取失败: {}", e)))?;
+        if cancels.contains_key(generation_id) {
+            return Err(ApiError::InvalidInput(format!(
+                "生成任务{}正在执行",
+                generation_id
+            )));
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        cancels.insert(generation_id.to_string(), flag.clone());
+        Ok(flag)
+    }
+
+    fn unregister_draft_generation(&self, generation_id: &str) {
+        if let Ok(mut cancels) = self.draft_generation_cancels.lock() {
+            cancels.remove(generation_id);
+        }
+    }
+
+    /// 并发执行多个策略的草案试算
+    ///
+    /// # 返回
+    /// - 与 profiles 一一对应（保持输入顺序）；取消后未开始的策略返回错误
+    pub(super) fn run_strategy_draft_jobs(
+        &self,
+        ctx: &StrategyDraftContext,
+        profiles: &[ResolvedStrategyProfile],
+        operator: &str,
+        generation_id: &str,
+        cancel: &AtomicBool,
+        on_progress: StrategyDraftProgressFn<'_>,
+    ) -> Vec<ApiResult<StrategyDraftSummary>> {
+        let total_count = profiles.len();
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(MAX_DRAFT_WORKERS)
+            .min(total_count)
+            .max(1);
+
+        let next_job = AtomicUsize::new(0);
+        let finished = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<ApiResult<StrategyDraftSummary>>>> =
+            Mutex::new((0..total_count).map(|_| None).collect());
+
+        let progress = |profile: &ResolvedStrategyProfile,
+                        status: StrategyDraftProgressStatus,
+                        draft_id: Option<String>,
+                        message: String| {
+            let finished_count = if status == StrategyDraftProgressStatus::Started {
+                finished.load(Ordering::Relaxed)
+            } else {
+                finished.fetch_add(1, Ordering::Relaxed) + 1
+            };
+            on_progress(StrategyDraftProgress {
+                generation_id: generation_id.to_string(),
+                strategy: profile.strategy_key.clone(),
+                status,
+                finished_count,
+                total_count,
+                draft_id,
+                message,
+            });
+        };
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next_job.fetch_add(1, Ordering::Relaxed);
+                    let Some(profile) = profiles.get(index) else {
+                        break;
+                    };
+
+                    let result = if cancel.load(Ordering::Relaxed) {
+                        progress(
+                            profile,
+                            StrategyDraftProgressStatus::Cancelled,
+                            None,
+                            "已取消".to_string(),
+                        );
+                        Err(Self::draft_generation_cancelled())
+                    } else {
+                        progress(
+                            profile,
+                            StrategyDraftProgressStatus::Started,
+                            None,
+                            format!("{} 试算中", profile.title_cn),
+                        );
+                        let result =
+                            self.generate_strategy_draft(ctx, profile, operator, Some(cancel));
+                        match &result {
+                            Ok(summary) => progress(
+                                profile,
+                                StrategyDraftProgressStatus::Completed,
+                                Some(summary.draft_id.clone()),
+                                summary.message.clone(),
+                            ),
+                            Err(_) if cancel.load(Ordering::Relaxed) => progress(
+                                profile,
+                                StrategyDraftProgressStatus::Cancelled,
+                                None,
+                                "已取消".to_string(),
+                            ),
+                            Err(e) => progress(
+                                profile,
+                                StrategyDraftProgressStatus::Failed,
+                                None,
+                                e.to_string(),
+                            ),
+                        }
+                        result
+                    };
+
+                    if let Ok(mut results) = results.lock() {
+                        results[index] = Some(result);
+                    }
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| {
+                r.unwrap_or_else(|| Err(ApiError::InternalError("草案试算结果缺失".to_string())))
+            })
+            .collect()
+    }
+
+    fn draft_generation_cancelled() -> ApiError {
+        ApiError::BusinessRuleViolation("草案生成已取消".to_string())
+    }
+
+    /// 草案时间跨度上限（全局配置 strategy_draft_max_days，默认 60 天）
+    fn strategy_draft_max_days(&self) -> i64 {
+        self.config_manager
+            .get_global_config_value(config_keys::STRATEGY_DRAFT_MAX_DAYS)
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|v| *v >= 1)
+            .map(|v| v.min(STRATEGY_DRAFT_MAX_DAYS_CEILING))
+            .unwrap_or(DEFAULT_STRATEGY_DRAFT_MAX_DAYS)
+    }
+
+    /// 草案生成前置校验 + 基准快照（多策略共享）
+    ///
+    /// # 校验
+    /// - 基准版本存在且为当前激活版本（避免发布时基准漂移导致不可复现）
+    /// - 时间跨度不超过 strategy_draft_max_days（默认 60 天）
+    pub(super) fn prepare_strategy_draft_context(
+        &self,
+        base_version_id: &str,
+        plan_date_from: NaiveDate,
+        plan_date_to: NaiveDate,
+        operator: &str,
+    ) -> ApiResult<StrategyDraftContext> {
         if base_version_id.trim().is_empty() {
             return Err(ApiError::InvalidInput("基准版本ID不能为空".to_string()));
         }
         if operator.trim().is_empty() {
             return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
         }
-        if strategies.is_empty() {
-            return Err(ApiError::InvalidInput("策略列表不能为空".to_string()));
-        }
 
         let (from, to) = if plan_date_to < plan_date_from {
             (plan_date_to, plan_date_from)
@@ -36,10 +331,12 @@ impl PlanApi {
         };
 
         let range_days = (to - from).num_days();
-        if range_days > 60 {
-            return Err(ApiError::InvalidInput(
-                "时间跨度过大，最多支持60天".to_string(),
-            ));
+        let max_days = self.strategy_draft_max_days();
+        if range_days > max_days {
+            return Err(ApiError::InvalidInput(format!(
+                "时间跨度过大，最多支持{}天",
+                max_days
+            )));
         }
 
         // 校验基准版本存在
@@ -71,94 +368,131 @@ impl PlanApi {
             .find_by_date_range(base_version_id, from, to)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
 
+        // 与 RecalcEngine 默认一致：固定三条机组（后续可改为从配置/机组表动态加载）
+        let machine_codes = vec!["H032".to_string(), "H033".to_string(), "H034".to_string()];
+
+        // 试算输入只读快照：各策略共享，避免并发试算时重复查库
+        let input_snapshot = self
+            .recalc_engine
+            .load_reschedule_snapshot(base_version_id, &machine_codes)
+            .map_err(|e| ApiError::InternalError(format!("加载试算输入失败: {}", e)))?;
+
         // 冻结项（locked_in_plan=1）在范围内需要计入草案快照，否则会被误判为“挤出”
-        let frozen_items_in_range: Vec<PlanItem> = self
-            .plan_item_repo
-            .find_frozen_items(base_version_id)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
-            .into_iter()
+        let frozen_items_in_range: Vec<PlanItem> = input_snapshot
+            .frozen_items
+            .iter()
             .filter(|item| item.plan_date >= from && item.plan_date <= to)
+            .cloned()
             .collect();
 
-        // 与 RecalcEngine 默认一致：固定三条机组（后续可改为从配置/机组表动态加载）
-        let machine_codes = vec!["H032".to_string(), "H033".to_string(), "H034".to_string()];
-
         let now = chrono::Local::now().naive_local();
-        let expires_at = now + chrono::Duration::hours(72);
-        let mut summaries = Vec::new();
 
-        let mut seen: HashSet<String> = HashSet::new();
-        for raw_strategy_key in strategies {
-            let raw_strategy_key = raw_strategy_key.trim().to_string();
-            if raw_strategy_key.is_empty() {
-                continue;
-            }
-            if !seen.insert(raw_strategy_key.clone()) {
-                continue;
-            }
+        Ok(StrategyDraftContext {
+            base_version_id: base_version_id.to_string(),
+            from,
+            to,
+            base_items_in_range,
+            frozen_items_in_range,
+            machine_codes,
+            input_snapshot,
+            created_at: now,
+            expires_at: now + chrono::Duration::hours(72),
+        })
+    }
 
-            let profile = self
-                .recalc_engine
-                .resolve_strategy_profile(&raw_strategy_key)
-                .map_err(|e| {
-                    ApiError::InvalidInput(format!("策略解析失败（{}）: {}", raw_strategy_key, e))
-                })?;
+    /// 按单个策略 profile 试算并落库一条草案
+    ///
+    /// # 参数
+    /// - cancel: 取消标记；置位后不再落库，返回“已取消”
+    pub(super) fn generate_strategy_draft(
+        &self,
+        ctx: &StrategyDraftContext,
+        profile: &ResolvedStrategyProfile,
+        operator: &str,
+        cancel: Option<&AtomicBool>,
+    ) -> ApiResult<StrategyDraftSummary> {
+        let base_version_id = ctx.base_version_id.as_str();
+        let (from, to) = (ctx.from, ctx.to);
+        let draft_id = uuid::Uuid::new_v4().to_string();
+        let is_cancelled = || cancel.is_some_and(|c| c.load(Ordering::Relaxed));
+
+        let reschedule = self
+            .recalc_engine
+            .execute_dry_run_with_snapshot(
+                base_version_id,
+                (from, to),
+                &ctx.machine_codes,
+                profile.base_strategy,
+                profile.parameters.clone(),
+                &ctx.input_snapshot,
+                cancel,
+            )
+            .map_err(|e| {
+                if is_cancelled() {
+                    Self::draft_generation_cancelled()
+                } else {
+                    ApiError::InternalError(format!("生成草案失败: {}", e))
+                }
+            })?;
 
-            let draft_id = uuid::Uuid::new_v4().to_string();
+        let mature_count = reschedule.mature_count;
+        let immature_count = reschedule.immature_count;
+        let total_capacity_used_t = reschedule.total_capacity_used;
+        let overflow_days = reschedule.overflow_days;
+        let reschedule_items = reschedule.plan_items;
 
-            let reschedule = self
-                .recalc_engine
-                .execute_reschedule(
-                    base_version_id,
-                    (from, to),
-                    &machine_codes,
-                    true,
-                    profile.base_strategy,
-                    profile.parameters.clone(),
-                )
-                .map_err(|e| ApiError::InternalError(format!("生成草案失败: {}", e)))?;
-
-            let mature_count = reschedule.mature_count;
-            let immature_count = reschedule.immature_count;
-            let total_capacity_used_t = reschedule.total_capacity_used;
-            let overflow_days = reschedule.overflow_days;
-            let reschedule_items = reschedule.plan_items;
-
-            let mut draft_items_in_range: Vec<PlanItem> =
-                Vec::with_capacity(frozen_items_in_range.len() + reschedule_items.len());
-
-            for mut item in frozen_items_in_range.clone() {
-                item.version_id = draft_id.clone();
-                draft_items_in_range.push(item);
-            }
+        let mut draft_items_in_range: Vec<PlanItem> =
+            Vec::with_capacity(ctx.frozen_items_in_range.len() + reschedule_items.len());
 
-            let frozen_items_count = frozen_items_in_range.len();
-            let mut calc_items_count = 0usize;
+        for mut item in ctx.frozen_items_in_range.clone() {
+            item.version_id = draft_id.clone();
+            draft_items_in_range.push(item);
+        }
 
-            for mut item in reschedule_items.into_iter() {
-                if item.plan_date < from || item.plan_date > to {
-                    continue;
-                }
-                item.version_id = draft_id.clone();
-                draft_items_in_range.push(item);
-                calc_items_count += 1;
+        let frozen_items_count = ctx.frozen_items_in_range.len();
+        let mut calc_items_count = 0usize;
+
+        for mut item in reschedule_items.into_iter() {
+            if item.plan_date < from || item.plan_date > to {
+                continue;
             }
+            item.version_id = draft_id.clone();
+            draft_items_in_range.push(item);
+            calc_items_count += 1;
+        }
 
-            let (
-                moved_count,
-                added_count,
-                removed_count,
-                squeezed_out_count,
-                diff_items,
-                diff_items_total,
-                diff_items_truncated,
-            ) = Self::diff_plan_items_detail(&base_items_in_range, &draft_items_in_range);
-
-            let summary = StrategyDraftSummary {
-                draft_id: draft_id.clone(),
-                base_version_id: base_version_id.to_string(),
-                strategy: profile.strategy_key.clone(),
-                plan_items_count: draft_items_in_range.len(),
+        let (
+            moved_count,
+            added_count,
+            removed_count,
+            squeezed_out_count,
+            diff_items,
+            diff_items_total,
+            diff_items_truncated,
+        ) = Self::diff_plan_items_detail(&ctx.base_items_in_range, &draft_items_in_range);
+
+        let objectives =
+            self.evaluate_plan_items(&mut draft_items_in_range, base_version_id, Some((from, to)))?;
+
+        let summary = StrategyDraftSummary {
+            draft_id: draft_id.clone(),
+            base_version_id: base_version_id.to_string(),
+            strategy: profile.strategy_key.clone(),
+            plan_items_count: draft_items_in_range.len(),
+            frozen_items_count,
+            calc_items_count,
+            mature_count,
+            immature_count,
+            total_capacity_used_t,
+            overflow_days,
+            moved_count,
+            added_count,
+            removed_count,
+            squeezed_out_count,
+            message: format!(
+                "{} | 排产{}(冻结{}+新排{}) | 成熟{} 未成熟{} | 预计产量{:.1}t | 超限机组日{} | 移动{} 新增{} 挤出{}",
+                profile.title_cn.as_str(),
+                draft_items_in_range.len(),
                 frozen_items_count,
                 calc_items_count,
                 mature_count,
@@ -167,76 +501,55 @@ impl PlanApi {
                 overflow_days,
                 moved_count,
                 added_count,
-                removed_count,
-                squeezed_out_count,
-                message: format!(
-                    "{} | 排产{}(冻结{}+新排{}) | 成熟{} 未成熟{} | 预计产量{:.1}t | 超限机组日{} | 移动{} 新增{} 挤出{}",
-                    profile.title_cn.as_str(),
-                    draft_items_in_range.len(),
-                    frozen_items_count,
-                    calc_items_count,
-                    mature_count,
-                    immature_count,
-                    total_capacity_used_t,
-                    overflow_days,
-                    moved_count,
-                    added_count,
-                    squeezed_out_count
-                ),
-            };
-
-            let params_json = profile.parameters_json();
-            let params_json = if params_json.is_null() {
-                None
-            } else {
-                Some(params_json.to_string())
-            };
-
-            let summary_json = serde_json::to_string(&summary)
-                .map_err(|e| ApiError::InternalError(format!("序列化草案摘要失败: {}", e)))?;
-            let diff_items_json = serde_json::to_string(&diff_items)
-                .map_err(|e| ApiError::InternalError(format!("序列化草案变更明细失败: {}", e)))?;
-
-            let entity = StrategyDraftEntity {
-                draft_id: draft_id.clone(),
-                base_version_id: base_version_id.to_string(),
-                plan_date_from: from,
-                plan_date_to: to,
-                strategy_key: profile.strategy_key.clone(),
-                strategy_base: profile.base_strategy.as_str().to_string(),
-                strategy_title_cn: profile.title_cn.clone(),
-                strategy_params_json: params_json,
-                status: StrategyDraftStatus::Draft,
-                created_by: operator.to_string(),
-                created_at: now,
-                expires_at,
-                published_as_version_id: None,
-                published_by: None,
-                published_at: None,
-                locked_by: None,
-                locked_at: None,
-                summary_json,
-                diff_items_json,
-                diff_items_total: diff_items_total as i64,
-                diff_items_truncated,
-            };
-
-            self.strategy_draft_repo
-                .insert(&entity)
-                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+                squeezed_out_count
+            ),
+            objectives: Some(objectives),
+        };
 
-            summaries.push(summary);
-        }
+        let params_json = profile.parameters_json();
+        let params_json = if params_json.is_null() {
+            None
+        } else {
+            Some(params_json.to_string())
+        };
 
-        let draft_count = summaries.len();
+        let summary_json = serde_json::to_string(&summary)
+            .map_err(|e| ApiError::InternalError(format!("序列化草案摘要失败: {}", e)))?;
+        let diff_items_json = serde_json::to_string(&diff_items)
+            .map_err(|e| ApiError::InternalError(format!("序列化草案变更明细失败: {}", e)))?;
 
-        Ok(GenerateStrategyDraftsResponse {
+        let entity = StrategyDraftEntity {
+            draft_id: draft_id.clone(),
             base_version_id: base_version_id.to_string(),
             plan_date_from: from,
             plan_date_to: to,
-            drafts: summaries,
-            message: format!("已生成{}个策略草案", draft_count),
-        })
+            strategy_key: profile.strategy_key.clone(),
+            strategy_base: profile.base_strategy.as_str().to_string(),
+            strategy_title_cn: profile.title_cn.clone(),
+            strategy_params_json: params_json,
+            status: StrategyDraftStatus::Draft,
+            created_by: operator.to_string(),
+            created_at: ctx.created_at,
+            expires_at: ctx.expires_at,
+            published_as_version_id: None,
+            published_by: None,
+            published_at: None,
+            locked_by: None,
+            locked_at: None,
+            summary_json,
+            diff_items_json,
+            diff_items_total: diff_items_total as i64,
+            diff_items_truncated,
+        };
+
+        if is_cancelled() {
+            return Err(Self::draft_generation_cancelled());
+        }
+        self.strategy_draft_repo
+            .insert(&entity)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok(summary)
     }
 
     /// 发布策略草案：生成正式版本（落库）
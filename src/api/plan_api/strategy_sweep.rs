@@ -0,0 +1,97 @@
+use super::*;
+
+impl PlanApi {
+    // ==========================================
+    // 策略参数扫描接口（自定义策略权重调优）
+    // ==========================================
+
+    /// 按网格/随机采样扫描自定义策略权重，返回 Pareto 最优集
+    ///
+    /// # 说明
+    /// - 每个参数组合生成一条策略草案（与 generate_strategy_drafts 同口径，dry-run + 落库）
+    /// - 目标向量取草案摘要中的多目标评价（PlanEvaluator）
+    /// - 任一扫描点可直接发布草案，或通过 save_custom_strategy 保存为自定义策略
+    pub fn run_strategy_sweep(
+        &self,
+        base_version_id: &str,
+        plan_date_from: NaiveDate,
+        plan_date_to: NaiveDate,
+        spec: StrategySweepSpec,
+        operator: &str,
+    ) -> ApiResult<StrategySweepResponse> {
+        let base_strategy = spec
+            .base_strategy
+            .trim()
+            .parse::<ScheduleStrategy>()
+            .map_err(|e| ApiError::InvalidInput(format!("基础策略非法: {}", e)))?;
+        let points = StrategySweepEngine::generate_points(&spec).map_err(ApiError::InvalidInput)?;
+
+        let ctx = self.prepare_strategy_draft_context(
+            base_version_id,
+            plan_date_from,
+            plan_date_to,
+            operator,
+        )?;
+
+        let sweep_id = uuid::Uuid::new_v4().to_string();
+        let profiles: Vec<ResolvedStrategyProfile> = points
+            .into_iter()
+            .enumerate()
+            .map(|(i, parameters)| ResolvedStrategyProfile {
+                strategy_key: format!("sweep:{}:{}", &sweep_id[..8], i + 1),
+                base_strategy,
+                title_cn: format!("参数扫描#{}", i + 1),
+                parameters: Some(parameters),
+            })
+            .collect();
+
+        // 各组合共享输入快照并发试算（与多策略草案同一执行器）
+        let results = self.run_strategy_draft_jobs(
+            &ctx,
+            &profiles,
+            operator,
+            &sweep_id,
+            &AtomicBool::new(false),
+            &|_| {},
+        );
+
+        let mut sweep_points = Vec::with_capacity(profiles.len());
+        for (i, (profile, result)) in profiles.into_iter().zip(results).enumerate() {
+            let summary = result?;
+            sweep_points.push(StrategySweepPoint {
+                index: i + 1,
+                draft_id: summary.draft_id,
+                strategy_key: profile.strategy_key,
+                parameters: profile.parameters.unwrap_or_default(),
+                objectives: summary.objectives.unwrap_or_default(),
+                pareto_optimal: false,
+                dominated_by_count: 0,
+            });
+        }
+
+        let objectives: Vec<_> = sweep_points.iter().map(|p| p.objectives.clone()).collect();
+        for (point, count) in sweep_points
+            .iter_mut()
+            .zip(StrategySweepEngine::dominated_counts(&objectives))
+        {
+            point.dominated_by_count = count;
+            point.pareto_optimal = count == 0;
+        }
+        let pareto_count = sweep_points.iter().filter(|p| p.pareto_optimal).count();
+
+        Ok(StrategySweepResponse {
+            message: format!(
+                "已扫描{}个参数组合，Pareto 最优{}个",
+                sweep_points.len(),
+                pareto_count
+            ),
+            sweep_id,
+            base_version_id: ctx.base_version_id,
+            plan_date_from: ctx.from,
+            plan_date_to: ctx.to,
+            base_strategy: base_strategy.as_str().to_string(),
+            points: sweep_points,
+            pareto_count,
+        })
+    }
+}
@@ -1,4 +1,9 @@
 use super::*;
+use crate::domain::action_log::ActionType;
+use crate::domain::undo::InversePayload;
+
+/// 物料级差异类型
+const VERSION_DIFF_CHANGE_TYPES: [&str; 4] = ["ADDED", "REMOVED", "MOVED", "SEQ_CHANGED"];
 
 impl PlanApi {
     // ==========================================
@@ -25,11 +30,11 @@ impl PlanApi {
         }
 
         // 1. 加载两个版本的排产明细
-        let items_a = self
+        let mut items_a = self
             .plan_item_repo
             .find_by_version(version_id_a)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-        let items_b = self
+        let mut items_b = self
             .plan_item_repo
             .find_by_version(version_id_b)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
@@ -101,6 +106,22 @@ impl PlanApi {
         // 7. 对比产能变化（按机组+日期）
         let capacity_delta = self.build_capacity_delta(version_id_a, version_id_b)?;
 
+        // 8. 明细级结构差异（合同交付 / 钢种结构 / 同日顺序）
+        self.enrich_plan_items(&mut items_a);
+        self.enrich_plan_items(&mut items_b);
+        let contract_delta = Some(VersionDiffEngine::contract_delivery_delta(
+            &items_a, &items_b,
+        ))
+        .filter(|v| !v.is_empty());
+        let grade_mix_delta =
+            Some(VersionDiffEngine::grade_mix_delta(&items_a, &items_b)).filter(|v| !v.is_empty());
+        let sequence_delta =
+            Some(VersionDiffEngine::sequence_delta(&items_a, &items_b)).filter(|v| !v.is_empty());
+
+        // 9. 路径突破 / 换辊次数变化
+        let (path_override_delta, roll_change_delta) =
+            self.build_route_roll_delta(version_id_a, version_id_b)?;
+
         Ok(VersionComparisonResult {
             version_id_a: version_id_a.to_string(),
             version_id_b: version_id_b.to_string(),
@@ -110,6 +131,11 @@ impl PlanApi {
             squeezed_out_count,
             risk_delta,
             capacity_delta,
+            contract_delta,
+            grade_mix_delta,
+            sequence_delta,
+            path_override_delta,
+            roll_change_delta,
             config_changes,
             message: format!(
                 "版本对比完成: 移动{}个, 新增{}个, 删除{}个, 挤出{}个",
@@ -261,6 +287,232 @@ impl PlanApi {
         Ok(Some(rows))
     }
 
+    /// 构建路径突破与换辊次数变化
+    ///
+    /// 口径：
+    /// - 两版本均无突破记录时 path_override_delta 为 None
+    /// - 换辊次数按机组对比，仅返回有变化的机组
+    fn build_route_roll_delta(
+        &self,
+        version_id_a: &str,
+        version_id_b: &str,
+    ) -> ApiResult<(Option<PathOverrideDelta>, Option<Vec<RollChangeDelta>>)> {
+        let counts_a = self
+            .plan_item_repo
+            .get_version_route_roll_counts(version_id_a)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let counts_b = self
+            .plan_item_repo
+            .get_version_route_roll_counts(version_id_b)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let confirmed_a = self.count_path_override_confirmed(version_id_a)?;
+        let confirmed_b = self.count_path_override_confirmed(version_id_b)?;
+
+        let path_override_delta = (counts_a.path_override_pending_count > 0
+            || counts_b.path_override_pending_count > 0
+            || confirmed_a > 0
+            || confirmed_b > 0)
+            .then(|| PathOverrideDelta {
+                pending_count_a: counts_a.path_override_pending_count,
+                pending_count_b: counts_b.path_override_pending_count,
+                pending_delta: counts_b.path_override_pending_count as i64
+                    - counts_a.path_override_pending_count as i64,
+                confirmed_count_a: confirmed_a,
+                confirmed_count_b: confirmed_b,
+                confirmed_delta: confirmed_b as i64 - confirmed_a as i64,
+            });
+
+        let roll_a: std::collections::BTreeMap<String, usize> =
+            counts_a.roll_change_by_machine.into_iter().collect();
+        let roll_b: std::collections::BTreeMap<String, usize> =
+            counts_b.roll_change_by_machine.into_iter().collect();
+        let mut machines: std::collections::BTreeSet<String> = roll_a.keys().cloned().collect();
+        machines.extend(roll_b.keys().cloned());
+
+        let roll_rows: Vec<RollChangeDelta> = machines
+            .into_iter()
+            .filter_map(|machine_code| {
+                let a = roll_a.get(&machine_code).copied().unwrap_or(0);
+                let b = roll_b.get(&machine_code).copied().unwrap_or(0);
+                (a != b).then(|| RollChangeDelta {
+                    machine_code,
+                    roll_change_count_a: a,
+                    roll_change_count_b: b,
+                    roll_change_delta: b as i64 - a as i64,
+                })
+            })
+            .collect();
+
+        Ok((
+            path_override_delta,
+            (!roll_rows.is_empty()).then_some(roll_rows),
+        ))
+    }
+
+    /// 本版本已确认路径突破且排入本版本的材料数
+    ///
+    /// 口径：按本版本的确认/拒绝/撤销/重做日志顺序回放（逆操作载荷 expected 即操作后状态），
+    /// 不读取全局 material_state.user_confirmed（其只反映当前最新状态，与版本无关）
+    fn count_path_override_confirmed(&self, version_id: &str) -> ApiResult<usize> {
+        let logs = self
+            .action_log_repo
+            .find_by_version_and_types(
+                version_id,
+                &[
+                    ActionType::PathOverrideConfirm.as_str(),
+                    ActionType::PathOverrideReject.as_str(),
+                    "UNDO",
+                    "REDO",
+                ],
+            )
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let mut confirmed: HashMap<String, bool> = HashMap::new();
+        for log in &logs {
+            match InversePayload::from_payload(log.payload_json.as_ref()) {
+                Some(InversePayload::PathOverride { expected, .. }) => {
+                    for s in expected {
+                        confirmed.insert(s.material_id, s.user_confirmed);
+                    }
+                }
+                Some(_) => {}
+                // 早期日志无逆操作载荷：按载荷中的材料ID回放
+                None => {
+                    let Some(payload) = log.payload_json.as_ref() else {
+                        continue;
+                    };
+                    let value = log.action_type == ActionType::PathOverrideConfirm.as_str();
+                    let failed: HashSet<&str> = payload
+                        .get("failed_material_ids")
+                        .and_then(|v| v.as_array())
+                        .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+                        .unwrap_or_default();
+                    let ids = payload
+                        .get("material_ids")
+                        .and_then(|v| v.as_array())
+                        .map(|a| a.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>())
+                        .or_else(|| {
+                            payload
+                                .get("material_id")
+                                .and_then(|v| v.as_str())
+                                .map(|id| vec![id])
+                        })
+                        .unwrap_or_default();
+                    for id in ids.into_iter().filter(|id| !failed.contains(id)) {
+                        confirmed.insert(id.to_string(), value);
+                    }
+                }
+            }
+        }
+        if !confirmed.values().any(|v| *v) {
+            return Ok(0);
+        }
+
+        let items = self
+            .plan_item_repo
+            .find_by_version(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        Ok(items
+            .iter()
+            .filter(|i| confirmed.get(&i.material_id).copied().unwrap_or(false))
+            .count())
+    }
+
+    /// 分页查询物料级版本差异
+    ///
+    /// # 参数
+    /// - change_type: 可选过滤（ADDED / REMOVED / MOVED / SEQ_CHANGED）
+    /// - machine_code: 可选过滤（匹配原机组或新机组）
+    /// - limit: 1-20000
+    /// - offset: >= 0
+    ///
+    /// # 说明
+    /// - 过滤/分页在 SQL 侧完成，大版本对比时前端按页加载
+    pub fn list_version_diff_items(
+        &self,
+        version_id_a: &str,
+        version_id_b: &str,
+        change_type: Option<&str>,
+        machine_code: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> ApiResult<VersionDiffItemPage> {
+        if version_id_a.trim().is_empty() || version_id_b.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        if limit <= 0 || limit > 20_000 {
+            return Err(ApiError::InvalidInput("limit必须在1-20000之间".to_string()));
+        }
+        if offset < 0 {
+            return Err(ApiError::InvalidInput("offset不能为负数".to_string()));
+        }
+
+        let change_type = change_type
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty());
+        if let Some(t) = change_type.as_deref() {
+            if !VERSION_DIFF_CHANGE_TYPES.contains(&t) {
+                return Err(ApiError::InvalidInput(format!(
+                    "不支持的差异类型: {}（可选: {}）",
+                    t,
+                    VERSION_DIFF_CHANGE_TYPES.join("/")
+                )));
+            }
+        }
+        let machine_code = machine_code.map(str::trim).filter(|s| !s.is_empty());
+
+        for version_id in [version_id_a, version_id_b] {
+            self.plan_version_repo
+                .find_by_id(version_id)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        }
+
+        let total_count = self
+            .plan_item_repo
+            .count_versions_diff(
+                version_id_a,
+                version_id_b,
+                change_type.as_deref(),
+                machine_code,
+            )
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let rows = self
+            .plan_item_repo
+            .find_versions_diff_paged(
+                version_id_a,
+                version_id_b,
+                change_type.as_deref(),
+                machine_code,
+                limit,
+                offset,
+            )
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok(VersionDiffItemPage {
+            version_id_a: version_id_a.to_string(),
+            version_id_b: version_id_b.to_string(),
+            total_count,
+            offset,
+            limit,
+            items: rows
+                .into_iter()
+                .map(|r| VersionDiffItem {
+                    material_id: r.material_id,
+                    change_type: r.change_type,
+                    machine_code_a: r.machine_code_a,
+                    plan_date_a: r.plan_date_a,
+                    seq_no_a: r.seq_no_a,
+                    machine_code_b: r.machine_code_b,
+                    plan_date_b: r.plan_date_b,
+                    seq_no_b: r.seq_no_b,
+                    weight_t: r.weight_t,
+                })
+                .collect(),
+        })
+    }
+
     fn risk_level_to_score(level: crate::domain::types::RiskLevel) -> f64 {
         match level {
             crate::domain::types::RiskLevel::Red => 90.0,
@@ -275,6 +527,7 @@ impl PlanApi {
     /// 说明：
     /// - plan_item 侧：使用 SQL 聚合（count/sum/min/max + diff counts）
     /// - risk_snapshot 侧：基于既有读模型聚合（mature/immature、overflow_days/overflow_t 等）
+    /// - objectives：多目标评价（与策略草案、决策层同一口径）
     pub fn compare_versions_kpi(
         &self,
         version_id_a: &str,
@@ -387,11 +640,16 @@ impl PlanApi {
             "KPI 汇总完成".to_string()
         };
 
+        let mut kpi_a = VersionKpiSummary::from_aggs(agg_a, risk_a);
+        let mut kpi_b = VersionKpiSummary::from_aggs(agg_b, risk_b);
+        kpi_a.objectives = Some(self.evaluate_version(version_id_a)?);
+        kpi_b.objectives = Some(self.evaluate_version(version_id_b)?);
+
         Ok(VersionComparisonKpiResult {
             version_id_a: version_id_a.to_string(),
             version_id_b: version_id_b.to_string(),
-            kpi_a: VersionKpiSummary::from_aggs(agg_a, risk_a),
-            kpi_b: VersionKpiSummary::from_aggs(agg_b, risk_b),
+            kpi_a,
+            kpi_b,
             diff_counts: VersionDiffCounts {
                 moved_count: diff_counts.moved_count,
                 added_count: diff_counts.added_count,
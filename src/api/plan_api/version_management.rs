@@ -25,6 +25,68 @@ impl PlanApi {
         note: Option<String>,
         created_by: String,
     ) -> ApiResult<String> {
+        self.create_version_with_lineage(
+            plan_id,
+            window_days,
+            frozen_from_date,
+            note,
+            created_by,
+            None,
+            VersionTrigger::Manual,
+        )
+    }
+
+    /// 创建新版本并记录谱系（父版本/触发方式写入 __meta_*）
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn create_version_with_lineage(
+        &self,
+        plan_id: String,
+        window_days: i32,
+        frozen_from_date: Option<NaiveDate>,
+        note: Option<String>,
+        created_by: String,
+        parent_version_id: Option<&str>,
+        trigger: VersionTrigger,
+    ) -> ApiResult<String> {
+        let mut version = self.build_lineage_version(
+            plan_id,
+            window_days,
+            frozen_from_date,
+            note,
+            created_by,
+            parent_version_id,
+            trigger,
+        )?;
+
+        // 保存到数据库（version_no 由仓储层在事务内分配，避免并发冲突）
+        self.plan_version_repo
+            .create_with_next_version_no(&mut version)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        // 记录ActionLog
+        self.action_log_repo
+            .insert(&Self::create_version_log(&version))
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok(version.version_id)
+    }
+
+    /// 构造带谱系的草稿版本（不落库）
+    ///
+    /// 说明：
+    /// - 校验方案与窗口天数，生成配置快照并写入 __meta_*（触发方式/父版本/备注）；
+    /// - version_no 由仓储层落库时分配。
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn build_lineage_version(
+        &self,
+        plan_id: String,
+        window_days: i32,
+        frozen_from_date: Option<NaiveDate>,
+        note: Option<String>,
+        created_by: String,
+        parent_version_id: Option<&str>,
+        trigger: VersionTrigger,
+    ) -> ApiResult<PlanVersion> {
         // 参数验证
         if plan_id.trim().is_empty() {
             return Err(ApiError::InvalidInput("方案ID不能为空".to_string()));
@@ -45,78 +107,67 @@ impl PlanApi {
 
         // 创建配置快照JSON（用于版本回滚/对比口径）
         // 注意：元信息（例如中文命名/备注）统一写入 __meta_*，避免污染“配置差异”与回滚恢复。
+        let note_text = note.as_deref().map(|s| s.trim()).filter(|s| !s.is_empty());
         let config_snapshot_json = Some(
             self.config_manager
                 .get_config_snapshot()
                 .map_err(|e| ApiError::InternalError(e.to_string()))?,
-        );
+        )
+        .map(|raw| {
+            match serde_json::from_str::<std::collections::HashMap<String, String>>(&raw) {
+                Ok(mut map) => {
+                    map.insert(META_TRIGGER.to_string(), trigger.as_str().to_string());
+                    if let Some(parent) = parent_version_id {
+                        map.insert(META_PARENT_VERSION_ID.to_string(), parent.to_string());
+                    }
+                    if let Some(note_text) = note_text {
+                        map.insert("__meta_version_name_cn".to_string(), note_text.to_string());
+                        map.insert("__meta_note".to_string(), note_text.to_string());
+                        map.insert(
+                            "__meta_note_created_at".to_string(),
+                            chrono::Local::now().to_rfc3339(),
+                        );
+                    }
+                    serde_json::to_string(&map).unwrap_or(raw)
+                }
+                Err(_) => raw,
+            }
+        });
 
-        // 创建PlanVersion实例（version_no 由仓储层在事务内分配，避免并发冲突）
-        let mut version = PlanVersion {
+        Ok(PlanVersion {
             version_id: uuid::Uuid::new_v4().to_string(),
-            plan_id: plan_id.clone(),
+            plan_id,
             version_no: 0,
             status: PlanVersionStatus::Draft,
             frozen_from_date,
             recalc_window_days: Some(window_days),
             config_snapshot_json,
-            created_by: Some(created_by.clone()),
+            created_by: Some(created_by),
             created_at: chrono::Local::now().naive_local(),
             revision: 1,
-        };
-
-        // 保存到数据库
-        self.plan_version_repo
-            .create_with_next_version_no(&mut version)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        // 写入备注/命名等元信息（不改变表结构，写到 config_snapshot_json.__meta_*）
-        if let Some(note_text) = note.as_deref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
-            // best-effort: meta 更新失败不影响版本创建，但会影响“命名显示/回滚可解释性”
-            if let Ok(mut map) = serde_json::from_str::<std::collections::HashMap<String, String>>(
-                version.config_snapshot_json.as_deref().unwrap_or("{}"),
-            ) {
-                map.insert("__meta_version_name_cn".to_string(), note_text.to_string());
-                map.insert("__meta_note".to_string(), note_text.to_string());
-                map.insert(
-                    "__meta_note_created_at".to_string(),
-                    chrono::Local::now().to_rfc3339(),
-                );
-
-                if let Ok(next_json) = serde_json::to_string(&map) {
-                    version.config_snapshot_json = Some(next_json);
-                    if let Err(e) = self.plan_version_repo.update(&version) {
-                        tracing::warn!("创建版本后写入 meta 失败: {}", e);
-                    }
-                }
-            }
-        }
+        })
+    }
 
-        // 记录ActionLog
-        let action_log = ActionLog {
+    /// CREATE_VERSION 日志（需在 version_no 分配后生成）
+    pub(super) fn create_version_log(version: &PlanVersion) -> ActionLog {
+        ActionLog {
             action_id: uuid::Uuid::new_v4().to_string(),
             version_id: Some(version.version_id.clone()),
             action_type: "CREATE_VERSION".to_string(),
             action_ts: chrono::Local::now().naive_local(),
-            actor: created_by,
+            actor: version.created_by.clone().unwrap_or_default(),
             payload_json: Some(serde_json::json!({
-                "plan_id": plan_id,
+                "plan_id": version.plan_id,
                 "version_no": version.version_no,
-                "window_days": window_days,
-                "frozen_from_date": frozen_from_date.map(|d| d.to_string()),
+                "window_days": version.recalc_window_days,
+                "frozen_from_date": version.frozen_from_date.map(|d| d.to_string()),
             })),
             impact_summary_json: None,
             machine_code: None,
             date_range_start: None,
             date_range_end: None,
             detail: Some(format!("创建版本: V{}", version.version_no)),
-        };
-
-        self.action_log_repo
-            .insert(&action_log)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        Ok(version.version_id)
+        }
     }
 
     /// 查询版本列表
@@ -253,10 +304,41 @@ impl PlanApi {
     /// - version_id: 版本ID
     /// - operator: 操作人
     ///
+    /// # 规则
+    /// - 仅 APPROVED 状态的版本可激活（提交/审批见 VersionApprovalApi）
+    /// - 存在红线硬违规时拒绝激活（需覆盖时见 activate_version_with_override）
+    ///
     /// # 返回
     /// - Ok(()): 成功
     /// - Err(ApiError): API错误
     pub fn activate_version(&self, version_id: &str, operator: &str) -> ApiResult<()> {
+        self.activate_version_with_override(version_id, operator, None)
+    }
+
+    /// 查询版本激活前红线校验报告
+    pub fn get_red_line_report(&self, version_id: &str) -> ApiResult<RedLineReport> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        self.activation_validator.validate_version(version_id)
+    }
+
+    /// 激活版本（可填写红线覆盖原因）
+    ///
+    /// # 参数
+    /// - version_id: 版本ID
+    /// - operator: 操作人
+    /// - override_reason: 红线覆盖原因（存在硬违规时必填）
+    ///
+    /// # 规则
+    /// - 激活前执行红线校验；存在 HARD 级违规且未填写覆盖原因时拒绝激活
+    /// - 校验报告摘要与覆盖原因写入 ACTIVATE_VERSION 操作日志
+    pub fn activate_version_with_override(
+        &self,
+        version_id: &str,
+        operator: &str,
+        override_reason: Option<&str>,
+    ) -> ApiResult<()> {
         // 参数验证
         if version_id.trim().is_empty() {
             return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
@@ -269,6 +351,49 @@ impl PlanApi {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?
             .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
 
+        // 审批流：只有审批通过的版本才能激活
+        match version.status {
+            PlanVersionStatus::Approved => {}
+            PlanVersionStatus::Active => {
+                return Err(ApiError::BusinessRuleViolation(format!(
+                    "版本{}已处于激活状态",
+                    version_id
+                )));
+            }
+            other => {
+                return Err(ApiError::InvalidStateTransition {
+                    from: other.to_string(),
+                    to: PlanVersionStatus::Active.to_string(),
+                });
+            }
+        }
+
+        // 红线校验：硬违规阻断激活，除非填写覆盖原因
+        let report = self.activation_validator.validate_version(version_id)?;
+        let override_reason = override_reason.map(str::trim).filter(|r| !r.is_empty());
+        if report.has_hard_violations() && override_reason.is_none() {
+            let summary = report
+                .count_by_code()
+                .into_iter()
+                .map(|(code, count)| format!("{}×{}", code, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "版本存在{}项红线硬违规({})，需填写覆盖原因后才能激活",
+                report.hard_count, summary
+            )));
+        }
+
+        // 现场变更通知：对比上一激活版本（激活前生成，随激活日志保存）
+        let previous_active = self
+            .plan_version_repo
+            .find_active_version(&version.plan_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let change_notice = self.build_activation_change_notice(
+            previous_active.as_ref().map(|v| v.version_id.as_str()),
+            version_id,
+        );
+
         // 同一方案只能有一个激活版本：仓储层在事务中完成归档+激活
         self.plan_version_repo
             .activate_version(version_id)
@@ -285,12 +410,31 @@ impl PlanApi {
                 "version_id": version_id,
                 "plan_id": version.plan_id,
                 "version_no": version.version_no,
+                "red_line_override_reason": override_reason,
+                "change_notice": change_notice,
+            })),
+            impact_summary_json: Some(serde_json::json!({
+                "red_line": {
+                    "hard_count": report.hard_count,
+                    "warning_count": report.warning_count,
+                    "by_code": report.count_by_code(),
+                    "baseline_version_id": report.baseline_version_id,
+                    "overridden": report.has_hard_violations(),
+                },
+                "change_notice": change_notice.as_ref().map(Self::change_notice_summary),
             })),
-            impact_summary_json: None,
             machine_code: None,
             date_range_start: None,
             date_range_end: None,
-            detail: Some(format!("激活版本: {}", version_id)),
+            detail: Some(
+                match override_reason.filter(|_| report.has_hard_violations()) {
+                    Some(reason) => format!(
+                        "激活版本: {} | 覆盖{}项红线硬违规: {}",
+                        version_id, report.hard_count, reason
+                    ),
+                    None => format!("激活版本: {}", version_id),
+                },
+            ),
         };
 
         self.action_log_repo
@@ -448,7 +592,7 @@ impl PlanApi {
     /// 版本回滚（激活历史版本，并按需恢复该版本记录的配置快照）
     ///
     /// 规则：
-    /// - 仅允许回滚到同一 plan 的历史版本
+    /// - 仅允许回滚到同一 plan 的已归档（曾激活）版本；已审批未激活的版本须经激活红线校验
     /// - 写入 ActionLog（包含 from/to/version_no/恢复配置数量/原因）
     /// - 发布刷新事件（触发决策读模型刷新）
     pub fn rollback_version(
@@ -492,6 +636,19 @@ impl PlanApi {
             )));
         }
 
+        // 回滚只能回到曾经激活过(已归档)的版本；已审批未激活的版本须走激活流程（红线校验）
+        if target.status != PlanVersionStatus::Archived {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "只能回滚到已归档的版本，目标版本当前状态为{}{}",
+                target.status,
+                if target.status == PlanVersionStatus::Approved {
+                    "（已审批版本请通过激活操作上线）"
+                } else {
+                    ""
+                }
+            )));
+        }
+
         let current_active = self
             .plan_version_repo
             .find_active_version(plan_id)
@@ -557,6 +714,10 @@ impl PlanApi {
             }
         }
 
+        // 现场变更通知：回滚同样是一次激活，对比当前激活版本
+        let change_notice =
+            self.build_activation_change_notice(from_version_id.as_deref(), target_version_id);
+
         // 2) 激活目标版本（事务内归档其他 ACTIVE）
         self.plan_version_repo
             .activate_version(target_version_id)
@@ -579,8 +740,11 @@ impl PlanApi {
                 "restored_config_count": restored_config_count,
                 "config_restore_skipped": config_restore_skipped,
                 "reason": reason,
+                "change_notice": change_notice,
             })),
-            impact_summary_json: None,
+            impact_summary_json: change_notice.as_ref().map(|notice| {
+                serde_json::json!({ "change_notice": Self::change_notice_summary(notice) })
+            }),
             machine_code: None,
             date_range_start: None,
             date_range_end: None,
@@ -0,0 +1,325 @@
+use super::*;
+use crate::api::path_rule_api::load_path_rule_config;
+use crate::domain::types::UrgentLevel;
+use crate::domain::undo::HistoryTag;
+use crate::engine::{
+    MergeContext, MergeMaterialFacts, MergeOutcome, PathRuleEngine, VersionMergeEngine,
+};
+
+impl PlanApi {
+    // ==========================================
+    // 版本三方合并接口
+    // ==========================================
+
+    /// 预览版本三方合并（不落库）
+    ///
+    /// # 参数
+    /// - ancestor_version_id: 共同祖先版本（调整前基线）
+    /// - adjusted_version_id: 人工调整版本（通常为激活版本）
+    /// - recalculated_version_id: 重算生成的新版本
+    ///
+    /// # 返回
+    /// - Ok(VersionMergePreviewResponse): 可回放变更 + 冲突清单
+    pub fn preview_version_merge(
+        &self,
+        ancestor_version_id: &str,
+        adjusted_version_id: &str,
+        recalculated_version_id: &str,
+    ) -> ApiResult<VersionMergePreviewResponse> {
+        let (_, change_count, outcome) = self.build_version_merge(
+            ancestor_version_id,
+            adjusted_version_id,
+            recalculated_version_id,
+            HashSet::new(),
+        )?;
+
+        Ok(VersionMergePreviewResponse {
+            ancestor_version_id: ancestor_version_id.to_string(),
+            adjusted_version_id: adjusted_version_id.to_string(),
+            recalculated_version_id: recalculated_version_id.to_string(),
+            manual_change_count: change_count,
+            message: format!(
+                "识别人工变更{}个: 可回放{}个, 冲突{}个, 已一致{}个",
+                change_count,
+                outcome.applied.len(),
+                outcome.conflicts.len(),
+                outcome.already_matching
+            ),
+            merged_items_count: outcome.merged_items.len(),
+            applied: outcome.applied,
+            conflicts: outcome.conflicts,
+            already_matching: outcome.already_matching,
+        })
+    }
+
+    /// 执行版本三方合并并保存为草稿版本
+    ///
+    /// # 参数
+    /// - resolutions: 冲突处理方式（预览中的每个冲突都必须给出处理方式）
+    ///
+    /// # 红线合规
+    /// - 材料不可排冲突不允许强制回放
+    /// - 合并结果保存为新草稿版本，不修改调整版本/重算版本
+    pub fn apply_version_merge(
+        &self,
+        ancestor_version_id: &str,
+        adjusted_version_id: &str,
+        recalculated_version_id: &str,
+        resolutions: Vec<MergeConflictResolution>,
+        operator: &str,
+    ) -> ApiResult<VersionMergeApplyResponse> {
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+
+        let mut forced = HashSet::new();
+        let mut keep_recalc = HashSet::new();
+        for r in &resolutions {
+            match r.resolution.trim().to_uppercase().as_str() {
+                "FORCE_MANUAL" => {
+                    forced.insert(r.material_id.clone());
+                }
+                "KEEP_RECALC" => {
+                    keep_recalc.insert(r.material_id.clone());
+                }
+                other => {
+                    return Err(ApiError::InvalidInput(format!(
+                        "材料{}的冲突处理方式无效: {}",
+                        r.material_id, other
+                    )))
+                }
+            }
+        }
+
+        let (recalculated, change_count, outcome) = self.build_version_merge(
+            ancestor_version_id,
+            adjusted_version_id,
+            recalculated_version_id,
+            forced,
+        )?;
+
+        // 所有剩余冲突必须显式选择 KEEP_RECALC（不可强制的冲突无法 FORCE_MANUAL）
+        let unresolved: Vec<String> = outcome
+            .conflicts
+            .iter()
+            .filter(|c| !keep_recalc.contains(&c.material_id))
+            .map(|c| format!("{}({})", c.material_id, c.reason))
+            .collect();
+        if !unresolved.is_empty() {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "存在未处理的合并冲突: {}",
+                unresolved.join(", ")
+            )));
+        }
+
+        // 合并草稿版本（沿用重算版本的窗口与冻结区）
+        let mut merged_version = self.build_lineage_version(
+            recalculated.plan_id.clone(),
+            recalculated.recalc_window_days.unwrap_or(30),
+            recalculated.frozen_from_date,
+            Some(format!("合并版本(重算V{})", recalculated.version_no)),
+            operator.to_string(),
+            Some(recalculated_version_id),
+            VersionTrigger::Merge,
+        )?;
+        let merged_version_id = merged_version.version_id.clone();
+
+        let merged_items: Vec<PlanItem> = outcome
+            .merged_items
+            .into_iter()
+            .map(|mut i| {
+                i.version_id = merged_version_id.clone();
+                i
+            })
+            .collect();
+
+        // 产能池随重算版本复制
+        let pools: Vec<_> = self
+            .capacity_repo
+            .find_by_version_id(recalculated_version_id)?
+            .into_iter()
+            .map(|mut p| {
+                p.version_id = merged_version_id.clone();
+                p
+            })
+            .collect();
+
+        let dropped_count = outcome.conflicts.len();
+        let log = ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: Some(merged_version_id.clone()),
+            action_type: "MERGE_VERSIONS".to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "ancestor_version_id": ancestor_version_id,
+                "adjusted_version_id": adjusted_version_id,
+                "recalculated_version_id": recalculated_version_id,
+                "applied_materials": outcome.applied.iter().map(|c| &c.material_id).collect::<Vec<_>>(),
+                "forced_materials": outcome.forced.iter().map(|c| &c.material_id).collect::<Vec<_>>(),
+                "dropped_materials": outcome.conflicts.iter().map(|c| &c.material_id).collect::<Vec<_>>(),
+            })),
+            impact_summary_json: Some(serde_json::json!({
+                "manual_change_count": change_count,
+                "applied_count": outcome.applied.len(),
+                "forced_count": outcome.forced.len(),
+                "dropped_count": dropped_count,
+                "already_matching": outcome.already_matching,
+                "plan_items_count": merged_items.len(),
+            })),
+            machine_code: None,
+            date_range_start: None,
+            date_range_end: None,
+            detail: Some(format!(
+                "合并人工调整: 回放{}个, 强制{}个, 放弃{}个",
+                outcome.applied.len(),
+                outcome.forced.len(),
+                dropped_count
+            )),
+        };
+
+        // 版本、明细、产能池与日志同一事务写入，失败不留孤儿草稿
+        self.plan_version_repo
+            .create_with_contents(&mut merged_version, &merged_items, &pools, |v| {
+                vec![Self::create_version_log(v), log]
+            })
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok(VersionMergeApplyResponse {
+            merged_version_id,
+            applied_count: outcome.applied.len(),
+            forced_count: outcome.forced.len(),
+            dropped_count,
+            merged_items_count: merged_items.len(),
+            message: "合并完成，已生成草稿版本".to_string(),
+        })
+    }
+
+    // ==========================================
+    // 内部: 合并计算
+    // ==========================================
+
+    fn build_version_merge(
+        &self,
+        ancestor_version_id: &str,
+        adjusted_version_id: &str,
+        recalculated_version_id: &str,
+        forced_material_ids: HashSet<String>,
+    ) -> ApiResult<(PlanVersion, usize, MergeOutcome)> {
+        let ancestor = self.load_merge_version(ancestor_version_id)?;
+        let adjusted = self.load_merge_version(adjusted_version_id)?;
+        let recalculated = self.load_merge_version(recalculated_version_id)?;
+
+        if adjusted.version_id == recalculated.version_id {
+            return Err(ApiError::InvalidInput(
+                "调整版本与重算版本不能相同".to_string(),
+            ));
+        }
+        if ancestor.plan_id != adjusted.plan_id || adjusted.plan_id != recalculated.plan_id {
+            return Err(ApiError::InvalidInput(
+                "参与合并的版本必须属于同一方案".to_string(),
+            ));
+        }
+
+        let ancestor_items = self.plan_item_repo.find_by_version(ancestor_version_id)?;
+        let adjusted_items = self.plan_item_repo.find_by_version(adjusted_version_id)?;
+        let recalculated_items = self
+            .plan_item_repo
+            .find_by_version(recalculated_version_id)?;
+
+        // 调整版本上的移动日志（MOVE_ITEMS / LocalAdjust / 移动的重做）
+        // - 已被撤销的操作（含被撤销的重做）净移动为零，不计入
+        // - UNDO 日志本身只是还原，不计入
+        let move_logs = self.action_log_repo.find_by_version_and_types(
+            adjusted_version_id,
+            &["MOVE_ITEMS", "LocalAdjust", "UNDO", "REDO"],
+        )?;
+        let undone_action_ids: HashSet<String> = move_logs
+            .iter()
+            .filter(|log| log.action_type == "UNDO")
+            .filter_map(|log| HistoryTag::from_payload(log.payload_json.as_ref()))
+            .map(|tag| tag.target_action_id)
+            .collect();
+        let logged_material_ids: HashSet<String> = move_logs
+            .into_iter()
+            .filter(|log| log.action_type != "UNDO" && !undone_action_ids.contains(&log.action_id))
+            .filter_map(|log| log.payload_json)
+            .filter_map(|payload| payload.get("moved_materials").cloned())
+            .filter_map(|v| v.as_array().cloned())
+            .flatten()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect();
+
+        let engine = VersionMergeEngine::new();
+        let changes =
+            engine.extract_manual_changes(&ancestor_items, &adjusted_items, &logged_material_ids);
+
+        // 材料事实: 人工变更材料 + 重算版本材料（路径锚点）
+        let mut material_ids: Vec<String> = changes.iter().map(|c| c.material_id.clone()).collect();
+        material_ids.extend(recalculated_items.iter().map(|i| i.material_id.clone()));
+        material_ids.sort();
+        material_ids.dedup();
+
+        let specs = self
+            .material_master_repo
+            .find_spec_lite_by_ids(&material_ids)?;
+        let change_ids: Vec<String> = changes.iter().map(|c| c.material_id.clone()).collect();
+        let snapshots = self
+            .material_state_repo
+            .find_snapshots_by_material_ids(&change_ids)?;
+        let snapshot_map: HashMap<String, MaterialStateSnapshotLite> = snapshots
+            .into_iter()
+            .map(|s| (s.material_id.clone(), s))
+            .collect();
+
+        let mut facts = HashMap::with_capacity(material_ids.len());
+        for id in &material_ids {
+            let snapshot = snapshot_map.get(id);
+            let spec = specs.get(id);
+            facts.insert(
+                id.clone(),
+                MergeMaterialFacts {
+                    sched_state: snapshot.and_then(|s| s.parsed_sched_state()),
+                    earliest_sched_date: snapshot.and_then(|s| s.earliest_sched_date),
+                    urgent_level: snapshot
+                        .map(|s| s.parsed_urgent_level())
+                        .unwrap_or(UrgentLevel::L0),
+                    width_mm: spec.and_then(|s| s.width_mm),
+                    thickness_mm: spec.and_then(|s| s.thickness_mm),
+                },
+            );
+        }
+
+        let capacity_limits = self
+            .capacity_repo
+            .find_by_version_id(recalculated_version_id)?
+            .into_iter()
+            .map(|p| ((p.machine_code, p.plan_date), p.limit_capacity_t))
+            .collect();
+
+        let path_rule_config = load_path_rule_config(&self.config_manager);
+        let path_rule_engine = PathRuleEngine::new(path_rule_config.clone());
+        let ctx = MergeContext {
+            facts,
+            capacity_limits,
+            path_rule_engine: if path_rule_config.enabled {
+                Some(&path_rule_engine)
+            } else {
+                None
+            },
+            forced_material_ids,
+        };
+
+        let outcome = engine.merge(recalculated_version_id, &changes, &recalculated_items, &ctx);
+        Ok((recalculated, changes.len(), outcome))
+    }
+
+    fn load_merge_version(&self, version_id: &str) -> ApiResult<PlanVersion> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        self.plan_version_repo
+            .find_by_id(version_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))
+    }
+}
@@ -0,0 +1,296 @@
+use super::*;
+use crate::config::config_keys;
+use crate::domain::material::{MaterialMaster, MaterialState};
+use crate::domain::types::SchedState;
+use crate::engine::weight_learning::{PreferencePair, WeightLearningEngine};
+
+/// 分析窗口上限（天）
+const MAX_LEARNING_WINDOW_DAYS: i64 = 366;
+/// 偏好样本上限（超出截断，避免坐标上升耗时过长）
+pub const MAX_PREFERENCE_PAIRS: usize = 5000;
+
+type MaterialPair = (MaterialMaster, MaterialState);
+
+impl PlanApi {
+    // ==========================================
+    // 人工调整反推策略权重（离线分析）
+    // ==========================================
+
+    /// 从操作日志中挖掘人工调整，拟合能更好复现计划员选择的策略权重
+    ///
+    /// # 样本口径
+    /// - MOVE_ITEMS: 提前移动 => 被移动材料优先于目标机组 (新日期, 原日期] 内的引擎排产项；
+    ///   推后移动 => 目标机组 [原日期, 新日期) 内的引擎排产项优先于被移动材料
+    /// - SET_URGENT(设为紧急) / FORCE_RELEASE: 以调整当日距交期天数作为紧急阈值建议的证据
+    /// - 材料特征取当前主数据/状态，库龄按调整日期回推；锁定/强制放行材料不参与拟合（红线）
+    /// - 旧版 MOVE_ITEMS 日志无原位置明细，计入 legacy_move_log_count 后跳过
+    ///
+    /// # 说明
+    /// 只读分析，不修改任何配置；学到的参数可通过 save_custom_strategy 保存为自定义策略
+    pub fn learn_strategy_weights(
+        &self,
+        analysis_from: NaiveDate,
+        analysis_to: NaiveDate,
+        strategy_key: &str,
+    ) -> ApiResult<StrategyWeightLearningResponse> {
+        if analysis_from > analysis_to {
+            return Err(ApiError::InvalidInput(
+                "分析开始日期不能晚于结束日期".to_string(),
+            ));
+        }
+        if (analysis_to - analysis_from).num_days() + 1 > MAX_LEARNING_WINDOW_DAYS {
+            return Err(ApiError::InvalidInput(format!(
+                "分析窗口过大，最多支持{}天",
+                MAX_LEARNING_WINDOW_DAYS
+            )));
+        }
+        let profile = self
+            .recalc_engine
+            .resolve_strategy_profile(strategy_key)
+            .map_err(|e| {
+                ApiError::InvalidInput(format!("策略解析失败（{}）: {}", strategy_key.trim(), e))
+            })?;
+
+        let logs = self
+            .action_log_repo
+            .find_by_time_range(
+                analysis_from.and_hms_opt(0, 0, 0).unwrap_or_default(),
+                analysis_to.and_hms_opt(23, 59, 59).unwrap_or_default(),
+            )
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let today = chrono::Local::now().date_naive();
+        let mut materials: HashMap<String, Option<MaterialPair>> = HashMap::new();
+        let mut version_items: HashMap<String, Vec<PlanItem>> = HashMap::new();
+
+        let mut pairs: Vec<PreferencePair> = Vec::new();
+        let mut excluded_pair_count = 0;
+        let mut pairs_truncated = false;
+        let mut move_log_count = 0;
+        let mut legacy_move_log_count = 0;
+        let mut urgent_log_count = 0;
+        let mut force_release_log_count = 0;
+        let mut days_to_due_evidence: Vec<i64> = Vec::new();
+
+        // 日志按时间倒序返回；样本截断时优先保留最近的调整
+        for log in &logs {
+            let action_date = log.action_ts.date();
+            let payload = log.payload_json.as_ref();
+            match log.action_type.as_str() {
+                "MOVE_ITEMS" => {
+                    move_log_count += 1;
+                    let moves = payload
+                        .and_then(|p| p.get("moves"))
+                        .and_then(|m| m.as_array());
+                    let (Some(version_id), Some(moves)) = (log.version_id.as_deref(), moves) else {
+                        legacy_move_log_count += 1;
+                        continue;
+                    };
+                    if pairs_truncated {
+                        continue;
+                    }
+
+                    if !version_items.contains_key(version_id) {
+                        let items = self
+                            .plan_item_repo
+                            .find_by_version(version_id)
+                            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+                        version_items.insert(version_id.to_string(), items);
+                    }
+                    let items = &version_items[version_id];
+                    let moved_ids: HashSet<&str> = moves
+                        .iter()
+                        .filter_map(|m| m.get("material_id").and_then(|v| v.as_str()))
+                        .collect();
+
+                    for mv in moves {
+                        let (Some(material_id), Some(from_date), Some(to_date), Some(to_machine)) = (
+                            mv.get("material_id").and_then(|v| v.as_str()),
+                            json_date(mv.get("from_date")),
+                            json_date(mv.get("to_date")),
+                            mv.get("to_machine").and_then(|v| v.as_str()),
+                        ) else {
+                            continue;
+                        };
+                        let pulled_forward = match to_date.cmp(&from_date) {
+                            std::cmp::Ordering::Less => true,
+                            std::cmp::Ordering::Greater => false,
+                            std::cmp::Ordering::Equal => continue,
+                        };
+
+                        for item in items.iter().filter(|i| {
+                            i.machine_code == to_machine
+                                && i.source_type != "MANUAL"
+                                && !moved_ids.contains(i.material_id.as_str())
+                                && if pulled_forward {
+                                    i.plan_date > to_date && i.plan_date <= from_date
+                                } else {
+                                    i.plan_date >= from_date && i.plan_date < to_date
+                                }
+                        }) {
+                            let moved = self.load_material_pair(&mut materials, material_id)?;
+                            let other =
+                                self.load_material_pair(&mut materials, &item.material_id)?;
+                            let (Some(moved), Some(other)) = (moved, other) else {
+                                excluded_pair_count += 1;
+                                continue;
+                            };
+                            if is_red_line_state(&moved.1) || is_red_line_state(&other.1) {
+                                excluded_pair_count += 1;
+                                continue;
+                            }
+                            if pairs.len() >= MAX_PREFERENCE_PAIRS {
+                                pairs_truncated = true;
+                                break;
+                            }
+
+                            let days_since = (today - action_date).num_days();
+                            let moved = as_of(moved, days_since);
+                            let other = as_of(other, days_since);
+                            let (winner, loser) = if pulled_forward {
+                                (moved, other)
+                            } else {
+                                (other, moved)
+                            };
+                            pairs.push(PreferencePair {
+                                winner,
+                                loser,
+                                today: action_date,
+                            });
+                        }
+                        if pairs_truncated {
+                            break;
+                        }
+                    }
+                }
+                "SET_URGENT" | "FORCE_RELEASE" => {
+                    if log.action_type == "SET_URGENT" {
+                        let flag = payload
+                            .and_then(|p| p.get("manual_urgent_flag"))
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false);
+                        if !flag {
+                            continue;
+                        }
+                        urgent_log_count += 1;
+                    } else {
+                        force_release_log_count += 1;
+                    }
+
+                    let material_ids = payload
+                        .and_then(|p| p.get("material_ids"))
+                        .and_then(|v| v.as_array())
+                        .map(|ids| ids.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    for material_id in material_ids {
+                        let due_date = self
+                            .load_material_pair(&mut materials, material_id)?
+                            .and_then(|(master, _)| master.due_date);
+                        if let Some(due_date) = due_date {
+                            days_to_due_evidence.push((due_date - action_date).num_days());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let engine = WeightLearningEngine::new();
+        let outcome = engine.learn(&pairs, profile.base_strategy, profile.parameters.as_ref());
+        let n1_days = self.urgent_threshold_days(config_keys::URGENT_N1_DAYS, 3);
+        let n2_days = self.urgent_threshold_days(config_keys::URGENT_N2_DAYS, 7);
+        let rule_suggestions =
+            engine.suggest_urgency_rules(&days_to_due_evidence, n1_days, n2_days);
+
+        let message = if pairs.is_empty() {
+            "分析窗口内没有可用的人工移动记录，权重保持不变".to_string()
+        } else {
+            format!(
+                "基于{}条人工调整样本：一致率 {:.1}% → {:.1}%{}",
+                pairs.len(),
+                outcome.baseline.rate * 100.0,
+                outcome.learned.rate * 100.0,
+                if rule_suggestions.is_empty() {
+                    String::new()
+                } else {
+                    format!("，另有{}条紧急阈值建议", rule_suggestions.len())
+                }
+            )
+        };
+
+        Ok(StrategyWeightLearningResponse {
+            analysis_from,
+            analysis_to,
+            strategy_key: profile.strategy_key,
+            base_strategy: profile.base_strategy.as_str().to_string(),
+            move_log_count,
+            legacy_move_log_count,
+            urgent_log_count,
+            force_release_log_count,
+            pair_count: pairs.len(),
+            excluded_pair_count,
+            pairs_truncated,
+            baseline_agreement: outcome.baseline,
+            learned_agreement: outcome.learned,
+            learned_parameters: outcome.learned_parameters,
+            urgent_n1_days: n1_days,
+            urgent_n2_days: n2_days,
+            rule_suggestions,
+            message,
+        })
+    }
+
+    /// 按ID加载材料主数据+状态（带缓存；任一缺失返回 None）
+    fn load_material_pair(
+        &self,
+        cache: &mut HashMap<String, Option<MaterialPair>>,
+        material_id: &str,
+    ) -> ApiResult<Option<MaterialPair>> {
+        if let Some(cached) = cache.get(material_id) {
+            return Ok(cached.clone());
+        }
+        let master = self
+            .material_master_repo
+            .find_by_id(material_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let state = self
+            .material_state_repo
+            .find_by_id(material_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let pair = master.zip(state);
+        cache.insert(material_id.to_string(), pair.clone());
+        Ok(pair)
+    }
+
+    fn urgent_threshold_days(&self, key: &str, default: i64) -> i64 {
+        self.config_manager
+            .get_global_config_value(key)
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .unwrap_or(default)
+    }
+}
+
+fn json_date(value: Option<&Value>) -> Option<NaiveDate> {
+    value
+        .and_then(|v| v.as_str())
+        .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+}
+
+fn is_red_line_state(state: &MaterialState) -> bool {
+    matches!(
+        state.sched_state,
+        SchedState::Locked | SchedState::ForceRelease
+    )
+}
+
+/// 将库龄回推到调整当日（当前快照 - 距今天数）
+fn as_of(pair: MaterialPair, days_since: i64) -> MaterialPair {
+    let (mut master, mut state) = pair;
+    let shift = days_since.clamp(0, i32::MAX as i64) as i32;
+    state.stock_age_days = (state.stock_age_days - shift).max(0);
+    state.rolling_output_age_days = (state.rolling_output_age_days - shift).max(0);
+    master.stock_age_days = master.stock_age_days.map(|d| (d - shift).max(0));
+    (master, state)
+}
@@ -0,0 +1,409 @@
+// ==========================================
+// 热轧精整排产系统 - 生产实绩 API
+// ==========================================
+// 职责:
+// - 生产实绩文件导入（材料完工 + 换辊累计吨位推进）
+// - 计划 vs 实绩对账（机组×日 执行率、顺序偏差、未完工顺延）
+// - 对账结果查询（趋势分析）
+// 说明:
+// - 实绩按导入时的激活版本对账；无激活版本时仅记录完工
+// - 对账窗口内重复导入会覆盖重算，保证结果幂等
+// ==========================================
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::domain::action_log::ActionLog;
+use crate::domain::material::{DqLevel, DqSummary, DqViolation, ImportSourceInfo};
+use crate::domain::production::{CarryOverItem, PlanAdherenceRecord, ProductionActual};
+use crate::engine::{PlanAdherenceEngine, RollCampaignEngine};
+use crate::importer::file_parser::ParseOptions;
+use crate::importer::production_actuals::{
+    ProductionActualsParser, PRODUCTION_ACTUALS_IMPORT_TYPE,
+};
+use crate::repository::action_log_repo::ActionLogRepository;
+use crate::repository::plan_repo::{PlanItemRepository, PlanVersionRepository};
+use crate::repository::production_actual_repo::{
+    CampaignTonnageUpdate, ProductionActualRepository,
+};
+use crate::repository::roller_repo::RollerCampaignRepository;
+
+/// 实绩导入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductionActualsImportResponse {
+    pub batch_id: String,
+    /// 对账版本（导入时的激活版本）
+    pub version_id: Option<String>,
+    /// 成功回填的实绩条数
+    pub imported: usize,
+    /// 累计吨位被推进的换辊窗口数
+    pub campaigns_updated: usize,
+    pub dq_summary: DqSummary,
+    pub dq_violations: Vec<DqViolation>,
+    /// 本次对账覆盖的 机组×日 记录
+    pub adherence: Vec<PlanAdherenceRecord>,
+    /// 本次对账发现的未完工顺延项数
+    pub carry_over_count: usize,
+    pub elapsed_ms: i64,
+    pub source_info: ImportSourceInfo,
+}
+
+/// 计划执行报告（按机组×日明细 + 区间汇总）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanAdherenceReport {
+    pub version_id: String,
+    pub rows: Vec<PlanAdherenceRecord>,
+    pub total_planned_weight_t: f64,
+    pub total_actual_weight_t: f64,
+    pub total_matched_weight_t: f64,
+    pub total_carry_over_count: i32,
+    pub total_sequence_deviation_count: i32,
+    /// 区间整体执行率（按计划吨位加权）
+    pub overall_adherence_rate: Option<f64>,
+}
+
+pub struct ProductionApi {
+    repo: Arc<ProductionActualRepository>,
+    plan_version_repo: Arc<PlanVersionRepository>,
+    plan_item_repo: Arc<PlanItemRepository>,
+    roller_campaign_repo: Arc<RollerCampaignRepository>,
+    action_log_repo: Arc<ActionLogRepository>,
+}
+
+impl ProductionApi {
+    pub fn new(
+        repo: Arc<ProductionActualRepository>,
+        plan_version_repo: Arc<PlanVersionRepository>,
+        plan_item_repo: Arc<PlanItemRepository>,
+        roller_campaign_repo: Arc<RollerCampaignRepository>,
+        action_log_repo: Arc<ActionLogRepository>,
+    ) -> Self {
+        Self {
+            repo,
+            plan_version_repo,
+            plan_item_repo,
+            roller_campaign_repo,
+            action_log_repo,
+        }
+    }
+
+    // ==========================================
+    // 实绩导入
+    // ==========================================
+
+    /// 导入生产实绩文件
+    ///
+    /// # DQ 规则（在文件级规则之外）
+    /// - 材料号不存在于材料主数据 → ERROR
+    /// - 材料已有实绩 → CONFLICT（不覆盖已回填实绩）
+    /// - 实际机组无进行中的换辊窗口 → WARNING（仅不推进累计吨位）
+    pub fn import_actuals(
+        &self,
+        file_path: &str,
+        options: ParseOptions,
+        operator: &str,
+    ) -> ApiResult<ProductionActualsImportResponse> {
+        let ext = std::path::Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if !matches!(ext.as_str(), "csv" | "xlsx" | "xls") {
+            return Err(ApiError::ImportError(
+                "当前仅支持 .csv/.xlsx/.xls 格式文件导入".to_string(),
+            ));
+        }
+
+        let start_time = std::time::Instant::now();
+        let batch_id = uuid::Uuid::new_v4().to_string();
+
+        // === 步骤 1: 解析文件 + 行级 DQ ===
+        let parsed = ProductionActualsParser::default()
+            .parse_file(file_path, options)
+            .map_err(|e| ApiError::ImportError(format!("实绩导入失败: {}", e)))?;
+        let mut violations = parsed.violations;
+        let mut blocked = parsed.blocked;
+        let mut conflict = parsed.conflict;
+
+        // === 步骤 2: 库级 DQ (材料存在性 / 重复回填) ===
+        let ids: Vec<String> = parsed
+            .records
+            .iter()
+            .map(|(_, a)| a.material_id.clone())
+            .collect();
+        let known = self.repo.find_known_material_ids(&ids)?;
+        let completed = self.repo.find_completed_material_ids(&ids)?;
+
+        let mut actuals: Vec<ProductionActual> = Vec::new();
+        for (row_number, actual) in parsed.records {
+            let (level, message) = if !known.contains(&actual.material_id) {
+                (DqLevel::Error, "材料不存在于材料主数据")
+            } else if completed.contains(&actual.material_id) {
+                (DqLevel::Conflict, "材料已有实绩，跳过重复回填")
+            } else {
+                actuals.push(actual);
+                continue;
+            };
+            match level {
+                DqLevel::Error => blocked += 1,
+                _ => conflict += 1,
+            }
+            violations.push(DqViolation {
+                row_number,
+                material_id: Some(actual.material_id.clone()),
+                level,
+                field: "material_id".to_string(),
+                message: format!("{}: {}", message, actual.material_id),
+            });
+        }
+
+        // === 步骤 3: 换辊累计吨位 (按激活版本的进行中窗口) ===
+        let version_id = self.plan_version_repo.find_latest_active_version_id()?;
+        let mut campaign_updates = Vec::new();
+        if let Some(vid) = version_id.as_deref() {
+            let mut tonnage_by_machine: BTreeMap<&str, f64> = BTreeMap::new();
+            for actual in &actuals {
+                *tonnage_by_machine
+                    .entry(actual.machine_code.as_str())
+                    .or_default() += actual.actual_weight_t;
+            }
+
+            let roll_engine = RollCampaignEngine::new();
+            for (machine_code, tonnage) in tonnage_by_machine {
+                let Some(mut campaign) = self
+                    .roller_campaign_repo
+                    .find_active_campaign(vid, machine_code)?
+                else {
+                    violations.push(DqViolation {
+                        row_number: 0,
+                        material_id: None,
+                        level: DqLevel::Warning,
+                        field: "machine_code".to_string(),
+                        message: format!(
+                            "机组无进行中的换辊窗口，未推进累计吨位: {}",
+                            machine_code
+                        ),
+                    });
+                    continue;
+                };
+                campaign.cum_weight_t += tonnage;
+                let (status, _) = roll_engine.check_roll_status(&campaign);
+                campaign_updates.push(CampaignTonnageUpdate {
+                    version_id: vid.to_string(),
+                    machine_code: machine_code.to_string(),
+                    campaign_no: campaign.campaign_no,
+                    cum_weight_t: campaign.cum_weight_t,
+                    status,
+                });
+            }
+        }
+
+        // === 步骤 4: 事务落库 ===
+        let imported = if actuals.is_empty() {
+            0
+        } else {
+            self.repo.record_actuals(
+                &batch_id,
+                version_id.as_deref(),
+                &actuals,
+                &campaign_updates,
+                operator,
+            )?
+        };
+
+        // === 步骤 5: 计划 vs 实绩对账 ===
+        let (adherence, carry_over_count) = match version_id.as_deref() {
+            Some(vid) if !actuals.is_empty() => self.reconcile(vid, &actuals, &batch_id)?,
+            _ => (Vec::new(), 0),
+        };
+
+        let warning = violations
+            .iter()
+            .filter(|v| v.level == DqLevel::Warning)
+            .count();
+        let dq_summary = DqSummary {
+            total_rows: parsed.total_rows,
+            success: imported,
+            blocked,
+            warning,
+            conflict,
+        };
+
+        // === 步骤 6: 审计 ===
+        let (date_from, date_to) = date_span(&actuals);
+        let action_log = ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: version_id.clone(),
+            action_type: "IMPORT_PRODUCTION_ACTUALS".to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "import_type": PRODUCTION_ACTUALS_IMPORT_TYPE,
+                "batch_id": batch_id,
+                "file_path": file_path,
+                "summary": dq_summary,
+                "campaigns_updated": campaign_updates.len(),
+            })),
+            impact_summary_json: Some(serde_json::json!({
+                "completed_materials": imported,
+                "adherence_days": adherence.len(),
+                "carry_over_count": carry_over_count,
+            })),
+            machine_code: None,
+            date_range_start: date_from,
+            date_range_end: date_to,
+            detail: Some(format!(
+                "生产实绩导入: 回填 {} 条, 阻断 {} 条, 冲突 {} 条",
+                imported, blocked, conflict
+            )),
+        };
+        if let Err(e) = self.action_log_repo.insert(&action_log) {
+            tracing::warn!("实绩导入审计日志写入失败: {}", e);
+        }
+
+        Ok(ProductionActualsImportResponse {
+            batch_id,
+            version_id,
+            imported,
+            campaigns_updated: campaign_updates.len(),
+            dq_summary,
+            dq_violations: violations,
+            adherence,
+            carry_over_count,
+            elapsed_ms: start_time.elapsed().as_millis() as i64,
+            source_info: parsed.source_info,
+        })
+    }
+
+    /// 对账并落库（窗口内全部实绩参与，覆盖旧记录）
+    fn reconcile(
+        &self,
+        version_id: &str,
+        new_actuals: &[ProductionActual],
+        batch_id: &str,
+    ) -> ApiResult<(Vec<PlanAdherenceRecord>, usize)> {
+        let mut windows: HashMap<&str, (NaiveDate, NaiveDate)> = HashMap::new();
+        for actual in new_actuals {
+            let date = actual.completed_date();
+            let entry = windows
+                .entry(actual.machine_code.as_str())
+                .or_insert((date, date));
+            entry.0 = entry.0.min(date);
+            entry.1 = entry.1.max(date);
+        }
+
+        let mut window_actuals = Vec::new();
+        for (machine_code, (from, to)) in &windows {
+            window_actuals.extend(self.repo.find_actuals_by_machine_range(
+                machine_code,
+                *from,
+                *to,
+            )?);
+        }
+
+        let (from, to) = date_span(new_actuals);
+        let (Some(from), Some(to)) = (from, to) else {
+            return Ok((Vec::new(), 0));
+        };
+        let plan_items: Vec<_> = self
+            .plan_item_repo
+            .find_by_date_range(version_id, from, to)?
+            .into_iter()
+            .filter(|item| windows.contains_key(item.machine_code.as_str()))
+            .collect();
+
+        let planned_ids: Vec<String> = plan_items.iter().map(|i| i.material_id.clone()).collect();
+        let completed_ids: HashSet<String> = self.repo.find_completed_material_ids(&planned_ids)?;
+
+        let outcome = PlanAdherenceEngine::new().reconcile(
+            version_id,
+            &plan_items,
+            &window_actuals,
+            &completed_ids,
+            Some(batch_id),
+            chrono::Local::now().naive_local(),
+        );
+        self.repo
+            .save_reconciliation(&outcome.records, &outcome.carry_overs)?;
+
+        Ok((outcome.records, outcome.carry_overs.len()))
+    }
+
+    // ==========================================
+    // 查询
+    // ==========================================
+
+    /// 查询计划执行报告
+    ///
+    /// # 参数
+    /// - version_id: 版本ID
+    /// - machine_code: 机组（None 表示全部）
+    /// - date_from / date_to: 日期区间 (YYYY-MM-DD，可选)
+    pub fn get_plan_adherence_report(
+        &self,
+        version_id: &str,
+        machine_code: Option<&str>,
+        date_from: Option<&str>,
+        date_to: Option<&str>,
+    ) -> ApiResult<PlanAdherenceReport> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        let from = parse_optional_date(date_from)?;
+        let to = parse_optional_date(date_to)?;
+
+        let rows = self
+            .repo
+            .list_adherence(version_id, machine_code, from, to)?;
+
+        let total_planned_weight_t: f64 = rows.iter().map(|r| r.planned_weight_t).sum();
+        let total_matched_weight_t: f64 = rows.iter().map(|r| r.matched_weight_t).sum();
+        let overall_adherence_rate = if total_planned_weight_t > 0.0 {
+            Some(total_matched_weight_t / total_planned_weight_t)
+        } else {
+            None
+        };
+
+        Ok(PlanAdherenceReport {
+            version_id: version_id.to_string(),
+            total_planned_weight_t,
+            total_actual_weight_t: rows.iter().map(|r| r.actual_weight_t).sum(),
+            total_matched_weight_t,
+            total_carry_over_count: rows.iter().map(|r| r.carry_over_count).sum(),
+            total_sequence_deviation_count: rows.iter().map(|r| r.sequence_deviation_count).sum(),
+            overall_adherence_rate,
+            rows,
+        })
+    }
+
+    /// 查询未完工顺延项
+    pub fn list_carry_over_items(
+        &self,
+        version_id: &str,
+        open_only: bool,
+    ) -> ApiResult<Vec<CarryOverItem>> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        Ok(self.repo.list_carry_overs(version_id, open_only)?)
+    }
+}
+
+fn date_span(actuals: &[ProductionActual]) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    let from = actuals.iter().map(|a| a.completed_date()).min();
+    let to = actuals.iter().map(|a| a.completed_date()).max();
+    (from, to)
+}
+
+fn parse_optional_date(raw: Option<&str>) -> ApiResult<Option<NaiveDate>> {
+    match raw.map(str::trim).filter(|s| !s.is_empty()) {
+        None => Ok(None),
+        Some(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| ApiError::InvalidInput("日期格式错误（应为 YYYY-MM-DD）".to_string())),
+    }
+}
@@ -0,0 +1,591 @@
+// ==========================================
+// 热轧精整排产系统 - 场景/沙盘方案 API
+// ==========================================
+// 职责:
+// - 创建挂在基准方案下的场景方案(SCENARIO/SANDBOX)
+// - 维护场景假设叠加层（产能调整/假设材料/机组停机/配置覆盖）
+// - 在隔离沙盘中评估场景（不写生产 material_state / risk_snapshot）
+// - 将评估结果提升为基准方案的草稿版本
+// - 在隔离沙盘中回放历史一键重算（确定性核对）
+// ==========================================
+
+mod breakdown;
+mod replay;
+mod sandbox;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::config::ConfigManager;
+use crate::domain::action_log::ActionLog;
+use crate::domain::capacity::CapacityPool;
+use crate::domain::plan::{Plan, PlanItem, PlanVersion};
+use crate::domain::scenario::{
+    is_isolated_plan_type, ScenarioOverlay, ScenarioRun, PLAN_TYPE_BASELINE,
+};
+use crate::domain::types::PlanVersionStatus;
+use crate::domain::version_lineage::{VersionTrigger, META_PARENT_VERSION_ID, META_TRIGGER};
+use crate::engine::{BreakdownSuggestion, ContractAtRisk, DisplacedMaterial};
+use crate::repository::action_log_repo::ActionLogRepository;
+use crate::repository::capacity_repo::CapacityPoolRepository;
+use crate::repository::plan_repo::{PlanItemRepository, PlanRepository, PlanVersionRepository};
+use crate::repository::ScenarioRepository;
+
+use sandbox::ScenarioSandbox;
+
+/// 场景评估响应（含与基准激活版本的对比口径）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioEvaluationResponse {
+    pub run_id: String,
+    pub plan_id: String,
+    pub base_version_id: String,
+    pub plan_date_from: NaiveDate,
+    pub plan_date_to: NaiveDate,
+    pub strategy: String,
+    pub total_items: usize,
+    pub total_weight_t: f64,
+    pub mature_count: usize,
+    pub immature_count: usize,
+    pub overflow_days: usize,
+    pub hypothetical_scheduled: usize,
+    /// 基准版本同区间的明细数/吨位
+    pub baseline_items: usize,
+    pub baseline_weight_t: f64,
+}
+
+/// 场景提升响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromoteScenarioResponse {
+    pub version_id: String,
+    pub plan_id: String,
+    pub items_count: usize,
+    /// 被剔除的假设材料数（假设材料不进入生产版本）
+    pub skipped_hypothetical: usize,
+    pub message: String,
+}
+
+/// 故障日产能损失
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityLossDay {
+    pub plan_date: NaiveDate,
+    pub loss_ratio: f64,
+    pub lost_capacity_t: f64,
+}
+
+/// 机组故障模拟响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakdownSimulationResponse {
+    pub version_id: String,
+    pub machine_code: String,
+    pub outage_start: NaiveDateTime,
+    pub outage_end: NaiveDateTime,
+    pub plan_date_from: NaiveDate,
+    pub plan_date_to: NaiveDate,
+    pub capacity_loss: Vec<CapacityLossDay>,
+    /// 故障前/故障后 窗口内明细数
+    pub baseline_items: usize,
+    pub whatif_items: usize,
+    pub squeezed_weight_t: f64,
+    pub squeezed_out: Vec<DisplacedMaterial>,
+    pub delayed: Vec<DisplacedMaterial>,
+    pub newly_at_risk: Vec<ContractAtRisk>,
+    pub suggestions: Vec<BreakdownSuggestion>,
+    /// 仅 create_version=true 时生成
+    pub draft_version_id: Option<String>,
+}
+
+pub struct ScenarioApi {
+    scenario_repo: Arc<ScenarioRepository>,
+    plan_repo: Arc<PlanRepository>,
+    plan_version_repo: Arc<PlanVersionRepository>,
+    plan_item_repo: Arc<PlanItemRepository>,
+    capacity_repo: Arc<CapacityPoolRepository>,
+    action_log_repo: Arc<ActionLogRepository>,
+    config_manager: Arc<ConfigManager>,
+}
+
+impl ScenarioApi {
+    pub fn new(
+        scenario_repo: Arc<ScenarioRepository>,
+        plan_repo: Arc<PlanRepository>,
+        plan_version_repo: Arc<PlanVersionRepository>,
+        plan_item_repo: Arc<PlanItemRepository>,
+        capacity_repo: Arc<CapacityPoolRepository>,
+        action_log_repo: Arc<ActionLogRepository>,
+        config_manager: Arc<ConfigManager>,
+    ) -> Self {
+        Self {
+            scenario_repo,
+            plan_repo,
+            plan_version_repo,
+            plan_item_repo,
+            capacity_repo,
+            action_log_repo,
+            config_manager,
+        }
+    }
+
+    // ==========================================
+    // 场景方案 / 叠加层
+    // ==========================================
+
+    /// 创建场景方案
+    ///
+    /// # 参数
+    /// - base_plan_id: 基准方案（必须为 BASELINE）
+    /// - plan_type: SCENARIO / SANDBOX
+    pub fn create_scenario_plan(
+        &self,
+        base_plan_id: &str,
+        plan_name: &str,
+        plan_type: &str,
+        operator: &str,
+    ) -> ApiResult<String> {
+        if plan_name.trim().is_empty() {
+            return Err(ApiError::InvalidInput("方案名称不能为空".to_string()));
+        }
+        let plan_type = plan_type.trim().to_uppercase();
+        if !is_isolated_plan_type(&plan_type) {
+            return Err(ApiError::InvalidInput(format!(
+                "场景方案类型必须为 SCENARIO/SANDBOX: {}",
+                plan_type
+            )));
+        }
+
+        let base_plan = self.load_plan(base_plan_id)?;
+        if base_plan.plan_type != PLAN_TYPE_BASELINE {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "场景方案只能基于基准方案创建: {}({})",
+                base_plan.plan_name, base_plan.plan_type
+            )));
+        }
+
+        let now = chrono::Local::now().naive_local();
+        let plan = Plan {
+            plan_id: uuid::Uuid::new_v4().to_string(),
+            plan_name: plan_name.trim().to_string(),
+            plan_type: plan_type.clone(),
+            base_plan_id: Some(base_plan.plan_id.clone()),
+            created_by: operator.to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        self.plan_repo.create(&plan)?;
+
+        self.log_action(ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: None,
+            action_type: "CREATE_SCENARIO_PLAN".to_string(),
+            action_ts: now,
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "plan_id": plan.plan_id,
+                "plan_type": plan_type,
+                "base_plan_id": base_plan.plan_id,
+            })),
+            impact_summary_json: None,
+            machine_code: None,
+            date_range_start: None,
+            date_range_end: None,
+            detail: Some(format!("创建场景方案: {}", plan.plan_name)),
+        })?;
+
+        Ok(plan.plan_id)
+    }
+
+    /// 保存场景叠加层（整体覆盖）
+    pub fn save_scenario_overlay(
+        &self,
+        plan_id: &str,
+        overlay: ScenarioOverlay,
+        operator: &str,
+    ) -> ApiResult<()> {
+        self.load_scenario_plan(plan_id)?;
+
+        for change in &overlay.capacity_changes {
+            let invalid = |v: Option<f64>| v.is_some_and(|v| !v.is_finite() || v < 0.0);
+            if invalid(change.target_capacity_t) || invalid(change.limit_capacity_t) {
+                return Err(ApiError::InvalidInput(format!(
+                    "产能调整数值非法: {} {}",
+                    change.machine_code, change.plan_date
+                )));
+            }
+        }
+        let mut seen = HashSet::new();
+        for m in &overlay.hypothetical_materials {
+            if m.material_id.trim().is_empty() || !seen.insert(m.material_id.as_str()) {
+                return Err(ApiError::InvalidInput(format!(
+                    "假设材料ID为空或重复: {}",
+                    m.material_id
+                )));
+            }
+            if !m.weight_t.is_finite() || m.weight_t <= 0.0 {
+                return Err(ApiError::InvalidInput(format!(
+                    "假设材料吨位必须大于0: {}",
+                    m.material_id
+                )));
+            }
+        }
+        for outage in &overlay.machine_outages {
+            if outage.date_to < outage.date_from {
+                return Err(ApiError::InvalidInput(format!(
+                    "停机结束日期早于开始日期: {}",
+                    outage.machine_code
+                )));
+            }
+        }
+
+        self.scenario_repo
+            .upsert_overlay(plan_id, &overlay, operator)?;
+        Ok(())
+    }
+
+    /// 查询场景叠加层（未设置时返回空叠加层）
+    pub fn get_scenario_overlay(&self, plan_id: &str) -> ApiResult<ScenarioOverlay> {
+        self.load_scenario_plan(plan_id)?;
+        Ok(self
+            .scenario_repo
+            .find_overlay(plan_id)?
+            .unwrap_or_default())
+    }
+
+    // ==========================================
+    // 隔离评估
+    // ==========================================
+
+    /// 评估场景方案
+    ///
+    /// # 说明
+    /// - 以基准方案的激活版本为起点，在数据库快照上应用叠加层后试算
+    /// - 生产库只写入评估结果(scenario_run)与操作日志
+    pub fn evaluate_scenario(
+        &self,
+        plan_id: &str,
+        base_date: NaiveDate,
+        window_days: i32,
+        strategy: Option<String>,
+        operator: &str,
+    ) -> ApiResult<ScenarioEvaluationResponse> {
+        if !(1..=60).contains(&window_days) {
+            return Err(ApiError::InvalidInput("窗口天数必须在1-60之间".to_string()));
+        }
+        let plan = self.load_scenario_plan(plan_id)?;
+        let base_version = self.load_base_active_version(&plan)?;
+        let overlay = self
+            .scenario_repo
+            .find_overlay(plan_id)?
+            .unwrap_or_default();
+        let strategy_key = strategy
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "balanced".to_string());
+        let date_to = base_date + chrono::Duration::days(window_days as i64 - 1);
+
+        let (evaluation, hypothetical_ids) = {
+            let sandbox = ScenarioSandbox::create(&self.scenario_repo)?;
+            let hypothetical_ids =
+                sandbox.apply_overlay(&base_version.version_id, &overlay, base_date)?;
+            let evaluation = sandbox.evaluate(
+                &base_version.version_id,
+                (base_date, date_to),
+                &strategy_key,
+            )?;
+            (evaluation, hypothetical_ids)
+        };
+
+        // 冻结区明细按基准版本保留（试算结果可能已包含，按材料去重）
+        let items = evaluation.all_items();
+
+        let mut hypothetical_material_ids: Vec<String> = hypothetical_ids.into_iter().collect();
+        hypothetical_material_ids.sort();
+        let run = ScenarioRun {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            plan_id: plan.plan_id.clone(),
+            base_version_id: base_version.version_id.clone(),
+            plan_date_from: base_date,
+            plan_date_to: date_to,
+            strategy: evaluation.profile.strategy_key.clone(),
+            total_items: items.len(),
+            total_weight_t: items.iter().map(|i| i.weight_t).sum(),
+            mature_count: evaluation.reschedule.mature_count,
+            immature_count: evaluation.reschedule.immature_count,
+            overflow_days: evaluation.reschedule.overflow_days,
+            hypothetical_scheduled: items
+                .iter()
+                .filter(|i| hypothetical_material_ids.contains(&i.material_id))
+                .count(),
+            items,
+            hypothetical_material_ids,
+            overlay,
+            created_by: operator.to_string(),
+            created_at: chrono::Local::now().naive_local(),
+            promoted_version_id: None,
+        };
+        self.scenario_repo.insert_run(&run)?;
+
+        let baseline_items: Vec<PlanItem> = self
+            .plan_item_repo
+            .find_by_version(&base_version.version_id)?
+            .into_iter()
+            .filter(|i| i.plan_date >= base_date && i.plan_date <= date_to)
+            .collect();
+        let baseline_weight_t: f64 = baseline_items.iter().map(|i| i.weight_t).sum();
+
+        self.log_action(ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: None,
+            action_type: "EVALUATE_SCENARIO".to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "plan_id": run.plan_id,
+                "run_id": run.run_id,
+                "base_version_id": run.base_version_id,
+                "strategy": run.strategy,
+            })),
+            impact_summary_json: Some(serde_json::json!({
+                "total_items": run.total_items,
+                "total_weight_t": run.total_weight_t,
+                "baseline_items": baseline_items.len(),
+                "baseline_weight_t": baseline_weight_t,
+                "overflow_days": run.overflow_days,
+            })),
+            machine_code: None,
+            date_range_start: Some(base_date),
+            date_range_end: Some(date_to),
+            detail: Some(format!("评估场景方案: {}", plan.plan_name)),
+        })?;
+
+        Ok(ScenarioEvaluationResponse {
+            run_id: run.run_id,
+            plan_id: run.plan_id,
+            base_version_id: run.base_version_id,
+            plan_date_from: run.plan_date_from,
+            plan_date_to: run.plan_date_to,
+            strategy: run.strategy,
+            total_items: run.total_items,
+            total_weight_t: run.total_weight_t,
+            mature_count: run.mature_count,
+            immature_count: run.immature_count,
+            overflow_days: run.overflow_days,
+            hypothetical_scheduled: run.hypothetical_scheduled,
+            baseline_items: baseline_items.len(),
+            baseline_weight_t,
+        })
+    }
+
+    /// 查询评估结果（含明细）
+    pub fn get_scenario_run(&self, run_id: &str) -> ApiResult<ScenarioRun> {
+        self.scenario_repo
+            .find_run(run_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("场景评估结果{}不存在", run_id)))
+    }
+
+    // ==========================================
+    // 提升为基准方案草稿版本
+    // ==========================================
+
+    /// 将评估结果提升为基准方案的草稿版本
+    ///
+    /// # 规则
+    /// - 基准方案激活版本必须仍为评估时的版本（否则需重新评估）
+    /// - 每个评估结果只能提升一次
+    /// - 假设材料不进入生产版本
+    pub fn promote_scenario_run(
+        &self,
+        run_id: &str,
+        operator: &str,
+    ) -> ApiResult<PromoteScenarioResponse> {
+        let run = self.get_scenario_run(run_id)?;
+        if let Some(version_id) = &run.promoted_version_id {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "该评估结果已提升为版本{}",
+                version_id
+            )));
+        }
+        let plan = self.load_scenario_plan(&run.plan_id)?;
+        let base_version = self.load_base_active_version(&plan)?;
+        if base_version.version_id != run.base_version_id {
+            return Err(ApiError::VersionConflict(
+                "基准方案激活版本已变化，请重新评估场景后再提升".to_string(),
+            ));
+        }
+
+        let hypothetical: HashSet<&str> = run
+            .hypothetical_material_ids
+            .iter()
+            .map(|s| s.as_str())
+            .collect();
+        let items: Vec<PlanItem> = run
+            .items
+            .iter()
+            .filter(|i| !hypothetical.contains(i.material_id.as_str()))
+            .cloned()
+            .collect();
+        let skipped_hypothetical = run.items.len() - items.len();
+
+        // 叠加层中的产能假设不带入生产版本
+        let version = self.create_draft_from_items(
+            &base_version,
+            &[
+                (
+                    "__meta_version_name_cn",
+                    format!("场景提升({})", plan.plan_name),
+                ),
+                ("__meta_scenario_run_id", run.run_id.clone()),
+                (
+                    META_TRIGGER,
+                    VersionTrigger::ScenarioPromote.as_str().to_string(),
+                ),
+            ],
+            &items,
+            Vec::new(),
+            operator,
+        )?;
+
+        self.scenario_repo
+            .mark_promoted(&run.run_id, &version.version_id)?;
+
+        self.log_action(ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: Some(version.version_id.clone()),
+            action_type: "PROMOTE_SCENARIO".to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "scenario_plan_id": plan.plan_id,
+                "run_id": run.run_id,
+                "base_version_id": run.base_version_id,
+            })),
+            impact_summary_json: Some(serde_json::json!({
+                "items_count": items.len(),
+                "skipped_hypothetical": skipped_hypothetical,
+            })),
+            machine_code: None,
+            date_range_start: Some(run.plan_date_from),
+            date_range_end: Some(run.plan_date_to),
+            detail: Some(format!("场景提升为草稿版本: V{}", version.version_no)),
+        })?;
+
+        Ok(PromoteScenarioResponse {
+            version_id: version.version_id,
+            plan_id: version.plan_id,
+            items_count: items.len(),
+            skipped_hypothetical,
+            message: "场景已提升为基准方案草稿版本".to_string(),
+        })
+    }
+
+    // ==========================================
+    // 内部辅助
+    // ==========================================
+
+    fn load_plan(&self, plan_id: &str) -> ApiResult<Plan> {
+        self.plan_repo
+            .find_by_id(plan_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("方案{}不存在", plan_id)))
+    }
+
+    fn load_scenario_plan(&self, plan_id: &str) -> ApiResult<Plan> {
+        let plan = self.load_plan(plan_id)?;
+        if !is_isolated_plan_type(&plan.plan_type) {
+            return Err(ApiError::InvalidInput(format!(
+                "方案{}不是场景方案({})",
+                plan.plan_name, plan.plan_type
+            )));
+        }
+        Ok(plan)
+    }
+
+    fn load_base_active_version(&self, plan: &Plan) -> ApiResult<PlanVersion> {
+        let base_plan_id = plan.base_plan_id.as_deref().ok_or_else(|| {
+            ApiError::BusinessRuleViolation(format!("场景方案{}未关联基准方案", plan.plan_name))
+        })?;
+        self.plan_version_repo
+            .find_active_version(base_plan_id)?
+            .ok_or_else(|| {
+                ApiError::BusinessRuleViolation("基准方案没有激活版本，无法评估场景".to_string())
+            })
+    }
+
+    /// 以基准版本为模板创建草稿版本并写入明细
+    ///
+    /// # 说明
+    /// - 产能池复制自基准版本，capacity_overrides 覆盖其上
+    /// - meta 写入 config_snapshot_json 的 __meta_* 键；父版本默认为基准版本
+    fn create_draft_from_items(
+        &self,
+        base_version: &PlanVersion,
+        meta: &[(&str, String)],
+        items: &[PlanItem],
+        capacity_overrides: Vec<CapacityPool>,
+        operator: &str,
+    ) -> ApiResult<PlanVersion> {
+        let mut config_map: HashMap<String, String> = serde_json::from_str(
+            &self
+                .config_manager
+                .get_config_snapshot()
+                .map_err(|e| ApiError::InternalError(e.to_string()))?,
+        )
+        .unwrap_or_default();
+        config_map.insert(
+            META_PARENT_VERSION_ID.to_string(),
+            base_version.version_id.clone(),
+        );
+        for (key, value) in meta {
+            config_map.insert(key.to_string(), value.clone());
+        }
+
+        let mut version = PlanVersion {
+            version_id: uuid::Uuid::new_v4().to_string(),
+            plan_id: base_version.plan_id.clone(),
+            version_no: 0,
+            status: PlanVersionStatus::Draft,
+            frozen_from_date: base_version.frozen_from_date,
+            recalc_window_days: base_version.recalc_window_days,
+            config_snapshot_json: serde_json::to_string(&config_map).ok(),
+            created_by: Some(operator.to_string()),
+            created_at: chrono::Local::now().naive_local(),
+            revision: 1,
+        };
+        self.plan_version_repo
+            .create_with_next_version_no(&mut version)?;
+
+        let items: Vec<PlanItem> = items
+            .iter()
+            .cloned()
+            .map(|mut i| {
+                i.version_id = version.version_id.clone();
+                i
+            })
+            .collect();
+        self.plan_item_repo.batch_insert(&items)?;
+
+        let pools: Vec<CapacityPool> = self
+            .capacity_repo
+            .find_by_version_id(&base_version.version_id)?
+            .into_iter()
+            .chain(capacity_overrides)
+            .map(|mut p| {
+                p.version_id = version.version_id.clone();
+                p
+            })
+            .collect();
+        if !pools.is_empty() {
+            self.capacity_repo.upsert_batch(pools)?;
+        }
+
+        Ok(version)
+    }
+
+    fn log_action(&self, log: ActionLog) -> ApiResult<()> {
+        self.action_log_repo
+            .insert(&log)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+}
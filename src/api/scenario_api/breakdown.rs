@@ -0,0 +1,181 @@
+// ==========================================
+// 机组故障 what-if 模拟
+// ==========================================
+// 职责: 在隔离沙盘中对比 故障前/故障后 两次试算,评估停机影响
+// 说明:
+// - 两次试算共用同一快照与策略,差异只来自故障机组的产能扣减
+// - 默认不生成版本；create_version=true 时把故障后结果保存为草稿版本
+// ==========================================
+
+use super::sandbox::{ScenarioSandbox, SANDBOX_MACHINE_CODES};
+use super::*;
+use crate::domain::scenario::MachineBreakdown;
+use crate::engine::{BreakdownImpactEngine, BreakdownImpactInput};
+
+impl ScenarioApi {
+    /// 模拟机组故障停机
+    ///
+    /// # 参数
+    /// - version_id: 评估基准版本（通常为激活版本）
+    /// - breakdown: 故障机组/开始时间/时长/产能损失比例
+    /// - window_days: 分析窗口（从故障开始日起）
+    /// - create_version: 是否将故障后结果保存为草稿版本
+    pub fn simulate_machine_breakdown(
+        &self,
+        version_id: &str,
+        breakdown: MachineBreakdown,
+        window_days: i32,
+        strategy: Option<String>,
+        create_version: bool,
+        operator: &str,
+    ) -> ApiResult<BreakdownSimulationResponse> {
+        if !(1..=60).contains(&window_days) {
+            return Err(ApiError::InvalidInput("窗口天数必须在1-60之间".to_string()));
+        }
+        if !SANDBOX_MACHINE_CODES.contains(&breakdown.machine_code.as_str()) {
+            return Err(ApiError::InvalidInput(format!(
+                "不支持的机组: {}",
+                breakdown.machine_code
+            )));
+        }
+        if !breakdown.outage_hours.is_finite() || breakdown.outage_hours <= 0.0 {
+            return Err(ApiError::InvalidInput("故障时长必须大于0".to_string()));
+        }
+        if breakdown
+            .capacity_reduction_pct
+            .is_some_and(|p| !p.is_finite() || p <= 0.0 || p > 100.0)
+        {
+            return Err(ApiError::InvalidInput(
+                "产能损失比例必须在(0,100]之间".to_string(),
+            ));
+        }
+
+        let base_version = self
+            .plan_version_repo
+            .find_by_id(version_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        let strategy_key = strategy
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "balanced".to_string());
+        let date_from = breakdown.outage_start.date();
+        let date_to = date_from + chrono::Duration::days(window_days as i64 - 1);
+        let losses: Vec<(NaiveDate, f64)> = breakdown
+            .daily_capacity_loss()
+            .into_iter()
+            .filter(|(d, _)| *d <= date_to)
+            .collect();
+
+        // 故障前/故障后 在同一快照上试算
+        let sandbox = ScenarioSandbox::create(&self.scenario_repo)?;
+        let baseline = sandbox.evaluate(version_id, (date_from, date_to), &strategy_key)?;
+        let limits_before = sandbox.capacity_limits(version_id, (date_from, date_to))?;
+        let reduced_pools =
+            sandbox.reduce_capacity(version_id, &breakdown.machine_code, &losses)?;
+        let whatif = sandbox.evaluate(version_id, (date_from, date_to), &strategy_key)?;
+        let limits_after = sandbox.capacity_limits(version_id, (date_from, date_to))?;
+        drop(sandbox);
+
+        let baseline_items = baseline.all_items();
+        let whatif_items = whatif.all_items();
+        let outage_dates: Vec<NaiveDate> = losses.iter().map(|(d, _)| *d).collect();
+        let impact = BreakdownImpactEngine::analyze(&BreakdownImpactInput {
+            baseline_items: &baseline_items,
+            whatif_items: &whatif_items,
+            machine_code: &breakdown.machine_code,
+            outage_dates: &outage_dates,
+            date_range: (date_from, date_to),
+            whatif_limits: &limits_after,
+        });
+
+        let capacity_loss: Vec<CapacityLossDay> = losses
+            .iter()
+            .map(|(date, ratio)| {
+                let key = (breakdown.machine_code.clone(), *date);
+                let before = limits_before.get(&key).copied().unwrap_or(0.0);
+                let after = limits_after.get(&key).copied().unwrap_or(0.0);
+                CapacityLossDay {
+                    plan_date: *date,
+                    loss_ratio: *ratio,
+                    lost_capacity_t: before - after,
+                }
+            })
+            .collect();
+
+        let draft_version_id = if create_version {
+            let version = self.create_draft_from_items(
+                &base_version,
+                &[
+                    (
+                        "__meta_version_name_cn",
+                        format!(
+                            "故障模拟({} {:.0}h)",
+                            breakdown.machine_code, breakdown.outage_hours
+                        ),
+                    ),
+                    ("__meta_breakdown_from_version_id", version_id.to_string()),
+                    (
+                        META_TRIGGER,
+                        VersionTrigger::BreakdownWhatIf.as_str().to_string(),
+                    ),
+                ],
+                &whatif_items,
+                reduced_pools,
+                operator,
+            )?;
+            Some(version.version_id)
+        } else {
+            None
+        };
+
+        self.log_action(ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: Some(version_id.to_string()),
+            action_type: "SIMULATE_BREAKDOWN".to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: operator.to_string(),
+            payload_json: Some(serde_json::json!({
+                "machine_code": breakdown.machine_code,
+                "outage_start": breakdown.outage_start.to_string(),
+                "outage_hours": breakdown.outage_hours,
+                "capacity_reduction_pct": breakdown.capacity_reduction_pct,
+                "strategy": strategy_key,
+                "draft_version_id": draft_version_id,
+            })),
+            impact_summary_json: Some(serde_json::json!({
+                "squeezed_out_count": impact.squeezed_out.len(),
+                "squeezed_weight_t": impact.squeezed_weight_t,
+                "delayed_count": impact.delayed.len(),
+                "newly_at_risk_count": impact.newly_at_risk.len(),
+            })),
+            machine_code: Some(breakdown.machine_code.clone()),
+            date_range_start: Some(date_from),
+            date_range_end: Some(date_to),
+            detail: Some(format!(
+                "故障模拟: {} 停机{:.1}h, 挤出{}件, 延后{}件",
+                breakdown.machine_code,
+                breakdown.outage_hours,
+                impact.squeezed_out.len(),
+                impact.delayed.len()
+            )),
+        })?;
+
+        Ok(BreakdownSimulationResponse {
+            version_id: version_id.to_string(),
+            machine_code: breakdown.machine_code.clone(),
+            outage_start: breakdown.outage_start,
+            outage_end: breakdown.outage_end(),
+            plan_date_from: date_from,
+            plan_date_to: date_to,
+            capacity_loss,
+            baseline_items: baseline_items.len(),
+            whatif_items: whatif_items.len(),
+            squeezed_weight_t: impact.squeezed_weight_t,
+            squeezed_out: impact.squeezed_out,
+            delayed: impact.delayed,
+            newly_at_risk: impact.newly_at_risk,
+            suggestions: impact.suggestions,
+            draft_version_id,
+        })
+    }
+}
@@ -0,0 +1,111 @@
+// ==========================================
+// 历史重算确定性回放
+// ==========================================
+// 职责: 按版本记录的重算输入在沙盘中重新执行一键重算，并与已存明细对比
+// 输入来源:
+// - 重算时开启 recalc_persist_inputs → 使用保存的全量输入（配置/材料/产能池/冻结区）
+// - 未保存全量输入 → 使用当前数据，冻结区取已存版本的 FROZEN 明细；
+//   指纹不一致的部分在报告中列出，此时差异不代表引擎不确定
+// ==========================================
+
+use super::sandbox::ScenarioSandbox;
+use super::ScenarioApi;
+use crate::api::error::{ApiError, ApiResult};
+use crate::domain::plan::PlanItem;
+use crate::domain::recalc_input::{
+    diff_replay_items, RecalcInputFingerprint, RecalcInputSnapshot, RecalcReplayReport,
+    ReplayInputSource,
+};
+
+/// 报告中保留的差异明细上限（计数仍为全量）
+const MAX_REPLAY_DIFFS: usize = 5000;
+
+impl ScenarioApi {
+    /// 回放历史一键重算并与已存明细对比
+    ///
+    /// # 参数
+    /// - version_id: 一键重算生成的版本
+    pub fn replay_recalc(&self, version_id: &str) -> ApiResult<RecalcReplayReport> {
+        let version = self
+            .plan_version_repo
+            .find_by_id(version_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        let recorded =
+            RecalcInputFingerprint::from_snapshot(version.config_snapshot_json.as_deref())
+                .ok_or_else(|| {
+                    ApiError::BusinessRuleViolation(format!(
+                        "版本{}未记录重算输入指纹（非一键重算生成或早于指纹记录），无法回放",
+                        version_id
+                    ))
+                })?;
+        let persisted: Option<RecalcInputSnapshot> = self
+            .plan_version_repo
+            .find_input_snapshot(version_id)?
+            .map(|(_, raw)| serde_json::from_str(&raw))
+            .transpose()
+            .map_err(|e| ApiError::InternalError(format!("重算输入快照解析失败: {}", e)))?;
+        let stored = self.plan_item_repo.find_by_version(version_id)?;
+
+        let sandbox = ScenarioSandbox::create(&self.scenario_repo)?;
+        let (input_source, frozen_items, capacity_pools) = match &persisted {
+            Some(inputs) => {
+                sandbox.restore_recalc_inputs(inputs)?;
+                (
+                    ReplayInputSource::Persisted,
+                    inputs.frozen_items.clone(),
+                    inputs.capacity_pools.clone(),
+                )
+            }
+            None => {
+                let frozen: Vec<PlanItem> = stored
+                    .iter()
+                    .filter(|i| i.locked_in_plan && i.source_type == "FROZEN")
+                    .cloned()
+                    .collect();
+                (ReplayInputSource::Current, frozen, Vec::new())
+            }
+        };
+        let (captured, replayed) =
+            sandbox.replay_recalc(&recorded.run, &frozen_items, &capacity_pools)?;
+        drop(sandbox);
+
+        let replay_fingerprint = captured.fingerprint();
+        let input_mismatches = recorded.mismatched_components(&replay_fingerprint);
+        let mut diffs = diff_replay_items(&stored, &replayed);
+        let count = |kind: &str| diffs.iter().filter(|d| d.change_type == kind).count();
+        let (added_count, removed_count, moved_count, seq_changed_count) = (
+            count("ADDED"),
+            count("REMOVED"),
+            count("MOVED"),
+            count("SEQ_CHANGED"),
+        );
+        let identical = diffs.is_empty();
+        let diffs_truncated = diffs.len() > MAX_REPLAY_DIFFS;
+        diffs.truncate(MAX_REPLAY_DIFFS);
+
+        tracing::info!(
+            version_id = %version_id,
+            input_source = ?input_source,
+            identical,
+            input_mismatches = ?input_mismatches,
+            "重算回放完成"
+        );
+
+        Ok(RecalcReplayReport {
+            version_id: version_id.to_string(),
+            input_source,
+            recorded_fingerprint: recorded,
+            replay_fingerprint,
+            input_mismatches,
+            stored_count: stored.len(),
+            replayed_count: replayed.len(),
+            identical,
+            added_count,
+            removed_count,
+            moved_count,
+            seq_changed_count,
+            diffs,
+            diffs_truncated,
+        })
+    }
+}
@@ -0,0 +1,523 @@
+// ==========================================
+// 场景隔离沙盘
+// ==========================================
+// 职责: 在生产库的独立快照上应用叠加层并试算
+// 隔离口径:
+// - 快照由 VACUUM INTO 导出到临时文件,沙盘内全部仓储/配置均指向快照连接
+// - 叠加层只写快照；排产计算使用 dry-run,快照内也不落 plan_item/material_state
+// - 沙盘释放时删除临时文件
+// ==========================================
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::config::strategy_profile::CustomStrategyParameters;
+use crate::config::ConfigManager;
+use crate::db::open_sqlite_connection;
+use crate::domain::capacity::CapacityPool;
+use crate::domain::material::{MaterialMaster, MaterialState};
+use crate::domain::plan::{PlanItem, PlanVersion};
+use crate::domain::recalc_input::{RecalcInputSnapshot, RecalcRunParams, ENGINE_VERSION};
+use crate::domain::scenario::ScenarioOverlay;
+use crate::domain::types::{PlanVersionStatus, RushLevel, SchedState, UrgentLevel};
+use crate::engine::recalc::{RecalcEngine, RescheduleResult, ResolvedStrategyProfile};
+use crate::engine::strategy::ScheduleStrategy;
+use crate::engine::{CapacityFiller, EligibilityEngine, PrioritySorter, RiskEngine, UrgencyEngine};
+use crate::repository::material_repo::PathOverrideRejectionSummary;
+use crate::repository::{
+    ActionLogRepository, CapacityPoolRepository, MaterialMasterRepository, MaterialStateRepository,
+    PathOverridePendingRepository, PlanItemRepository, PlanVersionRepository,
+    RiskSnapshotRepository, RollerCampaignRepository, ScenarioRepository,
+};
+
+/// 与 RecalcEngine 默认一致的机组范围
+pub(super) const SANDBOX_MACHINE_CODES: [&str; 3] = ["H032", "H033", "H034"];
+
+/// 假设材料的等效产出天数（远超任何季节的适温阈值，视为已适温）
+const HYPOTHETICAL_OUTPUT_AGE_DAYS: i32 = 30;
+
+struct SandboxInner {
+    recalc_engine: RecalcEngine,
+    plan_version_repo: Arc<PlanVersionRepository>,
+    capacity_repo: Arc<CapacityPoolRepository>,
+    material_master_repo: Arc<MaterialMasterRepository>,
+    material_state_repo: Arc<MaterialStateRepository>,
+    plan_item_repo: Arc<PlanItemRepository>,
+    config_manager: Arc<ConfigManager>,
+}
+
+/// 场景沙盘（生命周期内独占一份数据库快照）
+pub(super) struct ScenarioSandbox {
+    path: PathBuf,
+    inner: Option<SandboxInner>,
+}
+
+/// 沙盘试算结果
+pub(super) struct SandboxEvaluation {
+    pub profile: ResolvedStrategyProfile,
+    pub reschedule: RescheduleResult,
+    pub frozen_items: Vec<PlanItem>,
+}
+
+impl SandboxEvaluation {
+    /// 试算明细 + 区间内冻结明细（按材料去重，按 日期/机组/序号 排序）
+    pub fn all_items(&self) -> Vec<PlanItem> {
+        let mut items = self.reschedule.plan_items.clone();
+        let scheduled: HashSet<&str> = items.iter().map(|i| i.material_id.as_str()).collect();
+        let frozen: Vec<PlanItem> = self
+            .frozen_items
+            .iter()
+            .filter(|i| !scheduled.contains(i.material_id.as_str()))
+            .cloned()
+            .collect();
+        items.extend(frozen);
+        items.sort_by(|a, b| {
+            (a.plan_date, &a.machine_code, a.seq_no).cmp(&(b.plan_date, &b.machine_code, b.seq_no))
+        });
+        items
+    }
+}
+
+impl ScenarioSandbox {
+    /// 从生产库导出快照并构建沙盘引擎
+    pub(super) fn create(scenario_repo: &ScenarioRepository) -> ApiResult<Self> {
+        let path = std::env::temp_dir().join(format!("aps_scenario_{}.db", uuid::Uuid::new_v4()));
+        scenario_repo.snapshot_to(&path)?;
+
+        // 先持有路径：后续构建失败时由 Drop 清理快照文件
+        let mut sandbox = Self { path, inner: None };
+        let conn = open_sqlite_connection(&sandbox.path.to_string_lossy())
+            .map_err(|e| ApiError::DatabaseError(format!("打开沙盘快照失败: {}", e)))?;
+        let conn = Arc::new(Mutex::new(conn));
+
+        let config_manager = Arc::new(
+            ConfigManager::from_connection(conn.clone())
+                .map_err(|e| ApiError::InternalError(format!("沙盘配置初始化失败: {}", e)))?,
+        );
+        let capacity_repo = Arc::new(CapacityPoolRepository::from_connection(conn.clone()));
+        let material_master_repo =
+            Arc::new(MaterialMasterRepository::from_connection(conn.clone()));
+        let material_state_repo = Arc::new(MaterialStateRepository::from_connection(conn.clone()));
+        let plan_item_repo = Arc::new(PlanItemRepository::new(conn.clone()));
+        let plan_version_repo = Arc::new(PlanVersionRepository::new(conn.clone()));
+
+        // 沙盘不挂事件发布器：评估结果不触发生产决策读模型刷新
+        let recalc_engine = RecalcEngine::with_default_config(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            material_state_repo.clone(),
+            material_master_repo.clone(),
+            capacity_repo.clone(),
+            Arc::new(ActionLogRepository::new(conn.clone())),
+            Arc::new(RiskSnapshotRepository::from_connection(conn.clone())),
+            Arc::new(RollerCampaignRepository::from_connection(conn.clone())),
+            Arc::new(PathOverridePendingRepository::new(conn)),
+            Arc::new(EligibilityEngine::new(config_manager.clone())),
+            Arc::new(UrgencyEngine::new()),
+            Arc::new(PrioritySorter::new()),
+            Arc::new(CapacityFiller::new()),
+            Arc::new(RiskEngine::new()),
+            config_manager.clone(),
+            None,
+        );
+
+        sandbox.inner = Some(SandboxInner {
+            recalc_engine,
+            plan_version_repo,
+            capacity_repo,
+            material_master_repo,
+            material_state_repo,
+            plan_item_repo,
+            config_manager,
+        });
+        Ok(sandbox)
+    }
+
+    fn inner(&self) -> ApiResult<&SandboxInner> {
+        self.inner
+            .as_ref()
+            .ok_or_else(|| ApiError::InternalError("沙盘未初始化".to_string()))
+    }
+
+    /// 将叠加层写入快照
+    ///
+    /// # 返回
+    /// - 假设材料ID集合
+    pub(super) fn apply_overlay(
+        &self,
+        base_version_id: &str,
+        overlay: &ScenarioOverlay,
+        base_date: NaiveDate,
+    ) -> ApiResult<HashSet<String>> {
+        let inner = self.inner()?;
+
+        // 1. 配置覆盖
+        if !overlay.config_overrides.is_empty() {
+            let json = serde_json::to_string(&overlay.config_overrides)
+                .map_err(|e| ApiError::InternalError(e.to_string()))?;
+            inner
+                .config_manager
+                .restore_config_from_snapshot(&json)
+                .map_err(|e| ApiError::InternalError(format!("沙盘配置覆盖失败: {}", e)))?;
+        }
+
+        // 2. 产能调整（停机在调整之后应用：停机优先）
+        let mut pools = Vec::new();
+        let load_pool = |machine_code: &str, plan_date: NaiveDate| {
+            Self::load_pool(inner, base_version_id, machine_code, plan_date)
+        };
+        for change in &overlay.capacity_changes {
+            let mut pool = load_pool(&change.machine_code, change.plan_date)?;
+            if let Some(target) = change.target_capacity_t {
+                pool.target_capacity_t = target;
+            }
+            if let Some(limit) = change.limit_capacity_t {
+                pool.limit_capacity_t = limit;
+            }
+            pools.push(pool);
+        }
+        for outage in &overlay.machine_outages {
+            for date in outage.dates() {
+                let mut pool = load_pool(&outage.machine_code, date)?;
+                pool.target_capacity_t = 0.0;
+                pool.limit_capacity_t = 0.0;
+                pools.push(pool);
+            }
+        }
+        if !pools.is_empty() {
+            inner.capacity_repo.upsert_batch(pools)?;
+        }
+
+        // 3. 假设材料（视为已适温）
+        let mut hypothetical_ids = HashSet::new();
+        if !overlay.hypothetical_materials.is_empty() {
+            let ids: Vec<String> = overlay
+                .hypothetical_materials
+                .iter()
+                .map(|m| m.material_id.clone())
+                .collect();
+            let existing = inner.material_master_repo.batch_check_exists(ids)?;
+            if !existing.is_empty() {
+                return Err(ApiError::InvalidInput(format!(
+                    "假设材料与现有材料重号: {}",
+                    existing.join(", ")
+                )));
+            }
+
+            let now = chrono::Utc::now();
+            let mut masters = Vec::new();
+            let mut states = Vec::new();
+            for m in &overlay.hypothetical_materials {
+                hypothetical_ids.insert(m.material_id.clone());
+                masters.push(MaterialMaster {
+                    material_id: m.material_id.clone(),
+                    manufacturing_order_id: None,
+                    material_status_code_src: None,
+                    steel_mark: m.steel_mark.clone(),
+                    slab_id: None,
+                    next_machine_code: Some(m.machine_code.clone()),
+                    rework_machine_code: None,
+                    current_machine_code: Some(m.machine_code.clone()),
+                    width_mm: m.width_mm,
+                    thickness_mm: m.thickness_mm,
+                    length_m: None,
+                    weight_t: Some(m.weight_t),
+                    available_width_mm: None,
+                    due_date: m.due_date,
+                    stock_age_days: Some(0),
+                    output_age_days_raw: Some(HYPOTHETICAL_OUTPUT_AGE_DAYS),
+                    rolling_output_date: None,
+                    status_updated_at: None,
+                    contract_no: None,
+                    contract_nature: None,
+                    weekly_delivery_flag: None,
+                    export_flag: None,
+                    created_at: now,
+                    updated_at: now,
+                });
+                states.push(MaterialState {
+                    material_id: m.material_id.clone(),
+                    sched_state: SchedState::Ready,
+                    lock_flag: false,
+                    force_release_flag: false,
+                    urgent_level: UrgentLevel::L0,
+                    urgent_reason: None,
+                    rush_level: RushLevel::L0,
+                    rolling_output_age_days: HYPOTHETICAL_OUTPUT_AGE_DAYS,
+                    ready_in_days: 0,
+                    earliest_sched_date: Some(base_date),
+                    stock_age_days: 0,
+                    scheduled_date: None,
+                    scheduled_machine_code: None,
+                    seq_no: None,
+                    manual_urgent_flag: false,
+                    user_confirmed: false,
+                    user_confirmed_at: None,
+                    user_confirmed_by: None,
+                    user_confirmed_reason: None,
+                    in_frozen_zone: false,
+                    last_calc_version_id: None,
+                    updated_at: now,
+                    updated_by: Some("scenario".to_string()),
+                });
+            }
+            inner
+                .material_master_repo
+                .batch_insert_material_master(masters)?;
+            inner
+                .material_state_repo
+                .batch_insert_material_state(states)?;
+        }
+
+        Ok(hypothetical_ids)
+    }
+
+    /// 按比例扣减机组产能（故障折算，loss 为 0.0-1.0）
+    ///
+    /// # 返回
+    /// - 扣减后的产能池
+    pub(super) fn reduce_capacity(
+        &self,
+        base_version_id: &str,
+        machine_code: &str,
+        losses: &[(NaiveDate, f64)],
+    ) -> ApiResult<Vec<CapacityPool>> {
+        let inner = self.inner()?;
+        let mut pools = Vec::new();
+        for (date, loss) in losses {
+            let mut pool = Self::load_pool(inner, base_version_id, machine_code, *date)?;
+            let keep = (1.0 - loss).clamp(0.0, 1.0);
+            pool.target_capacity_t *= keep;
+            pool.limit_capacity_t *= keep;
+            pools.push(pool);
+        }
+        if !pools.is_empty() {
+            inner.capacity_repo.upsert_batch(pools.clone())?;
+        }
+        Ok(pools)
+    }
+
+    /// 查询窗口内 机组×日 产能上限
+    pub(super) fn capacity_limits(
+        &self,
+        base_version_id: &str,
+        date_range: (NaiveDate, NaiveDate),
+    ) -> ApiResult<HashMap<(String, NaiveDate), f64>> {
+        let inner = self.inner()?;
+        let mut limits = HashMap::new();
+        for machine_code in SANDBOX_MACHINE_CODES {
+            for date in date_range.0.iter_days().take_while(|d| *d <= date_range.1) {
+                let pool = Self::load_pool(inner, base_version_id, machine_code, date)?;
+                limits.insert((machine_code.to_string(), date), pool.limit_capacity_t);
+            }
+        }
+        Ok(limits)
+    }
+
+    fn load_pool(
+        inner: &SandboxInner,
+        base_version_id: &str,
+        machine_code: &str,
+        plan_date: NaiveDate,
+    ) -> ApiResult<CapacityPool> {
+        Ok(inner
+            .capacity_repo
+            .find_by_machine_and_date(base_version_id, machine_code, plan_date)?
+            .unwrap_or_else(|| {
+                RecalcEngine::create_default_capacity_pool(base_version_id, machine_code, plan_date)
+            }))
+    }
+
+    /// 在快照上试算（dry-run）
+    pub(super) fn evaluate(
+        &self,
+        base_version_id: &str,
+        date_range: (NaiveDate, NaiveDate),
+        strategy_key: &str,
+    ) -> ApiResult<SandboxEvaluation> {
+        let inner = self.inner()?;
+        let profile = inner
+            .recalc_engine
+            .resolve_strategy_profile(strategy_key)
+            .map_err(|e| ApiError::InvalidInput(format!("策略解析失败: {}", e)))?;
+
+        let machine_codes: Vec<String> = SANDBOX_MACHINE_CODES
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let reschedule = inner
+            .recalc_engine
+            .execute_reschedule(
+                base_version_id,
+                date_range,
+                &machine_codes,
+                true,
+                profile.base_strategy,
+                profile.parameters.clone(),
+            )
+            .map_err(|e| ApiError::InternalError(format!("场景试算失败: {}", e)))?;
+
+        let frozen_items = inner
+            .plan_item_repo
+            .find_frozen_items(base_version_id)?
+            .into_iter()
+            .filter(|i| i.plan_date >= date_range.0 && i.plan_date <= date_range.1)
+            .collect();
+
+        Ok(SandboxEvaluation {
+            profile,
+            reschedule,
+            frozen_items,
+        })
+    }
+}
+
+impl ScenarioSandbox {
+    /// 将重算全量输入写入快照（配置 / 机组材料 / 人工确认与拒绝标记）
+    pub(super) fn restore_recalc_inputs(&self, inputs: &RecalcInputSnapshot) -> ApiResult<()> {
+        let inner = self.inner()?;
+
+        let config_json = serde_json::to_string(&inputs.config)
+            .map_err(|e| ApiError::InternalError(e.to_string()))?;
+        inner
+            .config_manager
+            .replace_config_from_snapshot(&config_json)
+            .map_err(|e| ApiError::InternalError(format!("沙盘配置恢复失败: {}", e)))?;
+
+        // 先清空机组上的现有材料，再写回当时的材料（当时不在机组上的材料不参与回放）
+        for machine_code in &inputs.run.machine_codes {
+            inner
+                .material_master_repo
+                .detach_from_machine(machine_code)?;
+        }
+        inner
+            .material_master_repo
+            .batch_insert_material_master(inputs.materials.clone())?;
+        inner
+            .material_state_repo
+            .batch_insert_material_state(inputs.states.clone())?;
+        let rejections: Vec<PathOverrideRejectionSummary> = inputs
+            .path_override_rejections
+            .iter()
+            .map(|r| PathOverrideRejectionSummary {
+                material_id: r.material_id.clone(),
+                reject_cycle_no: r.reject_cycle_no,
+                reject_base_sched_state: r.reject_base_sched_state.clone(),
+            })
+            .collect();
+        inner
+            .material_state_repo
+            .restore_path_override_flags(&inputs.states, &rejections)?;
+        Ok(())
+    }
+
+    /// 在快照上按生产模式回放一键重算（写入快照内的临时版本，与原重算的写入路径一致）
+    ///
+    /// # 返回
+    /// - (回放时采集到的输入, 回放明细[冻结区 + 计算区])
+    pub(super) fn replay_recalc(
+        &self,
+        run: &RecalcRunParams,
+        frozen_items: &[PlanItem],
+        capacity_pools: &[CapacityPool],
+    ) -> ApiResult<(RecalcInputSnapshot, Vec<PlanItem>)> {
+        let inner = self.inner()?;
+        let strategy = ScheduleStrategy::from_str(&run.base_strategy)
+            .map_err(|e| ApiError::InvalidInput(format!("策略解析失败: {}", e)))?;
+        let strategy_params: Option<CustomStrategyParameters> = run
+            .strategy_parameters
+            .clone()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| ApiError::InvalidInput(format!("策略参数解析失败: {}", e)))?;
+
+        let mut version = PlanVersion {
+            version_id: uuid::Uuid::new_v4().to_string(),
+            plan_id: run.plan_id.clone(),
+            version_no: 0,
+            status: PlanVersionStatus::Draft,
+            frozen_from_date: Some(run.frozen_from_date),
+            recalc_window_days: Some(run.window_days),
+            config_snapshot_json: None,
+            created_by: Some("replay".to_string()),
+            created_at: chrono::Utc::now().naive_utc(),
+            revision: 0,
+        };
+        inner
+            .plan_version_repo
+            .create_with_next_version_no(&mut version)?;
+
+        let frozen: Vec<PlanItem> = frozen_items
+            .iter()
+            .cloned()
+            .map(|mut item| {
+                item.version_id = version.version_id.clone();
+                item
+            })
+            .collect();
+        if !frozen.is_empty() {
+            inner.plan_item_repo.batch_insert(&frozen)?;
+        }
+        let pools: Vec<CapacityPool> = capacity_pools
+            .iter()
+            .cloned()
+            .map(|mut pool| {
+                pool.version_id = version.version_id.clone();
+                pool
+            })
+            .collect();
+        if !pools.is_empty() {
+            inner.capacity_repo.upsert_batch(pools)?;
+        }
+
+        let captured = inner
+            .recalc_engine
+            .capture_recalc_inputs(
+                &version.version_id,
+                RecalcRunParams {
+                    engine_version: ENGINE_VERSION.to_string(),
+                    ..run.clone()
+                },
+            )
+            .map_err(|e| ApiError::InternalError(format!("采集回放输入失败: {}", e)))?;
+        let reschedule = inner
+            .recalc_engine
+            .execute_reschedule(
+                &version.version_id,
+                (run.base_date, run.end_date),
+                &run.machine_codes,
+                false,
+                strategy,
+                strategy_params,
+            )
+            .map_err(|e| ApiError::InternalError(format!("重算回放失败: {}", e)))?;
+
+        let mut items = frozen;
+        items.extend(reschedule.plan_items);
+        Ok((captured, items))
+    }
+}
+
+impl Drop for ScenarioSandbox {
+    fn drop(&mut self) {
+        // 先释放快照连接，再删除文件（含 WAL/SHM）
+        self.inner.take();
+        let base = self.path.to_string_lossy().to_string();
+        for path in [
+            base.clone(),
+            format!("{}-wal", base),
+            format!("{}-shm", base),
+        ] {
+            if let Err(e) = std::fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("删除场景沙盘快照失败: {}: {}", path, e);
+                }
+            }
+        }
+    }
+}
@@ -18,6 +18,10 @@ export { configApi } from './tauri/configApi';
 export { pathRuleApi } from './tauri/pathRuleApi';
 export { rollApi } from './tauri/rollApi';
 export { rhythmApi } from './tauri/rhythmApi';
+export { productionApi } from './tauri/productionApi';
+export { scenarioApi } from './tauri/scenarioApi';
+export { versionApprovalApi } from './tauri/versionApprovalApi';
+export { versionLineageApi } from './tauri/versionLineageApi';
 
 // ==========================================
 // Decision Service (D1-D6)
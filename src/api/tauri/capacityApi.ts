@@ -5,6 +5,8 @@ import {
   EmptyOkResponseSchema,
   CapacityPoolSchema,
   BatchUpdateCapacityPoolsResponseSchema,
+  CapacityReservationSchema,
+  ReservationUtilizationReportSchema,
 } from '../ipcSchemas';
 
 // Capacity API (产能池管理)
@@ -79,5 +81,77 @@ export const capacityApi = {
       }
     );
   },
-};
 
+  async createCapacityReservation(params: {
+    machineCode: string;
+    dateFrom: string;
+    dateTo: string;
+    dailyReservedT: number;
+    contractNo?: string;
+    contractPrefix?: string;
+    releaseDaysBefore?: number;
+    expiresOn?: string;
+    reason?: string;
+    operator: string;
+  }): Promise<z.infer<typeof CapacityReservationSchema>> {
+    return IpcClient.call(
+      'create_capacity_reservation',
+      {
+        machine_code: params.machineCode,
+        date_from: params.dateFrom,
+        date_to: params.dateTo,
+        daily_reserved_t: params.dailyReservedT,
+        contract_no: params.contractNo,
+        contract_prefix: params.contractPrefix,
+        release_days_before: params.releaseDaysBefore,
+        expires_on: params.expiresOn,
+        reason: params.reason,
+        operator: params.operator,
+      },
+      {
+        validate: zodValidator(CapacityReservationSchema, 'create_capacity_reservation'),
+      }
+    );
+  },
+
+  async cancelCapacityReservation(reservationId: string, operator: string): Promise<void> {
+    await IpcClient.call(
+      'cancel_capacity_reservation',
+      {
+        reservation_id: reservationId,
+        operator,
+      },
+      {
+        validate: zodValidator(EmptyOkResponseSchema, 'cancel_capacity_reservation'),
+      }
+    );
+  },
+
+  async listCapacityReservations(
+    machineCode?: string
+  ): Promise<Array<z.infer<typeof CapacityReservationSchema>>> {
+    return IpcClient.call(
+      'list_capacity_reservations',
+      {
+        machine_code: machineCode,
+      },
+      {
+        validate: zodValidator(z.array(CapacityReservationSchema), 'list_capacity_reservations'),
+      }
+    );
+  },
+
+  async getReservationUtilization(
+    versionId: string
+  ): Promise<z.infer<typeof ReservationUtilizationReportSchema>> {
+    return IpcClient.call(
+      'get_reservation_utilization',
+      {
+        version_id: versionId,
+      },
+      {
+        validate: zodValidator(ReservationUtilizationReportSchema, 'get_reservation_utilization'),
+      }
+    );
+  },
+};
@@ -18,6 +18,7 @@ import {
   CapacityOpportunityResponseSchema,
   ErrorResponseSchema,
 } from '../ipcSchemas/decision';
+import { PlanObjectiveVectorSchema } from '../ipcSchemas/planEvaluationSchemas';
 import type {
   GetDecisionDaySummaryRequest,
   DecisionDaySummaryResponse,
@@ -33,6 +34,8 @@ import type {
   RollCampaignAlertResponse,
   GetCapacityOpportunityRequest,
   CapacityOpportunityResponse,
+  GetPlanObjectivesRequest,
+  PlanObjectives,
 } from '../../types/decision';
 
 // ==========================================
@@ -204,6 +207,23 @@ async function callWithValidation<T>(
   }
 }
 
+// ==========================================
+// D0: 方案多目标评价 API
+// ==========================================
+
+/**
+ * 获取方案多目标评价（D0，与策略草案/版本对比同一口径）
+ */
+export async function getPlanObjectives(
+  request: GetPlanObjectivesRequest
+): Promise<PlanObjectives> {
+  return callWithValidation<PlanObjectives>(
+    'get_plan_objectives',
+    request,
+    PlanObjectiveVectorSchema
+  );
+}
+
 // ==========================================
 // D1: 日期风险摘要 API
 // ==========================================
@@ -562,6 +582,9 @@ export async function getCapacityOpportunityForRecentDays(
 // ==========================================
 
 export const decisionService = {
+  // D0: 方案多目标评价
+  getPlanObjectives,
+
   // D1: 日期风险摘要
   getDecisionDaySummary,
   getMostRiskyDate,
@@ -9,13 +9,21 @@ import {
   type BatchResolveConflictsResponse,
   CancelImportBatchResponseSchema,
   type CancelImportBatchResponse,
+  CancelMaterialImportResponseSchema,
+  ContractImportResponseSchema,
+  type ContractImportResponse,
+  AutoImportStatusSchema,
+  type AutoImportStatus,
+  AutoImportFileResultSchema,
+  type AutoImportFileResult,
 } from '../ipcSchemas';
 
 export const importApi = {
   async importMaterials(
     filePath: string,
     sourceBatchId: string,
-    mappingProfileId?: string
+    mappingProfileId?: string,
+    options?: { sheetName?: string; encoding?: string }
   ): Promise<z.infer<typeof ImportApiResponseSchema>> {
     // 使用 snake_case 参数名（后端配置 rename_all = "snake_case"）
     return IpcClient.call(
@@ -24,6 +32,8 @@ export const importApi = {
         file_path: filePath,
         source_batch_id: sourceBatchId,
         mapping_profile_id: mappingProfileId,
+        sheet_name: options?.sheetName,
+        encoding: options?.encoding,
       },
       {
         validate: zodValidator(ImportApiResponseSchema, 'import_materials'),
@@ -31,6 +41,60 @@ export const importApi = {
     );
   },
 
+  // 取消进行中的导入（在下一个分块边界生效，已提交分块保留）
+  async cancelMaterialImport(sourceBatchId: string): Promise<{ cancelled: boolean }> {
+    return IpcClient.call(
+      'cancel_material_import',
+      {
+        source_batch_id: sourceBatchId,
+      },
+      {
+        validate: zodValidator(CancelMaterialImportResponseSchema, 'cancel_material_import'),
+      }
+    );
+  },
+
+  // 导入合同/订单文件（按合同号更新关联材料并重算催料/紧急等级）
+  async importContracts(
+    filePath: string,
+    options?: { sheetName?: string; encoding?: string },
+    operator: string = 'system'
+  ): Promise<ContractImportResponse> {
+    return IpcClient.call(
+      'import_contracts',
+      {
+        file_path: filePath,
+        sheet_name: options?.sheetName,
+        encoding: options?.encoding,
+        operator,
+      },
+      {
+        validate: zodValidator(ContractImportResponseSchema, 'import_contracts'),
+      }
+    );
+  },
+
+  async getAutoImportStatus(): Promise<AutoImportStatus> {
+    return IpcClient.call(
+      'get_auto_import_status',
+      {},
+      {
+        validate: zodValidator(AutoImportStatusSchema, 'get_auto_import_status'),
+      }
+    );
+  },
+
+  // 立即扫描监控目录（手动触发）
+  async runAutoImportScan(): Promise<AutoImportFileResult[]> {
+    return IpcClient.call(
+      'run_auto_import_scan',
+      {},
+      {
+        validate: zodValidator(z.array(AutoImportFileResultSchema), 'run_auto_import_scan'),
+      }
+    );
+  },
+
   async listImportConflicts(
     status?: string,
     limit: number = 50,
@@ -6,6 +6,11 @@ import {
   MaterialWithStateSchema,
   MaterialDetailResponseSchema,
   MaterialPoolSummaryResponseSchema,
+  InterventionExpirySchema,
+  UpcomingExpirationsResponseSchema,
+  ExpirySweepResultSchema,
+  InterventionExpiryStatusSchema,
+  type InterventionType,
 } from '../ipcSchemas';
 
 export const materialApi = {
@@ -152,4 +157,93 @@ export const materialApi = {
       }
     );
   },
+
+  // ==========================================
+  // 人工干预时效
+  // ==========================================
+
+  /** 设置人工干预时效（expiresAt 为 "YYYY-MM-DD HH:MM"，与 expireAfterRecalcs 至少一个） */
+  async setInterventionExpiry(params: {
+    materialIds: string[];
+    interventionType: InterventionType;
+    expiresAt?: string;
+    expireAfterRecalcs?: number;
+    operator: string;
+    reason?: string;
+  }): Promise<Array<z.infer<typeof InterventionExpirySchema>>> {
+    return IpcClient.call(
+      'set_intervention_expiry',
+      {
+        material_ids: params.materialIds,
+        intervention_type: params.interventionType,
+        expires_at: params.expiresAt,
+        expire_after_recalcs: params.expireAfterRecalcs,
+        operator: params.operator,
+        reason: params.reason,
+      },
+      {
+        validate: zodValidator(z.array(InterventionExpirySchema), 'set_intervention_expiry'),
+      }
+    );
+  },
+
+  async clearInterventionExpiry(
+    materialIds: string[],
+    interventionType: InterventionType,
+    operator: string
+  ): Promise<{ removed_count: number }> {
+    return IpcClient.call(
+      'clear_intervention_expiry',
+      {
+        material_ids: materialIds,
+        intervention_type: interventionType,
+        operator,
+      },
+      {
+        validate: zodValidator(
+          z.object({ removed_count: z.number() }).passthrough(),
+          'clear_intervention_expiry'
+        ),
+      }
+    );
+  },
+
+  async listUpcomingInterventionExpirations(
+    operator?: string,
+    withinHours?: number
+  ): Promise<z.infer<typeof UpcomingExpirationsResponseSchema>> {
+    return IpcClient.call(
+      'list_upcoming_intervention_expirations',
+      {
+        operator,
+        within_hours: withinHours,
+      },
+      {
+        validate: zodValidator(
+          UpcomingExpirationsResponseSchema,
+          'list_upcoming_intervention_expirations'
+        ),
+      }
+    );
+  },
+
+  async getInterventionExpiryStatus(): Promise<z.infer<typeof InterventionExpiryStatusSchema>> {
+    return IpcClient.call(
+      'get_intervention_expiry_status',
+      {},
+      {
+        validate: zodValidator(InterventionExpiryStatusSchema, 'get_intervention_expiry_status'),
+      }
+    );
+  },
+
+  async runInterventionExpirySweep(): Promise<z.infer<typeof ExpirySweepResultSchema>> {
+    return IpcClient.call(
+      'run_intervention_expiry_sweep',
+      {},
+      {
+        validate: zodValidator(ExpirySweepResultSchema, 'run_intervention_expiry_sweep'),
+      }
+    );
+  },
 };
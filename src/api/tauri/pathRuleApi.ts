@@ -8,6 +8,7 @@ import {
   PathOverridePendingSummarySchema,
   RollCycleAnchorSchema,
   BatchConfirmPathOverrideResultSchema,
+  ResequenceResultSchema,
 } from '../ipcSchemas';
 
 // Path Rule API (宽厚路径规则 v0.6)
@@ -237,4 +238,47 @@ export const pathRuleApi = {
       }
     );
   },
+  async previewResequenceMachineDay(params: {
+    versionId: string;
+    machineCode: string;
+    planDate: string;
+    materialIds: string[];
+  }): Promise<z.infer<typeof ResequenceResultSchema>> {
+    return IpcClient.call(
+      'preview_resequence_machine_day',
+      {
+        version_id: params.versionId,
+        machine_code: params.machineCode,
+        plan_date: params.planDate,
+        material_ids: JSON.stringify(params.materialIds),
+      },
+      {
+        validate: zodValidator(ResequenceResultSchema, 'preview_resequence_machine_day'),
+      }
+    );
+  },
+
+  async resequenceMachineDay(params: {
+    versionId: string;
+    machineCode: string;
+    planDate: string;
+    materialIds: string[];
+    operator: string;
+    reason?: string;
+  }): Promise<z.infer<typeof ResequenceResultSchema>> {
+    return IpcClient.call(
+      'resequence_machine_day',
+      {
+        version_id: params.versionId,
+        machine_code: params.machineCode,
+        plan_date: params.planDate,
+        material_ids: JSON.stringify(params.materialIds),
+        operator: params.operator,
+        reason: params.reason,
+      },
+      {
+        validate: zodValidator(ResequenceResultSchema, 'resequence_machine_day'),
+      }
+    );
+  },
 };
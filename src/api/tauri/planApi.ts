@@ -6,17 +6,32 @@ import {
   PlanVersionSchema,
   StrategyPresetSchema,
   GenerateStrategyDraftsResponseSchema,
+  CancelStrategyDraftGenerationResponseSchema,
+  StrategySweepResponseSchema,
+  StrategySweepSpecSchema,
+  StrategyWeightLearningResponseSchema,
   ApplyStrategyDraftResponseSchema,
   GetStrategyDraftDetailResponseSchema,
   ListStrategyDraftsResponseSchema,
   CleanupStrategyDraftsResponseSchema,
   PlanItemSchema,
   PlanItemDateBoundsResponseSchema,
+  BatchingDeferralSchema,
   VersionComparisonResultSchema,
   VersionComparisonKpiResultSchema,
+  VersionDiffItemPageSchema,
+  ChangeNoticeSchema,
+  ChangeNoticeExportResponseSchema,
   MoveItemsResponseSchema,
+  BottleneckRecommendationResponseSchema,
+  ApplyBottleneckRecommendationsResponseSchema,
   RollbackVersionResponseSchema,
   RecalcResponseSchema,
+  VersionMergePreviewResponseSchema,
+  VersionMergeApplyResponseSchema,
+  RushInsertionResponseSchema,
+  UndoStatusResponseSchema,
+  UndoRedoResponseSchema,
 } from '../ipcSchemas';
 
 export const planApi = {
@@ -85,12 +100,17 @@ export const planApi = {
     });
   },
 
-  async activateVersion(versionId: string, operator: string): Promise<void> {
+  async activateVersion(
+    versionId: string,
+    operator: string,
+    overrideReason?: string
+  ): Promise<void> {
     return IpcClient.call(
       'activate_version',
       {
         version_id: versionId,
         operator,
+        override_reason: overrideReason,
       },
       {
         timeout: IPC_TIMEOUT.VERY_SLOW,
@@ -182,6 +202,8 @@ export const planApi = {
     plan_date_to: string;
     strategies: string[];
     operator: string;
+    /** 由前端生成，用于匹配进度事件与取消 */
+    generation_id?: string;
   }): Promise<z.infer<typeof GenerateStrategyDraftsResponseSchema>> {
     return IpcClient.call(
       'generate_strategy_drafts',
@@ -191,6 +213,7 @@ export const planApi = {
         plan_date_to: params.plan_date_to,
         strategies: params.strategies,
         operator: params.operator,
+        generation_id: params.generation_id ?? undefined,
       },
       {
         timeout: IPC_TIMEOUT.VERY_SLOW,
@@ -199,6 +222,64 @@ export const planApi = {
     );
   },
 
+  async cancelStrategyDraftGeneration(
+    generationId: string
+  ): Promise<z.infer<typeof CancelStrategyDraftGenerationResponseSchema>> {
+    return IpcClient.call(
+      'cancel_strategy_draft_generation',
+      { generation_id: generationId },
+      {
+        validate: zodValidator(
+          CancelStrategyDraftGenerationResponseSchema,
+          'cancel_strategy_draft_generation'
+        ),
+      }
+    );
+  },
+
+  async runStrategySweep(params: {
+    base_version_id: string;
+    plan_date_from: string;
+    plan_date_to: string;
+    spec: z.infer<typeof StrategySweepSpecSchema>;
+    operator: string;
+  }): Promise<z.infer<typeof StrategySweepResponseSchema>> {
+    return IpcClient.call(
+      'run_strategy_sweep',
+      {
+        base_version_id: params.base_version_id,
+        plan_date_from: params.plan_date_from,
+        plan_date_to: params.plan_date_to,
+        spec_json: JSON.stringify(params.spec),
+        operator: params.operator,
+      },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(StrategySweepResponseSchema, 'run_strategy_sweep'),
+      }
+    );
+  },
+
+  /** 人工调整反推策略权重（离线分析，只读） */
+  async learnStrategyWeights(params: {
+    analysis_from: string;
+    analysis_to: string;
+    strategy_key: string;
+  }): Promise<z.infer<typeof StrategyWeightLearningResponseSchema>> {
+    return IpcClient.call(
+      'learn_strategy_weights',
+      {
+        analysis_from: params.analysis_from,
+        analysis_to: params.analysis_to,
+        strategy_key: params.strategy_key,
+      },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(StrategyWeightLearningResponseSchema, 'learn_strategy_weights'),
+      }
+    );
+  },
+
   async applyStrategyDraft(
     draftId: string,
     operator: string
@@ -325,6 +406,18 @@ export const planApi = {
     );
   },
 
+  async listBatchingDeferrals(versionId: string): Promise<Array<z.infer<typeof BatchingDeferralSchema>>> {
+    return IpcClient.call(
+      'list_batching_deferrals',
+      {
+        version_id: versionId,
+      },
+      {
+        validate: zodValidator(z.array(BatchingDeferralSchema), 'list_batching_deferrals'),
+      }
+    );
+  },
+
   async compareVersions(versionIdA: string, versionIdB: string): Promise<z.infer<typeof VersionComparisonResultSchema>> {
     return IpcClient.call(
       'compare_versions',
@@ -354,6 +447,86 @@ export const planApi = {
     );
   },
 
+  async listVersionDiffItems(params: {
+    versionIdA: string;
+    versionIdB: string;
+    changeType?: 'ADDED' | 'REMOVED' | 'MOVED' | 'SEQ_CHANGED';
+    machineCode?: string;
+    limit: number;
+    offset: number;
+  }): Promise<z.infer<typeof VersionDiffItemPageSchema>> {
+    return IpcClient.call(
+      'list_version_diff_items',
+      {
+        version_id_a: params.versionIdA,
+        version_id_b: params.versionIdB,
+        change_type: params.changeType,
+        machine_code: params.machineCode,
+        limit: params.limit,
+        offset: params.offset,
+      },
+      {
+        validate: zodValidator(VersionDiffItemPageSchema, 'list_version_diff_items'),
+      }
+    );
+  },
+
+  async generateChangeNotice(
+    fromVersionId: string | null | undefined,
+    toVersionId: string
+  ): Promise<z.infer<typeof ChangeNoticeSchema>> {
+    return IpcClient.call(
+      'generate_change_notice',
+      {
+        from_version_id: fromVersionId ?? undefined,
+        to_version_id: toVersionId,
+      },
+      {
+        validate: zodValidator(ChangeNoticeSchema, 'generate_change_notice'),
+      }
+    );
+  },
+
+  async getActivationChangeNotice(
+    versionId: string,
+    filters?: { machineCode?: string; dateFrom?: string; dateTo?: string }
+  ): Promise<z.infer<typeof ChangeNoticeSchema>> {
+    return IpcClient.call(
+      'get_activation_change_notice',
+      {
+        version_id: versionId,
+        machine_code: filters?.machineCode,
+        date_from: filters?.dateFrom,
+        date_to: filters?.dateTo,
+      },
+      {
+        validate: zodValidator(ChangeNoticeSchema, 'get_activation_change_notice'),
+      }
+    );
+  },
+
+  async exportChangeNotice(
+    versionId: string,
+    format: 'csv' | 'xlsx',
+    outputPath: string,
+    filters?: { machineCode?: string; dateFrom?: string; dateTo?: string }
+  ): Promise<z.infer<typeof ChangeNoticeExportResponseSchema>> {
+    return IpcClient.call(
+      'export_change_notice',
+      {
+        version_id: versionId,
+        format,
+        output_path: outputPath,
+        machine_code: filters?.machineCode,
+        date_from: filters?.dateFrom,
+        date_to: filters?.dateTo,
+      },
+      {
+        validate: zodValidator(ChangeNoticeExportResponseSchema, 'export_change_notice'),
+      }
+    );
+  },
+
   async moveItems(
     versionId: string,
     moves: Array<{
@@ -380,4 +553,200 @@ export const planApi = {
       }
     );
   },
+
+  /** 生成瓶颈优化建议（只读，含每条建议的预计影响） */
+  async listBottleneckRecommendations(params: {
+    version_id: string;
+    plan_date_from: string;
+    plan_date_to: string;
+    machine_code?: string | null;
+  }): Promise<z.infer<typeof BottleneckRecommendationResponseSchema>> {
+    return IpcClient.call(
+      'list_bottleneck_recommendations',
+      {
+        version_id: params.version_id,
+        plan_date_from: params.plan_date_from,
+        plan_date_to: params.plan_date_to,
+        machine_code: params.machine_code ?? null,
+      },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(
+          BottleneckRecommendationResponseSchema,
+          'list_bottleneck_recommendations'
+        ),
+      }
+    );
+  },
+
+  /** 一键执行瓶颈优化建议（后端按当前数据重算建议，失效ID计入 skipped） */
+  async applyBottleneckRecommendations(params: {
+    version_id: string;
+    plan_date_from: string;
+    plan_date_to: string;
+    machine_code?: string | null;
+    recommendation_ids: string[];
+    operator: string;
+    reason: string;
+  }): Promise<z.infer<typeof ApplyBottleneckRecommendationsResponseSchema>> {
+    return IpcClient.call(
+      'apply_bottleneck_recommendations',
+      {
+        version_id: params.version_id,
+        plan_date_from: params.plan_date_from,
+        plan_date_to: params.plan_date_to,
+        machine_code: params.machine_code ?? null,
+        recommendation_ids: params.recommendation_ids,
+        operator: params.operator,
+        reason: params.reason,
+      },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(
+          ApplyBottleneckRecommendationsResponseSchema,
+          'apply_bottleneck_recommendations'
+        ),
+      }
+    );
+  },
+
+  async previewVersionMerge(
+    ancestorVersionId: string,
+    adjustedVersionId: string,
+    recalculatedVersionId: string
+  ): Promise<z.infer<typeof VersionMergePreviewResponseSchema>> {
+    return IpcClient.call(
+      'preview_version_merge',
+      {
+        ancestor_version_id: ancestorVersionId,
+        adjusted_version_id: adjustedVersionId,
+        recalculated_version_id: recalculatedVersionId,
+      },
+      {
+        timeout: IPC_TIMEOUT.SLOW,
+        validate: zodValidator(VersionMergePreviewResponseSchema, 'preview_version_merge'),
+      }
+    );
+  },
+
+  async applyVersionMerge(
+    ancestorVersionId: string,
+    adjustedVersionId: string,
+    recalculatedVersionId: string,
+    resolutions: Array<{
+      material_id: string;
+      resolution: 'KEEP_RECALC' | 'FORCE_MANUAL';
+    }>,
+    operator: string = 'system'
+  ): Promise<z.infer<typeof VersionMergeApplyResponseSchema>> {
+    return IpcClient.call(
+      'apply_version_merge',
+      {
+        ancestor_version_id: ancestorVersionId,
+        adjusted_version_id: adjustedVersionId,
+        recalculated_version_id: recalculatedVersionId,
+        resolutions: JSON.stringify(resolutions),
+        operator,
+      },
+      {
+        timeout: IPC_TIMEOUT.SLOW,
+        validate: zodValidator(VersionMergeApplyResponseSchema, 'apply_version_merge'),
+      }
+    );
+  },
+
+  /** 预览紧急插单（最早可行日 + 挤出链 + 拖期变化，不落库） */
+  async previewRushInsertion(
+    versionId: string,
+    materialIds: string[]
+  ): Promise<z.infer<typeof RushInsertionResponseSchema>> {
+    return IpcClient.call(
+      'preview_rush_insertion',
+      {
+        version_id: versionId,
+        material_ids: materialIds,
+      },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(RushInsertionResponseSchema, 'preview_rush_insertion'),
+      }
+    );
+  },
+
+  /** 执行紧急插单（结果保存为派生草稿版本，基准版本不变） */
+  async applyRushInsertion(
+    versionId: string,
+    materialIds: string[],
+    operator: string,
+    reason: string
+  ): Promise<z.infer<typeof RushInsertionResponseSchema>> {
+    return IpcClient.call(
+      'apply_rush_insertion',
+      {
+        version_id: versionId,
+        material_ids: materialIds,
+        operator,
+        reason,
+      },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(RushInsertionResponseSchema, 'apply_rush_insertion'),
+      }
+    );
+  },
+
+  /** 查询撤销/重做状态（按操作人 + 版本） */
+  async getUndoStatus(
+    versionId: string,
+    operator: string
+  ): Promise<z.infer<typeof UndoStatusResponseSchema>> {
+    return IpcClient.call(
+      'get_undo_status',
+      {
+        version_id: versionId,
+        operator,
+      },
+      {
+        validate: zodValidator(UndoStatusResponseSchema, 'get_undo_status'),
+      }
+    );
+  },
+
+  /** 撤销最近一次人工操作（后续操作冲突时拒绝并返回逐材料说明） */
+  async undoLastOperation(
+    versionId: string,
+    operator: string,
+    reason?: string
+  ): Promise<z.infer<typeof UndoRedoResponseSchema>> {
+    return IpcClient.call(
+      'undo_last_operation',
+      {
+        version_id: versionId,
+        operator,
+        reason,
+      },
+      {
+        validate: zodValidator(UndoRedoResponseSchema, 'undo_last_operation'),
+      }
+    );
+  },
+
+  /** 重做最近一次撤销 */
+  async redoLastOperation(
+    versionId: string,
+    operator: string,
+    reason?: string
+  ): Promise<z.infer<typeof UndoRedoResponseSchema>> {
+    return IpcClient.call(
+      'redo_last_operation',
+      {
+        version_id: versionId,
+        operator,
+        reason,
+      },
+      {
+        validate: zodValidator(UndoRedoResponseSchema, 'redo_last_operation'),
+      }
+    );
+  },
 };
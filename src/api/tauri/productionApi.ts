@@ -0,0 +1,65 @@
+import { IpcClient } from '../ipcClient';
+import {
+  zodValidator,
+  ProductionActualsImportResponseSchema,
+  type ProductionActualsImportResponse,
+  PlanAdherenceReportSchema,
+  type PlanAdherenceReport,
+  CarryOverItemsResponseSchema,
+  type CarryOverItem,
+} from '../ipcSchemas';
+
+// Production Actuals API (生产实绩回填 / 计划执行对账)
+export const productionApi = {
+  async importProductionActuals(
+    filePath: string,
+    options?: { sheetName?: string; encoding?: string },
+    operator: string = 'system'
+  ): Promise<ProductionActualsImportResponse> {
+    return IpcClient.call(
+      'import_production_actuals',
+      {
+        file_path: filePath,
+        sheet_name: options?.sheetName,
+        encoding: options?.encoding,
+        operator,
+      },
+      {
+        validate: zodValidator(
+          ProductionActualsImportResponseSchema,
+          'import_production_actuals'
+        ),
+      }
+    );
+  },
+
+  async getPlanAdherenceReport(params: {
+    versionId: string;
+    machineCode?: string;
+    dateFrom?: string;
+    dateTo?: string;
+  }): Promise<PlanAdherenceReport> {
+    return IpcClient.call(
+      'get_plan_adherence_report',
+      {
+        version_id: params.versionId,
+        machine_code: params.machineCode,
+        date_from: params.dateFrom,
+        date_to: params.dateTo,
+      },
+      {
+        validate: zodValidator(PlanAdherenceReportSchema, 'get_plan_adherence_report'),
+      }
+    );
+  },
+
+  async listCarryOverItems(versionId: string, openOnly: boolean = true): Promise<CarryOverItem[]> {
+    return IpcClient.call(
+      'list_carry_over_items',
+      { version_id: versionId, open_only: openOnly },
+      {
+        validate: zodValidator(CarryOverItemsResponseSchema, 'list_carry_over_items'),
+      }
+    );
+  },
+};
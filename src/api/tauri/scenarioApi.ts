@@ -0,0 +1,163 @@
+import { IpcClient, IPC_TIMEOUT } from '../ipcClient';
+import {
+  zodValidator,
+  EmptyOkResponseSchema,
+  CreateScenarioPlanResponseSchema,
+  ScenarioOverlaySchema,
+  type ScenarioOverlay,
+  ScenarioEvaluationResponseSchema,
+  type ScenarioEvaluationResponse,
+  ScenarioRunSchema,
+  type ScenarioRun,
+  PromoteScenarioResponseSchema,
+  type PromoteScenarioResponse,
+  BreakdownSimulationResponseSchema,
+  type BreakdownSimulationResponse,
+  RecalcReplayReportSchema,
+  type RecalcReplayReport,
+} from '../ipcSchemas';
+
+// Scenario / Sandbox Plan API (场景方案隔离评估 / 提升为草稿版本)
+export const scenarioApi = {
+  async createScenarioPlan(
+    basePlanId: string,
+    planName: string,
+    planType: 'SCENARIO' | 'SANDBOX' = 'SCENARIO',
+    operator: string = 'admin'
+  ): Promise<string> {
+    const result = await IpcClient.call(
+      'create_scenario_plan',
+      {
+        base_plan_id: basePlanId,
+        plan_name: planName,
+        plan_type: planType,
+        operator,
+      },
+      {
+        validate: zodValidator(CreateScenarioPlanResponseSchema, 'create_scenario_plan'),
+      }
+    );
+    return result.plan_id;
+  },
+
+  async saveScenarioOverlay(
+    planId: string,
+    overlay: ScenarioOverlay,
+    operator: string = 'admin'
+  ): Promise<void> {
+    await IpcClient.call(
+      'save_scenario_overlay',
+      { plan_id: planId, overlay: JSON.stringify(overlay), operator },
+      {
+        validate: zodValidator(EmptyOkResponseSchema, 'save_scenario_overlay'),
+      }
+    );
+  },
+
+  async getScenarioOverlay(planId: string): Promise<ScenarioOverlay> {
+    return IpcClient.call(
+      'get_scenario_overlay',
+      { plan_id: planId },
+      {
+        validate: zodValidator(ScenarioOverlaySchema, 'get_scenario_overlay'),
+      }
+    );
+  },
+
+  async evaluateScenario(params: {
+    planId: string;
+    baseDate: string;
+    windowDays?: number;
+    strategy?: string;
+    operator?: string;
+  }): Promise<ScenarioEvaluationResponse> {
+    return IpcClient.call(
+      'evaluate_scenario',
+      {
+        plan_id: params.planId,
+        base_date: params.baseDate,
+        window_days: params.windowDays,
+        strategy: params.strategy,
+        operator: params.operator ?? 'admin',
+      },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(ScenarioEvaluationResponseSchema, 'evaluate_scenario'),
+      }
+    );
+  },
+
+  async getScenarioRun(runId: string): Promise<ScenarioRun> {
+    return IpcClient.call(
+      'get_scenario_run',
+      { run_id: runId },
+      {
+        validate: zodValidator(ScenarioRunSchema, 'get_scenario_run'),
+      }
+    );
+  },
+
+  async promoteScenarioRun(
+    runId: string,
+    operator: string = 'admin'
+  ): Promise<PromoteScenarioResponse> {
+    return IpcClient.call(
+      'promote_scenario_run',
+      { run_id: runId, operator },
+      {
+        timeout: IPC_TIMEOUT.SLOW,
+        validate: zodValidator(PromoteScenarioResponseSchema, 'promote_scenario_run'),
+      }
+    );
+  },
+
+  /**
+   * 机组故障 what-if：outageStart 格式 YYYY-MM-DD HH:MM:SS；
+   * 默认不生成版本，createVersion=true 时保存为草稿版本
+   */
+  async simulateMachineBreakdown(params: {
+    versionId: string;
+    machineCode: string;
+    outageStart: string;
+    outageHours: number;
+    capacityReductionPct?: number;
+    windowDays?: number;
+    strategy?: string;
+    createVersion?: boolean;
+    operator?: string;
+  }): Promise<BreakdownSimulationResponse> {
+    return IpcClient.call(
+      'simulate_machine_breakdown',
+      {
+        version_id: params.versionId,
+        machine_code: params.machineCode,
+        outage_start: params.outageStart,
+        outage_hours: params.outageHours,
+        capacity_reduction_pct: params.capacityReductionPct,
+        window_days: params.windowDays,
+        strategy: params.strategy,
+        create_version: params.createVersion,
+        operator: params.operator ?? 'admin',
+      },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(BreakdownSimulationResponseSchema, 'simulate_machine_breakdown'),
+      }
+    );
+  },
+
+  /**
+   * 回放历史一键重算（沙盘内执行）并与已存明细对比；
+   * 未保存全量输入时使用当前数据，input_mismatches 列出与原指纹不一致的部分
+   */
+  async replayRecalc(versionId: string): Promise<RecalcReplayReport> {
+    return IpcClient.call(
+      'replay_recalc',
+      { version_id: versionId },
+      {
+        timeout: IPC_TIMEOUT.VERY_SLOW,
+        validate: zodValidator(RecalcReplayReportSchema, 'replay_recalc'),
+      }
+    );
+  },
+};
@@ -0,0 +1,99 @@
+import { IpcClient } from '../ipcClient';
+import {
+  zodValidator,
+  ActivationChecklistSchema,
+  type ActivationChecklist,
+  RedLineReportSchema,
+  type RedLineReport,
+  VersionReviewSchema,
+  type VersionReview,
+  VersionReviewHistorySchema,
+  type VersionReviewHistory,
+} from '../ipcSchemas';
+
+// Version Approval API (版本激活审批：计划员提交 / 生产经理审批)
+export const versionApprovalApi = {
+  async submitVersion(
+    versionId: string,
+    comment?: string,
+    operator: string = 'admin'
+  ): Promise<VersionReview> {
+    return IpcClient.call(
+      'submit_version_for_approval',
+      {
+        version_id: versionId,
+        comment,
+        operator,
+      },
+      {
+        validate: zodValidator(VersionReviewSchema, 'submit_version_for_approval'),
+      }
+    );
+  },
+
+  async approveVersion(
+    versionId: string,
+    comment?: string,
+    operator: string = 'admin'
+  ): Promise<VersionReview> {
+    return IpcClient.call(
+      'approve_version',
+      {
+        version_id: versionId,
+        comment,
+        operator,
+      },
+      {
+        validate: zodValidator(VersionReviewSchema, 'approve_version'),
+      }
+    );
+  },
+
+  async rejectVersion(
+    versionId: string,
+    comment: string,
+    operator: string = 'admin'
+  ): Promise<VersionReview> {
+    return IpcClient.call(
+      'reject_version',
+      {
+        version_id: versionId,
+        comment,
+        operator,
+      },
+      {
+        validate: zodValidator(VersionReviewSchema, 'reject_version'),
+      }
+    );
+  },
+
+  async getVersionReviews(versionId: string): Promise<VersionReviewHistory> {
+    return IpcClient.call(
+      'get_version_reviews',
+      { version_id: versionId },
+      {
+        validate: zodValidator(VersionReviewHistorySchema, 'get_version_reviews'),
+      }
+    );
+  },
+
+  async getActivationChecklist(versionId: string): Promise<ActivationChecklist> {
+    return IpcClient.call(
+      'get_activation_checklist',
+      { version_id: versionId },
+      {
+        validate: zodValidator(ActivationChecklistSchema, 'get_activation_checklist'),
+      }
+    );
+  },
+
+  async getRedLineReport(versionId: string): Promise<RedLineReport> {
+    return IpcClient.call(
+      'get_red_line_report',
+      { version_id: versionId },
+      {
+        validate: zodValidator(RedLineReportSchema, 'get_red_line_report'),
+      }
+    );
+  },
+};
@@ -0,0 +1,46 @@
+import { IpcClient } from '../ipcClient';
+import {
+  zodValidator,
+  VersionLineageTreeSchema,
+  type VersionLineageTree,
+  VersionPurgeReportSchema,
+  type VersionPurgeReport,
+  type VersionRetentionPolicy,
+} from '../ipcSchemas';
+
+// Version Lineage API (版本谱系树 / 按保留策略清理历史版本)
+export const versionLineageApi = {
+  async getVersionLineage(planId: string): Promise<VersionLineageTree> {
+    return IpcClient.call(
+      'get_version_lineage',
+      {
+        plan_id: planId,
+      },
+      {
+        validate: zodValidator(VersionLineageTreeSchema, 'get_version_lineage'),
+      }
+    );
+  },
+
+  async purgeVersions(params: {
+    planId?: string;
+    policy?: Partial<VersionRetentionPolicy>;
+    dryRun?: boolean;
+    operator?: string;
+  }): Promise<VersionPurgeReport> {
+    return IpcClient.call(
+      'purge_versions',
+      {
+        plan_id: params.planId,
+        keep_archived_per_plan: params.policy?.keep_archived_per_plan,
+        keep_ever_active: params.policy?.keep_ever_active,
+        max_age_days: params.policy?.max_age_days,
+        dry_run: params.dryRun ?? true,
+        operator: params.operator ?? 'admin',
+      },
+      {
+        validate: zodValidator(VersionPurgeReportSchema, 'purge_versions'),
+      }
+    );
+  },
+};
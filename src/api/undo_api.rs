@@ -0,0 +1,433 @@
+// ==========================================
+// 热轧精整排产系统 - 人工操作撤销/重做 API
+// ==========================================
+// 职责:
+// - 按操作人 + 版本回放 action_log，得到撤销/重做栈
+// - 撤销/重做: 应用日志中的逆操作载荷（payload_json["undo"]），校验口径同原操作
+// - 后续操作已改动相关材料时拒绝执行，并逐材料说明冲突来源
+// 说明:
+// - 可撤销操作: move_items / 锁定解锁 / 强制放行 / 人工紧急 / 路径突破确认与拒绝
+// - 材料级操作不关联版本，计入该操作人所有版本的撤销栈
+// - 撤销/重做本身记录为 UNDO / REDO 日志（携带反向载荷，供重做/再撤销）
+// ==========================================
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::error::{ApiError, ApiResult, ValidationViolation};
+use crate::api::plan_api::item_position;
+use crate::api::{MaterialApi, PathRuleApi, PlanApi};
+use crate::domain::action_log::ActionLog;
+use crate::domain::undo::{
+    HistoryKind, HistoryTag, InversePayload, UndoStacks, HISTORY_PAYLOAD_KEY, UNDO_PAYLOAD_KEY,
+};
+use crate::repository::action_log_repo::ActionLogRepository;
+use crate::repository::plan_repo::{PlanItemRepository, PlanVersionRepository};
+
+/// 回放的可撤销日志上限（超出部分不再可撤销）
+const UNDO_HISTORY_LIMIT: i32 = 200;
+/// 冲突说明中每个材料检索的后续日志数上限
+const CONFLICT_LOOKUP_LIMIT: i32 = 20;
+
+// ==========================================
+// DTO 定义
+// ==========================================
+
+/// 撤销/重做栈条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntryDto {
+    /// 栈顶日志ID（撤销/重做时作为 target_action_id）
+    pub action_id: String,
+    /// 原始操作类型（如 MOVE_ITEMS / LOCK_MATERIALS / PathOverrideConfirm）
+    pub source_action_type: String,
+    /// 逆操作载荷类型（MOVE_ITEMS / MATERIAL_FLAGS / PATH_OVERRIDE）
+    pub payload_kind: String,
+    pub material_ids: Vec<String>,
+    pub action_ts: String,
+    pub detail: Option<String>,
+}
+
+/// 撤销/重做状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoStatusResponse {
+    pub version_id: String,
+    pub actor: String,
+    pub undo_depth: usize,
+    pub redo_depth: usize,
+    pub next_undo: Option<UndoEntryDto>,
+    pub next_redo: Option<UndoEntryDto>,
+}
+
+/// 撤销/重做执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoRedoResponse {
+    pub version_id: String,
+    /// UNDO / REDO
+    pub kind: String,
+    /// 新写入的 UNDO / REDO 日志ID
+    pub action_id: String,
+    pub target_action_id: String,
+    pub source_action_type: String,
+    pub payload_kind: String,
+    pub material_ids: Vec<String>,
+    /// 执行后的栈状态
+    pub status: UndoStatusResponse,
+    pub message: String,
+}
+
+// ==========================================
+// UndoApi
+// ==========================================
+
+pub struct UndoApi {
+    plan_version_repo: Arc<PlanVersionRepository>,
+    plan_item_repo: Arc<PlanItemRepository>,
+    action_log_repo: Arc<ActionLogRepository>,
+    plan_api: Arc<PlanApi>,
+    material_api: Arc<MaterialApi>,
+    path_rule_api: Arc<PathRuleApi>,
+}
+
+impl UndoApi {
+    pub fn new(
+        plan_version_repo: Arc<PlanVersionRepository>,
+        plan_item_repo: Arc<PlanItemRepository>,
+        action_log_repo: Arc<ActionLogRepository>,
+        plan_api: Arc<PlanApi>,
+        material_api: Arc<MaterialApi>,
+        path_rule_api: Arc<PathRuleApi>,
+    ) -> Self {
+        Self {
+            plan_version_repo,
+            plan_item_repo,
+            action_log_repo,
+            plan_api,
+            material_api,
+            path_rule_api,
+        }
+    }
+
+    /// 查询操作人在版本下的撤销/重做状态
+    pub fn get_undo_status(&self, version_id: &str, actor: &str) -> ApiResult<UndoStatusResponse> {
+        self.validate_scope(version_id, actor)?;
+        let stacks = self.load_stacks(version_id, actor)?;
+        Ok(build_status(version_id, actor, &stacks))
+    }
+
+    /// 撤销最近一次可撤销操作
+    pub fn undo_last(
+        &self,
+        version_id: &str,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> ApiResult<UndoRedoResponse> {
+        self.apply_history(version_id, actor, reason, HistoryKind::Undo)
+    }
+
+    /// 重做最近一次撤销
+    pub fn redo_last(
+        &self,
+        version_id: &str,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> ApiResult<UndoRedoResponse> {
+        self.apply_history(version_id, actor, reason, HistoryKind::Redo)
+    }
+
+    // ==========================================
+    // 内部实现
+    // ==========================================
+
+    fn validate_scope(&self, version_id: &str, actor: &str) -> ApiResult<()> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        if actor.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+        self.plan_version_repo
+            .find_by_id(version_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        Ok(())
+    }
+
+    fn load_stacks(&self, version_id: &str, actor: &str) -> ApiResult<UndoStacks> {
+        let logs = self
+            .action_log_repo
+            .find_undoable_by_actor(version_id, actor, UNDO_HISTORY_LIMIT)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        Ok(UndoStacks::replay(logs))
+    }
+
+    fn apply_history(
+        &self,
+        version_id: &str,
+        actor: &str,
+        reason: Option<&str>,
+        kind: HistoryKind,
+    ) -> ApiResult<UndoRedoResponse> {
+        self.validate_scope(version_id, actor)?;
+        let stacks = self.load_stacks(version_id, actor)?;
+        let (target, verb) = match kind {
+            HistoryKind::Undo => (stacks.undo.last(), "撤销"),
+            HistoryKind::Redo => (stacks.redo.last(), "重做"),
+        };
+        let target = target
+            .ok_or_else(|| ApiError::BusinessRuleViolation(format!("没有可{}的操作", verb)))?;
+        let payload =
+            InversePayload::from_payload(target.payload_json.as_ref()).ok_or_else(|| {
+                ApiError::BusinessRuleViolation(format!(
+                    "操作日志{}的逆操作载荷无法解析",
+                    target.action_id
+                ))
+            })?;
+        let source_action_type = source_action_type(target);
+
+        // 1. 后续操作冲突检查
+        let violations = self.find_conflicts(version_id, target, &stacks, &payload)?;
+        if !violations.is_empty() {
+            return Err(ApiError::ManualOperationValidationError {
+                reason: format!(
+                    "{}个材料已被后续操作修改，无法{} {}",
+                    violations.len(),
+                    verb,
+                    source_action_type
+                ),
+                violations,
+            });
+        }
+
+        // 2. 应用逆操作（校验口径同原操作）
+        match &payload {
+            InversePayload::MoveItems { restore, .. } => {
+                self.plan_api.apply_item_positions(version_id, restore)?
+            }
+            InversePayload::MaterialFlags {
+                restore, strict, ..
+            } => self
+                .material_api
+                .apply_material_flag_snapshots(restore, *strict)?,
+            InversePayload::PathOverride { restore, .. } => {
+                self.path_rule_api.apply_path_override_snapshots(restore)?
+            }
+        }
+
+        // 3. 记录 UNDO / REDO 日志（携带反向载荷）
+        let material_ids = payload.material_ids();
+        let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+        let mut payload_json = serde_json::json!({
+            HISTORY_PAYLOAD_KEY: HistoryTag {
+                kind,
+                target_action_id: target.action_id.clone(),
+            },
+            UNDO_PAYLOAD_KEY: payload.reversed().to_json(),
+            "source_action_type": source_action_type,
+            "material_ids": material_ids,
+            "reason": reason,
+        });
+        if matches!(payload, InversePayload::MoveItems { .. }) {
+            // 与 MOVE_ITEMS 一致，供版本合并识别人工移动
+            payload_json["moved_materials"] = serde_json::json!(material_ids);
+        }
+        let detail = match reason {
+            Some(r) => format!(
+                "{} {}（{}个材料） | {}",
+                verb,
+                source_action_type,
+                material_ids.len(),
+                r
+            ),
+            None => format!(
+                "{} {}（{}个材料）",
+                verb,
+                source_action_type,
+                material_ids.len()
+            ),
+        };
+        let log = ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            // 与被撤销日志同口径（材料级操作不关联版本）
+            version_id: target.version_id.clone(),
+            action_type: kind.action_type().to_string(),
+            action_ts: chrono::Local::now().naive_local(),
+            actor: actor.to_string(),
+            payload_json: Some(payload_json),
+            impact_summary_json: None,
+            machine_code: target.machine_code.clone(),
+            date_range_start: target.date_range_start,
+            date_range_end: target.date_range_end,
+            detail: Some(detail.clone()),
+        };
+        self.action_log_repo
+            .insert(&log)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        let status = self.get_undo_status(version_id, actor)?;
+        Ok(UndoRedoResponse {
+            version_id: version_id.to_string(),
+            kind: kind.action_type().to_string(),
+            action_id: log.action_id,
+            target_action_id: target.action_id.clone(),
+            source_action_type,
+            payload_kind: payload.kind().to_string(),
+            material_ids,
+            status,
+            message: detail,
+        })
+    }
+
+    /// 当前状态与逆操作 expected 不一致的材料（逐材料说明冲突来源）
+    fn find_conflicts(
+        &self,
+        version_id: &str,
+        target: &ActionLog,
+        stacks: &UndoStacks,
+        payload: &InversePayload,
+    ) -> ApiResult<Vec<ValidationViolation>> {
+        // (材料ID, 期望状态, 当前状态)
+        let mut mismatches: Vec<(String, serde_json::Value, serde_json::Value)> = Vec::new();
+        match payload {
+            InversePayload::MoveItems { expected, .. } => {
+                let current: HashMap<String, _> = self
+                    .plan_item_repo
+                    .find_by_version(version_id)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                    .iter()
+                    .map(|item| (item.material_id.clone(), item_position(item)))
+                    .collect();
+                for exp in expected {
+                    let cur = current.get(&exp.material_id);
+                    if !cur.is_some_and(|c| exp.matches(c)) {
+                        mismatches.push((exp.material_id.clone(), json(exp), json(&cur)));
+                    }
+                }
+            }
+            InversePayload::MaterialFlags { expected, .. } => {
+                let ids: Vec<String> = expected.iter().map(|s| s.material_id.clone()).collect();
+                let current: HashMap<String, _> = self
+                    .material_api
+                    .material_flag_snapshots(&ids)?
+                    .into_iter()
+                    .map(|s| (s.material_id.clone(), s))
+                    .collect();
+                for exp in expected {
+                    let cur = current.get(&exp.material_id);
+                    if cur != Some(exp) {
+                        mismatches.push((exp.material_id.clone(), json(exp), json(&cur)));
+                    }
+                }
+            }
+            InversePayload::PathOverride { expected, .. } => {
+                let ids: Vec<String> = expected.iter().map(|s| s.material_id.clone()).collect();
+                let current: HashMap<String, _> = self
+                    .path_rule_api
+                    .path_override_snapshots(&ids)?
+                    .into_iter()
+                    .map(|s| (s.material_id.clone(), s))
+                    .collect();
+                for exp in expected {
+                    let cur = current.get(&exp.material_id);
+                    if !cur.is_some_and(|c| c.same_state(exp)) {
+                        mismatches.push((exp.material_id.clone(), json(exp), json(&cur)));
+                    }
+                }
+            }
+        }
+
+        // 撤销历史内的日志不算“后续操作”
+        let history_ids: HashSet<&str> = stacks
+            .undo
+            .iter()
+            .chain(stacks.redo.iter())
+            .map(|l| l.action_id.as_str())
+            .collect();
+
+        let mut violations = Vec::with_capacity(mismatches.len());
+        for (material_id, expected, current) in mismatches {
+            let later = self
+                .action_log_repo
+                .find_by_material_id_after_action(
+                    &material_id,
+                    &target.action_id,
+                    CONFLICT_LOOKUP_LIMIT,
+                )
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                .into_iter()
+                .find(|l| !history_ids.contains(l.action_id.as_str()));
+
+            let reason = match &later {
+                Some(l) => format!(
+                    "材料{}已被后续操作 {} 修改（{}，{}）",
+                    material_id,
+                    l.action_type,
+                    l.actor,
+                    l.action_ts.format("%Y-%m-%d %H:%M:%S")
+                ),
+                None => format!(
+                    "材料{}当前状态与操作后状态不一致（可能被重算或其他系统操作修改）",
+                    material_id
+                ),
+            };
+            violations.push(ValidationViolation {
+                violation_type: "UNDO_CONFLICT".to_string(),
+                material_id,
+                reason,
+                details: Some(serde_json::json!({
+                    "expected": expected,
+                    "current": current,
+                    "conflict_action_id": later.as_ref().map(|l| l.action_id.clone()),
+                    "conflict_action_type": later.as_ref().map(|l| l.action_type.clone()),
+                })),
+            });
+        }
+        Ok(violations)
+    }
+}
+
+// ==========================================
+// 辅助函数
+// ==========================================
+
+fn json<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+}
+
+/// 原始操作类型：UNDO / REDO 日志取其记录的 source_action_type
+fn source_action_type(log: &ActionLog) -> String {
+    log.payload_json
+        .as_ref()
+        .filter(|p| p.get(HISTORY_PAYLOAD_KEY).is_some())
+        .and_then(|p| p.get("source_action_type"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(&log.action_type)
+        .to_string()
+}
+
+fn build_entry(log: &ActionLog) -> UndoEntryDto {
+    let payload = InversePayload::from_payload(log.payload_json.as_ref());
+    UndoEntryDto {
+        action_id: log.action_id.clone(),
+        source_action_type: source_action_type(log),
+        payload_kind: payload
+            .as_ref()
+            .map(|p| p.kind().to_string())
+            .unwrap_or_default(),
+        material_ids: payload.map(|p| p.material_ids()).unwrap_or_default(),
+        action_ts: log.action_ts.format("%Y-%m-%d %H:%M:%S").to_string(),
+        detail: log.detail.clone(),
+    }
+}
+
+fn build_status(version_id: &str, actor: &str, stacks: &UndoStacks) -> UndoStatusResponse {
+    UndoStatusResponse {
+        version_id: version_id.to_string(),
+        actor: actor.to_string(),
+        undo_depth: stacks.undo.len(),
+        redo_depth: stacks.redo.len(),
+        next_undo: stacks.undo.last().map(build_entry),
+        next_redo: stacks.redo.last().map(build_entry),
+    }
+}
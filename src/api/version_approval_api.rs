@@ -0,0 +1,394 @@
+// ==========================================
+// 热轧精整排产系统 - 版本激活审批 API
+// ==========================================
+// 职责:
+// - 计划员提交版本（附激活前检查单）
+// - 生产经理审批通过/驳回（附审批意见）
+// - 查询审批记录
+// 说明:
+// - 只有 APPROVED 状态的版本允许激活（见 PlanApi::activate_version）
+// - 审批人不能与提交人相同
+// - 每个审批动作写入 action_log
+// ==========================================
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::activation_validator::ActivationRedLineValidator;
+use crate::api::error::{ApiError, ApiResult};
+use crate::domain::action_log::ActionLog;
+use crate::domain::plan::PlanVersion;
+use crate::domain::types::PlanVersionStatus;
+use crate::domain::version_review::{
+    ActivationChecklist, ChecklistItem, VersionReview, VersionReviewAction,
+};
+use crate::repository::action_log_repo::ActionLogRepository;
+use crate::repository::capacity_repo::CapacityPoolRepository;
+use crate::repository::plan_repo::{PlanItemRepository, PlanVersionRepository};
+use crate::repository::VersionReviewRepository;
+
+/// 超限判定容差（吨）
+const OVERFLOW_EPSILON_T: f64 = 1e-6;
+
+/// 版本审批记录响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionReviewHistory {
+    pub version_id: String,
+    pub status: PlanVersionStatus,
+    pub reviews: Vec<VersionReview>,
+}
+
+pub struct VersionApprovalApi {
+    plan_version_repo: Arc<PlanVersionRepository>,
+    plan_item_repo: Arc<PlanItemRepository>,
+    capacity_repo: Arc<CapacityPoolRepository>,
+    review_repo: Arc<VersionReviewRepository>,
+    action_log_repo: Arc<ActionLogRepository>,
+    activation_validator: Arc<ActivationRedLineValidator>,
+}
+
+impl VersionApprovalApi {
+    pub fn new(
+        plan_version_repo: Arc<PlanVersionRepository>,
+        plan_item_repo: Arc<PlanItemRepository>,
+        capacity_repo: Arc<CapacityPoolRepository>,
+        review_repo: Arc<VersionReviewRepository>,
+        action_log_repo: Arc<ActionLogRepository>,
+        activation_validator: Arc<ActivationRedLineValidator>,
+    ) -> Self {
+        Self {
+            plan_version_repo,
+            plan_item_repo,
+            capacity_repo,
+            review_repo,
+            action_log_repo,
+            activation_validator,
+        }
+    }
+
+    // ==========================================
+    // 审批流
+    // ==========================================
+
+    /// 提交版本审批（DRAFT/REJECTED → SUBMITTED）
+    ///
+    /// 提交时生成激活前检查单并随记录保存；检查项未通过不阻断提交，由审批人判断。
+    pub fn submit_version(
+        &self,
+        version_id: &str,
+        comment: Option<String>,
+        operator: &str,
+    ) -> ApiResult<VersionReview> {
+        let version = self.load_version(version_id, operator)?;
+        if !matches!(
+            version.status,
+            PlanVersionStatus::Draft | PlanVersionStatus::Rejected
+        ) {
+            return Err(ApiError::InvalidStateTransition {
+                from: version.status.to_string(),
+                to: PlanVersionStatus::Submitted.to_string(),
+            });
+        }
+
+        let checklist = self.build_activation_checklist(version_id)?;
+        let review = new_review(
+            version_id,
+            VersionReviewAction::Submit,
+            operator,
+            comment,
+            Some(checklist),
+        );
+        self.review_repo.record_transition(
+            &review,
+            version.status,
+            PlanVersionStatus::Submitted,
+        )?;
+
+        let checklist = review.checklist.as_ref();
+        self.log_review(
+            &version,
+            &review,
+            "SUBMIT_VERSION",
+            Some(serde_json::json!({
+                "checklist_passed": checklist.map(|c| c.all_passed()),
+                "checklist_failed_count": checklist.map(|c| c.failed_count()),
+            })),
+            format!("提交审批: V{}", version.version_no),
+        )?;
+        Ok(review)
+    }
+
+    /// 审批通过（SUBMITTED → APPROVED）
+    pub fn approve_version(
+        &self,
+        version_id: &str,
+        comment: Option<String>,
+        reviewer: &str,
+    ) -> ApiResult<VersionReview> {
+        let version = self.load_submitted_version(version_id, reviewer)?;
+        let review = new_review(
+            version_id,
+            VersionReviewAction::Approve,
+            reviewer,
+            comment,
+            None,
+        );
+        self.review_repo.record_transition(
+            &review,
+            PlanVersionStatus::Submitted,
+            PlanVersionStatus::Approved,
+        )?;
+
+        self.log_review(
+            &version,
+            &review,
+            "APPROVE_VERSION",
+            None,
+            format!("审批通过: V{}", version.version_no),
+        )?;
+        Ok(review)
+    }
+
+    /// 审批驳回（SUBMITTED → REJECTED，必须填写驳回意见）
+    pub fn reject_version(
+        &self,
+        version_id: &str,
+        comment: &str,
+        reviewer: &str,
+    ) -> ApiResult<VersionReview> {
+        if comment.trim().is_empty() {
+            return Err(ApiError::InvalidInput("驳回意见不能为空".to_string()));
+        }
+        let version = self.load_submitted_version(version_id, reviewer)?;
+        let review = new_review(
+            version_id,
+            VersionReviewAction::Reject,
+            reviewer,
+            Some(comment.trim().to_string()),
+            None,
+        );
+        self.review_repo.record_transition(
+            &review,
+            PlanVersionStatus::Submitted,
+            PlanVersionStatus::Rejected,
+        )?;
+
+        self.log_review(
+            &version,
+            &review,
+            "REJECT_VERSION",
+            None,
+            format!("审批驳回: V{} | {}", version.version_no, comment.trim()),
+        )?;
+        Ok(review)
+    }
+
+    /// 查询版本审批记录
+    pub fn get_version_reviews(&self, version_id: &str) -> ApiResult<VersionReviewHistory> {
+        let version = self
+            .plan_version_repo
+            .find_by_id(version_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))?;
+        let reviews = self.review_repo.list_by_version(version_id)?;
+        Ok(VersionReviewHistory {
+            version_id: version.version_id,
+            status: version.status,
+            reviews,
+        })
+    }
+
+    // ==========================================
+    // 激活前检查单
+    // ==========================================
+
+    /// 生成激活前检查单（可在提交前预览）
+    pub fn build_activation_checklist(&self, version_id: &str) -> ApiResult<ActivationChecklist> {
+        let items = self.plan_item_repo.find_by_version(version_id)?;
+        let pools = self.capacity_repo.find_by_version_id(version_id)?;
+
+        let mut checklist = Vec::new();
+
+        checklist.push(ChecklistItem {
+            code: "HAS_ITEMS".to_string(),
+            label: "版本包含排产明细".to_string(),
+            passed: !items.is_empty(),
+            detail: format!("排产明细 {} 条", items.len()),
+        });
+
+        let overflow: Vec<String> = pools
+            .iter()
+            .filter(|p| p.used_capacity_t > p.limit_capacity_t + OVERFLOW_EPSILON_T)
+            .map(|p| format!("{}@{}", p.machine_code, p.plan_date))
+            .collect();
+        checklist.push(ChecklistItem {
+            code: "CAPACITY_WITHIN_LIMIT".to_string(),
+            label: "产能池未超上限".to_string(),
+            passed: overflow.is_empty(),
+            detail: if overflow.is_empty() {
+                "全部机组日未超上限".to_string()
+            } else {
+                format!(
+                    "超上限 {} 个机组日: {}",
+                    overflow.len(),
+                    overflow.join(", ")
+                )
+            },
+        });
+
+        let unreleased = items
+            .iter()
+            .filter(|i| has_violation_flags(i.violation_flags.as_deref()))
+            .filter(|i| !i.force_release_in_plan)
+            .count();
+        checklist.push(ChecklistItem {
+            code: "VIOLATIONS_RELEASED".to_string(),
+            label: "违规明细均已强制放行".to_string(),
+            passed: unreleased == 0,
+            detail: format!("未放行的违规明细 {} 条", unreleased),
+        });
+
+        let red_line = self.activation_validator.validate_version(version_id)?;
+        checklist.push(ChecklistItem {
+            code: "RED_LINES_CLEAR".to_string(),
+            label: "无红线硬违规".to_string(),
+            passed: !red_line.has_hard_violations(),
+            detail: format!(
+                "硬违规 {} 项，警告 {} 项",
+                red_line.hard_count, red_line.warning_count
+            ),
+        });
+
+        let active = self
+            .plan_version_repo
+            .find_by_id(version_id)?
+            .map(|v| self.plan_version_repo.find_active_version(&v.plan_id))
+            .transpose()?
+            .flatten();
+        checklist.push(ChecklistItem {
+            code: "SUPERSEDES_ACTIVE".to_string(),
+            label: "激活后替换当前激活版本".to_string(),
+            passed: true,
+            detail: match active {
+                Some(v) => format!("当前激活版本 V{} 将被归档", v.version_no),
+                None => "方案当前无激活版本".to_string(),
+            },
+        });
+
+        Ok(ActivationChecklist {
+            items: checklist,
+            generated_at: chrono::Local::now().naive_local(),
+        })
+    }
+
+    // ==========================================
+    // 内部辅助
+    // ==========================================
+
+    fn load_version(&self, version_id: &str, operator: &str) -> ApiResult<PlanVersion> {
+        if version_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("版本ID不能为空".to_string()));
+        }
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+        self.plan_version_repo
+            .find_by_id(version_id)?
+            .ok_or_else(|| ApiError::NotFound(format!("版本{}不存在", version_id)))
+    }
+
+    /// 加载待审批版本，并校验审批人不是提交人
+    fn load_submitted_version(&self, version_id: &str, reviewer: &str) -> ApiResult<PlanVersion> {
+        let version = self.load_version(version_id, reviewer)?;
+        if version.status != PlanVersionStatus::Submitted {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "版本当前状态为{}，只能审批已提交的版本",
+                version.status
+            )));
+        }
+        let submitter = self
+            .review_repo
+            .find_latest(version_id, VersionReviewAction::Submit)?
+            .map(|r| r.actor);
+        if submitter.as_deref() == Some(reviewer) {
+            return Err(ApiError::BusinessRuleViolation(
+                "审批人不能与提交人相同".to_string(),
+            ));
+        }
+        Ok(version)
+    }
+
+    fn log_review(
+        &self,
+        version: &PlanVersion,
+        review: &VersionReview,
+        action_type: &str,
+        impact_summary_json: Option<serde_json::Value>,
+        detail: String,
+    ) -> ApiResult<()> {
+        let log = ActionLog {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            version_id: Some(version.version_id.clone()),
+            action_type: action_type.to_string(),
+            action_ts: review.created_at,
+            actor: review.actor.clone(),
+            payload_json: Some(serde_json::json!({
+                "review_id": review.review_id,
+                "plan_id": version.plan_id,
+                "version_no": version.version_no,
+                "from_status": version.status.to_db_str(),
+                "comment": review.comment,
+            })),
+            impact_summary_json,
+            machine_code: None,
+            date_range_start: None,
+            date_range_end: None,
+            detail: Some(detail),
+        };
+        self.action_log_repo
+            .insert(&log)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn new_review(
+    version_id: &str,
+    action: VersionReviewAction,
+    actor: &str,
+    comment: Option<String>,
+    checklist: Option<ActivationChecklist>,
+) -> VersionReview {
+    VersionReview {
+        review_id: uuid::Uuid::new_v4().to_string(),
+        version_id: version_id.to_string(),
+        action,
+        actor: actor.to_string(),
+        comment: comment
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty()),
+        checklist,
+        created_at: chrono::Local::now().naive_local(),
+    }
+}
+
+/// violation_flags 为 JSON 字符串，空值/空数组/空对象视为无违规
+fn has_violation_flags(flags: Option<&str>) -> bool {
+    match flags.map(str::trim) {
+        None | Some("") | Some("[]") | Some("{}") | Some("null") => false,
+        Some(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_violation_flags_ignores_empty_json() {
+        assert!(!has_violation_flags(None));
+        assert!(!has_violation_flags(Some(" [] ")));
+        assert!(!has_violation_flags(Some("{}")));
+        assert!(has_violation_flags(Some("[\"PATH_WIDTH\"]")));
+    }
+}
@@ -0,0 +1,403 @@
+// ==========================================
+// 热轧精整排产系统 - 版本谱系与保留策略 API
+// ==========================================
+// 职责:
+// - 按方案构建版本谱系树（父版本 / 触发方式 / 与父版本差异）
+// - 按保留策略清理历史版本（支持 dry-run 预览）
+// 说明:
+// - 谱系信息来自 config_snapshot_json 的 __meta_* 键（见 domain::version_lineage）
+// - 清理在单事务内完成，并写入 action_log (PURGE_VERSIONS)
+// ==========================================
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::domain::action_log::ActionLog;
+use crate::domain::plan::PlanVersion;
+use crate::domain::types::PlanVersionStatus;
+use crate::domain::version_lineage::{
+    LineageDiffCounts, VersionLineageMeta, VersionLineageNode, VersionLineageTree,
+    VersionPurgeCandidate, VersionPurgeReport, VersionRetentionPolicy,
+};
+use crate::repository::plan_repo::{PlanItemRepository, PlanRepository, PlanVersionRepository};
+use crate::repository::VersionRetentionRepository;
+
+pub struct VersionLineageApi {
+    plan_repo: Arc<PlanRepository>,
+    plan_version_repo: Arc<PlanVersionRepository>,
+    plan_item_repo: Arc<PlanItemRepository>,
+    retention_repo: Arc<VersionRetentionRepository>,
+}
+
+impl VersionLineageApi {
+    pub fn new(
+        plan_repo: Arc<PlanRepository>,
+        plan_version_repo: Arc<PlanVersionRepository>,
+        plan_item_repo: Arc<PlanItemRepository>,
+        retention_repo: Arc<VersionRetentionRepository>,
+    ) -> Self {
+        Self {
+            plan_repo,
+            plan_version_repo,
+            plan_item_repo,
+            retention_repo,
+        }
+    }
+
+    // ==========================================
+    // 谱系查询
+    // ==========================================
+
+    /// 查询方案的版本谱系树
+    ///
+    /// 父版本缺失（已清理/跨方案）或无谱系信息的版本作为根节点。
+    pub fn get_version_lineage(&self, plan_id: &str) -> ApiResult<VersionLineageTree> {
+        if plan_id.trim().is_empty() {
+            return Err(ApiError::InvalidInput("方案ID不能为空".to_string()));
+        }
+        self.plan_repo
+            .find_by_id(plan_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("方案{}不存在", plan_id)))?;
+
+        let mut versions = self
+            .plan_version_repo
+            .find_by_plan_id(plan_id)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        versions.sort_by_key(|v| v.version_no);
+
+        let known_ids: HashSet<&str> = versions.iter().map(|v| v.version_id.as_str()).collect();
+        let mut children_of: HashMap<String, Vec<VersionLineageNode>> = HashMap::new();
+        let mut flat: Vec<VersionLineageNode> = Vec::with_capacity(versions.len());
+
+        for v in &versions {
+            let meta = VersionLineageMeta::from_snapshot(v.config_snapshot_json.as_deref());
+            let diff_from_parent = match meta.parent_version_id.as_deref() {
+                Some(parent) if known_ids.contains(parent) => {
+                    let counts = self
+                        .plan_item_repo
+                        .get_versions_diff_counts(parent, &v.version_id)
+                        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+                    Some(LineageDiffCounts {
+                        moved_count: counts.moved_count,
+                        added_count: counts.added_count,
+                        removed_count: counts.removed_count,
+                    })
+                }
+                _ => None,
+            };
+
+            flat.push(VersionLineageNode {
+                version_id: v.version_id.clone(),
+                version_no: v.version_no,
+                status: v.status,
+                parent_version_id: meta.parent_version_id,
+                trigger: meta.trigger,
+                strategy: meta.strategy,
+                version_name: meta.version_name,
+                created_by: v.created_by.clone(),
+                created_at: v.created_at,
+                diff_from_parent,
+                children: Vec::new(),
+            });
+        }
+
+        // 版本号倒序挂载：子版本总是晚于父版本创建，先挂好子树再挂到父节点
+        let mut roots = Vec::new();
+        for mut node in flat.into_iter().rev() {
+            node.children = children_of.remove(&node.version_id).unwrap_or_default();
+            node.children.sort_by_key(|c| c.version_no);
+            match node.parent_version_id.clone() {
+                Some(parent)
+                    if known_ids.contains(parent.as_str()) && parent != node.version_id =>
+                {
+                    children_of.entry(parent).or_default().push(node);
+                }
+                _ => roots.push(node),
+            }
+        }
+        // 异常数据（父版本号更大）无法挂载时降级为根节点
+        roots.extend(children_of.into_values().flatten());
+        roots.sort_by_key(|r| r.version_no);
+
+        Ok(VersionLineageTree {
+            plan_id: plan_id.to_string(),
+            version_count: versions.len(),
+            roots,
+        })
+    }
+
+    // ==========================================
+    // 保留策略清理
+    // ==========================================
+
+    /// 按保留策略清理历史版本
+    ///
+    /// # 参数
+    /// - plan_id: 指定方案；None 表示全部方案
+    /// - dry_run: true 时仅返回待清理列表，不删除
+    pub fn purge_versions(
+        &self,
+        plan_id: Option<&str>,
+        policy: VersionRetentionPolicy,
+        dry_run: bool,
+        operator: &str,
+    ) -> ApiResult<VersionPurgeReport> {
+        if operator.trim().is_empty() {
+            return Err(ApiError::InvalidInput("操作人不能为空".to_string()));
+        }
+        if matches!(policy.max_age_days, Some(d) if d < 0) {
+            return Err(ApiError::InvalidInput("max_age_days不能为负数".to_string()));
+        }
+
+        let plan_ids: Vec<String> = match plan_id.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(id) => {
+                self.plan_repo
+                    .find_by_id(id)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                    .ok_or_else(|| ApiError::NotFound(format!("方案{}不存在", id)))?;
+                vec![id.to_string()]
+            }
+            None => self
+                .plan_repo
+                .list_all()
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+                .into_iter()
+                .map(|p| p.plan_id)
+                .collect(),
+        };
+
+        let ever_active: HashSet<String> = self
+            .retention_repo
+            .list_ever_active_version_ids()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .collect();
+
+        let now = chrono::Local::now().naive_local();
+        let mut scanned_count = 0usize;
+        let mut candidates = Vec::new();
+        for pid in &plan_ids {
+            let versions = self
+                .plan_version_repo
+                .find_by_plan_id(pid)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            scanned_count += versions.len();
+            candidates.extend(select_purge_candidates(
+                &versions,
+                &ever_active,
+                &policy,
+                now,
+            ));
+        }
+
+        let (deleted_rows, detached_action_logs) = if dry_run || candidates.is_empty() {
+            (BTreeMap::new(), 0)
+        } else {
+            let ids: Vec<String> = candidates.iter().map(|c| c.version_id.clone()).collect();
+            self.retention_repo
+                .purge_versions(&ids, |deleted_rows, detached_action_logs| ActionLog {
+                    action_id: uuid::Uuid::new_v4().to_string(),
+                    // 被清理版本已删除，不能再引用 plan_version
+                    version_id: None,
+                    action_type: "PURGE_VERSIONS".to_string(),
+                    action_ts: now,
+                    actor: operator.to_string(),
+                    payload_json: Some(serde_json::json!({
+                        "plan_id": plan_id,
+                        "policy": policy,
+                        "purged_versions": candidates
+                            .iter()
+                            .map(|c| serde_json::json!({
+                                "version_id": c.version_id,
+                                "plan_id": c.plan_id,
+                                "version_no": c.version_no,
+                                "reason": c.reason,
+                            }))
+                            .collect::<Vec<_>>(),
+                        "deleted_rows": deleted_rows,
+                        "detached_action_logs": detached_action_logs,
+                    })),
+                    impact_summary_json: None,
+                    machine_code: None,
+                    date_range_start: None,
+                    date_range_end: None,
+                    detail: Some(format!("按保留策略清理版本: {}个", candidates.len())),
+                })
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+        };
+
+        Ok(VersionPurgeReport {
+            dry_run,
+            policy,
+            scanned_count,
+            candidates,
+            deleted_rows,
+            detached_action_logs,
+            executed_at: now,
+        })
+    }
+}
+
+/// 按保留策略挑选同一方案下的待清理版本（纯函数）
+fn select_purge_candidates(
+    versions: &[PlanVersion],
+    ever_active: &HashSet<String>,
+    policy: &VersionRetentionPolicy,
+    now: NaiveDateTime,
+) -> Vec<VersionPurgeCandidate> {
+    let cutoff = policy.max_age_days.map(|d| now - chrono::Duration::days(d));
+    let old_enough = |v: &PlanVersion| cutoff.is_none_or(|c| v.created_at < c);
+
+    // 归档版本按版本号倒序，前 N 个保留
+    let mut archived: Vec<&PlanVersion> = versions
+        .iter()
+        .filter(|v| v.status == PlanVersionStatus::Archived)
+        .collect();
+    archived.sort_by(|a, b| b.version_no.cmp(&a.version_no));
+    let kept_archived: HashSet<&str> = archived
+        .iter()
+        .take(policy.keep_archived_per_plan)
+        .map(|v| v.version_id.as_str())
+        .collect();
+
+    let mut candidates: Vec<VersionPurgeCandidate> = versions
+        .iter()
+        .filter_map(|v| {
+            // 以激活/回滚记录为准；无记录的归档版本只受归档保留数量约束
+            let was_active = ever_active.contains(&v.version_id);
+            let reason = match v.status {
+                PlanVersionStatus::Active
+                | PlanVersionStatus::Submitted
+                | PlanVersionStatus::Approved => return None,
+                _ if policy.keep_ever_active && was_active => return None,
+                PlanVersionStatus::Archived => {
+                    if kept_archived.contains(v.version_id.as_str()) || !old_enough(v) {
+                        return None;
+                    }
+                    format!("超出归档保留数量({})", policy.keep_archived_per_plan)
+                }
+                PlanVersionStatus::Draft | PlanVersionStatus::Rejected => {
+                    let days = policy.max_age_days?;
+                    if !old_enough(v) {
+                        return None;
+                    }
+                    format!("{}版本超过{}天未使用", v.status, days)
+                }
+            };
+            Some(VersionPurgeCandidate {
+                version_id: v.version_id.clone(),
+                plan_id: v.plan_id.clone(),
+                version_no: v.version_no,
+                status: v.status,
+                created_at: v.created_at,
+                reason,
+            })
+        })
+        .collect();
+    candidates.sort_by_key(|c| c.version_no);
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(
+        no: i32,
+        status: PlanVersionStatus,
+        age_days: i64,
+        now: NaiveDateTime,
+    ) -> PlanVersion {
+        PlanVersion {
+            version_id: format!("V{}", no),
+            plan_id: "P1".to_string(),
+            version_no: no,
+            status,
+            frozen_from_date: None,
+            recalc_window_days: Some(30),
+            config_snapshot_json: None,
+            created_by: Some("admin".to_string()),
+            created_at: now - chrono::Duration::days(age_days),
+            revision: 0,
+        }
+    }
+
+    #[test]
+    fn test_select_purge_candidates_respects_policy() {
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        let versions = vec![
+            version(1, PlanVersionStatus::Archived, 200, now),
+            version(2, PlanVersionStatus::Archived, 150, now),
+            version(3, PlanVersionStatus::Draft, 120, now), // 曾激活后被回滚覆盖
+            version(4, PlanVersionStatus::Draft, 100, now),
+            version(5, PlanVersionStatus::Rejected, 10, now),
+            version(6, PlanVersionStatus::Approved, 100, now),
+            version(7, PlanVersionStatus::Active, 1, now),
+        ];
+        let ever_active: HashSet<String> = ["V3".to_string()].into_iter().collect();
+
+        // 默认策略：曾激活版本全部保留，仅清理超龄草稿
+        let ids =
+            |c: Vec<VersionPurgeCandidate>| c.into_iter().map(|c| c.version_id).collect::<Vec<_>>();
+        let policy = VersionRetentionPolicy::default();
+        assert_eq!(
+            ids(select_purge_candidates(
+                &versions,
+                &ever_active,
+                &policy,
+                now
+            )),
+            vec!["V4"]
+        );
+
+        // 保护曾激活版本时，归档数量只裁剪无激活记录的归档版本
+        let policy = VersionRetentionPolicy {
+            keep_archived_per_plan: 0,
+            ..VersionRetentionPolicy::default()
+        };
+        let with_v1: HashSet<String> = ["V1".to_string(), "V3".to_string()].into_iter().collect();
+        assert_eq!(
+            ids(select_purge_candidates(&versions, &with_v1, &policy, now)),
+            vec!["V2", "V4"]
+        );
+
+        // 不保护曾激活版本，归档仅保留最近1个
+        let policy = VersionRetentionPolicy {
+            keep_archived_per_plan: 1,
+            keep_ever_active: false,
+            max_age_days: Some(90),
+        };
+        assert_eq!(
+            ids(select_purge_candidates(
+                &versions,
+                &ever_active,
+                &policy,
+                now
+            )),
+            vec!["V1", "V3", "V4"]
+        );
+
+        // 未设置超龄天数：草稿/驳回不清理
+        let policy = VersionRetentionPolicy {
+            keep_archived_per_plan: 0,
+            keep_ever_active: false,
+            max_age_days: None,
+        };
+        assert_eq!(
+            ids(select_purge_candidates(
+                &versions,
+                &ever_active,
+                &policy,
+                now
+            )),
+            vec!["V1", "V2"]
+        );
+    }
+}
@@ -0,0 +1,643 @@
+// ==========================================
+// 热轧精整排产系统 - 监控目录自动导入
+// ==========================================
+// 职责: 轮询 MES 快照投放目录，文件稳定后自动导入
+// 流程: 扫描 → 稳定性等待 → 导入 → 归档(archive/failed) → 可选重算草稿
+// 红线: 无人值守不得改动激活版本，重算结果为派生草稿版本，需审批后人工激活
+// 配置: config_kv (global) auto_import_* 键，每轮扫描重新读取
+// ==========================================
+
+use chrono::{Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::api::{ImportApi, PlanApi};
+use crate::config::config_manager::{config_keys, ConfigManager};
+use crate::engine::{ScheduleEvent, ScheduleEventPublisher, ScheduleEventType, ScheduleStrategy};
+use crate::importer::ParseOptions;
+
+/// 自动导入操作人（写入 import_batch.imported_by / action_log.actor）
+pub const AUTO_IMPORT_OPERATOR: &str = "auto-import";
+
+/// 导入成功文件归档子目录
+pub const ARCHIVE_DIR_NAME: &str = "archive";
+
+/// 导入失败文件归档子目录
+pub const FAILED_DIR_NAME: &str = "failed";
+
+/// 状态中保留的最近处理结果条数
+const MAX_RECENT_RESULTS: usize = 50;
+
+/// 默认文件名匹配模式（分号分隔，支持 * 与 ?）
+const DEFAULT_FILE_PATTERN: &str = "*.csv;*.xlsx;*.xls";
+
+// ==========================================
+// AutoImportConfig - 自动导入配置
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoImportConfig {
+    pub enabled: bool,                      // 是否启用
+    pub watch_dir: Option<String>,          // 监控目录
+    pub file_pattern: String,               // 文件名匹配模式
+    pub mapping_profile_id: Option<String>, // 映射配置ID
+    pub stability_secs: u64,                // 稳定性等待（文件最后修改后静置秒数）
+    pub poll_interval_secs: u64,            // 轮询间隔（秒）
+    pub trigger_recalc: bool,               // 导入后是否生成重算草稿
+    pub recalc_days: i64,                   // 重算窗口天数（从今天起）
+}
+
+impl Default for AutoImportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            watch_dir: None,
+            file_pattern: DEFAULT_FILE_PATTERN.to_string(),
+            mapping_profile_id: None,
+            stability_secs: 30,
+            poll_interval_secs: 60,
+            trigger_recalc: false,
+            recalc_days: 7,
+        }
+    }
+}
+
+impl AutoImportConfig {
+    /// 从 config_kv 读取配置（缺失或非法值回退默认值）
+    pub fn load(config: &ConfigManager) -> Result<Self, String> {
+        let read = |key: &str| -> Result<Option<String>, String> {
+            config
+                .get_global_config_value(key)
+                .map(|v| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()))
+                .map_err(|e| format!("读取配置 {} 失败: {}", key, e))
+        };
+        let defaults = Self::default();
+
+        Ok(Self {
+            enabled: read(config_keys::AUTO_IMPORT_ENABLED)?
+                .map(|v| parse_flag(&v))
+                .unwrap_or(defaults.enabled),
+            watch_dir: read(config_keys::AUTO_IMPORT_DIR)?,
+            file_pattern: read(config_keys::AUTO_IMPORT_FILE_PATTERN)?
+                .unwrap_or(defaults.file_pattern),
+            mapping_profile_id: read(config_keys::AUTO_IMPORT_MAPPING_PROFILE_ID)?,
+            stability_secs: read(config_keys::AUTO_IMPORT_STABILITY_SECS)?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.stability_secs),
+            poll_interval_secs: read(config_keys::AUTO_IMPORT_POLL_SECS)?
+                .and_then(|v| v.parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.poll_interval_secs),
+            trigger_recalc: read(config_keys::AUTO_IMPORT_TRIGGER_RECALC)?
+                .map(|v| parse_flag(&v))
+                .unwrap_or(defaults.trigger_recalc),
+            recalc_days: read(config_keys::AUTO_IMPORT_RECALC_DAYS)?
+                .and_then(|v| v.parse().ok())
+                .filter(|v| (1..=60).contains(v))
+                .unwrap_or(defaults.recalc_days),
+        })
+    }
+
+    /// 文件名是否匹配（大小写不敏感，分号分隔多个模式）
+    pub fn matches_file_name(&self, file_name: &str) -> bool {
+        let name = file_name.to_lowercase();
+        self.file_pattern
+            .split(';')
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .any(|p| wildcard_match(&p, &name))
+    }
+}
+
+fn parse_flag(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "on"
+    )
+}
+
+/// 通配符匹配（* 任意长度，? 单个字符）
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+// ==========================================
+// AutoImportFileResult - 单文件处理结果
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AutoImportOutcome {
+    Imported, // 导入成功，已移入 archive
+    Failed,   // 导入失败，已移入 failed
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoImportFileResult {
+    pub file_name: String,
+    pub outcome: AutoImportOutcome,
+    pub source_batch_id: String,
+    pub import_batch_id: Option<String>, // 实际落库批次ID（失败时可能为空）
+    pub imported: i64,
+    pub conflicts: i64,
+    pub moved_to: Option<String>, // 归档后路径（移动失败时为空）
+    pub error: Option<String>,
+    pub recalc_version_id: Option<String>, // 重算生成的草稿版本
+    pub recalc_error: Option<String>,      // 重算失败不影响导入结果
+    pub processed_at: String,
+}
+
+// ==========================================
+// AutoImportStatus - 服务状态
+// ==========================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoImportStatus {
+    pub running: bool, // 后台轮询线程是否在运行
+    pub config: AutoImportConfig,
+    pub last_scan_at: Option<String>,
+    pub last_scan_error: Option<String>,
+    pub recent_results: Vec<AutoImportFileResult>, // 最近处理结果（新→旧）
+}
+
+/// 单文件处理完成通知（用于推送前端事件）
+pub type AutoImportNotifier = Arc<dyn Fn(&AutoImportFileResult) + Send + Sync>;
+
+#[derive(Default)]
+struct ScanHistory {
+    last_scan_at: Option<String>,
+    last_scan_error: Option<String>,
+    recent_results: VecDeque<AutoImportFileResult>,
+}
+
+// ==========================================
+// AutoImportService - 监控目录自动导入服务
+// ==========================================
+pub struct AutoImportService {
+    import_api: Arc<ImportApi>,
+    plan_api: Arc<PlanApi>,
+    config_manager: Arc<ConfigManager>,
+    event_publisher: Option<Arc<dyn ScheduleEventPublisher>>,
+    notifier: Mutex<Option<AutoImportNotifier>>,
+    running: Arc<AtomicBool>,
+    stop_requested: Arc<AtomicBool>,
+    // 串行化扫描（后台轮询与手动触发互斥，避免同一文件被导入两次）
+    scan_lock: tokio::sync::Mutex<()>,
+    history: Mutex<ScanHistory>,
+}
+
+impl AutoImportService {
+    pub fn new(
+        import_api: Arc<ImportApi>,
+        plan_api: Arc<PlanApi>,
+        config_manager: Arc<ConfigManager>,
+        event_publisher: Option<Arc<dyn ScheduleEventPublisher>>,
+    ) -> Self {
+        Self {
+            import_api,
+            plan_api,
+            config_manager,
+            event_publisher,
+            notifier: Mutex::new(None),
+            running: Arc::new(AtomicBool::new(false)),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            scan_lock: tokio::sync::Mutex::new(()),
+            history: Mutex::new(ScanHistory::default()),
+        }
+    }
+
+    /// 读取当前配置（配置读取失败时回退默认值 = 未启用）
+    pub fn current_config(&self) -> AutoImportConfig {
+        AutoImportConfig::load(&self.config_manager).unwrap_or_else(|e| {
+            tracing::warn!("自动导入配置读取失败，按未启用处理: {}", e);
+            AutoImportConfig::default()
+        })
+    }
+
+    /// 启动后台轮询线程（重复调用无副作用）
+    ///
+    /// # 说明
+    /// - 每轮重新读取配置，启停/目录变更无需重启应用
+    /// - 未启用时线程空转等待，不扫描目录
+    pub fn start(self: &Arc<Self>, notifier: Option<AutoImportNotifier>) {
+        if let Ok(mut slot) = self.notifier.lock() {
+            *slot = notifier;
+        }
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.stop_requested.store(false, Ordering::SeqCst);
+
+        let service = Arc::clone(self);
+        let spawn_result = std::thread::Builder::new()
+            .name("auto-import".to_string())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(rt) => rt,
+                    Err(e) => {
+                        tracing::error!("自动导入运行时创建失败: {}", e);
+                        service.running.store(false, Ordering::SeqCst);
+                        return;
+                    }
+                };
+                tracing::info!("自动导入监控线程已启动");
+
+                while !service.stop_requested.load(Ordering::SeqCst) {
+                    let config = service.current_config();
+                    if config.enabled && config.watch_dir.is_some() {
+                        if let Err(e) = runtime.block_on(service.scan_with_config(&config)) {
+                            tracing::warn!("自动导入扫描失败: {}", e);
+                        }
+                    }
+                    service.sleep_interruptibly(Duration::from_secs(config.poll_interval_secs));
+                }
+
+                service.running.store(false, Ordering::SeqCst);
+                tracing::info!("自动导入监控线程已停止");
+            });
+
+        if let Err(e) = spawn_result {
+            tracing::error!("自动导入监控线程启动失败: {}", e);
+            self.running.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// 请求停止后台轮询（在当前扫描结束后生效）
+    pub fn stop(&self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+    }
+
+    fn sleep_interruptibly(&self, total: Duration) {
+        let step = Duration::from_millis(500);
+        let mut waited = Duration::ZERO;
+        while waited < total && !self.stop_requested.load(Ordering::SeqCst) {
+            std::thread::sleep(step);
+            waited += step;
+        }
+    }
+
+    /// 查询服务状态
+    pub fn status(&self) -> AutoImportStatus {
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        AutoImportStatus {
+            running: self.running.load(Ordering::SeqCst),
+            config: self.current_config(),
+            last_scan_at: history.last_scan_at.clone(),
+            last_scan_error: history.last_scan_error.clone(),
+            recent_results: history.recent_results.iter().cloned().collect(),
+        }
+    }
+
+    /// 立即扫描一次（不要求 enabled，用于手动触发/验证配置）
+    pub async fn scan_once(&self) -> Result<Vec<AutoImportFileResult>, String> {
+        let config = self.current_config();
+        self.scan_with_config(&config).await
+    }
+
+    async fn scan_with_config(
+        &self,
+        config: &AutoImportConfig,
+    ) -> Result<Vec<AutoImportFileResult>, String> {
+        let _guard = self.scan_lock.lock().await;
+
+        let outcome = self.scan_inner(config).await;
+
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        history.last_scan_at = Some(Utc::now().to_rfc3339());
+        history.last_scan_error = outcome.as_ref().err().cloned();
+        if let Ok(results) = &outcome {
+            for r in results {
+                history.recent_results.push_front(r.clone());
+            }
+            history.recent_results.truncate(MAX_RECENT_RESULTS);
+        }
+        outcome
+    }
+
+    async fn scan_inner(
+        &self,
+        config: &AutoImportConfig,
+    ) -> Result<Vec<AutoImportFileResult>, String> {
+        let watch_dir = config
+            .watch_dir
+            .as_deref()
+            .map(PathBuf::from)
+            .ok_or_else(|| "未配置自动导入目录 (auto_import_dir)".to_string())?;
+        if !watch_dir.is_dir() {
+            return Err(format!("自动导入目录不存在: {}", watch_dir.display()));
+        }
+
+        let candidates = list_stable_files(&watch_dir, config, SystemTime::now())?;
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+        tracing::info!(count = candidates.len(), dir = %watch_dir.display(), "发现待导入文件");
+
+        let mut results = Vec::with_capacity(candidates.len());
+        for path in candidates {
+            let result = self.process_file(&watch_dir, &path, config).await;
+            if let Ok(slot) = self.notifier.lock() {
+                if let Some(notify) = slot.as_ref() {
+                    notify(&result);
+                }
+            }
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    async fn process_file(
+        &self,
+        watch_dir: &Path,
+        path: &Path,
+        config: &AutoImportConfig,
+    ) -> AutoImportFileResult {
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let source_batch_id = format!(
+            "AUTO_{}_{}",
+            Local::now().format("%Y%m%d%H%M%S"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        tracing::info!(file = %file_name, source_batch_id = %source_batch_id, "自动导入开始");
+
+        let import = self
+            .import_api
+            .import_materials_as(
+                &path.to_string_lossy(),
+                &source_batch_id,
+                config.mapping_profile_id.as_deref(),
+                ParseOptions::default(),
+                None,
+                AUTO_IMPORT_OPERATOR,
+            )
+            .await;
+
+        let mut result = AutoImportFileResult {
+            file_name: file_name.clone(),
+            outcome: AutoImportOutcome::Imported,
+            source_batch_id,
+            import_batch_id: None,
+            imported: 0,
+            conflicts: 0,
+            moved_to: None,
+            error: None,
+            recalc_version_id: None,
+            recalc_error: None,
+            processed_at: Utc::now().to_rfc3339(),
+        };
+
+        let target_dir = match import {
+            Ok(response) => {
+                result.import_batch_id = Some(response.import_batch_id);
+                result.imported = response.imported;
+                result.conflicts = response.conflicts;
+                if response.imported > 0 {
+                    self.after_import(config, &mut result);
+                }
+                watch_dir.join(ARCHIVE_DIR_NAME)
+            }
+            Err(e) => {
+                result.outcome = AutoImportOutcome::Failed;
+                result.error = Some(e.to_string());
+                watch_dir.join(FAILED_DIR_NAME)
+            }
+        };
+
+        match move_into(path, &target_dir) {
+            Ok(moved) => {
+                if let Some(error) = &result.error {
+                    // 失败原因写入同名 .error.txt，便于现场排查
+                    let note = moved.with_file_name(format!(
+                        "{}.error.txt",
+                        moved.file_name().and_then(|n| n.to_str()).unwrap_or("file")
+                    ));
+                    if let Err(e) = std::fs::write(&note, error) {
+                        tracing::warn!("写入失败说明文件失败: {}", e);
+                    }
+                }
+                result.moved_to = Some(moved.to_string_lossy().to_string());
+            }
+            Err(e) => {
+                // 移动失败时文件仍留在监控目录，下一轮会再次导入（重复材料进入冲突队列），需人工处理
+                tracing::error!(file = %file_name, error = %e, "自动导入文件归档失败");
+                let msg = format!("文件归档失败: {}", e);
+                result.error = Some(match result.error.take() {
+                    Some(prev) => format!("{}; {}", prev, msg),
+                    None => msg,
+                });
+            }
+        }
+
+        tracing::info!(
+            file = %file_name,
+            outcome = ?result.outcome,
+            imported = result.imported,
+            conflicts = result.conflicts,
+            "自动导入完成"
+        );
+        result
+    }
+
+    /// 导入成功后：发布材料状态变更事件，按配置基于激活版本生成重算草稿
+    fn after_import(&self, config: &AutoImportConfig, result: &mut AutoImportFileResult) {
+        let version_id = match self.plan_api.get_latest_active_version_id() {
+            Ok(Some(v)) => v,
+            Ok(None) => return,
+            Err(e) => {
+                result.recalc_error = Some(format!("查询激活版本失败: {}", e));
+                return;
+            }
+        };
+
+        if let Some(publisher) = &self.event_publisher {
+            let event = ScheduleEvent::full_scope(
+                version_id.clone(),
+                ScheduleEventType::MaterialStateChanged,
+                Some("auto_import".to_string()),
+            );
+            if let Err(e) = publisher.publish(event) {
+                tracing::warn!("发布 MaterialStateChanged 事件失败: {}", e);
+            }
+        }
+
+        if !config.trigger_recalc {
+            return;
+        }
+
+        // 不原地重排激活版本（会丢弃人工调整并绕过审批/红线校验），
+        // 而是派生新的草稿版本，由计划员审阅、审批后再激活
+        let base_date = Local::now().date_naive();
+        match self.plan_api.recalc_full_with_strategy(
+            &version_id,
+            base_date,
+            None,
+            AUTO_IMPORT_OPERATOR,
+            ScheduleStrategy::Balanced,
+            Some(config.recalc_days as i32),
+        ) {
+            Ok(response) => result.recalc_version_id = Some(response.version_id),
+            Err(e) => {
+                tracing::warn!(version_id = %version_id, error = %e, "自动导入后生成重算草稿失败");
+                result.recalc_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// 列出已稳定且匹配模式的文件（按修改时间升序，先到先导）
+fn list_stable_files(
+    dir: &Path,
+    config: &AutoImportConfig,
+    now: SystemTime,
+) -> Result<Vec<PathBuf>, String> {
+    let stability = Duration::from_secs(config.stability_secs);
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("读取目录失败 {}: {}", dir.display(), e))?;
+
+    let mut files: Vec<(SystemTime, PathBuf)> = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if !meta.is_file() {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        // 跳过隐藏/临时文件（如 Excel 锁文件 ~$xxx.xlsx）
+        if name.starts_with('.') || name.starts_with("~$") || !config.matches_file_name(name) {
+            continue;
+        }
+        let modified = meta.modified().unwrap_or(now);
+        let quiet_for = now.duration_since(modified).unwrap_or(Duration::ZERO);
+        if quiet_for < stability {
+            tracing::debug!(file = name, "文件仍在写入或未达稳定等待时间，跳过");
+            continue;
+        }
+        files.push((modified, path));
+    }
+
+    files.sort();
+    Ok(files.into_iter().map(|(_, p)| p).collect())
+}
+
+/// 移动文件到目标目录（重名时追加时间戳前缀）
+fn move_into(path: &Path, target_dir: &Path) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(target_dir)?;
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+
+    let mut target = target_dir.join(&file_name);
+    if target.exists() {
+        target = target_dir.join(format!(
+            "{}_{}",
+            Local::now().format("%Y%m%d%H%M%S%3f"),
+            file_name
+        ));
+    }
+
+    if std::fs::rename(path, &target).is_err() {
+        // 跨设备移动回退为复制 + 删除
+        std::fs::copy(path, &target)?;
+        std::fs::remove_file(path)?;
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_file_name_patterns() {
+        let config = AutoImportConfig {
+            file_pattern: "MES_*.csv; snapshot_??.xlsx".to_string(),
+            ..Default::default()
+        };
+        assert!(config.matches_file_name("MES_20260101.csv"));
+        assert!(config.matches_file_name("mes_a.CSV"));
+        assert!(config.matches_file_name("snapshot_01.xlsx"));
+        assert!(!config.matches_file_name("snapshot_001.xlsx"));
+        assert!(!config.matches_file_name("other.csv"));
+
+        let default = AutoImportConfig::default();
+        assert!(default.matches_file_name("a.xls"));
+        assert!(!default.matches_file_name("a.txt"));
+    }
+
+    #[test]
+    fn test_list_stable_files_skips_recent_and_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.csv"), "x").unwrap();
+        std::fs::write(dir.path().join("~$b.xlsx"), "x").unwrap();
+        std::fs::write(dir.path().join("c.txt"), "x").unwrap();
+        std::fs::create_dir(dir.path().join(ARCHIVE_DIR_NAME)).unwrap();
+
+        let config = AutoImportConfig {
+            stability_secs: 60,
+            ..Default::default()
+        };
+
+        // 刚写入：未达稳定等待时间
+        let now = SystemTime::now();
+        assert!(list_stable_files(dir.path(), &config, now)
+            .unwrap()
+            .is_empty());
+
+        // 静置 2 分钟后：仅匹配的非临时文件
+        let later = now + Duration::from_secs(120);
+        let files = list_stable_files(dir.path(), &config, later).unwrap();
+        assert_eq!(files, vec![dir.path().join("a.csv")]);
+    }
+
+    #[test]
+    fn test_move_into_renames_on_collision() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join(ARCHIVE_DIR_NAME);
+        std::fs::create_dir(&archive).unwrap();
+        std::fs::write(archive.join("a.csv"), "old").unwrap();
+        let source = dir.path().join("a.csv");
+        std::fs::write(&source, "new").unwrap();
+
+        let moved = move_into(&source, &archive).unwrap();
+        assert!(!source.exists());
+        assert_ne!(moved, archive.join("a.csv"));
+        assert_eq!(std::fs::read_to_string(moved).unwrap(), "new");
+        assert_eq!(
+            std::fs::read_to_string(archive.join("a.csv")).unwrap(),
+            "old"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+// ==========================================
+// 热轧精整排产系统 - 人工干预时效清扫
+// ==========================================
+// 职责: 后台定时清扫已失效的 锁定 / 强制放行 / 人工紧急 / 优先级提升
+// 流程: 清扫 → 有清除时发布材料状态变更事件 → 通知前端
+// 配置: config_kv (global) intervention_expiry_sweep_secs，每轮重新读取
+// ==========================================
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{ExpirySweepResult, InterventionExpiryApi, PlanApi};
+use crate::config::config_manager::{config_keys, ConfigManager};
+use crate::engine::{ScheduleEvent, ScheduleEventPublisher, ScheduleEventType};
+
+/// 默认清扫间隔（秒）
+const DEFAULT_SWEEP_SECS: u64 = 60;
+
+/// 清扫间隔取值范围（秒）
+const SWEEP_SECS_RANGE: std::ops::RangeInclusive<u64> = 10..=3600;
+
+// ==========================================
+// InterventionExpiryStatus - 服务状态
+// ==========================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterventionExpiryStatus {
+    pub running: bool, // 后台清扫线程是否在运行
+    pub sweep_interval_secs: u64,
+    pub last_sweep_at: Option<String>,
+    pub last_sweep_error: Option<String>,
+    pub last_expired_count: usize,
+}
+
+/// 清扫清除了干预时的通知（用于推送前端事件）
+pub type InterventionExpiryNotifier = Arc<dyn Fn(&ExpirySweepResult) + Send + Sync>;
+
+#[derive(Default)]
+struct SweepHistory {
+    last_sweep_at: Option<String>,
+    last_sweep_error: Option<String>,
+    last_expired_count: usize,
+}
+
+// ==========================================
+// InterventionExpiryService - 人工干预时效清扫服务
+// ==========================================
+pub struct InterventionExpiryService {
+    expiry_api: Arc<InterventionExpiryApi>,
+    plan_api: Arc<PlanApi>,
+    config_manager: Arc<ConfigManager>,
+    event_publisher: Option<Arc<dyn ScheduleEventPublisher>>,
+    notifier: Mutex<Option<InterventionExpiryNotifier>>,
+    running: Arc<AtomicBool>,
+    stop_requested: Arc<AtomicBool>,
+    // 串行化清扫（后台定时与手动触发互斥，避免同一干预被清除两次）
+    sweep_lock: Mutex<()>,
+    history: Mutex<SweepHistory>,
+}
+
+impl InterventionExpiryService {
+    pub fn new(
+        expiry_api: Arc<InterventionExpiryApi>,
+        plan_api: Arc<PlanApi>,
+        config_manager: Arc<ConfigManager>,
+        event_publisher: Option<Arc<dyn ScheduleEventPublisher>>,
+    ) -> Self {
+        Self {
+            expiry_api,
+            plan_api,
+            config_manager,
+            event_publisher,
+            notifier: Mutex::new(None),
+            running: Arc::new(AtomicBool::new(false)),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            sweep_lock: Mutex::new(()),
+            history: Mutex::new(SweepHistory::default()),
+        }
+    }
+
+    /// 读取清扫间隔（缺失或非法值回退默认值）
+    pub fn sweep_interval_secs(&self) -> u64 {
+        self.config_manager
+            .get_global_config_value(config_keys::INTERVENTION_EXPIRY_SWEEP_SECS)
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|v| SWEEP_SECS_RANGE.contains(v))
+            .unwrap_or(DEFAULT_SWEEP_SECS)
+    }
+
+    /// 启动后台清扫线程（重复调用无副作用）
+    pub fn start(self: &Arc<Self>, notifier: Option<InterventionExpiryNotifier>) {
+        if let Ok(mut slot) = self.notifier.lock() {
+            *slot = notifier;
+        }
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.stop_requested.store(false, Ordering::SeqCst);
+
+        let service = Arc::clone(self);
+        let spawn_result = std::thread::Builder::new()
+            .name("intervention-expiry".to_string())
+            .spawn(move || {
+                tracing::info!("人工干预时效清扫线程已启动");
+                while !service.stop_requested.load(Ordering::SeqCst) {
+                    if let Err(e) = service.sweep_once() {
+                        tracing::warn!("人工干预时效清扫失败: {}", e);
+                    }
+                    service.sleep_interruptibly(Duration::from_secs(service.sweep_interval_secs()));
+                }
+                service.running.store(false, Ordering::SeqCst);
+                tracing::info!("人工干预时效清扫线程已停止");
+            });
+
+        if let Err(e) = spawn_result {
+            tracing::error!("人工干预时效清扫线程启动失败: {}", e);
+            self.running.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// 请求停止后台清扫（在当前清扫结束后生效）
+    pub fn stop(&self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+    }
+
+    fn sleep_interruptibly(&self, total: Duration) {
+        let step = Duration::from_millis(500);
+        let mut waited = Duration::ZERO;
+        while waited < total && !self.stop_requested.load(Ordering::SeqCst) {
+            std::thread::sleep(step);
+            waited += step;
+        }
+    }
+
+    /// 查询服务状态
+    pub fn status(&self) -> InterventionExpiryStatus {
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        InterventionExpiryStatus {
+            running: self.running.load(Ordering::SeqCst),
+            sweep_interval_secs: self.sweep_interval_secs(),
+            last_sweep_at: history.last_sweep_at.clone(),
+            last_sweep_error: history.last_sweep_error.clone(),
+            last_expired_count: history.last_expired_count,
+        }
+    }
+
+    /// 立即清扫一次（后台线程与手动触发共用）
+    pub fn sweep_once(&self) -> Result<ExpirySweepResult, String> {
+        let _guard = self.sweep_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let outcome = self
+            .expiry_api
+            .sweep_expired(chrono::Local::now().naive_local())
+            .map_err(|e| e.to_string());
+
+        {
+            let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+            history.last_sweep_at = Some(chrono::Utc::now().to_rfc3339());
+            history.last_sweep_error = outcome.as_ref().err().cloned();
+            if let Ok(result) = &outcome {
+                history.last_expired_count = result.expired.len();
+            }
+        }
+
+        if let Ok(result) = &outcome {
+            if !result.expired.is_empty() {
+                self.after_sweep(result);
+            }
+        }
+        outcome
+    }
+
+    /// 清除了干预后：发布材料状态变更事件并通知前端
+    fn after_sweep(&self, result: &ExpirySweepResult) {
+        if let Some(publisher) = &self.event_publisher {
+            match self.plan_api.get_latest_active_version_id() {
+                Ok(Some(version_id)) => {
+                    let event = ScheduleEvent::full_scope(
+                        version_id,
+                        ScheduleEventType::MaterialStateChanged,
+                        Some("intervention_expiry".to_string()),
+                    );
+                    if let Err(e) = publisher.publish(event) {
+                        tracing::warn!("发布 MaterialStateChanged 事件失败: {}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("查询激活版本失败: {}", e),
+            }
+        }
+
+        if let Ok(slot) = self.notifier.lock() {
+            if let Some(notify) = slot.as_ref() {
+                notify(result);
+            }
+        }
+    }
+}
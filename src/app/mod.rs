@@ -4,10 +4,14 @@
 // 职责: Tauri 集成,连接前端与后端
 // ==========================================
 
+pub mod auto_import;
+pub mod intervention_expiry;
 pub mod state;
 pub mod tauri_commands;
 
 // 重导出
+pub use auto_import::{AutoImportConfig, AutoImportService, AutoImportStatus};
+pub use intervention_expiry::{InterventionExpiryService, InterventionExpiryStatus};
 pub use state::{get_default_db_path, AppState};
 
 #[cfg(feature = "tauri-app")]
@@ -9,9 +9,13 @@ use rusqlite::{Connection, OpenFlags};
 use std::sync::{Arc, Mutex};
 
 use crate::api::{
-    ConfigApi, DashboardApi, ImportApi, ManualOperationValidator, MaterialApi, PathRuleApi,
-    PlanApi, RhythmApi, RollerApi,
+    ActivationRedLineValidator, BottleneckOptimizerApi, CapacityReservationApi, ConfigApi,
+    DashboardApi, ImportApi, InterventionExpiryApi, ManualOperationValidator, MaterialApi,
+    PathRuleApi, PlanApi, ProductionApi, RhythmApi, RollerApi, ScenarioApi, UndoApi,
+    VersionApprovalApi, VersionLineageApi,
 };
+use crate::app::auto_import::AutoImportService;
+use crate::app::intervention_expiry::InterventionExpiryService;
 use crate::config::config_manager::ConfigManager;
 use crate::db::open_sqlite_connection;
 use crate::decision::api::DecisionApiImpl;
@@ -36,14 +40,19 @@ use crate::repository::{
     action_log_repo::ActionLogRepository,
     capacity_repo::CapacityPoolRepository,
     decision_refresh_repo::DecisionRefreshRepository,
+    intervention_expiry_repo::InterventionExpiryRepository,
     material_repo::{MaterialMasterRepository, MaterialStateRepository},
     path_override_pending_repo::PathOverridePendingRepository,
     plan_repo::{PlanItemRepository, PlanRepository, PlanVersionRepository},
     plan_rhythm_repo::PlanRhythmRepository,
+    production_actual_repo::ProductionActualRepository,
     risk_repo::RiskSnapshotRepository,
     roll_campaign_plan_repo::RollCampaignPlanRepository,
     roller_repo::RollerCampaignRepository,
+    scenario_repo::ScenarioRepository,
     strategy_draft_repo::StrategyDraftRepository,
+    version_retention_repo::VersionRetentionRepository,
+    version_review_repo::VersionReviewRepository,
 };
 
 /// 应用状态
@@ -75,12 +84,42 @@ pub struct AppState {
     /// 每日生产节奏API
     pub rhythm_api: Arc<RhythmApi>,
 
+    /// 生产实绩API（实绩回填 + 计划执行对账）
+    pub production_api: Arc<ProductionApi>,
+
+    /// 场景/沙盘方案API（隔离评估 + 提升为草稿版本）
+    pub scenario_api: Arc<ScenarioApi>,
+
+    /// 版本激活审批API（提交/审批/驳回）
+    pub version_approval_api: Arc<VersionApprovalApi>,
+
+    /// 版本谱系与保留策略API（谱系树 + 历史版本清理）
+    pub version_lineage_api: Arc<VersionLineageApi>,
+
+    /// 瓶颈优化建议API（一键优化）
+    pub bottleneck_optimizer_api: Arc<BottleneckOptimizerApi>,
+
+    /// 人工操作撤销/重做API
+    pub undo_api: Arc<UndoApi>,
+
+    /// 人工干预时效API（到期/重算 N 次后自动清除）
+    pub intervention_expiry_api: Arc<InterventionExpiryApi>,
+
+    /// 产能预留API（战略合同/客户）
+    pub capacity_reservation_api: Arc<CapacityReservationApi>,
+
     /// 决策支持API
     pub decision_api: Arc<DecisionApiImpl>,
 
     /// 材料导入API
     pub import_api: Arc<ImportApi>,
 
+    /// 监控目录自动导入服务（后台线程由入口按需启动）
+    pub auto_import: Arc<AutoImportService>,
+
+    /// 人工干预时效清扫服务（后台线程由入口启动）
+    pub intervention_expiry: Arc<InterventionExpiryService>,
+
     /// 产能池仓储（用于产能管理命令）
     pub capacity_pool_repo: Arc<CapacityPoolRepository>,
 
@@ -184,6 +223,31 @@ impl AppState {
                 .map_err(|e| format!("无法创建PlanRhythmRepository: {}", e))?,
         );
 
+        let production_actual_repo = Arc::new(
+            ProductionActualRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建ProductionActualRepository: {}", e))?,
+        );
+
+        let scenario_repo = Arc::new(
+            ScenarioRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建ScenarioRepository: {}", e))?,
+        );
+
+        let version_review_repo = Arc::new(
+            VersionReviewRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建VersionReviewRepository: {}", e))?,
+        );
+
+        let version_retention_repo = Arc::new(
+            VersionRetentionRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建VersionRetentionRepository: {}", e))?,
+        );
+
+        let intervention_expiry_repo = Arc::new(
+            InterventionExpiryRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建InterventionExpiryRepository: {}", e))?,
+        );
+
         // 决策层Repository (D1-D6)
         let day_summary_repo = Arc::new(DaySummaryRepository::new(conn.clone())); // D1
         let order_failure_repo = Arc::new(OrderFailureRepository::new(conn.clone())); // D2
@@ -327,6 +391,54 @@ impl AppState {
             config_manager.clone(),
         ));
 
+        // 生产实绩 API（实绩回填 + 计划执行对账）
+        let production_api = Arc::new(ProductionApi::new(
+            production_actual_repo,
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            roller_campaign_repo.clone(),
+            action_log_repo.clone(),
+        ));
+
+        // 场景/沙盘方案 API（评估在数据库快照上进行，不触碰生产状态）
+        let scenario_api = Arc::new(ScenarioApi::new(
+            scenario_repo,
+            plan_repo.clone(),
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            capacity_pool_repo.clone(),
+            action_log_repo.clone(),
+            config_manager.clone(),
+        ));
+
+        // 激活前红线校验器（审批检查单与激活共用）
+        let activation_validator = Arc::new(ActivationRedLineValidator::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            material_state_repo.clone(),
+            capacity_pool_repo.clone(),
+            roller_campaign_repo.clone(),
+            path_override_pending_repo.clone(),
+        ));
+
+        // 版本激活审批 API（只有审批通过的版本才能激活）
+        let version_approval_api = Arc::new(VersionApprovalApi::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            capacity_pool_repo.clone(),
+            version_review_repo,
+            action_log_repo.clone(),
+            activation_validator.clone(),
+        ));
+
+        // 版本谱系与保留策略 API
+        let version_lineage_api = Arc::new(VersionLineageApi::new(
+            plan_repo.clone(),
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            version_retention_repo,
+        ));
+
         // 重算引擎（需要所有依赖）
         // 使用事件发布器而非直接依赖 RefreshQueue，实现依赖倒置
         let recalc_engine = Arc::new(RecalcEngine::with_default_config(
@@ -373,7 +485,7 @@ impl AppState {
         // 使用事件发布器而非直接依赖 RefreshQueue，实现依赖倒置
         let plan_api = Arc::new(PlanApi::new(
             plan_repo,
-            plan_version_repo,
+            plan_version_repo.clone(),
             plan_item_repo.clone(),
             material_state_repo.clone(),
             material_master_repo.clone(),
@@ -384,6 +496,7 @@ impl AppState {
             config_manager.clone(),
             recalc_engine,
             risk_engine,
+            activation_validator,
             event_publisher.clone(),
         ));
 
@@ -446,14 +559,69 @@ impl AppState {
         // 换辊管理API
         let roller_api = Arc::new(RollerApi::new(
             roller_campaign_repo,
-            roll_campaign_plan_repo,
+            roll_campaign_plan_repo.clone(),
             action_log_repo.clone(),
             config_manager.clone(),
         ));
 
+        // 瓶颈优化建议API（执行时复用各业务API的校验与日志）
+        let bottleneck_optimizer_api = Arc::new(BottleneckOptimizerApi::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            capacity_pool_repo.clone(),
+            material_master_repo.clone(),
+            material_state_repo.clone(),
+            path_override_pending_repo.clone(),
+            roll_campaign_plan_repo,
+            config_manager.clone(),
+            plan_api.clone(),
+            material_api.clone(),
+            path_rule_api.clone(),
+            roller_api.clone(),
+        ));
+
+        // 人工操作撤销/重做API（逆操作复用各业务API的校验）
+        let undo_api = Arc::new(UndoApi::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            action_log_repo.clone(),
+            plan_api.clone(),
+            material_api.clone(),
+            path_rule_api.clone(),
+        ));
+
+        // 人工干预时效API
+        let intervention_expiry_api = Arc::new(InterventionExpiryApi::new(
+            material_state_repo.clone(),
+            intervention_expiry_repo,
+            action_log_repo.clone(),
+        ));
+
+        // 产能预留API
+        let capacity_reservation_api = Arc::new(CapacityReservationApi::new(
+            capacity_pool_repo.clone(),
+            action_log_repo.clone(),
+        ));
+
         // 材料导入API
         let import_api = Arc::new(ImportApi::new(db_path.clone()));
 
+        // 监控目录自动导入服务
+        let auto_import = Arc::new(AutoImportService::new(
+            import_api.clone(),
+            plan_api.clone(),
+            config_manager.clone(),
+            event_publisher.clone(),
+        ));
+
+        // 人工干预时效清扫服务
+        let intervention_expiry = Arc::new(InterventionExpiryService::new(
+            intervention_expiry_api.clone(),
+            plan_api.clone(),
+            config_manager.clone(),
+            event_publisher.clone(),
+        ));
+
         tracing::info!("AppState初始化完成");
 
         Ok(Self {
@@ -465,8 +633,18 @@ impl AppState {
             path_rule_api,
             roller_api,
             rhythm_api,
+            production_api,
+            scenario_api,
+            version_approval_api,
+            version_lineage_api,
+            bottleneck_optimizer_api,
+            undo_api,
+            intervention_expiry_api,
+            capacity_reservation_api,
             decision_api,
             import_api,
+            auto_import,
+            intervention_expiry,
             capacity_pool_repo,
             action_log_repo,
             event_publisher,
@@ -16,9 +16,13 @@ mod import;
 mod material;
 mod path_rule;
 mod plan;
+mod production;
 mod rhythm;
 mod roller;
+mod scenario;
 mod telemetry;
+mod version_approval;
+mod version_lineage;
 
 pub use capacity::*;
 pub use config::*;
@@ -28,6 +32,10 @@ pub use import::*;
 pub use material::*;
 pub use path_rule::*;
 pub use plan::*;
+pub use production::*;
 pub use rhythm::*;
 pub use roller::*;
+pub use scenario::*;
 pub use telemetry::*;
+pub use version_approval::*;
+pub use version_lineage::*;
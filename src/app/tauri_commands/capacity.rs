@@ -1,7 +1,7 @@
 use crate::app::state::AppState;
 use serde::{Deserialize, Serialize};
 
-use super::common::emit_frontend_event;
+use super::common::{emit_frontend_event, map_api_error, parse_date};
 
 // ==========================================
 // 产能池管理相关命令
@@ -695,3 +695,97 @@ pub async fn get_machine_config_history(
 
     Ok(result)
 }
+
+// ==========================================
+// 产能预留（战略合同/客户）
+// ==========================================
+
+/// 创建产能预留
+///
+/// # 参数
+/// - date_from/date_to/expires_on: YYYY-MM-DD
+/// - contract_no / contract_prefix: 至少设置一个（合同号精确匹配 / 合同号前缀匹配客户）
+/// - release_days_before: 排产日期前 N 天释放未用预留（默认 0）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn create_capacity_reservation(
+    state: tauri::State<'_, AppState>,
+    machine_code: String,
+    date_from: String,
+    date_to: String,
+    daily_reserved_t: f64,
+    contract_no: Option<String>,
+    contract_prefix: Option<String>,
+    release_days_before: Option<i32>,
+    expires_on: Option<String>,
+    reason: Option<String>,
+    operator: String,
+) -> Result<String, String> {
+    use crate::api::CreateCapacityReservationRequest;
+
+    let expires_on = match expires_on.as_deref().map(str::trim) {
+        Some(raw) if !raw.is_empty() => Some(parse_date(raw)?),
+        _ => None,
+    };
+    let request = CreateCapacityReservationRequest {
+        machine_code,
+        date_from: parse_date(&date_from)?,
+        date_to: parse_date(&date_to)?,
+        daily_reserved_t,
+        contract_no,
+        contract_prefix,
+        release_days_before,
+        expires_on,
+        reason,
+        operator,
+    };
+
+    let result = state
+        .capacity_reservation_api
+        .create_reservation(request)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 取消产能预留
+#[tauri::command(rename_all = "snake_case")]
+pub async fn cancel_capacity_reservation(
+    state: tauri::State<'_, AppState>,
+    reservation_id: String,
+    operator: String,
+) -> Result<String, String> {
+    state
+        .capacity_reservation_api
+        .cancel_reservation(&reservation_id, &operator)
+        .map_err(map_api_error)?;
+
+    Ok("{}".to_string())
+}
+
+/// 查询产能预留（可按机组过滤）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn list_capacity_reservations(
+    state: tauri::State<'_, AppState>,
+    machine_code: Option<String>,
+) -> Result<String, String> {
+    let result = state
+        .capacity_reservation_api
+        .list_reservations(machine_code.as_deref())
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询版本的产能预留利用率
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_reservation_utilization(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+) -> Result<String, String> {
+    let result = state
+        .capacity_reservation_api
+        .get_reservation_utilization(&version_id)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
@@ -515,3 +515,32 @@ pub async fn get_capacity_opportunity(
     // 序列化返回
     serde_json::to_string(&response).map_err(|e| format!("序列化失败: {}", e))
 }
+
+/// D0: 方案多目标评价 - "这个版本整体怎么样"
+///
+/// # 参数
+/// - version_id: 方案版本ID
+///
+/// # 返回
+/// - 成功: JSON字符串, 包含标准目标向量（拖期/准时率/产能/结构/路径突破/换辊/冷料/换钢种）
+/// - 失败: 错误消息
+///
+/// # 说明
+/// - 与策略草案摘要、版本 KPI 对比使用同一评价引擎，保证各界面口径一致
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_plan_objectives(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    expected_plan_rev: Option<i32>,
+) -> Result<String, String> {
+    validate_expected_plan_rev(&state, &version_id, expected_plan_rev)?;
+
+    let plan_api = state.plan_api.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || plan_api.evaluate_version(&version_id))
+            .await
+            .map_err(|e| format!("任务执行失败: {}", e))?
+            .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
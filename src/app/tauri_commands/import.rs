@@ -1,5 +1,7 @@
 use crate::app::state::AppState;
 use crate::engine::{ScheduleEvent, ScheduleEventType};
+use crate::importer::{ImportProgress, ImportProgressCallback, ParseOptions};
+use std::sync::Arc;
 
 use super::common::{emit_frontend_event, map_api_error};
 
@@ -15,16 +17,38 @@ pub async fn import_materials(
     file_path: String,
     source_batch_id: String,
     mapping_profile_id: Option<String>,
+    sheet_name: Option<String>,
+    encoding: Option<String>,
 ) -> Result<String, String> {
     // 调试日志
     tracing::info!("[import_materials] 收到请求:");
     tracing::info!("  file_path: {}", file_path);
     tracing::info!("  source_batch_id: {}", source_batch_id);
     tracing::info!("  mapping_profile_id: {:?}", mapping_profile_id);
+    tracing::info!("  sheet_name: {:?}, encoding: {:?}", sheet_name, encoding);
+
+    let options = ParseOptions {
+        encoding,
+        sheet_name,
+    };
+
+    // 分块进度推送给前端
+    let progress_app = app.clone();
+    let progress: ImportProgressCallback = Arc::new(move |p: &ImportProgress| {
+        if let Ok(payload) = serde_json::to_value(p) {
+            emit_frontend_event(&progress_app, "material_import_progress", payload);
+        }
+    });
 
     let result = state
         .import_api
-        .import_materials(&file_path, &source_batch_id, mapping_profile_id.as_deref())
+        .import_materials_with_options(
+            &file_path,
+            &source_batch_id,
+            mapping_profile_id.as_deref(),
+            options,
+            Some(progress),
+        )
         .await
         .map_err(|e| {
             tracing::error!("[import_materials] 导入失败: {:?}", e);
@@ -55,6 +79,110 @@ pub async fn import_materials(
     serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
 }
 
+/// 导入合同/订单数据（按合同号更新关联材料影子字段并重算催料/紧急等级）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn import_contracts(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    sheet_name: Option<String>,
+    encoding: Option<String>,
+    operator: Option<String>,
+) -> Result<String, String> {
+    tracing::info!("[import_contracts] file_path: {}", file_path);
+
+    let options = ParseOptions {
+        encoding,
+        sheet_name,
+    };
+    let operator = operator.unwrap_or_else(|| "system".to_string());
+
+    let result = state
+        .import_api
+        .import_contracts(&file_path, options, &operator)
+        .await
+        .map_err(|e| {
+            tracing::error!("[import_contracts] 导入失败: {:?}", e);
+            map_api_error(e)
+        })?;
+
+    // 影子字段/等级变化 → 触发决策读模型刷新
+    if result.updated_materials > 0 {
+        if let Some(ref publisher) = state.event_publisher {
+            if let Ok(Some(version_id)) = state.plan_api.get_latest_active_version_id() {
+                let event = ScheduleEvent::full_scope(
+                    version_id,
+                    ScheduleEventType::MaterialStateChanged,
+                    Some("import_contracts".to_string()),
+                );
+                if let Err(e) = publisher.publish(event) {
+                    tracing::warn!("发布 MaterialStateChanged 事件失败: {}", e);
+                }
+            }
+        }
+
+        emit_frontend_event(
+            &app,
+            "material_state_changed",
+            serde_json::json!({ "import_batch_id": result.import_batch_id }),
+        );
+    }
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 取消进行中的材料导入
+///
+/// # 参数
+/// - source_batch_id: 发起 import_materials 时使用的批次ID
+///
+/// # 返回
+/// - { "cancelled": true } 已请求取消（在下一个分块边界生效）
+/// - { "cancelled": false } 该批次没有进行中的导入
+#[tauri::command(rename_all = "snake_case")]
+pub async fn cancel_material_import(
+    state: tauri::State<'_, AppState>,
+    source_batch_id: String,
+) -> Result<String, String> {
+    let cancelled = state
+        .import_api
+        .cancel_running_import(&source_batch_id)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&serde_json::json!({ "cancelled": cancelled }))
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询监控目录自动导入状态
+///
+/// # 返回
+/// - AutoImportStatus: 运行状态 / 当前配置 / 最近处理结果
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_auto_import_status(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let status = state.auto_import.status();
+    serde_json::to_string(&status).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 立即扫描一次监控目录（手动触发，不要求已启用）
+///
+/// # 返回
+/// - Vec<AutoImportFileResult>: 本次处理的文件结果
+#[tauri::command(rename_all = "snake_case")]
+pub async fn run_auto_import_scan(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let results = state.auto_import.scan_once().await?;
+
+    if results.iter().any(|r| r.imported > 0) {
+        emit_frontend_event(
+            &app,
+            "material_state_changed",
+            serde_json::json!({ "source": "auto_import" }),
+        );
+    }
+    serde_json::to_string(&results).map_err(|e| format!("序列化失败: {}", e))
+}
+
 /// 列出导入冲突
 #[tauri::command(rename_all = "snake_case")]
 pub async fn list_import_conflicts(
@@ -295,3 +295,114 @@ pub async fn list_materials_by_urgent_level(
 
     serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
 }
+
+// ==========================================
+// 人工干预时效
+// ==========================================
+
+fn parse_intervention_type(raw: &str) -> Result<crate::domain::InterventionType, String> {
+    crate::domain::InterventionType::parse(raw).ok_or_else(|| format!("无效的干预类型: {}", raw))
+}
+
+/// 设置人工干预时效（到期时间 "YYYY-MM-DD HH:MM[:SS]" 或重算次数，至少一个）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_intervention_expiry(
+    state: tauri::State<'_, AppState>,
+    material_ids: Vec<String>,
+    intervention_type: String,
+    expires_at: Option<String>,
+    expire_after_recalcs: Option<i32>,
+    operator: String,
+    reason: Option<String>,
+) -> Result<String, String> {
+    let intervention_type = parse_intervention_type(&intervention_type)?;
+    let expires_at = match expires_at
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        Some(raw) => {
+            let raw = raw.replace('T', " ");
+            Some(
+                chrono::NaiveDateTime::parse_from_str(&raw, "%Y-%m-%d %H:%M:%S")
+                    .or_else(|_| chrono::NaiveDateTime::parse_from_str(&raw, "%Y-%m-%d %H:%M"))
+                    .map_err(|e| format!("到期时间格式错误（应为YYYY-MM-DD HH:MM）: {}", e))?,
+            )
+        }
+        None => None,
+    };
+
+    let result = state
+        .intervention_expiry_api
+        .set_intervention_expiry(
+            material_ids,
+            intervention_type,
+            expires_at,
+            expire_after_recalcs,
+            &operator,
+            reason.as_deref(),
+        )
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 取消人工干预时效（干预恢复为长期有效）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn clear_intervention_expiry(
+    state: tauri::State<'_, AppState>,
+    material_ids: Vec<String>,
+    intervention_type: String,
+    operator: String,
+) -> Result<String, String> {
+    let intervention_type = parse_intervention_type(&intervention_type)?;
+    let removed = state
+        .intervention_expiry_api
+        .clear_intervention_expiry(material_ids, intervention_type, &operator)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&serde_json::json!({ "removed_count": removed }))
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 按设置人列出即将失效的人工干预
+#[tauri::command(rename_all = "snake_case")]
+pub async fn list_upcoming_intervention_expirations(
+    state: tauri::State<'_, AppState>,
+    operator: Option<String>,
+    within_hours: Option<i64>,
+) -> Result<String, String> {
+    let result = state
+        .intervention_expiry_api
+        .list_upcoming_expirations(operator.as_deref(), within_hours.unwrap_or(24))
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询人工干预时效清扫服务状态
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_intervention_expiry_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let status = state.intervention_expiry.status();
+    serde_json::to_string(&status).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 立即清扫一次已失效的人工干预
+#[tauri::command(rename_all = "snake_case")]
+pub async fn run_intervention_expiry_sweep(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let result = state.intervention_expiry.sweep_once()?;
+
+    if !result.expired.is_empty() {
+        emit_frontend_event(
+            &app,
+            "material_state_changed",
+            serde_json::json!({ "source": "intervention_expiry", "count": result.expired.len() }),
+        );
+    }
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
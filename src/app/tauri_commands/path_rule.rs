@@ -277,3 +277,70 @@ pub async fn reset_roll_cycle(
 
     Ok("{}".to_string())
 }
+
+/// 预览机组日内顺序调整（路径规则校验，不落库）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn preview_resequence_machine_day(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    machine_code: String,
+    plan_date: String,
+    material_ids: String, // JSON数组字符串（新顺序）
+) -> Result<String, String> {
+    let date = parse_date(&plan_date)?;
+    let ids: Vec<String> =
+        serde_json::from_str(&material_ids).map_err(|e| format!("解析材料ID列表失败: {}", e))?;
+
+    let result = state
+        .path_rule_api
+        .preview_resequence_machine_day(&version_id, &machine_code, date, &ids)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 机组日内顺序调整（甘特图拖拽）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn resequence_machine_day(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    machine_code: String,
+    plan_date: String,
+    material_ids: String, // JSON数组字符串（新顺序）
+    operator: String,
+    reason: Option<String>,
+) -> Result<String, String> {
+    let date = parse_date(&plan_date)?;
+    let ids: Vec<String> =
+        serde_json::from_str(&material_ids).map_err(|e| format!("解析材料ID列表失败: {}", e))?;
+
+    let result = state
+        .path_rule_api
+        .resequence_machine_day(
+            &version_id,
+            &machine_code,
+            date,
+            &ids,
+            &operator,
+            reason.as_deref(),
+        )
+        .map_err(map_api_error)?;
+
+    // 发布 ScheduleEvent 触发决策读模型刷新
+    if result.applied {
+        if let Some(ref publisher) = state.event_publisher {
+            let event = ScheduleEvent::incremental(
+                version_id.clone(),
+                ScheduleEventType::PlanItemChanged,
+                Some(format!("resequence_machine_day: {}", machine_code)),
+                Some(vec![machine_code.clone()]),
+                Some((date, date)),
+            );
+            if let Err(e) = publisher.publish(event) {
+                tracing::warn!("发布 PlanItemChanged 事件失败: {}", e);
+            }
+        }
+    }
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
@@ -162,20 +162,25 @@ pub async fn list_versions(
     serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
 }
 
-/// 激活版本
+/// 激活版本（存在红线硬违规时需填写覆盖原因）
 #[tauri::command(rename_all = "snake_case")]
 pub async fn activate_version(
     app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     version_id: String,
     operator: String,
+    override_reason: Option<String>,
 ) -> Result<String, String> {
     let plan_api = state.plan_api.clone();
     let plan_api_for_rev = state.plan_api.clone();
     let version_id_clone = version_id.clone();
     let operator_clone = operator.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        plan_api.activate_version(&version_id_clone, &operator_clone)
+        plan_api.activate_version_with_override(
+            &version_id_clone,
+            &operator_clone,
+            override_reason.as_deref(),
+        )
     })
     .await
     .map_err(|e| format!("任务执行失败: {}", e))?
@@ -340,21 +345,112 @@ pub async fn get_strategy_presets(state: tauri::State<'_, AppState>) -> Result<S
 }
 
 /// 生成多策略草案（dry-run 试算；不写正式排产，但会持久化草案）
+///
+/// 各策略并发试算，逐策略推送 strategy_draft_progress 事件；
+/// generation_id 由前端传入时可用 cancel_strategy_draft_generation 取消。
 #[tauri::command(rename_all = "snake_case")]
 pub async fn generate_strategy_drafts(
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     base_version_id: String,
     plan_date_from: String,
     plan_date_to: String,
     strategies: Vec<String>,
     operator: String,
+    generation_id: Option<String>,
 ) -> Result<String, String> {
+    let _perf = crate::perf::PerfGuard::new("ipc.generate_strategy_drafts");
     let from = parse_date(&plan_date_from)?;
     let to = parse_date(&plan_date_to)?;
 
     let plan_api = state.plan_api.clone();
     let result = tauri::async_runtime::spawn_blocking(move || {
-        plan_api.generate_strategy_drafts(&base_version_id, from, to, strategies, &operator)
+        use crate::api::plan_api::StrategyDraftProgress;
+
+        let on_progress = |progress: StrategyDraftProgress| {
+            if let Ok(payload) = serde_json::to_value(&progress) {
+                emit_frontend_event(&app, "strategy_draft_progress", payload);
+            }
+        };
+        plan_api.generate_strategy_drafts_with_progress(
+            &base_version_id,
+            from,
+            to,
+            strategies,
+            &operator,
+            generation_id.as_deref(),
+            &on_progress,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 取消进行中的草案生成（已完成的草案保留）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn cancel_strategy_draft_generation(
+    state: tauri::State<'_, AppState>,
+    generation_id: String,
+) -> Result<String, String> {
+    let cancelled = state
+        .plan_api
+        .cancel_strategy_draft_generation(&generation_id)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&serde_json::json!({
+        "generation_id": generation_id,
+        "cancelled": cancelled,
+    }))
+    .map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 自定义策略权重参数扫描（每个参数组合生成一条草案，返回 Pareto 最优集）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn run_strategy_sweep(
+    state: tauri::State<'_, AppState>,
+    base_version_id: String,
+    plan_date_from: String,
+    plan_date_to: String,
+    spec_json: String,
+    operator: String,
+) -> Result<String, String> {
+    use crate::engine::strategy_sweep::StrategySweepSpec;
+
+    let _perf = crate::perf::PerfGuard::new("ipc.run_strategy_sweep");
+    let from = parse_date(&plan_date_from)?;
+    let to = parse_date(&plan_date_to)?;
+    let spec: StrategySweepSpec =
+        serde_json::from_str(&spec_json).map_err(|e| format!("解析扫描规格失败: {}", e))?;
+
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        plan_api.run_strategy_sweep(&base_version_id, from, to, spec, &operator)
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 人工调整反推策略权重（离线分析，只读）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn learn_strategy_weights(
+    state: tauri::State<'_, AppState>,
+    analysis_from: String,
+    analysis_to: String,
+    strategy_key: String,
+) -> Result<String, String> {
+    let _perf = crate::perf::PerfGuard::new("ipc.learn_strategy_weights");
+    let from = parse_date(&analysis_from)?;
+    let to = parse_date(&analysis_to)?;
+
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        plan_api.learn_strategy_weights(from, to, &strategy_key)
     })
     .await
     .map_err(|e| format!("任务执行失败: {}", e))?
@@ -473,6 +569,20 @@ pub async fn get_plan_item_date_bounds(
     serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
 }
 
+/// 查询钢种族批量顺延材料
+#[tauri::command(rename_all = "snake_case")]
+pub async fn list_batching_deferrals(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+) -> Result<String, String> {
+    let result = state
+        .plan_api
+        .list_batching_deferrals(&version_id)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
 /// 查询排产明细
 #[tauri::command(rename_all = "snake_case")]
 pub async fn list_plan_items(
@@ -595,6 +705,105 @@ pub async fn compare_versions_kpi(
     serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
 }
 
+/// 分页查询物料级版本差异
+#[tauri::command(rename_all = "snake_case")]
+pub async fn list_version_diff_items(
+    state: tauri::State<'_, AppState>,
+    version_id_a: String,
+    version_id_b: String,
+    change_type: Option<String>,
+    machine_code: Option<String>,
+    limit: i64,
+    offset: i64,
+) -> Result<String, String> {
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let _perf = crate::perf::PerfGuard::new("ipc.list_version_diff_items");
+        plan_api.list_version_diff_items(
+            &version_id_a,
+            &version_id_b,
+            change_type.as_deref(),
+            machine_code.as_deref(),
+            limit,
+            offset,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 生成两个版本间的现场变更通知（from 为空表示首次激活）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn generate_change_notice(
+    state: tauri::State<'_, AppState>,
+    from_version_id: Option<String>,
+    to_version_id: String,
+) -> Result<String, String> {
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        plan_api.generate_change_notice(from_version_id.as_deref(), &to_version_id)
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询版本激活时保存的变更通知（可按机组/日期筛选）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_activation_change_notice(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    machine_code: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<String, String> {
+    let date_from = date_from.map(|s| parse_date(&s)).transpose()?;
+    let date_to = date_to.map(|s| parse_date(&s)).transpose()?;
+    let result = state
+        .plan_api
+        .get_activation_change_notice(&version_id)
+        .map_err(map_api_error)?
+        .filtered(machine_code.as_deref(), date_from, date_to);
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 导出版本激活变更通知（csv / xlsx）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn export_change_notice(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    format: String,
+    output_path: String,
+    machine_code: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<String, String> {
+    let date_from = date_from.map(|s| parse_date(&s)).transpose()?;
+    let date_to = date_to.map(|s| parse_date(&s)).transpose()?;
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        plan_api.export_change_notice(
+            &version_id,
+            &format,
+            &output_path,
+            machine_code.as_deref(),
+            date_from,
+            date_to,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
 /// 移动排产项
 ///
 /// # 参数
@@ -656,3 +865,291 @@ pub async fn move_items(
 
     serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
 }
+
+/// 生成瓶颈优化建议（只读，含预计影响）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn list_bottleneck_recommendations(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    plan_date_from: String,
+    plan_date_to: String,
+    machine_code: Option<String>,
+) -> Result<String, String> {
+    let _perf = crate::perf::PerfGuard::new("ipc.list_bottleneck_recommendations");
+    let from = parse_date(&plan_date_from)?;
+    let to = parse_date(&plan_date_to)?;
+
+    let optimizer_api = state.bottleneck_optimizer_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        optimizer_api.list_bottleneck_recommendations(
+            &version_id,
+            from,
+            to,
+            machine_code.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 一键执行瓶颈优化建议（移动走 move_items 校验与日志）
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub async fn apply_bottleneck_recommendations(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    plan_date_from: String,
+    plan_date_to: String,
+    machine_code: Option<String>,
+    recommendation_ids: Vec<String>,
+    operator: String,
+    reason: String,
+) -> Result<String, String> {
+    let _perf = crate::perf::PerfGuard::new("ipc.apply_bottleneck_recommendations");
+    let from = parse_date(&plan_date_from)?;
+    let to = parse_date(&plan_date_to)?;
+
+    let optimizer_api = state.bottleneck_optimizer_api.clone();
+    let plan_api_for_rev = state.plan_api.clone();
+    let version_id_clone = version_id.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        optimizer_api.apply_bottleneck_recommendations(
+            &version_id_clone,
+            from,
+            to,
+            machine_code.as_deref(),
+            &recommendation_ids,
+            &operator,
+            &reason,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    if !result.applied_ids.is_empty() {
+        let plan_rev = fetch_plan_rev_best_effort(plan_api_for_rev, version_id.clone()).await;
+        let mut payload = serde_json::json!({
+            "version_id": version_id,
+            "source": "bottleneck_optimizer",
+        });
+        attach_plan_rev(&mut payload, plan_rev);
+
+        emit_frontend_event(&app, "plan_updated", payload);
+        emit_frontend_event(&app, "risk_snapshot_updated", serde_json::json!({}));
+    }
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 预览版本三方合并（人工调整回放到重算版本，返回冲突清单）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn preview_version_merge(
+    state: tauri::State<'_, AppState>,
+    ancestor_version_id: String,
+    adjusted_version_id: String,
+    recalculated_version_id: String,
+) -> Result<String, String> {
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        plan_api.preview_version_merge(
+            &ancestor_version_id,
+            &adjusted_version_id,
+            &recalculated_version_id,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 执行版本三方合并并保存为草稿版本
+///
+/// # 参数
+/// - resolutions: 冲突处理方式列表 (JSON字符串, 可选)
+#[tauri::command(rename_all = "snake_case")]
+pub async fn apply_version_merge(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    ancestor_version_id: String,
+    adjusted_version_id: String,
+    recalculated_version_id: String,
+    resolutions: Option<String>,
+    operator: String,
+) -> Result<String, String> {
+    use crate::api::plan_api::MergeConflictResolution;
+
+    let resolutions: Vec<MergeConflictResolution> = match resolutions.as_deref() {
+        Some(raw) if !raw.trim().is_empty() => {
+            serde_json::from_str(raw).map_err(|e| format!("解析冲突处理方式失败: {}", e))?
+        }
+        _ => Vec::new(),
+    };
+
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        plan_api.apply_version_merge(
+            &ancestor_version_id,
+            &adjusted_version_id,
+            &recalculated_version_id,
+            resolutions,
+            &operator,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    emit_frontend_event(
+        &app,
+        "plan_updated",
+        serde_json::json!({ "version_id": result.merged_version_id }),
+    );
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 预览紧急插单（最早可行日 + 挤出链 + 拖期变化，不落库）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn preview_rush_insertion(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    material_ids: Vec<String>,
+) -> Result<String, String> {
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        plan_api.preview_rush_insertion(&version_id, &material_ids)
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 执行紧急插单并保存为派生草稿版本
+#[tauri::command(rename_all = "snake_case")]
+pub async fn apply_rush_insertion(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    material_ids: Vec<String>,
+    operator: String,
+    reason: String,
+) -> Result<String, String> {
+    let plan_api = state.plan_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        plan_api.apply_rush_insertion(&version_id, &material_ids, &operator, &reason)
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    emit_frontend_event(
+        &app,
+        "plan_updated",
+        serde_json::json!({ "version_id": result.derived_version_id }),
+    );
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询撤销/重做状态（按操作人 + 版本）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_undo_status(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    operator: String,
+) -> Result<String, String> {
+    let result = state
+        .undo_api
+        .get_undo_status(&version_id, &operator)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 撤销最近一次人工操作
+#[tauri::command(rename_all = "snake_case")]
+pub async fn undo_last_operation(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    operator: String,
+    reason: Option<String>,
+) -> Result<String, String> {
+    let undo_api = state.undo_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        undo_api.undo_last(&version_id, &operator, reason.as_deref())
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    emit_undo_redo_events(&app, &state, &result).await;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 重做最近一次撤销
+#[tauri::command(rename_all = "snake_case")]
+pub async fn redo_last_operation(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    operator: String,
+    reason: Option<String>,
+) -> Result<String, String> {
+    let undo_api = state.undo_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        undo_api.redo_last(&version_id, &operator, reason.as_deref())
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    emit_undo_redo_events(&app, &state, &result).await;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 撤销/重做后的刷新事件（移动刷新计划，其余刷新材料状态）
+async fn emit_undo_redo_events(
+    app: &tauri::AppHandle,
+    state: &tauri::State<'_, AppState>,
+    result: &crate::api::UndoRedoResponse,
+) {
+    if result.payload_kind == "MOVE_ITEMS" {
+        let plan_rev =
+            fetch_plan_rev_best_effort(state.plan_api.clone(), result.version_id.clone()).await;
+        let mut payload = serde_json::json!({
+            "version_id": result.version_id,
+            "source": "undo_redo",
+        });
+        attach_plan_rev(&mut payload, plan_rev);
+        emit_frontend_event(app, "plan_updated", payload);
+        return;
+    }
+
+    if let Some(ref publisher) = state.event_publisher {
+        let event = crate::engine::ScheduleEvent::full_scope(
+            result.version_id.clone(),
+            crate::engine::ScheduleEventType::MaterialStateChanged,
+            Some("undo_redo".to_string()),
+        );
+        if let Err(e) = publisher.publish(event) {
+            tracing::warn!("发布 MaterialStateChanged 事件失败: {}", e);
+        }
+    }
+    emit_frontend_event(
+        app,
+        "material_state_changed",
+        serde_json::json!({ "count": result.material_ids.len(), "source": "undo_redo" }),
+    );
+}
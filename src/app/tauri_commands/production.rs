@@ -0,0 +1,102 @@
+use crate::app::state::AppState;
+use crate::engine::{ScheduleEvent, ScheduleEventType};
+use crate::importer::ParseOptions;
+
+use super::common::{emit_frontend_event, map_api_error};
+
+// ==========================================
+// 生产实绩 / 计划执行对账相关命令
+// ==========================================
+
+/// 导入生产实绩（材料完工 + 换辊累计吨位 + 计划执行对账）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn import_production_actuals(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+    sheet_name: Option<String>,
+    encoding: Option<String>,
+    operator: Option<String>,
+) -> Result<String, String> {
+    tracing::info!("[import_production_actuals] file_path: {}", file_path);
+
+    let options = ParseOptions {
+        encoding,
+        sheet_name,
+    };
+    let operator = operator.unwrap_or_else(|| "system".to_string());
+
+    let result = state
+        .production_api
+        .import_actuals(&file_path, options, &operator)
+        .map_err(|e| {
+            tracing::error!("[import_production_actuals] 导入失败: {:?}", e);
+            map_api_error(e)
+        })?;
+
+    // 材料完工 / 换辊累计吨位变化 → 触发决策读模型刷新
+    if result.imported > 0 {
+        if let (Some(publisher), Some(version_id)) =
+            (state.event_publisher.as_ref(), result.version_id.as_ref())
+        {
+            let mut event_types = vec![ScheduleEventType::MaterialStateChanged];
+            if result.campaigns_updated > 0 {
+                event_types.push(ScheduleEventType::RollCampaignChanged);
+            }
+            for event_type in event_types {
+                let event = ScheduleEvent::full_scope(
+                    version_id.clone(),
+                    event_type,
+                    Some("import_production_actuals".to_string()),
+                );
+                if let Err(e) = publisher.publish(event) {
+                    tracing::warn!("发布实绩导入事件失败: {}", e);
+                }
+            }
+        }
+
+        emit_frontend_event(
+            &app,
+            "material_state_changed",
+            serde_json::json!({ "production_batch_id": result.batch_id }),
+        );
+    }
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询计划执行报告（按机组×日）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_plan_adherence_report(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    machine_code: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<String, String> {
+    let result = state
+        .production_api
+        .get_plan_adherence_report(
+            &version_id,
+            machine_code.as_deref(),
+            date_from.as_deref(),
+            date_to.as_deref(),
+        )
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询计划未完工顺延项
+#[tauri::command(rename_all = "snake_case")]
+pub async fn list_carry_over_items(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    open_only: Option<bool>,
+) -> Result<String, String> {
+    let result = state
+        .production_api
+        .list_carry_over_items(&version_id, open_only.unwrap_or(true))
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
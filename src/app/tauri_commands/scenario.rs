@@ -0,0 +1,177 @@
+use chrono::NaiveDateTime;
+
+use crate::app::state::AppState;
+use crate::domain::scenario::{MachineBreakdown, ScenarioOverlay};
+
+use super::common::{map_api_error, parse_date};
+
+// ==========================================
+// 场景/沙盘方案相关命令
+// ==========================================
+
+/// 创建场景方案（挂在基准方案下）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn create_scenario_plan(
+    state: tauri::State<'_, AppState>,
+    base_plan_id: String,
+    plan_name: String,
+    plan_type: Option<String>,
+    operator: String,
+) -> Result<String, String> {
+    let plan_type = plan_type.unwrap_or_else(|| "SCENARIO".to_string());
+    let plan_id = state
+        .scenario_api
+        .create_scenario_plan(&base_plan_id, &plan_name, &plan_type, &operator)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&serde_json::json!({ "plan_id": plan_id }))
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 保存场景叠加层（overlay 为 JSON 字符串，整体覆盖）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_scenario_overlay(
+    state: tauri::State<'_, AppState>,
+    plan_id: String,
+    overlay: String,
+    operator: String,
+) -> Result<String, String> {
+    let overlay: ScenarioOverlay =
+        serde_json::from_str(&overlay).map_err(|e| format!("叠加层格式错误: {}", e))?;
+    state
+        .scenario_api
+        .save_scenario_overlay(&plan_id, overlay, &operator)
+        .map_err(map_api_error)?;
+
+    Ok("{}".to_string())
+}
+
+/// 查询场景叠加层
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_scenario_overlay(
+    state: tauri::State<'_, AppState>,
+    plan_id: String,
+) -> Result<String, String> {
+    let result = state
+        .scenario_api
+        .get_scenario_overlay(&plan_id)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 在隔离沙盘中评估场景方案
+#[tauri::command(rename_all = "snake_case")]
+pub async fn evaluate_scenario(
+    state: tauri::State<'_, AppState>,
+    plan_id: String,
+    base_date: String,
+    window_days: Option<i32>,
+    strategy: Option<String>,
+    operator: String,
+) -> Result<String, String> {
+    let base_date = parse_date(&base_date)?;
+    let scenario_api = state.scenario_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        scenario_api.evaluate_scenario(
+            &plan_id,
+            base_date,
+            window_days.unwrap_or(7),
+            strategy,
+            &operator,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询场景评估结果（含明细）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_scenario_run(
+    state: tauri::State<'_, AppState>,
+    run_id: String,
+) -> Result<String, String> {
+    let result = state
+        .scenario_api
+        .get_scenario_run(&run_id)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 将场景评估结果提升为基准方案草稿版本
+#[tauri::command(rename_all = "snake_case")]
+pub async fn promote_scenario_run(
+    state: tauri::State<'_, AppState>,
+    run_id: String,
+    operator: String,
+) -> Result<String, String> {
+    let scenario_api = state.scenario_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        scenario_api.promote_scenario_run(&run_id, &operator)
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 模拟机组故障停机（默认不生成版本）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn simulate_machine_breakdown(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    machine_code: String,
+    outage_start: String,
+    outage_hours: f64,
+    capacity_reduction_pct: Option<f64>,
+    window_days: Option<i32>,
+    strategy: Option<String>,
+    create_version: Option<bool>,
+    operator: String,
+) -> Result<String, String> {
+    let outage_start = NaiveDateTime::parse_from_str(&outage_start, "%Y-%m-%d %H:%M:%S")
+        .map_err(|e| format!("故障开始时间格式错误（应为YYYY-MM-DD HH:MM:SS）: {}", e))?;
+    let breakdown = MachineBreakdown {
+        machine_code,
+        outage_start,
+        outage_hours,
+        capacity_reduction_pct,
+    };
+    let scenario_api = state.scenario_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        scenario_api.simulate_machine_breakdown(
+            &version_id,
+            breakdown,
+            window_days.unwrap_or(3),
+            strategy,
+            create_version.unwrap_or(false),
+            &operator,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 回放历史一键重算并与已存明细对比（沙盘内执行，不写生产库）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn replay_recalc(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+) -> Result<String, String> {
+    let scenario_api = state.scenario_api.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || scenario_api.replay_recalc(&version_id))
+            .await
+            .map_err(|e| format!("任务执行失败: {}", e))?
+            .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
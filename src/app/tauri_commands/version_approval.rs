@@ -0,0 +1,99 @@
+use crate::app::state::AppState;
+
+use super::common::map_api_error;
+
+// ==========================================
+// 版本激活审批相关命令
+// ==========================================
+
+/// 提交版本审批（附激活前检查单）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn submit_version_for_approval(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    comment: Option<String>,
+    operator: String,
+) -> Result<String, String> {
+    let result = state
+        .version_approval_api
+        .submit_version(&version_id, comment, &operator)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 审批通过版本
+#[tauri::command(rename_all = "snake_case")]
+pub async fn approve_version(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    comment: Option<String>,
+    operator: String,
+) -> Result<String, String> {
+    let result = state
+        .version_approval_api
+        .approve_version(&version_id, comment, &operator)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 驳回版本（必须填写驳回意见）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn reject_version(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+    comment: String,
+    operator: String,
+) -> Result<String, String> {
+    let result = state
+        .version_approval_api
+        .reject_version(&version_id, &comment, &operator)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询版本审批记录
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_version_reviews(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+) -> Result<String, String> {
+    let result = state
+        .version_approval_api
+        .get_version_reviews(&version_id)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 预览激活前检查单
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_activation_checklist(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+) -> Result<String, String> {
+    let result = state
+        .version_approval_api
+        .build_activation_checklist(&version_id)
+        .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 查询版本激活前红线校验报告
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_red_line_report(
+    state: tauri::State<'_, AppState>,
+    version_id: String,
+) -> Result<String, String> {
+    let plan_api = state.plan_api.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || plan_api.get_red_line_report(&version_id))
+            .await
+            .map_err(|e| format!("任务执行失败: {}", e))?
+            .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
@@ -0,0 +1,58 @@
+use crate::app::state::AppState;
+use crate::domain::version_lineage::VersionRetentionPolicy;
+
+use super::common::map_api_error;
+
+// ==========================================
+// 版本谱系与保留策略相关命令
+// ==========================================
+
+/// 查询方案的版本谱系树
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_version_lineage(
+    state: tauri::State<'_, AppState>,
+    plan_id: String,
+) -> Result<String, String> {
+    let version_lineage_api = state.version_lineage_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        version_lineage_api.get_version_lineage(&plan_id)
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 按保留策略清理历史版本（dry_run 默认 true，仅预览）
+#[tauri::command(rename_all = "snake_case")]
+pub async fn purge_versions(
+    state: tauri::State<'_, AppState>,
+    plan_id: Option<String>,
+    keep_archived_per_plan: Option<usize>,
+    keep_ever_active: Option<bool>,
+    max_age_days: Option<i64>,
+    dry_run: Option<bool>,
+    operator: String,
+) -> Result<String, String> {
+    let defaults = VersionRetentionPolicy::default();
+    let policy = VersionRetentionPolicy {
+        keep_archived_per_plan: keep_archived_per_plan.unwrap_or(defaults.keep_archived_per_plan),
+        keep_ever_active: keep_ever_active.unwrap_or(defaults.keep_ever_active),
+        max_age_days: max_age_days.or(defaults.max_age_days),
+    };
+    let version_lineage_api = state.version_lineage_api.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        version_lineage_api.purge_versions(
+            plan_id.as_deref(),
+            policy,
+            dry_run.unwrap_or(true),
+            &operator,
+        )
+    })
+    .await
+    .map_err(|e| format!("任务执行失败: {}", e))?
+    .map_err(map_api_error)?;
+
+    serde_json::to_string(&result).map_err(|e| format!("序列化失败: {}", e))
+}
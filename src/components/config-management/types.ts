@@ -61,11 +61,16 @@ export const configKeyLabels: Record<string, string> = {
   // 重算配置
   recalc_window_days: '重算窗口天数',
   cascade_window_days: '级联窗口天数',
+  recalc_persist_inputs: '保存重算全量输入',
+
+  // 策略草案配置
+  strategy_draft_max_days: '草案时间跨度上限',
 
   // 结构校正配置
   target_ratio: '目标钢种配比',
   deviation_threshold: '结构偏差阈值',
   rhythm_deviation_threshold: '节奏偏差阈值',
+  cold_stock_age_threshold_days: '冷料库龄阈值',
 
   // 堵塞评分 堵塞评分配置
   d4_capacity_hard_threshold: '堵塞评分 产能硬阈值',
@@ -83,6 +88,16 @@ export const configKeyLabels: Record<string, string> = {
   weight_anomaly_threshold: '重量异常阈值',
   batch_retention_days: '批次保留天数',
 
+  // 监控目录自动导入
+  auto_import_enabled: '自动导入开关',
+  auto_import_dir: '自动导入监控目录',
+  auto_import_file_pattern: '自动导入文件名模式',
+  auto_import_mapping_profile_id: '自动导入映射配置',
+  auto_import_stability_secs: '自动导入稳定等待（秒）',
+  auto_import_poll_secs: '自动导入轮询间隔（秒）',
+  auto_import_trigger_recalc: '自动导入后生成重算草稿',
+  auto_import_recalc_days: '自动导入重算窗口天数',
+
   // 宽厚路径规则（v0.6）
   path_rule_enabled: '宽厚路径规则开关',
   path_width_tolerance_mm: '路径宽度容差（毫米）',
@@ -124,11 +139,16 @@ export const configDescriptions: Record<string, string> = {
   // 重算配置
   recalc_window_days: '重算窗口天数（重新计算排产的时间窗口，默认7天）',
   cascade_window_days: '级联重算窗口天数（影响后续排产的级联范围，默认14天）',
+  recalc_persist_inputs: '是否保存一键重算的全量输入（材料/配置/产能池/冻结区），用于历史重算确定性回放；true/false，默认false',
+
+  // 策略草案配置
+  strategy_draft_max_days: '策略草案/参数扫描允许的最大时间跨度（单位：天，默认60天，最大366天）',
 
   // 结构校正配置
   target_ratio: '目标钢种配比（数据格式，如：{"钢种甲":0.3,"钢种乙":0.5}，空对象{}表示不启用）',
   deviation_threshold: '结构偏差阈值（允许的目标配比偏差，默认0.1即10%）',
   rhythm_deviation_threshold: '每日生产节奏偏差阈值（用于节奏监控的最大偏差阈值，默认0.1即10%）',
+  cold_stock_age_threshold_days: '冷料库龄阈值（单位：天，默认30天；D3冷料压库与方案评估共用）',
 
   // 堵塞评分 堵塞评分配置
   d4_capacity_hard_threshold: '产能硬阈值（已用/上限）。低于该值不计堵塞，仅提示。默认0.95。',
@@ -146,6 +166,17 @@ export const configDescriptions: Record<string, string> = {
   weight_anomaly_threshold: '重量异常阈值（单位：吨，超过此值视为异常，默认100.0吨）',
   batch_retention_days: '批次数据保留天数（导入批次记录保留时长，默认90天）',
 
+  // 监控目录自动导入
+  auto_import_enabled: '是否启用监控目录自动导入（是/否，默认否）',
+  auto_import_dir:
+    'MES 快照投放目录。导入成功的文件移入其下 archive 子目录，失败的移入 failed 子目录（附 .error.txt 说明）。',
+  auto_import_file_pattern: '文件名匹配模式（分号分隔，支持 * 和 ?），默认 *.csv;*.xlsx;*.xls',
+  auto_import_mapping_profile_id: '自动导入使用的映射配置ID（可选）',
+  auto_import_stability_secs: '稳定等待（秒）。文件最后修改后静置达到该时长才导入，避免读到写入中的文件，默认30。',
+  auto_import_poll_secs: '目录轮询间隔（秒），默认60。',
+  auto_import_trigger_recalc: '导入成功后是否基于激活版本生成重算草稿版本（需审批后激活，不改动激活版本；是/否，默认否）',
+  auto_import_recalc_days: '重算窗口（从今天起的天数，1~60），默认7。',
+
   // 宽厚路径规则（v0.6）
   path_rule_enabled: '是否启用“由宽到窄、由厚到薄”的路径约束（是/否）',
   path_width_tolerance_mm: '宽度容差（单位：毫米）。候选宽度允许小于锚点宽度的最大差值，超过则判定违规。',
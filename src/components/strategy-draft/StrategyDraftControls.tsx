@@ -4,9 +4,9 @@
  */
 
 import React from 'react';
-import { Button, Card, Checkbox, DatePicker, Space, Tag, Typography } from 'antd';
+import { Button, Card, Checkbox, DatePicker, Progress, Space, Tag, Typography } from 'antd';
 import type { Dayjs } from 'dayjs';
-import type { StrategyKey, StrategyPreset } from '../../types/strategy-draft';
+import type { StrategyDraftProgress, StrategyKey, StrategyPreset } from '../../types/strategy-draft';
 
 const { RangePicker } = DatePicker;
 const { Text } = Typography;
@@ -20,9 +20,13 @@ export interface StrategyDraftControlsProps {
   strategyTitleMap: Partial<Record<StrategyKey, string>>;
   canGenerate: boolean;
   isGenerating: boolean;
+  progressByStrategy: Partial<Record<StrategyKey, StrategyDraftProgress>>;
+  isCancelling: boolean;
+  maxDays: number;
   onRangeChange: (range: [Dayjs, Dayjs]) => void;
   onSelectedStrategiesChange: (keys: StrategyKey[]) => void;
   onGenerate: () => void;
+  onCancelGenerate: () => void;
   onNavigateSettings: () => void;
   onNavigateHistorical: () => void;
   onNavigateWorkbench: () => void;
@@ -37,13 +41,29 @@ export const StrategyDraftControls: React.FC<StrategyDraftControlsProps> = ({
   strategyTitleMap,
   canGenerate,
   isGenerating,
+  progressByStrategy,
+  isCancelling,
+  maxDays,
   onRangeChange,
   onSelectedStrategiesChange,
   onGenerate,
+  onCancelGenerate,
   onNavigateSettings,
   onNavigateHistorical,
   onNavigateWorkbench,
 }) => {
+  const progressList = Object.values(progressByStrategy).filter(
+    (p): p is StrategyDraftProgress => Boolean(p)
+  );
+  const totalCount = progressList[0]?.total_count ?? selectedStrategies.length;
+  const finishedCount = progressList.filter((p) => p.status !== 'STARTED').length;
+  const progressColor: Record<StrategyDraftProgress['status'], string> = {
+    STARTED: 'processing',
+    COMPLETED: 'success',
+    FAILED: 'error',
+    CANCELLED: 'default',
+  };
+
   return (
     <Card
       size="small"
@@ -90,7 +110,7 @@ export const StrategyDraftControls: React.FC<StrategyDraftControlsProps> = ({
             allowClear={false}
           />
           <Text type="secondary" style={{ fontSize: 12 }}>
-            {headerHint}
+            {headerHint}（最多{maxDays}天）
           </Text>
         </Space>
 
@@ -118,6 +138,29 @@ export const StrategyDraftControls: React.FC<StrategyDraftControlsProps> = ({
           <Button type="primary" disabled={!canGenerate} loading={isGenerating} onClick={onGenerate}>
             重新计算策略草案
           </Button>
+          {isGenerating && (
+            <Button danger loading={isCancelling} onClick={onCancelGenerate}>
+              取消生成
+            </Button>
+          )}
+        </Space>
+
+        {isGenerating && totalCount > 0 && (
+          <Space wrap>
+            <Progress
+              percent={Math.round((finishedCount / totalCount) * 100)}
+              size="small"
+              style={{ width: 200 }}
+            />
+            {progressList.map((p) => (
+              <Tag key={p.strategy} color={progressColor[p.status]}>
+                {strategyTitleMap[p.strategy] || p.strategy}
+              </Tag>
+            ))}
+          </Space>
+        )}
+
+        <Space wrap>
           <Text type="secondary" style={{ fontSize: 12 }}>
             说明：草案会持久化保存（用于恢复/审计），但不会改写正式排产；发布后才生成正式版本。
           </Text>
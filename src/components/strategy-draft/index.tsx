@@ -30,9 +30,13 @@ const StrategyDraftComparison: React.FC = () => {
         strategyTitleMap={workflow.strategyTitleMap}
         canGenerate={workflow.canGenerate}
         isGenerating={workflow.isGenerating}
+        progressByStrategy={workflow.progressByStrategy}
+        isCancelling={workflow.isCancelling}
+        maxDays={workflow.maxDays}
         onRangeChange={workflow.setRange}
         onSelectedStrategiesChange={workflow.setSelectedStrategies}
         onGenerate={workflow.handleGenerate}
+        onCancelGenerate={workflow.handleCancelGenerate}
         onNavigateSettings={() => workflow.navigate('/settings?tab=strategy')}
         onNavigateHistorical={() => workflow.navigate('/comparison?tab=historical')}
         onNavigateWorkbench={() => workflow.navigate('/workbench')}
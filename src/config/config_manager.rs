@@ -10,6 +10,7 @@
 use crate::config::import_config_trait::ImportConfigReader;
 use crate::config::strategy_profile::CustomStrategyProfile;
 use crate::db::open_sqlite_connection;
+use crate::domain::batching::GradeBatchingRule;
 use crate::domain::types::{Season, SeasonMode};
 use async_trait::async_trait;
 use chrono::{Datelike, NaiveDate};
@@ -234,6 +235,31 @@ impl ConfigManager {
         Ok(count)
     }
 
+    /// 以配置快照整体替换 global 配置（快照中不存在的键将被删除）
+    ///
+    /// # 注意
+    /// - 仅用于隔离快照库（例如重算回放沙盘），不应对生产库调用
+    pub fn replace_config_from_snapshot(
+        &self,
+        snapshot_json: &str,
+    ) -> Result<usize, Box<dyn Error>> {
+        let config_map: HashMap<String, String> = serde_json::from_str(snapshot_json)?;
+        {
+            let conn = self.conn.lock().map_err(|e| format!("锁获取失败: {}", e))?;
+            let keys: Vec<String> = conn
+                .prepare("SELECT key FROM config_kv WHERE scope_id = 'global'")?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<_, _>>()?;
+            for key in keys.iter().filter(|k| !config_map.contains_key(*k)) {
+                conn.execute(
+                    "DELETE FROM config_kv WHERE scope_id = 'global' AND key = ?1",
+                    params![key],
+                )?;
+            }
+        }
+        self.restore_config_from_snapshot(snapshot_json)
+    }
+
     // ===== 结构校正配置 =====
 
     /// 获取目标钢种配比
@@ -265,6 +291,27 @@ impl ConfigManager {
         let value = self.get_config_or_default(config_keys::DEVIATION_THRESHOLD, "0.1")?;
         Ok(value.parse::<f64>().unwrap_or(0.1))
     }
+
+    /// 获取钢种族最小批量规则
+    ///
+    /// # 返回
+    /// - Vec<GradeBatchingRule>: 有效规则（格式错误时返回空，无效规则被忽略）
+    pub async fn get_grade_batching_rules(&self) -> Result<Vec<GradeBatchingRule>, Box<dyn Error>> {
+        let value = self.get_config_or_default(config_keys::GRADE_BATCHING_RULES, "[]")?;
+        let rules: Vec<GradeBatchingRule> = serde_json::from_str(&value).unwrap_or_else(|_| {
+            tracing::warn!(
+                config_key = config_keys::GRADE_BATCHING_RULES,
+                raw_value = %value,
+                "钢种族批量规则配置格式错误，使用空配置"
+            );
+            Vec::new()
+        });
+        let (valid, invalid): (Vec<_>, Vec<_>) = rules.into_iter().partition(|r| r.is_valid());
+        for rule in &invalid {
+            tracing::warn!(family = %rule.family, "钢种族批量规则无效（缺少前缀或最小批量），已忽略");
+        }
+        Ok(valid)
+    }
 }
 
 // ==========================================
@@ -557,14 +604,37 @@ pub mod config_keys {
     // 重算
     pub const RECALC_WINDOW_DAYS: &str = "recalc_window_days";
     pub const CASCADE_WINDOW_DAYS: &str = "cascade_window_days";
+    pub const RECALC_PERSIST_INPUTS: &str = "recalc_persist_inputs"; // 是否保存重算全量输入（用于回放）
+
+    // 策略草案
+    pub const STRATEGY_DRAFT_MAX_DAYS: &str = "strategy_draft_max_days"; // 草案时间跨度上限（天）
 
     // 结构校正
     pub const TARGET_RATIO: &str = "target_ratio"; // 目标钢种配比 (JSON)
     pub const DEVIATION_THRESHOLD: &str = "deviation_threshold"; // 偏差阈值
 
+    // 冷料库龄阈值（天）：D3 冷料压库最高压力档与方案评估冷料口径共用
+    pub const COLD_STOCK_AGE_THRESHOLD_DAYS: &str = "cold_stock_age_threshold_days";
+
+    // 钢种族最小批量（JSON 数组，见 domain::batching::GradeBatchingRule）
+    pub const GRADE_BATCHING_RULES: &str = "grade_batching_rules";
+
     // 每日生产节奏（品种大类等）
     // 说明：与结构校正的 deviation_threshold 口径解耦，避免相互影响。
     pub const RHYTHM_DEVIATION_THRESHOLD: &str = "rhythm_deviation_threshold";
+
+    // 监控目录自动导入
+    pub const AUTO_IMPORT_ENABLED: &str = "auto_import_enabled";
+    pub const AUTO_IMPORT_DIR: &str = "auto_import_dir";
+    pub const AUTO_IMPORT_FILE_PATTERN: &str = "auto_import_file_pattern";
+    pub const AUTO_IMPORT_MAPPING_PROFILE_ID: &str = "auto_import_mapping_profile_id";
+    pub const AUTO_IMPORT_STABILITY_SECS: &str = "auto_import_stability_secs";
+    pub const AUTO_IMPORT_POLL_SECS: &str = "auto_import_poll_secs";
+    pub const AUTO_IMPORT_TRIGGER_RECALC: &str = "auto_import_trigger_recalc";
+    pub const AUTO_IMPORT_RECALC_DAYS: &str = "auto_import_recalc_days";
+
+    // 人工干预时效清扫
+    pub const INTERVENTION_EXPIRY_SWEEP_SECS: &str = "intervention_expiry_sweep_secs";
 }
 
 // TODO: 实现错误处理
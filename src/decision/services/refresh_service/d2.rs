@@ -79,6 +79,55 @@ impl DecisionRefreshService {
 
         let rows_affected = tx.execute(insert_sql, rusqlite::params![&scope.version_id])?;
 
+        // 4. 钢种族批量顺延：对含顺延材料的合同追加失败原因与阻塞因素
+        // 需要 grade_batching_deferral 表（重算时按需创建）；若不存在则跳过
+        let has_batching_table: i32 = tx.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='grade_batching_deferral'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_batching_table > 0 {
+            let batching_sql = r#"
+                WITH batching AS (
+                    SELECT
+                        contract_no,
+                        group_concat(
+                            '钢种族 ' || grade_family || ' 未达最小批量，顺延 ' || cnt || ' 块',
+                            '; '
+                        ) AS description
+                    FROM (
+                        SELECT ms.contract_no, d.grade_family, COUNT(*) AS cnt
+                        FROM grade_batching_deferral d
+                        JOIN material_state ms ON ms.material_id = d.material_id
+                        WHERE d.version_id = ?1 AND ms.contract_no IS NOT NULL
+                        GROUP BY ms.contract_no, d.grade_family
+                    )
+                    GROUP BY contract_no
+                )
+                UPDATE decision_order_failure_set
+                SET
+                    failure_reasons = json_insert(
+                        failure_reasons,
+                        '$[#]',
+                        (SELECT b.description FROM batching b
+                         WHERE b.contract_no = decision_order_failure_set.contract_no)
+                    ),
+                    blocking_factors = json_insert(
+                        blocking_factors,
+                        '$[#]',
+                        json_object(
+                            'factor', 'GradeBatching',
+                            'severity', 0.6,
+                            'description', (SELECT b.description FROM batching b
+                                            WHERE b.contract_no = decision_order_failure_set.contract_no)
+                        )
+                    )
+                WHERE version_id = ?1
+                    AND contract_no IN (SELECT contract_no FROM batching)
+            "#;
+            tx.execute(batching_sql, rusqlite::params![&scope.version_id])?;
+        }
+
         Ok(rows_affected)
     }
 }
@@ -112,7 +112,7 @@ impl DecisionRefreshService {
                     (
                         (
                             CASE
-                                WHEN age_min_days >= 30 THEN 1.0
+                                WHEN age_min_days >= COALESCE(NULLIF(CAST((SELECT value FROM config_kv WHERE scope_id = 'global' AND key = 'cold_stock_age_threshold_days') AS INTEGER), 0), 30) THEN 1.0
                                 WHEN age_min_days >= 15 THEN 0.7
                                 WHEN age_min_days >= 8 THEN 0.4
                                 ELSE 0.2
@@ -0,0 +1,194 @@
+// ==========================================
+// 热轧精整排产系统 - 钢种族批量约束领域模型
+// ==========================================
+// 用途: 酸洗/特殊合金等钢种换规格准备成本高，
+//       同一机组同一天的同族材料需凑满最小批量（吨位/块数）才开轧，
+//       否则整族顺延，并在跳过原因与 D 层解释中给出批量原因
+// 配置: config_keys::GRADE_BATCHING_RULES (JSON 数组)
+// ==========================================
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// 批量顺延原因码（skipped_materials 原因前缀）
+pub const GRADE_BATCHING_REASON: &str = "GRADE_BATCHING";
+
+// ==========================================
+// GradeBatchingRule - 钢种族最小批量规则
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradeBatchingRule {
+    /// 钢种族名称（如 PICKLED / ALLOY）
+    pub family: String,
+    /// 出钢记号前缀（不区分大小写，命中任一即属于该族）
+    pub steel_mark_prefixes: Vec<String>,
+    /// 单批最小吨位
+    #[serde(default)]
+    pub min_run_t: Option<f64>,
+    /// 单批最小块数
+    #[serde(default)]
+    pub min_run_coils: Option<u32>,
+    /// 适用机组（为空表示全部机组）
+    #[serde(default)]
+    pub machine_codes: Vec<String>,
+}
+
+impl GradeBatchingRule {
+    /// 规则是否有效：族名、前缀非空，且至少设置一个正的最小批量
+    pub fn is_valid(&self) -> bool {
+        !self.family.trim().is_empty()
+            && self
+                .steel_mark_prefixes
+                .iter()
+                .any(|p| !p.trim().is_empty())
+            && (self.min_run_t.is_some_and(|t| t.is_finite() && t > 0.0)
+                || self.min_run_coils.is_some_and(|c| c > 0))
+    }
+
+    /// 是否适用于机组
+    pub fn applies_to_machine(&self, machine_code: &str) -> bool {
+        self.machine_codes.is_empty()
+            || self
+                .machine_codes
+                .iter()
+                .any(|m| m.trim().eq_ignore_ascii_case(machine_code))
+    }
+
+    /// 出钢记号是否属于该族
+    pub fn matches_steel_mark(&self, steel_mark: Option<&str>) -> bool {
+        let Some(mark) = steel_mark.map(str::trim).filter(|s| !s.is_empty()) else {
+            return false;
+        };
+        let mark = mark.to_uppercase();
+        self.steel_mark_prefixes
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .any(|p| mark.starts_with(&p.to_uppercase()))
+    }
+
+    /// 批次是否满足最小批量（吨位与块数均需达标）
+    pub fn is_satisfied(&self, run: &BatchRun) -> bool {
+        let weight_ok = self.min_run_t.is_none_or(|t| run.weight_t + 1e-9 >= t);
+        let coils_ok = self.min_run_coils.is_none_or(|c| run.coils >= c);
+        weight_ok && coils_ok
+    }
+
+    /// 顺延原因
+    ///
+    /// capacity_limited: true 表示材料足够但当日产能装不下最小批量
+    pub fn deferral_reason(&self, run: &BatchRun, capacity_limited: bool) -> String {
+        format!(
+            "{}: family={}, run_t={:.1}, run_coils={}, min_run_t={}, min_run_coils={}, cause={}",
+            GRADE_BATCHING_REASON,
+            self.family,
+            run.weight_t,
+            run.coils,
+            self.min_run_t
+                .map(|t| format!("{t:.1}"))
+                .unwrap_or_else(|| "-".to_string()),
+            self.min_run_coils
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            if capacity_limited {
+                "INSUFFICIENT_CAPACITY"
+            } else {
+                "INSUFFICIENT_MATERIAL"
+            }
+        )
+    }
+}
+
+/// 按机组与出钢记号匹配钢种族（多条规则命中时取第一条）
+pub fn match_batching_rule<'a>(
+    rules: &'a [GradeBatchingRule],
+    machine_code: &str,
+    steel_mark: Option<&str>,
+) -> Option<&'a GradeBatchingRule> {
+    rules
+        .iter()
+        .find(|r| r.applies_to_machine(machine_code) && r.matches_steel_mark(steel_mark))
+}
+
+// ==========================================
+// BatchRun - 同族批次累计
+// ==========================================
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BatchRun {
+    pub weight_t: f64,
+    pub coils: u32,
+}
+
+impl BatchRun {
+    pub fn add(&mut self, weight_t: f64) {
+        if weight_t.is_finite() && weight_t > 0.0 {
+            self.weight_t += weight_t;
+        }
+        self.coils += 1;
+    }
+}
+
+// ==========================================
+// BatchingDeferral - 批量顺延记录（版本化）
+// ==========================================
+// 说明: 重算结束时材料仍未排入窗口，记录其最后一次被批量规则顺延的日期与原因
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchingDeferral {
+    pub version_id: String,
+    pub material_id: String,
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub grade_family: String,
+    pub reason: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(min_run_t: Option<f64>, min_run_coils: Option<u32>) -> GradeBatchingRule {
+        GradeBatchingRule {
+            family: "PICKLED".to_string(),
+            steel_mark_prefixes: vec!["SPHC".to_string(), "sapH".to_string()],
+            min_run_t,
+            min_run_coils,
+            machine_codes: vec!["H032".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let r = rule(Some(500.0), None);
+        assert!(r.matches_steel_mark(Some("SPHC-P")));
+        assert!(r.matches_steel_mark(Some("saph440")));
+        assert!(!r.matches_steel_mark(Some("Q235")));
+        assert!(!r.matches_steel_mark(None));
+        assert!(r.applies_to_machine("h032"));
+        assert!(!r.applies_to_machine("H033"));
+
+        let rules = vec![r.clone()];
+        assert!(match_batching_rule(&rules, "H032", Some("SPHC")).is_some());
+        assert!(match_batching_rule(&rules, "H033", Some("SPHC")).is_none());
+    }
+
+    #[test]
+    fn test_rule_validity_and_satisfaction() {
+        assert!(rule(Some(500.0), None).is_valid());
+        assert!(rule(None, Some(3)).is_valid());
+        assert!(!rule(None, None).is_valid());
+        assert!(!rule(Some(0.0), Some(0)).is_valid());
+
+        let r = rule(Some(500.0), Some(3));
+        let mut run = BatchRun::default();
+        run.add(300.0);
+        run.add(250.0);
+        assert!(!r.is_satisfied(&run), "块数未达标");
+        run.add(10.0);
+        assert!(r.is_satisfied(&run));
+
+        let reason = r.deferral_reason(&BatchRun::default(), false);
+        assert!(reason.starts_with(GRADE_BATCHING_REASON));
+        assert!(reason.contains("family=PICKLED"));
+        assert!(reason.contains("cause=INSUFFICIENT_MATERIAL"));
+    }
+}
@@ -5,7 +5,7 @@
 // 依据: Engine_Specs_v0.3_Integrated.md - capacity_pool
 // ==========================================
 
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
 // ==========================================
@@ -103,3 +103,148 @@ impl CapacityConstraint for CapacityPool {
 }
 
 // TODO: 实现数据库映射 (sqlx derive)
+
+// ==========================================
+// CapacityReservation - 产能预留
+// ==========================================
+// 用途: 为战略合同/重点客户在指定机组、日期区间内预留每日吨位
+// 规则:
+// - 预留期内（距排产日期超过 release_days_before 天）仅匹配材料可使用预留吨位，
+//   一般需求只能使用 limit_capacity_t 扣除未用预留后的部分
+// - 到达释放点后未使用的预留吨位释放给一般需求
+// - 基准日晚于 expires_on 后预留整体失效（不再保留也不统计）
+// 匹配: contract_no 精确匹配；contract_prefix 按合同号前缀匹配（客户合同号段）；两者同时设置时需同时满足
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapacityReservation {
+    pub reservation_id: String,
+    pub machine_code: String,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub daily_reserved_t: f64,           // 每日预留吨位
+    pub contract_no: Option<String>,     // 合同号（精确匹配）
+    pub contract_prefix: Option<String>, // 合同号前缀（按客户预留）
+    pub release_days_before: i32,        // 释放点：排产日期前 N 天释放未用预留
+    pub expires_on: Option<NaiveDate>,   // 预留失效日期
+    pub reason: Option<String>,
+    pub created_by: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl CapacityReservation {
+    /// 合同号是否匹配预留筛选条件
+    pub fn matches_contract(&self, contract_no: Option<&str>) -> bool {
+        let Some(contract_no) = contract_no.map(str::trim).filter(|c| !c.is_empty()) else {
+            return false;
+        };
+        if self.contract_no.is_none() && self.contract_prefix.is_none() {
+            return false;
+        }
+        self.contract_no
+            .as_deref()
+            .is_none_or(|c| c.eq_ignore_ascii_case(contract_no))
+            && self.contract_prefix.as_deref().is_none_or(|p| {
+                contract_no
+                    .get(..p.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(p))
+            })
+    }
+
+    /// 是否覆盖排产日期
+    pub fn covers(&self, plan_date: NaiveDate) -> bool {
+        self.date_from <= plan_date && plan_date <= self.date_to
+    }
+
+    /// 以 base_date 为基准是否已失效
+    pub fn is_expired(&self, base_date: NaiveDate) -> bool {
+        self.expires_on.is_some_and(|d| base_date > d)
+    }
+
+    /// 以 base_date 为基准，plan_date 当日的预留是否仍在保留期（未到释放点）
+    pub fn is_held(&self, plan_date: NaiveDate, base_date: NaiveDate) -> bool {
+        (plan_date - base_date).num_days() > self.release_days_before as i64
+    }
+}
+
+// ==========================================
+// ReservationUsage - 预留使用快照（版本 × 预留 × 日期）
+// ==========================================
+// 用途: 重算时落库，按版本汇报预留利用率
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReservationUsage {
+    pub version_id: String,
+    pub reservation_id: String,
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub reserved_t: f64,
+    pub used_t: f64,     // 匹配材料占用的预留吨位
+    pub released_t: f64, // 到达释放点后释放给一般需求的未用吨位
+    pub held: bool,      // 重算时是否仍在保留期
+}
+
+impl ReservationUsage {
+    /// 保留期内未使用的吨位（产能被空置）
+    pub fn held_unused_t(&self) -> f64 {
+        if self.held {
+            (self.reserved_t - self.used_t).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 5, day).unwrap()
+    }
+
+    fn reservation(
+        contract_no: Option<&str>,
+        contract_prefix: Option<&str>,
+    ) -> CapacityReservation {
+        CapacityReservation {
+            reservation_id: "R1".to_string(),
+            machine_code: "H032".to_string(),
+            date_from: d(10),
+            date_to: d(20),
+            daily_reserved_t: 100.0,
+            contract_no: contract_no.map(str::to_string),
+            contract_prefix: contract_prefix.map(str::to_string),
+            release_days_before: 2,
+            expires_on: Some(d(18)),
+            reason: None,
+            created_by: "sales".to_string(),
+            created_at: d(1).and_hms_opt(8, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_reservation_contract_matching() {
+        let by_contract = reservation(Some("EX2026001"), None);
+        assert!(by_contract.matches_contract(Some("ex2026001")));
+        assert!(!by_contract.matches_contract(Some("EX2026002")));
+        assert!(!by_contract.matches_contract(None));
+
+        let by_customer = reservation(None, Some("EX"));
+        assert!(by_customer.matches_contract(Some("EX2026002")));
+        assert!(!by_customer.matches_contract(Some("DM2026002")));
+        assert!(!by_customer.matches_contract(Some("E")));
+
+        let both = reservation(Some("EX2026001"), Some("DM"));
+        assert!(!both.matches_contract(Some("EX2026001")));
+        assert!(!reservation(None, None).matches_contract(Some("EX2026001")));
+    }
+
+    #[test]
+    fn test_reservation_release_point_and_expiry() {
+        let r = reservation(Some("EX2026001"), None);
+        assert!(r.covers(d(10)) && r.covers(d(20)) && !r.covers(d(21)));
+        // 释放点：排产日期前 2 天
+        assert!(r.is_held(d(13), d(10)));
+        assert!(!r.is_held(d(12), d(10)));
+        assert!(!r.is_expired(d(18)));
+        assert!(r.is_expired(d(19)));
+    }
+}
@@ -0,0 +1,176 @@
+// ==========================================
+// 热轧精整排产系统 - 人工干预时效领域模型
+// ==========================================
+// 职责: 为锁定 / 强制放行 / 人工紧急 / 优先级提升 等人工干预设置失效条件
+// 失效口径:
+// - 到期时间: expires_at 之后失效
+// - 重算次数: 设置之后完成 N 次重算（RECALC_FULL / RECALC_PARTIAL）后失效
+// - 两者同时设置时，先满足者生效
+// 说明:
+// - 优先级提升 = 路径突破拒绝带来的紧急等级 +1（重排时进入 priority_boost_material_ids），
+//   失效时清除拒绝标记
+// - 设置时效后若有人再次手工调整同一标志，时效作废（以最新的人工决策为准）
+// ==========================================
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// 计入“重算次数”口径的操作类型
+pub const RECALC_ACTION_TYPES: &[&str] = &["RECALC_FULL", "RECALC_PARTIAL"];
+
+// ==========================================
+// InterventionType - 人工干预类型
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InterventionType {
+    Lock,          // 锁定（lock_flag）
+    ForceRelease,  // 强制放行（force_release_flag）
+    ManualUrgent,  // 人工紧急（manual_urgent_flag）
+    PriorityBoost, // 优先级提升（路径突破拒绝 → 紧急等级 +1）
+}
+
+impl InterventionType {
+    pub const ALL: [InterventionType; 4] = [
+        InterventionType::Lock,
+        InterventionType::ForceRelease,
+        InterventionType::ManualUrgent,
+        InterventionType::PriorityBoost,
+    ];
+
+    /// 转换为数据库存储的字符串
+    pub fn to_db_str(&self) -> &'static str {
+        match self {
+            InterventionType::Lock => "LOCK",
+            InterventionType::ForceRelease => "FORCE_RELEASE",
+            InterventionType::ManualUrgent => "MANUAL_URGENT",
+            InterventionType::PriorityBoost => "PRIORITY_BOOST",
+        }
+    }
+
+    /// 从字符串解析 (未知值返回 None)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "LOCK" => Some(InterventionType::Lock),
+            "FORCE_RELEASE" => Some(InterventionType::ForceRelease),
+            "MANUAL_URGENT" | "URGENT" => Some(InterventionType::ManualUrgent),
+            "PRIORITY_BOOST" => Some(InterventionType::PriorityBoost),
+            _ => None,
+        }
+    }
+
+    /// 手工调整该标志的操作类型（设置时效之后出现即视为时效作废）
+    pub fn manual_action_types(&self) -> &'static [&'static str] {
+        match self {
+            InterventionType::Lock => &["LOCK_MATERIALS", "UNLOCK_MATERIALS"],
+            InterventionType::ForceRelease => &["FORCE_RELEASE", "CLEAR_FORCE_RELEASE"],
+            InterventionType::ManualUrgent => &["SET_URGENT"],
+            InterventionType::PriorityBoost => &["PathOverrideConfirm", "PathOverrideReject"],
+        }
+    }
+
+    /// 撤销/重做该标志时的逆操作载荷类型（见 InversePayload::kind）
+    pub fn undo_payload_kind(&self) -> &'static str {
+        match self {
+            InterventionType::PriorityBoost => "PATH_OVERRIDE",
+            _ => "MATERIAL_FLAGS",
+        }
+    }
+}
+
+impl std::fmt::Display for InterventionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.to_db_str())
+    }
+}
+
+// ==========================================
+// InterventionExpiry - 人工干预时效
+// ==========================================
+// 对齐: material_intervention_expiry 表（material_id + intervention_type 唯一）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterventionExpiry {
+    pub material_id: String,
+    pub intervention_type: InterventionType,
+    pub expires_at: Option<NaiveDateTime>, // 到期时间（本地时间）
+    pub expire_after_recalcs: Option<i32>, // 重算 N 次后失效
+    pub operator: String,                  // 设置时效的操作人
+    pub reason: Option<String>,            // 设置说明
+    pub anchor_action_id: String,          // 设置时效写入的操作日志（重算计数/作废判定起点）
+    pub created_at: NaiveDateTime,
+}
+
+impl InterventionExpiry {
+    /// 剩余重算次数（未设置重算口径时为 None）
+    pub fn recalcs_remaining(&self, recalcs_since: i64) -> Option<i64> {
+        self.expire_after_recalcs
+            .map(|n| (n as i64 - recalcs_since).max(0))
+    }
+
+    /// 是否已失效
+    ///
+    /// # 参数
+    /// - now: 当前本地时间
+    /// - recalcs_since: 设置之后完成的重算次数
+    pub fn is_expired(&self, now: NaiveDateTime, recalcs_since: i64) -> bool {
+        self.expires_at.is_some_and(|t| t <= now)
+            || self.recalcs_remaining(recalcs_since) == Some(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn expiry(expires_at: Option<NaiveDateTime>, after_recalcs: Option<i32>) -> InterventionExpiry {
+        InterventionExpiry {
+            material_id: "M1".to_string(),
+            intervention_type: InterventionType::Lock,
+            expires_at,
+            expire_after_recalcs: after_recalcs,
+            operator: "planner".to_string(),
+            reason: None,
+            anchor_action_id: "a1".to_string(),
+            created_at: at(8),
+        }
+    }
+
+    #[test]
+    fn test_expiry_by_time_or_recalcs_whichever_first() {
+        let by_time = expiry(Some(at(12)), None);
+        assert!(!by_time.is_expired(at(11), 99));
+        assert!(by_time.is_expired(at(12), 0));
+        assert_eq!(by_time.recalcs_remaining(5), None);
+
+        let by_recalcs = expiry(None, Some(2));
+        assert!(!by_recalcs.is_expired(at(23), 1));
+        assert_eq!(by_recalcs.recalcs_remaining(1), Some(1));
+        assert!(by_recalcs.is_expired(at(9), 3));
+        assert_eq!(by_recalcs.recalcs_remaining(3), Some(0));
+
+        let both = expiry(Some(at(12)), Some(2));
+        assert!(both.is_expired(at(9), 2));
+        assert!(both.is_expired(at(13), 0));
+        assert!(!both.is_expired(at(9), 1));
+    }
+
+    #[test]
+    fn test_intervention_type_roundtrip() {
+        for t in InterventionType::ALL {
+            assert_eq!(InterventionType::parse(t.to_db_str()), Some(t));
+        }
+        assert_eq!(
+            InterventionType::parse("urgent"),
+            Some(InterventionType::ManualUrgent)
+        );
+        assert_eq!(InterventionType::parse("unknown"), None);
+    }
+}
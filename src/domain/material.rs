@@ -144,6 +144,49 @@ pub struct RawMaterialRecord {
     pub row_number: usize, // 原始文件行号（用于 DQ 报告）
 }
 
+// ==========================================
+// RawContractRecord - 合同导入中间结构体
+// ==========================================
+// 用途: 合同/订单文件导入的中间产物（按 contract_no 更新材料影子字段）
+// 红线: 合同字段仍写入 material_master 影子列，不独立建表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawContractRecord {
+    pub contract_no: Option<String>,          // 合同号（关联键）
+    pub contract_nature: Option<String>,      // 合同性质代码
+    pub weekly_delivery_flag: Option<String>, // 按周交货标志
+    pub export_flag: Option<String>,          // 出口标记（'1'/'0'）
+    pub due_date: Option<NaiveDate>,          // 合同交货期
+
+    // 元信息
+    pub row_number: usize, // 原始文件行号（用于 DQ 报告）
+}
+
+impl RawContractRecord {
+    /// 是否包含至少一个可更新的合同属性
+    pub fn has_attributes(&self) -> bool {
+        self.contract_nature.is_some()
+            || self.weekly_delivery_flag.is_some()
+            || self.export_flag.is_some()
+            || self.due_date.is_some()
+    }
+
+    /// 将合同属性写入材料影子字段（文件中为空的属性保留原值）
+    pub fn apply_to(&self, material: &mut MaterialMaster) {
+        if let Some(v) = &self.contract_nature {
+            material.contract_nature = Some(v.clone());
+        }
+        if let Some(v) = &self.weekly_delivery_flag {
+            material.weekly_delivery_flag = Some(v.clone());
+        }
+        if let Some(v) = &self.export_flag {
+            material.export_flag = Some(v.clone());
+        }
+        if let Some(v) = self.due_date {
+            material.due_date = Some(v);
+        }
+    }
+}
+
 // ==========================================
 // ImportBatch - 导入批次
 // ==========================================
@@ -249,6 +292,42 @@ pub struct ImportResult {
     pub summary: DqSummary,                // 汇总统计
     pub violations: Vec<DqViolation>,      // 违规明细
     pub elapsed_time: std::time::Duration, // 导入耗时
+    #[serde(default)]
+    pub source_info: ImportSourceInfo, // 实际读取的源信息（编码/工作表/表头行）
+    #[serde(default)]
+    pub cancelled: bool, // 是否被取消（仅包含已提交分块）
+}
+
+// ==========================================
+// ContractImportResult - 合同导入结果
+// ==========================================
+// 用途: 合同/订单导入接口返回值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractImportResult {
+    pub batch: ImportBatch,                // 批次信息
+    pub summary: DqSummary,                // 汇总统计（按合同行）
+    pub violations: Vec<DqViolation>,      // 违规明细
+    pub matched_contracts: usize,          // 关联到材料的合同数
+    pub unmatched_contracts: usize,        // 未关联任何材料的合同数
+    pub updated_materials: usize,          // 更新影子字段并重算等级的材料数
+    pub elapsed_time: std::time::Duration, // 导入耗时
+    #[serde(default)]
+    pub source_info: ImportSourceInfo, // 实际读取的源信息
+}
+
+// ==========================================
+// ImportSourceInfo - 源文件读取信息
+// ==========================================
+// 用途: 告知操作员实际读取了什么（编码探测结果、工作表、表头行）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportSourceInfo {
+    pub file_format: String,           // 文件格式（CSV/XLSX）
+    pub encoding: Option<String>,      // CSV 实际使用的编码（UTF-8/GB18030）
+    pub encoding_detected: bool,       // 编码是否为自动探测（false 表示调用方指定）
+    pub sheet_name: Option<String>,    // Excel 实际读取的工作表
+    pub available_sheets: Vec<String>, // Excel 全部工作表
+    pub header_row: Option<usize>,     // 表头所在行（1-based）
+    pub header_auto_detected: bool,    // 表头行是否通过映射配置自动识别
 }
 
 // ==========================================
@@ -9,27 +9,57 @@
 // ==========================================
 
 pub mod action_log;
+pub mod batching;
 pub mod capacity;
+pub mod intervention;
 pub mod material;
 pub mod plan;
+pub mod production;
+pub mod recalc_input;
+pub mod red_line;
 pub mod risk;
 pub mod roller;
+pub mod scenario;
 pub mod types;
+pub mod undo;
+pub mod version_lineage;
+pub mod version_review;
 
 // 重导出核心类型
 pub use action_log::{
     ActionLog, ActionType, CapacityChange, ImpactSummary, MaterialChange, RiskChange,
 };
-pub use capacity::{CapacityConstraint, CapacityPool};
+pub use batching::{BatchRun, BatchingDeferral, GradeBatchingRule};
+pub use capacity::{CapacityConstraint, CapacityPool, CapacityReservation, ReservationUsage};
+pub use intervention::{InterventionExpiry, InterventionType};
 pub use material::{
     ConflictType, DqLevel, DqReport, DqSummary, DqViolation, ImportBatch, ImportConflict,
-    ImportResult, MaterialEligibility, MaterialMaster, MaterialState, MaterialUrgency,
-    RawMaterialRecord,
+    ImportResult, ImportSourceInfo, MaterialEligibility, MaterialMaster, MaterialState,
+    MaterialUrgency, RawMaterialRecord,
 };
 pub use plan::{Plan, PlanItem, PlanVersion, PlanVersionManagement};
+pub use production::{CarryOverItem, PlanAdherenceRecord, ProductionActual};
+pub use recalc_input::{
+    PathOverrideRejectionInput, RecalcInputFingerprint, RecalcInputSnapshot, RecalcReplayDiff,
+    RecalcReplayReport, RecalcRunParams, ReplayInputSource,
+};
+pub use red_line::{RedLineReport, RedLineSeverity, RedLineViolation};
 pub use risk::{RiskAssessment, RiskSnapshot};
 pub use roller::{RollerCampaign, RollerCampaignMonitor};
+pub use scenario::{
+    CapacityOverride, HypotheticalMaterial, MachineBreakdown, MachineOutage, ScenarioOverlay,
+    ScenarioRun,
+};
 pub use types::{RiskLevel, RollStatus, RushLevel, SchedState, Season, SeasonMode, UrgentLevel};
+pub use undo::{
+    HistoryKind, HistoryTag, InversePayload, ItemPosition, MaterialFlagSnapshot,
+    PathOverrideSnapshot, UndoStacks,
+};
+pub use version_lineage::{
+    VersionLineageNode, VersionLineageTree, VersionPurgeReport, VersionRetentionPolicy,
+    VersionTrigger,
+};
+pub use version_review::{ActivationChecklist, ChecklistItem, VersionReview, VersionReviewAction};
 
 // TODO: 添加领域服务模块 (domain services)
 // TODO: 添加值对象模块 (value objects)
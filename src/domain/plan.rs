@@ -56,6 +56,14 @@ impl PlanVersion {
     pub fn is_archived(&self) -> bool {
         self.status == PlanVersionStatus::Archived
     }
+
+    /// 判断是否可编辑 (已提交/已审批的版本需驳回后才能修改)
+    pub fn is_editable(&self) -> bool {
+        matches!(
+            self.status,
+            PlanVersionStatus::Draft | PlanVersionStatus::Rejected | PlanVersionStatus::Active
+        )
+    }
 }
 
 // ==========================================
@@ -79,12 +87,12 @@ pub struct PlanItem {
     pub locked_in_plan: bool,            // 计划中锁定 (对齐schema)
     pub force_release_in_plan: bool,     // 计划中强制放行 (对齐schema)
     pub violation_flags: Option<String>, // 违规标志 (JSON字符串, 对齐schema)
+    pub assign_reason: Option<String>,   // 落位原因 (可解释性红线，旧明细可能为空)
 
     // ===== 快照字段 (业务逻辑需要，但不存储在schema中) =====
     // 注: 这些字段由 API 层从 material_state / material_master 动态补充
     pub urgent_level: Option<String>, // 紧急等级快照 (可选，用于可解释性)
     pub sched_state: Option<String>,  // 状态快照 (可选，用于可解释性)
-    pub assign_reason: Option<String>, // 落位原因 (可选，用于可解释性)
     pub steel_grade: Option<String>,  // 钢种/出钢记号 (来自 material_master.steel_mark)
     pub width_mm: Option<f64>,        // 宽度快照 (来自 material_master.width_mm)
     pub thickness_mm: Option<f64>,    // 厚度快照 (来自 material_master.thickness_mm)
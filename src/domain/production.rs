@@ -0,0 +1,80 @@
+// ==========================================
+// 热轧精整排产系统 - 生产实绩领域模型
+// ==========================================
+// 职责: 生产实绩回填 + 计划/实绩对账结果
+// 说明:
+// - 实绩为事实数据(MES/产线报工)，不随版本变化
+// - 对账结果按 版本×机组×日期 落库，用于计划执行率趋势分析
+// ==========================================
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+// ==========================================
+// ProductionActual - 生产实绩
+// ==========================================
+// 对齐: production_actual 表
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductionActual {
+    pub material_id: String,         // 材料号
+    pub machine_code: String,        // 实际加工机组
+    pub completed_at: NaiveDateTime, // 完工时间
+    pub actual_weight_t: f64,        // 实际重量 (吨)
+}
+
+impl ProductionActual {
+    /// 完工日期 (对账按自然日归属)
+    pub fn completed_date(&self) -> NaiveDate {
+        self.completed_at.date()
+    }
+}
+
+// ==========================================
+// PlanAdherenceRecord - 计划执行对账 (机组×日)
+// ==========================================
+// 对齐: plan_adherence_daily 表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanAdherenceRecord {
+    // ===== 主键 =====
+    pub version_id: String,
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+
+    // ===== 计划 vs 实绩 =====
+    pub planned_count: i32,    // 计划块数
+    pub planned_weight_t: f64, // 计划吨位
+    pub actual_count: i32,     // 实绩块数 (当日该机组全部完工)
+    pub actual_weight_t: f64,  // 实绩吨位
+    pub matched_count: i32,    // 按计划完工块数
+    pub matched_weight_t: f64, // 按计划完工的计划吨位
+    pub unplanned_count: i32,  // 计划外完工块数
+    pub carry_over_count: i32, // 未完工需顺延块数
+
+    // ===== 顺序偏差 =====
+    pub sequence_deviation_count: i32, // 实际顺序与计划不一致的块数
+    pub max_sequence_displacement: i32, // 最大位次偏移
+    pub avg_sequence_displacement: f64, // 平均位次偏移 (按计划完工块)
+
+    // ===== 执行率 =====
+    pub adherence_rate: Option<f64>, // matched_weight_t / planned_weight_t (无计划时为 None)
+
+    // ===== 追溯 =====
+    pub batch_id: Option<String>,    // 触发对账的实绩导入批次
+    pub generated_at: NaiveDateTime, // 对账时间
+}
+
+// ==========================================
+// CarryOverItem - 计划未完工顺延项
+// ==========================================
+// 对齐: plan_carry_over 表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarryOverItem {
+    pub version_id: String,
+    pub material_id: String,
+    pub machine_code: String,
+    pub plan_date: NaiveDate, // 原计划日期
+    pub seq_no: i32,          // 原计划序号
+    pub weight_t: f64,
+    pub detected_at: NaiveDateTime,
+    pub resolved_at: Option<NaiveDateTime>, // 后续完工时回填
+}
@@ -0,0 +1,419 @@
+// ==========================================
+// 热轧精整排产系统 - 重算输入快照与确定性回放
+// ==========================================
+// 职责:
+// - 重算输入指纹: 材料快照 / 产能池 / 策略参数 / 全局配置 / 冻结区 / 引擎版本 各自的哈希
+// - 可选的全量输入快照（用于在沙盘中回放历史重算）
+// - 回放结果与已存明细的差异
+// 说明:
+// - 指纹写入 config_snapshot_json 的 __meta_input_fingerprint 键（不改 plan_version 表结构）
+// - 哈希口径: 规范化 JSON（对象键排序）+ FNV-1a 64
+// - 时间戳 / 版本归属 / 上次计算版本 等与计算结果无关的字段不参与哈希
+// ==========================================
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::capacity::CapacityPool;
+use crate::domain::material::{MaterialMaster, MaterialState};
+use crate::domain::plan::PlanItem;
+
+/// 重算输入指纹（__meta_* 键，值为 RecalcInputFingerprint JSON）
+pub const META_INPUT_FINGERPRINT: &str = "__meta_input_fingerprint";
+
+/// 引擎版本（随程序版本变化）
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 不参与哈希的字段
+const VOLATILE_FIELDS: [&str; 5] = [
+    "version_id",
+    "created_at",
+    "updated_at",
+    "updated_by",
+    "last_calc_version_id",
+];
+
+// ==========================================
+// RecalcRunParams - 重算执行参数（随指纹记录，回放据此重建执行窗口与策略）
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecalcRunParams {
+    pub engine_version: String,
+    pub plan_id: String,
+    pub base_version_id: Option<String>, // 复制冻结区的来源版本
+    pub base_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub window_days: i32,
+    pub frozen_from_date: NaiveDate,
+    pub machine_codes: Vec<String>,
+    pub strategy_key: String,
+    pub base_strategy: String, // 预设策略 (ScheduleStrategy::as_str)
+    pub strategy_parameters: Option<JsonValue>, // 自定义策略参数
+}
+
+// ==========================================
+// RecalcInputSnapshot - 重算全量输入
+// ==========================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecalcInputSnapshot {
+    pub run: RecalcRunParams,
+    pub config: BTreeMap<String, String>, // 全局配置 (不含 __meta_*)
+    pub materials: Vec<MaterialMaster>,   // 窗口机组上的材料主数据
+    pub states: Vec<MaterialState>,       // 对应材料状态
+    #[serde(default)]
+    pub path_override_rejections: Vec<PathOverrideRejectionInput>, // 路径突破人工拒绝标记
+    pub capacity_pools: Vec<CapacityPool>, // 新版本已有的产能池（未配置的日期按默认产能）
+    pub frozen_items: Vec<PlanItem>,      // 复制到新版本的冻结区明细
+}
+
+/// 路径突破人工拒绝标记（material_state 扩展列，不在 MaterialState 中）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathOverrideRejectionInput {
+    pub material_id: String,
+    pub reject_cycle_no: Option<i32>,
+    pub reject_base_sched_state: Option<String>,
+}
+
+impl RecalcInputSnapshot {
+    /// 计算输入指纹
+    pub fn fingerprint(&self) -> RecalcInputFingerprint {
+        let mut materials: Vec<&MaterialMaster> = self.materials.iter().collect();
+        materials.sort_by(|a, b| a.material_id.cmp(&b.material_id));
+        let mut states: Vec<&MaterialState> = self.states.iter().collect();
+        states.sort_by(|a, b| a.material_id.cmp(&b.material_id));
+        let mut pools: Vec<&CapacityPool> = self.capacity_pools.iter().collect();
+        pools.sort_by(|a, b| (&a.machine_code, a.plan_date).cmp(&(&b.machine_code, b.plan_date)));
+        let mut frozen: Vec<&PlanItem> = self.frozen_items.iter().collect();
+        frozen.sort_by(|a, b| a.material_id.cmp(&b.material_id));
+
+        let mut rejections: Vec<&PathOverrideRejectionInput> =
+            self.path_override_rejections.iter().collect();
+        rejections.sort_by(|a, b| a.material_id.cmp(&b.material_id));
+
+        let material_hash = canonical_hash(&serde_json::json!([materials, states, rejections]));
+        let capacity_hash = canonical_hash(&serde_json::json!(pools));
+        // 策略口径包含执行窗口：同一策略在不同窗口下的输入不同
+        let run = &self.run;
+        let strategy_hash = canonical_hash(&serde_json::json!({
+            "strategy_key": run.strategy_key,
+            "base_strategy": run.base_strategy,
+            "parameters": run.strategy_parameters,
+            "base_date": run.base_date,
+            "end_date": run.end_date,
+            "frozen_from_date": run.frozen_from_date,
+            "machine_codes": run.machine_codes,
+        }));
+        let config_hash = canonical_hash(&serde_json::json!(self.config));
+        let frozen_hash = canonical_hash(&serde_json::json!(frozen));
+        let combined_hash = fnv1a64_hex(
+            [
+                run.engine_version.as_str(),
+                &material_hash,
+                &capacity_hash,
+                &strategy_hash,
+                &config_hash,
+                &frozen_hash,
+            ]
+            .join("|")
+            .as_bytes(),
+        );
+
+        RecalcInputFingerprint {
+            run: run.clone(),
+            material_hash,
+            capacity_hash,
+            strategy_hash,
+            config_hash,
+            frozen_hash,
+            combined_hash,
+            material_count: self.materials.len(),
+            capacity_pool_count: self.capacity_pools.len(),
+            frozen_item_count: self.frozen_items.len(),
+            inputs_persisted: false,
+        }
+    }
+}
+
+// ==========================================
+// RecalcInputFingerprint - 重算输入指纹
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecalcInputFingerprint {
+    pub run: RecalcRunParams,
+    pub material_hash: String,
+    pub capacity_hash: String,
+    pub strategy_hash: String,
+    pub config_hash: String,
+    pub frozen_hash: String,
+    pub combined_hash: String,
+    pub material_count: usize,
+    pub capacity_pool_count: usize,
+    pub frozen_item_count: usize,
+    #[serde(default)]
+    pub inputs_persisted: bool, // 是否保存了全量输入快照
+}
+
+impl RecalcInputFingerprint {
+    /// 从 config_snapshot_json 解析输入指纹（无指纹/格式错误返回 None）
+    pub fn from_snapshot(snapshot_json: Option<&str>) -> Option<Self> {
+        let map: HashMap<String, String> = serde_json::from_str(snapshot_json?).ok()?;
+        serde_json::from_str(map.get(META_INPUT_FINGERPRINT)?).ok()
+    }
+
+    /// 与另一指纹不一致的组成部分
+    pub fn mismatched_components(&self, other: &RecalcInputFingerprint) -> Vec<String> {
+        [
+            (
+                "engine_version",
+                self.run.engine_version == other.run.engine_version,
+            ),
+            ("materials", self.material_hash == other.material_hash),
+            ("capacity_pools", self.capacity_hash == other.capacity_hash),
+            ("strategy", self.strategy_hash == other.strategy_hash),
+            ("config", self.config_hash == other.config_hash),
+            ("frozen_items", self.frozen_hash == other.frozen_hash),
+        ]
+        .into_iter()
+        .filter(|(_, same)| !same)
+        .map(|(name, _)| name.to_string())
+        .collect()
+    }
+}
+
+/// 规范化 JSON 的哈希（对象键排序，剔除易变字段）
+fn canonical_hash(value: &JsonValue) -> String {
+    let mut buf = String::new();
+    write_canonical(value, &mut buf);
+    fnv1a64_hex(buf.as_bytes())
+}
+
+fn write_canonical(value: &JsonValue, buf: &mut String) {
+    match value {
+        JsonValue::Object(map) => {
+            let mut keys: Vec<&String> = map
+                .keys()
+                .filter(|k| !VOLATILE_FIELDS.contains(&k.as_str()))
+                .collect();
+            keys.sort();
+            buf.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    buf.push(',');
+                }
+                buf.push_str(&JsonValue::String(key.clone()).to_string());
+                buf.push(':');
+                write_canonical(&map[key], buf);
+            }
+            buf.push('}');
+        }
+        JsonValue::Array(items) => {
+            buf.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    buf.push(',');
+                }
+                write_canonical(item, buf);
+            }
+            buf.push(']');
+        }
+        other => buf.push_str(&other.to_string()),
+    }
+}
+
+/// FNV-1a 64 位哈希（十六进制）
+fn fnv1a64_hex(bytes: &[u8]) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let hash = bytes
+        .iter()
+        .fold(OFFSET, |h, b| (h ^ *b as u64).wrapping_mul(PRIME));
+    format!("{:016x}", hash)
+}
+
+// ==========================================
+// 回放结果
+// ==========================================
+
+/// 回放输入来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReplayInputSource {
+    Persisted, // 重算时保存的全量输入
+    Current,   // 未保存全量输入，使用当前数据（指纹不一致时结果仅供参考）
+}
+
+/// 回放明细差异（stored = 已存明细，replayed = 回放结果）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecalcReplayDiff {
+    pub material_id: String,
+    pub change_type: String, // ADDED / REMOVED / MOVED / SEQ_CHANGED
+    pub stored_machine_code: Option<String>,
+    pub stored_plan_date: Option<NaiveDate>,
+    pub stored_seq_no: Option<i32>,
+    pub replayed_machine_code: Option<String>,
+    pub replayed_plan_date: Option<NaiveDate>,
+    pub replayed_seq_no: Option<i32>,
+}
+
+/// 回放报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecalcReplayReport {
+    pub version_id: String,
+    pub input_source: ReplayInputSource,
+    pub recorded_fingerprint: RecalcInputFingerprint,
+    pub replay_fingerprint: RecalcInputFingerprint,
+    pub input_mismatches: Vec<String>, // 指纹不一致的组成部分
+    pub stored_count: usize,
+    pub replayed_count: usize,
+    pub identical: bool,
+    pub added_count: usize,
+    pub removed_count: usize,
+    pub moved_count: usize,
+    pub seq_changed_count: usize,
+    pub diffs: Vec<RecalcReplayDiff>,
+    pub diffs_truncated: bool,
+}
+
+/// 对比已存明细与回放明细（按材料；结果按 material_id 排序）
+pub fn diff_replay_items(stored: &[PlanItem], replayed: &[PlanItem]) -> Vec<RecalcReplayDiff> {
+    let stored_map: BTreeMap<&str, &PlanItem> =
+        stored.iter().map(|i| (i.material_id.as_str(), i)).collect();
+    let replayed_map: BTreeMap<&str, &PlanItem> = replayed
+        .iter()
+        .map(|i| (i.material_id.as_str(), i))
+        .collect();
+
+    let mut ids: Vec<&str> = stored_map
+        .keys()
+        .chain(replayed_map.keys())
+        .copied()
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    ids.into_iter()
+        .filter_map(|id| {
+            let s = stored_map.get(id).copied();
+            let r = replayed_map.get(id).copied();
+            let change_type = match (s, r) {
+                (Some(_), None) => "REMOVED",
+                (None, Some(_)) => "ADDED",
+                (Some(s), Some(r))
+                    if (&s.machine_code, s.plan_date) != (&r.machine_code, r.plan_date) =>
+                {
+                    "MOVED"
+                }
+                (Some(s), Some(r)) if s.seq_no != r.seq_no => "SEQ_CHANGED",
+                _ => return None,
+            };
+            Some(RecalcReplayDiff {
+                material_id: id.to_string(),
+                change_type: change_type.to_string(),
+                stored_machine_code: s.map(|i| i.machine_code.clone()),
+                stored_plan_date: s.map(|i| i.plan_date),
+                stored_seq_no: s.map(|i| i.seq_no),
+                replayed_machine_code: r.map(|i| i.machine_code.clone()),
+                replayed_plan_date: r.map(|i| i.plan_date),
+                replayed_seq_no: r.map(|i| i.seq_no),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(material_id: &str, machine: &str, day: u32, seq_no: i32) -> PlanItem {
+        PlanItem {
+            version_id: "V1".to_string(),
+            material_id: material_id.to_string(),
+            machine_code: machine.to_string(),
+            plan_date: NaiveDate::from_ymd_opt(2030, 1, day).unwrap(),
+            seq_no,
+            weight_t: 10.0,
+            source_type: "CALC".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            assign_reason: None,
+            urgent_level: None,
+            sched_state: None,
+            steel_grade: None,
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: None,
+            due_date: None,
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+
+    fn snapshot(frozen_items: Vec<PlanItem>) -> RecalcInputSnapshot {
+        let day = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        RecalcInputSnapshot {
+            run: RecalcRunParams {
+                engine_version: ENGINE_VERSION.to_string(),
+                plan_id: "P1".to_string(),
+                base_version_id: None,
+                base_date: day,
+                end_date: day,
+                window_days: 0,
+                frozen_from_date: day,
+                machine_codes: vec!["H032".to_string()],
+                strategy_key: "balanced".to_string(),
+                base_strategy: "balanced".to_string(),
+                strategy_parameters: None,
+            },
+            config: BTreeMap::from([("a".to_string(), "1".to_string())]),
+            materials: Vec::new(),
+            states: Vec::new(),
+            path_override_rejections: Vec::new(),
+            capacity_pools: Vec::new(),
+            frozen_items,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_version_and_order() {
+        let mut moved = item("M2", "H032", 1, 2);
+        moved.version_id = "V2".to_string();
+        let a = snapshot(vec![item("M1", "H032", 1, 1), item("M2", "H032", 1, 2)]);
+        let b = snapshot(vec![moved, item("M1", "H032", 1, 1)]);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        let mut c = snapshot(a.frozen_items.clone());
+        c.config.insert("a".to_string(), "2".to_string());
+        assert_eq!(
+            a.fingerprint().mismatched_components(&c.fingerprint()),
+            vec!["config".to_string()]
+        );
+        assert_ne!(a.fingerprint().combined_hash, c.fingerprint().combined_hash);
+    }
+
+    #[test]
+    fn test_diff_replay_items() {
+        let stored = vec![
+            item("M1", "H032", 1, 1),
+            item("M2", "H032", 1, 2),
+            item("M3", "H032", 1, 3),
+        ];
+        let replayed = vec![
+            item("M1", "H032", 1, 1),
+            item("M2", "H032", 1, 3),
+            item("M4", "H033", 2, 1),
+        ];
+        let diffs = diff_replay_items(&stored, &replayed);
+        let kinds: Vec<(&str, &str)> = diffs
+            .iter()
+            .map(|d| (d.material_id.as_str(), d.change_type.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![("M2", "SEQ_CHANGED"), ("M3", "REMOVED"), ("M4", "ADDED")]
+        );
+        assert!(diff_replay_items(&stored, &stored).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+// ==========================================
+// 热轧精整排产系统 - 激活前红线校验报告
+// ==========================================
+// 职责: 描述版本激活前的工业红线扫描结果
+// 红线: 冻结区保护 / 适温约束 / 产能优先 / 换辊硬上限 / 可解释性 / 路径规则人工确认
+// 说明: 存在 HARD 级违规时禁止激活，除非填写覆盖原因
+// ==========================================
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+// ==========================================
+// RedLineSeverity - 违规严重度
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RedLineSeverity {
+    Hard,    // 硬违规：阻断激活（可填写覆盖原因放行）
+    Warning, // 警告：不阻断激活
+}
+
+// ==========================================
+// RedLineViolation - 单条违规
+// ==========================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedLineViolation {
+    pub code: String, // IMMATURE_NOT_RELEASED / FROZEN_MOVED / CAPACITY_OVER_LIMIT / ...
+    pub severity: RedLineSeverity,
+    pub material_id: Option<String>, // 材料级违规
+    pub machine_code: Option<String>,
+    pub plan_date: Option<NaiveDate>,
+    pub message: String,
+}
+
+// ==========================================
+// RedLineReport - 版本红线校验报告
+// ==========================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedLineReport {
+    pub version_id: String,
+    pub baseline_version_id: Option<String>, // 冻结区对比基准（方案当前激活版本）
+    pub hard_count: usize,
+    pub warning_count: usize,
+    pub violations: Vec<RedLineViolation>,
+    pub generated_at: NaiveDateTime,
+}
+
+impl RedLineReport {
+    /// 由违规列表构建报告（自动统计各级数量）
+    pub fn new(
+        version_id: &str,
+        baseline_version_id: Option<String>,
+        violations: Vec<RedLineViolation>,
+        generated_at: NaiveDateTime,
+    ) -> Self {
+        let hard_count = violations
+            .iter()
+            .filter(|v| v.severity == RedLineSeverity::Hard)
+            .count();
+        Self {
+            version_id: version_id.to_string(),
+            baseline_version_id,
+            hard_count,
+            warning_count: violations.len() - hard_count,
+            violations,
+            generated_at,
+        }
+    }
+
+    /// 是否存在阻断激活的硬违规
+    pub fn has_hard_violations(&self) -> bool {
+        self.hard_count > 0
+    }
+
+    /// 按违规代码统计数量（用于摘要/日志）
+    pub fn count_by_code(&self) -> std::collections::BTreeMap<String, usize> {
+        let mut counts = std::collections::BTreeMap::new();
+        for v in &self.violations {
+            *counts.entry(v.code.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+}
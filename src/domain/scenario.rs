@@ -0,0 +1,215 @@
+// ==========================================
+// 热轧精整排产系统 - 场景/沙盘方案领域模型
+// ==========================================
+// 职责: 场景方案(SCENARIO/SANDBOX)的假设叠加层 + 评估结果
+// 说明:
+// - 场景方案挂在基准方案(BASELINE)下 (plan.base_plan_id)
+// - 叠加层只在隔离沙盘中生效,不写生产 material_state / risk_snapshot
+// - 评估结果可提升为基准方案的草稿版本
+// ==========================================
+
+use crate::domain::plan::PlanItem;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 方案类型: 基准方案
+pub const PLAN_TYPE_BASELINE: &str = "BASELINE";
+/// 方案类型: 场景方案 (what-if 对比)
+pub const PLAN_TYPE_SCENARIO: &str = "SCENARIO";
+/// 方案类型: 沙盘方案 (自由演练)
+pub const PLAN_TYPE_SANDBOX: &str = "SANDBOX";
+
+/// 是否为隔离评估的方案类型 (SCENARIO/SANDBOX)
+pub fn is_isolated_plan_type(plan_type: &str) -> bool {
+    matches!(
+        plan_type.trim().to_uppercase().as_str(),
+        PLAN_TYPE_SCENARIO | PLAN_TYPE_SANDBOX
+    )
+}
+
+// ==========================================
+// ScenarioOverlay - 场景假设叠加层
+// ==========================================
+// 对齐: scenario_overlay 表 (overlay_json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioOverlay {
+    #[serde(default)]
+    pub capacity_changes: Vec<CapacityOverride>, // 产能调整
+    #[serde(default)]
+    pub hypothetical_materials: Vec<HypotheticalMaterial>, // 假设材料
+    #[serde(default)]
+    pub machine_outages: Vec<MachineOutage>, // 机组停机
+    #[serde(default)]
+    pub config_overrides: BTreeMap<String, String>, // 配置覆盖 (config_kv global)
+}
+
+impl ScenarioOverlay {
+    /// 叠加层是否为空
+    pub fn is_empty(&self) -> bool {
+        self.capacity_changes.is_empty()
+            && self.hypothetical_materials.is_empty()
+            && self.machine_outages.is_empty()
+            && self.config_overrides.is_empty()
+    }
+}
+
+/// 产能调整 (为空的字段沿用基准产能)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapacityOverride {
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub target_capacity_t: Option<f64>,
+    pub limit_capacity_t: Option<f64>,
+}
+
+/// 假设材料 (仅存在于沙盘中,视为已适温)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HypotheticalMaterial {
+    pub material_id: String,
+    pub machine_code: String,
+    pub weight_t: f64,
+    pub width_mm: Option<f64>,
+    pub thickness_mm: Option<f64>,
+    pub steel_mark: Option<String>,
+    pub due_date: Option<NaiveDate>,
+}
+
+/// 机组停机 (区间内产能置零,含首尾)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MachineOutage {
+    pub machine_code: String,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub reason: Option<String>,
+}
+
+impl MachineOutage {
+    /// 停机覆盖的日期 (date_to 早于 date_from 时为空)
+    pub fn dates(&self) -> Vec<NaiveDate> {
+        self.date_from
+            .iter_days()
+            .take_while(|d| *d <= self.date_to)
+            .collect()
+    }
+}
+
+// ==========================================
+// MachineBreakdown - 机组故障假设
+// ==========================================
+// 用途: 故障停机 what-if (按小时折算当日产能损失)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MachineBreakdown {
+    pub machine_code: String,
+    pub outage_start: NaiveDateTime,
+    pub outage_hours: f64,
+    /// 故障期间产能损失比例(%),为空视为完全停机(100)
+    pub capacity_reduction_pct: Option<f64>,
+}
+
+impl MachineBreakdown {
+    /// 故障结束时间
+    pub fn outage_end(&self) -> NaiveDateTime {
+        self.outage_start + Duration::seconds((self.outage_hours.max(0.0) * 3600.0) as i64)
+    }
+
+    /// 按日折算的产能损失比例 (0.0-1.0,仅含有损失的日期)
+    pub fn daily_capacity_loss(&self) -> Vec<(NaiveDate, f64)> {
+        let severity = self
+            .capacity_reduction_pct
+            .unwrap_or(100.0)
+            .clamp(0.0, 100.0)
+            / 100.0;
+        let end = self.outage_end();
+        let mut result = Vec::new();
+        let mut day = self.outage_start.date();
+        loop {
+            let day_start = day.and_time(NaiveTime::MIN);
+            if day_start >= end {
+                break;
+            }
+            let overlap = end.min(day_start + Duration::days(1)) - self.outage_start.max(day_start);
+            let hours = overlap.num_seconds() as f64 / 3600.0;
+            if hours > 0.0 && severity > 0.0 {
+                result.push((day, (hours / 24.0 * severity).min(1.0)));
+            }
+            match day.succ_opt() {
+                Some(next) => day = next,
+                None => break,
+            }
+        }
+        result
+    }
+}
+
+// ==========================================
+// ScenarioRun - 场景评估结果
+// ==========================================
+// 对齐: scenario_run 表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioRun {
+    pub run_id: String,
+    pub plan_id: String,         // 场景方案
+    pub base_version_id: String, // 评估时基准方案的激活版本
+    pub plan_date_from: NaiveDate,
+    pub plan_date_to: NaiveDate,
+    pub strategy: String,
+
+    // ===== 汇总 =====
+    pub total_items: usize,
+    pub total_weight_t: f64,
+    pub mature_count: usize,
+    pub immature_count: usize,
+    pub overflow_days: usize,
+    pub hypothetical_scheduled: usize, // 已排入的假设材料数
+
+    // ===== 明细 (version_id 为基准版本,提升时改写) =====
+    pub items: Vec<PlanItem>,
+    pub hypothetical_material_ids: Vec<String>,
+
+    pub overlay: ScenarioOverlay, // 评估时的叠加层快照
+    pub created_by: String,
+    pub created_at: NaiveDateTime,
+    pub promoted_version_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outage_dates_inclusive() {
+        let outage = MachineOutage {
+            machine_code: "H032".to_string(),
+            date_from: NaiveDate::from_ymd_opt(2026, 6, 1).unwrap(),
+            date_to: NaiveDate::from_ymd_opt(2026, 6, 3).unwrap(),
+            reason: None,
+        };
+        assert_eq!(outage.dates().len(), 3);
+        assert!(is_isolated_plan_type("sandbox"));
+        assert!(!is_isolated_plan_type(PLAN_TYPE_BASELINE));
+    }
+
+    #[test]
+    fn test_breakdown_daily_capacity_loss_spans_midnight() {
+        let breakdown = MachineBreakdown {
+            machine_code: "H033".to_string(),
+            outage_start: NaiveDate::from_ymd_opt(2026, 6, 1)
+                .unwrap()
+                .and_hms_opt(18, 0, 0)
+                .unwrap(),
+            outage_hours: 16.0,
+            capacity_reduction_pct: None,
+        };
+        let loss = breakdown.daily_capacity_loss();
+        assert_eq!(loss.len(), 2);
+        assert!((loss[0].1 - 0.25).abs() < 1e-9);
+        assert!((loss[1].1 - 10.0 / 24.0).abs() < 1e-9);
+
+        let degraded = MachineBreakdown {
+            capacity_reduction_pct: Some(50.0),
+            ..breakdown
+        };
+        assert!((degraded.daily_capacity_loss()[0].1 - 0.125).abs() < 1e-9);
+    }
+}
@@ -66,6 +66,7 @@ pub enum SchedState {
     ForceRelease,  // 强制放行
     Blocked,       // 数据质量阻断
     Scheduled,     // 已排产
+    Completed,     // 已完工(实绩回填)
 }
 
 impl fmt::Display for SchedState {
@@ -77,6 +78,7 @@ impl fmt::Display for SchedState {
             SchedState::ForceRelease => write!(f, "FORCE_RELEASE"),
             SchedState::Blocked => write!(f, "BLOCKED"),
             SchedState::Scheduled => write!(f, "SCHEDULED"),
+            SchedState::Completed => write!(f, "COMPLETED"),
         }
     }
 }
@@ -174,15 +176,21 @@ impl fmt::Display for RollStatus {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PlanVersionStatus {
-    Draft,    // 草稿
-    Active,   // 激活
-    Archived, // 归档
+    Draft,     // 草稿
+    Submitted, // 已提交待审
+    Approved,  // 审批通过(待激活)
+    Rejected,  // 审批驳回
+    Active,    // 激活
+    Archived,  // 归档
 }
 
 impl fmt::Display for PlanVersionStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             PlanVersionStatus::Draft => write!(f, "DRAFT"),
+            PlanVersionStatus::Submitted => write!(f, "SUBMITTED"),
+            PlanVersionStatus::Approved => write!(f, "APPROVED"),
+            PlanVersionStatus::Rejected => write!(f, "REJECTED"),
             PlanVersionStatus::Active => write!(f, "ACTIVE"),
             PlanVersionStatus::Archived => write!(f, "ARCHIVED"),
         }
@@ -194,6 +202,9 @@ impl PlanVersionStatus {
     pub fn from_str(s: &str) -> Self {
         match s.to_uppercase().as_str() {
             "DRAFT" => PlanVersionStatus::Draft,
+            "SUBMITTED" => PlanVersionStatus::Submitted,
+            "APPROVED" => PlanVersionStatus::Approved,
+            "REJECTED" => PlanVersionStatus::Rejected,
             "ACTIVE" => PlanVersionStatus::Active,
             "ARCHIVED" => PlanVersionStatus::Archived,
             _ => PlanVersionStatus::Draft, // 默认值
@@ -204,6 +215,9 @@ impl PlanVersionStatus {
     pub fn to_db_str(&self) -> &'static str {
         match self {
             PlanVersionStatus::Draft => "DRAFT",
+            PlanVersionStatus::Submitted => "SUBMITTED",
+            PlanVersionStatus::Approved => "APPROVED",
+            PlanVersionStatus::Rejected => "REJECTED",
             PlanVersionStatus::Active => "ACTIVE",
             PlanVersionStatus::Archived => "ARCHIVED",
         }
@@ -0,0 +1,396 @@
+// ==========================================
+// 热轧精整排产系统 - 人工操作撤销/重做领域模型
+// ==========================================
+// 职责: 定义可撤销操作写入 action_log.payload_json 的逆操作载荷，
+//       以及按日志回放撤销/重做栈的规则
+// 约定:
+// - payload_json["undo"]: 逆操作（InversePayload），存在即表示该日志可撤销
+// - payload_json["history"]: 撤销/重做产生的日志携带（HistoryTag）
+// ==========================================
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::domain::action_log::ActionLog;
+use crate::domain::types::SchedState;
+
+/// payload_json 中逆操作载荷的键
+pub const UNDO_PAYLOAD_KEY: &str = "undo";
+/// payload_json 中撤销/重做标记的键
+pub const HISTORY_PAYLOAD_KEY: &str = "history";
+
+// ==========================================
+// 快照类型
+// ==========================================
+
+/// 排产明细位置（机组 + 日期 + 日内顺序）及来源
+///
+/// source_type / assign_reason 随位置原样恢复；旧日志无来源字段（source_type=None）时按人工移动恢复
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemPosition {
+    pub material_id: String,
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub seq_no: i32,
+    #[serde(default)]
+    pub source_type: Option<String>,
+    #[serde(default)]
+    pub assign_reason: Option<String>,
+}
+
+impl ItemPosition {
+    /// 当前明细是否与快照一致（旧日志快照仅比较位置）
+    pub fn matches(&self, current: &ItemPosition) -> bool {
+        let same_position = self.material_id == current.material_id
+            && self.machine_code == current.machine_code
+            && self.plan_date == current.plan_date
+            && self.seq_no == current.seq_no;
+        match self.source_type {
+            Some(_) => same_position && self == current,
+            None => same_position,
+        }
+    }
+}
+
+/// 材料人工标志快照（锁定 / 强制放行 / 人工紧急）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaterialFlagSnapshot {
+    pub material_id: String,
+    pub sched_state: SchedState,
+    pub lock_flag: bool,
+    pub force_release_flag: bool,
+    pub manual_urgent_flag: bool,
+}
+
+/// 路径突破人工确认 / 拒绝快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathOverrideSnapshot {
+    pub material_id: String,
+    pub user_confirmed: bool,
+    pub user_confirmed_at: Option<DateTime<Utc>>,
+    pub user_confirmed_by: Option<String>,
+    pub user_confirmed_reason: Option<String>,
+    pub rejected: bool,
+    pub reject_cycle_no: Option<i32>,
+    pub reject_base_sched_state: Option<String>,
+}
+
+impl PathOverrideSnapshot {
+    /// 业务状态是否一致（确认/拒绝标记，不比较时间戳与原因文本）
+    pub fn same_state(&self, other: &Self) -> bool {
+        self.material_id == other.material_id
+            && self.user_confirmed == other.user_confirmed
+            && self.user_confirmed_by == other.user_confirmed_by
+            && self.rejected == other.rejected
+            && self.reject_cycle_no == other.reject_cycle_no
+    }
+}
+
+// ==========================================
+// InversePayload - 逆操作载荷
+// ==========================================
+
+/// 逆操作载荷
+///
+/// - restore: 执行逆操作后应恢复到的状态（操作前快照）
+/// - expected: 执行逆操作前当前状态应保持的样子（操作后快照），不一致即视为被后续操作改动
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InversePayload {
+    /// 排产明细位置（move_items）
+    MoveItems {
+        restore: Vec<ItemPosition>,
+        expected: Vec<ItemPosition>,
+    },
+    /// 材料人工标志（锁定 / 强制放行 / 人工紧急）
+    MaterialFlags {
+        restore: Vec<MaterialFlagSnapshot>,
+        expected: Vec<MaterialFlagSnapshot>,
+        /// 原操作是否使用严格校验（重新锁定/放行时沿用）
+        #[serde(default = "default_strict")]
+        strict: bool,
+    },
+    /// 路径突破人工确认 / 拒绝
+    PathOverride {
+        restore: Vec<PathOverrideSnapshot>,
+        expected: Vec<PathOverrideSnapshot>,
+    },
+}
+
+fn default_strict() -> bool {
+    true
+}
+
+impl InversePayload {
+    /// 载荷类型（与序列化 kind 一致）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InversePayload::MoveItems { .. } => "MOVE_ITEMS",
+            InversePayload::MaterialFlags { .. } => "MATERIAL_FLAGS",
+            InversePayload::PathOverride { .. } => "PATH_OVERRIDE",
+        }
+    }
+
+    /// 反向载荷：restore 与 expected 互换（撤销后用于重做，反之亦然）
+    pub fn reversed(&self) -> Self {
+        match self {
+            InversePayload::MoveItems { restore, expected } => InversePayload::MoveItems {
+                restore: expected.clone(),
+                expected: restore.clone(),
+            },
+            InversePayload::MaterialFlags {
+                restore,
+                expected,
+                strict,
+            } => InversePayload::MaterialFlags {
+                restore: expected.clone(),
+                expected: restore.clone(),
+                strict: *strict,
+            },
+            InversePayload::PathOverride { restore, expected } => InversePayload::PathOverride {
+                restore: expected.clone(),
+                expected: restore.clone(),
+            },
+        }
+    }
+
+    /// 涉及的材料ID（按 restore 顺序）
+    pub fn material_ids(&self) -> Vec<String> {
+        match self {
+            InversePayload::MoveItems { restore, .. } => {
+                restore.iter().map(|p| p.material_id.clone()).collect()
+            }
+            InversePayload::MaterialFlags { restore, .. } => {
+                restore.iter().map(|s| s.material_id.clone()).collect()
+            }
+            InversePayload::PathOverride { restore, .. } => {
+                restore.iter().map(|s| s.material_id.clone()).collect()
+            }
+        }
+    }
+
+    /// 是否无可恢复内容
+    pub fn is_empty(&self) -> bool {
+        match self {
+            InversePayload::MoveItems { restore, .. } => restore.is_empty(),
+            InversePayload::MaterialFlags { restore, .. } => restore.is_empty(),
+            InversePayload::PathOverride { restore, .. } => restore.is_empty(),
+        }
+    }
+
+    /// 从日志 payload 解析逆操作（无或格式不符时返回 None）
+    pub fn from_payload(payload: Option<&JsonValue>) -> Option<Self> {
+        payload
+            .and_then(|p| p.get(UNDO_PAYLOAD_KEY))
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    /// 序列化为 JSON（写入 payload_json["undo"]）
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::to_value(self).unwrap_or(JsonValue::Null)
+    }
+}
+
+// ==========================================
+// HistoryTag - 撤销/重做标记
+// ==========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HistoryKind {
+    Undo,
+    Redo,
+}
+
+impl HistoryKind {
+    /// 对应的 action_log.action_type
+    pub fn action_type(&self) -> &'static str {
+        match self {
+            HistoryKind::Undo => "UNDO",
+            HistoryKind::Redo => "REDO",
+        }
+    }
+}
+
+/// 撤销/重做日志标记：target_action_id 为被撤销（或被重做的撤销）日志
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryTag {
+    pub kind: HistoryKind,
+    pub target_action_id: String,
+}
+
+impl HistoryTag {
+    /// 从日志 payload 解析撤销/重做标记
+    pub fn from_payload(payload: Option<&JsonValue>) -> Option<Self> {
+        payload
+            .and_then(|p| p.get(HISTORY_PAYLOAD_KEY))
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+}
+
+// ==========================================
+// UndoStacks - 撤销/重做栈（由日志回放得到）
+// ==========================================
+
+/// 撤销/重做栈
+///
+/// 栈元素均为“当前可应用其逆操作”的日志：
+/// - undo 栈：普通操作日志或 REDO 日志，应用其 undo 载荷即撤销
+/// - redo 栈：UNDO 日志，应用其 undo 载荷即重做
+#[derive(Debug, Clone, Default)]
+pub struct UndoStacks {
+    pub undo: Vec<ActionLog>,
+    pub redo: Vec<ActionLog>,
+}
+
+impl UndoStacks {
+    /// 按写入顺序回放日志
+    ///
+    /// # 规则
+    /// - 普通可撤销操作：入 undo 栈，清空 redo 栈（新操作使重做失效）
+    /// - UNDO(target)：target 位于 undo 栈顶时出栈，UNDO 日志入 redo 栈
+    /// - REDO(target)：target 位于 redo 栈顶时出栈，REDO 日志入 undo 栈
+    /// - 标记与栈顶不符（历史被截断等）时忽略该日志
+    pub fn replay<I>(logs: I) -> Self
+    where
+        I: IntoIterator<Item = ActionLog>,
+    {
+        let mut stacks = UndoStacks::default();
+        for log in logs {
+            match HistoryTag::from_payload(log.payload_json.as_ref()) {
+                None => {
+                    stacks.undo.push(log);
+                    stacks.redo.clear();
+                }
+                Some(tag) => {
+                    let (from, to) = match tag.kind {
+                        HistoryKind::Undo => (&mut stacks.undo, &mut stacks.redo),
+                        HistoryKind::Redo => (&mut stacks.redo, &mut stacks.undo),
+                    };
+                    if from.last().map(|l| l.action_id.as_str())
+                        == Some(tag.target_action_id.as_str())
+                    {
+                        from.pop();
+                        to.push(log);
+                    }
+                }
+            }
+        }
+        stacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(id: &str, history: Option<(HistoryKind, &str)>) -> ActionLog {
+        let mut payload = serde_json::json!({ UNDO_PAYLOAD_KEY: {"kind": "MOVE_ITEMS", "restore": [], "expected": []} });
+        if let Some((kind, target)) = history {
+            payload[HISTORY_PAYLOAD_KEY] = serde_json::to_value(HistoryTag {
+                kind,
+                target_action_id: target.to_string(),
+            })
+            .unwrap();
+        }
+        let mut log = ActionLog::new(id.to_string(), None, "MOVE_ITEMS", "planner".to_string());
+        log.payload_json = Some(payload);
+        log
+    }
+
+    fn ids(logs: &[ActionLog]) -> Vec<&str> {
+        logs.iter().map(|l| l.action_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_replay_undo_redo_sequence() {
+        let stacks = UndoStacks::replay(vec![
+            log("a", None),
+            log("b", None),
+            log("u1", Some((HistoryKind::Undo, "b"))),
+            log("u2", Some((HistoryKind::Undo, "a"))),
+            log("r1", Some((HistoryKind::Redo, "u2"))),
+        ]);
+        assert_eq!(ids(&stacks.undo), vec!["r1"]);
+        assert_eq!(ids(&stacks.redo), vec!["u1"]);
+    }
+
+    #[test]
+    fn test_replay_new_operation_clears_redo() {
+        let stacks = UndoStacks::replay(vec![
+            log("a", None),
+            log("u1", Some((HistoryKind::Undo, "a"))),
+            log("b", None),
+            // 与栈顶不符的标记被忽略
+            log("r1", Some((HistoryKind::Redo, "u1"))),
+        ]);
+        assert_eq!(ids(&stacks.undo), vec!["b"]);
+        assert!(stacks.redo.is_empty());
+    }
+
+    #[test]
+    fn test_inverse_payload_roundtrip_and_reverse() {
+        let before = ItemPosition {
+            material_id: "M1".to_string(),
+            machine_code: "H032".to_string(),
+            plan_date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            seq_no: 1,
+            source_type: Some("CALC".to_string()),
+            assign_reason: None,
+        };
+        let after = ItemPosition {
+            plan_date: NaiveDate::from_ymd_opt(2026, 1, 2).unwrap(),
+            source_type: Some("MANUAL".to_string()),
+            assign_reason: Some("MANUAL_MOVE".to_string()),
+            ..before.clone()
+        };
+        let payload = InversePayload::MoveItems {
+            restore: vec![before.clone()],
+            expected: vec![after.clone()],
+        };
+        let json = serde_json::json!({ UNDO_PAYLOAD_KEY: payload.to_json() });
+        assert_eq!(json[UNDO_PAYLOAD_KEY]["kind"], "MOVE_ITEMS");
+        let parsed = InversePayload::from_payload(Some(&json)).unwrap();
+        assert_eq!(parsed, payload);
+        assert_eq!(
+            parsed.reversed(),
+            InversePayload::MoveItems {
+                restore: vec![after],
+                expected: vec![before],
+            }
+        );
+        assert_eq!(parsed.material_ids(), vec!["M1".to_string()]);
+    }
+
+    #[test]
+    fn test_item_position_matches_legacy_snapshot_by_position() {
+        let legacy: ItemPosition = serde_json::from_value(serde_json::json!({
+            "material_id": "M1",
+            "machine_code": "H032",
+            "plan_date": "2026-01-01",
+            "seq_no": 1,
+        }))
+        .unwrap();
+        assert_eq!(legacy.source_type, None);
+
+        let current = ItemPosition {
+            source_type: Some("MANUAL".to_string()),
+            assign_reason: Some("MANUAL_MOVE".to_string()),
+            ..legacy.clone()
+        };
+        assert!(legacy.matches(&current));
+        assert!(!legacy.matches(&ItemPosition {
+            seq_no: 2,
+            ..current.clone()
+        }));
+
+        // 新快照连同来源一起比较
+        let snapshot = ItemPosition {
+            source_type: Some("CALC".to_string()),
+            ..current.clone()
+        };
+        assert!(!snapshot.matches(&current));
+        assert!(current.matches(&current));
+    }
+}
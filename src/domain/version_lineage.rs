@@ -0,0 +1,224 @@
+// ==========================================
+// 热轧精整排产系统 - 版本谱系与保留策略
+// ==========================================
+// 职责:
+// - 版本派生关系（父版本 / 触发方式 / 策略），写入 config_snapshot_json 的 __meta_* 键
+// - 版本保留策略与清理结果
+// 说明:
+// - 不改变 plan_version 表结构；旧版本缺少谱系元信息时按已有 __meta_* 推断
+// ==========================================
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::types::PlanVersionStatus;
+
+/// 父版本ID（__meta_* 键）
+pub const META_PARENT_VERSION_ID: &str = "__meta_parent_version_id";
+/// 版本派生触发方式（__meta_* 键）
+pub const META_TRIGGER: &str = "__meta_trigger";
+
+// ==========================================
+// VersionTrigger - 版本派生触发方式
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VersionTrigger {
+    Manual,          // 人工创建
+    Recalc,          // 一键重算
+    Merge,           // 三方合并
+    ScenarioPromote, // 场景提升
+    BreakdownWhatIf, // 故障模拟另存
+    RushInsert,      // 紧急插单
+}
+
+impl VersionTrigger {
+    /// 转换为 __meta_trigger 存储的字符串
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VersionTrigger::Manual => "MANUAL",
+            VersionTrigger::Recalc => "RECALC",
+            VersionTrigger::Merge => "MERGE",
+            VersionTrigger::ScenarioPromote => "SCENARIO_PROMOTE",
+            VersionTrigger::BreakdownWhatIf => "BREAKDOWN_WHAT_IF",
+            VersionTrigger::RushInsert => "RUSH_INSERT",
+        }
+    }
+
+    /// 从字符串解析 (未知值返回 None)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "MANUAL" => Some(VersionTrigger::Manual),
+            "RECALC" => Some(VersionTrigger::Recalc),
+            "MERGE" => Some(VersionTrigger::Merge),
+            "SCENARIO_PROMOTE" => Some(VersionTrigger::ScenarioPromote),
+            "BREAKDOWN_WHAT_IF" => Some(VersionTrigger::BreakdownWhatIf),
+            "RUSH_INSERT" => Some(VersionTrigger::RushInsert),
+            _ => None,
+        }
+    }
+}
+
+// ==========================================
+// VersionLineageMeta - 版本谱系元信息
+// ==========================================
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VersionLineageMeta {
+    pub parent_version_id: Option<String>,
+    pub trigger: Option<VersionTrigger>,
+    pub strategy: Option<String>,
+    pub version_name: Option<String>,
+}
+
+impl VersionLineageMeta {
+    /// 从 config_snapshot_json 解析谱系元信息
+    ///
+    /// 旧版本无 __meta_trigger 时按已有元信息推断:
+    /// - __meta_breakdown_from_version_id → 故障模拟（父版本即来源版本）
+    /// - __meta_scenario_run_id → 场景提升
+    /// - __meta_strategy → 一键重算
+    pub fn from_snapshot(snapshot_json: Option<&str>) -> Self {
+        let map: HashMap<String, String> = snapshot_json
+            .and_then(|raw| serde_json::from_str(raw).ok())
+            .unwrap_or_default();
+        let get = |key: &str| {
+            map.get(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let breakdown_from = get("__meta_breakdown_from_version_id");
+        let trigger = get(META_TRIGGER)
+            .and_then(|t| VersionTrigger::parse(&t))
+            .or_else(|| {
+                if breakdown_from.is_some() {
+                    Some(VersionTrigger::BreakdownWhatIf)
+                } else if map.contains_key("__meta_scenario_run_id") {
+                    Some(VersionTrigger::ScenarioPromote)
+                } else if map.contains_key("__meta_strategy") {
+                    Some(VersionTrigger::Recalc)
+                } else {
+                    None
+                }
+            });
+
+        Self {
+            parent_version_id: get(META_PARENT_VERSION_ID).or(breakdown_from),
+            trigger,
+            strategy: get("__meta_strategy"),
+            version_name: get("__meta_version_name_cn"),
+        }
+    }
+}
+
+// ==========================================
+// 谱系树
+// ==========================================
+
+/// 相对父版本的明细差异（口径同版本对比：仅比较机组/日期）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageDiffCounts {
+    pub moved_count: usize,
+    pub added_count: usize,
+    pub removed_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionLineageNode {
+    pub version_id: String,
+    pub version_no: i32,
+    pub status: PlanVersionStatus,
+    pub parent_version_id: Option<String>, // 父版本（可能已被清理）
+    pub trigger: Option<VersionTrigger>,   // None: 无法判定（旧数据）
+    pub strategy: Option<String>,
+    pub version_name: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub diff_from_parent: Option<LineageDiffCounts>,
+    pub children: Vec<VersionLineageNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionLineageTree {
+    pub plan_id: String,
+    pub version_count: usize,
+    pub roots: Vec<VersionLineageNode>, // 无父版本或父版本已清理的版本
+}
+
+// ==========================================
+// 保留策略
+// ==========================================
+
+/// 版本保留策略
+///
+/// - ACTIVE / SUBMITTED / APPROVED 版本始终保留
+/// - keep_ever_active: 有激活/回滚记录的版本始终保留
+/// - keep_archived_per_plan: 每个方案保留最近 N 个归档版本
+///
+/// 注意: 归档版本通常都经过激活（激活新版本时旧版本转为 ARCHIVED），
+/// 因此 keep_ever_active=true（默认）时这些归档版本全部保留，
+/// keep_archived_per_plan 只约束缺少激活记录的归档版本；需按数量裁剪归档时应关闭 keep_ever_active
+/// - max_age_days: 仅清理创建超过 N 天的版本；未设置时草稿/驳回版本不清理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionRetentionPolicy {
+    pub keep_archived_per_plan: usize,
+    pub keep_ever_active: bool,
+    pub max_age_days: Option<i64>,
+}
+
+impl Default for VersionRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_archived_per_plan: 5,
+            keep_ever_active: true,
+            max_age_days: Some(90),
+        }
+    }
+}
+
+/// 待清理版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionPurgeCandidate {
+    pub version_id: String,
+    pub plan_id: String,
+    pub version_no: i32,
+    pub status: PlanVersionStatus,
+    pub created_at: NaiveDateTime,
+    pub reason: String,
+}
+
+/// 版本清理结果（dry_run 时 deleted_rows 为空）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionPurgeReport {
+    pub dry_run: bool,
+    pub policy: VersionRetentionPolicy,
+    pub scanned_count: usize,
+    pub candidates: Vec<VersionPurgeCandidate>,
+    pub deleted_rows: BTreeMap<String, usize>, // 表名 → 删除行数
+    pub detached_action_logs: usize,
+    pub executed_at: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lineage_meta_explicit_and_inferred() {
+        let explicit = VersionLineageMeta::from_snapshot(Some(
+            r#"{"__meta_parent_version_id":"V1","__meta_trigger":"MERGE","k":"v"}"#,
+        ));
+        assert_eq!(explicit.parent_version_id.as_deref(), Some("V1"));
+        assert_eq!(explicit.trigger, Some(VersionTrigger::Merge));
+
+        let legacy = VersionLineageMeta::from_snapshot(Some(
+            r#"{"__meta_breakdown_from_version_id":"V2","__meta_version_name_cn":"故障模拟"}"#,
+        ));
+        assert_eq!(legacy.parent_version_id.as_deref(), Some("V2"));
+        assert_eq!(legacy.trigger, Some(VersionTrigger::BreakdownWhatIf));
+
+        let unknown = VersionLineageMeta::from_snapshot(Some("试算 (操作人: admin)"));
+        assert_eq!(unknown, VersionLineageMeta::default());
+    }
+}
@@ -0,0 +1,88 @@
+// ==========================================
+// 热轧精整排产系统 - 版本审批领域模型
+// ==========================================
+// 职责: 版本激活审批流 (计划员提交 → 生产经理审批 → 激活)
+// 状态流转:
+//   DRAFT/REJECTED --提交--> SUBMITTED --通过--> APPROVED --激活--> ACTIVE
+//                                      --驳回--> REJECTED
+// ==========================================
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+// ==========================================
+// VersionReviewAction - 审批动作
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VersionReviewAction {
+    Submit,  // 提交审批
+    Approve, // 审批通过
+    Reject,  // 审批驳回
+}
+
+impl VersionReviewAction {
+    /// 转换为数据库存储的字符串
+    pub fn to_db_str(&self) -> &'static str {
+        match self {
+            VersionReviewAction::Submit => "SUBMIT",
+            VersionReviewAction::Approve => "APPROVE",
+            VersionReviewAction::Reject => "REJECT",
+        }
+    }
+
+    /// 从字符串解析 (未知值返回 None)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_uppercase().as_str() {
+            "SUBMIT" => Some(VersionReviewAction::Submit),
+            "APPROVE" => Some(VersionReviewAction::Approve),
+            "REJECT" => Some(VersionReviewAction::Reject),
+            _ => None,
+        }
+    }
+}
+
+// ==========================================
+// ActivationChecklist - 激活前检查单
+// ==========================================
+// 提交时生成并随提交记录保存，供审批人参考（不阻断提交）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub code: String,   // 检查项代码
+    pub label: String,  // 检查项名称
+    pub passed: bool,   // 是否通过
+    pub detail: String, // 检查结果说明
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivationChecklist {
+    pub items: Vec<ChecklistItem>,
+    pub generated_at: NaiveDateTime,
+}
+
+impl ActivationChecklist {
+    /// 全部检查项是否通过
+    pub fn all_passed(&self) -> bool {
+        self.items.iter().all(|i| i.passed)
+    }
+
+    /// 未通过的检查项数量
+    pub fn failed_count(&self) -> usize {
+        self.items.iter().filter(|i| !i.passed).count()
+    }
+}
+
+// ==========================================
+// VersionReview - 审批记录
+// ==========================================
+// 对齐: plan_version_review 表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionReview {
+    pub review_id: String,
+    pub version_id: String,
+    pub action: VersionReviewAction,
+    pub actor: String,
+    pub comment: Option<String>,                // 提交说明/审批意见
+    pub checklist: Option<ActivationChecklist>, // 仅提交记录携带
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,736 @@
+// ==========================================
+// 热轧精整排产系统 - 瓶颈优化建议引擎
+// ==========================================
+// 职责: 针对 机组×日 产能瓶颈生成可直接执行的调整建议
+// 输入: 机组×日 负荷 + 版本排产项 + 未适温紧急材料 + 路径待确认材料 + 换辊计划
+// 输出: 按收益排序的建议 (移动材料 / 强制放行 / 确认路径突破 / 平移换辊)
+// ==========================================
+// 口径:
+// - 有效负荷 = 已用吨位 + 换辊停机折算吨位 (上限产能 × 停机分钟 / 1440)
+// - 瓶颈: 有效负荷 > 目标产能; 余量: 目标产能 - 有效负荷
+// - 任何建议都不会让目标日有效负荷超过目标产能（不制造新瓶颈）
+// - 移动仅限同机组（跨机组需工艺确认，不自动建议）;
+//   不早于最早可排日期，推后不晚于交期
+// - 建议按生成顺序累计生效: 后一条建议基于前面建议执行后的负荷
+// - 收益: 消除超目标吨位 × 1.0; 填充余量吨位 × 紧急等级系数
+// ==========================================
+
+use crate::domain::action_log::ImpactSummary;
+use crate::domain::types::UrgentLevel;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 吨位比较容差
+const EPS_T: f64 = 1e-6;
+/// 建议条数上限
+pub const MAX_RECOMMENDATIONS: usize = 200;
+
+/// 余量填充的紧急等级系数
+fn urgency_factor(level: UrgentLevel) -> f64 {
+    match level {
+        UrgentLevel::L3 => 1.0,
+        UrgentLevel::L2 => 0.8,
+        UrgentLevel::L1 => 0.5,
+        UrgentLevel::L0 => 0.2,
+    }
+}
+
+// ==========================================
+// 输入
+// ==========================================
+
+/// 机组×日 负荷（已用吨位由调用方按排产明细汇总）
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachineDayLoad {
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub target_capacity_t: f64,
+    pub limit_capacity_t: f64,
+    pub used_capacity_t: f64,
+}
+
+/// 版本内排产项（含材料事实）
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovableItem {
+    pub material_id: String,
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub weight_t: f64,
+    pub urgent_level: UrgentLevel,
+    pub due_date: Option<NaiveDate>,
+    pub earliest_sched_date: Option<NaiveDate>,
+    /// 冻结区/锁定材料（红线: 不参与移动）
+    pub locked: bool,
+}
+
+/// 强制放行候选（未适温、未排产的紧急材料）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseCandidate {
+    pub material_id: String,
+    pub machine_code: String,
+    pub weight_t: f64,
+    pub urgent_level: UrgentLevel,
+    pub due_date: Option<NaiveDate>,
+    pub ready_in_days: i32,
+}
+
+/// 路径突破待确认材料
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathOverrideCandidate {
+    pub material_id: String,
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub weight_t: f64,
+    pub urgent_level: UrgentLevel,
+    pub violation_type: String,
+}
+
+/// 计划换辊
+#[derive(Debug, Clone, PartialEq)]
+pub struct RollChangePlan {
+    pub machine_code: String,
+    pub next_change_at: NaiveDateTime,
+    pub downtime_minutes: i32,
+}
+
+/// 瓶颈优化输入
+pub struct BottleneckOptimizerInput<'a> {
+    pub loads: &'a [MachineDayLoad],
+    pub items: &'a [MovableItem],
+    pub release_candidates: &'a [ReleaseCandidate],
+    pub path_overrides: &'a [PathOverrideCandidate],
+    pub roll_changes: &'a [RollChangePlan],
+}
+
+// ==========================================
+// 输出
+// ==========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BottleneckActionKind {
+    MoveItem,            // 同机组移动材料
+    ForceRelease,        // 强制放行未适温紧急材料
+    ConfirmPathOverride, // 确认路径突破
+    ShiftRollChange,     // 平移换辊时间
+}
+
+impl BottleneckActionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BottleneckActionKind::MoveItem => "MOVE_ITEM",
+            BottleneckActionKind::ForceRelease => "FORCE_RELEASE",
+            BottleneckActionKind::ConfirmPathOverride => "CONFIRM_PATH_OVERRIDE",
+            BottleneckActionKind::ShiftRollChange => "SHIFT_ROLL_CHANGE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BottleneckRecommendation {
+    /// 确定性ID（编码具体操作，负荷变化后旧ID自然失效）
+    pub recommendation_id: String,
+    pub rank: usize,
+    pub kind: BottleneckActionKind,
+    pub machine_code: String,
+    pub material_id: Option<String>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    /// 平移换辊: 原/新换辊时间 (YYYY-MM-DD HH:MM:SS)
+    pub roll_change_from: Option<String>,
+    pub roll_change_to: Option<String>,
+    pub weight_t: f64,
+    pub urgent_level: Option<UrgentLevel>,
+    pub overload_relief_t: f64,
+    pub slack_fill_t: f64,
+    pub benefit_score: f64,
+    pub reason: String,
+    /// 预计影响（API 层基于 ImpactSummaryEngine 填充）
+    pub predicted_impact: Option<ImpactSummary>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BottleneckOptimizerOutput {
+    pub recommendations: Vec<BottleneckRecommendation>,
+    pub bottleneck_days_before: usize,
+    pub bottleneck_days_after: usize,
+    pub overload_before_t: f64,
+    pub overload_after_t: f64,
+}
+
+// ==========================================
+// BottleneckOptimizer
+// ==========================================
+
+#[derive(Debug, Clone, Copy)]
+struct DayState {
+    target_t: f64,
+    limit_t: f64,
+    used_t: f64,
+    reserved_t: f64,
+}
+
+impl DayState {
+    fn overload_t(&self) -> f64 {
+        (self.used_t + self.reserved_t - self.target_t).max(0.0)
+    }
+
+    fn slack_t(&self) -> f64 {
+        self.target_t - self.used_t - self.reserved_t
+    }
+}
+
+type DayKey = (String, NaiveDate);
+
+pub struct BottleneckOptimizer;
+
+impl BottleneckOptimizer {
+    /// 生成瓶颈优化建议
+    pub fn recommend(input: &BottleneckOptimizerInput) -> BottleneckOptimizerOutput {
+        let mut days: BTreeMap<DayKey, DayState> = input
+            .loads
+            .iter()
+            .map(|l| {
+                (
+                    (l.machine_code.clone(), l.plan_date),
+                    DayState {
+                        target_t: l.target_capacity_t,
+                        limit_t: l.limit_capacity_t,
+                        used_t: l.used_capacity_t,
+                        reserved_t: 0.0,
+                    },
+                )
+            })
+            .collect();
+
+        let mut roll_changes: Vec<&RollChangePlan> = input
+            .roll_changes
+            .iter()
+            .filter(|r| {
+                r.downtime_minutes > 0
+                    && days.contains_key(&(r.machine_code.clone(), r.next_change_at.date()))
+            })
+            .collect();
+        roll_changes.sort_by(|a, b| {
+            (&a.machine_code, a.next_change_at).cmp(&(&b.machine_code, b.next_change_at))
+        });
+        for roll in &roll_changes {
+            let day = days
+                .get_mut(&(roll.machine_code.clone(), roll.next_change_at.date()))
+                .expect("已过滤");
+            day.reserved_t += Self::downtime_t(day.limit_t, roll.downtime_minutes);
+        }
+
+        let (bottleneck_days_before, overload_before_t) = Self::overload_stats(&days);
+
+        let mut recommendations = Vec::new();
+        Self::recommend_moves(input.items, &mut days, &mut recommendations);
+        Self::recommend_roll_shifts(&roll_changes, &mut days, &mut recommendations);
+        Self::recommend_path_overrides(input.path_overrides, &mut days, &mut recommendations);
+        Self::recommend_force_releases(input.release_candidates, &mut days, &mut recommendations);
+
+        let (bottleneck_days_after, overload_after_t) = Self::overload_stats(&days);
+
+        recommendations.sort_by(|a, b| {
+            b.benefit_score
+                .total_cmp(&a.benefit_score)
+                .then_with(|| a.recommendation_id.cmp(&b.recommendation_id))
+        });
+        recommendations.truncate(MAX_RECOMMENDATIONS);
+        for (idx, rec) in recommendations.iter_mut().enumerate() {
+            rec.rank = idx + 1;
+        }
+
+        BottleneckOptimizerOutput {
+            recommendations,
+            bottleneck_days_before,
+            bottleneck_days_after,
+            overload_before_t: round3(overload_before_t),
+            overload_after_t: round3(overload_after_t),
+        }
+    }
+
+    /// 换辊停机折算吨位
+    pub fn downtime_t(limit_capacity_t: f64, downtime_minutes: i32) -> f64 {
+        round3(limit_capacity_t.max(0.0) * downtime_minutes.max(0) as f64 / 1440.0)
+    }
+
+    fn overload_stats(days: &BTreeMap<DayKey, DayState>) -> (usize, f64) {
+        days.values()
+            .map(|d| d.overload_t())
+            .filter(|o| *o > EPS_T)
+            .fold((0, 0.0), |(n, t), o| (n + 1, t + o))
+    }
+
+    /// 同机组内距离最近的可承接日期（距离相同时按 prefer_later 取向）
+    fn nearest_day(
+        days: &BTreeMap<DayKey, DayState>,
+        machine_code: &str,
+        from: NaiveDate,
+        need_t: f64,
+        prefer_later: bool,
+        allowed: impl Fn(NaiveDate) -> bool,
+    ) -> Option<NaiveDate> {
+        days.iter()
+            .filter(|((m, d), s)| {
+                m == machine_code && *d != from && allowed(*d) && s.slack_t() + EPS_T >= need_t
+            })
+            .map(|((_, d), _)| *d)
+            .min_by_key(|d| {
+                let distance = (*d - from).num_days().abs();
+                let direction_penalty = if (*d > from) == prefer_later { 0 } else { 1 };
+                (distance, direction_penalty)
+            })
+    }
+
+    /// 瓶颈日移出材料：优先移动紧急等级低、交期远的材料
+    fn recommend_moves(
+        items: &[MovableItem],
+        days: &mut BTreeMap<DayKey, DayState>,
+        out: &mut Vec<BottleneckRecommendation>,
+    ) {
+        let mut bottlenecks: Vec<(DayKey, f64)> = days
+            .iter()
+            .filter(|(_, s)| s.overload_t() > EPS_T)
+            .map(|(k, s)| (k.clone(), s.overload_t()))
+            .collect();
+        bottlenecks.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        for (key, _) in bottlenecks {
+            let mut candidates: Vec<&MovableItem> = items
+                .iter()
+                .filter(|i| !i.locked && i.machine_code == key.0 && i.plan_date == key.1)
+                .filter(|i| i.weight_t > EPS_T)
+                .collect();
+            candidates.sort_by(|a, b| {
+                a.urgent_level
+                    .cmp(&b.urgent_level)
+                    .then_with(|| match (a.due_date, b.due_date) {
+                        (None, None) => std::cmp::Ordering::Equal,
+                        (None, Some(_)) => std::cmp::Ordering::Less,
+                        (Some(_), None) => std::cmp::Ordering::Greater,
+                        (Some(x), Some(y)) => y.cmp(&x),
+                    })
+                    .then_with(|| a.material_id.cmp(&b.material_id))
+            });
+
+            for item in candidates {
+                let overload = days[&key].overload_t();
+                if overload <= EPS_T {
+                    break;
+                }
+                let Some(to_date) = Self::nearest_day(
+                    days,
+                    &item.machine_code,
+                    item.plan_date,
+                    item.weight_t,
+                    true,
+                    |d| {
+                        item.earliest_sched_date.is_none_or(|e| d >= e)
+                            && (d < item.plan_date || item.due_date.is_none_or(|due| d <= due))
+                    },
+                ) else {
+                    continue;
+                };
+
+                days.get_mut(&key).expect("瓶颈日").used_t -= item.weight_t;
+                days.get_mut(&(item.machine_code.clone(), to_date))
+                    .expect("目标日")
+                    .used_t += item.weight_t;
+
+                let relief = item.weight_t.min(overload);
+                out.push(BottleneckRecommendation {
+                    recommendation_id: format!(
+                        "MOVE_ITEM:{}:{}:{}",
+                        item.material_id, item.machine_code, to_date
+                    ),
+                    rank: 0,
+                    kind: BottleneckActionKind::MoveItem,
+                    machine_code: item.machine_code.clone(),
+                    material_id: Some(item.material_id.clone()),
+                    from_date: Some(item.plan_date),
+                    to_date: Some(to_date),
+                    roll_change_from: None,
+                    roll_change_to: None,
+                    weight_t: item.weight_t,
+                    urgent_level: Some(item.urgent_level),
+                    overload_relief_t: round3(relief),
+                    slack_fill_t: 0.0,
+                    benefit_score: round3(relief),
+                    reason: format!(
+                        "{} {} 超目标{:.1}t，将{}({:.1}t, {})移至{}可消除{:.1}t",
+                        item.machine_code,
+                        item.plan_date,
+                        overload,
+                        item.material_id,
+                        item.weight_t,
+                        item.urgent_level,
+                        to_date,
+                        relief
+                    ),
+                    predicted_impact: None,
+                });
+            }
+        }
+    }
+
+    /// 移动材料后仍超目标的换辊日：换辊平移到最近的余量日（距离相同时优先提前，避免轧辊超吨）
+    fn recommend_roll_shifts(
+        roll_changes: &[&RollChangePlan],
+        days: &mut BTreeMap<DayKey, DayState>,
+        out: &mut Vec<BottleneckRecommendation>,
+    ) {
+        for roll in roll_changes {
+            let from = roll.next_change_at.date();
+            let key = (roll.machine_code.clone(), from);
+            let (overload, downtime_t) = {
+                let day = &days[&key];
+                (
+                    day.overload_t(),
+                    Self::downtime_t(day.limit_t, roll.downtime_minutes),
+                )
+            };
+            if overload <= EPS_T || downtime_t <= EPS_T {
+                continue;
+            }
+            let Some(to_date) =
+                Self::nearest_day(days, &roll.machine_code, from, downtime_t, false, |_| true)
+            else {
+                continue;
+            };
+
+            days.get_mut(&key).expect("换辊日").reserved_t -= downtime_t;
+            days.get_mut(&(roll.machine_code.clone(), to_date))
+                .expect("目标日")
+                .reserved_t += downtime_t;
+
+            let shifted_at = to_date.and_time(roll.next_change_at.time());
+            let relief = downtime_t.min(overload);
+            out.push(BottleneckRecommendation {
+                recommendation_id: format!(
+                    "SHIFT_ROLL_CHANGE:{}:{}:{}",
+                    roll.machine_code, from, to_date
+                ),
+                rank: 0,
+                kind: BottleneckActionKind::ShiftRollChange,
+                machine_code: roll.machine_code.clone(),
+                material_id: None,
+                from_date: Some(from),
+                to_date: Some(to_date),
+                roll_change_from: Some(roll.next_change_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+                roll_change_to: Some(shifted_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+                weight_t: downtime_t,
+                urgent_level: None,
+                overload_relief_t: round3(relief),
+                slack_fill_t: 0.0,
+                benefit_score: round3(relief),
+                reason: format!(
+                    "{} {} 换辊停机{}分钟(折算{:.1}t)，平移至{}可消除{:.1}t超目标",
+                    roll.machine_code, from, roll.downtime_minutes, downtime_t, to_date, relief
+                ),
+                predicted_impact: None,
+            });
+        }
+    }
+
+    /// 路径突破待确认材料：原计划日仍有余量时建议确认
+    fn recommend_path_overrides(
+        candidates: &[PathOverrideCandidate],
+        days: &mut BTreeMap<DayKey, DayState>,
+        out: &mut Vec<BottleneckRecommendation>,
+    ) {
+        let mut sorted: Vec<&PathOverrideCandidate> =
+            candidates.iter().filter(|c| c.weight_t > EPS_T).collect();
+        sorted.sort_by(|a, b| {
+            b.urgent_level
+                .cmp(&a.urgent_level)
+                .then_with(|| a.plan_date.cmp(&b.plan_date))
+                .then_with(|| a.material_id.cmp(&b.material_id))
+        });
+
+        for c in sorted {
+            let Some(day) = days.get_mut(&(c.machine_code.clone(), c.plan_date)) else {
+                continue;
+            };
+            if day.slack_t() + EPS_T < c.weight_t {
+                continue;
+            }
+            day.used_t += c.weight_t;
+
+            let benefit = c.weight_t * urgency_factor(c.urgent_level);
+            out.push(BottleneckRecommendation {
+                recommendation_id: format!(
+                    "CONFIRM_PATH_OVERRIDE:{}:{}:{}",
+                    c.material_id, c.machine_code, c.plan_date
+                ),
+                rank: 0,
+                kind: BottleneckActionKind::ConfirmPathOverride,
+                machine_code: c.machine_code.clone(),
+                material_id: Some(c.material_id.clone()),
+                from_date: None,
+                to_date: Some(c.plan_date),
+                roll_change_from: None,
+                roll_change_to: None,
+                weight_t: c.weight_t,
+                urgent_level: Some(c.urgent_level),
+                overload_relief_t: 0.0,
+                slack_fill_t: round3(c.weight_t),
+                benefit_score: round3(benefit),
+                reason: format!(
+                    "{}({}, {:.1}t)因{}待确认，{} {} 有余量可承接",
+                    c.material_id,
+                    c.urgent_level,
+                    c.weight_t,
+                    c.violation_type,
+                    c.machine_code,
+                    c.plan_date
+                ),
+                predicted_impact: None,
+            });
+        }
+    }
+
+    /// 未适温紧急材料（L2+）：放行后落在本机组最早的余量日
+    fn recommend_force_releases(
+        candidates: &[ReleaseCandidate],
+        days: &mut BTreeMap<DayKey, DayState>,
+        out: &mut Vec<BottleneckRecommendation>,
+    ) {
+        let mut sorted: Vec<&ReleaseCandidate> = candidates
+            .iter()
+            .filter(|c| c.urgent_level >= UrgentLevel::L2 && c.weight_t > EPS_T)
+            .collect();
+        sorted.sort_by(|a, b| {
+            b.urgent_level
+                .cmp(&a.urgent_level)
+                .then_with(|| match (a.due_date, b.due_date) {
+                    (None, None) => std::cmp::Ordering::Equal,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (Some(x), Some(y)) => x.cmp(&y),
+                })
+                .then_with(|| a.material_id.cmp(&b.material_id))
+        });
+
+        for c in sorted {
+            let Some(to_date) = days
+                .iter()
+                .find(|((m, _), s)| *m == c.machine_code && s.slack_t() + EPS_T >= c.weight_t)
+                .map(|((_, d), _)| *d)
+            else {
+                continue;
+            };
+            days.get_mut(&(c.machine_code.clone(), to_date))
+                .expect("目标日")
+                .used_t += c.weight_t;
+
+            let benefit = c.weight_t * urgency_factor(c.urgent_level);
+            out.push(BottleneckRecommendation {
+                recommendation_id: format!("FORCE_RELEASE:{}:{}", c.material_id, c.machine_code),
+                rank: 0,
+                kind: BottleneckActionKind::ForceRelease,
+                machine_code: c.machine_code.clone(),
+                material_id: Some(c.material_id.clone()),
+                from_date: None,
+                to_date: Some(to_date),
+                roll_change_from: None,
+                roll_change_to: None,
+                weight_t: c.weight_t,
+                urgent_level: Some(c.urgent_level),
+                overload_relief_t: 0.0,
+                slack_fill_t: round3(c.weight_t),
+                benefit_score: round3(benefit),
+                reason: format!(
+                    "{}({}, {:.1}t)还需{}天适温，强制放行后可落位{} {}余量",
+                    c.material_id,
+                    c.urgent_level,
+                    c.weight_t,
+                    c.ready_in_days,
+                    c.machine_code,
+                    to_date
+                ),
+                predicted_impact: None,
+            });
+        }
+    }
+}
+
+fn round3(v: f64) -> f64 {
+    (v * 1000.0).round() / 1000.0
+}
+
+// ==========================================
+// 测试
+// ==========================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    fn load(day: u32, used: f64) -> MachineDayLoad {
+        MachineDayLoad {
+            machine_code: "H032".to_string(),
+            plan_date: d(day),
+            target_capacity_t: 100.0,
+            limit_capacity_t: 120.0,
+            used_capacity_t: used,
+        }
+    }
+
+    fn item(id: &str, day: u32, weight: f64, level: UrgentLevel, due: Option<u32>) -> MovableItem {
+        MovableItem {
+            material_id: id.to_string(),
+            machine_code: "H032".to_string(),
+            plan_date: d(day),
+            weight_t: weight,
+            urgent_level: level,
+            due_date: due.map(d),
+            earliest_sched_date: None,
+            locked: false,
+        }
+    }
+
+    fn run(
+        loads: &[MachineDayLoad],
+        items: &[MovableItem],
+        releases: &[ReleaseCandidate],
+        overrides: &[PathOverrideCandidate],
+        rolls: &[RollChangePlan],
+    ) -> BottleneckOptimizerOutput {
+        BottleneckOptimizer::recommend(&BottleneckOptimizerInput {
+            loads,
+            items,
+            release_candidates: releases,
+            path_overrides: overrides,
+            roll_changes: rolls,
+        })
+    }
+
+    #[test]
+    fn test_moves_least_urgent_item_to_nearest_slack_day() {
+        let loads = [load(3, 130.0), load(4, 95.0), load(5, 60.0)];
+        let items = [
+            item("U", 3, 40.0, UrgentLevel::L3, Some(10)),
+            item("A", 3, 40.0, UrgentLevel::L0, Some(20)),
+            item("B", 3, 50.0, UrgentLevel::L0, Some(8)),
+        ];
+        let out = run(&loads, &items, &[], &[], &[]);
+
+        // A: L0 且交期最远；4日余量不足，移到5日
+        assert_eq!(out.recommendations.len(), 1);
+        let rec = &out.recommendations[0];
+        assert_eq!(rec.kind, BottleneckActionKind::MoveItem);
+        assert_eq!(rec.material_id.as_deref(), Some("A"));
+        assert_eq!((rec.from_date, rec.to_date), (Some(d(3)), Some(d(5))));
+        assert_eq!(rec.overload_relief_t, 30.0);
+        assert_eq!(rec.recommendation_id, "MOVE_ITEM:A:H032:2026-03-05");
+        assert_eq!(
+            (out.bottleneck_days_before, out.bottleneck_days_after),
+            (1, 0)
+        );
+        assert_eq!((out.overload_before_t, out.overload_after_t), (30.0, 0.0));
+    }
+
+    #[test]
+    fn test_moves_respect_locks_maturity_and_due_date() {
+        let loads = [load(2, 40.0), load(3, 130.0), load(5, 40.0)];
+        let mut locked = item("L", 3, 40.0, UrgentLevel::L0, None);
+        locked.locked = true;
+        let mut immature = item("M", 3, 40.0, UrgentLevel::L0, None);
+        immature.earliest_sched_date = Some(d(3));
+        let items = [
+            locked,
+            // 交期4日：推后到5日超交期，只能提前到2日
+            item("D", 3, 40.0, UrgentLevel::L1, Some(4)),
+            immature,
+        ];
+        let out = run(&loads, &items, &[], &[], &[]);
+
+        let moves: Vec<(&str, NaiveDate)> = out
+            .recommendations
+            .iter()
+            .map(|r| (r.material_id.as_deref().unwrap(), r.to_date.unwrap()))
+            .collect();
+        // M(L0)最早可排3日 -> 只能推后到5日，已消除超目标；L 锁定不动
+        assert_eq!(moves, vec![("M", d(5))]);
+
+        let out = run(&loads, &items[..2], &[], &[], &[]);
+        let moves: Vec<(&str, NaiveDate)> = out
+            .recommendations
+            .iter()
+            .map(|r| (r.material_id.as_deref().unwrap(), r.to_date.unwrap()))
+            .collect();
+        assert_eq!(moves, vec![("D", d(2))]);
+    }
+
+    #[test]
+    fn test_roll_shift_path_override_and_force_release_ranked_by_benefit() {
+        let loads = [load(3, 90.0), load(4, 20.0), load(5, 50.0)];
+        // 3日换辊停机 240 分钟 => 120 × 240 / 1440 = 20t，有效负荷 110 > 100
+        let rolls = [RollChangePlan {
+            machine_code: "H032".to_string(),
+            next_change_at: d(3).and_hms_opt(8, 30, 0).unwrap(),
+            downtime_minutes: 240,
+        }];
+        let overrides = [PathOverrideCandidate {
+            material_id: "P".to_string(),
+            machine_code: "H032".to_string(),
+            plan_date: d(5),
+            weight_t: 30.0,
+            urgent_level: UrgentLevel::L1,
+            violation_type: "WIDTH_JUMP".to_string(),
+        }];
+        let releases = [
+            ReleaseCandidate {
+                material_id: "R".to_string(),
+                machine_code: "H032".to_string(),
+                weight_t: 25.0,
+                urgent_level: UrgentLevel::L3,
+                due_date: Some(d(6)),
+                ready_in_days: 2,
+            },
+            ReleaseCandidate {
+                material_id: "N".to_string(),
+                machine_code: "H032".to_string(),
+                weight_t: 10.0,
+                urgent_level: UrgentLevel::L1,
+                due_date: None,
+                ready_in_days: 1,
+            },
+        ];
+        let out = run(&loads, &[], &releases, &overrides, &rolls);
+
+        let ids: Vec<&str> = out
+            .recommendations
+            .iter()
+            .map(|r| r.recommendation_id.as_str())
+            .collect();
+        // 放行 25×1.0=25 > 路径确认 30×0.5=15 > 换辊消除 10
+        assert_eq!(
+            ids,
+            vec![
+                "FORCE_RELEASE:R:H032",
+                "CONFIRM_PATH_OVERRIDE:P:H032:2026-03-05",
+                "SHIFT_ROLL_CHANGE:H032:2026-03-03:2026-03-04",
+            ]
+        );
+        let roll = &out.recommendations[2];
+        assert_eq!(roll.weight_t, 20.0);
+        assert_eq!(roll.overload_relief_t, 10.0);
+        assert_eq!(roll.roll_change_to.as_deref(), Some("2026-03-04 08:30:00"));
+        // 换辊移入4日后余量 60 => 放行 R 落位4日（最早余量日）
+        assert_eq!(out.recommendations[0].to_date, Some(d(4)));
+        assert_eq!(
+            out.recommendations
+                .iter()
+                .map(|r| r.rank)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(out.bottleneck_days_after, 0);
+    }
+}
@@ -0,0 +1,420 @@
+// ==========================================
+// 热轧精整排产系统 - 机组故障影响分析引擎
+// ==========================================
+// 职责: 对比 故障前试算 与 故障后试算 的排产明细,输出故障影响
+// 输入: 两次 dry-run 的 plan_item + 故障后 机组×日 产能上限
+// 输出: 被挤出材料 / 延后材料 / 新增风险合同(D2口径) / 处置建议
+// ==========================================
+// 口径:
+// - 被挤出: 故障前在窗口内有落位,故障后窗口内无落位
+// - 延后: 故障后落位日期晚于故障前
+// - 新增风险合同: 与 D2 一致 (紧急等级 L1+;临期<=3天且完成率<80%,或已超期),
+//   仅统计故障后命中而故障前未命中的合同
+// - 完成率: 交期前(含)已落位吨位 / 合同在两次试算中出现的总吨位
+// ==========================================
+
+use crate::domain::plan::PlanItem;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// D2 临期阈值（天）
+const NEAR_DUE_DAYS: i64 = 3;
+/// D2 临期完成率阈值
+const NEAR_DUE_COMPLETION_THRESHOLD: f64 = 0.8;
+/// 认为存在可用余量的最小吨位
+const MIN_SLACK_T: f64 = 1.0;
+/// 转机组建议最多条数
+const MAX_ALTERNATE_SUGGESTIONS: usize = 5;
+
+// ==========================================
+// DisplacedMaterial - 受影响材料
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisplacedMaterial {
+    pub material_id: String,
+    pub contract_no: Option<String>,
+    pub urgent_level: Option<String>,
+    pub due_date: Option<String>,
+    pub weight_t: f64,
+
+    // ===== 故障前落位 =====
+    pub machine_code: String,
+    pub baseline_date: NaiveDate,
+
+    // ===== 故障后落位 (被挤出时为空) =====
+    pub whatif_machine_code: Option<String>,
+    pub whatif_date: Option<NaiveDate>,
+    pub delay_days: Option<i64>,
+}
+
+// ==========================================
+// ContractAtRisk - 新增风险合同
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractAtRisk {
+    pub contract_no: String,
+    pub due_date: NaiveDate,
+    pub days_to_due: i64,
+    pub urgent_level: String, // 合同内最高紧急等级
+    pub total_weight_t: f64,
+    pub baseline_completion_rate: f64,
+    pub whatif_completion_rate: f64,
+    pub reason: String,
+}
+
+// ==========================================
+// BreakdownSuggestion - 处置建议
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BreakdownSuggestionKind {
+    AlternateMachine, // 其他机组余量承接
+    CapacityMakeup,   // 故障机组恢复后加产
+    ExpediteContract, // 风险合同提级/锁定
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownSuggestion {
+    pub kind: BreakdownSuggestionKind,
+    pub machine_code: Option<String>,
+    pub plan_date: Option<NaiveDate>,
+    pub contract_no: Option<String>,
+    pub weight_t: f64,
+    pub detail: String,
+}
+
+// ==========================================
+// BreakdownImpact - 分析结果
+// ==========================================
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BreakdownImpact {
+    pub squeezed_out: Vec<DisplacedMaterial>,
+    pub delayed: Vec<DisplacedMaterial>,
+    pub newly_at_risk: Vec<ContractAtRisk>,
+    pub suggestions: Vec<BreakdownSuggestion>,
+    pub squeezed_weight_t: f64,
+}
+
+/// 故障影响分析输入
+pub struct BreakdownImpactInput<'a> {
+    pub baseline_items: &'a [PlanItem],
+    pub whatif_items: &'a [PlanItem],
+    pub machine_code: &'a str,
+    /// 产能受损日期
+    pub outage_dates: &'a [NaiveDate],
+    /// 分析窗口
+    pub date_range: (NaiveDate, NaiveDate),
+    /// 故障后 机组×日 产能上限
+    pub whatif_limits: &'a HashMap<(String, NaiveDate), f64>,
+}
+
+// ==========================================
+// BreakdownImpactEngine
+// ==========================================
+pub struct BreakdownImpactEngine;
+
+impl BreakdownImpactEngine {
+    /// 分析故障影响
+    pub fn analyze(input: &BreakdownImpactInput) -> BreakdownImpact {
+        let whatif_by_id: HashMap<&str, &PlanItem> = input
+            .whatif_items
+            .iter()
+            .map(|i| (i.material_id.as_str(), i))
+            .collect();
+
+        let mut squeezed_out = Vec::new();
+        let mut delayed = Vec::new();
+        for base in input.baseline_items {
+            let after = whatif_by_id.get(base.material_id.as_str());
+            let displaced = |after: Option<&PlanItem>| DisplacedMaterial {
+                material_id: base.material_id.clone(),
+                contract_no: base.contract_no.clone(),
+                urgent_level: base.urgent_level.clone(),
+                due_date: base.due_date.clone(),
+                weight_t: base.weight_t,
+                machine_code: base.machine_code.clone(),
+                baseline_date: base.plan_date,
+                whatif_machine_code: after.map(|a| a.machine_code.clone()),
+                whatif_date: after.map(|a| a.plan_date),
+                delay_days: after.map(|a| (a.plan_date - base.plan_date).num_days()),
+            };
+            match after {
+                None => squeezed_out.push(displaced(None)),
+                Some(after) if after.plan_date > base.plan_date => {
+                    delayed.push(displaced(Some(after)))
+                }
+                Some(_) => {}
+            }
+        }
+        let sort_key = |m: &DisplacedMaterial| (m.baseline_date, m.machine_code.clone());
+        squeezed_out.sort_by_key(sort_key);
+        delayed.sort_by_key(sort_key);
+
+        let newly_at_risk = Self::newly_at_risk_contracts(input);
+        let squeezed_weight_t: f64 = squeezed_out.iter().map(|m| m.weight_t).sum();
+        let suggestions = Self::suggest(input, squeezed_weight_t, &newly_at_risk);
+
+        BreakdownImpact {
+            squeezed_out,
+            delayed,
+            newly_at_risk,
+            suggestions,
+            squeezed_weight_t,
+        }
+    }
+
+    /// D2 口径的新增风险合同
+    fn newly_at_risk_contracts(input: &BreakdownImpactInput) -> Vec<ContractAtRisk> {
+        struct ContractAgg {
+            due_date: NaiveDate,
+            urgent_level: String,
+            weights: HashMap<String, f64>,
+        }
+
+        let mut contracts: BTreeMap<String, ContractAgg> = BTreeMap::new();
+        for item in input.baseline_items.iter().chain(input.whatif_items) {
+            let Some(contract_no) = item.contract_no.as_deref().filter(|c| !c.is_empty()) else {
+                continue;
+            };
+            let Some(due_date) = item
+                .due_date
+                .as_deref()
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            else {
+                continue;
+            };
+            let level = item
+                .urgent_level
+                .clone()
+                .unwrap_or_else(|| "L0".to_string());
+            let agg = contracts
+                .entry(contract_no.to_string())
+                .or_insert_with(|| ContractAgg {
+                    due_date,
+                    urgent_level: level.clone(),
+                    weights: HashMap::new(),
+                });
+            if level > agg.urgent_level {
+                agg.urgent_level = level;
+            }
+            agg.weights.insert(item.material_id.clone(), item.weight_t);
+        }
+
+        let completion = |items: &[PlanItem], contract_no: &str, agg: &ContractAgg| -> f64 {
+            let total: f64 = agg.weights.values().sum();
+            if total <= 0.0 {
+                return 0.0;
+            }
+            let done: f64 = items
+                .iter()
+                .filter(|i| i.contract_no.as_deref() == Some(contract_no))
+                .filter(|i| i.plan_date <= agg.due_date)
+                .map(|i| i.weight_t)
+                .sum();
+            (done / total).min(1.0)
+        };
+        let at_risk = |days_to_due: i64, rate: f64| {
+            days_to_due < 0
+                || (days_to_due <= NEAR_DUE_DAYS && rate < NEAR_DUE_COMPLETION_THRESHOLD)
+        };
+
+        let mut result = Vec::new();
+        for (contract_no, agg) in &contracts {
+            if agg.urgent_level.as_str() < "L1" {
+                continue;
+            }
+            let days_to_due = (agg.due_date - input.date_range.0).num_days();
+            let baseline_rate = completion(input.baseline_items, contract_no, agg);
+            let whatif_rate = completion(input.whatif_items, contract_no, agg);
+            if !at_risk(days_to_due, whatif_rate) || at_risk(days_to_due, baseline_rate) {
+                continue;
+            }
+            result.push(ContractAtRisk {
+                contract_no: contract_no.clone(),
+                due_date: agg.due_date,
+                days_to_due,
+                urgent_level: agg.urgent_level.clone(),
+                total_weight_t: agg.weights.values().sum(),
+                baseline_completion_rate: baseline_rate,
+                whatif_completion_rate: whatif_rate,
+                reason: format!(
+                    "临期 {} 天，完成率 {:.1}% → {:.1}%",
+                    days_to_due,
+                    baseline_rate * 100.0,
+                    whatif_rate * 100.0
+                ),
+            });
+        }
+        result
+    }
+
+    /// 处置建议
+    fn suggest(
+        input: &BreakdownImpactInput,
+        squeezed_weight_t: f64,
+        newly_at_risk: &[ContractAtRisk],
+    ) -> Vec<BreakdownSuggestion> {
+        let mut suggestions = Vec::new();
+
+        if squeezed_weight_t > 0.0 {
+            // 1. 其他机组在故障日的余量
+            let mut used: HashMap<(&str, NaiveDate), f64> = HashMap::new();
+            for item in input.whatif_items {
+                *used
+                    .entry((item.machine_code.as_str(), item.plan_date))
+                    .or_default() += item.weight_t;
+            }
+            let mut slack: Vec<(String, NaiveDate, f64)> = input
+                .whatif_limits
+                .iter()
+                .filter(|((machine, date), _)| {
+                    machine != input.machine_code && input.outage_dates.contains(date)
+                })
+                .map(|((machine, date), limit)| {
+                    let used_t = used.get(&(machine.as_str(), *date)).copied().unwrap_or(0.0);
+                    (machine.clone(), *date, limit - used_t)
+                })
+                .filter(|(_, _, s)| *s >= MIN_SLACK_T)
+                .collect();
+            slack.sort_by(|a, b| {
+                b.2.total_cmp(&a.2)
+                    .then_with(|| (&a.0, a.1).cmp(&(&b.0, b.1)))
+            });
+            for (machine, date, slack_t) in slack.into_iter().take(MAX_ALTERNATE_SUGGESTIONS) {
+                suggestions.push(BreakdownSuggestion {
+                    kind: BreakdownSuggestionKind::AlternateMachine,
+                    machine_code: Some(machine.clone()),
+                    plan_date: Some(date),
+                    contract_no: None,
+                    weight_t: slack_t.min(squeezed_weight_t),
+                    detail: format!(
+                        "{} {} 尚有余量 {:.1}t，可承接可转机组的被挤出材料",
+                        machine, date, slack_t
+                    ),
+                });
+            }
+
+            // 2. 故障机组恢复后首日加产
+            let first_outage = input.outage_dates.iter().min();
+            let recovery_day = input
+                .date_range
+                .0
+                .iter_days()
+                .take_while(|d| *d <= input.date_range.1)
+                .find(|d| !input.outage_dates.contains(d) && first_outage.is_some_and(|f| d > f));
+            if let Some(day) = recovery_day {
+                suggestions.push(BreakdownSuggestion {
+                    kind: BreakdownSuggestionKind::CapacityMakeup,
+                    machine_code: Some(input.machine_code.to_string()),
+                    plan_date: Some(day),
+                    contract_no: None,
+                    weight_t: squeezed_weight_t,
+                    detail: format!(
+                        "{} 恢复后 {} 上调产能上限 {:.1}t 可回收被挤出材料",
+                        input.machine_code, day, squeezed_weight_t
+                    ),
+                });
+            }
+        }
+
+        // 3. 新增风险合同提级
+        for contract in newly_at_risk {
+            let gap_t = contract.total_weight_t
+                * (NEAR_DUE_COMPLETION_THRESHOLD - contract.whatif_completion_rate).max(0.0);
+            suggestions.push(BreakdownSuggestion {
+                kind: BreakdownSuggestionKind::ExpediteContract,
+                machine_code: None,
+                plan_date: Some(contract.due_date),
+                contract_no: Some(contract.contract_no.clone()),
+                weight_t: gap_t,
+                detail: format!(
+                    "合同 {} 交期 {}，建议人工提级或锁定，至少补排 {:.1}t",
+                    contract.contract_no, contract.due_date, gap_t
+                ),
+            });
+        }
+
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, day).unwrap()
+    }
+
+    fn item(id: &str, machine: &str, date: NaiveDate, contract: &str, level: &str) -> PlanItem {
+        PlanItem {
+            version_id: "V1".to_string(),
+            material_id: id.to_string(),
+            machine_code: machine.to_string(),
+            plan_date: date,
+            seq_no: 1,
+            weight_t: 100.0,
+            source_type: "CALC".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            urgent_level: Some(level.to_string()),
+            sched_state: None,
+            assign_reason: None,
+            steel_grade: None,
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: Some(contract.to_string()),
+            due_date: Some(d(3).to_string()),
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+
+    #[test]
+    fn test_analyze_reports_displacement_and_new_risk() {
+        let baseline = vec![
+            item("M1", "H033", d(1), "C1", "L2"),
+            item("M2", "H033", d(1), "C1", "L2"),
+            item("M3", "H033", d(2), "C2", "L0"),
+        ];
+        let whatif = vec![
+            item("M1", "H033", d(1), "C1", "L2"),
+            item("M2", "H033", d(4), "C1", "L2"),
+        ];
+        let limits = HashMap::from([
+            (("H032".to_string(), d(1)), 500.0),
+            (("H033".to_string(), d(1)), 100.0),
+        ]);
+        let outage = [d(1)];
+        let impact = BreakdownImpactEngine::analyze(&BreakdownImpactInput {
+            baseline_items: &baseline,
+            whatif_items: &whatif,
+            machine_code: "H033",
+            outage_dates: &outage,
+            date_range: (d(1), d(5)),
+            whatif_limits: &limits,
+        });
+
+        assert_eq!(impact.squeezed_out.len(), 1);
+        assert_eq!(impact.squeezed_out[0].material_id, "M3");
+        assert_eq!(impact.delayed.len(), 1);
+        assert_eq!(impact.delayed[0].delay_days, Some(3));
+
+        // C1 完成率 100% → 50%，命中 D2 临期口径；C2 为 L0 不计
+        assert_eq!(impact.newly_at_risk.len(), 1);
+        assert_eq!(impact.newly_at_risk[0].contract_no, "C1");
+
+        let kinds: Vec<_> = impact.suggestions.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BreakdownSuggestionKind::AlternateMachine,
+                BreakdownSuggestionKind::CapacityMakeup,
+                BreakdownSuggestionKind::ExpediteContract,
+            ]
+        );
+        assert_eq!(impact.suggestions[1].plan_date, Some(d(2)));
+    }
+}
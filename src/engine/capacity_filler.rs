@@ -9,7 +9,7 @@
 // 输出: plan_item + 更新 material_state.sched_state=SCHEDULED
 // ==========================================
 
-use crate::domain::capacity::{CapacityConstraint, CapacityPool};
+use crate::domain::capacity::{CapacityConstraint, CapacityPool, CapacityReservation};
 use crate::domain::material::{MaterialMaster, MaterialState};
 use crate::domain::plan::PlanItem;
 use crate::domain::types::{PathRuleStatus, SchedState};
@@ -48,6 +48,71 @@ pub struct PathOverridePendingItem {
     pub thickness_delta_mm: f64,
 }
 
+/// 单日产能预留占用（由上层按机组/日期构造，CapacityFiller 填充时更新 used_t）
+///
+/// - held=true: 未到释放点，一般需求不可占用未用预留吨位
+/// - held=false: 已过释放点，未用吨位已释放给一般需求（匹配材料仍计入 used_t 用于利用率统计）
+#[derive(Debug, Clone)]
+pub struct ReservationHold {
+    pub reservation: CapacityReservation,
+    pub held: bool,
+    pub used_t: f64,
+}
+
+impl ReservationHold {
+    pub fn new(reservation: CapacityReservation, held: bool) -> Self {
+        Self {
+            reservation,
+            held,
+            used_t: 0.0,
+        }
+    }
+
+    /// 剩余可占用的预留吨位
+    pub fn remaining_t(&self) -> f64 {
+        (self.reservation.daily_reserved_t - self.used_t).max(0.0)
+    }
+
+    /// 未到释放点的未用预留合计（一般需求不可占用）
+    fn held_unused_t(holds: &[ReservationHold]) -> f64 {
+        holds
+            .iter()
+            .filter(|h| h.held)
+            .map(ReservationHold::remaining_t)
+            .sum()
+    }
+
+    /// 按合同号计算材料可占用的预留吨位：[(hold 下标, 占用吨位)]，按预留顺序依次占满
+    fn plan_consumption(
+        holds: &[ReservationHold],
+        contract_no: Option<&str>,
+        weight_t: f64,
+    ) -> Vec<(usize, f64)> {
+        let mut left = weight_t.max(0.0);
+        let mut plan = Vec::new();
+        for (idx, hold) in holds.iter().enumerate() {
+            if left <= 0.0 {
+                break;
+            }
+            if !hold.reservation.matches_contract(contract_no) {
+                continue;
+            }
+            let take = hold.remaining_t().min(left);
+            if take > 0.0 {
+                plan.push((idx, take));
+                left -= take;
+            }
+        }
+        plan
+    }
+
+    fn apply_consumption(holds: &mut [ReservationHold], plan: &[(usize, f64)]) {
+        for (idx, take) in plan {
+            holds[*idx].used_t += take;
+        }
+    }
+}
+
 impl CapacityFiller {
     /// 构造函数
     ///
@@ -113,6 +178,37 @@ impl CapacityFiller {
         path_rule_engine: Option<&PathRuleEngine>,
         initial_anchor: Option<Anchor>,
         initial_anchor_material_id: Option<String>,
+    ) -> FillSingleDayResult {
+        self.fill_single_day_with_reservations(
+            capacity_pool,
+            candidates,
+            frozen_items,
+            version_id,
+            path_rule_engine,
+            initial_anchor,
+            initial_anchor_material_id,
+            &mut [],
+        )
+    }
+
+    /// 填充产能池（单日单机组）- 支持宽厚路径规则门控 + 产能预留
+    ///
+    /// 预留规则：
+    /// - 冻结/锁定/普通材料按合同号匹配预留，依次占用剩余预留吨位（reservation_holds[].used_t）
+    /// - 未到释放点的未用预留吨位对一般需求不可用：普通材料需满足
+    ///   used + weight + 其他未用预留 <= limit_capacity_t，否则以 CAPACITY_RESERVED 跳过
+    /// - 锁定材料不受预留约束（同 limit 规则）
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_single_day_with_reservations(
+        &self,
+        capacity_pool: &mut CapacityPool,
+        candidates: &[(MaterialMaster, MaterialState)],
+        frozen_items: Vec<PlanItem>,
+        version_id: &str,
+        path_rule_engine: Option<&PathRuleEngine>,
+        initial_anchor: Option<Anchor>,
+        initial_anchor_material_id: Option<String>,
+        reservation_holds: &mut [ReservationHold],
     ) -> FillSingleDayResult {
         let mut plan_items = Vec::new();
         let mut skipped_materials = Vec::new();
@@ -134,6 +230,12 @@ impl CapacityFiller {
             capacity_pool.used_capacity_t += frozen_item.weight_t;
             capacity_pool.frozen_capacity_t += frozen_item.weight_t;
             max_frozen_seq_no = max_frozen_seq_no.max(frozen_item.seq_no);
+            let consumption = ReservationHold::plan_consumption(
+                reservation_holds,
+                frozen_item.contract_no.as_deref(),
+                frozen_item.weight_t,
+            );
+            ReservationHold::apply_consumption(reservation_holds, &consumption);
             plan_items.push(frozen_item);
         }
         let mut sequence_no = max_frozen_seq_no.saturating_add(1).max(1);
@@ -234,6 +336,12 @@ impl CapacityFiller {
                 plan_items.push(plan_item);
                 capacity_pool.used_capacity_t += weight;
                 sequence_no += 1;
+                let consumption = ReservationHold::plan_consumption(
+                    reservation_holds,
+                    master.contract_no.as_deref(),
+                    weight,
+                );
+                ReservationHold::apply_consumption(reservation_holds, &consumption);
 
                 // 入池后更新锚点（宽厚有效时）
                 if dims_valid {
@@ -246,6 +354,20 @@ impl CapacityFiller {
                 continue;
             }
 
+            // 预留占用：匹配材料先占用自身预留，其余未用预留对本材料不可用
+            let consumption = ReservationHold::plan_consumption(
+                reservation_holds,
+                master.contract_no.as_deref(),
+                weight,
+            );
+            let consumed_held_t: f64 = consumption
+                .iter()
+                .filter(|(idx, _)| reservation_holds[*idx].held)
+                .map(|(_, take)| take)
+                .sum();
+            let reserved_for_others_t =
+                (ReservationHold::held_unused_t(reservation_holds) - consumed_held_t).max(0.0);
+
             // 检查是否可以添加（普通材料）
             if !capacity_pool.can_add_material(weight) {
                 // 超过 limit_capacity_t，跳过
@@ -260,8 +382,27 @@ impl CapacityFiller {
                 continue;
             }
 
-            // 普通材料：填充至 target，允许填充到 limit
-            let assign_reason = if capacity_pool.used_capacity_t < capacity_pool.target_capacity_t {
+            if reserved_for_others_t > 0.0
+                && !capacity_pool.can_add_material(weight + reserved_for_others_t)
+            {
+                skipped_materials.push((
+                    master.clone(),
+                    state.clone(),
+                    format!(
+                        "CAPACITY_RESERVED: would use reserved capacity ({} + {} > {} - reserved {:.3})",
+                        capacity_pool.used_capacity_t,
+                        weight,
+                        capacity_pool.limit_capacity_t,
+                        reserved_for_others_t
+                    ),
+                ));
+                continue;
+            }
+
+            // 普通材料：填充至 target，允许填充到 limit（占用预留的材料标记为 FILL_RESERVATION）
+            let assign_reason = if consumed_held_t > 0.0 {
+                "FILL_RESERVATION"
+            } else if capacity_pool.used_capacity_t < capacity_pool.target_capacity_t {
                 "FILL_TO_TARGET"
             } else if capacity_pool.used_capacity_t < capacity_pool.limit_capacity_t {
                 "FILL_TO_LIMIT"
@@ -291,6 +432,7 @@ impl CapacityFiller {
             plan_items.push(plan_item);
             capacity_pool.used_capacity_t += weight;
             sequence_no += 1;
+            ReservationHold::apply_consumption(reservation_holds, &consumption);
 
             // 入池后更新锚点（宽厚有效时）
             if dims_valid {
@@ -0,0 +1,456 @@
+// ==========================================
+// 热轧精整排产系统 - 现场变更通知引擎
+// ==========================================
+// 职责: 对比相邻两个激活版本的 plan_item，按 机组×日期 生成现场变更清单
+// 输入: 旧版本明细 + 新版本明细 + 被移出材料的当前状态
+// 输出: 变更明细（新增/移出/调整/换序，均附原因） + 机组日汇总
+// ==========================================
+// 口径:
+// - 新增: 仅在新版本中
+// - 移出: 仅在旧版本中（原因取材料当前状态）
+// - 调整: 机组或日期变化（明细归属新位置，from_* 记录原位置）
+// - 换序: 机组/日期不变，但与同日其他留存材料的相对顺序变化
+//   （以最长保序子序列为基准，仅报告真正被挪动的材料，
+//    避免插单导致后续序号整体顺延时全部被报告）
+// ==========================================
+
+use crate::domain::plan::PlanItem;
+use crate::domain::types::SchedState;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// ==========================================
+// ChangeNoticeType - 变更类型
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangeNoticeType {
+    Added,       // 新增
+    Removed,     // 移出
+    Moved,       // 机组/日期调整
+    Resequenced, // 同日换序
+}
+
+impl ChangeNoticeType {
+    /// 中文名称（导出用）
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeNoticeType::Added => "新增",
+            ChangeNoticeType::Removed => "移出",
+            ChangeNoticeType::Moved => "调整",
+            ChangeNoticeType::Resequenced => "换序",
+        }
+    }
+}
+
+// ==========================================
+// ChangeNoticeLine - 单条变更
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeNoticeLine {
+    // ===== 归属（新增/调整/换序为新位置，移出为原位置） =====
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub material_id: String,
+    pub change_type: ChangeNoticeType,
+
+    // ===== 原位置 / 新位置 =====
+    pub from_machine_code: Option<String>,
+    pub from_plan_date: Option<NaiveDate>,
+    pub from_seq_no: Option<i32>,
+    pub to_machine_code: Option<String>,
+    pub to_plan_date: Option<NaiveDate>,
+    pub to_seq_no: Option<i32>,
+
+    pub weight_t: f64,
+    pub reason: String,
+}
+
+impl ChangeNoticeLine {
+    /// 是否涉及指定机组（调整类同时匹配原机组）
+    pub fn touches_machine(&self, machine_code: &str) -> bool {
+        self.machine_code == machine_code || self.from_machine_code.as_deref() == Some(machine_code)
+    }
+
+    /// 是否涉及 [from, to] 日期区间（调整类同时匹配原日期）
+    pub fn touches_dates(&self, from: NaiveDate, to: NaiveDate) -> bool {
+        let in_range = |d: NaiveDate| d >= from && d <= to;
+        in_range(self.plan_date) || self.from_plan_date.is_some_and(in_range)
+    }
+}
+
+// ==========================================
+// ChangeNoticeMachineDay - 机组日汇总
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeNoticeMachineDay {
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub added_count: usize,
+    pub removed_count: usize,
+    pub moved_in_count: usize,
+    pub moved_out_count: usize,
+    pub resequenced_count: usize,
+    pub weight_delta_t: f64, // 当日吨位变化（新 - 旧）
+}
+
+// ==========================================
+// ChangeNoticeEngine
+// ==========================================
+pub struct ChangeNoticeEngine;
+
+impl ChangeNoticeEngine {
+    /// 生成变更明细（按 机组 → 日期 → 新序号 → 材料 排序）
+    ///
+    /// # 参数
+    /// - from_items: 旧版本明细（首次激活时为空）
+    /// - to_items: 新版本明细
+    /// - removed_states: 被移出材料的当前排产状态（用于解释移出原因）
+    pub fn diff(
+        from_items: &[PlanItem],
+        to_items: &[PlanItem],
+        removed_states: &HashMap<String, SchedState>,
+    ) -> Vec<ChangeNoticeLine> {
+        let from_map: HashMap<&str, &PlanItem> = from_items
+            .iter()
+            .map(|i| (i.material_id.as_str(), i))
+            .collect();
+        let to_map: HashMap<&str, &PlanItem> = to_items
+            .iter()
+            .map(|i| (i.material_id.as_str(), i))
+            .collect();
+
+        let mut lines = Vec::new();
+        // 机组×日期不变的留存材料，用于换序判定
+        let mut stayed: BTreeMap<(String, NaiveDate), Vec<(&PlanItem, &PlanItem)>> =
+            BTreeMap::new();
+
+        for to in to_items {
+            match from_map.get(to.material_id.as_str()) {
+                None => lines.push(Self::line(
+                    None,
+                    Some(to),
+                    ChangeNoticeType::Added,
+                    Self::added_reason(to),
+                )),
+                Some(from)
+                    if from.machine_code != to.machine_code || from.plan_date != to.plan_date =>
+                {
+                    lines.push(Self::line(
+                        Some(from),
+                        Some(to),
+                        ChangeNoticeType::Moved,
+                        Self::moved_reason(from, to),
+                    ))
+                }
+                Some(from) => stayed
+                    .entry((to.machine_code.clone(), to.plan_date))
+                    .or_default()
+                    .push((from, to)),
+            }
+        }
+
+        for from in from_items {
+            if !to_map.contains_key(from.material_id.as_str()) {
+                let reason = Self::removed_reason(removed_states.get(&from.material_id));
+                lines.push(Self::line(
+                    Some(from),
+                    None,
+                    ChangeNoticeType::Removed,
+                    reason,
+                ));
+            }
+        }
+
+        for (_, mut pairs) in stayed {
+            pairs.sort_by_key(|(from, _)| from.seq_no);
+            let new_ranks: Vec<i32> = pairs.iter().map(|(_, to)| to.seq_no).collect();
+            let keep = Self::longest_increasing_mask(&new_ranks);
+            for ((from, to), kept) in pairs.into_iter().zip(keep) {
+                if !kept {
+                    lines.push(Self::line(
+                        Some(from),
+                        Some(to),
+                        ChangeNoticeType::Resequenced,
+                        format!("同日换序: 序号{}→{}", from.seq_no, to.seq_no),
+                    ));
+                }
+            }
+        }
+
+        lines.sort_by(|a, b| {
+            (
+                &a.machine_code,
+                a.plan_date,
+                a.to_seq_no.or(a.from_seq_no),
+                &a.material_id,
+            )
+                .cmp(&(
+                    &b.machine_code,
+                    b.plan_date,
+                    b.to_seq_no.or(b.from_seq_no),
+                    &b.material_id,
+                ))
+        });
+        lines
+    }
+
+    /// 按 机组×日期 汇总变更（调整类同时计入原机组日的移出和新机组日的移入）
+    pub fn summarize(lines: &[ChangeNoticeLine]) -> Vec<ChangeNoticeMachineDay> {
+        let mut days: BTreeMap<(String, NaiveDate), ChangeNoticeMachineDay> = BTreeMap::new();
+        for line in lines {
+            match line.change_type {
+                ChangeNoticeType::Added => {
+                    let day = Self::day_entry(&mut days, &line.machine_code, line.plan_date);
+                    day.added_count += 1;
+                    day.weight_delta_t += line.weight_t;
+                }
+                ChangeNoticeType::Removed => {
+                    let day = Self::day_entry(&mut days, &line.machine_code, line.plan_date);
+                    day.removed_count += 1;
+                    day.weight_delta_t -= line.weight_t;
+                }
+                ChangeNoticeType::Moved => {
+                    let day = Self::day_entry(&mut days, &line.machine_code, line.plan_date);
+                    day.moved_in_count += 1;
+                    day.weight_delta_t += line.weight_t;
+                    if let (Some(machine), Some(date)) =
+                        (line.from_machine_code.as_deref(), line.from_plan_date)
+                    {
+                        let day = Self::day_entry(&mut days, machine, date);
+                        day.moved_out_count += 1;
+                        day.weight_delta_t -= line.weight_t;
+                    }
+                }
+                ChangeNoticeType::Resequenced => {
+                    Self::day_entry(&mut days, &line.machine_code, line.plan_date)
+                        .resequenced_count += 1;
+                }
+            }
+        }
+
+        days.into_values().collect()
+    }
+
+    fn day_entry<'a>(
+        days: &'a mut BTreeMap<(String, NaiveDate), ChangeNoticeMachineDay>,
+        machine_code: &str,
+        plan_date: NaiveDate,
+    ) -> &'a mut ChangeNoticeMachineDay {
+        days.entry((machine_code.to_string(), plan_date))
+            .or_insert_with(|| ChangeNoticeMachineDay {
+                machine_code: machine_code.to_string(),
+                plan_date,
+                added_count: 0,
+                removed_count: 0,
+                moved_in_count: 0,
+                moved_out_count: 0,
+                resequenced_count: 0,
+                weight_delta_t: 0.0,
+            })
+    }
+
+    fn line(
+        from: Option<&PlanItem>,
+        to: Option<&PlanItem>,
+        change_type: ChangeNoticeType,
+        reason: String,
+    ) -> ChangeNoticeLine {
+        // 归属位置：有新位置取新位置，否则取原位置
+        let anchor = to.or(from).expect("from/to 至少一个非空");
+        ChangeNoticeLine {
+            machine_code: anchor.machine_code.clone(),
+            plan_date: anchor.plan_date,
+            material_id: anchor.material_id.clone(),
+            change_type,
+            from_machine_code: from.map(|i| i.machine_code.clone()),
+            from_plan_date: from.map(|i| i.plan_date),
+            from_seq_no: from.map(|i| i.seq_no),
+            to_machine_code: to.map(|i| i.machine_code.clone()),
+            to_plan_date: to.map(|i| i.plan_date),
+            to_seq_no: to.map(|i| i.seq_no),
+            weight_t: anchor.weight_t,
+            reason,
+        }
+    }
+
+    fn added_reason(to: &PlanItem) -> String {
+        let base = if to.source_type.eq_ignore_ascii_case("MANUAL") {
+            "人工插单"
+        } else {
+            "新纳入排产"
+        };
+        Self::with_assign_reason(base.to_string(), to)
+    }
+
+    fn moved_reason(from: &PlanItem, to: &PlanItem) -> String {
+        let mut parts = Vec::new();
+        if from.machine_code != to.machine_code {
+            parts.push(format!("机组{}→{}", from.machine_code, to.machine_code));
+        }
+        if from.plan_date != to.plan_date {
+            let direction = if to.plan_date < from.plan_date {
+                "提前"
+            } else {
+                "推迟"
+            };
+            parts.push(format!(
+                "{}: {}→{}",
+                direction,
+                from.plan_date.format("%Y-%m-%d"),
+                to.plan_date.format("%Y-%m-%d")
+            ));
+        }
+        if to.source_type.eq_ignore_ascii_case("MANUAL") {
+            parts.push("人工调整".to_string());
+        }
+        Self::with_assign_reason(parts.join("; "), to)
+    }
+
+    fn removed_reason(state: Option<&SchedState>) -> String {
+        match state {
+            Some(SchedState::Completed) => "已完工（实绩回填）".to_string(),
+            Some(SchedState::Blocked) => "数据质量阻断".to_string(),
+            Some(SchedState::PendingMature) => "未适温（冷料）".to_string(),
+            Some(_) => "未纳入新版本（产能/优先级调整）".to_string(),
+            None => "材料状态缺失".to_string(),
+        }
+    }
+
+    fn with_assign_reason(base: String, to: &PlanItem) -> String {
+        match to.assign_reason.as_deref().map(str::trim) {
+            Some(reason) if !reason.is_empty() => format!("{}; 落位原因: {}", base, reason),
+            _ => base,
+        }
+    }
+
+    /// 最长严格递增子序列标记（true = 属于保序子序列）
+    fn longest_increasing_mask(values: &[i32]) -> Vec<bool> {
+        // tails[k]: 长度为 k+1 的递增子序列的末尾下标
+        let mut tails: Vec<usize> = Vec::new();
+        let mut prev: Vec<Option<usize>> = vec![None; values.len()];
+        for (i, v) in values.iter().enumerate() {
+            let pos = tails.partition_point(|&t| values[t] < *v);
+            if pos > 0 {
+                prev[i] = Some(tails[pos - 1]);
+            }
+            if pos == tails.len() {
+                tails.push(i);
+            } else {
+                tails[pos] = i;
+            }
+        }
+
+        let mut mask = vec![false; values.len()];
+        let mut cursor = tails.last().copied();
+        while let Some(i) = cursor {
+            mask[i] = true;
+            cursor = prev[i];
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(material_id: &str, machine: &str, day: u32, seq: i32) -> PlanItem {
+        PlanItem {
+            version_id: "V".to_string(),
+            material_id: material_id.to_string(),
+            machine_code: machine.to_string(),
+            plan_date: NaiveDate::from_ymd_opt(2026, 5, day).unwrap(),
+            seq_no: seq,
+            weight_t: 10.0,
+            source_type: "CALC".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            assign_reason: None,
+            urgent_level: None,
+            sched_state: None,
+            steel_grade: None,
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: None,
+            due_date: None,
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+
+    fn kinds(lines: &[ChangeNoticeLine]) -> Vec<(&str, ChangeNoticeType)> {
+        lines
+            .iter()
+            .map(|l| (l.material_id.as_str(), l.change_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_classifies_changes_with_reasons() {
+        let from = vec![
+            item("A", "H032", 1, 1),
+            item("B", "H032", 1, 2),
+            item("C", "H032", 1, 3),
+            item("D", "H032", 2, 1),
+            item("E", "H033", 1, 1),
+        ];
+        // 插单 N 放在首位（A/B/C 序号整体顺延但相对顺序不变不应报换序）
+        // D 改到 H033；E 完工移出
+        let mut n = item("N", "H032", 1, 1);
+        n.source_type = "MANUAL".to_string();
+        let to = vec![
+            n,
+            item("A", "H032", 1, 2),
+            item("B", "H032", 1, 3),
+            item("C", "H032", 1, 4),
+            item("D", "H033", 2, 1),
+        ];
+        let states: HashMap<String, SchedState> = [("E".to_string(), SchedState::Completed)]
+            .into_iter()
+            .collect();
+
+        let lines = ChangeNoticeEngine::diff(&from, &to, &states);
+        assert_eq!(
+            kinds(&lines),
+            vec![
+                ("N", ChangeNoticeType::Added),
+                ("E", ChangeNoticeType::Removed),
+                ("D", ChangeNoticeType::Moved),
+            ]
+        );
+        assert_eq!(lines[0].reason, "人工插单");
+        assert_eq!(lines[1].reason, "已完工（实绩回填）");
+        assert_eq!(lines[2].reason, "机组H032→H033");
+
+        let days = ChangeNoticeEngine::summarize(&lines);
+        let h032_d2 = days
+            .iter()
+            .find(|d| d.machine_code == "H032" && d.plan_date.to_string() == "2026-05-02")
+            .unwrap();
+        assert_eq!(h032_d2.moved_out_count, 1);
+        assert_eq!(h032_d2.weight_delta_t, -10.0);
+    }
+
+    #[test]
+    fn test_diff_reports_only_truly_resequenced_items() {
+        let from = vec![
+            item("A", "H032", 1, 1),
+            item("B", "H032", 1, 2),
+            item("C", "H032", 1, 3),
+            item("D", "H032", 1, 4),
+        ];
+        // D 提到最前，其余保持相对顺序
+        let to = vec![
+            item("D", "H032", 1, 1),
+            item("A", "H032", 1, 2),
+            item("B", "H032", 1, 3),
+            item("C", "H032", 1, 4),
+        ];
+        let lines = ChangeNoticeEngine::diff(&from, &to, &HashMap::new());
+        assert_eq!(kinds(&lines), vec![("D", ChangeNoticeType::Resequenced)]);
+        assert_eq!(lines[0].reason, "同日换序: 序号4→1");
+    }
+}
@@ -60,6 +60,12 @@ where
         let mut reasons = Vec::new();
         let mut updated_state = state.clone();
 
+        // === 步骤 0: 已完工材料保持终态 (实绩回填后不再参与准入判定) ===
+        if state.sched_state == SchedState::Completed {
+            reasons.push("COMPLETED: production actual recorded".to_string());
+            return Ok((updated_state, reasons));
+        }
+
         // === 步骤 1: 数据质量检查 ===
         let output_age_raw = match material.output_age_days_raw {
             Some(days) if days >= 0 => days,
@@ -418,6 +424,28 @@ mod tests {
             .any(|r| r.contains("output_age_days_raw missing")));
     }
 
+    #[tokio::test]
+    async fn test_evaluate_single_completed_is_terminal() {
+        let config = Arc::new(MockConfigReader);
+        let engine = EligibilityEngine::new(config);
+        let today = NaiveDate::from_ymd_opt(2025, 1, 14).unwrap();
+
+        let material = create_test_material("MAT_DONE");
+        let mut state = create_test_state("MAT_DONE");
+        state.sched_state = SchedState::Completed;
+        state.ready_in_days = 7;
+
+        let (updated_state, reasons) = engine
+            .evaluate_single(&material, &state, today)
+            .await
+            .unwrap();
+
+        // 已完工材料不被重算回 READY，其余字段保持不变
+        assert_eq!(updated_state.sched_state, SchedState::Completed);
+        assert_eq!(updated_state.ready_in_days, 7);
+        assert!(reasons.iter().any(|r| r.contains("COMPLETED")));
+    }
+
     #[tokio::test]
     async fn test_evaluate_batch() {
         let config = Arc::new(MockConfigReader);
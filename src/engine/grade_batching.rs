@@ -0,0 +1,239 @@
+// ==========================================
+// 热轧精整排产系统 - 钢种族集批引擎
+// ==========================================
+// 依据: domain::batching - 钢种族最小批量规则
+// 职责:
+// - 集批: 将同族候选材料聚集为连续批次（在族内首块材料的位置整体入池）
+// - 顺延: 候选+冻结仍不足最小批量，或当日产能装不下最小批量时，整族顺延
+// 红线: 锁定/L3/强制放行材料不可顺延（但计入批量）；所有顺延必须输出 GRADE_BATCHING 原因
+// ==========================================
+
+use crate::domain::batching::{match_batching_rule, BatchRun, GradeBatchingRule};
+use crate::domain::material::{MaterialMaster, MaterialState};
+use crate::domain::plan::PlanItem;
+use crate::domain::types::{SchedState, UrgentLevel};
+use std::collections::{HashMap, HashSet};
+
+/// 材料是否可被集批顺延（锁定、L3 紧急、强制放行材料不顺延）
+fn is_deferrable(state: &MaterialState) -> bool {
+    !matches!(
+        state.sched_state,
+        SchedState::Locked | SchedState::ForceRelease
+    ) && !state.force_release_flag
+        && state.urgent_level != UrgentLevel::L3
+}
+
+/// 单材料批量顺延明细（供上层落库与解释）
+#[derive(Debug, Clone)]
+pub struct BatchingDeferredItem {
+    pub material_id: String,
+    pub grade_family: String,
+    pub reason: String,
+}
+
+/// 集批结果
+#[derive(Debug, Clone, Default)]
+pub struct BatchingPass {
+    /// 入池候选（同族连续）
+    pub candidates: Vec<(MaterialMaster, MaterialState)>,
+    /// 被顺延的材料（原因前缀 GRADE_BATCHING）
+    pub deferred: Vec<(MaterialMaster, MaterialState, String)>,
+    pub deferred_items: Vec<BatchingDeferredItem>,
+}
+
+// ==========================================
+// GradeBatchingEnforcer - 钢种族集批引擎
+// ==========================================
+#[derive(Debug, Clone, Default)]
+pub struct GradeBatchingEnforcer {
+    rules: Vec<GradeBatchingRule>,
+}
+
+impl GradeBatchingEnforcer {
+    pub fn new(rules: Vec<GradeBatchingRule>) -> Self {
+        Self {
+            rules: rules.into_iter().filter(|r| r.is_valid()).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    fn rule_for(&self, machine_code: &str, steel_mark: Option<&str>) -> Option<&GradeBatchingRule> {
+        match_batching_rule(&self.rules, machine_code, steel_mark)
+    }
+
+    fn rule_by_family(&self, family: &str) -> Option<&GradeBatchingRule> {
+        self.rules.iter().find(|r| r.family == family)
+    }
+
+    /// 材料不足的族：候选 + 当日冻结项仍凑不满最小批量
+    ///
+    /// # 返回
+    /// - 族名 → 顺延原因
+    pub fn families_short_of_material(
+        &self,
+        machine_code: &str,
+        candidates: &[(MaterialMaster, MaterialState)],
+        frozen_items: &[PlanItem],
+    ) -> HashMap<String, String> {
+        if self.rules.is_empty() {
+            return HashMap::new();
+        }
+
+        let mut available: HashMap<&str, BatchRun> = HashMap::new();
+        for (master, _) in candidates {
+            if let Some(rule) = self.rule_for(machine_code, master.steel_mark.as_deref()) {
+                available
+                    .entry(rule.family.as_str())
+                    .or_default()
+                    .add(master.weight_t.unwrap_or(0.0));
+            }
+        }
+        for item in frozen_items {
+            if let Some(rule) = self.rule_for(machine_code, item.steel_grade.as_deref()) {
+                available
+                    .entry(rule.family.as_str())
+                    .or_default()
+                    .add(item.weight_t);
+            }
+        }
+
+        available
+            .into_iter()
+            .filter_map(|(family, run)| {
+                let rule = self.rule_by_family(family)?;
+                (!rule.is_satisfied(&run))
+                    .then(|| (family.to_string(), rule.deferral_reason(&run, false)))
+            })
+            .collect()
+    }
+
+    /// 集批：剔除顺延族材料（锁定材料保留原位，L3/强制放行材料不顺延），其余同族材料聚集为连续批次
+    ///
+    /// # 参数
+    /// - candidates: 已排序候选
+    /// - deferred_families: 族名 → 顺延原因
+    pub fn prepare(
+        &self,
+        machine_code: &str,
+        candidates: &[(MaterialMaster, MaterialState)],
+        deferred_families: &HashMap<String, String>,
+    ) -> BatchingPass {
+        if self.rules.is_empty() {
+            return BatchingPass {
+                candidates: candidates.to_vec(),
+                ..Default::default()
+            };
+        }
+
+        let mut pass = BatchingPass::default();
+        let mut members: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut family_of: Vec<Option<&str>> = Vec::with_capacity(candidates.len());
+
+        for (idx, (master, state)) in candidates.iter().enumerate() {
+            let family = self
+                .rule_for(machine_code, master.steel_mark.as_deref())
+                .map(|r| r.family.as_str());
+            match family {
+                Some(f) if state.sched_state != SchedState::Locked => {
+                    if let Some(reason) = deferred_families.get(f) {
+                        // L3/强制放行材料不顺延，保持原位
+                        if !is_deferrable(state) {
+                            family_of.push(None);
+                            continue;
+                        }
+                        pass.deferred
+                            .push((master.clone(), state.clone(), reason.clone()));
+                        pass.deferred_items.push(BatchingDeferredItem {
+                            material_id: master.material_id.clone(),
+                            grade_family: f.to_string(),
+                            reason: reason.clone(),
+                        });
+                        family_of.push(None);
+                        continue;
+                    }
+                    members.entry(f).or_default().push(idx);
+                    family_of.push(Some(f));
+                }
+                // 锁定材料不参与集批重排，保持原位
+                _ => family_of.push(None),
+            }
+        }
+
+        let deferred_ids: HashSet<&str> = pass
+            .deferred_items
+            .iter()
+            .map(|d| d.material_id.as_str())
+            .collect();
+        let mut emitted: HashSet<&str> = HashSet::new();
+        for (idx, candidate) in candidates.iter().enumerate() {
+            if deferred_ids.contains(candidate.0.material_id.as_str()) {
+                continue;
+            }
+            match family_of[idx] {
+                Some(f) => {
+                    if emitted.insert(f) {
+                        pass.candidates
+                            .extend(members[f].iter().map(|&i| candidates[i].clone()));
+                    }
+                }
+                None => pass.candidates.push(candidate.clone()),
+            }
+        }
+
+        pass
+    }
+
+    /// 产能不足的族：已入池批次未达最小批量，且含可顺延（非锁定/L3/强制放行）材料
+    ///
+    /// # 参数
+    /// - plan_items: 填充结果（含冻结项）
+    /// - candidates: 本轮入池候选（用于取新排材料的出钢记号与状态）
+    /// - deferred_families: 已顺延的族（不重复判定）
+    pub fn families_short_of_capacity(
+        &self,
+        machine_code: &str,
+        plan_items: &[PlanItem],
+        candidates: &[(MaterialMaster, MaterialState)],
+        deferred_families: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        if self.rules.is_empty() {
+            return HashMap::new();
+        }
+
+        let by_id: HashMap<&str, &(MaterialMaster, MaterialState)> = candidates
+            .iter()
+            .map(|c| (c.0.material_id.as_str(), c))
+            .collect();
+
+        let mut placed: HashMap<&str, (BatchRun, bool)> = HashMap::new();
+        for item in plan_items {
+            let candidate = by_id.get(item.material_id.as_str());
+            let steel_mark = candidate
+                .and_then(|(m, _)| m.steel_mark.as_deref())
+                .or(item.steel_grade.as_deref());
+            let Some(rule) = self.rule_for(machine_code, steel_mark) else {
+                continue;
+            };
+            if deferred_families.contains_key(&rule.family) {
+                continue;
+            }
+            let entry = placed.entry(rule.family.as_str()).or_default();
+            entry.0.add(item.weight_t);
+            if candidate.is_some_and(|(_, s)| is_deferrable(s)) {
+                entry.1 = true;
+            }
+        }
+
+        placed
+            .into_iter()
+            .filter_map(|(family, (run, deferrable))| {
+                let rule = self.rule_by_family(family)?;
+                (deferrable && !rule.is_satisfied(&run))
+                    .then(|| (family.to_string(), rule.deferral_reason(&run, true)))
+            })
+            .collect()
+    }
+}
@@ -12,7 +12,7 @@
 use crate::config::ImportConfigReader;
 use crate::domain::material::{
     ConflictType, DqLevel, DqReport, DqSummary, DqViolation, ImportBatch, ImportConflict,
-    ImportResult, MaterialMaster, MaterialState, RawMaterialRecord,
+    ImportResult, ImportSourceInfo, MaterialMaster, MaterialState, RawMaterialRecord,
 };
 use crate::engine::MaterialStateDerivationService;
 use crate::repository::material_import_repo::MaterialImportRepository;
@@ -143,6 +143,13 @@ where
             summary,
             violations,
             elapsed_time: elapsed,
+            source_info: ImportSourceInfo {
+                file_format: "CSV".to_string(),
+                encoding: Some("UTF-8".to_string()),
+                header_row: Some(1),
+                ..Default::default()
+            },
+            cancelled: false,
         })
     }
 
@@ -636,6 +643,13 @@ mod tests {
         ) -> Result<usize, Box<dyn Error>> {
             Ok(0)
         }
+        async fn batch_update_contract_fields(
+            &self,
+            _materials: Vec<MaterialMaster>,
+            _states: Vec<MaterialState>,
+        ) -> Result<usize, Box<dyn Error>> {
+            Ok(0)
+        }
         async fn insert_conflict(&self, _conflict: ImportConflict) -> Result<(), Box<dyn Error>> {
             Ok(())
         }
@@ -678,6 +692,12 @@ mod tests {
         ) -> Result<Vec<String>, Box<dyn Error>> {
             Ok(vec![])
         }
+        async fn find_materials_by_contract_nos(
+            &self,
+            _contract_nos: Vec<String>,
+        ) -> Result<Vec<(MaterialMaster, MaterialState)>, Box<dyn Error>> {
+            Ok(vec![])
+        }
         async fn count_materials(&self) -> Result<usize, Box<dyn Error>> {
             Ok(0)
         }
@@ -9,43 +9,85 @@
 // ==========================================
 
 pub mod anchor_resolver;
+pub mod bottleneck_optimizer;
+pub mod breakdown_impact;
 pub mod capacity_filler;
+pub mod change_notice;
 pub mod eligibility;
 pub mod eligibility_core;
 pub mod events;
+pub mod grade_batching;
 pub mod impact_summary;
 pub mod importer;
 pub mod material_state_derivation;
 pub mod orchestrator;
 pub mod path_rule;
+pub mod plan_adherence;
+pub mod plan_evaluation;
 pub mod priority;
 pub mod recalc;
 pub mod repositories;
 pub mod risk;
 pub mod roll_campaign;
+pub mod rush_insertion;
 pub mod strategy;
+pub mod strategy_sweep;
 pub mod structure;
 pub mod urgency;
+pub mod version_diff;
+pub mod version_merge;
+pub mod weight_learning;
 
 // 重导出核心引擎
 pub use anchor_resolver::{AnchorResolver, MaterialSummary, ResolvedAnchor, SeedS2Config};
-pub use capacity_filler::CapacityFiller;
+pub use bottleneck_optimizer::{
+    BottleneckActionKind, BottleneckOptimizer, BottleneckOptimizerInput, BottleneckOptimizerOutput,
+    BottleneckRecommendation, MachineDayLoad, MovableItem, PathOverrideCandidate, ReleaseCandidate,
+    RollChangePlan,
+};
+pub use breakdown_impact::{
+    BreakdownImpact, BreakdownImpactEngine, BreakdownImpactInput, BreakdownSuggestion,
+    BreakdownSuggestionKind, ContractAtRisk, DisplacedMaterial,
+};
+pub use capacity_filler::{CapacityFiller, ReservationHold};
+pub use change_notice::{
+    ChangeNoticeEngine, ChangeNoticeLine, ChangeNoticeMachineDay, ChangeNoticeType,
+};
 pub use eligibility::EligibilityEngine;
 pub use eligibility_core::EligibilityCore;
 pub use events::{
     NoOpEventPublisher, OptionalEventPublisher, ScheduleEvent, ScheduleEventPublisher,
     ScheduleEventType,
 };
+pub use grade_batching::{BatchingDeferredItem, BatchingPass, GradeBatchingEnforcer};
 pub use impact_summary::ImpactSummaryEngine;
 pub use importer::MaterialImporter;
 pub use material_state_derivation::MaterialStateDerivationService;
 pub use orchestrator::{ScheduleOrchestrator, ScheduleResult};
 pub use path_rule::{Anchor, PathRuleConfig, PathRuleEngine, PathRuleResult};
+pub use plan_adherence::{AdherenceOutcome, PlanAdherenceEngine};
+pub use plan_evaluation::{PlanEvaluationContext, PlanEvaluator, PlanObjectiveVector};
 pub use priority::PrioritySorter;
 pub use recalc::{RecalcConfig, RecalcEngine, RecalcResult};
 pub use repositories::ScheduleRepositories;
 pub use risk::RiskEngine;
 pub use roll_campaign::RollCampaignEngine;
+pub use rush_insertion::{
+    DisplacedItem, RushInfeasible, RushInsertionEngine, RushInsertionInput, RushInsertionOutcome,
+    RushMaterial, RushPlacement,
+};
 pub use strategy::ScheduleStrategy;
+pub use strategy_sweep::{
+    StrategySweepEngine, StrategySweepPoint, StrategySweepSpec, SweepSampleMode, WeightRange,
+};
 pub use structure::{StructureCorrector, StructureViolationReport};
 pub use urgency::UrgencyEngine;
+pub use version_diff::{ContractDeliveryDelta, GradeMixDelta, SequenceDelta, VersionDiffEngine};
+pub use version_merge::{
+    ManualChange, MergeConflict, MergeConflictType, MergeContext, MergeMaterialFacts, MergeOutcome,
+    VersionMergeEngine,
+};
+pub use weight_learning::{
+    AgreementRate, PreferencePair, UrgencyRuleSuggestion, WeightLearningEngine,
+    WeightLearningOutcome,
+};
@@ -7,6 +7,7 @@
 
 use crate::config::strategy_profile::CustomStrategyParameters;
 use crate::config::ImportConfigReader;
+use crate::domain::batching::GradeBatchingRule;
 use crate::domain::capacity::CapacityPool;
 use crate::domain::material::{MaterialMaster, MaterialState};
 use crate::domain::plan::PlanItem;
@@ -14,8 +15,9 @@ use crate::domain::types::{SchedState, UrgentLevel};
 use crate::engine::capacity_filler::PathOverridePendingItem;
 use crate::engine::strategy::ScheduleStrategy;
 use crate::engine::{
-    Anchor, CapacityFiller, EligibilityEngine, PathRuleEngine, PrioritySorter, StructureCorrector,
-    StructureViolationReport, UrgencyEngine,
+    Anchor, BatchingDeferredItem, CapacityFiller, EligibilityEngine, GradeBatchingEnforcer,
+    PathRuleEngine, PrioritySorter, ReservationHold, StructureCorrector, StructureViolationReport,
+    UrgencyEngine,
 };
 use chrono::NaiveDate;
 use std::collections::{HashMap, HashSet};
@@ -45,6 +47,9 @@ pub struct ScheduleResult {
     pub path_override_pending: Vec<PathOverridePendingItem>,
     pub updated_capacity_pool: CapacityPool,
 
+    // Grade Batching 输出（同时以 GRADE_BATCHING 原因计入 skipped_materials）
+    pub batching_deferred: Vec<BatchingDeferredItem>,
+
     // Path Rule / RollCycle 输出（锚点状态）
     pub roll_cycle_anchor: Option<Anchor>,
     pub roll_cycle_anchor_material_id: Option<String>,
@@ -67,6 +72,7 @@ where
     sorter: PrioritySorter,
     filler: CapacityFiller,
     structure: StructureCorrector,
+    batching: GradeBatchingEnforcer,
     strategy: ScheduleStrategy,
     strategy_params: Option<CustomStrategyParameters>,
 }
@@ -90,6 +96,7 @@ where
             sorter: PrioritySorter::new(),
             filler: CapacityFiller::new(),
             structure: StructureCorrector::new(),
+            batching: GradeBatchingEnforcer::default(),
             config,
             strategy,
             strategy_params: None,
@@ -107,12 +114,19 @@ where
             sorter: PrioritySorter::new(),
             filler: CapacityFiller::new(),
             structure: StructureCorrector::new(),
+            batching: GradeBatchingEnforcer::default(),
             config,
             strategy,
             strategy_params: Some(params),
         }
     }
 
+    /// 设置钢种族最小批量规则（为空时不做集批）
+    pub fn with_grade_batching_rules(mut self, rules: Vec<GradeBatchingRule>) -> Self {
+        self.batching = GradeBatchingEnforcer::new(rules);
+        self
+    }
+
     /// 执行完整排产流程（单日单机组）
     ///
     /// # 参数
@@ -151,11 +165,14 @@ where
             None,
             None,
             None,
+            &mut [],
         )
         .await
     }
 
-    /// 执行完整排产流程（单日单机组）- 支持宽厚路径规则门控
+    /// 执行完整排产流程（单日单机组）- 支持宽厚路径规则门控 + 产能预留
+    ///
+    /// reservation_holds: 当日机组的产能预留占用（填充时更新 used_t，供上层汇总利用率）
     #[allow(clippy::too_many_arguments)]
     pub async fn execute_single_day_schedule_with_path_rule<'a>(
         &self,
@@ -171,6 +188,7 @@ where
         initial_anchor: Option<Anchor>,
         initial_anchor_material_id: Option<String>,
         priority_boost_material_ids: Option<&std::collections::HashSet<String>>,
+        reservation_holds: &mut [ReservationHold],
     ) -> Result<ScheduleResult, Box<dyn Error>> {
         info!(
             machine_code = %capacity_pool.machine_code,
@@ -296,30 +314,63 @@ where
         info!(sorted_count = sorted_materials.len(), "等级内排序完成");
 
         // ==========================================
-        // 步骤4: Capacity Filler - 产能池填充
+        // 步骤4: Capacity Filler - 产能池填充（含钢种族集批）
         // ==========================================
         debug!("步骤4: 执行产能池填充");
 
-        let fill_result = self.filler.fill_single_day_with_path_rule(
-            capacity_pool,
+        // 集批：同族材料连续入池；材料不足或当日装不下最小批量的族整族顺延后重新填充
+        // 每轮从原始产能池/预留占用/锚点重新开始，保证顺延后释放的产能可被其他材料使用
+        let machine_code = capacity_pool.machine_code.clone();
+        let mut deferred_families = self.batching.families_short_of_material(
+            &machine_code,
             &sorted_materials,
-            frozen_items,
-            version_id,
-            path_rule_engine,
-            initial_anchor,
-            initial_anchor_material_id,
+            &frozen_items,
         );
+        let (fill_result, batching_pass) = loop {
+            let pass = self
+                .batching
+                .prepare(&machine_code, &sorted_materials, &deferred_families);
+            let mut pool = capacity_pool.clone();
+            let mut holds = reservation_holds.to_vec();
+            let result = self.filler.fill_single_day_with_reservations(
+                &mut pool,
+                &pass.candidates,
+                frozen_items.clone(),
+                version_id,
+                path_rule_engine,
+                initial_anchor,
+                initial_anchor_material_id.clone(),
+                &mut holds,
+            );
+
+            let short = self.batching.families_short_of_capacity(
+                &machine_code,
+                &result.plan_items,
+                &pass.candidates,
+                &deferred_families,
+            );
+            if short.is_empty() {
+                *capacity_pool = pool;
+                reservation_holds.clone_from_slice(&holds);
+                break (result, pass);
+            }
+            debug!(families = ?short.keys().collect::<Vec<_>>(), "钢种族批次未达最小批量，整族顺延后重新填充");
+            deferred_families.extend(short);
+        };
         let crate::engine::capacity_filler::FillSingleDayResult {
             plan_items,
-            skipped_materials,
+            skipped_materials: fill_skipped,
             path_override_pending,
             final_anchor,
             final_anchor_material_id,
         } = fill_result;
+        let mut skipped_materials = batching_pass.deferred;
+        skipped_materials.extend(fill_skipped);
 
         info!(
             plan_items_count = plan_items.len(),
             skipped_count = skipped_materials.len(),
+            batching_deferred_count = batching_pass.deferred_items.len(),
             used_capacity = capacity_pool.used_capacity_t,
             "产能池填充完成"
         );
@@ -370,6 +421,7 @@ where
             skipped_materials,
             path_override_pending,
             updated_capacity_pool: capacity_pool.clone(),
+            batching_deferred: batching_pass.deferred_items,
             roll_cycle_anchor: final_anchor,
             roll_cycle_anchor_material_id: final_anchor_material_id,
             structure_report,
@@ -0,0 +1,370 @@
+// ==========================================
+// 热轧精整排产系统 - 计划执行对账引擎
+// ==========================================
+// 职责: 计划明细 vs 生产实绩 按 机组×日 对账
+// 输入: plan_item (激活版本) + production_actual + 已完工材料集合
+// 输出: plan_adherence_daily 记录 + 未完工顺延项
+// ==========================================
+// 对账口径:
+// - 对账窗口: 每个机组取实绩完工日期的 [最早, 最晚] 区间
+// - 按计划完工: 材料在计划的 机组×日 上有实绩
+// - 计划外完工: 当日该机组有实绩但不在当日计划内
+// - 顺延: 计划材料在任意日期/机组均未完工
+// - 顺序偏差: 按计划完工材料的 计划位次 vs 完工时间位次
+// ==========================================
+
+use crate::domain::plan::PlanItem;
+use crate::domain::production::{CarryOverItem, PlanAdherenceRecord, ProductionActual};
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// ==========================================
+// AdherenceOutcome - 对账结果
+// ==========================================
+#[derive(Debug, Clone, Default)]
+pub struct AdherenceOutcome {
+    pub records: Vec<PlanAdherenceRecord>,
+    pub carry_overs: Vec<CarryOverItem>,
+}
+
+// ==========================================
+// PlanAdherenceEngine - 计划执行对账引擎
+// ==========================================
+// 红线: 无状态引擎,所有方法都是纯函数
+pub struct PlanAdherenceEngine;
+
+impl PlanAdherenceEngine {
+    /// 创建新的对账引擎
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 执行对账
+    ///
+    /// # 参数
+    /// - `version_id`: 对账版本
+    /// - `plan_items`: 版本计划明细 (可含窗口外数据,引擎内过滤)
+    /// - `actuals`: 对账窗口内的全部实绩 (含历史批次,保证重复对账幂等)
+    /// - `completed_ids`: 已完工材料集合 (任意日期/机组)
+    /// - `batch_id`: 触发对账的实绩批次
+    /// - `generated_at`: 对账时间
+    pub fn reconcile(
+        &self,
+        version_id: &str,
+        plan_items: &[PlanItem],
+        actuals: &[ProductionActual],
+        completed_ids: &HashSet<String>,
+        batch_id: Option<&str>,
+        generated_at: NaiveDateTime,
+    ) -> AdherenceOutcome {
+        // 1. 每个机组的对账窗口
+        let mut windows: HashMap<&str, (NaiveDate, NaiveDate)> = HashMap::new();
+        for actual in actuals {
+            let date = actual.completed_date();
+            windows
+                .entry(actual.machine_code.as_str())
+                .and_modify(|(from, to)| {
+                    *from = (*from).min(date);
+                    *to = (*to).max(date);
+                })
+                .or_insert((date, date));
+        }
+
+        // 2. 按 机组×日 分组 (BTreeMap 保证输出顺序稳定)
+        let mut planned_by_day: BTreeMap<(String, NaiveDate), Vec<&PlanItem>> = BTreeMap::new();
+        for item in plan_items {
+            let Some((from, to)) = windows.get(item.machine_code.as_str()) else {
+                continue;
+            };
+            if item.plan_date < *from || item.plan_date > *to {
+                continue;
+            }
+            planned_by_day
+                .entry((item.machine_code.clone(), item.plan_date))
+                .or_default()
+                .push(item);
+        }
+
+        let mut actual_by_day: BTreeMap<(String, NaiveDate), Vec<&ProductionActual>> =
+            BTreeMap::new();
+        for actual in actuals {
+            actual_by_day
+                .entry((actual.machine_code.clone(), actual.completed_date()))
+                .or_default()
+                .push(actual);
+        }
+
+        let mut keys: Vec<(String, NaiveDate)> = planned_by_day.keys().cloned().collect();
+        keys.extend(actual_by_day.keys().cloned());
+        keys.sort();
+        keys.dedup();
+
+        // 3. 逐日对账
+        let mut outcome = AdherenceOutcome::default();
+        for key in keys {
+            let mut planned = planned_by_day.remove(&key).unwrap_or_default();
+            let mut day_actuals = actual_by_day.remove(&key).unwrap_or_default();
+            planned.sort_by_key(|p| p.seq_no);
+            day_actuals.sort_by(|a, b| {
+                a.completed_at
+                    .cmp(&b.completed_at)
+                    .then_with(|| a.material_id.cmp(&b.material_id))
+            });
+
+            let planned_ids: HashSet<&str> =
+                planned.iter().map(|p| p.material_id.as_str()).collect();
+            let actual_ids: HashSet<&str> =
+                day_actuals.iter().map(|a| a.material_id.as_str()).collect();
+
+            let matched: Vec<&PlanItem> = planned
+                .iter()
+                .copied()
+                .filter(|p| actual_ids.contains(p.material_id.as_str()))
+                .collect();
+            let unplanned_count = day_actuals
+                .iter()
+                .filter(|a| !planned_ids.contains(a.material_id.as_str()))
+                .count();
+
+            for item in planned
+                .iter()
+                .filter(|p| !completed_ids.contains(&p.material_id))
+            {
+                outcome.carry_overs.push(CarryOverItem {
+                    version_id: version_id.to_string(),
+                    material_id: item.material_id.clone(),
+                    machine_code: item.machine_code.clone(),
+                    plan_date: item.plan_date,
+                    seq_no: item.seq_no,
+                    weight_t: item.weight_t,
+                    detected_at: generated_at,
+                    resolved_at: None,
+                });
+            }
+
+            let displacements = Self::sequence_displacements(&matched, &day_actuals);
+            let deviation_count = displacements.iter().filter(|d| **d > 0).count();
+            let max_displacement = displacements.iter().copied().max().unwrap_or(0);
+            let avg_displacement = if displacements.is_empty() {
+                0.0
+            } else {
+                displacements.iter().sum::<i32>() as f64 / displacements.len() as f64
+            };
+
+            let planned_weight_t: f64 = planned.iter().map(|p| p.weight_t).sum();
+            let matched_weight_t: f64 = matched.iter().map(|p| p.weight_t).sum();
+            let actual_weight_t: f64 = day_actuals.iter().map(|a| a.actual_weight_t).sum();
+            let adherence_rate = if planned_weight_t > 0.0 {
+                Some(matched_weight_t / planned_weight_t)
+            } else {
+                None
+            };
+
+            outcome.records.push(PlanAdherenceRecord {
+                version_id: version_id.to_string(),
+                machine_code: key.0,
+                plan_date: key.1,
+                planned_count: planned.len() as i32,
+                planned_weight_t,
+                actual_count: day_actuals.len() as i32,
+                actual_weight_t,
+                matched_count: matched.len() as i32,
+                matched_weight_t,
+                unplanned_count: unplanned_count as i32,
+                carry_over_count: planned
+                    .iter()
+                    .filter(|p| !completed_ids.contains(&p.material_id))
+                    .count() as i32,
+                sequence_deviation_count: deviation_count as i32,
+                max_sequence_displacement: max_displacement,
+                avg_sequence_displacement: avg_displacement,
+                adherence_rate,
+                batch_id: batch_id.map(str::to_string),
+                generated_at,
+            });
+        }
+
+        outcome
+    }
+
+    /// 计算按计划完工材料的位次偏移
+    ///
+    /// 计划位次: matched 按 seq_no 排序后的下标
+    /// 实际位次: 同一批材料按完工时间排序后的下标
+    fn sequence_displacements(
+        matched: &[&PlanItem],
+        day_actuals: &[&ProductionActual],
+    ) -> Vec<i32> {
+        let matched_ids: HashSet<&str> = matched.iter().map(|p| p.material_id.as_str()).collect();
+        let actual_rank: HashMap<&str, usize> = day_actuals
+            .iter()
+            .filter(|a| matched_ids.contains(a.material_id.as_str()))
+            .enumerate()
+            .map(|(rank, a)| (a.material_id.as_str(), rank))
+            .collect();
+
+        matched
+            .iter()
+            .enumerate()
+            .filter_map(|(planned_rank, p)| {
+                let actual = actual_rank.get(p.material_id.as_str())?;
+                Some((planned_rank as i32 - *actual as i32).abs())
+            })
+            .collect()
+    }
+}
+
+impl Default for PlanAdherenceEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        d(day).and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn plan(material_id: &str, machine: &str, day: u32, seq_no: i32, weight_t: f64) -> PlanItem {
+        PlanItem {
+            version_id: "V1".to_string(),
+            material_id: material_id.to_string(),
+            machine_code: machine.to_string(),
+            plan_date: d(day),
+            seq_no,
+            weight_t,
+            source_type: "CALC".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            urgent_level: None,
+            sched_state: None,
+            assign_reason: None,
+            steel_grade: None,
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: None,
+            due_date: None,
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+
+    fn actual(material_id: &str, machine: &str, day: u32, hour: u32, w: f64) -> ProductionActual {
+        ProductionActual {
+            material_id: material_id.to_string(),
+            machine_code: machine.to_string(),
+            completed_at: at(day, hour),
+            actual_weight_t: w,
+        }
+    }
+
+    fn completed(actuals: &[ProductionActual]) -> HashSet<String> {
+        actuals.iter().map(|a| a.material_id.clone()).collect()
+    }
+
+    #[test]
+    fn test_reconcile_matched_unplanned_and_carry_over() {
+        let items = vec![
+            plan("M1", "H032", 2, 1, 10.0),
+            plan("M2", "H032", 2, 2, 20.0),
+            plan("M3", "H032", 2, 3, 30.0),
+        ];
+        let actuals = vec![
+            actual("M1", "H032", 2, 8, 9.8),
+            actual("M2", "H032", 2, 9, 20.5),
+            actual("X9", "H032", 2, 10, 5.0),
+        ];
+
+        let outcome = PlanAdherenceEngine::new().reconcile(
+            "V1",
+            &items,
+            &actuals,
+            &completed(&actuals),
+            Some("B1"),
+            at(3, 0),
+        );
+
+        assert_eq!(outcome.records.len(), 1);
+        let r = &outcome.records[0];
+        assert_eq!(r.planned_count, 3);
+        assert_eq!(r.matched_count, 2);
+        assert_eq!(r.unplanned_count, 1);
+        assert_eq!(r.carry_over_count, 1);
+        assert!((r.planned_weight_t - 60.0).abs() < 1e-9);
+        assert!((r.actual_weight_t - 35.3).abs() < 1e-9);
+        assert!((r.adherence_rate.unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(r.sequence_deviation_count, 0);
+
+        assert_eq!(outcome.carry_overs.len(), 1);
+        assert_eq!(outcome.carry_overs[0].material_id, "M3");
+    }
+
+    #[test]
+    fn test_reconcile_sequence_deviation() {
+        let items = vec![
+            plan("M1", "H032", 2, 1, 10.0),
+            plan("M2", "H032", 2, 2, 10.0),
+            plan("M3", "H032", 2, 3, 10.0),
+        ];
+        // 实际顺序: M3 → M1 → M2
+        let actuals = vec![
+            actual("M3", "H032", 2, 6, 10.0),
+            actual("M1", "H032", 2, 7, 10.0),
+            actual("M2", "H032", 2, 8, 10.0),
+        ];
+
+        let outcome = PlanAdherenceEngine::new().reconcile(
+            "V1",
+            &items,
+            &actuals,
+            &completed(&actuals),
+            None,
+            at(3, 0),
+        );
+
+        let r = &outcome.records[0];
+        assert_eq!(r.sequence_deviation_count, 3);
+        assert_eq!(r.max_sequence_displacement, 2);
+        assert!((r.avg_sequence_displacement - 4.0 / 3.0).abs() < 1e-9);
+        assert_eq!(r.adherence_rate, Some(1.0));
+    }
+
+    #[test]
+    fn test_reconcile_window_and_completed_elsewhere() {
+        let items = vec![
+            plan("M1", "H032", 1, 1, 10.0), // 窗口外,忽略
+            plan("M2", "H032", 2, 1, 10.0), // 窗口外,3 日完工计为当日计划外
+            plan("M3", "H032", 3, 1, 10.0), // 当日未完工 → 顺延
+            plan("M4", "H033", 2, 1, 10.0), // 机组无实绩,忽略
+        ];
+        let actuals = vec![actual("M2", "H032", 3, 9, 10.0)];
+        let mut done = completed(&actuals);
+        done.insert("M9".to_string());
+
+        let outcome =
+            PlanAdherenceEngine::new().reconcile("V1", &items, &actuals, &done, None, at(4, 0));
+
+        assert_eq!(outcome.records.len(), 1);
+        let r = &outcome.records[0];
+        assert_eq!(r.plan_date, d(3));
+        assert_eq!(r.planned_count, 1);
+        assert_eq!(r.matched_count, 0);
+        assert_eq!(r.unplanned_count, 1);
+        assert_eq!(r.adherence_rate, Some(0.0));
+
+        let carried: Vec<&str> = outcome
+            .carry_overs
+            .iter()
+            .map(|c| c.material_id.as_str())
+            .collect();
+        assert_eq!(carried, vec!["M3"]);
+    }
+}
@@ -0,0 +1,415 @@
+// ==========================================
+// 热轧精整排产系统 - 方案多目标评价引擎
+// ==========================================
+// 职责: 对任意版本 / dry-run 试算结果的 plan_item 计算统一的目标向量
+// 使用方: 策略草案摘要、版本 KPI 对比、决策层驾驶舱（各界面同一口径）
+// 输入: plan_item（已由 API 层补充 steel_grade/due_date 快照）+ 评价范围内的产能池 + 评价上下文
+// ==========================================
+// 口径:
+// - 拖期天数 = max(0, plan_date - 材料交期)；仅统计已排产且有交期的材料
+// - 准时吨位率 = 准时吨位 / 有交期吨位；无交期吨位时记 1.0
+// - 产能已用吨位按明细重新汇总（不读 capacity_pool.used_capacity_t，dry-run 结果同样适用）；
+//   目标/上限取评价范围内的产能池；超限吨位 = Σ max(0, 已用 - 上限)（按 机组×日）
+// - 结构偏差: 按 机组×日 计算钢种配比相对目标配比的最大偏差（StructureCorrector 口径），按吨位加权平均
+// - 路径突破: 明细中已人工确认突破宽厚路径规则的材料数
+// - 换辊次数: 按机组沿 (日期, 序号) 累计吨位，达到换辊建议阈值计一次（估算，不含评价起点前已轧吨位）
+// - 冷料去库: 库龄 >= 冷料阈值 的已排材料；库龄削减 = Σ 这些材料的库龄天数
+// - 换钢种次数: 同机组同日相邻两块材料钢种不同计一次（缺钢种的材料跳过）
+// ==========================================
+
+use crate::domain::capacity::CapacityPool;
+use crate::domain::plan::PlanItem;
+use crate::engine::structure::StructureCorrector;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// 冷料库龄阈值默认值（天，与 balanced 预设策略 cold_stock_age_threshold_days 一致）
+pub const DEFAULT_COLD_STOCK_AGE_DAYS: i32 = 30;
+
+/// 结构偏差阈值默认值（与 deviation_threshold 配置默认值一致）
+pub const DEFAULT_STRUCTURE_DEVIATION_THRESHOLD: f64 = 0.1;
+
+/// 换辊建议阈值默认值（吨，与 RollCampaignEngine 默认值一致）
+pub const DEFAULT_ROLL_SUGGEST_THRESHOLD_T: f64 = 1500.0;
+
+// ==========================================
+// PlanEvaluationContext - 评价上下文
+// ==========================================
+#[derive(Debug, Clone)]
+pub struct PlanEvaluationContext {
+    pub stock_age_days: HashMap<String, i32>, // material_id → 库龄（天）
+    pub path_override_material_ids: HashSet<String>, // 已人工确认突破路径规则的材料
+    pub target_ratio: HashMap<String, f64>,   // 目标钢种配比（为空则不计结构偏差）
+    pub deviation_threshold: f64,
+    pub cold_stock_age_threshold_days: i32,
+    pub roll_suggest_threshold_t: f64,
+}
+
+impl Default for PlanEvaluationContext {
+    fn default() -> Self {
+        Self {
+            stock_age_days: HashMap::new(),
+            path_override_material_ids: HashSet::new(),
+            target_ratio: HashMap::new(),
+            deviation_threshold: DEFAULT_STRUCTURE_DEVIATION_THRESHOLD,
+            cold_stock_age_threshold_days: DEFAULT_COLD_STOCK_AGE_DAYS,
+            roll_suggest_threshold_t: DEFAULT_ROLL_SUGGEST_THRESHOLD_T,
+        }
+    }
+}
+
+// ==========================================
+// PlanObjectiveVector - 标准目标向量
+// ==========================================
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlanObjectiveVector {
+    pub plan_items_count: usize,
+    pub total_weight_t: f64,
+
+    // ===== 交付 =====
+    pub due_items_count: usize, // 有交期的已排材料数
+    pub tardy_count: usize,
+    pub total_tardiness_days: i64,
+    pub max_tardiness_days: i64,
+    pub on_time_weight_t: f64,
+    pub due_weight_t: f64,
+    pub on_time_weight_rate: f64, // [0,1]
+
+    // ===== 产能 =====
+    pub capacity_used_t: f64,
+    pub capacity_target_t: f64,
+    pub capacity_limit_t: f64,
+    pub capacity_util_pct: f64, // 已用 / 目标 × 100
+    pub overflow_t: f64,
+    pub overflow_machine_days: usize,
+
+    // ===== 结构 =====
+    pub structure_deviation: f64,        // 吨位加权平均最大偏差 [0,1]
+    pub structure_violation_days: usize, // 偏差超过阈值的 机组×日 数
+
+    // ===== 路径/换辊/换钢种 =====
+    pub path_override_count: usize,
+    pub roll_change_count: usize,
+    pub changeover_count: usize,
+
+    // ===== 冷料 =====
+    pub cold_stock_cleared_count: usize,
+    pub cold_stock_cleared_t: f64,
+    pub cold_stock_age_reduction_days: i64,
+}
+
+// ==========================================
+// PlanEvaluator - 多目标评价引擎
+// ==========================================
+pub struct PlanEvaluator;
+
+impl PlanEvaluator {
+    /// 计算目标向量
+    ///
+    /// # 参数
+    /// - `items`: 待评价明细（调用方负责按评价范围过滤）
+    /// - `capacity_pools`: 评价范围内的产能池（提供目标/上限）
+    /// - `ctx`: 评价上下文
+    pub fn evaluate(
+        items: &[PlanItem],
+        capacity_pools: &[CapacityPool],
+        ctx: &PlanEvaluationContext,
+    ) -> PlanObjectiveVector {
+        let mut v = PlanObjectiveVector {
+            plan_items_count: items.len(),
+            ..Default::default()
+        };
+
+        // 按 机组×日 分组（BTreeMap 保证输出稳定）
+        let mut by_machine_day: BTreeMap<(String, NaiveDate), Vec<&PlanItem>> = BTreeMap::new();
+
+        for item in items {
+            v.total_weight_t += item.weight_t;
+            by_machine_day
+                .entry((item.machine_code.clone(), item.plan_date))
+                .or_default()
+                .push(item);
+
+            if let Some(due) = item
+                .due_date
+                .as_deref()
+                .and_then(|s| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok())
+            {
+                v.due_items_count += 1;
+                v.due_weight_t += item.weight_t;
+                let tardiness = (item.plan_date - due).num_days();
+                if tardiness > 0 {
+                    v.tardy_count += 1;
+                    v.total_tardiness_days += tardiness;
+                    v.max_tardiness_days = v.max_tardiness_days.max(tardiness);
+                } else {
+                    v.on_time_weight_t += item.weight_t;
+                }
+            }
+
+            if ctx.path_override_material_ids.contains(&item.material_id) {
+                v.path_override_count += 1;
+            }
+
+            if let Some(age) = ctx.stock_age_days.get(&item.material_id) {
+                if *age >= ctx.cold_stock_age_threshold_days {
+                    v.cold_stock_cleared_count += 1;
+                    v.cold_stock_cleared_t += item.weight_t;
+                    v.cold_stock_age_reduction_days += *age as i64;
+                }
+            }
+        }
+
+        v.on_time_weight_rate = if v.due_weight_t > 0.0 {
+            v.on_time_weight_t / v.due_weight_t
+        } else {
+            1.0
+        };
+
+        Self::evaluate_capacity(&mut v, &by_machine_day, capacity_pools);
+        Self::evaluate_structure(&mut v, &by_machine_day, ctx);
+        v.changeover_count = Self::count_changeovers(&by_machine_day);
+        v.roll_change_count = Self::estimate_roll_changes(&by_machine_day, ctx);
+
+        v
+    }
+
+    /// 产能利用与超限
+    fn evaluate_capacity(
+        v: &mut PlanObjectiveVector,
+        by_machine_day: &BTreeMap<(String, NaiveDate), Vec<&PlanItem>>,
+        capacity_pools: &[CapacityPool],
+    ) {
+        let pools: HashMap<(&str, NaiveDate), &CapacityPool> = capacity_pools
+            .iter()
+            .map(|p| ((p.machine_code.as_str(), p.plan_date), p))
+            .collect();
+
+        for pool in pools.values() {
+            v.capacity_target_t += pool.target_capacity_t;
+            v.capacity_limit_t += pool.limit_capacity_t;
+        }
+
+        for ((machine_code, plan_date), day_items) in by_machine_day {
+            let used: f64 = day_items.iter().map(|i| i.weight_t).sum();
+            v.capacity_used_t += used;
+
+            let Some(pool) = pools.get(&(machine_code.as_str(), *plan_date)) else {
+                continue;
+            };
+            if pool.limit_capacity_t > 0.0 && used > pool.limit_capacity_t {
+                v.overflow_t += used - pool.limit_capacity_t;
+                v.overflow_machine_days += 1;
+            }
+        }
+
+        v.capacity_util_pct = if v.capacity_target_t > 0.0 {
+            v.capacity_used_t / v.capacity_target_t * 100.0
+        } else {
+            0.0
+        };
+    }
+
+    /// 结构偏差（按 机组×日）
+    fn evaluate_structure(
+        v: &mut PlanObjectiveVector,
+        by_machine_day: &BTreeMap<(String, NaiveDate), Vec<&PlanItem>>,
+        ctx: &PlanEvaluationContext,
+    ) {
+        if ctx.target_ratio.is_empty() {
+            return;
+        }
+
+        let corrector = StructureCorrector::new();
+        let mut weighted_deviation = 0.0;
+        let mut graded_weight = 0.0;
+
+        for day_items in by_machine_day.values() {
+            let mut grade_weights: HashMap<String, f64> = HashMap::new();
+            for item in day_items {
+                if let Some(grade) = item.steel_grade.as_deref().filter(|g| !g.is_empty()) {
+                    *grade_weights.entry(grade.to_string()).or_insert(0.0) += item.weight_t;
+                }
+            }
+            let day_weight: f64 = grade_weights.values().sum();
+            if day_weight <= 0.0 {
+                continue;
+            }
+
+            let actual: HashMap<String, f64> = grade_weights
+                .into_iter()
+                .map(|(grade, w)| (grade, w / day_weight))
+                .collect();
+            let deviation = corrector.calculate_deviation(&actual, &ctx.target_ratio);
+
+            weighted_deviation += deviation * day_weight;
+            graded_weight += day_weight;
+            if deviation > ctx.deviation_threshold {
+                v.structure_violation_days += 1;
+            }
+        }
+
+        if graded_weight > 0.0 {
+            v.structure_deviation = weighted_deviation / graded_weight;
+        }
+    }
+
+    /// 换钢种次数（同机组同日相邻材料）
+    fn count_changeovers(by_machine_day: &BTreeMap<(String, NaiveDate), Vec<&PlanItem>>) -> usize {
+        let mut count = 0;
+        for day_items in by_machine_day.values() {
+            let mut ordered = day_items.clone();
+            ordered.sort_by_key(|i| i.seq_no);
+
+            let mut prev: Option<&str> = None;
+            for grade in ordered
+                .iter()
+                .filter_map(|i| i.steel_grade.as_deref().filter(|g| !g.is_empty()))
+            {
+                if prev.is_some_and(|p| p != grade) {
+                    count += 1;
+                }
+                prev = Some(grade);
+            }
+        }
+        count
+    }
+
+    /// 换辊次数估算（按机组累计吨位）
+    fn estimate_roll_changes(
+        by_machine_day: &BTreeMap<(String, NaiveDate), Vec<&PlanItem>>,
+        ctx: &PlanEvaluationContext,
+    ) -> usize {
+        if ctx.roll_suggest_threshold_t <= 0.0 {
+            return 0;
+        }
+
+        let mut count = 0;
+        let mut accumulated: HashMap<&str, f64> = HashMap::new();
+        // BTreeMap 按 (机组, 日期) 有序，同一机组内按日期推进
+        for ((machine_code, _), day_items) in by_machine_day {
+            let mut ordered = day_items.clone();
+            ordered.sort_by_key(|i| i.seq_no);
+
+            let acc = accumulated.entry(machine_code.as_str()).or_insert(0.0);
+            for item in ordered {
+                *acc += item.weight_t;
+                if *acc >= ctx.roll_suggest_threshold_t {
+                    count += 1;
+                    *acc = 0.0;
+                }
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    fn item(id: &str, date: NaiveDate, seq: i32, weight: f64, grade: &str) -> PlanItem {
+        PlanItem {
+            version_id: "V1".to_string(),
+            material_id: id.to_string(),
+            machine_code: "H032".to_string(),
+            plan_date: date,
+            seq_no: seq,
+            weight_t: weight,
+            source_type: "CALC".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            assign_reason: None,
+            urgent_level: None,
+            sched_state: None,
+            steel_grade: Some(grade.to_string()),
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: None,
+            due_date: None,
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+
+    fn pool(date: NaiveDate, target: f64, limit: f64) -> CapacityPool {
+        CapacityPool {
+            version_id: "V1".to_string(),
+            machine_code: "H032".to_string(),
+            plan_date: date,
+            target_capacity_t: target,
+            limit_capacity_t: limit,
+            used_capacity_t: 0.0,
+            overflow_t: 0.0,
+            frozen_capacity_t: 0.0,
+            accumulated_tonnage_t: 0.0,
+            roll_campaign_id: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_objective_vector() {
+        let mut items = vec![
+            item("M1", d(1), 1, 600.0, "Q235"),
+            item("M2", d(1), 2, 600.0, "Q345"),
+            item("M3", d(1), 3, 200.0, "Q235"),
+            item("M4", d(2), 1, 500.0, "Q235"),
+        ];
+        items[0].due_date = Some("2026-03-01".to_string()); // 准时
+        items[3].due_date = Some("2026-02-27".to_string()); // 拖期 3 天
+
+        let mut ctx = PlanEvaluationContext {
+            roll_suggest_threshold_t: 1000.0,
+            ..Default::default()
+        };
+        ctx.stock_age_days.insert("M2".to_string(), 45);
+        ctx.stock_age_days.insert("M3".to_string(), 10);
+        ctx.path_override_material_ids.insert("M4".to_string());
+        ctx.target_ratio.insert("Q235".to_string(), 0.5);
+        ctx.target_ratio.insert("Q345".to_string(), 0.5);
+
+        let v = PlanEvaluator::evaluate(
+            &items,
+            &[pool(d(1), 1000.0, 1200.0), pool(d(2), 1000.0, 1200.0)],
+            &ctx,
+        );
+
+        assert_eq!(v.plan_items_count, 4);
+        assert_eq!((v.due_items_count, v.tardy_count), (2, 1));
+        assert_eq!((v.total_tardiness_days, v.max_tardiness_days), (3, 3));
+        assert!((v.on_time_weight_rate - 600.0 / 1100.0).abs() < 1e-9);
+
+        assert_eq!(v.capacity_used_t, 1900.0);
+        assert_eq!(v.capacity_target_t, 2000.0);
+        assert!((v.capacity_util_pct - 95.0).abs() < 1e-9);
+        assert_eq!((v.overflow_t, v.overflow_machine_days), (200.0, 1));
+
+        // 1日: Q235 800/1400 vs 0.5 → 偏差 1/14；2日: 全 Q235 → 偏差 0.5
+        let expected = (1.0 / 14.0 * 1400.0 + 0.5 * 500.0) / 1900.0;
+        assert!((v.structure_deviation - expected).abs() < 1e-9);
+        assert_eq!(v.structure_violation_days, 1);
+
+        assert_eq!(v.changeover_count, 2);
+        // 累计 600 → 1200(换) → 200 → 700 → 不足 1000
+        assert_eq!(v.roll_change_count, 1);
+        assert_eq!(v.path_override_count, 1);
+        assert_eq!(v.cold_stock_cleared_count, 1);
+        assert_eq!(v.cold_stock_cleared_t, 600.0);
+        assert_eq!(v.cold_stock_age_reduction_days, 45);
+    }
+
+    #[test]
+    fn test_evaluate_empty_plan() {
+        let v = PlanEvaluator::evaluate(&[], &[], &PlanEvaluationContext::default());
+        assert_eq!(v.plan_items_count, 0);
+        assert_eq!(v.on_time_weight_rate, 1.0);
+        assert_eq!(v.capacity_util_pct, 0.0);
+        assert_eq!(v.structure_deviation, 0.0);
+    }
+}
@@ -77,14 +77,7 @@ impl PrioritySorter {
         params: &CustomStrategyParameters,
         today: NaiveDate,
     ) -> Vec<(MaterialMaster, MaterialState)> {
-        let has_any = params.urgent_weight.is_some()
-            || params.capacity_weight.is_some()
-            || params.cold_stock_weight.is_some()
-            || params.due_date_weight.is_some()
-            || params.rolling_output_age_weight.is_some()
-            || params.cold_stock_age_threshold_days.is_some();
-
-        if !has_any {
+        if !has_any_parameter(params) {
             return self.sort_with_strategy(materials, base_strategy);
         }
 
@@ -96,35 +89,38 @@ impl PrioritySorter {
         }
 
         materials.sort_by(|a, b| {
-            let (master_a, state_a) = a;
-            let (master_b, state_b) = b;
-
-            if let Some(ord) = self.compare_sched_state(state_a.sched_state, state_b.sched_state) {
-                return ord;
-            }
-
-            let sa = score_by_id
-                .get(&master_a.material_id)
-                .copied()
-                .unwrap_or(0.0);
-            let sb = score_by_id
-                .get(&master_b.material_id)
-                .copied()
-                .unwrap_or(0.0);
-
-            // 分数高者优先
-            match sb.total_cmp(&sa) {
-                Ordering::Equal => {
-                    // tie-break：回落到基于预设策略的稳定排序（可解释性更强，且避免不稳定）。
-                    self.compare_with_strategy(a, b, base_strategy)
-                }
-                other => other,
-            }
+            let sa = score_by_id.get(&a.0.material_id).copied().unwrap_or(0.0);
+            let sb = score_by_id.get(&b.0.material_id).copied().unwrap_or(0.0);
+            self.compare_scored(a, b, sa, sb, base_strategy)
         });
 
         materials
     }
 
+    /// 两两比较（与 sort_with_parameters / sort_with_strategy 同口径）
+    ///
+    /// 用于离线分析（如人工调整反推权重），避免为比较两条材料构造排序向量。
+    /// - `params` 为 None 或参数均为空时按预设策略比较
+    /// - Ordering::Less 表示 a 优先于 b
+    pub fn compare_pair(
+        &self,
+        a: &(MaterialMaster, MaterialState),
+        b: &(MaterialMaster, MaterialState),
+        base_strategy: ScheduleStrategy,
+        params: Option<&CustomStrategyParameters>,
+        today: NaiveDate,
+    ) -> Ordering {
+        match params.filter(|p| has_any_parameter(p)) {
+            Some(params) => {
+                let sa = compute_param_score(&a.0, &a.1, params, today);
+                let sb = compute_param_score(&b.0, &b.1, params, today);
+                self.compare_scored(a, b, sa, sb, base_strategy)
+            }
+            None if base_strategy == ScheduleStrategy::Balanced => self.compare(a, b),
+            None => self.compare_with_strategy(a, b, base_strategy),
+        }
+    }
+
     /// 按机组分组排序
     ///
     /// # 参数
@@ -246,6 +242,29 @@ impl PrioritySorter {
         due_a.cmp(&due_b)
     }
 
+    /// 参数化策略比较：状态红线 > 分数（高者优先）> 预设策略 tie-break
+    fn compare_scored(
+        &self,
+        a: &(MaterialMaster, MaterialState),
+        b: &(MaterialMaster, MaterialState),
+        score_a: f64,
+        score_b: f64,
+        base_strategy: ScheduleStrategy,
+    ) -> Ordering {
+        if let Some(ord) = self.compare_sched_state(a.1.sched_state, b.1.sched_state) {
+            return ord;
+        }
+
+        // 分数高者优先
+        match score_b.total_cmp(&score_a) {
+            Ordering::Equal => {
+                // tie-break：回落到基于预设策略的稳定排序（可解释性更强，且避免不稳定）。
+                self.compare_with_strategy(a, b, base_strategy)
+            }
+            other => other,
+        }
+    }
+
     fn compare_with_strategy(
         &self,
         a: &(MaterialMaster, MaterialState),
@@ -414,3 +433,13 @@ impl Default for PrioritySorter {
         Self::new()
     }
 }
+
+/// 参数是否有任一项被设置（均为空时退化为预设策略）
+fn has_any_parameter(params: &CustomStrategyParameters) -> bool {
+    params.urgent_weight.is_some()
+        || params.capacity_weight.is_some()
+        || params.cold_stock_weight.is_some()
+        || params.due_date_weight.is_some()
+        || params.rolling_output_age_weight.is_some()
+        || params.cold_stock_age_threshold_days.is_some()
+}
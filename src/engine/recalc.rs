@@ -11,6 +11,7 @@
 // ==========================================
 
 mod core;
+mod inputs;
 mod ops;
 mod refresh;
 mod reschedule;
@@ -18,7 +19,9 @@ mod risk;
 mod types;
 mod versioning;
 
-pub use types::{RecalcConfig, RecalcResult, RescheduleResult, ResolvedStrategyProfile};
+pub use types::{
+    RecalcConfig, RecalcResult, RescheduleInputSnapshot, RescheduleResult, ResolvedStrategyProfile,
+};
 
 use crate::config::ConfigManager;
 use crate::engine::events::OptionalEventPublisher;
@@ -245,7 +245,7 @@ impl RecalcEngine {
     ///
     /// 当数据库中不存在指定版本/机组/日期的产能池时，使用此方法创建默认值。
     /// 默认值：target=1800t, limit=2000t, 其余字段为 0 或 None。
-    pub(super) fn create_default_capacity_pool(
+    pub fn create_default_capacity_pool(
         version_id: &str,
         machine_code: &str,
         plan_date: chrono::NaiveDate,
@@ -0,0 +1,130 @@
+use super::{RecalcEngine, ResolvedStrategyProfile};
+use crate::config::config_keys;
+use crate::domain::recalc_input::{
+    PathOverrideRejectionInput, RecalcInputFingerprint, RecalcInputSnapshot, RecalcRunParams,
+    ENGINE_VERSION, META_INPUT_FINGERPRINT,
+};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+impl RecalcEngine {
+    /// 采集重算输入
+    ///
+    /// # 说明
+    /// - 须在 execute_reschedule 之前调用（生产重算会回写 material_state）
+    /// - 冻结区/产能池按 version_id 读取（即本次重算写入的新版本）
+    pub fn capture_recalc_inputs(
+        &self,
+        version_id: &str,
+        run: RecalcRunParams,
+    ) -> Result<RecalcInputSnapshot, Box<dyn Error>> {
+        let config: HashMap<String, String> =
+            serde_json::from_str(&self.config_manager.get_config_snapshot()?)?;
+        let config: BTreeMap<String, String> = config
+            .into_iter()
+            .filter(|(k, _)| !k.starts_with("__meta_"))
+            .collect();
+
+        let mut materials = Vec::new();
+        let mut states = Vec::new();
+        let mut path_override_rejections = Vec::new();
+        for machine_code in &run.machine_codes {
+            materials.extend(self.material_master_repo.find_by_machine(machine_code)?);
+            states.extend(
+                self.material_state_repo
+                    .list_by_machine_code(machine_code)?,
+            );
+            path_override_rejections.extend(
+                self.material_state_repo
+                    .list_path_override_rejections_by_machine(machine_code)?
+                    .into_iter()
+                    .map(|r| PathOverrideRejectionInput {
+                        material_id: r.material_id,
+                        reject_cycle_no: r.reject_cycle_no,
+                        reject_base_sched_state: r.reject_base_sched_state,
+                    }),
+            );
+        }
+
+        let capacity_pools = self
+            .capacity_repo
+            .find_by_version_id(version_id)?
+            .into_iter()
+            .filter(|p| {
+                run.machine_codes.contains(&p.machine_code)
+                    && p.plan_date >= run.base_date
+                    && p.plan_date <= run.end_date
+            })
+            .collect();
+
+        Ok(RecalcInputSnapshot {
+            run,
+            config,
+            materials,
+            states,
+            path_override_rejections,
+            capacity_pools,
+            frozen_items: self.item_repo.find_frozen_items(version_id)?,
+        })
+    }
+
+    /// 本次重算的执行参数
+    pub(super) fn build_run_params(
+        plan_id: &str,
+        base_version_id: Option<&str>,
+        date_range: (NaiveDate, NaiveDate),
+        window_days: i32,
+        frozen_from_date: NaiveDate,
+        machine_codes: &[String],
+        profile: &ResolvedStrategyProfile,
+    ) -> RecalcRunParams {
+        RecalcRunParams {
+            engine_version: ENGINE_VERSION.to_string(),
+            plan_id: plan_id.to_string(),
+            base_version_id: base_version_id.map(str::to_string),
+            base_date: date_range.0,
+            end_date: date_range.1,
+            window_days,
+            frozen_from_date,
+            machine_codes: machine_codes.to_vec(),
+            strategy_key: profile.strategy_key.clone(),
+            base_strategy: profile.base_strategy.as_str().to_string(),
+            strategy_parameters: profile
+                .parameters
+                .as_ref()
+                .map(|_| profile.parameters_json()),
+        }
+    }
+
+    /// 是否保存重算全量输入（全局配置 recalc_persist_inputs，默认关闭）
+    pub(super) fn should_persist_recalc_inputs(&self) -> bool {
+        self.config_manager
+            .get_global_config_value(config_keys::RECALC_PERSIST_INPUTS)
+            .ok()
+            .flatten()
+            .map(|v| {
+                matches!(
+                    v.trim().to_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false)
+    }
+
+    /// 将输入指纹写入 config_snapshot_json（__meta_input_fingerprint）
+    pub(super) fn upsert_input_fingerprint(
+        snapshot_json: Option<String>,
+        fingerprint: &RecalcInputFingerprint,
+    ) -> Result<String, Box<dyn Error>> {
+        let mut map: HashMap<String, String> = match snapshot_json.as_deref() {
+            Some(raw) => serde_json::from_str(raw).unwrap_or_default(),
+            None => HashMap::new(),
+        };
+        map.insert(
+            META_INPUT_FINGERPRINT.to_string(),
+            serde_json::to_string(fingerprint)?,
+        );
+        Ok(serde_json::to_string(&map)?)
+    }
+}
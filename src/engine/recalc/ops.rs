@@ -105,6 +105,7 @@ impl RecalcEngine {
                 &profile.strategy_key,
                 profile.base_strategy,
                 profile.parameters.as_ref(),
+                base_version.as_ref().map(|v| v.version_id.as_str()),
             )?;
             new_version.config_snapshot_json = Some(snapshot_json);
         }
@@ -142,9 +143,34 @@ impl RecalcEngine {
             }
         }
 
-        // 5. 执行重排 (计算区)
+        // 4.2 采集重算输入并写入指纹（仅生产模式；开启 recalc_persist_inputs 时保存全量输入）
         let end_date = base_date + chrono::Duration::days(window_days as i64);
         let machine_codes = vec!["H032".to_string(), "H033".to_string(), "H034".to_string()];
+        let persisted_inputs = if !is_dry_run {
+            let run = Self::build_run_params(
+                plan_id,
+                base_version.as_ref().map(|v| v.version_id.as_str()),
+                (base_date, end_date),
+                window_days,
+                frozen_from_date,
+                &machine_codes,
+                &profile,
+            );
+            let inputs = self.capture_recalc_inputs(&new_version.version_id, run)?;
+            let mut fingerprint = inputs.fingerprint();
+            fingerprint.inputs_persisted = self.should_persist_recalc_inputs();
+            new_version.config_snapshot_json = Some(Self::upsert_input_fingerprint(
+                new_version.config_snapshot_json.take(),
+                &fingerprint,
+            )?);
+            fingerprint
+                .inputs_persisted
+                .then_some((inputs, fingerprint.combined_hash))
+        } else {
+            None
+        };
+
+        // 5. 执行重排 (计算区)
         let reschedule_result = self.execute_reschedule(
             &new_version.version_id,
             (base_date, end_date),
@@ -166,6 +192,15 @@ impl RecalcEngine {
             plan_items.len()
         };
 
+        // 7.1 保存重算全量输入
+        if let Some((inputs, combined_hash)) = &persisted_inputs {
+            self.version_repo.save_input_snapshot(
+                &new_version.version_id,
+                combined_hash,
+                &serde_json::to_string(inputs)?,
+            )?;
+        }
+
         // 8. 更新版本的frozen_from_date（仅生产模式）
         let plan_rev = if !is_dry_run {
             self.version_repo.update(&new_version)?;
@@ -203,11 +238,7 @@ impl RecalcEngine {
         //     ActionLogRepository.insert()
         // }
 
-        // 11. 激活新版本（仅生产模式且auto_activate=true）
-        if !is_dry_run && self.config.auto_activate {
-            self.version_repo
-                .activate_version(&new_version.version_id)?;
-        }
+        // 11. 新版本保持草稿，激活须经审批与红线校验（PlanApi::activate_version）
 
         // 12. 触发决策视图刷新（仅生产模式）
         if !is_dry_run {
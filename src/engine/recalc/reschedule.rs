@@ -1,6 +1,8 @@
-use super::{RecalcEngine, RescheduleResult};
+use super::{RecalcEngine, RescheduleInputSnapshot, RescheduleResult};
 use crate::config::config_keys;
 use crate::config::strategy_profile::CustomStrategyParameters;
+use crate::domain::batching::BatchingDeferral;
+use crate::domain::capacity::ReservationUsage;
 use crate::domain::material::{MaterialMaster, MaterialState};
 use crate::domain::plan::PlanItem;
 use crate::domain::roller::RollerCampaign;
@@ -8,12 +10,18 @@ use crate::domain::types::{AnchorSource, RollStatus, SchedState, UrgentLevel};
 use crate::engine::orchestrator::ScheduleOrchestrator;
 use crate::engine::strategy::ScheduleStrategy;
 use crate::engine::{
-    Anchor, AnchorResolver, MaterialSummary, PathRuleConfig, PathRuleEngine, SeedS2Config,
+    Anchor, AnchorResolver, MaterialSummary, PathRuleConfig, PathRuleEngine, ReservationHold,
+    SeedS2Config,
 };
 use crate::repository::PathOverridePendingRecord;
 use chrono::NaiveDate;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 试算被取消时的错误信息
+pub const RESCHEDULE_CANCELLED: &str = "排产试算已取消";
 
 impl RecalcEngine {
     fn normalize_sched_state_for_reject(raw: Option<&str>) -> Option<SchedState> {
@@ -25,6 +33,7 @@ impl RecalcEngine {
             "FORCE_RELEASE" => Some(SchedState::ForceRelease),
             "BLOCKED" => Some(SchedState::Blocked),
             "SCHEDULED" => Some(SchedState::Scheduled),
+            "COMPLETED" => Some(SchedState::Completed),
             _ => None,
         }
     }
@@ -63,36 +72,155 @@ impl RecalcEngine {
         strategy: ScheduleStrategy,
         strategy_params: Option<CustomStrategyParameters>,
     ) -> Result<RescheduleResult, Box<dyn Error>> {
+        Self::block_on_reschedule(self.execute_reschedule_async(
+            version_id,
+            date_range,
+            machine_codes,
+            is_dry_run,
+            strategy,
+            strategy_params,
+            None,
+            None,
+        ))
+    }
+
+    /// 基于只读输入快照执行 dry-run 重排（草案多策略并发试算）
+    ///
+    /// # 参数
+    /// - `snapshot`: 由 load_reschedule_snapshot 预先加载，多个策略共享
+    /// - `cancel`: 取消标记；每个排产日开始前检查，置位后返回错误
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_dry_run_with_snapshot(
+        &self,
+        version_id: &str,
+        date_range: (NaiveDate, NaiveDate),
+        machine_codes: &[String],
+        strategy: ScheduleStrategy,
+        strategy_params: Option<CustomStrategyParameters>,
+        snapshot: &RescheduleInputSnapshot,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<RescheduleResult, Box<dyn Error>> {
+        Self::block_on_reschedule(self.execute_reschedule_async(
+            version_id,
+            date_range,
+            machine_codes,
+            true,
+            strategy,
+            strategy_params,
+            Some(snapshot),
+            cancel,
+        ))
+    }
+
+    /// 加载重排产只读输入快照（冻结区 + 机组材料/状态 + 路径规则记录 + 产能预留）
+    pub fn load_reschedule_snapshot(
+        &self,
+        version_id: &str,
+        machine_codes: &[String],
+    ) -> Result<RescheduleInputSnapshot, Box<dyn Error>> {
+        let mut snapshot = RescheduleInputSnapshot {
+            frozen_items: self.item_repo.find_frozen_items(version_id)?,
+            ..Default::default()
+        };
+
+        for machine_code in machine_codes {
+            let materials = self.material_master_repo.find_by_machine(machine_code)?;
+            let states = self
+                .material_state_repo
+                .list_by_machine_code(machine_code)?;
+            let mut state_map: HashMap<String, MaterialState> =
+                HashMap::with_capacity(states.len());
+            for s in states {
+                state_map.insert(s.material_id.clone(), s);
+            }
+
+            snapshot
+                .materials_by_machine
+                .insert(machine_code.clone(), materials);
+            snapshot
+                .state_map_by_machine
+                .insert(machine_code.clone(), state_map);
+
+            let summaries: Vec<MaterialSummary> = self
+                .material_state_repo
+                .list_user_confirmed_materials(machine_code)?
+                .into_iter()
+                .filter_map(|u| {
+                    let w = u.width_mm;
+                    let t = u.thickness_mm;
+                    if !(w.is_finite() && t.is_finite() && w > 0.0 && t > 0.0) {
+                        return None;
+                    }
+                    Some(MaterialSummary {
+                        material_id: u.material_id,
+                        width_mm: w,
+                        thickness_mm: t,
+                        seq_no: u.seq_no.unwrap_or(0),
+                        user_confirmed_at: u.user_confirmed_at,
+                    })
+                })
+                .collect();
+            snapshot
+                .user_confirmed_summaries_by_machine
+                .insert(machine_code.clone(), summaries);
+
+            let mut rejection_map: HashMap<String, (Option<i32>, Option<String>)> = HashMap::new();
+            for row in self
+                .material_state_repo
+                .list_path_override_rejections_by_machine(machine_code)?
+            {
+                rejection_map.insert(
+                    row.material_id,
+                    (row.reject_cycle_no, row.reject_base_sched_state),
+                );
+            }
+            snapshot
+                .rejection_map_by_machine
+                .insert(machine_code.clone(), rejection_map);
+
+            let mut reservations = self.capacity_repo.list_reservations(Some(machine_code))?;
+            reservations.sort_by(|a, b| {
+                (a.created_at, &a.reservation_id).cmp(&(b.created_at, &b.reservation_id))
+            });
+            snapshot
+                .reservations_by_machine
+                .insert(machine_code.clone(), reservations);
+        }
+
+        Ok(snapshot)
+    }
+
+    /// 汇总当日预留占用：已过释放点的未用吨位计为释放给一般需求
+    fn collect_reservation_usage(
+        usage: &mut Vec<ReservationUsage>,
+        version_id: &str,
+        plan_date: NaiveDate,
+        holds: &[ReservationHold],
+    ) {
+        usage.extend(holds.iter().map(|h| ReservationUsage {
+            version_id: version_id.to_string(),
+            reservation_id: h.reservation.reservation_id.clone(),
+            machine_code: h.reservation.machine_code.clone(),
+            plan_date,
+            reserved_t: h.reservation.daily_reserved_t,
+            used_t: h.used_t,
+            released_t: if h.held { 0.0 } else { h.remaining_t() },
+            held: h.held,
+        }));
+    }
+
+    fn block_on_reschedule<F>(fut: F) -> Result<RescheduleResult, Box<dyn Error>>
+    where
+        F: Future<Output = Result<RescheduleResult, Box<dyn Error>>>,
+    {
         // 检查是否已经在 tokio 运行时中
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             // 已经在运行时中，使用 block_in_place 来运行异步代码
-            tokio::task::block_in_place(|| {
-                handle.block_on(async move {
-                    self.execute_reschedule_async(
-                        version_id,
-                        date_range,
-                        machine_codes,
-                        is_dry_run,
-                        strategy,
-                        strategy_params,
-                    )
-                    .await
-                })
-            })
+            tokio::task::block_in_place(|| handle.block_on(fut))
         } else {
             // 不在运行时中，创建新的运行时
             let rt = tokio::runtime::Runtime::new()?;
-            rt.block_on(async move {
-                self.execute_reschedule_async(
-                    version_id,
-                    date_range,
-                    machine_codes,
-                    is_dry_run,
-                    strategy,
-                    strategy_params,
-                )
-                .await
-            })
+            rt.block_on(fut)
         }
     }
 
@@ -106,6 +234,7 @@ impl RecalcEngine {
     /// # 返回
     /// - `Ok(RescheduleResult)`: 重排产结果
     /// - `Err`: 重排失败
+    #[allow(clippy::too_many_arguments)]
     async fn execute_reschedule_async(
         &self,
         version_id: &str,
@@ -114,18 +243,62 @@ impl RecalcEngine {
         is_dry_run: bool,
         strategy: ScheduleStrategy,
         strategy_params: Option<CustomStrategyParameters>,
+        snapshot: Option<&RescheduleInputSnapshot>,
+        cancel: Option<&AtomicBool>,
     ) -> Result<RescheduleResult, Box<dyn Error>> {
-        // ===== Step 1: 查询冻结区材料（冻结区保护红线） =====
-        let frozen_items = self.item_repo.find_frozen_items(version_id)?;
+        // ===== Step 1: 加载输入快照（冻结区保护红线 + 机组材料/状态） =====
+        // 预加载机组材料与状态，避免在多日循环中重复查库；草案多策略试算时由调用方共享同一快照
+        let loaded_snapshot;
+        let snapshot = match snapshot {
+            Some(snapshot) => snapshot,
+            None => {
+                loaded_snapshot = self.load_reschedule_snapshot(version_id, machine_codes)?;
+                &loaded_snapshot
+            }
+        };
+        let RescheduleInputSnapshot {
+            frozen_items,
+            materials_by_machine,
+            state_map_by_machine,
+            user_confirmed_summaries_by_machine,
+            rejection_map_by_machine,
+            reservations_by_machine,
+        } = snapshot;
+
+        // 钢种族最小批量规则：冻结项需补齐出钢记号，才能计入当日同族批量
+        let grade_batching_rules = self
+            .config_manager
+            .get_grade_batching_rules()
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("加载钢种族批量规则失败: {}, 不做集批", e);
+                Vec::new()
+            });
+        let steel_mark_by_id: HashMap<&str, &str> = if grade_batching_rules.is_empty() {
+            HashMap::new()
+        } else {
+            materials_by_machine
+                .values()
+                .flatten()
+                .filter_map(|m| Some((m.material_id.as_str(), m.steel_mark.as_deref()?)))
+                .collect()
+        };
+
         let mut frozen_by_date_machine: HashMap<NaiveDate, HashMap<String, Vec<PlanItem>>> =
             HashMap::new();
-        for item in &frozen_items {
+        for item in frozen_items {
+            let mut item = item.clone();
+            if item.steel_grade.is_none() {
+                item.steel_grade = steel_mark_by_id
+                    .get(item.material_id.as_str())
+                    .map(|s| s.to_string());
+            }
             frozen_by_date_machine
                 .entry(item.plan_date)
                 .or_default()
                 .entry(item.machine_code.clone())
                 .or_default()
-                .push(item.clone());
+                .push(item);
         }
 
         let orchestrator = match strategy_params {
@@ -135,7 +308,8 @@ impl RecalcEngine {
                 params,
             ),
             None => ScheduleOrchestrator::new_with_strategy(self.config_manager.clone(), strategy),
-        };
+        }
+        .with_grade_batching_rules(grade_batching_rules);
 
         // ===== Step 2: 初始化统计 =====
         let mut all_plan_items = Vec::new();
@@ -150,8 +324,14 @@ impl RecalcEngine {
         // 路径规则：待人工确认（由重算生成，按版本+机组+material 去重，plan_date=首次遇到的日期）
         let mut path_override_pending_records: Vec<PathOverridePendingRecord> = Vec::new();
 
+        // 产能预留：按机组/日期记录占用与释放（仅生产模式落库）
+        let mut reservation_usage: Vec<ReservationUsage> = Vec::new();
+
+        // 钢种族批量顺延：按材料保留最后一次顺延（重算结束仍未排入的才落库）
+        let mut batching_deferrals: HashMap<String, BatchingDeferral> = HashMap::new();
+
         // 将冻结区材料加入已排产集合
-        for item in &frozen_items {
+        for item in frozen_items {
             scheduled_material_ids.insert(item.material_id.clone());
         }
 
@@ -347,71 +527,13 @@ impl RecalcEngine {
             active_campaigns.insert(machine_code.clone(), campaign);
         }
 
-        // 预加载机组材料与状态，避免在多日循环中重复查库（尤其是 dry-run 草案多策略对比）
-        let mut materials_by_machine: HashMap<String, Vec<MaterialMaster>> = HashMap::new();
-        let mut state_map_by_machine: HashMap<String, HashMap<String, MaterialState>> =
-            HashMap::new();
-        let mut user_confirmed_summaries_by_machine: HashMap<String, Vec<MaterialSummary>> =
-            HashMap::new();
-        let mut rejection_map_by_machine: HashMap<
-            String,
-            HashMap<String, (Option<i32>, Option<String>)>,
-        > = HashMap::new();
-
-        for machine_code in machine_codes {
-            let materials = self.material_master_repo.find_by_machine(machine_code)?;
-            let states = self
-                .material_state_repo
-                .list_by_machine_code(machine_code)?;
-            let mut state_map: HashMap<String, MaterialState> =
-                HashMap::with_capacity(states.len());
-            for s in states {
-                state_map.insert(s.material_id.clone(), s);
-            }
-
-            materials_by_machine.insert(machine_code.clone(), materials);
-            state_map_by_machine.insert(machine_code.clone(), state_map);
-
-            if path_rule_config.enabled {
-                let summaries: Vec<MaterialSummary> = self
-                    .material_state_repo
-                    .list_user_confirmed_materials(machine_code)?
-                    .into_iter()
-                    .filter_map(|u| {
-                        let w = u.width_mm;
-                        let t = u.thickness_mm;
-                        if !(w.is_finite() && t.is_finite() && w > 0.0 && t > 0.0) {
-                            return None;
-                        }
-                        Some(MaterialSummary {
-                            material_id: u.material_id,
-                            width_mm: w,
-                            thickness_mm: t,
-                            seq_no: u.seq_no.unwrap_or(0),
-                            user_confirmed_at: u.user_confirmed_at,
-                        })
-                    })
-                    .collect();
-                user_confirmed_summaries_by_machine.insert(machine_code.clone(), summaries);
-
-                let mut rejection_map: HashMap<String, (Option<i32>, Option<String>)> =
-                    HashMap::new();
-                for row in self
-                    .material_state_repo
-                    .list_path_override_rejections_by_machine(machine_code)?
-                {
-                    rejection_map.insert(
-                        row.material_id,
-                        (row.reject_cycle_no, row.reject_base_sched_state),
-                    );
-                }
-                rejection_map_by_machine.insert(machine_code.clone(), rejection_map);
-            }
-        }
-
         let mut current_date = start_date;
 
         while current_date <= end_date {
+            if cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+                return Err(RESCHEDULE_CANCELLED.into());
+            }
+
             // 适温/产出时间等“随时间推进而变化”的字段需要按排产日期动态推进：
             // - output_age_days_raw 是“截至 base_date”的原始天数口径；
             // - 当排产日期向后推进 N 天时，应使用 output_age_days_raw + N 参与 Eligibility 判定；
@@ -448,10 +570,14 @@ impl RecalcEngine {
                     0
                 };
 
-                let rejection_map = rejection_map_by_machine
-                    .get(machine_code)
-                    .cloned()
-                    .unwrap_or_default();
+                let rejection_map = if path_rule_config.enabled {
+                    rejection_map_by_machine
+                        .get(machine_code)
+                        .cloned()
+                        .unwrap_or_default()
+                } else {
+                    HashMap::new()
+                };
 
                 let build_candidates_for_campaign = |campaign_no: i32| {
                     let mut candidate_materials: Vec<MaterialMaster> = Vec::new();
@@ -593,8 +719,24 @@ impl RecalcEngine {
                     }
                 }
 
+                // ----- 4.5.0 构造当日产能预留占用（以 start_date 为基准判断释放点/失效） -----
+                let mut reservation_holds: Vec<ReservationHold> = reservations_by_machine
+                    .get(machine_code)
+                    .map(Vec::as_slice)
+                    .unwrap_or(&[])
+                    .iter()
+                    .filter(|r| r.covers(current_date) && !r.is_expired(start_date))
+                    .map(|r| ReservationHold::new(r.clone(), r.is_held(current_date, start_date)))
+                    .collect();
+
                 // 无候选且无冻结项：跳过本次排产
                 if candidate_materials.is_empty() && frozen_for_today.is_empty() {
+                    Self::collect_reservation_usage(
+                        &mut reservation_usage,
+                        version_id,
+                        current_date,
+                        &reservation_holds,
+                    );
                     continue;
                 }
 
@@ -796,13 +938,35 @@ impl RecalcEngine {
                         } else {
                             Some(&reject_boost_material_ids)
                         },
+                        &mut reservation_holds,
                     )
                     .await?;
+                Self::collect_reservation_usage(
+                    &mut reservation_usage,
+                    version_id,
+                    current_date,
+                    &reservation_holds,
+                );
 
                 // 统计成熟/未成熟：按 Eligibility 评估结果口径（避免“未来永远不适温”的错判）
                 mature_count += schedule_result.eligible_materials.len();
                 immature_count += schedule_result.blocked_materials.len();
 
+                // ----- 4.7.3 收集钢种族批量顺延 -----
+                for d in &schedule_result.batching_deferred {
+                    batching_deferrals.insert(
+                        d.material_id.clone(),
+                        BatchingDeferral {
+                            version_id: version_id.to_string(),
+                            material_id: d.material_id.clone(),
+                            machine_code: machine_code.clone(),
+                            plan_date: current_date,
+                            grade_family: d.grade_family.clone(),
+                            reason: d.reason.clone(),
+                        },
+                    );
+                }
+
                 // ----- 4.7.2 收集路径规则待确认（由 CapacityFiller 产生，供上层落库/汇总） -----
                 if !schedule_result.path_override_pending.is_empty() {
                     for p in &schedule_result.path_override_pending {
@@ -928,6 +1092,42 @@ impl RecalcEngine {
             }
         }
 
+        // ===== Step 4.12: 持久化产能预留使用快照（仅生产模式） =====
+        if !is_dry_run {
+            if let Err(e) = self.capacity_repo.replace_reservation_usage(
+                version_id,
+                machine_codes,
+                start_date,
+                end_date,
+                &reservation_usage,
+            ) {
+                tracing::warn!(
+                    version_id = %version_id,
+                    "产能预留使用快照落库失败(将继续返回重算结果): {}",
+                    e
+                );
+            }
+        }
+
+        // ===== Step 4.13: 持久化钢种族批量顺延（仅生产模式） =====
+        if !is_dry_run {
+            let mut deferrals: Vec<BatchingDeferral> = batching_deferrals
+                .into_values()
+                .filter(|d| !scheduled_material_ids.contains(&d.material_id))
+                .collect();
+            deferrals.sort_by(|a, b| a.material_id.cmp(&b.material_id));
+            if let Err(e) =
+                self.item_repo
+                    .replace_batching_deferrals(version_id, machine_codes, &deferrals)
+            {
+                tracing::warn!(
+                    version_id = %version_id,
+                    "钢种族批量顺延落库失败(将继续返回重算结果): {}",
+                    e
+                );
+            }
+        }
+
         // ===== Step 5: 返回结果 =====
         Ok(RescheduleResult {
             plan_items: all_plan_items,
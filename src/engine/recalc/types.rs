@@ -1,8 +1,12 @@
 use crate::config::strategy_profile::CustomStrategyParameters;
+use crate::domain::capacity::CapacityReservation;
+use crate::domain::material::{MaterialMaster, MaterialState};
 use crate::domain::plan::PlanItem;
+use crate::engine::anchor_resolver::MaterialSummary;
 use crate::engine::strategy::ScheduleStrategy;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 
 // ==========================================
 // RecalcResult - 重算结果
@@ -40,6 +44,28 @@ pub struct RescheduleResult {
     pub overflow_days: usize,
 }
 
+// ==========================================
+// RescheduleInputSnapshot - 重排产只读输入快照
+// ==========================================
+/// 重排产输入快照（冻结区 + 机组材料/状态 + 路径规则人工确认/拒绝记录 + 产能预留）
+/// 职责: 多策略草案并发试算时只查库一次，各策略共享只读快照
+#[derive(Debug, Clone, Default)]
+pub struct RescheduleInputSnapshot {
+    /// 冻结区明细（全量，不限日期）
+    pub frozen_items: Vec<PlanItem>,
+    pub materials_by_machine: HashMap<String, Vec<MaterialMaster>>,
+    /// machine_code -> (material_id -> 状态)
+    pub state_map_by_machine: HashMap<String, HashMap<String, MaterialState>>,
+    /// 人工确认队列（按 user_confirmed_at 排序）
+    pub user_confirmed_summaries_by_machine: HashMap<String, Vec<MaterialSummary>>,
+    /// machine_code -> (material_id -> (拒绝时换辊周期, 拒绝时基础状态))
+    pub rejection_map_by_machine: PathOverrideRejectionMap,
+    /// machine_code -> 产能预留（按创建时间排序，决定同一材料匹配多条预留时的占用顺序）
+    pub reservations_by_machine: HashMap<String, Vec<CapacityReservation>>,
+}
+
+pub type PathOverrideRejectionMap = HashMap<String, HashMap<String, (Option<i32>, Option<String>)>>;
+
 // ==========================================
 // RecalcConfig - 重算配置
 // ==========================================
@@ -48,7 +74,6 @@ pub struct RecalcConfig {
     pub default_window_days: i32,      // 默认计算窗口: 30天
     pub default_cascade_days: i32,     // 默认联动窗口: 7天
     pub frozen_days_before_today: i32, // 冻结区天数: 2天
-    pub auto_activate: bool,           // 是否自动激活: false
 }
 
 impl Default for RecalcConfig {
@@ -57,7 +82,6 @@ impl Default for RecalcConfig {
             default_window_days: 30,
             default_cascade_days: 7,
             frozen_days_before_today: 2,
-            auto_activate: false,
         }
     }
 }
@@ -2,6 +2,7 @@ use super::RecalcEngine;
 use crate::config::strategy_profile::CustomStrategyParameters;
 use crate::domain::plan::{PlanItem, PlanVersion};
 use crate::domain::types::PlanVersionStatus;
+use crate::domain::version_lineage::{VersionTrigger, META_PARENT_VERSION_ID, META_TRIGGER};
 use crate::engine::strategy::ScheduleStrategy;
 use chrono::NaiveDate;
 use std::collections::HashMap;
@@ -24,6 +25,7 @@ impl RecalcEngine {
         strategy_key: &str,
         base_strategy: ScheduleStrategy,
         strategy_params: Option<&CustomStrategyParameters>,
+        parent_version_id: Option<&str>,
     ) -> Result<String, Box<dyn Error>> {
         let mut map: HashMap<String, String> = match snapshot_json.as_deref() {
             Some(raw) => match serde_json::from_str(raw) {
@@ -47,6 +49,14 @@ impl RecalcEngine {
             base_strategy.as_str().to_string(),
         );
 
+        map.insert(
+            META_TRIGGER.to_string(),
+            VersionTrigger::Recalc.as_str().to_string(),
+        );
+        if let Some(parent) = parent_version_id {
+            map.insert(META_PARENT_VERSION_ID.to_string(), parent.to_string());
+        }
+
         if let Some(p) = strategy_params {
             let raw = serde_json::to_string(p)?;
             map.insert("__meta_strategy_params_json".to_string(), raw);
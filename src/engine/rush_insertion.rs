@@ -0,0 +1,637 @@
+// ==========================================
+// 热轧精整排产系统 - 紧急插单引擎
+// ==========================================
+// 职责: 为紧急材料在其机组上寻找最早可行日, 计算最小挤出链
+// 输入: 基准版本 plan_item + 机组×日产能上限 + 插单材料
+// 输出: 插单后 plan_item + 插单落位 + 挤出链(谁被推到哪天) + 拖期变化
+// ==========================================
+// 插单口径:
+// - 可插日期: >= 首个开放日(今日/冻结区之后) 且 >= 材料最早可排日(适温)
+// - 已排材料: 只尝试早于当前排产日的日期, 原位置先释放
+// - 插入位置: 当日锁定材料/已插单材料之后, 其余材料之前
+// - 超上限挤出: 非锁定、非插单材料按 紧急等级低 → 交期远(无交期最先) → 序号大
+//   依次挤到本机组下一计划日(排在该日开放段最前), 逐日级联
+// - 最小挤出: 贪心挤出后按相反顺序尝试放回; 已超上限的日期只要求不比插单前更满
+// - 不可行: 锁定+插单吨位已超上限, 或级联到窗口末日仍放不下 → 尝试下一日
+// ==========================================
+
+use crate::domain::plan::PlanItem;
+use crate::domain::types::UrgentLevel;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const WEIGHT_EPS: f64 = 1e-6;
+
+// ==========================================
+// RushMaterial - 插单材料
+// ==========================================
+#[derive(Debug, Clone)]
+pub struct RushMaterial {
+    /// 插单排产项 (已排材料为当前明细, 未排材料为新建明细)
+    pub item: PlanItem,
+    /// 是否已在基准版本中排产
+    pub scheduled: bool,
+    pub urgent_level: UrgentLevel,
+    /// 最早可排日 (适温约束, None 表示已适温)
+    pub earliest_sched_date: Option<NaiveDate>,
+    pub due_date: Option<NaiveDate>,
+}
+
+// ==========================================
+// RushInsertionInput - 引擎输入
+// ==========================================
+pub struct RushInsertionInput<'a> {
+    /// 基准版本全部明细
+    pub items: &'a [PlanItem],
+    /// 机组×日 产能上限 (无产能池的日期不可排)
+    pub limits: &'a HashMap<(String, NaiveDate), f64>,
+    /// 首个开放日 (今日与冻结区结束日取大)
+    pub first_open_date: NaiveDate,
+    /// 材料交期 (用于计算挤出材料拖期)
+    pub due_dates: &'a HashMap<String, NaiveDate>,
+    pub rush: &'a [RushMaterial],
+}
+
+// ==========================================
+// RushPlacement - 插单落位
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RushPlacement {
+    pub material_id: String,
+    pub machine_code: String,
+    /// 原排产日 (未排材料为空)
+    pub from_date: Option<NaiveDate>,
+    pub to_date: NaiveDate,
+    pub seq_no: i32,
+    pub weight_t: f64,
+    pub urgent_level: UrgentLevel,
+    pub due_date: Option<NaiveDate>,
+    /// 插单后仍拖期天数
+    pub tardiness_days: i64,
+    /// 本次插单直接或级联挤出的材料数
+    pub displaced_count: usize,
+}
+
+// ==========================================
+// DisplacedItem - 被挤出材料 (挤出链)
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisplacedItem {
+    pub material_id: String,
+    pub machine_code: String,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub weight_t: f64,
+    pub urgent_level: Option<String>,
+    pub due_date: Option<NaiveDate>,
+    pub tardiness_before_days: i64,
+    pub tardiness_after_days: i64,
+    /// 触发挤出的插单材料
+    pub caused_by: String,
+    /// 链上位置 (1=被插单直接挤出, 2=被上一级挤出材料挤出 ...)
+    pub chain_step: usize,
+}
+
+impl DisplacedItem {
+    /// 新增拖期天数
+    pub fn added_tardiness_days(&self) -> i64 {
+        self.tardiness_after_days - self.tardiness_before_days
+    }
+}
+
+// ==========================================
+// RushInfeasible - 无法插单
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RushInfeasible {
+    pub material_id: String,
+    pub reason: String,
+}
+
+// ==========================================
+// RushInsertionOutcome - 引擎输出
+// ==========================================
+#[derive(Debug, Clone)]
+pub struct RushInsertionOutcome {
+    /// 插单后全部明细
+    pub items: Vec<PlanItem>,
+    pub placements: Vec<RushPlacement>,
+    pub displaced: Vec<DisplacedItem>,
+    pub infeasible: Vec<RushInfeasible>,
+    pub displaced_weight_t: f64,
+    /// 挤出链新增拖期天数合计
+    pub added_tardiness_days: i64,
+    /// 原本不拖期、挤出后拖期的材料数
+    pub newly_late_count: usize,
+}
+
+/// 单机组日计划 (日期 → 按序号排列的明细)
+type MachineDays = BTreeMap<NaiveDate, Vec<PlanItem>>;
+
+// ==========================================
+// RushInsertionEngine - 紧急插单引擎
+// ==========================================
+// 红线: 无状态引擎, 不拼 SQL
+pub struct RushInsertionEngine;
+
+impl RushInsertionEngine {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 计算插单方案
+    ///
+    /// 插单顺序: 紧急等级高 → 交期近 → 材料号; 先插入的材料不会被后插入的材料挤出
+    pub fn insert(&self, input: &RushInsertionInput) -> RushInsertionOutcome {
+        let mut machines: HashMap<String, MachineDays> = HashMap::new();
+        for item in input.items {
+            machines
+                .entry(item.machine_code.clone())
+                .or_default()
+                .entry(item.plan_date)
+                .or_default()
+                .push(item.clone());
+        }
+        for days in machines.values_mut() {
+            for list in days.values_mut() {
+                list.sort_by_key(|i| i.seq_no);
+            }
+        }
+        let mut limits: HashMap<String, BTreeMap<NaiveDate, f64>> = HashMap::new();
+        for ((machine, date), limit) in input.limits {
+            limits
+                .entry(machine.clone())
+                .or_default()
+                .insert(*date, *limit);
+        }
+
+        let mut order: Vec<&RushMaterial> = input.rush.iter().collect();
+        order.sort_by(|a, b| {
+            b.urgent_level
+                .cmp(&a.urgent_level)
+                .then_with(|| {
+                    a.due_date
+                        .unwrap_or(NaiveDate::MAX)
+                        .cmp(&b.due_date.unwrap_or(NaiveDate::MAX))
+                })
+                .then_with(|| a.item.material_id.cmp(&b.item.material_id))
+        });
+
+        let mut protected: HashSet<String> = HashSet::new();
+        let mut chain_origin: HashMap<String, (String, usize)> = HashMap::new();
+        let mut touched: HashSet<(String, NaiveDate)> = HashSet::new();
+        let mut placed: Vec<(&RushMaterial, NaiveDate, usize)> = Vec::new();
+        let mut infeasible = Vec::new();
+        let empty_limits = BTreeMap::new();
+
+        for rush in order {
+            let material_id = &rush.item.material_id;
+            let machine = &rush.item.machine_code;
+            let machine_limits = limits.get(machine).unwrap_or(&empty_limits);
+            let from_date = rush.scheduled.then_some(rush.item.plan_date);
+            let start = rush
+                .earliest_sched_date
+                .map_or(input.first_open_date, |d| d.max(input.first_open_date));
+            let candidates: Vec<NaiveDate> = machine_limits
+                .range(start..)
+                .map(|(d, _)| *d)
+                .filter(|d| from_date.is_none_or(|f| *d < f))
+                .collect();
+            if candidates.is_empty() {
+                let reason = match from_date {
+                    Some(f) => format!("当前排产日{}已是最早可行日", f),
+                    None => format!("机组{}自{}起无产能池，无法插单", machine, start),
+                };
+                infeasible.push(RushInfeasible {
+                    material_id: material_id.clone(),
+                    reason,
+                });
+                continue;
+            }
+
+            let mut rush_protected = protected.clone();
+            rush_protected.insert(material_id.clone());
+            let base_days = machines.get(machine).cloned().unwrap_or_default();
+            let mut result = None;
+            for date in candidates {
+                let mut trial = base_days.clone();
+                if let Some(from) = from_date {
+                    if let Some(list) = trial.get_mut(&from) {
+                        list.retain(|i| &i.material_id != material_id);
+                    }
+                }
+                let mut item = rush.item.clone();
+                item.plan_date = date;
+                if let Some(chain) =
+                    Self::insert_with_chain(&mut trial, machine_limits, item, &rush_protected)
+                {
+                    result = Some((trial, date, chain));
+                    break;
+                }
+            }
+
+            match result {
+                Some((days, date, chain)) => {
+                    if let Some(from) = from_date {
+                        touched.insert((machine.clone(), from));
+                    }
+                    touched.insert((machine.clone(), date));
+                    for (displaced_id, from, to, step) in &chain {
+                        touched.insert((machine.clone(), *from));
+                        touched.insert((machine.clone(), *to));
+                        chain_origin
+                            .entry(displaced_id.clone())
+                            .or_insert_with(|| (material_id.clone(), *step));
+                    }
+                    let displaced_count = chain
+                        .iter()
+                        .map(|(id, ..)| id)
+                        .collect::<HashSet<_>>()
+                        .len();
+                    machines.insert(machine.clone(), days);
+                    protected.insert(material_id.clone());
+                    placed.push((rush, date, displaced_count));
+                }
+                None => infeasible.push(RushInfeasible {
+                    material_id: material_id.clone(),
+                    reason: format!(
+                        "{}起各日均无法腾出产能（锁定材料已占满或挤出链超出窗口）",
+                        start
+                    ),
+                }),
+            }
+        }
+
+        // 重排受影响机组日的序号
+        for (machine, date) in &touched {
+            if let Some(list) = machines.get_mut(machine).and_then(|d| d.get_mut(date)) {
+                for (idx, item) in list.iter_mut().enumerate() {
+                    item.seq_no = idx as i32 + 1;
+                }
+            }
+        }
+
+        let items: Vec<PlanItem> = machines
+            .into_values()
+            .flat_map(|days| days.into_values().flatten())
+            .collect();
+        let item_by_id: HashMap<&str, &PlanItem> =
+            items.iter().map(|i| (i.material_id.as_str(), i)).collect();
+
+        let placements: Vec<RushPlacement> = placed
+            .into_iter()
+            .map(|(rush, date, displaced_count)| {
+                let seq_no = item_by_id
+                    .get(rush.item.material_id.as_str())
+                    .map_or(0, |i| i.seq_no);
+                RushPlacement {
+                    material_id: rush.item.material_id.clone(),
+                    machine_code: rush.item.machine_code.clone(),
+                    from_date: rush.scheduled.then_some(rush.item.plan_date),
+                    to_date: date,
+                    seq_no,
+                    weight_t: rush.item.weight_t,
+                    urgent_level: rush.urgent_level,
+                    due_date: rush.due_date,
+                    tardiness_days: tardiness_days(date, rush.due_date),
+                    displaced_count,
+                }
+            })
+            .collect();
+
+        let mut displaced: Vec<DisplacedItem> = input
+            .items
+            .iter()
+            .filter(|base| !protected.contains(&base.material_id))
+            .filter_map(|base| {
+                let after = item_by_id.get(base.material_id.as_str())?;
+                if after.plan_date == base.plan_date && after.machine_code == base.machine_code {
+                    return None;
+                }
+                let (caused_by, chain_step) = chain_origin.get(&base.material_id)?.clone();
+                let due_date = input.due_dates.get(&base.material_id).copied().or_else(|| {
+                    base.due_date
+                        .as_deref()
+                        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+                });
+                Some(DisplacedItem {
+                    material_id: base.material_id.clone(),
+                    machine_code: base.machine_code.clone(),
+                    from_date: base.plan_date,
+                    to_date: after.plan_date,
+                    weight_t: base.weight_t,
+                    urgent_level: base.urgent_level.clone(),
+                    due_date,
+                    tardiness_before_days: tardiness_days(base.plan_date, due_date),
+                    tardiness_after_days: tardiness_days(after.plan_date, due_date),
+                    caused_by,
+                    chain_step,
+                })
+            })
+            .collect();
+        displaced.sort_by(|a, b| {
+            a.caused_by
+                .cmp(&b.caused_by)
+                .then_with(|| a.chain_step.cmp(&b.chain_step))
+                .then_with(|| a.from_date.cmp(&b.from_date))
+                .then_with(|| a.material_id.cmp(&b.material_id))
+        });
+
+        RushInsertionOutcome {
+            displaced_weight_t: displaced.iter().map(|d| d.weight_t).sum(),
+            added_tardiness_days: displaced.iter().map(|d| d.added_tardiness_days()).sum(),
+            newly_late_count: displaced
+                .iter()
+                .filter(|d| d.tardiness_before_days == 0 && d.tardiness_after_days > 0)
+                .count(),
+            items,
+            placements,
+            displaced,
+            infeasible,
+        }
+    }
+
+    // ==========================================
+    // 内部: 插入 + 级联挤出
+    // ==========================================
+
+    /// 在 date 插入材料并逐日级联挤出
+    ///
+    /// # 返回
+    /// - Some(挤出链): (材料, 原日期, 新日期, 链上位置)
+    /// - None: 当日无法腾出产能或级联超出窗口
+    fn insert_with_chain(
+        days: &mut MachineDays,
+        limits: &BTreeMap<NaiveDate, f64>,
+        item: PlanItem,
+        protected: &HashSet<String>,
+    ) -> Option<Vec<(String, NaiveDate, NaiveDate, usize)>> {
+        let mut chain = Vec::new();
+        let mut day = item.plan_date;
+        let mut incoming = vec![item];
+        let mut step = 0;
+
+        loop {
+            let limit = *limits.get(&day)?;
+            let list = days.entry(day).or_default();
+            let load_before: f64 = list.iter().map(|i| i.weight_t).sum();
+            // 已超上限的日期只要求不比插入前更满
+            let cap = limit.max(load_before);
+
+            let pos = list
+                .iter()
+                .take_while(|i| i.locked_in_plan || protected.contains(&i.material_id))
+                .count();
+            for (offset, incoming_item) in incoming.drain(..).enumerate() {
+                list.insert(pos + offset, incoming_item);
+            }
+
+            let load: f64 = list.iter().map(|i| i.weight_t).sum();
+            if load <= cap + WEIGHT_EPS {
+                return Some(chain);
+            }
+
+            let mut evictable: Vec<usize> = (0..list.len())
+                .filter(|&idx| {
+                    !list[idx].locked_in_plan && !protected.contains(&list[idx].material_id)
+                })
+                .collect();
+            evictable.sort_by(|&a, &b| {
+                eviction_key(&list[a])
+                    .cmp(&eviction_key(&list[b]))
+                    .then_with(|| b.cmp(&a))
+            });
+
+            let mut over = load - cap;
+            let mut evicted = Vec::new();
+            for idx in evictable {
+                if over <= WEIGHT_EPS {
+                    break;
+                }
+                over -= list[idx].weight_t;
+                evicted.push(idx);
+            }
+            if over > WEIGHT_EPS {
+                return None;
+            }
+
+            // 放回: 按相反顺序, 剩余空间放得下即不挤出
+            let mut slack = -over;
+            let mut kept = Vec::new();
+            for &idx in evicted.iter().rev() {
+                if list[idx].weight_t <= slack + WEIGHT_EPS {
+                    slack -= list[idx].weight_t;
+                    kept.push(idx);
+                }
+            }
+            evicted.retain(|idx| !kept.contains(idx));
+            evicted.sort_unstable();
+
+            let next_day = limits.range(day.succ_opt()?..).next().map(|(d, _)| *d)?;
+            step += 1;
+            for idx in evicted.into_iter().rev() {
+                let mut moved = list.remove(idx);
+                chain.push((moved.material_id.clone(), day, next_day, step));
+                moved.plan_date = next_day;
+                incoming.insert(0, moved);
+            }
+            day = next_day;
+        }
+    }
+}
+
+impl Default for RushInsertionEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 挤出优先级 (小者先挤出): 紧急等级低 → 交期远(无交期最先)
+fn eviction_key(item: &PlanItem) -> (UrgentLevel, std::cmp::Reverse<NaiveDate>) {
+    let level = match item.urgent_level.as_deref().map(str::trim) {
+        Some("L3") => UrgentLevel::L3,
+        Some("L2") => UrgentLevel::L2,
+        Some("L1") => UrgentLevel::L1,
+        _ => UrgentLevel::L0,
+    };
+    let due = item
+        .due_date
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .unwrap_or(NaiveDate::MAX);
+    (level, std::cmp::Reverse(due))
+}
+
+fn tardiness_days(plan_date: NaiveDate, due_date: Option<NaiveDate>) -> i64 {
+    due_date.map_or(0, |due| (plan_date - due).num_days().max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 4, day).unwrap()
+    }
+
+    fn item(id: &str, date: NaiveDate, seq: i32, weight: f64, level: &str, due: u32) -> PlanItem {
+        PlanItem {
+            version_id: "V1".to_string(),
+            material_id: id.to_string(),
+            machine_code: "H032".to_string(),
+            plan_date: date,
+            seq_no: seq,
+            weight_t: weight,
+            source_type: "CALC".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            urgent_level: Some(level.to_string()),
+            sched_state: None,
+            assign_reason: None,
+            steel_grade: None,
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: None,
+            due_date: Some(d(due).format("%Y-%m-%d").to_string()),
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+
+    fn rush(item: PlanItem, scheduled: bool, earliest: Option<NaiveDate>) -> RushMaterial {
+        RushMaterial {
+            item,
+            scheduled,
+            urgent_level: UrgentLevel::L3,
+            earliest_sched_date: earliest,
+            due_date: Some(d(1)),
+        }
+    }
+
+    fn limits(days: &[u32], limit: f64) -> HashMap<(String, NaiveDate), f64> {
+        days.iter()
+            .map(|day| (("H032".to_string(), d(*day)), limit))
+            .collect()
+    }
+
+    fn run(
+        items: &[PlanItem],
+        limits: &HashMap<(String, NaiveDate), f64>,
+        rush: &[RushMaterial],
+    ) -> RushInsertionOutcome {
+        RushInsertionEngine::new().insert(&RushInsertionInput {
+            items,
+            limits,
+            first_open_date: d(1),
+            due_dates: &HashMap::new(),
+            rush,
+        })
+    }
+
+    fn position(outcome: &RushInsertionOutcome, id: &str) -> (NaiveDate, i32) {
+        let item = outcome.items.iter().find(|i| i.material_id == id).unwrap();
+        (item.plan_date, item.seq_no)
+    }
+
+    #[test]
+    fn test_insert_with_cascading_displacement_chain() {
+        let mut locked = item("L", d(1), 1, 20.0, "L0", 20);
+        locked.locked_in_plan = true;
+        let items = vec![
+            locked,
+            item("X1", d(1), 2, 60.0, "L0", 2),
+            item("X2", d(1), 3, 20.0, "L2", 1),
+            item("Y1", d(2), 1, 80.0, "L0", 20),
+        ];
+        let limits = limits(&[1, 2, 3], 100.0);
+        let outcome = run(
+            &items,
+            &limits,
+            &[rush(item("R", d(1), 0, 30.0, "L3", 1), false, None)],
+        );
+
+        assert!(outcome.infeasible.is_empty());
+        assert_eq!(outcome.placements.len(), 1);
+        assert_eq!(outcome.placements[0].to_date, d(1));
+        assert_eq!(outcome.placements[0].displaced_count, 2);
+        // 锁定材料之后插入; X2 紧急等级高保留
+        assert_eq!(position(&outcome, "L"), (d(1), 1));
+        assert_eq!(position(&outcome, "R"), (d(1), 2));
+        assert_eq!(position(&outcome, "X2"), (d(1), 3));
+        // X1 挤到次日, 次日交期更远的 Y1 被级联挤出
+        assert_eq!(position(&outcome, "X1"), (d(2), 1));
+        assert_eq!(position(&outcome, "Y1"), (d(3), 1));
+        let chain: Vec<(&str, NaiveDate, NaiveDate, usize)> = outcome
+            .displaced
+            .iter()
+            .map(|x| (x.material_id.as_str(), x.from_date, x.to_date, x.chain_step))
+            .collect();
+        assert_eq!(chain, vec![("X1", d(1), d(2), 1), ("Y1", d(2), d(3), 2)]);
+        assert_eq!(outcome.added_tardiness_days, 0);
+        assert!((outcome.displaced_weight_t - 140.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_insert_honors_maturity_and_reports_tardiness() {
+        let items = vec![item("B", d(2), 1, 40.0, "L0", 2)];
+        let limits = limits(&[1, 2, 3], 50.0);
+        let outcome = run(
+            &items,
+            &limits,
+            &[rush(item("R", d(1), 0, 20.0, "L3", 1), false, Some(d(2)))],
+        );
+
+        assert_eq!(outcome.placements[0].to_date, d(2));
+        assert_eq!(outcome.placements[0].tardiness_days, 1);
+        assert_eq!(position(&outcome, "B"), (d(3), 1));
+        assert_eq!(outcome.displaced[0].tardiness_after_days, 1);
+        assert_eq!(outcome.added_tardiness_days, 1);
+        assert_eq!(outcome.newly_late_count, 1);
+    }
+
+    #[test]
+    fn test_scheduled_rush_moves_earlier_past_locked_day() {
+        let mut locked = item("L", d(1), 1, 100.0, "L0", 20);
+        locked.locked_in_plan = true;
+        let items = vec![
+            locked,
+            item("R", d(3), 1, 30.0, "L0", 1),
+            item("S", d(1), 1, 10.0, "L0", 20),
+        ];
+        let limits = limits(&[1, 2, 3], 100.0);
+        let outcome = run(
+            &items,
+            &limits,
+            &[
+                rush(items[1].clone(), true, None),
+                rush(item("S", d(1), 1, 10.0, "L0", 20), true, None),
+            ],
+        );
+
+        // D1 被锁定材料占满, R 提前到 D2, 无挤出
+        assert_eq!(outcome.placements.len(), 1);
+        assert_eq!(outcome.placements[0].from_date, Some(d(3)));
+        assert_eq!(outcome.placements[0].to_date, d(2));
+        assert!(outcome.displaced.is_empty());
+        // 已在最早日的材料无需插单
+        assert_eq!(outcome.infeasible.len(), 1);
+        assert_eq!(outcome.infeasible[0].material_id, "S");
+    }
+
+    #[test]
+    fn test_chain_beyond_window_is_infeasible() {
+        let items = vec![item("A", d(1), 1, 50.0, "L0", 20)];
+        let limits = limits(&[1], 50.0);
+        let outcome = run(
+            &items,
+            &limits,
+            &[rush(item("R", d(1), 0, 30.0, "L3", 1), false, None)],
+        );
+
+        assert!(outcome.placements.is_empty());
+        assert_eq!(outcome.infeasible.len(), 1);
+        assert_eq!(position(&outcome, "A"), (d(1), 1));
+    }
+}
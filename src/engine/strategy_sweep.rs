@@ -0,0 +1,390 @@
+// ==========================================
+// 热轧精整排产系统 - 自定义策略权重参数扫描
+// ==========================================
+// 职责:
+// - 按网格 / 随机采样生成 CustomStrategyParameters 的权重组合
+// - 对每个组合的目标向量（PlanEvaluator 口径）求 Pareto 最优集
+// ==========================================
+// 口径:
+// - 未指定范围的权重保持为空（评分时按 0 处理），固定值可用 min=max
+// - 网格: 每个维度在 [min, max] 上等距取 steps 个点，组合数 = Π steps
+// - 随机: 每个维度在 [min, max] 上均匀采样，seed 固定时结果可复现
+// - Pareto 目标: 拖期天数↓ 产能利用率↑ 冷料库龄削减↑ 路径突破↓ 超限吨位↓
+// ==========================================
+
+use crate::config::strategy_profile::{CustomStrategyParameters, CustomStrategyProfile};
+use crate::engine::plan_evaluation::PlanObjectiveVector;
+use serde::{Deserialize, Serialize};
+
+/// 单次扫描最多生成的参数组合数（每个组合都要跑一次 dry-run 排产）
+pub const MAX_SWEEP_POINTS: usize = 64;
+
+/// 随机采样默认种子
+const DEFAULT_SWEEP_SEED: u64 = 20_260_101;
+
+// ==========================================
+// 扫描规格
+// ==========================================
+
+/// 采样方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SweepSampleMode {
+    Grid,
+    Random,
+}
+
+/// 单个权重的取值范围
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightRange {
+    pub min: f64,
+    pub max: f64,
+    /// 网格点数（仅 GRID 使用，默认 1 = 只取 min）
+    #[serde(default = "default_steps")]
+    pub steps: usize,
+}
+
+fn default_steps() -> usize {
+    1
+}
+
+/// 参数扫描规格
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategySweepSpec {
+    /// 基于哪个预设策略（同分时回落排序）
+    pub base_strategy: String,
+    pub mode: SweepSampleMode,
+
+    #[serde(default)]
+    pub urgent_weight: Option<WeightRange>,
+    #[serde(default)]
+    pub capacity_weight: Option<WeightRange>,
+    #[serde(default)]
+    pub cold_stock_weight: Option<WeightRange>,
+    #[serde(default)]
+    pub due_date_weight: Option<WeightRange>,
+    #[serde(default)]
+    pub rolling_output_age_weight: Option<WeightRange>,
+
+    /// 随机采样点数（仅 RANDOM 使用）
+    #[serde(default)]
+    pub sample_count: Option<usize>,
+    /// 随机种子（仅 RANDOM 使用）
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    // ===== 固定参数（所有组合共用）=====
+    #[serde(default)]
+    pub cold_stock_age_threshold_days: Option<i32>,
+    #[serde(default)]
+    pub overflow_tolerance_pct: Option<f64>,
+}
+
+// ==========================================
+// 扫描结果
+// ==========================================
+
+/// 扫描点（一个参数组合 = 一条策略草案）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategySweepPoint {
+    pub index: usize, // 从 1 开始
+    pub draft_id: String,
+    pub strategy_key: String,
+    pub parameters: CustomStrategyParameters,
+    pub objectives: PlanObjectiveVector,
+    pub pareto_optimal: bool,
+    pub dominated_by_count: usize, // 被多少个点支配
+}
+
+impl StrategySweepPoint {
+    /// 将扫描点转为可保存的自定义策略
+    pub fn to_custom_profile(
+        &self,
+        base_strategy: &str,
+        strategy_id: &str,
+        title: &str,
+    ) -> CustomStrategyProfile {
+        CustomStrategyProfile {
+            strategy_id: strategy_id.to_string(),
+            title: title.to_string(),
+            description: Some(format!(
+                "参数扫描点 #{}（{}）",
+                self.index, self.strategy_key
+            )),
+            base_strategy: base_strategy.to_string(),
+            parameters: self.parameters.clone(),
+        }
+    }
+}
+
+// ==========================================
+// StrategySweepEngine
+// ==========================================
+pub struct StrategySweepEngine;
+
+type WeightSetter = fn(&mut CustomStrategyParameters, f64);
+
+impl StrategySweepEngine {
+    /// 生成参数组合
+    ///
+    /// # 返回
+    /// - Err: 规格非法（无扫描维度 / 范围越界 / 组合数超过 MAX_SWEEP_POINTS）
+    pub fn generate_points(
+        spec: &StrategySweepSpec,
+    ) -> Result<Vec<CustomStrategyParameters>, String> {
+        let axes: Vec<(&str, &WeightRange, WeightSetter)> = [
+            (
+                "urgent_weight",
+                spec.urgent_weight.as_ref(),
+                (|p, v| p.urgent_weight = Some(v)) as WeightSetter,
+            ),
+            ("capacity_weight", spec.capacity_weight.as_ref(), |p, v| {
+                p.capacity_weight = Some(v)
+            }),
+            (
+                "cold_stock_weight",
+                spec.cold_stock_weight.as_ref(),
+                |p, v| p.cold_stock_weight = Some(v),
+            ),
+            ("due_date_weight", spec.due_date_weight.as_ref(), |p, v| {
+                p.due_date_weight = Some(v)
+            }),
+            (
+                "rolling_output_age_weight",
+                spec.rolling_output_age_weight.as_ref(),
+                |p, v| p.rolling_output_age_weight = Some(v),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, range, setter)| range.map(|r| (name, r, setter)))
+        .collect();
+
+        if axes.is_empty() {
+            return Err("至少需要指定一个权重的扫描范围".to_string());
+        }
+        for (name, range, _) in &axes {
+            if !range.min.is_finite() || !range.max.is_finite() {
+                return Err(format!("{} 范围必须为有效数字", name));
+            }
+            if range.min < 0.0 || range.max > 100.0 || range.min > range.max {
+                return Err(format!(
+                    "{} 范围非法（需满足 0 <= min <= max <= 100）",
+                    name
+                ));
+            }
+        }
+
+        let base = CustomStrategyParameters {
+            cold_stock_age_threshold_days: spec.cold_stock_age_threshold_days,
+            overflow_tolerance_pct: spec.overflow_tolerance_pct,
+            ..Default::default()
+        };
+
+        match spec.mode {
+            SweepSampleMode::Grid => {
+                let mut total = 1usize;
+                for (name, range, _) in &axes {
+                    if range.steps == 0 {
+                        return Err(format!("{} 网格点数必须 >= 1", name));
+                    }
+                    total = total.saturating_mul(range.steps);
+                }
+                if total > MAX_SWEEP_POINTS {
+                    return Err(format!(
+                        "网格组合数 {} 超过上限 {}",
+                        total, MAX_SWEEP_POINTS
+                    ));
+                }
+
+                let mut points = vec![base];
+                for (_, range, setter) in &axes {
+                    let values = Self::grid_values(range);
+                    points = points
+                        .into_iter()
+                        .flat_map(|p| {
+                            values.iter().map(move |v| {
+                                let mut next = p.clone();
+                                setter(&mut next, *v);
+                                next
+                            })
+                        })
+                        .collect();
+                }
+                Ok(points)
+            }
+            SweepSampleMode::Random => {
+                let count = spec.sample_count.unwrap_or(0);
+                if count == 0 || count > MAX_SWEEP_POINTS {
+                    return Err(format!("随机采样点数需在 1~{} 之间", MAX_SWEEP_POINTS));
+                }
+
+                let mut rng = SplitMix64(spec.seed.unwrap_or(DEFAULT_SWEEP_SEED));
+                Ok((0..count)
+                    .map(|_| {
+                        let mut p = base.clone();
+                        for (_, range, setter) in &axes {
+                            let v = range.min + rng.next_f64() * (range.max - range.min);
+                            setter(&mut p, round2(v));
+                        }
+                        p
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// 标记 Pareto 最优点
+    ///
+    /// # 返回
+    /// - 每个点被支配的次数（0 = Pareto 最优）
+    pub fn dominated_counts(objectives: &[PlanObjectiveVector]) -> Vec<usize> {
+        let keys: Vec<[f64; 5]> = objectives.iter().map(Self::minimization_key).collect();
+        keys.iter()
+            .map(|a| keys.iter().filter(|b| Self::dominates(b, a)).count())
+            .collect()
+    }
+
+    /// Pareto 目标（统一转换为越小越好）
+    fn minimization_key(v: &PlanObjectiveVector) -> [f64; 5] {
+        [
+            v.total_tardiness_days as f64,
+            -v.capacity_util_pct,
+            -(v.cold_stock_age_reduction_days as f64),
+            v.path_override_count as f64,
+            v.overflow_t,
+        ]
+    }
+
+    /// a 支配 b：所有目标不差于 b，且至少一个目标严格更好
+    fn dominates(a: &[f64; 5], b: &[f64; 5]) -> bool {
+        const EPS: f64 = 1e-9;
+        a.iter().zip(b.iter()).all(|(x, y)| *x <= *y + EPS)
+            && a.iter().zip(b.iter()).any(|(x, y)| *x < *y - EPS)
+    }
+
+    fn grid_values(range: &WeightRange) -> Vec<f64> {
+        if range.steps <= 1 {
+            return vec![round2(range.min)];
+        }
+        let step = (range.max - range.min) / (range.steps - 1) as f64;
+        (0..range.steps)
+            .map(|i| round2(range.min + step * i as f64))
+            .collect()
+    }
+}
+
+fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+/// 轻量确定性随机数（SplitMix64），避免为采样引入额外依赖
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(mode: SweepSampleMode) -> StrategySweepSpec {
+        StrategySweepSpec {
+            base_strategy: "balanced".to_string(),
+            mode,
+            urgent_weight: Some(WeightRange {
+                min: 0.0,
+                max: 10.0,
+                steps: 3,
+            }),
+            capacity_weight: None,
+            cold_stock_weight: Some(WeightRange {
+                min: 2.0,
+                max: 2.0,
+                steps: 1,
+            }),
+            due_date_weight: Some(WeightRange {
+                min: 1.0,
+                max: 5.0,
+                steps: 2,
+            }),
+            rolling_output_age_weight: None,
+            sample_count: Some(5),
+            seed: Some(7),
+            cold_stock_age_threshold_days: Some(30),
+            overflow_tolerance_pct: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_grid_points() {
+        let points = StrategySweepEngine::generate_points(&spec(SweepSampleMode::Grid)).unwrap();
+        assert_eq!(points.len(), 6);
+        let urgent: Vec<f64> = points.iter().filter_map(|p| p.urgent_weight).collect();
+        assert_eq!(urgent, vec![0.0, 0.0, 5.0, 5.0, 10.0, 10.0]);
+        assert!(points.iter().all(|p| p.cold_stock_weight == Some(2.0)));
+        assert!(points.iter().all(|p| p.capacity_weight.is_none()));
+        assert!(points
+            .iter()
+            .all(|p| p.cold_stock_age_threshold_days == Some(30)));
+
+        let mut too_many = spec(SweepSampleMode::Grid);
+        too_many.capacity_weight = Some(WeightRange {
+            min: 0.0,
+            max: 10.0,
+            steps: 11,
+        });
+        assert!(StrategySweepEngine::generate_points(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_generate_random_points_reproducible() {
+        let s = spec(SweepSampleMode::Random);
+        let a = StrategySweepEngine::generate_points(&s).unwrap();
+        let b = StrategySweepEngine::generate_points(&s).unwrap();
+        assert_eq!(a.len(), 5);
+        assert_eq!(
+            serde_json::to_string(&a).unwrap(),
+            serde_json::to_string(&b).unwrap()
+        );
+        assert!(a.iter().all(|p| {
+            let u = p.urgent_weight.unwrap();
+            let d = p.due_date_weight.unwrap();
+            (0.0..=10.0).contains(&u) && (1.0..=5.0).contains(&d)
+        }));
+
+        let mut empty = s.clone();
+        empty.urgent_weight = None;
+        empty.cold_stock_weight = None;
+        empty.due_date_weight = None;
+        assert!(StrategySweepEngine::generate_points(&empty).is_err());
+    }
+
+    #[test]
+    fn test_dominated_counts() {
+        let v = |tardiness: i64, util: f64, cold: i64| PlanObjectiveVector {
+            total_tardiness_days: tardiness,
+            capacity_util_pct: util,
+            cold_stock_age_reduction_days: cold,
+            ..Default::default()
+        };
+        let counts = StrategySweepEngine::dominated_counts(&[
+            v(0, 90.0, 100), // 最优
+            v(5, 95.0, 100), // 利用率更高，拖期更多 → 不被支配
+            v(5, 90.0, 50),  // 被前两个支配
+            v(0, 90.0, 100), // 与第一个相同 → 不互相支配
+        ]);
+        assert_eq!(counts, vec![0, 0, 3, 0]);
+    }
+}
@@ -0,0 +1,358 @@
+// ==========================================
+// 热轧精整排产系统 - 版本明细级对比引擎
+// ==========================================
+// 职责: 在 compare_versions 的 移动/新增/删除 计数之外，给出明细级的结构差异
+// 输入: 两个版本的 plan_item（已由 API 层补充 steel_grade/contract_no/due_date 快照）
+// 输出:
+// - 合同准时交付变化（按合同）
+// - 钢种结构变化（按 机组×日期×钢种）
+// - 同日顺序变化（按 机组×日期，Kendall-tau 距离）
+// ==========================================
+// 口径:
+// - 准时: plan_date <= 材料交期；未排产或无交期的材料不计为准时
+// - 合同准时率 = 准时材料数 / 合同在两版本中出现的材料总数（并集）
+// - 顺序变化仅比较两版本中同机组同日的共同材料，不受插单/移出引起的序号顺延影响
+// ==========================================
+
+use crate::domain::plan::PlanItem;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// 钢种缺失时的归类
+pub const UNKNOWN_GRADE: &str = "UNKNOWN";
+
+// ==========================================
+// ContractDeliveryDelta - 合同准时交付变化
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractDeliveryDelta {
+    pub contract_no: String,
+    pub due_date: Option<NaiveDate>, // 合同内最早交期
+    pub material_count: usize,       // 两版本并集材料数
+
+    pub scheduled_count_a: usize,
+    pub scheduled_count_b: usize,
+    pub on_time_count_a: usize,
+    pub on_time_count_b: usize,
+    pub on_time_rate_a: f64,
+    pub on_time_rate_b: f64,
+    pub on_time_rate_delta: f64, // B - A
+
+    pub finish_date_a: Option<NaiveDate>, // 合同内最晚排产日期
+    pub finish_date_b: Option<NaiveDate>,
+}
+
+// ==========================================
+// GradeMixDelta - 钢种结构变化
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradeMixDelta {
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub steel_grade: String,
+    pub weight_a: f64,
+    pub weight_b: f64,
+    pub weight_delta: f64, // B - A
+    pub count_a: usize,
+    pub count_b: usize,
+}
+
+// ==========================================
+// SequenceDelta - 同日顺序变化
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequenceDelta {
+    pub machine_code: String,
+    pub plan_date: NaiveDate,
+    pub common_count: usize,       // 两版本同日共同材料数
+    pub discordant_pairs: usize,   // 相对顺序颠倒的材料对数（Kendall-tau 距离）
+    pub kendall_tau_distance: f64, // 归一化距离 [0,1]，0=顺序一致，1=完全反序
+}
+
+// ==========================================
+// VersionDiffEngine - 明细级对比引擎
+// ==========================================
+pub struct VersionDiffEngine;
+
+impl VersionDiffEngine {
+    /// 合同准时交付变化（仅返回有变化的合同，按合同号排序）
+    pub fn contract_delivery_delta(
+        items_a: &[PlanItem],
+        items_b: &[PlanItem],
+    ) -> Vec<ContractDeliveryDelta> {
+        #[derive(Default)]
+        struct Side {
+            scheduled: usize,
+            on_time: usize,
+            finish: Option<NaiveDate>,
+        }
+
+        #[derive(Default)]
+        struct Acc {
+            materials: BTreeSet<String>,
+            due_date: Option<NaiveDate>,
+            a: Side,
+            b: Side,
+        }
+
+        fn collect(acc: &mut BTreeMap<String, Acc>, items: &[PlanItem], side_b: bool) {
+            for item in items {
+                let Some(contract_no) = item.contract_no.as_deref().filter(|s| !s.is_empty())
+                else {
+                    continue;
+                };
+                let due = item
+                    .due_date
+                    .as_deref()
+                    .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+
+                let entry = acc.entry(contract_no.to_string()).or_default();
+                entry.materials.insert(item.material_id.clone());
+                if let Some(due) = due {
+                    entry.due_date = Some(entry.due_date.map_or(due, |d| d.min(due)));
+                }
+
+                let side = if side_b { &mut entry.b } else { &mut entry.a };
+                side.scheduled += 1;
+                if due.is_some_and(|due| item.plan_date <= due) {
+                    side.on_time += 1;
+                }
+                side.finish = Some(
+                    side.finish
+                        .map_or(item.plan_date, |d| d.max(item.plan_date)),
+                );
+            }
+        }
+
+        let mut acc: BTreeMap<String, Acc> = BTreeMap::new();
+        collect(&mut acc, items_a, false);
+        collect(&mut acc, items_b, true);
+
+        acc.into_iter()
+            .filter(|(_, c)| {
+                c.a.scheduled != c.b.scheduled
+                    || c.a.on_time != c.b.on_time
+                    || c.a.finish != c.b.finish
+            })
+            .map(|(contract_no, c)| {
+                let total = c.materials.len();
+                let rate = |n: usize| n as f64 / total as f64;
+                ContractDeliveryDelta {
+                    contract_no,
+                    due_date: c.due_date,
+                    material_count: total,
+                    scheduled_count_a: c.a.scheduled,
+                    scheduled_count_b: c.b.scheduled,
+                    on_time_count_a: c.a.on_time,
+                    on_time_count_b: c.b.on_time,
+                    on_time_rate_a: rate(c.a.on_time),
+                    on_time_rate_b: rate(c.b.on_time),
+                    on_time_rate_delta: rate(c.b.on_time) - rate(c.a.on_time),
+                    finish_date_a: c.a.finish,
+                    finish_date_b: c.b.finish,
+                }
+            })
+            .collect()
+    }
+
+    /// 钢种结构变化（仅返回吨位或块数有变化的 机组×日期×钢种）
+    pub fn grade_mix_delta(items_a: &[PlanItem], items_b: &[PlanItem]) -> Vec<GradeMixDelta> {
+        // (机组, 日期, 钢种) -> (weight_a, count_a, weight_b, count_b)
+        type GradeKey = (String, NaiveDate, String);
+        let mut mix: BTreeMap<GradeKey, (f64, usize, f64, usize)> = BTreeMap::new();
+
+        let grade_of = |item: &PlanItem| {
+            item.steel_grade
+                .clone()
+                .filter(|g| !g.trim().is_empty())
+                .unwrap_or_else(|| UNKNOWN_GRADE.to_string())
+        };
+
+        for item in items_a {
+            let e = mix
+                .entry((item.machine_code.clone(), item.plan_date, grade_of(item)))
+                .or_default();
+            e.0 += item.weight_t;
+            e.1 += 1;
+        }
+        for item in items_b {
+            let e = mix
+                .entry((item.machine_code.clone(), item.plan_date, grade_of(item)))
+                .or_default();
+            e.2 += item.weight_t;
+            e.3 += 1;
+        }
+
+        mix.into_iter()
+            .filter(|(_, (wa, ca, wb, cb))| ca != cb || (wb - wa).abs() > 1e-6)
+            .map(
+                |(
+                    (machine_code, plan_date, steel_grade),
+                    (weight_a, count_a, weight_b, count_b),
+                )| {
+                    GradeMixDelta {
+                        machine_code,
+                        plan_date,
+                        steel_grade,
+                        weight_a,
+                        weight_b,
+                        weight_delta: weight_b - weight_a,
+                        count_a,
+                        count_b,
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// 同日顺序变化（仅返回 Kendall-tau 距离 > 0 的 机组×日期）
+    pub fn sequence_delta(items_a: &[PlanItem], items_b: &[PlanItem]) -> Vec<SequenceDelta> {
+        let seq_b: HashMap<(&str, NaiveDate, &str), i32> = items_b
+            .iter()
+            .map(|i| {
+                (
+                    (i.machine_code.as_str(), i.plan_date, i.material_id.as_str()),
+                    i.seq_no,
+                )
+            })
+            .collect();
+
+        // 机组×日期 -> [(seq_a, seq_b)]，仅保留同机组同日的共同材料
+        let mut days: BTreeMap<(&str, NaiveDate), Vec<(i32, i32)>> = BTreeMap::new();
+        for item in items_a {
+            let key = (
+                item.machine_code.as_str(),
+                item.plan_date,
+                item.material_id.as_str(),
+            );
+            if let Some(seq_b) = seq_b.get(&key) {
+                days.entry((item.machine_code.as_str(), item.plan_date))
+                    .or_default()
+                    .push((item.seq_no, *seq_b));
+            }
+        }
+
+        days.into_iter()
+            .filter_map(|((machine_code, plan_date), mut pairs)| {
+                pairs.sort();
+                let discordant = Self::discordant_pairs(&pairs);
+                if discordant == 0 {
+                    return None;
+                }
+                let n = pairs.len();
+                Some(SequenceDelta {
+                    machine_code: machine_code.to_string(),
+                    plan_date,
+                    common_count: n,
+                    discordant_pairs: discordant,
+                    kendall_tau_distance: discordant as f64 / (n * (n - 1) / 2) as f64,
+                })
+            })
+            .collect()
+    }
+
+    /// 已按 A 序号排序的 (seq_a, seq_b) 中，B 序号逆序的对数
+    fn discordant_pairs(pairs: &[(i32, i32)]) -> usize {
+        let mut count = 0;
+        for (i, (_, bi)) in pairs.iter().enumerate() {
+            count += pairs[i + 1..].iter().filter(|(_, bj)| bj < bi).count();
+        }
+        count
+    }
+}
+
+// ==========================================
+// 测试
+// ==========================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(material_id: &str, date: u32, seq_no: i32, weight_t: f64) -> PlanItem {
+        PlanItem {
+            version_id: "V".to_string(),
+            material_id: material_id.to_string(),
+            machine_code: "H032".to_string(),
+            plan_date: NaiveDate::from_ymd_opt(2030, 6, date).unwrap(),
+            seq_no,
+            weight_t,
+            source_type: "CALC".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            assign_reason: None,
+            urgent_level: None,
+            sched_state: None,
+            steel_grade: None,
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: None,
+            due_date: None,
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+
+    #[test]
+    fn test_sequence_delta_ignores_shift_and_counts_swaps() {
+        // A: M1,M2,M3,M4 ; B: 插入 X 后 M1,M3,M2,M4（仅 M2/M3 交换）
+        let a = vec![
+            item("M1", 1, 1, 10.0),
+            item("M2", 1, 2, 10.0),
+            item("M3", 1, 3, 10.0),
+            item("M4", 1, 4, 10.0),
+        ];
+        let b = vec![
+            item("X", 1, 1, 10.0),
+            item("M1", 1, 2, 10.0),
+            item("M3", 1, 3, 10.0),
+            item("M2", 1, 4, 10.0),
+            item("M4", 1, 5, 10.0),
+        ];
+        let delta = VersionDiffEngine::sequence_delta(&a, &b);
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta[0].common_count, 4);
+        assert_eq!(delta[0].discordant_pairs, 1);
+        assert!((delta[0].kendall_tau_distance - 1.0 / 6.0).abs() < 1e-9);
+
+        // 仅顺延，无相对顺序变化
+        let shifted: Vec<_> = a
+            .iter()
+            .map(|i| item(&i.material_id, 1, i.seq_no + 1, 10.0))
+            .collect();
+        assert!(VersionDiffEngine::sequence_delta(&a, &shifted).is_empty());
+    }
+
+    #[test]
+    fn test_contract_and_grade_delta() {
+        let with = |mut i: PlanItem, grade: &str| {
+            i.steel_grade = Some(grade.to_string());
+            i.contract_no = Some("C1".to_string());
+            i.due_date = Some("2030-06-01".to_string());
+            i
+        };
+        let a = vec![
+            with(item("M1", 1, 1, 10.0), "Q235"),
+            with(item("M2", 1, 2, 20.0), "Q345"),
+        ];
+        let b = vec![
+            with(item("M1", 1, 1, 10.0), "Q235"),
+            with(item("M2", 2, 1, 20.0), "Q345"),
+        ];
+
+        let contracts = VersionDiffEngine::contract_delivery_delta(&a, &b);
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(
+            (contracts[0].on_time_count_a, contracts[0].on_time_count_b),
+            (2, 1)
+        );
+        assert!((contracts[0].on_time_rate_delta + 0.5).abs() < 1e-9);
+
+        let mix = VersionDiffEngine::grade_mix_delta(&a, &b);
+        assert_eq!(mix.len(), 2);
+        assert!(mix.iter().all(|m| m.steel_grade == "Q345"));
+        assert_eq!(mix[0].weight_delta, -20.0);
+        assert_eq!(mix[1].weight_delta, 20.0);
+    }
+}
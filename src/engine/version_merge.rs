@@ -0,0 +1,609 @@
+// ==========================================
+// 热轧精整排产系统 - 版本三方合并引擎
+// ==========================================
+// 职责: 将人工调整(MANUAL / move_items)从 调整版本 回放到 重算版本
+// 输入: 共同祖先版本 + 调整版本 + 重算版本 的 plan_item
+// 输出: 合并后的 plan_item 集合 + 已回放变更 + 冲突清单
+// ==========================================
+// 合并口径:
+// - 人工变更: 调整版本中 source_type=MANUAL 或 出现在移动日志中的材料,
+//   且落位(机组/日期/序号)与共同祖先不同
+// - 回放: 在重算版本上将材料放到人工指定的 机组×日×序号,并重排当日序号
+// - 冲突 (需人工处理,不自动回放):
+//   1. 材料已不可排 (BLOCKED/COMPLETED/状态缺失/未适温且早于最早可排日)
+//   2. 目标日产能超上限
+//   3. 违反宽厚路径规则 (与目标位置前一块材料比较)
+// - 强制回放: 人工确认后可对 产能/路径 冲突强制回放,材料不可排冲突不可强制
+// ==========================================
+
+use crate::domain::plan::PlanItem;
+use crate::domain::types::{PathRuleStatus, SchedState, UrgentLevel};
+use crate::engine::path_rule::{Anchor, PathRuleEngine};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// ==========================================
+// ManualChange - 人工变更
+// ==========================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManualChange {
+    pub material_id: String,
+
+    // ===== 祖先版本落位 (祖先中不存在时为空) =====
+    pub from_machine_code: Option<String>,
+    pub from_plan_date: Option<NaiveDate>,
+    pub from_seq_no: Option<i32>,
+
+    // ===== 调整版本落位 =====
+    pub to_machine_code: String,
+    pub to_plan_date: NaiveDate,
+    pub to_seq_no: i32,
+    pub weight_t: f64,
+}
+
+// ==========================================
+// MergeConflictType - 冲突类型
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MergeConflictType {
+    MaterialNotEligible, // 材料已不可排
+    CapacityExceeded,    // 目标日产能超上限
+    PathRuleViolated,    // 违反宽厚路径规则
+}
+
+impl MergeConflictType {
+    /// 是否允许人工确认后强制回放
+    pub fn is_forceable(&self) -> bool {
+        !matches!(self, MergeConflictType::MaterialNotEligible)
+    }
+}
+
+// ==========================================
+// MergeConflict - 合并冲突
+// ==========================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeConflict {
+    pub material_id: String,
+    pub conflict_type: MergeConflictType,
+    pub change: ManualChange,
+    pub reason: String,
+}
+
+// ==========================================
+// MergeMaterialFacts - 合并校验所需的材料事实
+// ==========================================
+#[derive(Debug, Clone)]
+pub struct MergeMaterialFacts {
+    pub sched_state: Option<SchedState>,
+    pub earliest_sched_date: Option<NaiveDate>,
+    pub urgent_level: UrgentLevel,
+    pub width_mm: Option<f64>,
+    pub thickness_mm: Option<f64>,
+}
+
+// ==========================================
+// MergeContext - 合并上下文
+// ==========================================
+#[derive(Default)]
+pub struct MergeContext<'a> {
+    /// material_id → 材料事实 (缺失视为不可排)
+    pub facts: HashMap<String, MergeMaterialFacts>,
+    /// (机组, 日期) → 上限产能 (缺失时不做产能校验)
+    pub capacity_limits: HashMap<(String, NaiveDate), f64>,
+    /// 路径规则引擎 (None 表示不做路径校验)
+    pub path_rule_engine: Option<&'a PathRuleEngine>,
+    /// 人工确认强制回放的材料 (仅对可强制冲突生效)
+    pub forced_material_ids: HashSet<String>,
+}
+
+// ==========================================
+// MergeOutcome - 合并结果
+// ==========================================
+#[derive(Debug, Clone, Default)]
+pub struct MergeOutcome {
+    /// 合并后的明细 (version_id 为目标版本)
+    pub merged_items: Vec<PlanItem>,
+    /// 已回放的人工变更
+    pub applied: Vec<ManualChange>,
+    /// 强制回放的人工变更 (亦包含在 applied 中)
+    pub forced: Vec<MergeConflict>,
+    /// 未回放的冲突
+    pub conflicts: Vec<MergeConflict>,
+    /// 重算结果与人工落位一致,无需回放的数量
+    pub already_matching: usize,
+}
+
+// ==========================================
+// VersionMergeEngine - 版本三方合并引擎
+// ==========================================
+// 红线: 无状态引擎,所有方法都是纯函数
+pub struct VersionMergeEngine;
+
+impl VersionMergeEngine {
+    /// 创建新的合并引擎
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 提取人工变更
+    ///
+    /// # 参数
+    /// - `ancestor`: 共同祖先版本明细
+    /// - `adjusted`: 调整版本明细
+    /// - `logged_material_ids`: 调整版本移动日志中的材料
+    pub fn extract_manual_changes(
+        &self,
+        ancestor: &[PlanItem],
+        adjusted: &[PlanItem],
+        logged_material_ids: &HashSet<String>,
+    ) -> Vec<ManualChange> {
+        let ancestor_map: HashMap<&str, &PlanItem> = ancestor
+            .iter()
+            .map(|i| (i.material_id.as_str(), i))
+            .collect();
+
+        let mut changes: Vec<ManualChange> = adjusted
+            .iter()
+            .filter(|item| {
+                item.source_type == "MANUAL" || logged_material_ids.contains(&item.material_id)
+            })
+            .filter_map(|item| {
+                let base = ancestor_map.get(item.material_id.as_str()).copied();
+                if let Some(b) = base {
+                    if Self::same_placement(b, &item.machine_code, item.plan_date, item.seq_no) {
+                        return None;
+                    }
+                }
+                Some(ManualChange {
+                    material_id: item.material_id.clone(),
+                    from_machine_code: base.map(|b| b.machine_code.clone()),
+                    from_plan_date: base.map(|b| b.plan_date),
+                    from_seq_no: base.map(|b| b.seq_no),
+                    to_machine_code: item.machine_code.clone(),
+                    to_plan_date: item.plan_date,
+                    to_seq_no: item.seq_no,
+                    weight_t: item.weight_t,
+                })
+            })
+            .collect();
+
+        changes.sort_by(|a, b| {
+            (
+                a.to_plan_date,
+                &a.to_machine_code,
+                a.to_seq_no,
+                &a.material_id,
+            )
+                .cmp(&(
+                    b.to_plan_date,
+                    &b.to_machine_code,
+                    b.to_seq_no,
+                    &b.material_id,
+                ))
+        });
+        changes
+    }
+
+    /// 将人工变更回放到重算版本
+    ///
+    /// # 参数
+    /// - `target_version_id`: 合并结果所属版本
+    /// - `changes`: 人工变更 (按目标 日期/机组/序号 顺序回放)
+    /// - `recalculated`: 重算版本明细
+    /// - `ctx`: 合并上下文
+    pub fn merge(
+        &self,
+        target_version_id: &str,
+        changes: &[ManualChange],
+        recalculated: &[PlanItem],
+        ctx: &MergeContext<'_>,
+    ) -> MergeOutcome {
+        let mut outcome = MergeOutcome::default();
+        let mut working: HashMap<String, PlanItem> = recalculated
+            .iter()
+            .map(|i| (i.material_id.clone(), i.clone()))
+            .collect();
+        let mut touched: BTreeSet<(String, NaiveDate)> = BTreeSet::new();
+
+        for change in changes {
+            if let Some(existing) = working.get(&change.material_id) {
+                if Self::same_placement(
+                    existing,
+                    &change.to_machine_code,
+                    change.to_plan_date,
+                    change.to_seq_no,
+                ) {
+                    outcome.already_matching += 1;
+                    continue;
+                }
+            }
+
+            // 1. 材料可排性 (不可强制)
+            if let Some(reason) = Self::check_eligibility(change, ctx) {
+                outcome.conflicts.push(MergeConflict {
+                    material_id: change.material_id.clone(),
+                    conflict_type: MergeConflictType::MaterialNotEligible,
+                    change: change.clone(),
+                    reason,
+                });
+                continue;
+            }
+
+            // 2. 产能 / 路径 (可强制)
+            let conflict = Self::check_capacity(change, &working, ctx)
+                .map(|r| (MergeConflictType::CapacityExceeded, r))
+                .or_else(|| {
+                    Self::check_path_rule(change, &working, ctx)
+                        .map(|r| (MergeConflictType::PathRuleViolated, r))
+                });
+            if let Some((conflict_type, reason)) = conflict {
+                let record = MergeConflict {
+                    material_id: change.material_id.clone(),
+                    conflict_type,
+                    change: change.clone(),
+                    reason,
+                };
+                if ctx.forced_material_ids.contains(&change.material_id) {
+                    outcome.forced.push(record);
+                } else {
+                    outcome.conflicts.push(record);
+                    continue;
+                }
+            }
+
+            // 3. 回放
+            let item = match working.remove(&change.material_id) {
+                Some(mut existing) => {
+                    touched.insert((existing.machine_code.clone(), existing.plan_date));
+                    existing.machine_code = change.to_machine_code.clone();
+                    existing.plan_date = change.to_plan_date;
+                    existing.seq_no = change.to_seq_no;
+                    existing
+                }
+                None => Self::new_manual_item(target_version_id, change),
+            };
+            let mut item = item;
+            item.source_type = "MANUAL".to_string();
+            item.assign_reason = Some("MERGE_REPLAY".to_string());
+            touched.insert((change.to_machine_code.clone(), change.to_plan_date));
+            working.insert(change.material_id.clone(), item);
+            outcome.applied.push(change.clone());
+        }
+
+        // 4. 重排受影响 机组×日 的序号 (人工回放项在同序号时优先)
+        let applied_ids: HashSet<&str> = outcome
+            .applied
+            .iter()
+            .map(|c| c.material_id.as_str())
+            .collect();
+        for (machine, date) in &touched {
+            let mut day: Vec<&mut PlanItem> = working
+                .values_mut()
+                .filter(|i| &i.machine_code == machine && i.plan_date == *date)
+                .collect();
+            day.sort_by(|a, b| {
+                let a_key = (a.seq_no, !applied_ids.contains(a.material_id.as_str()));
+                let b_key = (b.seq_no, !applied_ids.contains(b.material_id.as_str()));
+                a_key
+                    .cmp(&b_key)
+                    .then_with(|| a.material_id.cmp(&b.material_id))
+            });
+            for (idx, item) in day.into_iter().enumerate() {
+                item.seq_no = idx as i32 + 1;
+            }
+        }
+
+        let mut merged: Vec<PlanItem> = working
+            .into_values()
+            .map(|mut i| {
+                i.version_id = target_version_id.to_string();
+                i
+            })
+            .collect();
+        merged.sort_by(|a, b| {
+            (a.plan_date, &a.machine_code, a.seq_no).cmp(&(b.plan_date, &b.machine_code, b.seq_no))
+        });
+        outcome.merged_items = merged;
+        outcome
+    }
+
+    // ==========================================
+    // 内部校验
+    // ==========================================
+
+    fn same_placement(item: &PlanItem, machine: &str, date: NaiveDate, seq_no: i32) -> bool {
+        item.machine_code == machine && item.plan_date == date && item.seq_no == seq_no
+    }
+
+    fn check_eligibility(change: &ManualChange, ctx: &MergeContext<'_>) -> Option<String> {
+        let facts = match ctx.facts.get(&change.material_id) {
+            Some(f) => f,
+            None => return Some("材料状态缺失".to_string()),
+        };
+        match facts.sched_state {
+            None => Some("材料状态缺失".to_string()),
+            Some(SchedState::Blocked) => Some("材料被数据质量阻断(BLOCKED)".to_string()),
+            Some(SchedState::Completed) => Some("材料已完工(COMPLETED)".to_string()),
+            Some(SchedState::PendingMature) => match facts.earliest_sched_date {
+                Some(d) if change.to_plan_date < d => Some(format!(
+                    "材料未适温: 最早可排日 {}, 目标日 {}",
+                    d, change.to_plan_date
+                )),
+                _ => None,
+            },
+            Some(_) => None,
+        }
+    }
+
+    fn check_capacity(
+        change: &ManualChange,
+        working: &HashMap<String, PlanItem>,
+        ctx: &MergeContext<'_>,
+    ) -> Option<String> {
+        let limit = *ctx
+            .capacity_limits
+            .get(&(change.to_machine_code.clone(), change.to_plan_date))?;
+        let used: f64 = working
+            .values()
+            .filter(|i| {
+                i.material_id != change.material_id
+                    && i.machine_code == change.to_machine_code
+                    && i.plan_date == change.to_plan_date
+            })
+            .map(|i| i.weight_t)
+            .sum();
+        let total = used + change.weight_t;
+        if total > limit + 1e-9 {
+            Some(format!(
+                "{} {} 产能超限: {:.1}t > 上限 {:.1}t",
+                change.to_machine_code, change.to_plan_date, total, limit
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn check_path_rule(
+        change: &ManualChange,
+        working: &HashMap<String, PlanItem>,
+        ctx: &MergeContext<'_>,
+    ) -> Option<String> {
+        let engine = ctx.path_rule_engine?;
+        let facts = ctx.facts.get(&change.material_id)?;
+        let (width, thickness) = (facts.width_mm?, facts.thickness_mm?);
+
+        // 锚点: 目标位置前一块材料
+        let anchor = working
+            .values()
+            .filter(|i| {
+                i.material_id != change.material_id
+                    && i.machine_code == change.to_machine_code
+                    && i.plan_date == change.to_plan_date
+                    && i.seq_no < change.to_seq_no
+            })
+            .max_by_key(|i| i.seq_no)
+            .and_then(|prev| {
+                let prev_facts = ctx.facts.get(&prev.material_id);
+                let w = prev_facts.and_then(|f| f.width_mm).or(prev.width_mm)?;
+                let t = prev_facts
+                    .and_then(|f| f.thickness_mm)
+                    .or(prev.thickness_mm)?;
+                Some((
+                    prev.material_id.clone(),
+                    Anchor {
+                        width_mm: w,
+                        thickness_mm: t,
+                    },
+                ))
+            });
+        let (anchor_id, anchor) = anchor?;
+
+        let result = engine.check(width, thickness, facts.urgent_level, Some(&anchor), false);
+        if result.status == PathRuleStatus::Ok {
+            return None;
+        }
+        Some(format!(
+            "相对前一块 {} 路径违规({}): 宽度超出 {:.1}mm, 厚度超出 {:.2}mm",
+            anchor_id, result.status, result.width_delta_mm, result.thickness_delta_mm
+        ))
+    }
+
+    fn new_manual_item(version_id: &str, change: &ManualChange) -> PlanItem {
+        PlanItem {
+            version_id: version_id.to_string(),
+            material_id: change.material_id.clone(),
+            machine_code: change.to_machine_code.clone(),
+            plan_date: change.to_plan_date,
+            seq_no: change.to_seq_no,
+            weight_t: change.weight_t,
+            source_type: "MANUAL".to_string(),
+            locked_in_plan: false,
+            force_release_in_plan: false,
+            violation_flags: None,
+            urgent_level: None,
+            sched_state: None,
+            assign_reason: None,
+            steel_grade: None,
+            width_mm: None,
+            thickness_mm: None,
+            contract_no: None,
+            due_date: None,
+            scheduled_date: None,
+            scheduled_machine_code: None,
+        }
+    }
+}
+
+impl Default for VersionMergeEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ==========================================
+// 测试
+// ==========================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::path_rule::PathRuleConfig;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 4, day).unwrap()
+    }
+
+    fn item(version: &str, id: &str, date: NaiveDate, seq: i32, source: &str) -> PlanItem {
+        let mut item = VersionMergeEngine::new_manual_item(
+            version,
+            &ManualChange {
+                material_id: id.to_string(),
+                from_machine_code: None,
+                from_plan_date: None,
+                from_seq_no: None,
+                to_machine_code: "H032".to_string(),
+                to_plan_date: date,
+                to_seq_no: seq,
+                weight_t: 10.0,
+            },
+        );
+        item.source_type = source.to_string();
+        item
+    }
+
+    fn ready(width: f64, thickness: f64) -> MergeMaterialFacts {
+        MergeMaterialFacts {
+            sched_state: Some(SchedState::Ready),
+            earliest_sched_date: None,
+            urgent_level: UrgentLevel::L0,
+            width_mm: Some(width),
+            thickness_mm: Some(thickness),
+        }
+    }
+
+    #[test]
+    fn test_extract_manual_changes_skips_unchanged() {
+        let engine = VersionMergeEngine::new();
+        let ancestor = vec![
+            item("base", "M1", d(1), 1, "CALC"),
+            item("base", "M2", d(1), 2, "CALC"),
+        ];
+        let adjusted = vec![
+            item("adj", "M1", d(2), 1, "MANUAL"),
+            item("adj", "M2", d(1), 2, "MANUAL"),
+            item("adj", "M3", d(1), 3, "CALC"),
+        ];
+        let logged: HashSet<String> = ["M3".to_string()].into_iter().collect();
+
+        let changes = engine.extract_manual_changes(&ancestor, &adjusted, &logged);
+        let ids: Vec<_> = changes.iter().map(|c| c.material_id.as_str()).collect();
+        assert_eq!(ids, vec!["M3", "M1"]);
+        assert_eq!(changes[1].from_plan_date, Some(d(1)));
+        assert_eq!(changes[0].from_plan_date, None);
+    }
+
+    #[test]
+    fn test_merge_replays_and_resequences() {
+        let engine = VersionMergeEngine::new();
+        let recalculated = vec![
+            item("new", "M1", d(1), 1, "CALC"),
+            item("new", "M2", d(2), 1, "CALC"),
+            item("new", "M3", d(2), 2, "CALC"),
+        ];
+        let changes = vec![ManualChange {
+            material_id: "M1".to_string(),
+            from_machine_code: Some("H032".to_string()),
+            from_plan_date: Some(d(1)),
+            from_seq_no: Some(1),
+            to_machine_code: "H032".to_string(),
+            to_plan_date: d(2),
+            to_seq_no: 1,
+            weight_t: 10.0,
+        }];
+        let mut ctx = MergeContext::default();
+        for id in ["M1", "M2", "M3"] {
+            ctx.facts.insert(id.to_string(), ready(1200.0, 3.0));
+        }
+
+        let outcome = engine.merge("merged", &changes, &recalculated, &ctx);
+        assert_eq!(outcome.applied.len(), 1);
+        assert!(outcome.conflicts.is_empty());
+        let day2: Vec<_> = outcome
+            .merged_items
+            .iter()
+            .filter(|i| i.plan_date == d(2))
+            .map(|i| (i.material_id.as_str(), i.seq_no))
+            .collect();
+        assert_eq!(day2, vec![("M1", 1), ("M2", 2), ("M3", 3)]);
+        assert!(outcome
+            .merged_items
+            .iter()
+            .all(|i| i.version_id == "merged"));
+        let m1 = outcome
+            .merged_items
+            .iter()
+            .find(|i| i.material_id == "M1")
+            .unwrap();
+        assert_eq!(m1.source_type, "MANUAL");
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts_and_honors_force() {
+        let engine = VersionMergeEngine::new();
+        let path_engine = PathRuleEngine::new(PathRuleConfig::default());
+        let recalculated = vec![
+            item("new", "M1", d(1), 1, "CALC"),
+            item("new", "M2", d(2), 1, "CALC"),
+        ];
+        let change = |id: &str, seq: i32| ManualChange {
+            material_id: id.to_string(),
+            from_machine_code: None,
+            from_plan_date: None,
+            from_seq_no: None,
+            to_machine_code: "H032".to_string(),
+            to_plan_date: d(2),
+            to_seq_no: seq,
+            weight_t: 10.0,
+        };
+        // X1 已完工; M1 放到 M2 之后宽度变宽 → 路径违规; X2 放入后超产能
+        let changes = vec![change("X1", 1), change("M1", 2), change("X2", 3)];
+        let mut ctx = MergeContext {
+            path_rule_engine: Some(&path_engine),
+            ..Default::default()
+        };
+        ctx.facts.insert("M1".to_string(), ready(1500.0, 3.0));
+        ctx.facts.insert("M2".to_string(), ready(1200.0, 3.0));
+        ctx.facts.insert("X2".to_string(), ready(1000.0, 2.0));
+        let mut completed = ready(1000.0, 2.0);
+        completed.sched_state = Some(SchedState::Completed);
+        ctx.facts.insert("X1".to_string(), completed);
+        ctx.capacity_limits.insert(("H032".to_string(), d(2)), 20.0);
+
+        let outcome = engine.merge("merged", &changes, &recalculated, &ctx);
+        let types: Vec<_> = outcome
+            .conflicts
+            .iter()
+            .map(|c| (c.material_id.as_str(), c.conflict_type))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("X1", MergeConflictType::MaterialNotEligible),
+                ("M1", MergeConflictType::PathRuleViolated),
+            ]
+        );
+        assert_eq!(outcome.applied.len(), 1);
+        assert_eq!(outcome.applied[0].material_id, "X2");
+
+        // 强制回放 M1 与 X1: M1 生效, X1 不可强制
+        ctx.forced_material_ids = ["M1".to_string(), "X1".to_string()].into_iter().collect();
+        let outcome = engine.merge("merged", &changes, &recalculated, &ctx);
+        assert_eq!(outcome.forced.len(), 1);
+        assert_eq!(outcome.forced[0].material_id, "M1");
+        assert_eq!(outcome.conflicts.len(), 2);
+        assert!(outcome.conflicts.iter().any(
+            |c| c.material_id == "X2" && c.conflict_type == MergeConflictType::CapacityExceeded
+        ));
+    }
+}
@@ -0,0 +1,446 @@
+// ==========================================
+// 热轧精整排产系统 - 人工调整反推策略权重（离线分析）
+// ==========================================
+// 职责:
+// - 以计划员的人工调整（移动/设紧急/强制放行）为偏好样本
+// - 拟合 CustomStrategyParameters 的五个权重，使排序结论与人工选择尽量一致
+// - 基于人工设紧急/强放的交期分布，给出紧急阈值（N1/N2）调整建议
+// ==========================================
+// 口径:
+// - 偏好样本 = (胜者, 败者, 当时日期)，胜者表示计划员希望其排在败者之前
+// - 一致率 = PrioritySorter.compare_pair 判定胜者优先的样本占比（同分按预设策略 tie-break）
+// - 拟合方式: 在离散权重档位上做坐标上升，仅在一致率严格提升时更新，结果可复现
+// - LOCKED / FORCE_RELEASE 不受策略影响（红线），由调用方在构造样本时剔除
+// ==========================================
+
+use crate::config::strategy_profile::CustomStrategyParameters;
+use crate::domain::material::{MaterialMaster, MaterialState};
+use crate::engine::priority::PrioritySorter;
+use crate::engine::strategy::ScheduleStrategy;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// 权重候选档位（与自定义策略编辑器的 0~100 取值范围一致）
+pub const LEARNABLE_WEIGHT_VALUES: [f64; 8] = [0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0];
+
+/// 坐标上升最大轮数
+pub const MAX_LEARNING_ROUNDS: usize = 4;
+
+/// 紧急阈值建议的最少证据条数
+pub const MIN_RULE_EVIDENCE: usize = 3;
+
+/// 紧急阈值建议上限（天）
+const MAX_SUGGESTED_N1_DAYS: i64 = 30;
+const MAX_SUGGESTED_N2_DAYS: i64 = 60;
+
+// ==========================================
+// 输入 / 输出
+// ==========================================
+
+/// 偏好样本：计划员希望 winner 排在 loser 之前
+#[derive(Debug, Clone)]
+pub struct PreferencePair {
+    pub winner: (MaterialMaster, MaterialState),
+    pub loser: (MaterialMaster, MaterialState),
+    /// 调整发生的日期（交期紧迫度按该日计算）
+    pub today: NaiveDate,
+}
+
+/// 一致率统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgreementRate {
+    pub agreed: usize,
+    pub total: usize,
+    /// agreed / total（无样本时为 0）
+    pub rate: f64,
+}
+
+impl AgreementRate {
+    fn new(agreed: usize, total: usize) -> Self {
+        let rate = if total == 0 {
+            0.0
+        } else {
+            agreed as f64 / total as f64
+        };
+        Self {
+            agreed,
+            total,
+            rate,
+        }
+    }
+}
+
+/// 权重拟合结果
+#[derive(Debug, Clone)]
+pub struct WeightLearningOutcome {
+    pub learned_parameters: CustomStrategyParameters,
+    pub baseline: AgreementRate,
+    pub learned: AgreementRate,
+    /// 实际执行的坐标上升轮数
+    pub rounds: usize,
+}
+
+/// 紧急阈值调整建议
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrgencyRuleSuggestion {
+    /// 配置键（urgent_n1_days / urgent_n2_days）
+    pub config_key: String,
+    pub current_value: i64,
+    pub suggested_value: i64,
+    /// 证据条数（人工设紧急 / 强制放行且交期未过）
+    pub evidence_count: usize,
+    /// 现阈值下已覆盖的证据条数
+    pub covered_before: usize,
+    /// 建议阈值下覆盖的证据条数
+    pub covered_after: usize,
+    pub reason: String,
+}
+
+// ==========================================
+// WeightLearningEngine
+// ==========================================
+
+pub struct WeightLearningEngine {
+    sorter: PrioritySorter,
+}
+
+impl WeightLearningEngine {
+    pub fn new() -> Self {
+        Self {
+            sorter: PrioritySorter::new(),
+        }
+    }
+
+    /// 计算某组参数（None = 预设策略）对偏好样本的一致率
+    pub fn agreement(
+        &self,
+        pairs: &[PreferencePair],
+        base_strategy: ScheduleStrategy,
+        params: Option<&CustomStrategyParameters>,
+    ) -> AgreementRate {
+        let agreed = pairs
+            .iter()
+            .filter(|p| {
+                self.sorter
+                    .compare_pair(&p.winner, &p.loser, base_strategy, params, p.today)
+                    == Ordering::Less
+            })
+            .count();
+        AgreementRate::new(agreed, pairs.len())
+    }
+
+    /// 拟合权重
+    ///
+    /// # 参数
+    /// - `base_params`: 当前策略参数（预设策略为 None）；非权重参数（冷坨阈值/溢出比例）原样保留，
+    ///   已设置的权重作为坐标上升起点
+    pub fn learn(
+        &self,
+        pairs: &[PreferencePair],
+        base_strategy: ScheduleStrategy,
+        base_params: Option<&CustomStrategyParameters>,
+    ) -> WeightLearningOutcome {
+        let baseline = self.agreement(pairs, base_strategy, base_params);
+
+        let mut current = base_params.cloned().unwrap_or_default();
+        let mut weights = [
+            current.urgent_weight,
+            current.capacity_weight,
+            current.cold_stock_weight,
+            current.due_date_weight,
+            current.rolling_output_age_weight,
+        ]
+        .map(|w| w.unwrap_or(0.0));
+        apply_weights(&mut current, &weights);
+
+        let mut best = self.agreement(pairs, base_strategy, Some(&current)).agreed;
+        let mut rounds = 0;
+        while rounds < MAX_LEARNING_ROUNDS && !pairs.is_empty() {
+            rounds += 1;
+            let mut improved = false;
+            for axis in 0..weights.len() {
+                for value in LEARNABLE_WEIGHT_VALUES {
+                    if weights[axis] == value {
+                        continue;
+                    }
+                    let mut candidate_weights = weights;
+                    candidate_weights[axis] = value;
+                    let mut candidate = current.clone();
+                    apply_weights(&mut candidate, &candidate_weights);
+
+                    let agreed = self
+                        .agreement(pairs, base_strategy, Some(&candidate))
+                        .agreed;
+                    if agreed > best {
+                        best = agreed;
+                        weights = candidate_weights;
+                        current = candidate;
+                        improved = true;
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+
+        WeightLearningOutcome {
+            learned: AgreementRate::new(best, pairs.len()),
+            learned_parameters: current,
+            baseline,
+            rounds,
+        }
+    }
+
+    /// 基于人工设紧急/强制放行的“距交期天数”给出 N1/N2 阈值建议
+    ///
+    /// # 参数
+    /// - `days_to_due`: 每条证据在调整当日距交期的天数（已超期的证据应由调用方剔除，超期本身即 L3）
+    /// - `n1_days` / `n2_days`: 当前阈值
+    ///
+    /// # 规则
+    /// - 证据不足 MIN_RULE_EVIDENCE 条不给建议
+    /// - N1: 现阈值覆盖不到半数证据时，建议放宽到证据中位数
+    /// - N2: 建议覆盖 80% 证据；N1 放宽后不低于 N1 + 原间隔
+    pub fn suggest_urgency_rules(
+        &self,
+        days_to_due: &[i64],
+        n1_days: i64,
+        n2_days: i64,
+    ) -> Vec<UrgencyRuleSuggestion> {
+        let mut sorted: Vec<i64> = days_to_due.iter().copied().filter(|d| *d >= 0).collect();
+        if sorted.len() < MIN_RULE_EVIDENCE {
+            return Vec::new();
+        }
+        sorted.sort_unstable();
+        let total = sorted.len();
+        let covered = |threshold: i64| sorted.iter().filter(|d| **d <= threshold).count();
+
+        let mut suggestions = Vec::new();
+
+        let mut suggested_n1 = n1_days;
+        let covered_n1 = covered(n1_days);
+        if covered_n1 * 2 < total {
+            suggested_n1 = sorted[(total - 1) / 2].min(MAX_SUGGESTED_N1_DAYS);
+            if suggested_n1 > n1_days {
+                suggestions.push(UrgencyRuleSuggestion {
+                    config_key: "urgent_n1_days".to_string(),
+                    current_value: n1_days,
+                    suggested_value: suggested_n1,
+                    evidence_count: total,
+                    covered_before: covered_n1,
+                    covered_after: covered(suggested_n1),
+                    reason: format!(
+                        "人工提级材料距交期中位数{}天，现N1={}天仅覆盖{}/{}条",
+                        suggested_n1, n1_days, covered_n1, total
+                    ),
+                });
+            } else {
+                suggested_n1 = n1_days;
+            }
+        }
+
+        // 80 分位（nearest-rank）
+        let p80 = sorted[((total * 4).div_ceil(5)).saturating_sub(1)];
+        let gap = (n2_days - n1_days).max(1);
+        let suggested_n2 = p80
+            .max(if suggested_n1 > n1_days {
+                suggested_n1 + gap
+            } else {
+                n2_days
+            })
+            .min(MAX_SUGGESTED_N2_DAYS);
+        if suggested_n2 > n2_days {
+            let covered_n2 = covered(n2_days);
+            suggestions.push(UrgencyRuleSuggestion {
+                config_key: "urgent_n2_days".to_string(),
+                current_value: n2_days,
+                suggested_value: suggested_n2,
+                evidence_count: total,
+                covered_before: covered_n2,
+                covered_after: covered(suggested_n2),
+                reason: format!(
+                    "人工提级材料80%距交期不超过{}天，现N2={}天覆盖{}/{}条",
+                    p80, n2_days, covered_n2, total
+                ),
+            });
+        }
+
+        suggestions
+    }
+}
+
+impl Default for WeightLearningEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn apply_weights(params: &mut CustomStrategyParameters, weights: &[f64; 5]) {
+    params.urgent_weight = Some(weights[0]);
+    params.capacity_weight = Some(weights[1]);
+    params.cold_stock_weight = Some(weights[2]);
+    params.due_date_weight = Some(weights[3]);
+    params.rolling_output_age_weight = Some(weights[4]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::{RushLevel, SchedState, UrgentLevel};
+    use chrono::{Duration, Utc};
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()
+    }
+
+    fn material(id: &str, stock_age: i32, due_in: i64) -> (MaterialMaster, MaterialState) {
+        let master = MaterialMaster {
+            material_id: id.to_string(),
+            manufacturing_order_id: None,
+            material_status_code_src: None,
+            steel_mark: None,
+            slab_id: None,
+            next_machine_code: None,
+            rework_machine_code: None,
+            current_machine_code: Some("H032".to_string()),
+            width_mm: None,
+            thickness_mm: None,
+            length_m: None,
+            weight_t: None,
+            available_width_mm: None,
+            due_date: Some(today() + Duration::days(due_in)),
+            stock_age_days: Some(stock_age),
+            output_age_days_raw: None,
+            rolling_output_date: None,
+            status_updated_at: None,
+            contract_no: None,
+            contract_nature: None,
+            weekly_delivery_flag: None,
+            export_flag: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let state = MaterialState {
+            material_id: id.to_string(),
+            sched_state: SchedState::Ready,
+            lock_flag: false,
+            force_release_flag: false,
+            urgent_level: UrgentLevel::L0,
+            urgent_reason: None,
+            rush_level: RushLevel::L0,
+            rolling_output_age_days: 0,
+            ready_in_days: 0,
+            earliest_sched_date: None,
+            stock_age_days: stock_age,
+            scheduled_date: None,
+            scheduled_machine_code: None,
+            seq_no: None,
+            manual_urgent_flag: false,
+            user_confirmed: false,
+            user_confirmed_at: None,
+            user_confirmed_by: None,
+            user_confirmed_reason: None,
+            in_frozen_zone: false,
+            last_calc_version_id: None,
+            updated_at: Utc::now(),
+            updated_by: None,
+        };
+        (master, state)
+    }
+
+    fn pair(
+        winner: (MaterialMaster, MaterialState),
+        loser: (MaterialMaster, MaterialState),
+    ) -> PreferencePair {
+        PreferencePair {
+            winner,
+            loser,
+            today: today(),
+        }
+    }
+
+    #[test]
+    fn test_learns_due_date_preference_over_cold_stock() {
+        // 预设均衡策略冷料优先；计划员一贯把交期更近的材料提前
+        let pairs = vec![
+            pair(material("A", 5, 1), material("B", 50, 20)),
+            pair(material("C", 3, 2), material("D", 40, 15)),
+            pair(material("E", 10, 0), material("F", 60, 30)),
+        ];
+        let engine = WeightLearningEngine::new();
+        let outcome = engine.learn(&pairs, ScheduleStrategy::Balanced, None);
+
+        assert_eq!(outcome.baseline.agreed, 0);
+        assert_eq!(outcome.learned.agreed, 3);
+        assert!((outcome.learned.rate - 1.0).abs() < 1e-9);
+        assert_eq!(
+            engine.agreement(
+                &pairs,
+                ScheduleStrategy::Balanced,
+                Some(&outcome.learned_parameters)
+            ),
+            outcome.learned
+        );
+        let due_w = outcome.learned_parameters.due_date_weight.unwrap();
+        let cold_w = outcome.learned_parameters.cold_stock_weight.unwrap();
+        assert!(due_w > 0.0 && due_w * 14.0 > cold_w * 45.0);
+    }
+
+    #[test]
+    fn test_learning_never_regresses_and_keeps_non_weight_params() {
+        let base = CustomStrategyParameters {
+            cold_stock_weight: Some(10.0),
+            cold_stock_age_threshold_days: Some(30),
+            overflow_tolerance_pct: Some(0.05),
+            ..Default::default()
+        };
+        let pairs = vec![
+            pair(material("A", 50, 20), material("B", 5, 1)),
+            pair(material("C", 60, 10), material("D", 40, 2)),
+        ];
+        let engine = WeightLearningEngine::new();
+        let outcome = engine.learn(&pairs, ScheduleStrategy::ColdStockFirst, Some(&base));
+
+        assert!(outcome.learned.agreed >= outcome.baseline.agreed);
+        assert_eq!(
+            outcome.learned_parameters.cold_stock_age_threshold_days,
+            Some(30)
+        );
+        assert_eq!(
+            outcome.learned_parameters.overflow_tolerance_pct,
+            Some(0.05)
+        );
+
+        let empty = engine.learn(&[], ScheduleStrategy::Balanced, None);
+        assert_eq!(empty.baseline.total, 0);
+        assert_eq!(empty.rounds, 0);
+    }
+
+    #[test]
+    fn test_suggest_urgency_rules() {
+        let engine = WeightLearningEngine::new();
+
+        // 证据不足
+        assert!(engine.suggest_urgency_rules(&[5, 6], 3, 7).is_empty());
+        // 已被现阈值覆盖
+        assert!(engine.suggest_urgency_rules(&[1, 2, 3, 0], 3, 7).is_empty());
+
+        // 计划员常在距交期 5~10 天时手动提级
+        let suggestions = engine.suggest_urgency_rules(&[5, 6, 6, 8, 10, -2], 3, 7);
+        assert_eq!(suggestions.len(), 2);
+        let n1 = &suggestions[0];
+        assert_eq!(n1.config_key, "urgent_n1_days");
+        assert_eq!((n1.current_value, n1.suggested_value), (3, 6));
+        assert_eq!(
+            (n1.evidence_count, n1.covered_before, n1.covered_after),
+            (5, 0, 3)
+        );
+        let n2 = &suggestions[1];
+        assert_eq!(n2.config_key, "urgent_n2_days");
+        // N1 放宽到 6 后，N2 至少保持原间隔 4 天
+        assert_eq!((n2.current_value, n2.suggested_value), (7, 10));
+        assert_eq!(n2.covered_after, 5);
+    }
+}
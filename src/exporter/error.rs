@@ -0,0 +1,23 @@
+// ==========================================
+// 热轧精整排产系统 - 导出模块错误类型
+// ==========================================
+
+use thiserror::Error;
+
+/// 导出模块错误类型
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("导出格式不支持: {0}（仅支持 csv/xlsx）")]
+    UnsupportedFormat(String),
+
+    #[error("文件写入失败: {0}")]
+    FileWriteError(String),
+
+    #[error("CSV 生成失败: {0}")]
+    CsvWriteError(String),
+
+    #[error("XLSX 生成失败: {0}")]
+    XlsxWriteError(String),
+}
+
+pub type ExportResult<T> = Result<T, ExportError>;
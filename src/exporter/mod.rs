@@ -0,0 +1,12 @@
+// ==========================================
+// 热轧精整排产系统 - 导出层
+// ==========================================
+// 职责: 将内部结果导出为现场可用的文件
+// 支持: CSV (UTF-8 BOM，Excel 直接打开不乱码), XLSX
+// ==========================================
+
+pub mod error;
+pub mod table_writer;
+
+pub use error::{ExportError, ExportResult};
+pub use table_writer::{ExportCell, ExportFormat, ExportTable};
@@ -0,0 +1,285 @@
+// ==========================================
+// 热轧精整排产系统 - 表格导出
+// ==========================================
+// 职责: 将二维表写为 CSV / XLSX
+// 说明:
+// - CSV 带 UTF-8 BOM，便于 Excel 直接打开中文
+// - XLSX 为最小 OpenXML 结构（单工作表，内联字符串），无需额外依赖
+// ==========================================
+
+use super::error::{ExportError, ExportResult};
+use std::io::Write;
+use std::path::Path;
+
+// ==========================================
+// ExportFormat - 导出格式
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    /// 从字符串解析（大小写不敏感，允许带点号的扩展名）
+    pub fn parse(s: &str) -> ExportResult<Self> {
+        match s.trim().trim_start_matches('.').to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "xlsx" => Ok(ExportFormat::Xlsx),
+            other => Err(ExportError::UnsupportedFormat(other.to_string())),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+// ==========================================
+// ExportCell / ExportTable
+// ==========================================
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportCell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl ExportCell {
+    fn as_text(&self) -> String {
+        match self {
+            ExportCell::Text(s) => s.clone(),
+            ExportCell::Number(n) => n.to_string(),
+            ExportCell::Empty => String::new(),
+        }
+    }
+}
+
+impl From<String> for ExportCell {
+    fn from(s: String) -> Self {
+        ExportCell::Text(s)
+    }
+}
+
+impl From<&str> for ExportCell {
+    fn from(s: &str) -> Self {
+        ExportCell::Text(s.to_string())
+    }
+}
+
+impl From<f64> for ExportCell {
+    fn from(n: f64) -> Self {
+        ExportCell::Number(n)
+    }
+}
+
+impl From<i32> for ExportCell {
+    fn from(n: i32) -> Self {
+        ExportCell::Number(n as f64)
+    }
+}
+
+impl<T: Into<ExportCell>> From<Option<T>> for ExportCell {
+    fn from(v: Option<T>) -> Self {
+        v.map(Into::into).unwrap_or(ExportCell::Empty)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportTable {
+    pub sheet_name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<ExportCell>>,
+}
+
+impl ExportTable {
+    /// 按格式生成文件内容
+    pub fn to_bytes(&self, format: ExportFormat) -> ExportResult<Vec<u8>> {
+        match format {
+            ExportFormat::Csv => self.to_csv_bytes(),
+            ExportFormat::Xlsx => self.to_xlsx_bytes(),
+        }
+    }
+
+    /// 写入文件，返回数据行数
+    pub fn write_to_path(&self, path: &Path, format: ExportFormat) -> ExportResult<usize> {
+        let bytes = self.to_bytes(format)?;
+        std::fs::write(path, bytes).map_err(|e| ExportError::FileWriteError(e.to_string()))?;
+        Ok(self.rows.len())
+    }
+
+    fn to_csv_bytes(&self) -> ExportResult<Vec<u8>> {
+        let mut buf = "\u{feff}".as_bytes().to_vec();
+        {
+            let mut writer = csv::Writer::from_writer(&mut buf);
+            writer
+                .write_record(&self.headers)
+                .map_err(|e| ExportError::CsvWriteError(e.to_string()))?;
+            for row in &self.rows {
+                writer
+                    .write_record(row.iter().map(ExportCell::as_text))
+                    .map_err(|e| ExportError::CsvWriteError(e.to_string()))?;
+            }
+            writer
+                .flush()
+                .map_err(|e| ExportError::CsvWriteError(e.to_string()))?;
+        }
+        Ok(buf)
+    }
+
+    fn to_xlsx_bytes(&self) -> ExportResult<Vec<u8>> {
+        let xlsx_err = |e: &dyn std::fmt::Display| ExportError::XlsxWriteError(e.to_string());
+
+        let mut sheet = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+        );
+        let header_cells: Vec<ExportCell> = self
+            .headers
+            .iter()
+            .map(|h| ExportCell::from(h.as_str()))
+            .collect();
+        for (r, row) in std::iter::once(&header_cells)
+            .chain(self.rows.iter())
+            .enumerate()
+        {
+            sheet.push_str(&format!(r#"<row r="{}">"#, r + 1));
+            for (c, cell) in row.iter().enumerate() {
+                let cell_ref = format!("{}{}", column_name(c), r + 1);
+                match cell {
+                    ExportCell::Text(s) => sheet.push_str(&format!(
+                        r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                        cell_ref,
+                        xml_escape(s)
+                    )),
+                    ExportCell::Number(n) if n.is_finite() => {
+                        sheet.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, cell_ref, n))
+                    }
+                    _ => {}
+                }
+            }
+            sheet.push_str("</row>");
+        }
+        sheet.push_str("</sheetData></worksheet>");
+
+        let workbook = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+            xml_escape(&sheet_title(&self.sheet_name))
+        );
+
+        let parts: [(&str, &str); 5] = [
+            (
+                "[Content_Types].xml",
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#,
+            ),
+            (
+                "_rels/.rels",
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#,
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#,
+            ),
+            ("xl/workbook.xml", &workbook),
+            ("xl/worksheets/sheet1.xml", &sheet),
+        ];
+
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut cursor);
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated);
+            for (name, content) in parts {
+                zip.start_file(name, options).map_err(|e| xlsx_err(&e))?;
+                zip.write_all(content.as_bytes())
+                    .map_err(|e| xlsx_err(&e))?;
+            }
+            zip.finish().map_err(|e| xlsx_err(&e))?;
+        }
+        Ok(cursor.into_inner())
+    }
+}
+
+/// 列序号转 Excel 列名（0 → A, 26 → AA）
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// 工作表名：去掉 Excel 不允许的字符，最长 31 字符
+fn sheet_title(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !matches!(c, '\\' | '/' | '?' | '*' | '[' | ']' | ':'))
+        .take(31)
+        .collect();
+    if cleaned.trim().is_empty() {
+        "Sheet1".to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // XML 1.0 不允许的控制字符直接丢弃
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ExportTable {
+        ExportTable {
+            sheet_name: "变更通知".to_string(),
+            headers: vec!["机组".to_string(), "吨位".to_string()],
+            rows: vec![
+                vec!["H032".into(), 12.5.into()],
+                vec!["A&B, \"C\"".into(), ExportCell::Empty],
+            ],
+        }
+    }
+
+    #[test]
+    fn test_csv_has_bom_and_quotes() {
+        let bytes = sample().to_bytes(ExportFormat::Csv).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.starts_with('\u{feff}'));
+        assert!(text.contains("H032,12.5"));
+        assert!(text.contains("\"A&B, \"\"C\"\"\","));
+    }
+
+    #[test]
+    fn test_xlsx_is_readable_by_calamine() {
+        use calamine::{Reader, Xlsx};
+        let bytes = sample().to_bytes(ExportFormat::Xlsx).unwrap();
+        let mut workbook: Xlsx<_> = Xlsx::new(std::io::Cursor::new(bytes)).unwrap();
+        let range = workbook.worksheet_range("变更通知").unwrap();
+        assert_eq!(range.get_size(), (3, 2));
+        assert_eq!(range.get_value((1, 1)).unwrap().to_string(), "12.5");
+        assert_eq!(range.get_value((2, 0)).unwrap().to_string(), "A&B, \"C\"");
+        assert_eq!(column_name(27), "AB");
+    }
+}
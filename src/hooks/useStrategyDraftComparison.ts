@@ -3,15 +3,20 @@
  * 集中管理所有状态和业务逻辑
  */
 
-import { useCallback, useEffect, useMemo, useState } from 'react';
+import { useCallback, useEffect, useMemo, useRef, useState } from 'react';
 import { Modal, message } from 'antd';
 import type { Dayjs } from 'dayjs';
 import dayjs from 'dayjs';
 import { useNavigate, useSearchParams } from 'react-router-dom';
 import { configApi, dashboardApi, materialApi, planApi } from '../api/tauri';
+import { useEvent } from '../api/eventBus';
+import { createRunId } from '../utils/runId';
 import { useActiveVersionId, useCurrentUser, useGlobalActions } from '../stores/use-global-store';
 import {
   FALLBACK_STRATEGIES,
+  MAX_DAYS,
+  STRATEGY_DRAFT_MAX_DAYS_CONFIG_KEY,
+  STRATEGY_DRAFT_PROGRESS_EVENT,
   type ActionLogRow,
   type ApplyStrategyDraftResponse,
   type CustomStrategyProfile,
@@ -21,6 +26,7 @@ import {
   type MaterialDetailPayload,
   type SqueezedHintCache,
   type StrategyDraftDiffItem,
+  type StrategyDraftProgress,
   type StrategyDraftSummary,
   type StrategyKey,
   type StrategyPreset,
@@ -45,6 +51,11 @@ export interface UseStrategyDraftComparisonReturn {
   // 草案数据
   draftsByStrategy: Partial<Record<StrategyKey, StrategyDraftSummary>>;
   isGenerating: boolean;
+  /** 本次生成各策略进度（strategy_draft_progress 事件） */
+  progressByStrategy: Partial<Record<StrategyKey, StrategyDraftProgress>>;
+  isCancelling: boolean;
+  /** 草案时间跨度上限（全局配置 strategy_draft_max_days） */
+  maxDays: number;
   publishingDraftId: string | null;
   hasAnyDraft: boolean;
 
@@ -89,6 +100,7 @@ export interface UseStrategyDraftComparisonReturn {
 
   // 操作方法
   handleGenerate: () => Promise<void>;
+  handleCancelGenerate: () => Promise<void>;
   handleApply: (draft: StrategyDraftSummary) => void;
   openDetail: (draft: StrategyDraftSummary) => Promise<void>;
   closeDetail: () => void;
@@ -124,6 +136,12 @@ export function useStrategyDraftComparison(): UseStrategyDraftComparisonReturn {
   // ========== 草案状态 ==========
   const [draftsByStrategy, setDraftsByStrategy] = useState<Partial<Record<StrategyKey, StrategyDraftSummary>>>({});
   const [isGenerating, setIsGenerating] = useState(false);
+  const [progressByStrategy, setProgressByStrategy] = useState<
+    Partial<Record<StrategyKey, StrategyDraftProgress>>
+  >({});
+  const [isCancelling, setIsCancelling] = useState(false);
+  const [maxDays, setMaxDays] = useState<number>(MAX_DAYS);
+  const generationIdRef = useRef<string | null>(null);
   const [publishingDraftId, setPublishingDraftId] = useState<string | null>(null);
 
   // ========== 发布后弹窗 ==========
@@ -214,14 +232,43 @@ export function useStrategyDraftComparison(): UseStrategyDraftComparisonReturn {
   }, [detailResp, detailFilter, detailSearch]);
 
   // ========== 设置日期范围（带限制） ==========
-  const setRange = useCallback((newRange: [Dayjs, Dayjs]) => {
-    const clamped = clampRange(newRange);
-    if (clamped[1].diff(clamped[0], 'day') + 1 > 60) {
-      message.warning('时间跨度过大，已限制为60天');
-    }
-    setRangeInternal(clamped);
+  const setRange = useCallback(
+    (newRange: [Dayjs, Dayjs]) => {
+      const clamped = clampRange(newRange, maxDays);
+      if (Math.abs(newRange[1].diff(newRange[0], 'day')) + 1 > maxDays) {
+        message.warning(`时间跨度过大，已限制为${maxDays}天`);
+      }
+      setRangeInternal(clamped);
+    },
+    [maxDays]
+  );
+
+  // ========== 加载时间跨度上限 ==========
+  useEffect(() => {
+    let cancelled = false;
+    (async () => {
+      try {
+        const cfg = await configApi.getConfig('global', STRATEGY_DRAFT_MAX_DAYS_CONFIG_KEY);
+        const parsed = Number(cfg?.value);
+        if (!cancelled && Number.isFinite(parsed) && parsed >= 1) {
+          setMaxDays(Math.min(Math.trunc(parsed), 366));
+        }
+      } catch {
+        // best-effort：使用默认值
+      }
+    })();
+    return () => {
+      cancelled = true;
+    };
   }, []);
 
+  // ========== 生成进度事件 ==========
+  useEvent(STRATEGY_DRAFT_PROGRESS_EVENT, (payload) => {
+    const progress = payload as StrategyDraftProgress | null;
+    if (!progress || progress.generation_id !== generationIdRef.current) return;
+    setProgressByStrategy((prev) => ({ ...prev, [String(progress.strategy)]: progress }));
+  });
+
   // ========== 加载策略预设 ==========
   useEffect(() => {
     let cancelled = false;
@@ -367,6 +414,9 @@ export function useStrategyDraftComparison(): UseStrategyDraftComparisonReturn {
     const plan_date_from = start.format('YYYY-MM-DD');
     const plan_date_to = end.format('YYYY-MM-DD');
 
+    const generationId = createRunId('draft');
+    generationIdRef.current = generationId;
+    setProgressByStrategy({});
     setIsGenerating(true);
     try {
       const operator = currentUser || 'admin';
@@ -376,6 +426,7 @@ export function useStrategyDraftComparison(): UseStrategyDraftComparisonReturn {
         plan_date_to,
         strategies: selectedStrategies,
         operator,
+        generation_id: generationId,
       })) as GenerateStrategyDraftsResponse;
 
       const next: Partial<Record<StrategyKey, StrategyDraftSummary>> = {};
@@ -384,12 +435,29 @@ export function useStrategyDraftComparison(): UseStrategyDraftComparisonReturn {
       });
 
       setDraftsByStrategy(next);
-      message.success(resp?.message || '策略草案生成完成');
+      if (resp?.cancelled) {
+        message.info(resp?.message || '策略草案生成已取消');
+      } else {
+        message.success(resp?.message || '策略草案生成完成');
+      }
     } finally {
+      generationIdRef.current = null;
       setIsGenerating(false);
+      setIsCancelling(false);
     }
   }, [activeVersionId, currentUser, range, selectedStrategies]);
 
+  const handleCancelGenerate = useCallback(async () => {
+    const generationId = generationIdRef.current;
+    if (!generationId) return;
+    setIsCancelling(true);
+    try {
+      await planApi.cancelStrategyDraftGeneration(generationId);
+    } catch {
+      setIsCancelling(false);
+    }
+  }, []);
+
   const handleApply = useCallback(
     (draft: StrategyDraftSummary) => {
       if (!draft?.draft_id) return;
@@ -572,6 +640,9 @@ export function useStrategyDraftComparison(): UseStrategyDraftComparisonReturn {
     setSelectedStrategies,
     draftsByStrategy,
     isGenerating,
+    progressByStrategy,
+    isCancelling,
+    maxDays,
     publishingDraftId,
     hasAnyDraft,
     postPublishOpen,
@@ -604,6 +675,7 @@ export function useStrategyDraftComparison(): UseStrategyDraftComparisonReturn {
     recommendation,
     canGenerate,
     handleGenerate,
+    handleCancelGenerate,
     handleApply,
     openDetail,
     closeDetail,
@@ -0,0 +1,439 @@
+// ==========================================
+// 热轧精整排产系统 - 合同/订单导入器
+// ==========================================
+// 依据: Field_Mapping_Spec_v0.3_Integrated.md - 合同影子字段
+// 红线: 合同字段作为 material_master 影子列，不独立建表
+// ==========================================
+// 职责: 合同文件（ERP 合同报表）→ 按 contract_no 更新关联材料影子字段
+//       → 重算关联材料的 rush_level / urgent_level
+// 流程: 文件解析 → 字段映射 → 清洗 → DQ 校验 → 关联材料 → 等级重算 → 事务落库 → 批次记录
+// ==========================================
+
+use crate::config::ImportConfigReader;
+use crate::domain::material::{
+    ContractImportResult, DqLevel, DqSummary, DqViolation, ImportBatch, RawContractRecord,
+};
+use crate::engine::UrgencyEngine;
+use crate::importer::data_cleaner::DataCleaner;
+use crate::importer::field_mapper::FieldMapper;
+use crate::importer::file_parser::{HeaderProfile, ParseOptions, UniversalFileParser};
+use crate::importer::material_importer_trait::FileParser;
+use crate::repository::material_import_repo::MaterialImportRepository;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// 合同导入批次的导入类型标识（写入 dq_report_json.import_type）
+pub const CONTRACT_IMPORT_TYPE: &str = "CONTRACT";
+
+/// 合同文件主键源列名（表头识别的必要条件）
+pub const CONTRACT_PRIMARY_KEY_HEADER: &str = "合同号";
+
+/// 合同映射配置识别的全部源列名（含别名）
+pub const KNOWN_CONTRACT_HEADERS: &[&str] = &[
+    "合同号",
+    "合同性质代码",
+    "合同性质",
+    "按周交货标志",
+    "周交期标记",
+    "出口标记",
+    "合同交货期",
+    "交货期",
+];
+
+/// 合同文件表头识别配置
+pub const CONTRACT_HEADER_PROFILE: HeaderProfile = HeaderProfile {
+    primary_key: CONTRACT_PRIMARY_KEY_HEADER,
+    known_headers: KNOWN_CONTRACT_HEADERS,
+};
+
+/// 出口标记可识别取值（其余取值按 '0' 处理并给出警告）
+const RECOGNIZED_EXPORT_FLAGS: &[&str] = &["1", "Y", "是", "TRUE", "0", "N", "否", "FALSE"];
+
+// ==========================================
+// ContractFieldMapper - 合同字段映射
+// ==========================================
+/// 合同源字段 → RawContractRecord（列名别名与材料映射一致）
+pub struct ContractFieldMapper {
+    inner: FieldMapper,
+}
+
+impl Default for ContractFieldMapper {
+    fn default() -> Self {
+        Self { inner: FieldMapper }
+    }
+}
+
+impl ContractFieldMapper {
+    pub fn map_to_raw_contract(
+        &self,
+        row: &HashMap<String, String>,
+        row_number: usize,
+    ) -> Result<RawContractRecord, Box<dyn Error>> {
+        Ok(RawContractRecord {
+            contract_no: self.inner.get_string(row, "合同号"),
+            contract_nature: self.inner.get_string(row, "合同性质代码"),
+            weekly_delivery_flag: self.inner.get_string(row, "按周交货标志"),
+            export_flag: self.inner.get_string(row, "出口标记"),
+            due_date: self.inner.parse_date(row, "合同交货期", row_number)?,
+            row_number,
+        })
+    }
+}
+
+// ==========================================
+// ContractImporter - 合同导入器
+// ==========================================
+pub struct ContractImporter<R, C>
+where
+    R: MaterialImportRepository,
+    C: ImportConfigReader,
+{
+    import_repo: R,
+    config: C,
+    field_mapper: ContractFieldMapper,
+    data_cleaner: DataCleaner,
+    urgency_engine: UrgencyEngine,
+}
+
+impl<R, C> ContractImporter<R, C>
+where
+    R: MaterialImportRepository,
+    C: ImportConfigReader,
+{
+    /// 创建合同导入器
+    ///
+    /// # 参数
+    /// - import_repo: 导入仓储（查询关联材料 + 事务更新）
+    /// - config: 配置读取器（N1/N2 阈值）
+    pub fn new(import_repo: R, config: C) -> Self {
+        Self {
+            import_repo,
+            config,
+            field_mapper: ContractFieldMapper::default(),
+            data_cleaner: DataCleaner,
+            urgency_engine: UrgencyEngine::new(),
+        }
+    }
+
+    /// 从合同文件导入并更新关联材料
+    ///
+    /// # DQ 规则
+    /// - 合同号缺失 / 交货期格式错误 → ERROR（阻断该行）
+    /// - 文件内合同号重复 → CONFLICT（保留首行，后续行跳过）
+    /// - 无任何可更新属性 / 未关联任何材料 / 出口标记无法识别 → WARNING
+    ///
+    /// # 说明
+    /// - 文件中为空的属性保留材料原值
+    /// - 重算仅涉及 rush_level/urgent_level，锁定/冻结/人工红线等状态保持不变
+    pub async fn import_from_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        options: ParseOptions,
+        imported_by: &str,
+    ) -> Result<ContractImportResult, Box<dyn Error>> {
+        let start_time = std::time::Instant::now();
+        let batch_id = Uuid::new_v4().to_string();
+        let file_path_str = file_path.as_ref().to_str().unwrap_or("unknown");
+        info!(batch_id = %batch_id, file_path = %file_path_str, "开始导入合同数据");
+
+        // === 步骤 1: 解析文件 ===
+        let parser =
+            UniversalFileParser::with_options(options).with_header_profile(CONTRACT_HEADER_PROFILE);
+        let (rows, source_info) = parser
+            .parse_with_source_info(file_path.as_ref())
+            .map_err(|e| format!("文件解析失败: {}", e))?;
+        let total_rows = rows.len();
+
+        // === 步骤 2: 字段映射 + 清洗 + 行级 DQ ===
+        let mut violations = Vec::new();
+        let mut blocked = 0usize;
+        let mut conflict = 0usize;
+        let mut records: Vec<RawContractRecord> = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (idx, row) in rows.iter().enumerate() {
+            let row_number = idx + 1;
+            let raw = match self.field_mapper.map_to_raw_contract(row, row_number) {
+                Ok(raw) => raw,
+                Err(e) => {
+                    violations.push(Self::violation(
+                        row_number,
+                        DqLevel::Error,
+                        "due_date",
+                        format!("字段映射失败: {}", e),
+                    ));
+                    blocked += 1;
+                    continue;
+                }
+            };
+
+            let Some(contract_no) = raw.contract_no.clone() else {
+                violations.push(Self::violation(
+                    row_number,
+                    DqLevel::Error,
+                    "contract_no",
+                    "合同号缺失".to_string(),
+                ));
+                blocked += 1;
+                continue;
+            };
+
+            if !seen.insert(contract_no.clone()) {
+                violations.push(Self::violation(
+                    row_number,
+                    DqLevel::Conflict,
+                    "contract_no",
+                    format!("合同号在文件内重复，已保留首行: {}", contract_no),
+                ));
+                conflict += 1;
+                continue;
+            }
+
+            if let Some(flag) = &raw.export_flag {
+                if !RECOGNIZED_EXPORT_FLAGS.contains(&flag.to_uppercase().as_str()) {
+                    violations.push(Self::violation(
+                        row_number,
+                        DqLevel::Warning,
+                        "export_flag",
+                        format!("出口标记无法识别，按 '0' 处理: {} ({})", flag, contract_no),
+                    ));
+                }
+            }
+
+            let record = RawContractRecord {
+                contract_no: Some(contract_no.clone()),
+                contract_nature: self.data_cleaner.clean_contract_nature(raw.contract_nature),
+                weekly_delivery_flag: self
+                    .data_cleaner
+                    .clean_weekly_delivery_flag(raw.weekly_delivery_flag),
+                export_flag: self.data_cleaner.clean_export_flag(raw.export_flag),
+                due_date: raw.due_date,
+                row_number,
+            };
+
+            if !record.has_attributes() {
+                violations.push(Self::violation(
+                    row_number,
+                    DqLevel::Warning,
+                    "contract_no",
+                    format!("合同无任何可更新属性: {}", contract_no),
+                ));
+                continue;
+            }
+
+            records.push(record);
+        }
+
+        // === 步骤 3: 查询关联材料 ===
+        let contract_nos: Vec<String> = records
+            .iter()
+            .filter_map(|r| r.contract_no.clone())
+            .collect();
+        let linked = self
+            .import_repo
+            .find_materials_by_contract_nos(contract_nos)
+            .await?;
+
+        let records_by_contract: HashMap<&str, &RawContractRecord> = records
+            .iter()
+            .filter_map(|r| r.contract_no.as_deref().map(|no| (no, r)))
+            .collect();
+        let matched: HashSet<String> = linked
+            .iter()
+            .filter_map(|(m, _)| m.contract_no.clone())
+            .collect();
+
+        let mut unmatched_contracts = 0usize;
+        for record in &records {
+            let contract_no = record.contract_no.as_deref().unwrap_or_default();
+            if !matched.contains(contract_no) {
+                violations.push(Self::violation(
+                    record.row_number,
+                    DqLevel::Warning,
+                    "contract_no",
+                    format!("合同未关联任何材料: {}", contract_no),
+                ));
+                unmatched_contracts += 1;
+            }
+        }
+
+        // === 步骤 4: 写入影子字段 + 重算催料/紧急等级 ===
+        let today = chrono::Local::now().date_naive();
+        let n1_days = self.config.get_n1_threshold_days().await?;
+        let n2_days = self.config.get_n2_threshold_days().await?;
+        let now = Utc::now();
+
+        let updated: Vec<_> = linked
+            .into_iter()
+            .filter_map(|(mut master, state)| {
+                let record = records_by_contract.get(master.contract_no.as_deref()?)?;
+                record.apply_to(&mut master);
+                master.updated_at = now;
+                Some((master, state))
+            })
+            .collect();
+        let materials: Vec<_> = updated.iter().map(|(m, _)| m.clone()).collect();
+        let states: Vec<_> = self
+            .urgency_engine
+            .evaluate_batch(updated, today, n1_days, n2_days)
+            .into_iter()
+            .map(|mut state| {
+                state.updated_at = now;
+                state.updated_by = Some(imported_by.to_string());
+                state
+            })
+            .collect();
+
+        // === 步骤 5: 事务落库 ===
+        let updated_materials = self
+            .import_repo
+            .batch_update_contract_fields(materials, states)
+            .await?;
+
+        // === 步骤 6: 记录批次信息 ===
+        let elapsed_time = start_time.elapsed();
+        let warning = violations
+            .iter()
+            .filter(|v| v.level == DqLevel::Warning)
+            .count();
+        let summary = DqSummary {
+            total_rows,
+            success: matched.len(),
+            blocked,
+            warning,
+            conflict,
+        };
+        let dq_report = serde_json::json!({
+            "import_type": CONTRACT_IMPORT_TYPE,
+            "summary": summary,
+            "violations": violations,
+            "source_info": source_info,
+            "matched_contracts": matched.len(),
+            "unmatched_contracts": unmatched_contracts,
+            "updated_materials": updated_materials,
+        });
+
+        let batch = ImportBatch {
+            batch_id: batch_id.clone(),
+            file_name: Some(
+                Path::new(file_path_str)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+            ),
+            file_path: Some(file_path_str.to_string()),
+            total_rows: total_rows as i32,
+            success_rows: summary.success as i32,
+            blocked_rows: blocked as i32,
+            warning_rows: warning as i32,
+            conflict_rows: conflict as i32,
+            imported_at: Some(now),
+            imported_by: Some(imported_by.to_string()),
+            elapsed_ms: Some(elapsed_time.as_millis() as i32),
+            dq_report_json: Some(serde_json::to_string(&dq_report)?),
+        };
+        self.import_repo.insert_batch(batch.clone()).await?;
+
+        if unmatched_contracts > 0 {
+            warn!(batch_id = %batch_id, unmatched_contracts, "部分合同未关联任何材料");
+        }
+        info!(
+            batch_id = %batch_id,
+            total = total_rows,
+            matched = matched.len(),
+            updated_materials,
+            elapsed_ms = elapsed_time.as_millis(),
+            "合同数据导入完成"
+        );
+
+        Ok(ContractImportResult {
+            batch,
+            summary,
+            violations,
+            matched_contracts: matched.len(),
+            unmatched_contracts,
+            updated_materials,
+            elapsed_time,
+            source_info,
+        })
+    }
+
+    fn violation(row_number: usize, level: DqLevel, field: &str, message: String) -> DqViolation {
+        DqViolation {
+            row_number,
+            material_id: None,
+            level,
+            field: field.to_string(),
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::file_parser::detect_header_row_with;
+
+    fn row(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_map_contract_row_with_aliases() {
+        let mapper = ContractFieldMapper::default();
+        let record = mapper
+            .map_to_raw_contract(
+                &row(&[
+                    ("合同号", " C001 "),
+                    ("合同性质", "a1"),
+                    ("周交期标记", "D"),
+                    ("出口标记", "Y"),
+                    ("交货期", "20260301"),
+                ]),
+                1,
+            )
+            .unwrap();
+
+        assert_eq!(record.contract_no.as_deref(), Some("C001"));
+        assert_eq!(record.contract_nature.as_deref(), Some("a1"));
+        assert_eq!(record.weekly_delivery_flag.as_deref(), Some("D"));
+        assert_eq!(record.due_date, chrono::NaiveDate::from_ymd_opt(2026, 3, 1));
+        assert!(record.has_attributes());
+    }
+
+    #[test]
+    fn test_map_contract_row_rejects_bad_date() {
+        let mapper = ContractFieldMapper::default();
+        let result =
+            mapper.map_to_raw_contract(&row(&[("合同号", "C001"), ("合同交货期", "03/01")]), 4);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_contract_header_profile_detects_header_row() {
+        let rows: Vec<Vec<String>> = vec![
+            vec!["合同明细报表".to_string()],
+            vec![],
+            vec!["合同号", "合同性质代码", "按周交货标志", "出口标记"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        ];
+        assert_eq!(
+            detect_header_row_with(&rows, &CONTRACT_HEADER_PROFILE),
+            Some(2)
+        );
+        assert_eq!(
+            detect_header_row_with(&rows, &HeaderProfile::MATERIAL),
+            None
+        );
+    }
+}
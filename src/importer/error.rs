@@ -26,6 +26,9 @@ pub enum ImportError {
     #[error("CSV 解析失败: {0}")]
     CsvParseError(String),
 
+    #[error("不支持的文件编码: {0}")]
+    UnsupportedEncoding(String),
+
     // ===== 数据映射错误 =====
     #[error("字段映射失败 (行 {row}): {message}")]
     FieldMappingError { row: usize, message: String },
@@ -11,6 +11,79 @@ use crate::importer::material_importer_trait::FieldMapper as FieldMapperTrait;
 use chrono::{DateTime, NaiveDate, Utc};
 use std::collections::HashMap;
 
+/// 主键源列名（表头识别的必要条件）
+pub const PRIMARY_KEY_HEADER: &str = "材料号";
+
+/// 源列名别名表：(标准列名, 别名)
+///
+/// 字段映射（get_string 按顺序尝试标准列名及别名）与表头识别（KNOWN_SOURCE_HEADERS）共用此表。
+pub const SOURCE_HEADER_ALIASES: &[(&str, &[&str])] = &[
+    ("材料号", &[]),
+    ("制造命令号", &[]),
+    ("材料状态码", &[]),
+    ("出钢记号", &[]),
+    ("板坯号", &[]),
+    ("下道机组代码", &[]),
+    ("精整返修机组", &[]),
+    ("材料实际宽度", &[]),
+    ("材料实际厚度", &[]),
+    ("材料实际长度", &[]),
+    ("材料实际重量", &[]),
+    ("可利用宽度", &["材料可用宽度"]),
+    ("合同交货期", &["交货期"]),
+    ("状态时间(天)", &["库存天数"]),
+    ("产出时间(天)", &["出钢天数"]),
+    ("物料状态修改时间", &["状态更新时间"]),
+    ("合同号", &[]),
+    ("合同性质代码", &["合同性质"]),
+    ("按周交货标志", &["周交期标记"]),
+    ("出口标记", &[]),
+    ("实际机组", &["机组代码", "机组"]),
+    ("完工时间", &["实际完工时间", "完成时间"]),
+    ("实际重量", &["实绩重量", "重量"]),
+];
+
+/// 别名表展开后的列名总数
+const fn count_source_headers(table: &[(&str, &[&str])]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < table.len() {
+        count += 1 + table[i].1.len();
+        i += 1;
+    }
+    count
+}
+
+/// 将别名表展开为列名数组（标准列名在前，随后为其别名）
+const fn flatten_source_headers<const N: usize>(
+    table: &[(&'static str, &'static [&'static str])],
+) -> [&'static str; N] {
+    let mut headers = [""; N];
+    let mut n = 0;
+    let mut i = 0;
+    while i < table.len() {
+        headers[n] = table[i].0;
+        n += 1;
+        let mut j = 0;
+        while j < table[i].1.len() {
+            headers[n] = table[i].1[j];
+            n += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    headers
+}
+
+const KNOWN_SOURCE_HEADER_COUNT: usize = count_source_headers(SOURCE_HEADER_ALIASES);
+const KNOWN_SOURCE_HEADER_ARRAY: [&str; KNOWN_SOURCE_HEADER_COUNT] =
+    flatten_source_headers(SOURCE_HEADER_ALIASES);
+
+/// 映射配置识别的全部源列名（含别名，由 SOURCE_HEADER_ALIASES 展开）
+///
+/// 用于 Excel 表头行自动识别：首个包含主键列且命中足够多已知列名的行即为表头。
+pub const KNOWN_SOURCE_HEADERS: &[&str] = &KNOWN_SOURCE_HEADER_ARRAY;
+
 pub struct FieldMapper;
 
 impl FieldMapperTrait for FieldMapper {
@@ -60,21 +133,15 @@ impl FieldMapperTrait for FieldMapper {
 
 impl FieldMapper {
     /// 提取字符串字段（返回 Option），支持多个可能的列名（别名）
-    fn get_string(&self, row: &HashMap<String, String>, key: &str) -> Option<String> {
-        // 定义列名别名映射
-        let aliases: Vec<&str> = match key {
-            "可利用宽度" => vec!["可利用宽度", "材料可用宽度"],
-            "合同交货期" => vec!["合同交货期", "交货期"],
-            "状态时间(天)" => vec!["状态时间(天)", "库存天数"],
-            "产出时间(天)" => vec!["产出时间(天)", "出钢天数"],
-            "物料状态修改时间" => vec!["物料状态修改时间", "状态更新时间"],
-            "合同性质代码" => vec!["合同性质代码", "合同性质"],
-            "按周交货标志" => vec!["按周交货标志", "周交期标记"],
-            _ => vec![key],
-        };
+    pub(crate) fn get_string(&self, row: &HashMap<String, String>, key: &str) -> Option<String> {
+        // 列名别名（未登记的列名按原名读取）
+        let aliases: &[&str] = SOURCE_HEADER_ALIASES
+            .iter()
+            .find(|(canonical, _)| *canonical == key)
+            .map_or(&[], |(_, aliases)| *aliases);
 
         // 尝试所有可能的列名
-        for alias in aliases {
+        for alias in std::iter::once(key).chain(aliases.iter().copied()) {
             if let Some(v) = row.get(alias) {
                 let trimmed = v.trim();
                 if !trimmed.is_empty() {
@@ -130,7 +197,7 @@ impl FieldMapper {
     }
 
     /// 解析日期（YYYYMMDD → NaiveDate）
-    fn parse_date(
+    pub(crate) fn parse_date(
         &self,
         row: &HashMap<String, String>,
         key: &str,
@@ -254,4 +321,28 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_known_headers_cover_alias_table() {
+        for (canonical, aliases) in SOURCE_HEADER_ALIASES {
+            assert!(KNOWN_SOURCE_HEADERS.contains(canonical));
+            for alias in *aliases {
+                assert!(KNOWN_SOURCE_HEADERS.contains(alias), "{}", alias);
+
+                let mut row = HashMap::new();
+                row.insert(alias.to_string(), "V".to_string());
+                assert_eq!(
+                    FieldMapper.get_string(&row, canonical),
+                    Some("V".to_string())
+                );
+            }
+        }
+        assert_eq!(
+            KNOWN_SOURCE_HEADERS.len(),
+            SOURCE_HEADER_ALIASES
+                .iter()
+                .map(|(_, aliases)| 1 + aliases.len())
+                .sum::<usize>()
+        );
+    }
 }
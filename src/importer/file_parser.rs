@@ -4,15 +4,100 @@
 // 依据: 设计冻结文档 - 阶段 0: 文件读取与解析
 // 支持: Excel (.xlsx/.xls) / CSV (.csv)
 // ==========================================
+// CSV: 自动探测编码（UTF-8 / GBK / GB18030），边读边转码为 UTF-8（流式）
+// Excel: 工作表选择 + 表头行自动识别（跳过标题横幅）
+// ==========================================
 
+use crate::domain::material::ImportSourceInfo;
 use crate::importer::error::ImportError;
-use crate::importer::material_importer_trait::FileParser;
-use calamine::{open_workbook, Reader, Xlsx};
+use crate::importer::field_mapper::{KNOWN_SOURCE_HEADERS, PRIMARY_KEY_HEADER};
+use crate::importer::material_importer_trait::{FileParser, ParsedRecords, RowStreamWithInfo};
+use calamine::{open_workbook_auto, Reader};
 use csv::ReaderBuilder;
+use encoding_rs::{DecoderResult, Encoding, GB18030, UTF_8};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 
+/// 表头自动识别时扫描的最大行数
+pub const HEADER_SCAN_ROWS: usize = 20;
+
+/// 表头行至少命中的已知列名数量（含主键列）
+pub const MIN_HEADER_MATCHES: usize = 3;
+
+/// CSV 编码探测读取的字节数
+pub const ENCODING_SNIFF_BYTES: usize = 64 * 1024;
+
+// ==========================================
+// ParseOptions - 解析选项
+// ==========================================
+/// 文件解析选项（均为可选，缺省时自动探测）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParseOptions {
+    /// CSV 编码（如 "UTF-8"/"GBK"/"GB18030"），None 表示自动探测
+    pub encoding: Option<String>,
+    /// Excel 工作表名称，None 表示取首个能识别出表头的工作表
+    pub sheet_name: Option<String>,
+}
+
+// ==========================================
+// HeaderProfile - 表头识别配置
+// ==========================================
+/// 表头识别所依据的列名配置（主键列 + 已知列名）
+///
+/// 材料文件与合同文件的表头不同，各导入类型提供自己的配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderProfile {
+    /// 主键列名（表头行必须包含）
+    pub primary_key: &'static str,
+    /// 映射配置中的已知列名
+    pub known_headers: &'static [&'static str],
+}
+
+impl HeaderProfile {
+    /// 材料主数据文件（材料号）
+    pub const MATERIAL: HeaderProfile = HeaderProfile {
+        primary_key: PRIMARY_KEY_HEADER,
+        known_headers: KNOWN_SOURCE_HEADERS,
+    };
+}
+
+impl Default for HeaderProfile {
+    fn default() -> Self {
+        Self::MATERIAL
+    }
+}
+
+/// 将行迭代器收集为完整记录列表
+fn collect_rows((stream, source_info): RowStreamWithInfo) -> Result<ParsedRecords, ImportError> {
+    let records = stream.collect::<Result<Vec<_>, _>>()?;
+    Ok((records, source_info))
+}
+
+/// 按表头将一行单元格转为 HashMap（跳过空表头；整行为空时返回 None）
+fn row_to_map<'a>(
+    headers: &[String],
+    cells: impl Iterator<Item = &'a str>,
+) -> Option<HashMap<String, String>> {
+    let mut row_map = HashMap::new();
+    for (col_idx, value) in cells.enumerate() {
+        if let Some(header) = headers.get(col_idx) {
+            if header.is_empty() {
+                continue;
+            }
+            row_map.insert(header.clone(), value.trim().to_string());
+        }
+    }
+
+    // 跳过完全空白的行
+    if row_map.values().all(|v| v.is_empty()) {
+        return None;
+    }
+    Some(row_map)
+}
+
 // ==========================================
 // CSV Parser 实现
 // ==========================================
@@ -23,165 +108,478 @@ impl FileParser for CsvParser {
         &self,
         file_path: &Path,
     ) -> Result<Vec<HashMap<String, String>>, Box<dyn std::error::Error>> {
-        let path = file_path;
+        let (records, _) = self.parse_with_source_info(file_path)?;
+        Ok(records)
+    }
 
-        // 检查文件存在
-        if !path.exists() {
-            return Err(Box::new(ImportError::FileNotFound(
-                path.display().to_string(),
-            )));
-        }
+    fn parse_with_source_info(
+        &self,
+        file_path: &Path,
+    ) -> Result<ParsedRecords, Box<dyn std::error::Error>> {
+        Ok(collect_rows(open_csv_stream(file_path, None)?)?)
+    }
 
-        // 检查扩展名
-        if let Some(ext) = path.extension() {
-            if ext != "csv" {
-                return Err(Box::new(ImportError::UnsupportedFormat(
-                    ext.to_string_lossy().to_string(),
-                )));
-            }
+    fn open_row_stream(
+        &self,
+        file_path: &Path,
+    ) -> Result<RowStreamWithInfo, Box<dyn std::error::Error>> {
+        Ok(open_csv_stream(file_path, None)?)
+    }
+}
+
+/// 打开 CSV 行迭代器（编码探测 → 流式转码 → 按首行表头逐行读取）
+fn open_csv_stream(
+    file_path: &Path,
+    encoding: Option<&str>,
+) -> Result<RowStreamWithInfo, ImportError> {
+    let path = file_path;
+
+    // 检查文件存在
+    if !path.exists() {
+        return Err(ImportError::FileNotFound(path.display().to_string()));
+    }
+
+    // 检查扩展名
+    if let Some(ext) = path.extension() {
+        if !ext.eq_ignore_ascii_case("csv") {
+            return Err(ImportError::UnsupportedFormat(
+                ext.to_string_lossy().to_string(),
+            ));
         }
+    }
 
-        // 打开 CSV 文件
-        let file = File::open(path)?;
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .flexible(true) // 允许行长度不一致
-            .from_reader(file);
+    // 读取文件头部用于编码探测，之后回到正文起点
+    let mut file = File::open(path)?;
+    let mut sniff = Vec::with_capacity(ENCODING_SNIFF_BYTES);
+    (&mut file)
+        .take(ENCODING_SNIFF_BYTES as u64)
+        .read_to_end(&mut sniff)?;
+    let is_complete = sniff.len() < ENCODING_SNIFF_BYTES;
+    let (enc, bom_len, encoding_detected) = detect_csv_encoding(&sniff, encoding, is_complete)?;
+    file.seek(SeekFrom::Start(bom_len as u64))?;
+
+    let source: Box<dyn Read + Send> = if enc == UTF_8 {
+        Box::new(BufReader::new(file))
+    } else {
+        Box::new(TranscodingReader::new(BufReader::new(file), enc))
+    };
+
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true) // 允许行长度不一致
+        .from_reader(source);
+
+    // 读取表头
+    let headers: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+
+    // 逐行读取
+    let stream = reader
+        .into_records()
+        .filter_map(move |result| match result {
+            Ok(record) => row_to_map(&headers, record.iter()).map(Ok),
+            Err(e) => Some(Err(ImportError::from(e))),
+        });
+
+    let source_info = ImportSourceInfo {
+        file_format: "CSV".to_string(),
+        encoding: Some(encoding_display_name(enc)),
+        encoding_detected,
+        header_row: Some(1),
+        ..Default::default()
+    };
+
+    Ok((Box::new(stream), source_info))
+}
 
-        // 读取表头
-        let headers: Vec<String> = reader
-            .headers()?
-            .iter()
-            .map(|h| h.trim().to_string())
-            .collect();
+/// CSV 编码探测
+///
+/// # 规则
+/// 1. 指定编码 → 使用指定编码
+/// 2. 带 BOM → 按 BOM 指示的编码
+/// 3. 探测片段为合法 UTF-8（允许末尾截断的多字节字符）→ UTF-8
+/// 4. 否则按 GB18030（GBK 超集）；正文中仍有非法字节时在读取阶段报错
+///
+/// # 参数
+/// - sniff: 文件头部片段
+/// - encoding: 调用方指定的编码标签
+/// - is_complete: 片段是否已包含整个文件
+///
+/// # 返回
+/// - (编码, BOM 字节数, 是否自动探测)
+pub fn detect_csv_encoding(
+    sniff: &[u8],
+    encoding: Option<&str>,
+    is_complete: bool,
+) -> Result<(&'static Encoding, usize, bool), ImportError> {
+    let bom = Encoding::for_bom(sniff);
+
+    if let Some(label) = encoding.map(str::trim).filter(|l| !l.is_empty()) {
+        let enc = Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| ImportError::UnsupportedEncoding(label.to_string()))?;
+        let bom_len = match bom {
+            Some((bom_enc, len)) if bom_enc == enc => len,
+            _ => 0,
+        };
+        return Ok((enc, bom_len, false));
+    }
 
-        // 读取所有行
-        let mut records = Vec::new();
-        for (_row_idx, result) in reader.records().enumerate() {
-            let record = result?;
-            let mut row_map = HashMap::new();
+    if let Some((enc, bom_len)) = bom {
+        return Ok((enc, bom_len, true));
+    }
 
-            for (col_idx, value) in record.iter().enumerate() {
-                if let Some(header) = headers.get(col_idx) {
-                    row_map.insert(header.clone(), value.trim().to_string());
-                }
+    match std::str::from_utf8(sniff) {
+        Ok(_) => Ok((UTF_8, 0, true)),
+        Err(e) if e.error_len().is_none() && !is_complete => Ok((UTF_8, 0, true)),
+        Err(_) => Ok((GB18030, 0, true)),
+    }
+}
+
+fn encoding_display_name(enc: &'static Encoding) -> String {
+    enc.name().to_uppercase()
+}
+
+// ==========================================
+// TranscodingReader - 流式转码读取器
+// ==========================================
+/// 将源编码字节流边读边转为 UTF-8（非法字节返回 InvalidData 错误）
+struct TranscodingReader<R: Read> {
+    inner: R,
+    decoder: encoding_rs::Decoder,
+    in_buf: Vec<u8>,
+    in_start: usize,
+    in_end: usize,
+    out_buf: Vec<u8>,
+    out_start: usize,
+    out_end: usize,
+    eof: bool,
+    finished: bool,
+}
+
+impl<R: Read> TranscodingReader<R> {
+    const BUF_SIZE: usize = 8 * 1024;
+
+    fn new(inner: R, encoding: &'static Encoding) -> Self {
+        Self {
+            inner,
+            decoder: encoding.new_decoder_without_bom_handling(),
+            in_buf: vec![0; Self::BUF_SIZE],
+            in_start: 0,
+            in_end: 0,
+            // UTF-8 输出最长为输入的 3 倍，预留余量避免 OutputFull 死循环
+            out_buf: vec![0; Self::BUF_SIZE * 4],
+            out_start: 0,
+            out_end: 0,
+            eof: false,
+            finished: false,
+        }
+    }
+}
+
+impl<R: Read> Read for TranscodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.out_start < self.out_end {
+                let n = buf.len().min(self.out_end - self.out_start);
+                buf[..n].copy_from_slice(&self.out_buf[self.out_start..self.out_start + n]);
+                self.out_start += n;
+                return Ok(n);
+            }
+            if self.finished {
+                return Ok(0);
             }
 
-            // 跳过完全空白的行
-            if row_map.values().all(|v| v.is_empty()) {
-                continue;
+            if self.in_start == self.in_end && !self.eof {
+                self.in_start = 0;
+                self.in_end = self.inner.read(&mut self.in_buf)?;
+                self.eof = self.in_end == 0;
             }
 
-            records.push(row_map);
+            let (result, read, written) = self.decoder.decode_to_utf8_without_replacement(
+                &self.in_buf[self.in_start..self.in_end],
+                &mut self.out_buf,
+                self.eof,
+            );
+            self.in_start += read;
+            self.out_start = 0;
+            self.out_end = written;
+
+            match result {
+                DecoderResult::Malformed(_, _) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "文件内容存在无法按源编码解码的字节",
+                    ));
+                }
+                DecoderResult::InputEmpty if self.eof => self.finished = true,
+                DecoderResult::InputEmpty | DecoderResult::OutputFull => {}
+            }
         }
-
-        Ok(records)
     }
 }
 
 // ==========================================
 // Excel Parser 实现
 // ==========================================
-pub struct ExcelParser;
+#[derive(Debug, Clone, Default)]
+pub struct ExcelParser {
+    /// 指定工作表（None 表示自动选择）
+    sheet_name: Option<String>,
+}
+
+impl ExcelParser {
+    /// 创建自动选择工作表的 Excel 解析器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建读取指定工作表的 Excel 解析器
+    pub fn with_sheet(sheet_name: impl Into<String>) -> Self {
+        Self {
+            sheet_name: Some(sheet_name.into()),
+        }
+    }
+}
 
 impl FileParser for ExcelParser {
     fn parse_to_raw_records(
         &self,
         file_path: &Path,
     ) -> Result<Vec<HashMap<String, String>>, Box<dyn std::error::Error>> {
-        let path = file_path;
-
-        // 检查文件存在
-        if !path.exists() {
-            return Err(Box::new(ImportError::FileNotFound(
-                path.display().to_string(),
-            )));
-        }
-
-        // 检查扩展名
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        if ext != "xlsx" && ext != "xls" {
-            return Err(Box::new(ImportError::UnsupportedFormat(ext.to_string())));
-        }
+        let (records, _) = self.parse_with_source_info(file_path)?;
+        Ok(records)
+    }
 
-        // 打开 Excel 文件
-        let mut workbook: Xlsx<_> = open_workbook(path)
-            .map_err(|e: calamine::XlsxError| ImportError::ExcelParseError(e.to_string()))?;
+    fn parse_with_source_info(
+        &self,
+        file_path: &Path,
+    ) -> Result<ParsedRecords, Box<dyn std::error::Error>> {
+        Ok(collect_rows(open_excel_stream(
+            file_path,
+            self.sheet_name.as_deref(),
+            &HeaderProfile::MATERIAL,
+        )?)?)
+    }
 
-        // 读取第一个 sheet
-        let sheet_names = workbook.sheet_names();
-        if sheet_names.is_empty() {
-            return Err(Box::new(ImportError::ExcelParseError(
-                "Excel 文件无工作表".to_string(),
-            )));
-        }
+    fn open_row_stream(
+        &self,
+        file_path: &Path,
+    ) -> Result<RowStreamWithInfo, Box<dyn std::error::Error>> {
+        Ok(open_excel_stream(
+            file_path,
+            self.sheet_name.as_deref(),
+            &HeaderProfile::MATERIAL,
+        )?)
+    }
+}
 
-        let sheet_name = sheet_names[0].clone();
-        let range = workbook
-            .worksheet_range(&sheet_name)
-            .map_err(|e| ImportError::ExcelParseError(e.to_string()))?;
+/// 打开 Excel 行迭代器（工作表选择 → 表头行识别 → 逐行产出）
+///
+/// # 说明
+/// calamine 需整表载入工作表单元格，Excel 的内存占用与工作表大小相关；
+/// 行映射与后续管道仍按行流式处理
+fn open_excel_stream(
+    file_path: &Path,
+    sheet_name: Option<&str>,
+    header_profile: &HeaderProfile,
+) -> Result<RowStreamWithInfo, ImportError> {
+    let path = file_path;
+
+    // 检查文件存在
+    if !path.exists() {
+        return Err(ImportError::FileNotFound(path.display().to_string()));
+    }
 
-        // 提取表头（第一行）
-        let mut rows = range.rows();
-        let header_row = rows
-            .next()
-            .ok_or_else(|| ImportError::ExcelParseError("Excel 文件无数据行".to_string()))?;
+    // 检查扩展名
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if ext != "xlsx" && ext != "xls" {
+        return Err(ImportError::UnsupportedFormat(ext));
+    }
 
-        let headers: Vec<String> = header_row
-            .iter()
-            .map(|cell| cell.to_string().trim().to_string())
-            .collect();
+    // 打开 Excel 文件
+    let mut workbook = open_workbook_auto(path)?;
 
-        // 读取数据行
-        let mut records = Vec::new();
-        for data_row in rows {
-            let mut row_map = HashMap::new();
+    let available_sheets = workbook.sheet_names();
+    if available_sheets.is_empty() {
+        return Err(ImportError::ExcelParseError(
+            "Excel 文件无工作表".to_string(),
+        ));
+    }
 
-            for (col_idx, cell) in data_row.iter().enumerate() {
-                if let Some(header) = headers.get(col_idx) {
-                    let value = cell.to_string().trim().to_string();
-                    row_map.insert(header.clone(), value);
-                }
+    // 读取候选工作表为文本行
+    let candidates: Vec<String> = match sheet_name {
+        Some(name) => {
+            if !available_sheets.iter().any(|s| s == name) {
+                return Err(ImportError::ExcelParseError(format!(
+                    "工作表不存在: {}（可用工作表: {}）",
+                    name,
+                    available_sheets.join(", ")
+                )));
             }
+            vec![name.to_string()]
+        }
+        None => available_sheets.clone(),
+    };
+
+    let mut fallback: Option<(String, Vec<Vec<String>>)> = None;
+    let mut selected: Option<(String, Vec<Vec<String>>, usize)> = None;
+    for name in candidates {
+        let range = workbook.worksheet_range(&name)?;
+        let rows: Vec<Vec<String>> = range
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.to_string().trim().to_string())
+                    .collect()
+            })
+            .collect();
 
-            // 跳过完全空白的行
-            if row_map.values().all(|v| v.is_empty()) {
-                continue;
-            }
+        if let Some(header_idx) = detect_header_row_with(&rows, header_profile) {
+            selected = Some((name, rows, header_idx));
+            break;
+        }
+        if fallback.is_none() {
+            fallback = Some((name, rows));
+        }
+    }
 
-            records.push(row_map);
+    // 未识别出表头时，回退为首个候选工作表的第一行
+    let (sheet, rows, header_idx, header_auto_detected) = match (selected, fallback) {
+        (Some((sheet, rows, idx)), _) => (sheet, rows, idx, true),
+        (None, Some((sheet, rows))) => (sheet, rows, 0, false),
+        (None, None) => {
+            return Err(ImportError::ExcelParseError(
+                "Excel 文件无可读取的工作表".to_string(),
+            ))
         }
+    };
 
-        Ok(records)
+    if rows.get(header_idx).is_none() {
+        return Err(ImportError::ExcelParseError(
+            "Excel 文件无数据行".to_string(),
+        ));
     }
+
+    let source_info = ImportSourceInfo {
+        file_format: ext.to_uppercase(),
+        sheet_name: Some(sheet),
+        available_sheets,
+        header_row: Some(header_idx + 1),
+        header_auto_detected,
+        ..Default::default()
+    };
+
+    // 逐行产出数据行
+    let mut rows = rows.into_iter().skip(header_idx);
+    let headers = rows.next().unwrap_or_default();
+    let stream = rows.filter_map(move |data_row| {
+        row_to_map(&headers, data_row.iter().map(String::as_str)).map(Ok)
+    });
+
+    Ok((Box::new(stream), source_info))
+}
+
+/// 表头行识别
+///
+/// # 规则
+/// 在前 HEADER_SCAN_ROWS 行中，取首个同时满足以下条件的行：
+/// - 包含主键列（材料号）
+/// - 命中映射配置已知列名数 >= MIN_HEADER_MATCHES
+///
+/// # 返回
+/// - Some(行索引, 0-based)
+/// - None: 未识别出表头
+pub fn detect_header_row(rows: &[Vec<String>]) -> Option<usize> {
+    detect_header_row_with(rows, &HeaderProfile::MATERIAL)
+}
+
+/// 按指定表头配置识别表头行（规则同 detect_header_row）
+pub fn detect_header_row_with(rows: &[Vec<String>], profile: &HeaderProfile) -> Option<usize> {
+    rows.iter().take(HEADER_SCAN_ROWS).position(|row| {
+        let has_primary_key = row.iter().any(|cell| cell == profile.primary_key);
+        let matches = row
+            .iter()
+            .filter(|cell| profile.known_headers.contains(&cell.as_str()))
+            .count();
+        has_primary_key && matches >= MIN_HEADER_MATCHES
+    })
 }
 
 // ==========================================
 // 通用文件解析器（根据扩展名自动选择）
 // ==========================================
-pub struct UniversalFileParser;
+#[derive(Debug, Clone, Default)]
+pub struct UniversalFileParser {
+    options: ParseOptions,
+    header_profile: HeaderProfile,
+}
 
 impl UniversalFileParser {
+    /// 使用指定解析选项创建通用解析器
+    pub fn with_options(options: ParseOptions) -> Self {
+        Self {
+            options,
+            header_profile: HeaderProfile::MATERIAL,
+        }
+    }
+
+    /// 指定 Excel 表头识别配置（默认按材料文件识别）
+    pub fn with_header_profile(mut self, header_profile: HeaderProfile) -> Self {
+        self.header_profile = header_profile;
+        self
+    }
+
     pub fn parse<P: AsRef<Path>>(
         &self,
         file_path: P,
     ) -> Result<Vec<HashMap<String, String>>, Box<dyn std::error::Error>> {
-        let path = file_path.as_ref();
-        let ext = path
+        self.parse_to_raw_records(file_path.as_ref())
+    }
+}
+
+impl FileParser for UniversalFileParser {
+    fn parse_to_raw_records(
+        &self,
+        file_path: &Path,
+    ) -> Result<Vec<HashMap<String, String>>, Box<dyn std::error::Error>> {
+        let (records, _) = self.parse_with_source_info(file_path)?;
+        Ok(records)
+    }
+
+    fn parse_with_source_info(
+        &self,
+        file_path: &Path,
+    ) -> Result<ParsedRecords, Box<dyn std::error::Error>> {
+        Ok(collect_rows(self.open_row_stream(file_path)?)?)
+    }
+
+    fn open_row_stream(
+        &self,
+        file_path: &Path,
+    ) -> Result<RowStreamWithInfo, Box<dyn std::error::Error>> {
+        let ext = file_path
             .extension()
             .and_then(|e| e.to_str())
             .unwrap_or("")
             .to_lowercase();
 
         match ext.as_str() {
-            "csv" => {
-                let parser = CsvParser;
-                parser.parse_to_raw_records(path)
-            }
-            "xlsx" | "xls" => {
-                let parser = ExcelParser;
-                parser.parse_to_raw_records(path)
-            }
+            "csv" => Ok(open_csv_stream(
+                file_path,
+                self.options.encoding.as_deref(),
+            )?),
+            "xlsx" | "xls" => Ok(open_excel_stream(
+                file_path,
+                self.options.sheet_name.as_deref(),
+                &self.header_profile,
+            )?),
             _ => Err(Box::new(ImportError::UnsupportedFormat(ext))),
         }
     }
@@ -193,6 +591,16 @@ mod tests {
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    fn csv_temp_file(bytes: &[u8]) -> NamedTempFile {
+        let mut temp_file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        temp_file.write_all(bytes).unwrap();
+        temp_file
+    }
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
     #[test]
     fn test_csv_parser_valid_file() {
         // 创建临时 CSV 文件
@@ -230,4 +638,112 @@ mod tests {
         // 应跳过空行
         assert_eq!(records.len(), 2);
     }
+
+    #[test]
+    fn test_csv_parser_detects_gbk() {
+        let (bytes, _, _) = GB18030.encode("材料号,出钢记号\nMAT001,Q235B\n");
+        let temp_file = csv_temp_file(&bytes);
+
+        let (records, info) = CsvParser.parse_with_source_info(temp_file.path()).unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].get("材料号"), Some(&"MAT001".to_string()));
+        assert_eq!(info.encoding.as_deref(), Some("GB18030"));
+        assert!(info.encoding_detected);
+    }
+
+    #[test]
+    fn test_csv_parser_strips_utf8_bom() {
+        let temp_file = csv_temp_file("\u{feff}材料号,重量\nMAT001,2.5\n".as_bytes());
+
+        let (records, info) = CsvParser.parse_with_source_info(temp_file.path()).unwrap();
+
+        assert_eq!(records[0].get("材料号"), Some(&"MAT001".to_string()));
+        assert_eq!(info.encoding.as_deref(), Some("UTF-8"));
+    }
+
+    #[test]
+    fn test_detect_csv_encoding_explicit_label() {
+        let (bytes, _, _) = GB18030.encode("材料号");
+        let (enc, bom_len, detected) = detect_csv_encoding(&bytes, Some("gbk"), true).unwrap();
+        assert_eq!(encoding_display_name(enc), "GBK");
+        assert_eq!(bom_len, 0);
+        assert!(!detected);
+
+        assert!(matches!(
+            detect_csv_encoding(&bytes, Some("no-such-encoding"), true),
+            Err(ImportError::UnsupportedEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_detect_csv_encoding_tolerates_truncated_utf8_sniff() {
+        let text = "材料号".as_bytes();
+        let truncated = &text[..text.len() - 1];
+        let (enc, _, _) = detect_csv_encoding(truncated, None, false).unwrap();
+        assert_eq!(enc, UTF_8);
+
+        let (enc, _, _) = detect_csv_encoding(truncated, None, true).unwrap();
+        assert_eq!(enc, GB18030);
+    }
+
+    #[test]
+    fn test_csv_stream_transcodes_large_gbk_file() {
+        let mut content = String::from("材料号,出钢记号\n");
+        for i in 0..5000 {
+            content.push_str(&format!("MAT{:05},钢种{}\n", i, i % 7));
+        }
+        let (bytes, _, _) = GB18030.encode(&content);
+        assert!(bytes.len() > ENCODING_SNIFF_BYTES);
+        let temp_file = csv_temp_file(&bytes);
+
+        let (stream, info) = CsvParser.open_row_stream(temp_file.path()).unwrap();
+        let rows: Vec<_> = stream.collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(info.encoding.as_deref(), Some("GB18030"));
+        assert_eq!(rows.len(), 5000);
+        assert_eq!(rows[4999].get("出钢记号"), Some(&"钢种1".to_string()));
+    }
+
+    #[test]
+    fn test_csv_stream_reports_undecodable_bytes() {
+        let mut bytes = "材料号,重量\n".as_bytes().to_vec();
+        bytes.extend_from_slice(&[0x4d, 0x31, b',', 0xff, 0xff, b'\n']);
+        let temp_file = csv_temp_file(&bytes);
+
+        let parser = UniversalFileParser::with_options(ParseOptions {
+            encoding: Some("GB18030".to_string()),
+            sheet_name: None,
+        });
+        let result = parser.parse(temp_file.path());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_detect_header_row_skips_title_banner() {
+        let rows = vec![
+            row(&["热轧精整材料库存快照", "", ""]),
+            row(&["导出时间: 2025-01-20", "", ""]),
+            row(&[]),
+            row(&["材料号", "出钢记号", "材料实际重量"]),
+            row(&["MAT001", "Q235B", "2.5"]),
+        ];
+        assert_eq!(detect_header_row(&rows), Some(3));
+    }
+
+    #[test]
+    fn test_detect_header_row_requires_primary_key() {
+        let rows = vec![
+            row(&["出钢记号", "材料实际重量", "合同号"]),
+            row(&["Q235B", "2.5", "C001"]),
+        ];
+        assert_eq!(detect_header_row(&rows), None);
+    }
+
+    #[test]
+    fn test_universal_parser_rejects_unknown_extension() {
+        let temp_file = tempfile::Builder::new().suffix(".txt").tempfile().unwrap();
+        let parser = UniversalFileParser::default();
+        assert!(parser.parse(temp_file.path()).is_err());
+    }
 }
@@ -9,14 +9,20 @@
 // ==========================================
 
 use crate::config::ImportConfigReader;
-use crate::domain::material::{ImportBatch, ImportConflict, MaterialMaster, RawMaterialRecord};
+use crate::domain::material::{
+    DqLevel, DqViolation, ImportBatch, ImportConflict, MaterialMaster, RawMaterialRecord,
+};
 use crate::engine::material_state_derivation::MaterialStateDerivationService;
 use crate::importer::material_importer_trait::{
     ConflictHandler, DataCleaner, DerivationService, DqValidator, FieldMapper, FileParser,
     MaterialImporter,
 };
+use crate::importer::streaming::{
+    ImportProgress, ImportStage, StreamingImportOptions, MAX_VIOLATION_DETAILS,
+};
 use crate::repository::MaterialImportRepository;
 use chrono::Utc;
+use std::collections::HashSet;
 use std::error::Error;
 use std::path::Path;
 use tracing::{debug, error, info, instrument, warn};
@@ -105,179 +111,153 @@ where
     /// # 返回
     /// - Ok(ImportResult): 导入结果
     /// - Err: 导入错误
-    #[instrument(skip(self, file_path), fields(batch_id))]
+    ///
+    /// # 说明
+    /// 使用默认分块大小的流式管道（无进度回调、不可取消）
     async fn import_from_excel<P: AsRef<Path> + Send>(
         &self,
         file_path: P,
+    ) -> Result<crate::domain::material::ImportResult, Box<dyn Error>> {
+        self.import_streaming(file_path, StreamingImportOptions::default())
+            .await
+    }
+
+    /// 流式导入材料数据
+    ///
+    /// # 参数
+    /// - file_path: 文件路径
+    /// - options: 分块大小 / 进度回调 / 取消令牌
+    ///
+    /// # 返回
+    /// - Ok(ImportResult): 导入结果（取消时 cancelled = true，仅包含已提交分块）
+    /// - Err: 导入错误
+    #[instrument(skip(self, file_path, options), fields(batch_id))]
+    async fn import_streaming<P: AsRef<Path> + Send>(
+        &self,
+        file_path: P,
+        options: StreamingImportOptions,
     ) -> Result<crate::domain::material::ImportResult, Box<dyn Error>> {
         use std::time::Instant;
         let start_time = Instant::now();
-        let _import_started_at = Utc::now();
         let batch_id = Uuid::new_v4().to_string();
+        let chunk_size = options.chunk_size.max(1);
 
         let file_path_str = file_path.as_ref().to_str().unwrap_or("unknown");
-        info!(batch_id = %batch_id, file_path = %file_path_str, "开始导入材料数据");
+        info!(batch_id = %batch_id, file_path = %file_path_str, chunk_size, "开始导入材料数据");
 
-        // === 步骤 1: 解析文件 ===
-        debug!("步骤 1: 解析文件");
-        let raw_rows = self
+        // === 步骤 1: 打开行迭代器 ===
+        debug!("步骤 1: 打开文件");
+        let (mut rows, source_info) = self
             .file_parser
-            .parse_to_raw_records(file_path.as_ref())
+            .open_row_stream(file_path.as_ref())
             .map_err(|e| {
                 error!(error = %e, "文件解析失败");
                 format!("文件解析失败: {}", e)
             })?;
-
-        let total_rows = raw_rows.len();
-        info!(total_rows = total_rows, "文件解析完成");
-
-        // === 步骤 2: 字段映射 ===
-        debug!("步骤 2: 字段映射");
-        let mut records = Vec::new();
-        let mut mapping_errors = Vec::new();
-        for (idx, row) in raw_rows.into_iter().enumerate() {
-            match self.field_mapper.map_to_raw_material(row, idx + 1) {
-                Ok(record) => records.push(record),
-                Err(e) => {
-                    // 映射失败：记录错误信息（转换为字符串以避免 Send 问题）
-                    warn!(row_number = idx + 1, error = %e, "字段映射失败");
-                    mapping_errors.push((idx + 1, format!("字段映射失败: {}", e)));
-                }
-            }
-        }
-        info!(
-            success = records.len(),
-            failed = mapping_errors.len(),
-            "字段映射完成"
-        );
-
-        // 批量写入映射错误到冲突队列
-        for (row_num, error_msg) in mapping_errors {
-            let conflict = ImportConflict {
-                conflict_id: Uuid::new_v4().to_string(),
-                batch_id: batch_id.clone(),
-                row_number: row_num,
-                material_id: None,
-                conflict_type: crate::domain::material::ConflictType::DataTypeError,
-                raw_data: "{}".to_string(),
-                reason: error_msg,
-                resolved: false,
-                created_at: Utc::now(),
-            };
-            self.import_repo.insert_conflict(conflict).await?;
-        }
-
-        // === 步骤 3: 数据清洗 ===
-        debug!("步骤 3: 数据清洗");
-        for record in &mut records {
-            self.clean_record(record);
-        }
-        debug!("数据清洗完成");
-
-        // === 步骤 4: 字段派生 ===
-        debug!("步骤 4: 字段派生");
-        for record in &mut records {
-            self.derive_fields(record).await?;
-        }
-        debug!("字段派生完成");
-
-        // === 步骤 5: DQ 校验 ===
-        debug!("步骤 5: DQ 校验");
-        let dq_report = self.validate_records(&records);
-        info!(
-            violations = dq_report["total_violations"].as_u64().unwrap_or(0),
-            "DQ 校验完成"
-        );
-
-        // 解析 DQ 报告并提取 violations 列表
-        let details = &dq_report["details"];
-        let mut pk_violations: Vec<crate::domain::material::DqViolation> =
-            serde_json::from_value(details["pk_violations"].clone()).unwrap_or_default();
-        let mut required_violations: Vec<crate::domain::material::DqViolation> =
-            serde_json::from_value(details["required_violations"].clone()).unwrap_or_default();
-        let mut range_violations: Vec<crate::domain::material::DqViolation> =
-            serde_json::from_value(details["range_violations"].clone()).unwrap_or_default();
-
-        // 合并所有 violations
-        let mut all_violations = Vec::new();
-        all_violations.append(&mut pk_violations);
-        all_violations.append(&mut required_violations);
-        all_violations.append(&mut range_violations);
-
-        // 统计 blocked 和 warning 数量
-        let blocked_rows = all_violations
-            .iter()
-            .filter(|v| matches!(v.level, crate::domain::material::DqLevel::Error))
-            .count();
-        let warning_rows = all_violations
-            .iter()
-            .filter(|v| matches!(v.level, crate::domain::material::DqLevel::Warning))
-            .count();
-
+        let total_rows_hint = rows.size_hint().1;
         info!(
-            blocked = blocked_rows,
-            warning = warning_rows,
-            "DQ 统计提取完成"
+            encoding = ?source_info.encoding,
+            sheet = ?source_info.sheet_name,
+            header_row = ?source_info.header_row,
+            "文件打开完成"
         );
 
-        // === 步骤 6: 冲突检测 ===
-        debug!("步骤 6: 冲突检测");
-        let (valid_records, conflict_count) =
-            self.detect_and_handle_conflicts(&batch_id, records).await?;
-        info!(
-            valid = valid_records.len(),
-            conflicts = conflict_count,
-            "冲突检测完成"
-        );
-
-        // === 步骤 7: 转换为 MaterialMaster ===
-        debug!("步骤 7: 转换为 MaterialMaster");
-        let materials = self.convert_to_material_master(valid_records);
-        debug!(count = materials.len(), "MaterialMaster 转换完成");
-
-        // === 步骤 8: 派生 MaterialState ===
-        debug!("步骤 8: 派生 MaterialState");
         let today = chrono::Local::now().date_naive();
-        let mut material_states = Vec::new();
-        for material in &materials {
-            match self
-                .state_derivation_service
-                .derive(material, &self.config, today)
-                .await
-            {
-                Ok(state) => material_states.push(state),
-                Err(e) => {
-                    // 派生失败:记录错误但不阻断导入
-                    warn!(material_id = %material.material_id, error = %e, "材料状态派生失败");
+        let mut dq = DqAccumulator::default();
+        let mut seen_ids = HashSet::new();
+        let mut progress = ImportProgress {
+            batch_id: batch_id.clone(),
+            stage: ImportStage::Parsing,
+            processed_rows: 0,
+            total_rows: total_rows_hint,
+            written_rows: 0,
+            conflict_rows: 0,
+            chunks_committed: 0,
+        };
+        options.report(&progress);
+
+        let mut cancelled = false;
+        let mut stream_error: Option<String> = None;
+        loop {
+            // 取消只在分块边界生效，已提交分块保持完整
+            if options.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+
+            // === 步骤 2: 读取一个分块并字段映射 ===
+            let mut records = Vec::with_capacity(chunk_size);
+            let mut mapping_errors = Vec::new();
+            let mut exhausted = false;
+            while records.len() + mapping_errors.len() < chunk_size {
+                match rows.next() {
+                    None => {
+                        exhausted = true;
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        stream_error = Some(e.to_string());
+                        break;
+                    }
+                    Some(Ok(row)) => {
+                        progress.processed_rows += 1;
+                        let row_number = progress.processed_rows;
+                        match self.field_mapper.map_to_raw_material(row, row_number) {
+                            Ok(record) => records.push(record),
+                            Err(e) => {
+                                // 映射失败：记录错误信息（转换为字符串以避免 Send 问题）
+                                warn!(row_number, error = %e, "字段映射失败");
+                                mapping_errors.push((row_number, format!("字段映射失败: {}", e)));
+                            }
+                        }
+                    }
                 }
             }
-        }
-        info!(
-            total = materials.len(),
-            success = material_states.len(),
-            "MaterialState 派生完成"
-        );
 
-        // === 步骤 9: 批量插入 MaterialMaster ===
-        debug!("步骤 9: 批量插入 MaterialMaster");
-        let success_count = self
-            .import_repo
-            .batch_insert_material_master(materials)
-            .await?;
-        info!(count = success_count, "MaterialMaster 插入完成");
+            if records.is_empty() && mapping_errors.is_empty() {
+                break;
+            }
 
-        // === 步骤 10: 批量插入 MaterialState ===
-        debug!("步骤 10: 批量插入 MaterialState");
-        let _state_count = self
-            .import_repo
-            .batch_insert_material_state(material_states)
-            .await?;
-        debug!("MaterialState 插入完成");
+            // === 步骤 3-9: 清洗 → 校验 → 冲突检测 → 派生状态 → 分块落库 ===
+            let (written, conflicts) = self
+                .process_chunk(
+                    &batch_id,
+                    records,
+                    mapping_errors,
+                    &mut seen_ids,
+                    &mut dq,
+                    today,
+                )
+                .await?;
+            progress.stage = ImportStage::Writing;
+            progress.written_rows += written;
+            progress.conflict_rows += conflicts;
+            progress.chunks_committed += 1;
+            debug!(
+                chunk = progress.chunks_committed,
+                processed = progress.processed_rows,
+                written = progress.written_rows,
+                "分块落库完成"
+            );
+            options.report(&progress);
+
+            if exhausted || stream_error.is_some() {
+                break;
+            }
+        }
 
-        let import_completed_at = Utc::now();
         let elapsed_time = start_time.elapsed();
 
-        // === 步骤 9: 记录批次信息 ===
+        // === 步骤 10: 记录批次信息 ===
+        let mut dq_report = dq.to_report();
+        dq_report["source_info"] = serde_json::to_value(&source_info)?;
+        dq_report["streaming"] = serde_json::json!({
+            "chunk_size": chunk_size,
+            "chunks_committed": progress.chunks_committed,
+            "cancelled": cancelled,
+            "aborted_reason": stream_error,
+        });
+
         let batch = ImportBatch {
             batch_id: batch_id.clone(),
             file_name: Some(
@@ -288,33 +268,55 @@ where
                     .to_string(),
             ),
             file_path: Some(file_path_str.to_string()),
-            total_rows: total_rows as i32,
-            success_rows: success_count as i32,
-            blocked_rows: blocked_rows as i32,
-            warning_rows: warning_rows as i32,
-            conflict_rows: conflict_count as i32,
-            imported_at: Some(import_completed_at),
-            imported_by: Some("system".to_string()),
+            total_rows: progress.processed_rows as i32,
+            success_rows: progress.written_rows as i32,
+            blocked_rows: dq.blocked as i32,
+            warning_rows: dq.warning as i32,
+            conflict_rows: progress.conflict_rows as i32,
+            imported_at: Some(Utc::now()),
+            imported_by: Some(
+                options
+                    .imported_by
+                    .clone()
+                    .unwrap_or_else(|| "system".to_string()),
+            ),
             elapsed_ms: Some(elapsed_time.as_millis() as i32),
             dq_report_json: Some(serde_json::to_string(&dq_report)?),
         };
 
         self.import_repo.insert_batch(batch.clone()).await?;
 
-        // === 步骤 10: 构造返回结果 ===
+        if let Some(reason) = stream_error {
+            error!(batch_id = %batch_id, error = %reason, "文件读取中断");
+            return Err(format!(
+                "文件解析失败（批次 {} 已提交 {} 行）: {}",
+                batch_id, progress.written_rows, reason
+            )
+            .into());
+        }
+
+        progress.stage = if cancelled {
+            ImportStage::Cancelled
+        } else {
+            ImportStage::Completed
+        };
+        options.report(&progress);
+
+        // === 步骤 11: 构造返回结果 ===
         let summary = crate::domain::material::DqSummary {
-            total_rows,
-            success: success_count,
-            blocked: blocked_rows,
-            warning: warning_rows,
-            conflict: conflict_count,
+            total_rows: progress.processed_rows,
+            success: progress.written_rows,
+            blocked: dq.blocked,
+            warning: dq.warning,
+            conflict: progress.conflict_rows,
         };
 
         info!(
             batch_id = %batch_id,
-            total = total_rows,
-            success = success_count,
-            conflicts = conflict_count,
+            total = progress.processed_rows,
+            success = progress.written_rows,
+            conflicts = progress.conflict_rows,
+            cancelled,
             elapsed_ms = elapsed_time.as_millis(),
             "材料数据导入完成"
         );
@@ -322,8 +324,10 @@ where
         Ok(crate::domain::material::ImportResult {
             batch,
             summary,
-            violations: all_violations,
+            violations: dq.into_violations(),
             elapsed_time,
+            source_info,
+            cancelled,
         })
     }
 
@@ -448,11 +452,81 @@ where
         Ok(())
     }
 
-    /// DQ 校验
-    fn validate_records(&self, records: &[RawMaterialRecord]) -> serde_json::Value {
-        use serde_json::json;
+    /// 处理单个分块（清洗 → 校验 → 冲突检测 → 派生状态 → 事务落库）
+    ///
+    /// # 返回
+    /// - (落库材料数, 冲突行数)
+    async fn process_chunk(
+        &self,
+        batch_id: &str,
+        mut records: Vec<RawMaterialRecord>,
+        mapping_errors: Vec<(usize, String)>,
+        seen_ids: &mut HashSet<String>,
+        dq: &mut DqAccumulator,
+        today: chrono::NaiveDate,
+    ) -> Result<(usize, usize), Box<dyn Error>> {
+        // 映射错误写入冲突队列
+        if !mapping_errors.is_empty() {
+            let conflicts = mapping_errors
+                .into_iter()
+                .map(|(row_num, error_msg)| ImportConflict {
+                    conflict_id: Uuid::new_v4().to_string(),
+                    batch_id: batch_id.to_string(),
+                    row_number: row_num,
+                    material_id: None,
+                    conflict_type: crate::domain::material::ConflictType::DataTypeError,
+                    raw_data: "{}".to_string(),
+                    reason: error_msg,
+                    resolved: false,
+                    created_at: Utc::now(),
+                })
+                .collect();
+            self.import_repo.batch_insert_conflicts(conflicts).await?;
+        }
+
+        // 数据清洗 + 字段派生
+        for record in &mut records {
+            self.clean_record(record);
+            self.derive_fields(record).await?;
+        }
+
+        // DQ 校验
+        self.validate_records(&records, dq);
+
+        // 冲突检测
+        let (valid_records, conflict_count) = self
+            .detect_and_handle_conflicts(batch_id, records, seen_ids)
+            .await?;
+
+        // 转换为 MaterialMaster + 派生 MaterialState
+        let materials = self.convert_to_material_master(valid_records);
+        let mut material_states = Vec::with_capacity(materials.len());
+        for material in &materials {
+            match self
+                .state_derivation_service
+                .derive(material, &self.config, today)
+                .await
+            {
+                Ok(state) => material_states.push(state),
+                Err(e) => {
+                    // 派生失败:记录错误但不阻断导入
+                    warn!(material_id = %material.material_id, error = %e, "材料状态派生失败");
+                }
+            }
+        }
+
+        // master + state 同事务落库
+        let written = self
+            .import_repo
+            .batch_insert_materials_with_states(materials, material_states)
+            .await?;
+
+        Ok((written, conflict_count))
+    }
 
-        // 主键校验
+    /// DQ 校验（结果累加到 DqAccumulator）
+    fn validate_records(&self, records: &[RawMaterialRecord], dq: &mut DqAccumulator) {
+        // 主键校验（分块内）
         let pk_violations = self.dq_validator.validate_primary_key(records);
 
         // 必填字段校验(逐条记录)
@@ -467,33 +541,38 @@ where
             range_violations.extend(self.dq_validator.validate_ranges(record));
         }
 
-        // 汇总 DQ 报告
-        json!({
-            "primary_key_violations": pk_violations.len(),
-            "required_field_violations": required_violations.len(),
-            "range_violations": range_violations.len(),
-            "total_violations": pk_violations.len() + required_violations.len() + range_violations.len(),
-            "details": {
-                "pk_violations": pk_violations,
-                "required_violations": required_violations,
-                "range_violations": range_violations,
-            }
-        })
+        dq.absorb(pk_violations, required_violations, range_violations);
     }
 
     /// 冲突检测和处理
+    ///
+    /// # 参数
+    /// - seen_ids: 本批次已出现的材料号（跨分块判定同批次重复）
     async fn detect_and_handle_conflicts(
         &self,
         batch_id: &str,
         records: Vec<RawMaterialRecord>,
+        seen_ids: &mut HashSet<String>,
     ) -> Result<(Vec<RawMaterialRecord>, usize), Box<dyn Error>> {
-        // 步骤 1: 检测同批次内重复
-        let intra_batch_duplicates = self.conflict_handler.detect_duplicates(&records);
+        // 步骤 1: 检测同批次内重复（分块内 + 与先前分块）
+        let mut intra_batch_duplicates = self.conflict_handler.detect_duplicates(&records);
+        let chunk_duplicate_rows: HashSet<usize> =
+            intra_batch_duplicates.iter().map(|(row, _)| *row).collect();
+        for record in &records {
+            if let Some(material_id) = &record.material_id {
+                if seen_ids.contains(material_id)
+                    && !chunk_duplicate_rows.contains(&record.row_number)
+                {
+                    intra_batch_duplicates.push((record.row_number, material_id.clone()));
+                }
+            }
+        }
 
-        // 步骤 2: 检测跨批次重复
+        // 步骤 2: 检测跨批次重复（先前分块已落库的材料不算跨批次）
         let material_ids: Vec<String> = records
             .iter()
             .filter_map(|r| r.material_id.clone())
+            .filter(|id| !seen_ids.contains(id))
             .collect();
 
         let existing_ids = self.import_repo.batch_check_exists(material_ids).await?;
@@ -502,8 +581,14 @@ where
             .conflict_handler
             .detect_cross_batch_duplicates(&records, &existing_ids);
 
+        for record in &records {
+            if let Some(material_id) = &record.material_id {
+                seen_ids.insert(material_id.clone());
+            }
+        }
+
         // 步骤 3: 合并冲突列表
-        let mut conflict_rows = std::collections::HashSet::new();
+        let mut conflict_rows = HashSet::new();
         for (row_num, _) in &intra_batch_duplicates {
             conflict_rows.insert(*row_num);
         }
@@ -513,27 +598,16 @@ where
 
         // 步骤 4: 写入冲突记录
         let mut conflicts = Vec::new();
-        for (row_num, material_id) in intra_batch_duplicates {
-            // 查找原始记录并序列化
-            let raw_record = records.iter().find(|r| r.row_number == row_num);
-            let raw_data = raw_record
-                .and_then(|r| serde_json::to_string(r).ok())
-                .unwrap_or_else(|| "{}".to_string());
-
-            conflicts.push(ImportConflict {
-                conflict_id: Uuid::new_v4().to_string(),
-                batch_id: batch_id.to_string(),
-                row_number: row_num,
-                material_id: Some(material_id.clone()),
-                conflict_type: crate::domain::material::ConflictType::PrimaryKeyDuplicate,
-                raw_data,
-                reason: format!("同批次内重复材料号: {}", material_id),
-                resolved: false,
-                created_at: Utc::now(),
-            });
-        }
-
-        for (row_num, material_id) in cross_batch_duplicates {
+        let labelled = intra_batch_duplicates
+            .into_iter()
+            .map(|d| (d, "同批次内重复材料号"))
+            .chain(
+                cross_batch_duplicates
+                    .into_iter()
+                    .filter(|(row, _)| !chunk_duplicate_rows.contains(row))
+                    .map(|d| (d, "跨批次重复材料号")),
+            );
+        for ((row_num, material_id), label) in labelled {
             // 查找原始记录并序列化
             let raw_record = records.iter().find(|r| r.row_number == row_num);
             let raw_data = raw_record
@@ -547,7 +621,7 @@ where
                 material_id: Some(material_id.clone()),
                 conflict_type: crate::domain::material::ConflictType::PrimaryKeyDuplicate,
                 raw_data,
-                reason: format!("跨批次重复材料号: {}", material_id),
+                reason: format!("{}: {}", label, material_id),
                 resolved: false,
                 created_at: Utc::now(),
             });
@@ -617,3 +691,77 @@ where
             .collect()
     }
 }
+
+// ==========================================
+// DqAccumulator - 分块 DQ 结果累加器
+// ==========================================
+// 计数完整保留；明细每类最多保留 MAX_VIOLATION_DETAILS 条，避免大文件内存膨胀
+#[derive(Debug, Default)]
+struct DqAccumulator {
+    pk_violations: Vec<DqViolation>,
+    required_violations: Vec<DqViolation>,
+    range_violations: Vec<DqViolation>,
+    pk_count: usize,
+    required_count: usize,
+    range_count: usize,
+    blocked: usize,
+    warning: usize,
+}
+
+impl DqAccumulator {
+    fn absorb(
+        &mut self,
+        pk: Vec<DqViolation>,
+        required: Vec<DqViolation>,
+        range: Vec<DqViolation>,
+    ) {
+        for v in pk.iter().chain(required.iter()).chain(range.iter()) {
+            match v.level {
+                DqLevel::Error => self.blocked += 1,
+                DqLevel::Warning => self.warning += 1,
+                _ => {}
+            }
+        }
+
+        self.pk_count += pk.len();
+        self.required_count += required.len();
+        self.range_count += range.len();
+        Self::keep(&mut self.pk_violations, pk);
+        Self::keep(&mut self.required_violations, required);
+        Self::keep(&mut self.range_violations, range);
+    }
+
+    fn keep(target: &mut Vec<DqViolation>, incoming: Vec<DqViolation>) {
+        let room = MAX_VIOLATION_DETAILS.saturating_sub(target.len());
+        target.extend(incoming.into_iter().take(room));
+    }
+
+    fn is_truncated(&self) -> bool {
+        self.pk_count > self.pk_violations.len()
+            || self.required_count > self.required_violations.len()
+            || self.range_count > self.range_violations.len()
+    }
+
+    /// 汇总 DQ 报告（结构与历史 dq_report_json 保持一致）
+    fn to_report(&self) -> serde_json::Value {
+        serde_json::json!({
+            "primary_key_violations": self.pk_count,
+            "required_field_violations": self.required_count,
+            "range_violations": self.range_count,
+            "total_violations": self.pk_count + self.required_count + self.range_count,
+            "details_truncated": self.is_truncated(),
+            "details": {
+                "pk_violations": self.pk_violations,
+                "required_violations": self.required_violations,
+                "range_violations": self.range_violations,
+            }
+        })
+    }
+
+    fn into_violations(self) -> Vec<DqViolation> {
+        let mut all = self.pk_violations;
+        all.extend(self.required_violations);
+        all.extend(self.range_violations);
+        all
+    }
+}
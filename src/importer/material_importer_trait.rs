@@ -6,11 +6,19 @@
 // 职责: 定义材料导入接口（不包含实现）
 // ==========================================
 
-use crate::domain::material::ImportResult;
+use crate::domain::material::{ImportResult, ImportSourceInfo};
+use crate::importer::streaming::RawRowStream;
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::error::Error;
 use std::path::Path;
 
+/// 解析结果：行记录列表 + 源信息（编码/工作表/表头行）
+pub type ParsedRecords = (Vec<HashMap<String, String>>, ImportSourceInfo);
+
+/// 流式解析结果：行迭代器 + 源信息
+pub type RowStreamWithInfo = (RawRowStream, ImportSourceInfo);
+
 // ==========================================
 // MaterialImporter Trait
 // ==========================================
@@ -40,6 +48,25 @@ pub trait MaterialImporter: Send + Sync {
         file_path: P,
     ) -> Result<ImportResult, Box<dyn Error>>;
 
+    /// 流式导入材料数据（逐行读取 + 分块事务落库）
+    ///
+    /// # 参数
+    /// - file_path: 文件路径（.csv/.xlsx/.xls）
+    /// - options: 分块大小 / 进度回调 / 取消令牌
+    ///
+    /// # 返回
+    /// - Ok(ImportResult): 导入结果；取消时 cancelled = true，已提交分块保留
+    /// - Err: 文件读取错误、数据库错误等
+    ///
+    /// # 说明
+    /// - 每个分块的 material_master + material_state 在同一事务中写入
+    /// - 取消在分块边界生效，不会出现只写一半的分块
+    async fn import_streaming<P: AsRef<Path> + Send>(
+        &self,
+        file_path: P,
+        options: crate::importer::streaming::StreamingImportOptions,
+    ) -> Result<ImportResult, Box<dyn Error>>;
+
     /// 从 CSV 文件导入材料数据
     ///
     /// # 参数
@@ -90,6 +117,38 @@ pub trait FileParser: Send + Sync {
         &self,
         file_path: &Path,
     ) -> Result<Vec<std::collections::HashMap<String, String>>, Box<dyn Error>>;
+
+    /// 解析文件并返回实际读取的源信息（编码/工作表/表头行）
+    ///
+    /// # 参数
+    /// - file_path: 文件路径
+    ///
+    /// # 返回
+    /// - Ok((records, source_info)): 行记录列表 + 源信息
+    /// - Err: 文件读取错误、格式错误
+    ///
+    /// # 说明
+    /// 默认实现仅调用 parse_to_raw_records，源信息留空
+    fn parse_with_source_info(&self, file_path: &Path) -> Result<ParsedRecords, Box<dyn Error>> {
+        let records = self.parse_to_raw_records(file_path)?;
+        Ok((records, Default::default()))
+    }
+
+    /// 打开行迭代器（流式导入）
+    ///
+    /// # 参数
+    /// - file_path: 文件路径
+    ///
+    /// # 返回
+    /// - Ok((stream, source_info)): 逐行产出的迭代器 + 源信息
+    /// - Err: 文件打开错误、表头读取错误
+    ///
+    /// # 说明
+    /// 默认实现一次性解析后再迭代；支持流式读取的解析器应覆盖此方法
+    fn open_row_stream(&self, file_path: &Path) -> Result<RowStreamWithInfo, Box<dyn Error>> {
+        let (records, source_info) = self.parse_with_source_info(file_path)?;
+        Ok((Box::new(records.into_iter().map(Ok)), source_info))
+    }
 }
 
 // ==========================================
@@ -9,6 +9,7 @@
 
 // 模块声明
 pub mod conflict_handler;
+pub mod contract_importer;
 pub mod data_cleaner;
 pub mod derivation;
 pub mod dq_validator;
@@ -18,16 +19,26 @@ pub mod file_parser;
 pub mod material_importer;
 pub mod material_importer_impl;
 pub mod material_importer_trait;
+pub mod production_actuals;
+pub mod streaming;
 
 // 重导出核心类型
 pub use conflict_handler::ConflictHandler as ConflictHandlerImpl;
+pub use contract_importer::{ContractFieldMapper, ContractImporter, CONTRACT_IMPORT_TYPE};
 pub use data_cleaner::DataCleaner as DataCleanerImpl;
 pub use derivation::DerivationService as DerivationServiceImpl;
 pub use dq_validator::DqValidator as DqValidatorImpl;
 pub use error::{ImportError, ImportResult};
 pub use field_mapper::FieldMapper as FieldMapperImpl;
-pub use file_parser::{CsvParser, ExcelParser, UniversalFileParser};
+pub use file_parser::{CsvParser, ExcelParser, HeaderProfile, ParseOptions, UniversalFileParser};
 pub use material_importer_impl::MaterialImporterImpl;
+pub use production_actuals::{
+    ParsedActuals, ProductionActualsParser, PRODUCTION_ACTUALS_IMPORT_TYPE,
+};
+pub use streaming::{
+    ImportCancelToken, ImportProgress, ImportProgressCallback, ImportStage, RawRowStream,
+    StreamingImportOptions,
+};
 
 // 重导出 Trait 接口
 pub use material_importer_trait::{
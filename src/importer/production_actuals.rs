@@ -0,0 +1,270 @@
+// ==========================================
+// 热轧精整排产系统 - 生产实绩文件解析
+// ==========================================
+// 职责: 实绩文件（MES/产线报工导出）→ ProductionActual + 行级 DQ
+// 说明:
+// - 仅做文件级校验（必填/格式/文件内重复）
+// - 材料存在性、重复回填等库级校验由 ProductionApi 完成
+// ==========================================
+
+use crate::domain::material::{DqLevel, DqViolation, ImportSourceInfo};
+use crate::domain::production::ProductionActual;
+use crate::importer::field_mapper::FieldMapper;
+use crate::importer::file_parser::{HeaderProfile, ParseOptions, UniversalFileParser};
+use crate::importer::material_importer_trait::FileParser;
+use chrono::NaiveDateTime;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+
+/// 实绩导入的导入类型标识（写入 action_log.payload_json.import_type）
+pub const PRODUCTION_ACTUALS_IMPORT_TYPE: &str = "PRODUCTION_ACTUALS";
+
+/// 实绩文件主键源列名（表头识别的必要条件）
+pub const ACTUALS_PRIMARY_KEY_HEADER: &str = "材料号";
+
+/// 实绩映射识别的全部源列名（含别名）
+pub const KNOWN_ACTUALS_HEADERS: &[&str] = &[
+    "材料号",
+    "实际机组",
+    "机组代码",
+    "机组",
+    "完工时间",
+    "实际完工时间",
+    "完成时间",
+    "实际重量",
+    "实绩重量",
+    "重量",
+];
+
+/// 实绩文件表头识别配置
+pub const ACTUALS_HEADER_PROFILE: HeaderProfile = HeaderProfile {
+    primary_key: ACTUALS_PRIMARY_KEY_HEADER,
+    known_headers: KNOWN_ACTUALS_HEADERS,
+};
+
+/// 完工时间可识别格式
+const COMPLETED_AT_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y%m%d%H%M%S",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M",
+];
+
+// ==========================================
+// ParsedActuals - 实绩文件解析结果
+// ==========================================
+#[derive(Debug, Clone)]
+pub struct ParsedActuals {
+    /// 通过行级校验的实绩（附原始行号）
+    pub records: Vec<(usize, ProductionActual)>,
+    pub violations: Vec<DqViolation>,
+    pub total_rows: usize,
+    pub blocked: usize,
+    pub conflict: usize,
+    pub source_info: ImportSourceInfo,
+}
+
+// ==========================================
+// ProductionActualsParser - 实绩文件解析器
+// ==========================================
+pub struct ProductionActualsParser {
+    mapper: FieldMapper,
+}
+
+impl Default for ProductionActualsParser {
+    fn default() -> Self {
+        Self {
+            mapper: FieldMapper,
+        }
+    }
+}
+
+impl ProductionActualsParser {
+    /// 解析实绩文件
+    ///
+    /// # DQ 规则
+    /// - 材料号/机组/完工时间/实际重量缺失、格式错误、重量非正 → ERROR（阻断该行）
+    /// - 文件内材料号重复 → CONFLICT（保留首行）
+    pub fn parse_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        options: ParseOptions,
+    ) -> Result<ParsedActuals, Box<dyn Error>> {
+        let parser =
+            UniversalFileParser::with_options(options).with_header_profile(ACTUALS_HEADER_PROFILE);
+        let (rows, source_info) = parser
+            .parse_with_source_info(file_path.as_ref())
+            .map_err(|e| format!("文件解析失败: {}", e))?;
+
+        let mut parsed = ParsedActuals {
+            records: Vec::new(),
+            violations: Vec::new(),
+            total_rows: rows.len(),
+            blocked: 0,
+            conflict: 0,
+            source_info,
+        };
+        let mut seen = HashSet::new();
+
+        for (idx, row) in rows.iter().enumerate() {
+            let row_number = idx + 1;
+            let actual = match self.map_row(row) {
+                Ok(actual) => actual,
+                Err(violation) => {
+                    parsed.violations.push(DqViolation {
+                        row_number,
+                        ..violation
+                    });
+                    parsed.blocked += 1;
+                    continue;
+                }
+            };
+
+            if !seen.insert(actual.material_id.clone()) {
+                parsed.violations.push(DqViolation {
+                    row_number,
+                    material_id: Some(actual.material_id.clone()),
+                    level: DqLevel::Conflict,
+                    field: "material_id".to_string(),
+                    message: format!("材料号在文件内重复，已保留首行: {}", actual.material_id),
+                });
+                parsed.conflict += 1;
+                continue;
+            }
+
+            parsed.records.push((row_number, actual));
+        }
+
+        Ok(parsed)
+    }
+
+    /// 单行映射（失败时返回 row_number=0 的 ERROR 违规，由调用方补行号）
+    pub fn map_row(&self, row: &HashMap<String, String>) -> Result<ProductionActual, DqViolation> {
+        let material_id = self.mapper.get_string(row, "材料号");
+        let error = |field: &str, message: String| DqViolation {
+            row_number: 0,
+            material_id: material_id.clone(),
+            level: DqLevel::Error,
+            field: field.to_string(),
+            message,
+        };
+
+        let Some(id) = material_id.clone() else {
+            return Err(error("material_id", "材料号缺失".to_string()));
+        };
+        let Some(machine_code) = self.mapper.get_string(row, "实际机组") else {
+            return Err(error("machine_code", format!("实际机组缺失: {}", id)));
+        };
+        let Some(completed_raw) = self.mapper.get_string(row, "完工时间") else {
+            return Err(error("completed_at", format!("完工时间缺失: {}", id)));
+        };
+        let Some(completed_at) = parse_completed_at(&completed_raw) else {
+            return Err(error(
+                "completed_at",
+                format!("完工时间格式无法识别: {} ({})", completed_raw, id),
+            ));
+        };
+        let Some(weight_raw) = self.mapper.get_string(row, "实际重量") else {
+            return Err(error("actual_weight_t", format!("实际重量缺失: {}", id)));
+        };
+        let actual_weight_t = match weight_raw.parse::<f64>() {
+            Ok(w) if w.is_finite() && w > 0.0 => w,
+            _ => {
+                return Err(error(
+                    "actual_weight_t",
+                    format!("实际重量无效: {} ({})", weight_raw, id),
+                ))
+            }
+        };
+
+        Ok(ProductionActual {
+            material_id: id,
+            machine_code: machine_code.to_uppercase(),
+            completed_at,
+            actual_weight_t,
+        })
+    }
+}
+
+fn parse_completed_at(value: &str) -> Option<NaiveDateTime> {
+    COMPLETED_AT_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::file_parser::detect_header_row_with;
+
+    fn row(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_map_actual_row_with_aliases() {
+        let parser = ProductionActualsParser::default();
+        let actual = parser
+            .map_row(&row(&[
+                ("材料号", " MAT001 "),
+                ("机组代码", "h032"),
+                ("实际完工时间", "2026/03/02 08:15:00"),
+                ("实绩重量", "2.45"),
+            ]))
+            .unwrap();
+
+        assert_eq!(actual.material_id, "MAT001");
+        assert_eq!(actual.machine_code, "H032");
+        assert_eq!(
+            actual.completed_at.to_string(),
+            "2026-03-02 08:15:00".to_string()
+        );
+        assert!((actual.actual_weight_t - 2.45).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_map_actual_row_rejects_bad_values() {
+        let parser = ProductionActualsParser::default();
+        let bad_time = parser
+            .map_row(&row(&[
+                ("材料号", "MAT001"),
+                ("实际机组", "H032"),
+                ("完工时间", "03-02"),
+                ("实际重量", "2.0"),
+            ]))
+            .unwrap_err();
+        assert_eq!(bad_time.field, "completed_at");
+
+        let bad_weight = parser
+            .map_row(&row(&[
+                ("材料号", "MAT001"),
+                ("实际机组", "H032"),
+                ("完工时间", "20260302081500"),
+                ("实际重量", "0"),
+            ]))
+            .unwrap_err();
+        assert_eq!(bad_weight.field, "actual_weight_t");
+        assert_eq!(bad_weight.level, DqLevel::Error);
+    }
+
+    #[test]
+    fn test_actuals_header_profile_detects_header_row() {
+        let rows: Vec<Vec<String>> = vec![
+            vec!["机组实绩报表".to_string()],
+            vec!["材料号", "实际机组", "完工时间", "实际重量"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        ];
+        assert_eq!(
+            detect_header_row_with(&rows, &ACTUALS_HEADER_PROFILE),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,180 @@
+// ==========================================
+// 热轧精整排产系统 - 流式导入支撑类型
+// ==========================================
+// 职责: 流式导入管道的行迭代器、进度回调、取消令牌
+// 流程: 行迭代 → 映射 → 清洗 → 校验 → 分块事务落库
+// 红线: 取消只发生在分块之间，已提交分块完整（master + state 同事务）
+// ==========================================
+
+use crate::importer::error::ImportError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 默认分块大小（行）
+pub const DEFAULT_IMPORT_CHUNK_SIZE: usize = 1000;
+
+/// DQ 违规明细保留上限（超出部分只计数不保留明细）
+pub const MAX_VIOLATION_DETAILS: usize = 10_000;
+
+/// 原始行迭代器（HashMap<列名, 值>）
+///
+/// size_hint 的上界（若有）作为总行数提示用于进度展示
+pub type RawRowStream =
+    Box<dyn Iterator<Item = Result<HashMap<String, String>, ImportError>> + Send>;
+
+/// 进度回调
+pub type ImportProgressCallback = Arc<dyn Fn(&ImportProgress) + Send + Sync>;
+
+// ==========================================
+// ImportStage - 导入阶段
+// ==========================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportStage {
+    Parsing,   // 已打开文件，开始读取
+    Writing,   // 分块已落库
+    Completed, // 导入完成
+    Cancelled, // 已取消（已提交分块保留）
+}
+
+// ==========================================
+// ImportProgress - 导入进度
+// ==========================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProgress {
+    pub batch_id: String,
+    pub stage: ImportStage,
+    pub processed_rows: usize,     // 已读取行数
+    pub total_rows: Option<usize>, // 总行数（CSV 流式读取时未知）
+    pub written_rows: usize,       // 已落库材料数
+    pub conflict_rows: usize,      // 已进入冲突队列行数
+    pub chunks_committed: usize,   // 已提交分块数
+}
+
+// ==========================================
+// ImportCancelToken - 取消令牌
+// ==========================================
+/// 导入取消令牌（可跨线程共享，克隆共享同一状态）
+#[derive(Clone, Default)]
+pub struct ImportCancelToken {
+    flag: Arc<AtomicBool>,
+}
+
+impl ImportCancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求取消（在下一个分块边界生效）
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+}
+
+impl fmt::Debug for ImportCancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportCancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+// ==========================================
+// StreamingImportOptions - 流式导入选项
+// ==========================================
+#[derive(Clone)]
+pub struct StreamingImportOptions {
+    /// 分块大小（每块一个事务）
+    pub chunk_size: usize,
+    /// 进度回调（每个分块提交后触发）
+    pub progress: Option<ImportProgressCallback>,
+    /// 取消令牌
+    pub cancel_token: Option<ImportCancelToken>,
+    /// 导入人（写入 import_batch.imported_by，缺省 "system"）
+    pub imported_by: Option<String>,
+}
+
+impl Default for StreamingImportOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_IMPORT_CHUNK_SIZE,
+            progress: None,
+            cancel_token: None,
+            imported_by: None,
+        }
+    }
+}
+
+impl StreamingImportOptions {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel_token
+            .as_ref()
+            .map(|t| t.is_cancelled())
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn report(&self, progress: &ImportProgress) {
+        if let Some(callback) = &self.progress {
+            callback(progress);
+        }
+    }
+}
+
+impl fmt::Debug for StreamingImportOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingImportOptions")
+            .field("chunk_size", &self.chunk_size)
+            .field("progress", &self.progress.is_some())
+            .field("cancel_token", &self.cancel_token)
+            .field("imported_by", &self.imported_by)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_cancel_token_shared_between_clones() {
+        let token = ImportCancelToken::new();
+        let cloned = token.clone();
+        assert!(!cloned.is_cancelled());
+
+        token.cancel();
+        assert!(cloned.is_cancelled());
+    }
+
+    #[test]
+    fn test_options_report_invokes_callback() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let options = StreamingImportOptions {
+            progress: Some(Arc::new(move |p: &ImportProgress| {
+                sink.lock().unwrap().push(p.processed_rows);
+            })),
+            ..Default::default()
+        };
+
+        options.report(&ImportProgress {
+            batch_id: "B1".to_string(),
+            stage: ImportStage::Writing,
+            processed_rows: 42,
+            total_rows: None,
+            written_rows: 40,
+            conflict_rows: 2,
+            chunks_committed: 1,
+        });
+
+        assert_eq!(*seen.lock().unwrap(), vec![42]);
+        assert!(!options.is_cancelled());
+    }
+}
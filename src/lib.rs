@@ -25,6 +25,9 @@ pub mod engine;
 // 导入层 - 外部数据
 pub mod importer;
 
+// 导出层 - 现场文件
+pub mod exporter;
+
 // 配置层 - 系统配置
 pub mod config;
 
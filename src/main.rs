@@ -34,20 +34,61 @@ fn main() {
     tracing::info!("AppState初始化成功");
     tracing::info!("启动Tauri应用...");
 
+    let auto_import = app_state.auto_import.clone();
+    let intervention_expiry = app_state.intervention_expiry.clone();
+
     // 启动Tauri应用
     tauri::Builder::default()
         .manage(app_state)
+        .setup(move |app| {
+            use tauri::Manager;
+
+            // 监控目录自动导入：每处理完一个文件推送前端事件
+            let handle = app.handle();
+            auto_import.start(Some(std::sync::Arc::new(
+                move |result: &hot_rolling_aps::app::auto_import::AutoImportFileResult| {
+                    if let Err(e) = handle.emit_all("auto_import_completed", result) {
+                        tracing::warn!("emit_all failed: event=auto_import_completed, error={}", e);
+                    }
+                    if result.imported > 0 {
+                        let _ = handle.emit_all(
+                            "material_state_changed",
+                            serde_json::json!({ "source": "auto_import" }),
+                        );
+                    }
+                },
+            )));
+
+            // 人工干预时效清扫：清除了干预时推送前端事件
+            let handle = app.handle();
+            intervention_expiry.start(Some(std::sync::Arc::new(
+                move |result: &hot_rolling_aps::api::ExpirySweepResult| {
+                    if let Err(e) = handle.emit_all("intervention_expired", result) {
+                        tracing::warn!("emit_all failed: event=intervention_expired, error={}", e);
+                    }
+                    let _ = handle.emit_all(
+                        "material_state_changed",
+                        serde_json::json!({ "source": "intervention_expiry" }),
+                    );
+                },
+            )));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // ==========================================
-            // 材料导入相关命令 (5个)
+            // 材料导入相关命令 (9个)
             // ==========================================
             import_materials,
+            cancel_material_import,
+            import_contracts,
+            get_auto_import_status,
+            run_auto_import_scan,
             list_import_conflicts,
             resolve_import_conflict,
             batch_resolve_import_conflicts,
             cancel_import_batch,
             // ==========================================
-            // 材料相关命令 (8个)
+            // 材料相关命令 (14个)
             // ==========================================
             list_materials,
             get_material_pool_summary,
@@ -58,6 +99,11 @@ fn main() {
             batch_clear_force_release,
             batch_set_urgent,
             list_materials_by_urgent_level,
+            set_intervention_expiry,
+            clear_intervention_expiry,
+            list_upcoming_intervention_expirations,
+            get_intervention_expiry_status,
+            run_intervention_expiry_sweep,
             // ==========================================
             // 排产方案相关命令 (19个)
             // ==========================================
@@ -76,6 +122,9 @@ fn main() {
             recalc_full,
             get_strategy_presets,
             generate_strategy_drafts,
+            cancel_strategy_draft_generation,
+            run_strategy_sweep,
+            learn_strategy_weights,
             apply_strategy_draft,
             get_strategy_draft_detail,
             list_strategy_drafts,
@@ -83,9 +132,23 @@ fn main() {
             get_plan_item_date_bounds,
             list_plan_items,
             list_items_by_date,
+            list_batching_deferrals,
             compare_versions,
             compare_versions_kpi,
+            list_version_diff_items,
+            generate_change_notice,
+            get_activation_change_notice,
+            export_change_notice,
             move_items,
+            list_bottleneck_recommendations,
+            apply_bottleneck_recommendations,
+            preview_version_merge,
+            apply_version_merge,
+            preview_rush_insertion,
+            apply_rush_insertion,
+            get_undo_status,
+            undo_last_operation,
+            redo_last_operation,
             // ==========================================
             // 驾驶舱相关命令 (9个)
             // ==========================================
@@ -127,6 +190,8 @@ fn main() {
             batch_reject_path_override_by_range,
             get_roll_cycle_anchor,
             reset_roll_cycle,
+            preview_resequence_machine_day,
+            resequence_machine_day,
             // ==========================================
             // 换辊管理相关命令 (7个)
             // ==========================================
@@ -148,8 +213,40 @@ fn main() {
             apply_rhythm_preset,
             get_daily_rhythm_profile,
             // ==========================================
-            // 决策支持相关命令 (7个)
+            // 生产实绩/计划执行对账相关命令 (3个)
+            // ==========================================
+            import_production_actuals,
+            get_plan_adherence_report,
+            list_carry_over_items,
+            // ==========================================
+            // 场景/沙盘方案相关命令 (8个)
+            // ==========================================
+            create_scenario_plan,
+            save_scenario_overlay,
+            get_scenario_overlay,
+            evaluate_scenario,
+            get_scenario_run,
+            promote_scenario_run,
+            simulate_machine_breakdown,
+            replay_recalc,
+            // ==========================================
+            // 版本激活审批相关命令 (6个)
+            // ==========================================
+            submit_version_for_approval,
+            approve_version,
+            reject_version,
+            get_version_reviews,
+            get_activation_checklist,
+            get_red_line_report,
+            // ==========================================
+            // 版本谱系与保留策略相关命令 (2个)
+            // ==========================================
+            get_version_lineage,
+            purge_versions,
+            // ==========================================
+            // 决策支持相关命令 (8个)
             // ==========================================
+            get_plan_objectives,            // D0: 方案多目标评价
             get_decision_day_summary,       // D1: 哪天最危险
             list_order_failure_set,         // D2: 哪些紧急单无法完成
             list_material_failure_set,      // D2M: 哪些材料无法满足
@@ -158,7 +255,7 @@ fn main() {
             get_roll_campaign_alert,        // D5: 换辊是否异常
             get_capacity_opportunity,       // D6: 是否存在产能优化空间
             // ==========================================
-            // 产能池管理相关命令 (8个)
+            // 产能池管理相关命令 (11个)
             // ==========================================
             get_capacity_pools,
             update_capacity_pool,
@@ -167,6 +264,10 @@ fn main() {
             create_or_update_machine_config,
             apply_machine_config_to_dates,
             get_machine_config_history,
+            create_capacity_reservation,
+            cancel_capacity_reservation,
+            list_capacity_reservations,
+            get_reservation_utilization,
             // ==========================================
             // 前端遥测/错误上报 (1个)
             // ==========================================
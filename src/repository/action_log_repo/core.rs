@@ -38,7 +38,11 @@ impl ActionLogRepository {
     /// - `Err(...)`: 数据库错误
     pub fn insert(&self, log: &ActionLog) -> RepositoryResult<String> {
         let conn = self.get_conn()?;
+        Self::insert_log(&conn, log)
+    }
 
+    /// 在调用方事务内插入操作日志（供跨表原子写入复用）
+    pub(crate) fn insert_log(conn: &Connection, log: &ActionLog) -> RepositoryResult<String> {
         conn.execute(
             r#"
             INSERT INTO action_log (
@@ -89,29 +93,7 @@ impl ActionLogRepository {
 
         let mut count = 0;
         for log in logs {
-            tx.execute(
-                r#"
-                INSERT INTO action_log (
-                    action_id, version_id, action_type, action_ts, actor,
-                    payload_json, impact_summary_json, machine_code,
-                    date_range_start, date_range_end, detail
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-                "#,
-                params![
-                    log.action_id,
-                    log.version_id,
-                    log.action_type,
-                    log.action_ts.format("%Y-%m-%d %H:%M:%S").to_string(),
-                    log.actor,
-                    log.payload_json.as_ref().map(|v| v.to_string()),
-                    log.impact_summary_json.as_ref().map(|v| v.to_string()),
-                    log.machine_code,
-                    log.date_range_start
-                        .map(|d| d.format("%Y-%m-%d").to_string()),
-                    log.date_range_end.map(|d| d.format("%Y-%m-%d").to_string()),
-                    log.detail,
-                ],
-            )?;
+            Self::insert_log(&tx, &log)?;
             count += 1;
         }
 
@@ -328,6 +328,164 @@ impl ActionLogRepository {
         Ok(logs)
     }
 
+    /// 查询指定日志之后写入的、涉及指定材料的操作日志（撤销冲突说明用）
+    ///
+    /// 说明：
+    /// - 匹配口径同 find_by_material_id_in_time_range（JSON 字段使用 `"MATERIAL_ID"` 匹配）
+    /// - action_ts 为秒级精度，“之后”按写入顺序（rowid）判断，最近的在前
+    pub fn find_by_material_id_after_action(
+        &self,
+        material_id: &str,
+        after_action_id: &str,
+        limit: i32,
+    ) -> RepositoryResult<Vec<ActionLog>> {
+        let conn = self.get_conn()?;
+        let json_token = format!("\"{}\"", material_id);
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT action_id, version_id, action_type, action_ts, actor,
+                   payload_json, impact_summary_json, machine_code,
+                   date_range_start, date_range_end, detail
+            FROM action_log
+            WHERE rowid > (SELECT rowid FROM action_log WHERE action_id = ?)
+              AND (
+                instr(COALESCE(detail, ''), ?) > 0
+                OR instr(COALESCE(payload_json, ''), ?) > 0
+                OR instr(COALESCE(impact_summary_json, ''), ?) > 0
+              )
+            ORDER BY rowid DESC
+            LIMIT ?
+            "#,
+        )?;
+
+        let logs = stmt
+            .query_map(
+                params![
+                    after_action_id,
+                    material_id,
+                    &json_token,
+                    &json_token,
+                    limit
+                ],
+                |row| self.map_row(row),
+            )?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(logs)
+    }
+
+    /// 查询操作人在版本下的可撤销日志（撤销/重做栈回放用）
+    ///
+    /// 说明：
+    /// - 口径：指定版本的日志 + 不关联版本的材料级操作（锁定/放行/紧急标志）
+    /// - 仅返回 payload_json 携带逆操作（`"undo"`）的日志
+    /// - action_ts 为秒级精度，按写入顺序（rowid）正序返回最近 limit 条
+    pub fn find_undoable_by_actor(
+        &self,
+        version_id: &str,
+        actor: &str,
+        limit: i32,
+    ) -> RepositoryResult<Vec<ActionLog>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT action_id, version_id, action_type, action_ts, actor,
+                   payload_json, impact_summary_json, machine_code,
+                   date_range_start, date_range_end, detail
+            FROM (
+                SELECT rowid AS rid, *
+                FROM action_log
+                WHERE actor = ?
+                  AND (version_id = ? OR version_id IS NULL)
+                  AND instr(COALESCE(payload_json, ''), '"undo":') > 0
+                ORDER BY rowid DESC
+                LIMIT ?
+            )
+            ORDER BY rid ASC
+            "#,
+        )?;
+
+        let logs = stmt
+            .query_map(params![actor, version_id, limit], |row| self.map_row(row))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(logs)
+    }
+
+    /// 按写入顺序（rowid 正序）查询指定版本、指定类型的日志
+    ///
+    /// 说明：action_ts 为秒级精度，需回放状态的场景以 rowid 定序
+    pub fn find_by_version_and_types(
+        &self,
+        version_id: &str,
+        action_types: &[&str],
+    ) -> RepositoryResult<Vec<ActionLog>> {
+        if action_types.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.get_conn()?;
+        let placeholders = vec!["?"; action_types.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT action_id, version_id, action_type, action_ts, actor,
+                   payload_json, impact_summary_json, machine_code,
+                   date_range_start, date_range_end, detail
+            FROM action_log
+            WHERE version_id = ?
+              AND action_type IN ({})
+            ORDER BY rowid ASC
+            "#,
+            placeholders
+        );
+
+        let mut values: Vec<&dyn rusqlite::ToSql> = Vec::with_capacity(action_types.len() + 1);
+        values.push(&version_id);
+        for t in action_types {
+            values.push(t);
+        }
+        let mut stmt = conn.prepare(&sql)?;
+        let logs = stmt
+            .query_map(values.as_slice(), |row| self.map_row(row))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(logs)
+    }
+
+    /// 统计指定日志之后写入的、指定类型的操作数（人工干预按重算次数失效用）
+    ///
+    /// 说明：指定日志不存在时返回 0
+    pub fn count_by_action_types_after(
+        &self,
+        after_action_id: &str,
+        action_types: &[&str],
+    ) -> RepositoryResult<i64> {
+        if action_types.is_empty() {
+            return Ok(0);
+        }
+        let conn = self.get_conn()?;
+        let placeholders = vec!["?"; action_types.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT COUNT(*)
+            FROM action_log
+            WHERE rowid > (SELECT rowid FROM action_log WHERE action_id = ?)
+              AND action_type IN ({})
+            "#,
+            placeholders
+        );
+
+        let mut values: Vec<&dyn rusqlite::ToSql> = Vec::with_capacity(action_types.len() + 1);
+        values.push(&after_action_id);
+        for t in action_types {
+            values.push(t);
+        }
+        let count: i64 = conn.query_row(&sql, values.as_slice(), |row| row.get(0))?;
+
+        Ok(count)
+    }
+
     /// 统计指定版本的操作总数
     pub fn count_by_version(&self, version_id: &str) -> RepositoryResult<i32> {
         let conn = self.get_conn()?;
@@ -274,3 +274,77 @@ fn test_find_by_material_id_in_time_range_matches_detail_and_json_token() {
         .unwrap();
     assert_eq!(logs_limited.len(), 2);
 }
+
+#[test]
+fn test_find_undoable_by_actor_in_write_order() {
+    let conn = setup_test_db();
+    let repo = ActionLogRepository::new(conn);
+
+    // 同一秒内写入：按写入顺序返回，仅返回携带逆操作的日志
+    for (id, version, actor, undoable) in [
+        ("log_c", "v1", "user1", true),
+        ("log_a", "v1", "user1", true),
+        ("log_x", "v1", "user1", false),
+        ("log_y", "v2", "user1", true),
+        ("log_z", "v1", "user2", true),
+        ("log_b", "v1", "user1", true),
+    ] {
+        let mut log = make_test_log(id, version, actor);
+        if undoable {
+            log.payload_json = Some(serde_json::json!({ "undo": { "kind": "MOVE_ITEMS" } }));
+        }
+        repo.insert(&log).unwrap();
+    }
+
+    let logs = repo.find_undoable_by_actor("v1", "user1", 10).unwrap();
+    let ids: Vec<&str> = logs.iter().map(|l| l.action_id.as_str()).collect();
+    assert_eq!(ids, vec!["log_c", "log_a", "log_b"]);
+
+    // limit 保留最近的日志
+    let logs = repo.find_undoable_by_actor("v1", "user1", 2).unwrap();
+    let ids: Vec<&str> = logs.iter().map(|l| l.action_id.as_str()).collect();
+    assert_eq!(ids, vec!["log_a", "log_b"]);
+
+    // 指定日志之后写入的匹配日志，最近的在前
+    let later = repo
+        .find_by_material_id_after_action("MOVE_ITEMS", "log_a", 10)
+        .unwrap();
+    let ids: Vec<&str> = later.iter().map(|l| l.action_id.as_str()).collect();
+    assert_eq!(ids, vec!["log_b", "log_z", "log_y"]);
+}
+
+#[test]
+fn test_count_by_action_types_after() {
+    let conn = setup_test_db();
+    let repo = ActionLogRepository::new(conn);
+
+    for (id, action_type) in [
+        ("r0", "RECALC_FULL"),
+        ("anchor", "SET_INTERVENTION_EXPIRY"),
+        ("r1", "RECALC_PARTIAL"),
+        ("m1", "MOVE_ITEMS"),
+        ("r2", "RECALC_FULL"),
+    ] {
+        let mut log = make_test_log(id, "v1", "user1");
+        log.action_type = action_type.to_string();
+        repo.insert(&log).unwrap();
+    }
+
+    let recalc_types = ["RECALC_FULL", "RECALC_PARTIAL"];
+    assert_eq!(
+        repo.count_by_action_types_after("anchor", &recalc_types)
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        repo.count_by_action_types_after("r2", &recalc_types)
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        repo.count_by_action_types_after("missing", &recalc_types)
+            .unwrap(),
+        0
+    );
+    assert_eq!(repo.count_by_action_types_after("anchor", &[]).unwrap(), 0);
+}
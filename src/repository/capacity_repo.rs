@@ -12,6 +12,8 @@ use chrono::NaiveDate;
 use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
 use std::sync::{Arc, Mutex};
 
+mod reservation;
+
 // ==========================================
 // CapacityPoolRepository - 产能池仓储
 // ==========================================
@@ -257,9 +259,28 @@ impl CapacityPoolRepository {
         // 开启事务
         conn.execute("BEGIN TRANSACTION", [])?;
 
+        let updated_count = match Self::upsert_pools(&conn, &pools) {
+            Ok(count) => count,
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK", []);
+                return Err(e);
+            }
+        };
+
+        // 提交事务
+        conn.execute("COMMIT", [])?;
+
+        Ok(updated_count)
+    }
+
+    /// 在调用方事务内批量写入产能池（INSERT OR REPLACE，供跨表原子写入复用）
+    pub(crate) fn upsert_pools(
+        conn: &Connection,
+        pools: &[CapacityPool],
+    ) -> RepositoryResult<usize> {
         let mut updated_count = 0;
 
-        for pool in &pools {
+        for pool in pools {
             let plan_date_str = pool.plan_date.format("%Y-%m-%d").to_string();
 
             let affected = conn.execute(
@@ -287,9 +308,6 @@ impl CapacityPoolRepository {
             updated_count += affected;
         }
 
-        // 提交事务
-        conn.execute("COMMIT", [])?;
-
         Ok(updated_count)
     }
 
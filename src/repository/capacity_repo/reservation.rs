@@ -0,0 +1,281 @@
+// ==========================================
+// 产能预留 / 预留使用快照
+// ==========================================
+// 职责:
+// - capacity_reservation: 战略合同/客户的机组日产能预留（定义）
+// - capacity_reservation_usage: 重算时落库的预留使用快照（版本 × 预留 × 日期）
+// 说明:
+// - 表由本模块按需创建（CREATE TABLE IF NOT EXISTS），兼容旧库
+// ==========================================
+
+use super::CapacityPoolRepository;
+use crate::domain::capacity::{CapacityReservation, ReservationUsage};
+use crate::repository::error::RepositoryResult;
+use chrono::{NaiveDate, NaiveDateTime};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+const DATE_FMT: &str = "%Y-%m-%d";
+const DATETIME_FMT: &str = "%Y-%m-%d %H:%M:%S";
+
+const RESERVATION_COLUMNS: &str = r#"
+    SELECT reservation_id, machine_code, date_from, date_to, daily_reserved_t,
+           contract_no, contract_prefix, release_days_before, expires_on,
+           reason, created_by, created_at
+    FROM capacity_reservation
+"#;
+
+impl CapacityPoolRepository {
+    fn ensure_reservation_schema(conn: &Connection) -> RepositoryResult<()> {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS capacity_reservation (
+              reservation_id TEXT PRIMARY KEY,
+              machine_code TEXT NOT NULL,
+              date_from TEXT NOT NULL,
+              date_to TEXT NOT NULL,
+              daily_reserved_t REAL NOT NULL,
+              contract_no TEXT,
+              contract_prefix TEXT,
+              release_days_before INTEGER NOT NULL DEFAULT 0,
+              expires_on TEXT,
+              reason TEXT,
+              created_by TEXT NOT NULL,
+              created_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_capacity_reservation_machine_date
+              ON capacity_reservation(machine_code, date_from, date_to);
+
+            CREATE TABLE IF NOT EXISTS capacity_reservation_usage (
+              version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
+              reservation_id TEXT NOT NULL,
+              machine_code TEXT NOT NULL,
+              plan_date TEXT NOT NULL,
+              reserved_t REAL NOT NULL,
+              used_t REAL NOT NULL,
+              released_t REAL NOT NULL,
+              held INTEGER NOT NULL,
+              PRIMARY KEY (version_id, reservation_id, plan_date)
+            );
+            "#,
+        )?;
+        Ok(())
+    }
+
+    // ==========================================
+    // 预留定义
+    // ==========================================
+
+    /// 新增产能预留
+    pub fn insert_reservation(&self, reservation: &CapacityReservation) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        Self::ensure_reservation_schema(&conn)?;
+        conn.execute(
+            r#"INSERT INTO capacity_reservation (
+                 reservation_id, machine_code, date_from, date_to, daily_reserved_t,
+                 contract_no, contract_prefix, release_days_before, expires_on,
+                 reason, created_by, created_at
+               ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"#,
+            params![
+                reservation.reservation_id,
+                reservation.machine_code,
+                reservation.date_from.format(DATE_FMT).to_string(),
+                reservation.date_to.format(DATE_FMT).to_string(),
+                reservation.daily_reserved_t,
+                reservation.contract_no,
+                reservation.contract_prefix,
+                reservation.release_days_before,
+                reservation
+                    .expires_on
+                    .map(|d| d.format(DATE_FMT).to_string()),
+                reservation.reason,
+                reservation.created_by,
+                reservation.created_at.format(DATETIME_FMT).to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 删除产能预留（取消），返回删除条数；已落库的使用快照保留用于历史版本汇报
+    pub fn delete_reservation(&self, reservation_id: &str) -> RepositoryResult<usize> {
+        let conn = self.get_conn()?;
+        Self::ensure_reservation_schema(&conn)?;
+        Ok(conn.execute(
+            "DELETE FROM capacity_reservation WHERE reservation_id = ?1",
+            params![reservation_id],
+        )?)
+    }
+
+    /// 按ID查询产能预留
+    pub fn find_reservation(
+        &self,
+        reservation_id: &str,
+    ) -> RepositoryResult<Option<CapacityReservation>> {
+        let conn = self.get_conn()?;
+        Self::ensure_reservation_schema(&conn)?;
+        let sql = format!("{} WHERE reservation_id = ?1", RESERVATION_COLUMNS);
+        Ok(conn
+            .query_row(&sql, params![reservation_id], Self::map_reservation)
+            .optional()?)
+    }
+
+    /// 查询产能预留（可按机组过滤；按机组、起始日期排序）
+    pub fn list_reservations(
+        &self,
+        machine_code: Option<&str>,
+    ) -> RepositoryResult<Vec<CapacityReservation>> {
+        let conn = self.get_conn()?;
+        Self::ensure_reservation_schema(&conn)?;
+        let sql = format!(
+            "{} WHERE (?1 IS NULL OR machine_code = ?1) ORDER BY machine_code, date_from, created_at",
+            RESERVATION_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(params![machine_code], Self::map_reservation)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// 查询与日期区间有交集的机组预留
+    pub fn find_reservations_overlapping(
+        &self,
+        machine_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> RepositoryResult<Vec<CapacityReservation>> {
+        let conn = self.get_conn()?;
+        Self::ensure_reservation_schema(&conn)?;
+        let sql = format!(
+            "{} WHERE machine_code = ?1 AND date_from <= ?3 AND date_to >= ?2 ORDER BY created_at, reservation_id",
+            RESERVATION_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(
+                params![
+                    machine_code,
+                    start_date.format(DATE_FMT).to_string(),
+                    end_date.format(DATE_FMT).to_string()
+                ],
+                Self::map_reservation,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    // ==========================================
+    // 预留使用快照
+    // ==========================================
+
+    /// 覆盖写入版本的预留使用快照（仅替换本次重算的机组与日期范围）
+    pub fn replace_reservation_usage(
+        &self,
+        version_id: &str,
+        machine_codes: &[String],
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        usage: &[ReservationUsage],
+    ) -> RepositoryResult<usize> {
+        let mut conn = self.get_conn()?;
+        Self::ensure_reservation_schema(&conn)?;
+        let tx = conn.transaction()?;
+        for machine_code in machine_codes {
+            tx.execute(
+                r#"DELETE FROM capacity_reservation_usage
+                   WHERE version_id = ?1 AND machine_code = ?2 AND plan_date BETWEEN ?3 AND ?4"#,
+                params![
+                    version_id,
+                    machine_code,
+                    start_date.format(DATE_FMT).to_string(),
+                    end_date.format(DATE_FMT).to_string()
+                ],
+            )?;
+        }
+        for u in usage {
+            tx.execute(
+                r#"INSERT OR REPLACE INTO capacity_reservation_usage (
+                     version_id, reservation_id, machine_code, plan_date,
+                     reserved_t, used_t, released_t, held
+                   ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"#,
+                params![
+                    u.version_id,
+                    u.reservation_id,
+                    u.machine_code,
+                    u.plan_date.format(DATE_FMT).to_string(),
+                    u.reserved_t,
+                    u.used_t,
+                    u.released_t,
+                    u.held as i32,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(usage.len())
+    }
+
+    /// 查询版本的预留使用快照（按预留、日期排序）
+    pub fn find_reservation_usage_by_version(
+        &self,
+        version_id: &str,
+    ) -> RepositoryResult<Vec<ReservationUsage>> {
+        let conn = self.get_conn()?;
+        Self::ensure_reservation_schema(&conn)?;
+        let mut stmt = conn.prepare(
+            r#"SELECT version_id, reservation_id, machine_code, plan_date,
+                      reserved_t, used_t, released_t, held
+               FROM capacity_reservation_usage
+               WHERE version_id = ?1
+               ORDER BY reservation_id, plan_date"#,
+        )?;
+        let rows = stmt
+            .query_map(params![version_id], |row| {
+                Ok(ReservationUsage {
+                    version_id: row.get(0)?,
+                    reservation_id: row.get(1)?,
+                    machine_code: row.get(2)?,
+                    plan_date: parse_date(3, &row.get::<_, String>(3)?)?,
+                    reserved_t: row.get(4)?,
+                    used_t: row.get(5)?,
+                    released_t: row.get(6)?,
+                    held: row.get::<_, i32>(7)? != 0,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    // ==========================================
+    // 辅助方法
+    // ==========================================
+
+    fn map_reservation(row: &Row) -> rusqlite::Result<CapacityReservation> {
+        let expires_on: Option<String> = row.get(8)?;
+        let created_at: String = row.get(11)?;
+        Ok(CapacityReservation {
+            reservation_id: row.get(0)?,
+            machine_code: row.get(1)?,
+            date_from: parse_date(2, &row.get::<_, String>(2)?)?,
+            date_to: parse_date(3, &row.get::<_, String>(3)?)?,
+            daily_reserved_t: row.get(4)?,
+            contract_no: row.get(5)?,
+            contract_prefix: row.get(6)?,
+            release_days_before: row.get(7)?,
+            expires_on: expires_on
+                .as_deref()
+                .map(|s| parse_date(8, s))
+                .transpose()?,
+            reason: row.get(9)?,
+            created_by: row.get(10)?,
+            created_at: NaiveDateTime::parse_from_str(&created_at, DATETIME_FMT).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(11, Type::Text, Box::new(e))
+            })?,
+        })
+    }
+}
+
+fn parse_date(idx: usize, raw: &str) -> rusqlite::Result<NaiveDate> {
+    NaiveDate::parse_from_str(raw, DATE_FMT)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, Box::new(e)))
+}
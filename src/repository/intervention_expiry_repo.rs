@@ -0,0 +1,225 @@
+// ==========================================
+// 热轧精整排产系统 - 人工干预时效仓储
+// ==========================================
+// 职责:
+// - 管理 material_intervention_expiry (材料 + 干预类型 唯一)
+// 说明:
+// - 仅存时效条件；干预标志本身仍在 material_state 上，清除由 API 层完成
+// ==========================================
+
+use crate::db::open_sqlite_connection;
+use crate::domain::intervention::{InterventionExpiry, InterventionType};
+use crate::repository::error::{RepositoryError, RepositoryResult};
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, Row};
+use std::sync::{Arc, Mutex};
+
+const DATETIME_FMT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// material_intervention_expiry 行（列顺序同 SELECT_COLUMNS）
+type ExpiryRow = (
+    String,
+    String,
+    Option<String>,
+    Option<i32>,
+    String,
+    Option<String>,
+    String,
+    String,
+);
+
+const SELECT_COLUMNS: &str = r#"
+    SELECT material_id, intervention_type, expires_at, expire_after_recalcs,
+           operator, reason, anchor_action_id, created_at
+    FROM material_intervention_expiry
+"#;
+
+pub struct InterventionExpiryRepository {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl InterventionExpiryRepository {
+    pub fn new(db_path: &str) -> RepositoryResult<Self> {
+        let conn = open_sqlite_connection(db_path)?;
+        let repo = Self {
+            conn: Arc::new(Mutex::new(conn)),
+        };
+        repo.ensure_tables()?;
+        Ok(repo)
+    }
+
+    pub fn from_connection(conn: Arc<Mutex<Connection>>) -> RepositoryResult<Self> {
+        let repo = Self { conn };
+        repo.ensure_tables()?;
+        Ok(repo)
+    }
+
+    fn get_conn(&self) -> RepositoryResult<std::sync::MutexGuard<Connection>> {
+        self.conn
+            .lock()
+            .map_err(|e| RepositoryError::LockError(e.to_string()))
+    }
+
+    fn ensure_tables(&self) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS material_intervention_expiry (
+              material_id TEXT NOT NULL REFERENCES material_master(material_id) ON DELETE CASCADE,
+              intervention_type TEXT NOT NULL,
+              expires_at TEXT,
+              expire_after_recalcs INTEGER,
+              operator TEXT NOT NULL,
+              reason TEXT,
+              anchor_action_id TEXT NOT NULL,
+              created_at TEXT NOT NULL,
+              PRIMARY KEY (material_id, intervention_type)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_material_intervention_expiry_operator
+              ON material_intervention_expiry(operator, expires_at);
+            "#,
+        )?;
+        Ok(())
+    }
+
+    /// 批量写入时效（同一材料同一干预类型覆盖旧记录，同一事务）
+    pub fn upsert_batch(&self, entries: &[InterventionExpiry]) -> RepositoryResult<usize> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        for e in entries {
+            tx.execute(
+                r#"INSERT INTO material_intervention_expiry (
+                     material_id, intervention_type, expires_at, expire_after_recalcs,
+                     operator, reason, anchor_action_id, created_at
+                   ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                   ON CONFLICT(material_id, intervention_type) DO UPDATE SET
+                     expires_at = excluded.expires_at,
+                     expire_after_recalcs = excluded.expire_after_recalcs,
+                     operator = excluded.operator,
+                     reason = excluded.reason,
+                     anchor_action_id = excluded.anchor_action_id,
+                     created_at = excluded.created_at"#,
+                params![
+                    &e.material_id,
+                    e.intervention_type.to_db_str(),
+                    e.expires_at.map(|t| t.format(DATETIME_FMT).to_string()),
+                    e.expire_after_recalcs,
+                    &e.operator,
+                    &e.reason,
+                    &e.anchor_action_id,
+                    e.created_at.format(DATETIME_FMT).to_string(),
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(entries.len())
+    }
+
+    /// 批量删除时效（返回实际删除条数）
+    pub fn delete_batch(&self, keys: &[(String, InterventionType)]) -> RepositoryResult<usize> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        let mut deleted = 0;
+        for (material_id, intervention_type) in keys {
+            deleted += tx.execute(
+                "DELETE FROM material_intervention_expiry WHERE material_id = ?1 AND intervention_type = ?2",
+                params![material_id, intervention_type.to_db_str()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// 查询单条时效
+    pub fn find(
+        &self,
+        material_id: &str,
+        intervention_type: InterventionType,
+    ) -> RepositoryResult<Option<InterventionExpiry>> {
+        let conn = self.get_conn()?;
+        let sql = format!(
+            "{} WHERE material_id = ?1 AND intervention_type = ?2",
+            SELECT_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query_map(
+            params![material_id, intervention_type.to_db_str()],
+            Self::map_row,
+        )?;
+        rows.next().transpose()?.map(Self::to_entity).transpose()
+    }
+
+    /// 查询全部时效（可按设置人过滤；有到期时间的在前，按到期时间正序）
+    pub fn list(&self, operator: Option<&str>) -> RepositoryResult<Vec<InterventionExpiry>> {
+        let conn = self.get_conn()?;
+        let sql = format!(
+            r#"{} WHERE (?1 IS NULL OR operator = ?1)
+               ORDER BY expires_at IS NULL, expires_at ASC, material_id ASC, intervention_type ASC"#,
+            SELECT_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(params![operator], Self::map_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter().map(Self::to_entity).collect()
+    }
+
+    // ==========================================
+    // 辅助方法
+    // ==========================================
+
+    fn map_row(row: &Row) -> rusqlite::Result<ExpiryRow> {
+        Ok((
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+            row.get(5)?,
+            row.get(6)?,
+            row.get(7)?,
+        ))
+    }
+
+    fn to_entity(
+        (
+            material_id,
+            intervention_type,
+            expires_at,
+            expire_after_recalcs,
+            operator,
+            reason,
+            anchor_action_id,
+            created_at,
+        ): ExpiryRow,
+    ) -> RepositoryResult<InterventionExpiry> {
+        let parse_ts = |field: &str, raw: &str| {
+            NaiveDateTime::parse_from_str(raw, DATETIME_FMT).map_err(|e| {
+                RepositoryError::FieldValueError {
+                    field: field.to_string(),
+                    message: e.to_string(),
+                }
+            })
+        };
+        let intervention_type = InterventionType::parse(&intervention_type).ok_or_else(|| {
+            RepositoryError::FieldValueError {
+                field: "intervention_type".to_string(),
+                message: format!("未知干预类型: {}", intervention_type),
+            }
+        })?;
+        Ok(InterventionExpiry {
+            material_id,
+            intervention_type,
+            expires_at: expires_at
+                .as_deref()
+                .map(|s| parse_ts("expires_at", s))
+                .transpose()?,
+            expire_after_recalcs,
+            operator,
+            reason,
+            anchor_action_id,
+            created_at: parse_ts("created_at", &created_at)?,
+        })
+    }
+}
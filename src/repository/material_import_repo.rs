@@ -45,6 +45,43 @@ pub trait MaterialImportRepository: Send + Sync {
         states: Vec<MaterialState>,
     ) -> Result<usize, Box<dyn Error>>;
 
+    /// 同一事务内批量写入 MaterialMaster + MaterialState（流式导入分块落库）
+    ///
+    /// # 参数
+    /// - materials: 材料主数据列表
+    /// - states: 材料状态列表
+    ///
+    /// # 返回
+    /// - Ok(usize): 写入的 MaterialMaster 记录数
+    /// - Err: 数据库错误（整个分块回滚，master/state 不会只写一半）
+    ///
+    /// # 说明
+    /// 默认实现依次调用两个批量写入方法（非原子），数据库实现应覆盖
+    async fn batch_insert_materials_with_states(
+        &self,
+        materials: Vec<MaterialMaster>,
+        states: Vec<MaterialState>,
+    ) -> Result<usize, Box<dyn Error>> {
+        let count = self.batch_insert_material_master(materials).await?;
+        self.batch_insert_material_state(states).await?;
+        Ok(count)
+    }
+
+    /// 同一事务内更新材料的合同影子字段及催料/紧急等级（合同导入）
+    ///
+    /// # 参数
+    /// - materials: 已写入合同属性的材料主数据（仅更新 contract_nature/weekly_delivery_flag/export_flag/due_date）
+    /// - states: 已重算等级的材料状态（仅更新 rush_level/urgent_level/urgent_reason）
+    ///
+    /// # 返回
+    /// - Ok(usize): 更新的 MaterialMaster 记录数
+    /// - Err: 数据库错误（整个事务回滚）
+    async fn batch_update_contract_fields(
+        &self,
+        materials: Vec<MaterialMaster>,
+        states: Vec<MaterialState>,
+    ) -> Result<usize, Box<dyn Error>>;
+
     // ===== 冲突队列管理 =====
 
     /// 插入冲突记录到 import_conflict 表
@@ -183,6 +220,18 @@ pub trait MaterialImportRepository: Send + Sync {
         material_ids: Vec<String>,
     ) -> Result<Vec<String>, Box<dyn Error>>;
 
+    /// 按合同号查询关联材料（主数据 + 状态）
+    ///
+    /// # 参数
+    /// - contract_nos: 合同号列表
+    ///
+    /// # 返回
+    /// - Ok(Vec<(MaterialMaster, MaterialState)>): 关联材料（无状态记录的材料不返回）
+    async fn find_materials_by_contract_nos(
+        &self,
+        contract_nos: Vec<String>,
+    ) -> Result<Vec<(MaterialMaster, MaterialState)>, Box<dyn Error>>;
+
     /// 统计 material_master 表记录数
     async fn count_materials(&self) -> Result<usize, Box<dyn Error>>;
 
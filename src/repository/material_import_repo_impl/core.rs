@@ -143,6 +143,7 @@ impl MaterialImportRepositoryImpl {
                 crate::domain::types::SchedState::ForceRelease => "FORCE_RELEASE",
                 crate::domain::types::SchedState::Blocked => "BLOCKED",
                 crate::domain::types::SchedState::Scheduled => "SCHEDULED",
+                crate::domain::types::SchedState::Completed => "COMPLETED",
             };
 
             let urgent_level_str = match state.urgent_level {
@@ -2,6 +2,7 @@ use super::core::parse_conflict_type;
 use super::MaterialImportRepositoryImpl;
 use crate::domain::material::{ImportBatch, ImportConflict, MaterialMaster, MaterialState};
 use crate::repository::material_import_repo::MaterialImportRepository;
+use crate::repository::material_repo::{MaterialMasterRepository, MaterialStateRepository};
 use async_trait::async_trait;
 use rusqlite::params;
 use std::error::Error;
@@ -36,6 +37,76 @@ impl MaterialImportRepository for MaterialImportRepositoryImpl {
         Ok(count)
     }
 
+    /// 同一事务内批量插入 MaterialMaster + MaterialState
+    async fn batch_insert_materials_with_states(
+        &self,
+        materials: Vec<MaterialMaster>,
+        states: Vec<MaterialState>,
+    ) -> Result<usize, Box<dyn Error>> {
+        let conn = self.conn.lock().map_err(|e| format!("锁获取失败: {}", e))?;
+        let tx = conn.unchecked_transaction()?;
+
+        let count = Self::batch_insert_material_master_tx(&tx, &materials)?;
+        Self::batch_insert_material_state_tx(&tx, &states)?;
+
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// 同一事务内更新合同影子字段 + 催料/紧急等级
+    async fn batch_update_contract_fields(
+        &self,
+        materials: Vec<MaterialMaster>,
+        states: Vec<MaterialState>,
+    ) -> Result<usize, Box<dyn Error>> {
+        let conn = self.conn.lock().map_err(|e| format!("锁获取失败: {}", e))?;
+        let tx = conn.unchecked_transaction()?;
+
+        let mut count = 0;
+        {
+            let mut master_stmt = tx.prepare(
+                r#"
+                UPDATE material_master
+                SET contract_nature = ?2, weekly_delivery_flag = ?3, export_flag = ?4,
+                    due_date = ?5, updated_at = ?6
+                WHERE material_id = ?1
+                "#,
+            )?;
+            for material in &materials {
+                count += master_stmt.execute(params![
+                    material.material_id,
+                    material.contract_nature,
+                    material.weekly_delivery_flag,
+                    material.export_flag,
+                    material.due_date,
+                    material.updated_at,
+                ])?;
+            }
+
+            let mut state_stmt = tx.prepare(
+                r#"
+                UPDATE material_state
+                SET rush_level = ?2, urgent_level = ?3, urgent_reason = ?4,
+                    updated_at = ?5, updated_by = ?6
+                WHERE material_id = ?1
+                "#,
+            )?;
+            for state in &states {
+                state_stmt.execute(params![
+                    state.material_id,
+                    format!("{:?}", state.rush_level),
+                    format!("{:?}", state.urgent_level),
+                    state.urgent_reason,
+                    state.updated_at.to_rfc3339(),
+                    state.updated_by,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(count)
+    }
+
     /// 插入单个冲突记录
     async fn insert_conflict(&self, conflict: ImportConflict) -> Result<(), Box<dyn Error>> {
         let conn = self.conn.lock().map_err(|e| format!("锁获取失败: {}", e))?;
@@ -527,6 +598,50 @@ impl MaterialImportRepository for MaterialImportRepositoryImpl {
         Ok(existing_ids)
     }
 
+    /// 按合同号查询关联材料（主数据 + 状态）
+    async fn find_materials_by_contract_nos(
+        &self,
+        contract_nos: Vec<String>,
+    ) -> Result<Vec<(MaterialMaster, MaterialState)>, Box<dyn Error>> {
+        if contract_nos.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // SQLite 变量上限通常为 999，分块查询
+        let mut material_ids = Vec::new();
+        {
+            let conn = self.conn.lock().map_err(|e| format!("锁获取失败: {}", e))?;
+            for chunk in contract_nos.chunks(500) {
+                let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+                let query = format!(
+                    "SELECT material_id FROM material_master WHERE contract_no IN ({}) ORDER BY material_id",
+                    placeholders
+                );
+                let mut stmt = conn.prepare(&query)?;
+                let ids = stmt
+                    .query_map(rusqlite::params_from_iter(chunk.iter()), |row| {
+                        row.get::<_, String>(0)
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                material_ids.extend(ids);
+            }
+        }
+
+        let master_repo = MaterialMasterRepository::from_connection(self.conn.clone());
+        let state_repo = MaterialStateRepository::from_connection(self.conn.clone());
+
+        let mut result = Vec::with_capacity(material_ids.len());
+        for chunk in material_ids.chunks(500) {
+            for master in master_repo.find_by_ids(chunk)? {
+                if let Some(state) = state_repo.find_by_id(&master.material_id)? {
+                    result.push((master, state));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
     /// 统计 material_master 表记录数
     async fn count_materials(&self) -> Result<usize, Box<dyn Error>> {
         let conn = self.conn.lock().map_err(|e| format!("锁获取失败: {}", e))?;
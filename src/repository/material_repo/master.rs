@@ -403,6 +403,23 @@ impl MaterialMasterRepository {
         Ok(materials)
     }
 
+    /// 解除机组上全部材料的当前机组归属（current_machine_code 置空）
+    ///
+    /// # 说明
+    /// - 仅用于隔离快照库：回放历史重算前先清空机组，再写回当时的材料快照
+    /// - 不删除材料（plan_item 仍引用 material_master）
+    ///
+    /// # 返回
+    /// - Ok(usize): 受影响的材料数
+    pub fn detach_from_machine(&self, machine_code: &str) -> RepositoryResult<usize> {
+        let conn = self.get_conn()?;
+        let affected = conn.execute(
+            "UPDATE material_master SET current_machine_code = NULL WHERE current_machine_code = ?1",
+            params![machine_code],
+        )?;
+        Ok(affected)
+    }
+
     /// 查询所有材料（带分页）
     ///
     /// # 参数
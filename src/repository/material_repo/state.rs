@@ -43,6 +43,23 @@ pub struct MaterialStateSnapshotLite {
     pub seq_no: Option<i32>,
 }
 
+impl MaterialStateSnapshotLite {
+    /// 解析排产状态（未写入时为 None）
+    pub fn parsed_sched_state(&self) -> Option<SchedState> {
+        self.sched_state
+            .as_deref()
+            .map(|s| MaterialStateRepository::str_to_sched_state(s.trim()))
+    }
+
+    /// 解析紧急等级（未写入时按 L0）
+    pub fn parsed_urgent_level(&self) -> UrgentLevel {
+        self.urgent_level
+            .as_deref()
+            .map(|s| MaterialStateRepository::str_to_urgent_level(s.trim()))
+            .unwrap_or(UrgentLevel::L0)
+    }
+}
+
 /// 人工确认材料摘要（用于路径规则锚点/队列查询）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserConfirmedMaterialSummary {
@@ -110,6 +127,7 @@ impl MaterialStateRepository {
             SchedState::ForceRelease => "FORCE_RELEASE",
             SchedState::Blocked => "BLOCKED",
             SchedState::Scheduled => "SCHEDULED",
+            SchedState::Completed => "COMPLETED",
         }
     }
 
@@ -122,6 +140,7 @@ impl MaterialStateRepository {
             "FORCE_RELEASE" => SchedState::ForceRelease,
             "BLOCKED" => SchedState::Blocked,
             "SCHEDULED" => SchedState::Scheduled,
+            "COMPLETED" => SchedState::Completed,
             _ => SchedState::Blocked, // 默认值
         }
     }
@@ -754,6 +773,36 @@ impl MaterialStateRepository {
         Ok(())
     }
 
+    /// 从给定材料中筛选已人工确认（突破路径规则）的材料ID
+    ///
+    /// 说明：用于方案多目标评价的路径突破计数，内部分块查询。
+    pub fn find_user_confirmed_ids(
+        &self,
+        material_ids: &[String],
+    ) -> RepositoryResult<Vec<String>> {
+        if material_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        const CHUNK_SIZE: usize = 900;
+
+        let conn = self.get_conn()?;
+        let mut out = Vec::new();
+        for chunk in material_ids.chunks(CHUNK_SIZE) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                "SELECT material_id FROM material_state WHERE user_confirmed = 1 AND material_id IN ({})",
+                placeholders
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let params_vec: Vec<&dyn ToSql> = chunk.iter().map(|s| s as &dyn ToSql).collect();
+            let rows = stmt.query_map(params_vec.as_slice(), |row| row.get::<_, String>(0))?;
+            out.extend(rows.collect::<SqliteResult<Vec<_>>>()?);
+        }
+
+        Ok(out)
+    }
+
     /// 查询已人工确认的材料摘要（用于锚点解析/队列展示）
     pub fn list_user_confirmed_materials(
         &self,
@@ -911,6 +960,114 @@ impl MaterialStateRepository {
         Ok(())
     }
 
+    /// 按快照恢复人工确认 / 路径拒绝标记
+    ///
+    /// # 说明
+    /// - 用于隔离快照库（重算回放）与人工操作撤销/重做：batch_insert_material_state 不写这些列
+    /// - states 中未出现在 rejections 的材料，拒绝标记一律清除
+    pub fn restore_path_override_flags(
+        &self,
+        states: &[MaterialState],
+        rejections: &[PathOverrideRejectionSummary],
+    ) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        let has_reject_columns = Self::has_material_state_column(&conn, "path_override_rejected")?;
+        let tx = conn.unchecked_transaction()?;
+
+        for state in states {
+            tx.execute(
+                r#"
+                UPDATE material_state
+                SET
+                    user_confirmed = ?2,
+                    user_confirmed_at = ?3,
+                    user_confirmed_by = ?4,
+                    user_confirmed_reason = ?5
+                WHERE material_id = ?1
+                "#,
+                params![
+                    state.material_id,
+                    state.user_confirmed as i32,
+                    state.user_confirmed_at.map(|t| t.to_rfc3339()),
+                    state.user_confirmed_by,
+                    state.user_confirmed_reason,
+                ],
+            )?;
+            if has_reject_columns {
+                tx.execute(
+                    r#"
+                    UPDATE material_state
+                    SET
+                        path_override_rejected = 0,
+                        path_override_reject_cycle_no = NULL,
+                        path_override_reject_base_sched_state = NULL
+                    WHERE material_id = ?1
+                    "#,
+                    params![state.material_id],
+                )?;
+            }
+        }
+
+        if has_reject_columns {
+            for r in rejections {
+                tx.execute(
+                    r#"
+                    UPDATE material_state
+                    SET
+                        path_override_rejected = 1,
+                        path_override_reject_cycle_no = ?2,
+                        path_override_reject_base_sched_state = ?3
+                    WHERE material_id = ?1
+                    "#,
+                    params![r.material_id, r.reject_cycle_no, r.reject_base_sched_state],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 从给定材料中查询“已拒绝路径突破”的材料摘要（内部分块查询）
+    pub fn find_path_override_rejections(
+        &self,
+        material_ids: &[String],
+    ) -> RepositoryResult<Vec<PathOverrideRejectionSummary>> {
+        if material_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        const CHUNK_SIZE: usize = 900;
+
+        let conn = self.get_conn()?;
+        let has_reject_columns = Self::has_material_state_column(&conn, "path_override_rejected")?;
+        if !has_reject_columns {
+            return Ok(vec![]);
+        }
+        let mut out = Vec::new();
+        for chunk in material_ids.chunks(CHUNK_SIZE) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                "SELECT material_id, path_override_reject_cycle_no, path_override_reject_base_sched_state \
+                 FROM material_state \
+                 WHERE COALESCE(path_override_rejected, 0) = 1 AND material_id IN ({})",
+                placeholders
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let params_vec: Vec<&dyn ToSql> = chunk.iter().map(|s| s as &dyn ToSql).collect();
+            let rows = stmt.query_map(params_vec.as_slice(), |row| {
+                Ok(PathOverrideRejectionSummary {
+                    material_id: row.get(0)?,
+                    reject_cycle_no: row.get(1)?,
+                    reject_base_sched_state: row.get(2)?,
+                })
+            })?;
+            out.extend(rows.collect::<SqliteResult<Vec<_>>>()?);
+        }
+
+        Ok(out)
+    }
+
     /// 查询机组下“已拒绝路径突破”的材料摘要
     pub fn list_path_override_rejections_by_machine(
         &self,
@@ -12,6 +12,7 @@ pub mod action_log_repo;
 pub mod capacity_repo;
 pub mod decision_refresh_repo;
 pub mod error;
+pub mod intervention_expiry_repo;
 pub mod machine_config_repo;
 pub mod material_import_repo;
 pub mod material_import_repo_impl;
@@ -19,10 +20,14 @@ pub mod material_repo;
 pub mod path_override_pending_repo;
 pub mod plan_repo;
 pub mod plan_rhythm_repo;
+pub mod production_actual_repo;
 pub mod risk_repo;
 pub mod roll_campaign_plan_repo;
 pub mod roller_repo;
+pub mod scenario_repo;
 pub mod strategy_draft_repo;
+pub mod version_retention_repo;
+pub mod version_review_repo;
 
 // 重导出核心仓储
 pub use action_log_repo::ActionLogRepository;
@@ -32,6 +37,7 @@ pub use decision_refresh_repo::{
     DecisionRefreshTaskEntity,
 };
 pub use error::{RepositoryError, RepositoryResult};
+pub use intervention_expiry_repo::InterventionExpiryRepository;
 pub use machine_config_repo::{MachineConfigEntity, MachineConfigRepository};
 pub use material_import_repo::MaterialImportRepository;
 pub use material_import_repo_impl::MaterialImportRepositoryImpl;
@@ -41,10 +47,14 @@ pub use path_override_pending_repo::{
 };
 pub use plan_repo::{PlanItemRepository, PlanRepository, PlanVersionRepository};
 pub use plan_rhythm_repo::{PlanRhythmPresetEntity, PlanRhythmRepository, PlanRhythmTargetEntity};
+pub use production_actual_repo::{CampaignTonnageUpdate, ProductionActualRepository};
 pub use risk_repo::RiskSnapshotRepository;
 pub use roll_campaign_plan_repo::{RollCampaignPlanEntity, RollCampaignPlanRepository};
 pub use roller_repo::RollerCampaignRepository;
+pub use scenario_repo::ScenarioRepository;
 pub use strategy_draft_repo::{StrategyDraftEntity, StrategyDraftRepository, StrategyDraftStatus};
+pub use version_retention_repo::VersionRetentionRepository;
+pub use version_review_repo::VersionReviewRepository;
 
 // TODO: 添加数据库连接池管理模块
 // TODO: 添加事务管理模块
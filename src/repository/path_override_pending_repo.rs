@@ -108,6 +108,23 @@ impl PathOverridePendingRepository {
         Ok(changed)
     }
 
+    /// 在调用方事务内删除指定机组下若干材料的待确认记录（日内顺序调整后按新锚点重新生成；调用方需先 ensure_schema）
+    pub(crate) fn delete_materials(
+        conn: &Connection,
+        version_id: &str,
+        machine_code: &str,
+        material_ids: &[String],
+    ) -> RepositoryResult<usize> {
+        let mut deleted = 0usize;
+        for material_id in material_ids {
+            deleted += conn.execute(
+                "DELETE FROM path_override_pending WHERE version_id = ?1 AND machine_code = ?2 AND material_id = ?3",
+                params![version_id, machine_code, material_id],
+            )?;
+        }
+        Ok(deleted)
+    }
+
     pub fn insert_ignore_many(
         &self,
         records: &[PathOverridePendingRecord],
@@ -118,10 +135,19 @@ impl PathOverridePendingRepository {
         self.ensure_schema()?;
         let mut conn = self.get_conn()?;
         let tx = conn.transaction()?;
+        let inserted = Self::insert_records(&tx, records)?;
+        tx.commit()?;
+        Ok(inserted)
+    }
 
+    /// 在调用方事务内写入待确认记录（已存在则忽略；调用方需先 ensure_schema）
+    pub(crate) fn insert_records(
+        conn: &Connection,
+        records: &[PathOverridePendingRecord],
+    ) -> RepositoryResult<usize> {
         let mut inserted = 0usize;
         for r in records {
-            let changed = tx.execute(
+            let changed = conn.execute(
                 r#"
                 INSERT OR IGNORE INTO path_override_pending (
                   version_id, machine_code, plan_date, material_id,
@@ -149,8 +175,6 @@ impl PathOverridePendingRepository {
             )?;
             inserted += changed;
         }
-
-        tx.commit()?;
         Ok(inserted)
     }
 
@@ -401,4 +425,66 @@ impl PathOverridePendingRepository {
             thickness_delta_mm: row.get(11)?,
         }))
     }
+
+    /// 查询版本内全部未确认（且未拒绝）的路径违规待办
+    ///
+    /// 用途：激活前红线校验（路径违规必须人工确认后才能上线）
+    pub fn list_unconfirmed_by_version(
+        &self,
+        version_id: &str,
+    ) -> RepositoryResult<Vec<PathOverridePendingRecord>> {
+        self.ensure_schema()?;
+        let conn = self.get_conn()?;
+        let has_reject_column = Self::has_material_state_reject_column(&conn)?;
+
+        let mut sql = String::from(
+            r#"
+            SELECT
+              p.version_id, p.machine_code, p.plan_date, p.material_id,
+              p.violation_type, p.urgent_level,
+              p.width_mm, p.thickness_mm,
+              p.anchor_width_mm, p.anchor_thickness_mm,
+              p.width_delta_mm, p.thickness_delta_mm
+            FROM path_override_pending p
+            JOIN material_state s ON p.material_id = s.material_id
+            WHERE p.version_id = ?1
+              AND s.user_confirmed = 0
+            "#,
+        );
+        if has_reject_column {
+            sql.push_str("\n              AND COALESCE(s.path_override_rejected, 0) = 0\n");
+        }
+        sql.push_str(" ORDER BY p.machine_code ASC, p.plan_date ASC, p.material_id ASC");
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt
+            .query_map(params![version_id], |row| {
+                let plan_date_str: String = row.get(2)?;
+                let plan_date =
+                    NaiveDate::parse_from_str(&plan_date_str, "%Y-%m-%d").map_err(|e| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            2,
+                            rusqlite::types::Type::Text,
+                            Box::new(e),
+                        )
+                    })?;
+                Ok(PathOverridePendingRecord {
+                    version_id: row.get(0)?,
+                    machine_code: row.get(1)?,
+                    plan_date,
+                    material_id: row.get(3)?,
+                    violation_type: row.get(4)?,
+                    urgent_level: row.get(5)?,
+                    width_mm: row.get(6)?,
+                    thickness_mm: row.get(7)?,
+                    anchor_width_mm: row.get(8)?,
+                    anchor_thickness_mm: row.get(9)?,
+                    width_delta_mm: row.get(10)?,
+                    thickness_delta_mm: row.get(11)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
 }
@@ -10,6 +10,9 @@ mod item;
 mod plan;
 mod version;
 
-pub use item::{PlanItemDiffCounts, PlanItemRepository, PlanItemVersionAgg};
+pub use item::{
+    PlanItemDiffCounts, PlanItemRepository, PlanItemVersionAgg, PlanItemVersionDiffRow,
+    PlanItemVersionRouteRollCounts,
+};
 pub use plan::PlanRepository;
 pub use version::PlanVersionRepository;
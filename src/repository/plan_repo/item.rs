@@ -1,10 +1,17 @@
+use crate::domain::action_log::ActionLog;
 use crate::domain::plan::PlanItem;
+use crate::repository::action_log_repo::ActionLogRepository;
 use crate::repository::error::{RepositoryError, RepositoryResult};
+use crate::repository::path_override_pending_repo::{
+    PathOverridePendingRecord, PathOverridePendingRepository,
+};
 use chrono::NaiveDate;
 use rusqlite::types::Value;
 use rusqlite::{params, params_from_iter, Connection};
 use std::sync::{Arc, Mutex};
 
+mod batching_deferral;
+
 // ==========================================
 // PlanItemRepository - 排产明细仓储
 // ==========================================
@@ -33,10 +40,63 @@ pub struct PlanItemDiffCounts {
     pub squeezed_out_count: usize,
 }
 
+/// 两版本间单个材料的差异行（物料级 diff 分页）
+#[derive(Debug, Clone)]
+pub struct PlanItemVersionDiffRow {
+    pub material_id: String,
+    /// ADDED / REMOVED / MOVED / SEQ_CHANGED
+    pub change_type: String,
+    pub machine_code_a: Option<String>,
+    pub plan_date_a: Option<NaiveDate>,
+    pub seq_no_a: Option<i32>,
+    pub machine_code_b: Option<String>,
+    pub plan_date_b: Option<NaiveDate>,
+    pub seq_no_b: Option<i32>,
+    pub weight_t: f64,
+}
+
+/// 版本的路径突破/换辊统计（来自 path_override_pending、material_state、roller_campaign）
+#[derive(Debug, Clone, Default)]
+pub struct PlanItemVersionRouteRollCounts {
+    /// 待确认的路径突破数（path_override_pending）
+    pub path_override_pending_count: usize,
+    /// 各机组换辊次数（换辊周期数 - 1）
+    pub roll_change_by_machine: Vec<(String, usize)>,
+}
+
 impl PlanItemRepository {
     /// 创建新的PlanItemRepository实例
     pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
-        Self { conn }
+        let repo = Self { conn };
+        if let Err(e) = repo.ensure_assign_reason_column() {
+            tracing::warn!("plan_item.assign_reason 列检查失败: {}", e);
+        }
+        repo
+    }
+
+    /// 兼容旧库: plan_item 缺少 assign_reason 列时补列（可空，旧明细保持为空）
+    fn ensure_assign_reason_column(&self) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        let has_table: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'plan_item'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_table == 0 {
+            return Ok(());
+        }
+
+        let has_col: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('plan_item') WHERE name = 'assign_reason'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_col > 0 {
+            return Ok(());
+        }
+
+        conn.execute_batch("ALTER TABLE plan_item ADD COLUMN assign_reason TEXT;")?;
+        Ok(())
     }
 
     /// 获取数据库连接
@@ -65,33 +125,37 @@ impl PlanItemRepository {
 
         let mut conn = self.get_conn()?;
         let tx = conn.transaction()?;
+        let count = Self::insert_items(&tx, items)?;
+        tx.commit()?;
+        Ok(count)
+    }
 
-        {
-            let mut stmt = tx.prepare(
-                r#"INSERT INTO plan_item (
-                        version_id, material_id, machine_code, plan_date, seq_no,
-                        weight_t, source_type, locked_in_plan, force_release_in_plan,
-                        violation_flags
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
-            )?;
+    /// 在调用方事务内批量插入明细（供跨表原子写入复用）
+    pub(crate) fn insert_items(conn: &Connection, items: &[PlanItem]) -> RepositoryResult<usize> {
+        let mut stmt = conn.prepare(
+            r#"INSERT INTO plan_item (
+                    version_id, material_id, machine_code, plan_date, seq_no,
+                    weight_t, source_type, locked_in_plan, force_release_in_plan,
+                    violation_flags, assign_reason
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        )?;
 
-            for item in items {
-                stmt.execute(params![
-                    &item.version_id,
-                    &item.material_id,
-                    &item.machine_code,
-                    &item.plan_date.format("%Y-%m-%d").to_string(),
-                    &item.seq_no,
-                    &item.weight_t,
-                    &item.source_type,
-                    if item.locked_in_plan { 1 } else { 0 },
-                    if item.force_release_in_plan { 1 } else { 0 },
-                    &item.violation_flags,
-                ])?;
-            }
+        for item in items {
+            stmt.execute(params![
+                &item.version_id,
+                &item.material_id,
+                &item.machine_code,
+                &item.plan_date.format("%Y-%m-%d").to_string(),
+                &item.seq_no,
+                &item.weight_t,
+                &item.source_type,
+                if item.locked_in_plan { 1 } else { 0 },
+                if item.force_release_in_plan { 1 } else { 0 },
+                &item.violation_flags,
+                &item.assign_reason,
+            ])?;
         }
 
-        tx.commit()?;
         Ok(items.len())
     }
 
@@ -103,36 +167,71 @@ impl PlanItemRepository {
 
         let mut conn = self.get_conn()?;
         let tx = conn.transaction()?;
+        let count = Self::upsert_items(&tx, items)?;
+        tx.commit()?;
+        Ok(count)
+    }
 
-        {
-            let mut stmt = tx.prepare(
-                r#"INSERT OR REPLACE INTO plan_item (
-                        version_id, material_id, machine_code, plan_date, seq_no,
-                        weight_t, source_type, locked_in_plan, force_release_in_plan,
-                        violation_flags
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
-            )?;
+    /// 在调用方事务内批量插入或更新明细（供跨表原子写入复用）
+    pub(crate) fn upsert_items(conn: &Connection, items: &[PlanItem]) -> RepositoryResult<usize> {
+        let mut stmt = conn.prepare(
+            r#"INSERT OR REPLACE INTO plan_item (
+                    version_id, material_id, machine_code, plan_date, seq_no,
+                    weight_t, source_type, locked_in_plan, force_release_in_plan,
+                    violation_flags, assign_reason
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        )?;
 
-            for item in items {
-                stmt.execute(params![
-                    &item.version_id,
-                    &item.material_id,
-                    &item.machine_code,
-                    &item.plan_date.format("%Y-%m-%d").to_string(),
-                    &item.seq_no,
-                    &item.weight_t,
-                    &item.source_type,
-                    if item.locked_in_plan { 1 } else { 0 },
-                    if item.force_release_in_plan { 1 } else { 0 },
-                    &item.violation_flags,
-                ])?;
-            }
+        for item in items {
+            stmt.execute(params![
+                &item.version_id,
+                &item.material_id,
+                &item.machine_code,
+                &item.plan_date.format("%Y-%m-%d").to_string(),
+                &item.seq_no,
+                &item.weight_t,
+                &item.source_type,
+                if item.locked_in_plan { 1 } else { 0 },
+                if item.force_release_in_plan { 1 } else { 0 },
+                &item.violation_flags,
+                &item.assign_reason,
+            ])?;
         }
 
-        tx.commit()?;
         Ok(items.len())
     }
 
+    /// 日内顺序调整落库：明细 seq_no、路径待确认刷新与操作日志同一事务写入
+    ///
+    /// # 参数
+    /// - items: 重写 seq_no 后的当日明细
+    /// - refresh_material_ids: 需按新锚点重新生成待确认的材料
+    /// - pending_records: 新生成的待确认记录
+    /// - log: LocalAdjust 日志
+    pub fn apply_resequence(
+        &self,
+        version_id: &str,
+        machine_code: &str,
+        items: &[PlanItem],
+        refresh_material_ids: &[String],
+        pending_records: &[PathOverridePendingRecord],
+        log: &ActionLog,
+    ) -> RepositoryResult<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        Self::upsert_items(&tx, items)?;
+        PathOverridePendingRepository::delete_materials(
+            &tx,
+            version_id,
+            machine_code,
+            refresh_material_ids,
+        )?;
+        PathOverridePendingRepository::insert_records(&tx, pending_records)?;
+        ActionLogRepository::insert_log(&tx, log)?;
+        tx.commit()?;
+        Ok(())
+    }
+
     /// 查询版本的所有明细
     pub fn find_by_version(&self, version_id: &str) -> RepositoryResult<Vec<PlanItem>> {
         let conn = self.get_conn()?;
@@ -140,7 +239,7 @@ impl PlanItemRepository {
         let mut stmt = conn.prepare(
             r#"SELECT version_id, material_id, machine_code, plan_date, seq_no,
                       weight_t, source_type, locked_in_plan, force_release_in_plan,
-                      violation_flags
+                      violation_flags, assign_reason
                FROM plan_item
                WHERE version_id = ?
                ORDER BY machine_code, plan_date, seq_no"#,
@@ -172,7 +271,7 @@ impl PlanItemRepository {
         let mut sql = String::from(
             r#"SELECT version_id, material_id, machine_code, plan_date, seq_no,
                       weight_t, source_type, locked_in_plan, force_release_in_plan,
-                      violation_flags
+                      violation_flags, assign_reason
                FROM plan_item
                WHERE version_id = ?1"#,
         );
@@ -505,6 +604,194 @@ impl PlanItemRepository {
         })
     }
 
+    /// 两版本物料级 diff 的公共 CTE（?1=版本A, ?2=版本B）
+    const VERSIONS_DIFF_CTE: &'static str = r#"
+        WITH d AS (
+            SELECT a.material_id,
+                   a.machine_code AS machine_code_a, a.plan_date AS plan_date_a, a.seq_no AS seq_no_a,
+                   b.machine_code AS machine_code_b, b.plan_date AS plan_date_b, b.seq_no AS seq_no_b,
+                   COALESCE(b.weight_t, a.weight_t) AS weight_t
+            FROM plan_item a
+            LEFT JOIN plan_item b ON b.version_id = ?2 AND b.material_id = a.material_id
+            WHERE a.version_id = ?1
+            UNION ALL
+            SELECT b.material_id,
+                   NULL, NULL, NULL,
+                   b.machine_code, b.plan_date, b.seq_no,
+                   b.weight_t
+            FROM plan_item b
+            WHERE b.version_id = ?2
+              AND NOT EXISTS (
+                SELECT 1 FROM plan_item a
+                WHERE a.version_id = ?1 AND a.material_id = b.material_id
+              )
+        ),
+        c AS (
+            SELECT d.*,
+                   CASE
+                     WHEN machine_code_b IS NULL THEN 'REMOVED'
+                     WHEN machine_code_a IS NULL THEN 'ADDED'
+                     WHEN machine_code_a <> machine_code_b OR plan_date_a <> plan_date_b THEN 'MOVED'
+                     WHEN seq_no_a <> seq_no_b THEN 'SEQ_CHANGED'
+                     ELSE 'UNCHANGED'
+                   END AS change_type
+            FROM d
+        )
+    "#;
+
+    /// 两版本物料级 diff 的过滤条件（?3=变更类型, ?4=机组）
+    const VERSIONS_DIFF_FILTER: &'static str = r#"
+        WHERE change_type <> 'UNCHANGED'
+          AND (?3 IS NULL OR change_type = ?3)
+          AND (?4 IS NULL OR machine_code_a = ?4 OR machine_code_b = ?4)
+    "#;
+
+    /// 分页查询两版本物料级 diff（SQL 侧过滤/分页，避免前端拉取全量明细）
+    ///
+    /// 排序：按新位置（缺失时取原位置）的 机组/日期/序号
+    pub fn find_versions_diff_paged(
+        &self,
+        version_id_a: &str,
+        version_id_b: &str,
+        change_type: Option<&str>,
+        machine_code: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> RepositoryResult<Vec<PlanItemVersionDiffRow>> {
+        let conn = self.get_conn()?;
+        let sql = format!(
+            r#"{}
+            SELECT material_id, change_type,
+                   machine_code_a, plan_date_a, seq_no_a,
+                   machine_code_b, plan_date_b, seq_no_b,
+                   weight_t
+            FROM c
+            {}
+            ORDER BY COALESCE(machine_code_b, machine_code_a),
+                     COALESCE(plan_date_b, plan_date_a),
+                     COALESCE(seq_no_b, seq_no_a),
+                     material_id
+            LIMIT ?5 OFFSET ?6"#,
+            Self::VERSIONS_DIFF_CTE,
+            Self::VERSIONS_DIFF_FILTER
+        );
+
+        let parse_date = |idx: usize, v: Option<String>| -> rusqlite::Result<Option<NaiveDate>> {
+            v.map(|s| {
+                NaiveDate::parse_from_str(&s, "%Y-%m-%d").map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        idx,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })
+            })
+            .transpose()
+        };
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(
+                params![
+                    version_id_a,
+                    version_id_b,
+                    change_type,
+                    machine_code,
+                    limit,
+                    offset
+                ],
+                |row| {
+                    Ok(PlanItemVersionDiffRow {
+                        material_id: row.get(0)?,
+                        change_type: row.get(1)?,
+                        machine_code_a: row.get(2)?,
+                        plan_date_a: parse_date(3, row.get(3)?)?,
+                        seq_no_a: row.get(4)?,
+                        machine_code_b: row.get(5)?,
+                        plan_date_b: parse_date(6, row.get(6)?)?,
+                        seq_no_b: row.get(7)?,
+                        weight_t: row.get(8)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// 统计两版本物料级 diff 数量（过滤口径同 `find_versions_diff_paged`）
+    pub fn count_versions_diff(
+        &self,
+        version_id_a: &str,
+        version_id_b: &str,
+        change_type: Option<&str>,
+        machine_code: Option<&str>,
+    ) -> RepositoryResult<i64> {
+        let conn = self.get_conn()?;
+        let sql = format!(
+            "{} SELECT COUNT(*) FROM c {}",
+            Self::VERSIONS_DIFF_CTE,
+            Self::VERSIONS_DIFF_FILTER
+        );
+        let count: i64 = conn.query_row(
+            &sql,
+            params![version_id_a, version_id_b, change_type, machine_code],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// 获取版本的路径突破/换辊统计
+    ///
+    /// 说明：旧库可能缺少 path_override_pending / roller_campaign 表，缺表时按 0 计
+    pub fn get_version_route_roll_counts(
+        &self,
+        version_id: &str,
+    ) -> RepositoryResult<PlanItemVersionRouteRollCounts> {
+        let conn = self.get_conn()?;
+        let has_table = |name: &str| -> rusqlite::Result<bool> {
+            conn.query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                params![name],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|n| n > 0)
+        };
+
+        let mut counts = PlanItemVersionRouteRollCounts::default();
+
+        if has_table("path_override_pending")? {
+            let n: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM path_override_pending WHERE version_id = ?1",
+                params![version_id],
+                |row| row.get(0),
+            )?;
+            counts.path_override_pending_count = n as usize;
+        }
+
+        if has_table("roller_campaign")? {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT machine_code, COUNT(*) - 1
+                FROM roller_campaign
+                WHERE version_id = ?1
+                GROUP BY machine_code
+                ORDER BY machine_code
+                "#,
+            )?;
+            counts.roll_change_by_machine = stmt
+                .query_map(params![version_id], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, i64>(1)?.max(0) as usize,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+
+        Ok(counts)
+    }
+
     /// 查询指定日期的明细
     pub fn find_by_date(
         &self,
@@ -516,7 +803,7 @@ impl PlanItemRepository {
         let mut stmt = conn.prepare(
             r#"SELECT version_id, material_id, machine_code, plan_date, seq_no,
                       weight_t, source_type, locked_in_plan, force_release_in_plan,
-                      violation_flags
+                      violation_flags, assign_reason
                FROM plan_item
                WHERE version_id = ? AND plan_date = ?
                ORDER BY machine_code, seq_no"#,
@@ -544,7 +831,7 @@ impl PlanItemRepository {
         let mut stmt = conn.prepare(
             r#"SELECT version_id, material_id, machine_code, plan_date, seq_no,
                       weight_t, source_type, locked_in_plan, force_release_in_plan,
-                      violation_flags
+                      violation_flags, assign_reason
                FROM plan_item
                WHERE version_id = ? AND plan_date BETWEEN ? AND ?
                ORDER BY plan_date, machine_code, seq_no"#,
@@ -575,7 +862,7 @@ impl PlanItemRepository {
         let mut stmt = conn.prepare(
             r#"SELECT version_id, material_id, machine_code, plan_date, seq_no,
                       weight_t, source_type, locked_in_plan, force_release_in_plan,
-                      violation_flags
+                      violation_flags, assign_reason
                FROM plan_item
                WHERE version_id = ? AND machine_code = ?
                ORDER BY plan_date, seq_no"#,
@@ -595,7 +882,7 @@ impl PlanItemRepository {
         let mut stmt = conn.prepare(
             r#"SELECT version_id, material_id, machine_code, plan_date, seq_no,
                       weight_t, source_type, locked_in_plan, force_release_in_plan,
-                      violation_flags
+                      violation_flags, assign_reason
                FROM plan_item
                WHERE version_id = ? AND locked_in_plan = 1
                ORDER BY plan_date, machine_code, seq_no"#,
@@ -615,7 +902,7 @@ impl PlanItemRepository {
         let mut stmt = conn.prepare(
             r#"SELECT pi.version_id, pi.material_id, pi.machine_code, pi.plan_date, pi.seq_no,
                       pi.weight_t, pi.source_type, pi.locked_in_plan, pi.force_release_in_plan,
-                      pi.violation_flags
+                      pi.violation_flags, pi.assign_reason
                FROM plan_item pi
                INNER JOIN plan_version pv ON pi.version_id = pv.version_id
                WHERE pi.material_id = ?
@@ -687,10 +974,10 @@ impl PlanItemRepository {
             locked_in_plan: row.get::<_, i32>(7)? == 1,
             force_release_in_plan: row.get::<_, i32>(8)? == 1,
             violation_flags: row.get(9)?,
+            assign_reason: row.get(10)?,
             // 快照字段 (不存储在schema中，由API层从material_state/material_master动态补充)
             urgent_level: None,
             sched_state: None,
-            assign_reason: None,
             steel_grade: None,
             width_mm: None,
             thickness_mm: None,
@@ -0,0 +1,106 @@
+// ==========================================
+// 钢种族批量顺延记录
+// ==========================================
+// 职责:
+// - grade_batching_deferral: 重算结束时仍因最小批量规则未排入的材料（版本 × 材料）
+// 说明:
+// - 表由本模块按需创建（CREATE TABLE IF NOT EXISTS），兼容旧库
+// - D2 刷新时按合同汇总为失败原因/阻塞因素（见 decision::services::refresh_service::d2）
+// ==========================================
+
+use super::PlanItemRepository;
+use crate::domain::batching::BatchingDeferral;
+use crate::repository::error::RepositoryResult;
+use chrono::NaiveDate;
+use rusqlite::types::Type;
+use rusqlite::{params, Connection};
+
+const DATE_FMT: &str = "%Y-%m-%d";
+
+impl PlanItemRepository {
+    fn ensure_batching_deferral_schema(conn: &Connection) -> RepositoryResult<()> {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS grade_batching_deferral (
+              version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
+              material_id TEXT NOT NULL,
+              machine_code TEXT NOT NULL,
+              plan_date TEXT NOT NULL,
+              grade_family TEXT NOT NULL,
+              reason TEXT NOT NULL,
+              PRIMARY KEY (version_id, material_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_grade_batching_deferral_family
+              ON grade_batching_deferral(version_id, grade_family);
+            "#,
+        )?;
+        Ok(())
+    }
+
+    /// 覆盖写入版本的批量顺延记录（按机组替换）
+    pub fn replace_batching_deferrals(
+        &self,
+        version_id: &str,
+        machine_codes: &[String],
+        deferrals: &[BatchingDeferral],
+    ) -> RepositoryResult<usize> {
+        let mut conn = self.get_conn()?;
+        Self::ensure_batching_deferral_schema(&conn)?;
+        let tx = conn.transaction()?;
+        for machine_code in machine_codes {
+            tx.execute(
+                "DELETE FROM grade_batching_deferral WHERE version_id = ?1 AND machine_code = ?2",
+                params![version_id, machine_code],
+            )?;
+        }
+        for d in deferrals {
+            tx.execute(
+                r#"INSERT OR REPLACE INTO grade_batching_deferral (
+                     version_id, material_id, machine_code, plan_date, grade_family, reason
+                   ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+                params![
+                    d.version_id,
+                    d.material_id,
+                    d.machine_code,
+                    d.plan_date.format(DATE_FMT).to_string(),
+                    d.grade_family,
+                    d.reason,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(deferrals.len())
+    }
+
+    /// 查询版本的批量顺延记录（按钢种族、机组、材料排序）
+    pub fn find_batching_deferrals_by_version(
+        &self,
+        version_id: &str,
+    ) -> RepositoryResult<Vec<BatchingDeferral>> {
+        let conn = self.get_conn()?;
+        Self::ensure_batching_deferral_schema(&conn)?;
+        let mut stmt = conn.prepare(
+            r#"SELECT version_id, material_id, machine_code, plan_date, grade_family, reason
+               FROM grade_batching_deferral
+               WHERE version_id = ?1
+               ORDER BY grade_family, machine_code, material_id"#,
+        )?;
+        let rows = stmt
+            .query_map(params![version_id], |row| {
+                let plan_date: String = row.get(3)?;
+                Ok(BatchingDeferral {
+                    version_id: row.get(0)?,
+                    material_id: row.get(1)?,
+                    machine_code: row.get(2)?,
+                    plan_date: NaiveDate::parse_from_str(&plan_date, DATE_FMT).map_err(|e| {
+                        rusqlite::Error::FromSqlConversionFailure(3, Type::Text, Box::new(e))
+                    })?,
+                    grade_family: row.get(4)?,
+                    reason: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+}
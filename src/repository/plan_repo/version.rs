@@ -1,8 +1,13 @@
-use crate::domain::plan::PlanVersion;
+use crate::domain::action_log::ActionLog;
+use crate::domain::capacity::CapacityPool;
+use crate::domain::plan::{PlanItem, PlanVersion};
 use crate::domain::types::PlanVersionStatus;
+use crate::repository::action_log_repo::ActionLogRepository;
+use crate::repository::capacity_repo::CapacityPoolRepository;
 use crate::repository::error::{RepositoryError, RepositoryResult};
+use crate::repository::plan_repo::PlanItemRepository;
 use chrono::{NaiveDate, NaiveDateTime};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use std::sync::{Arc, Mutex};
 
 // ==========================================
@@ -65,8 +70,43 @@ impl PlanVersionRepository {
     ) -> RepositoryResult<String> {
         let mut conn = self.get_conn()?;
         let tx = conn.transaction()?;
+        Self::insert_with_next_version_no(&tx, version)?;
+        tx.commit()?;
+        Ok(version.version_id.clone())
+    }
+
+    /// 创建派生版本并在同一事务内写入明细、产能池与操作日志
+    ///
+    /// 说明：
+    /// - 任一步失败整体回滚，不留下缺明细/缺日志的孤儿草稿版本；
+    /// - 日志在 version_no 分配后生成（CREATE_VERSION 日志需要版本号）。
+    pub fn create_with_contents(
+        &self,
+        version: &mut PlanVersion,
+        items: &[PlanItem],
+        pools: &[CapacityPool],
+        build_logs: impl FnOnce(&PlanVersion) -> Vec<ActionLog>,
+    ) -> RepositoryResult<String> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
 
-        let max_version_no: Option<i32> = tx.query_row(
+        Self::insert_with_next_version_no(&tx, version)?;
+        PlanItemRepository::insert_items(&tx, items)?;
+        CapacityPoolRepository::upsert_pools(&tx, pools)?;
+        for log in build_logs(version) {
+            ActionLogRepository::insert_log(&tx, &log)?;
+        }
+
+        tx.commit()?;
+        Ok(version.version_id.clone())
+    }
+
+    /// 在调用方事务内分配 version_no 并写入版本
+    fn insert_with_next_version_no(
+        conn: &Connection,
+        version: &mut PlanVersion,
+    ) -> RepositoryResult<()> {
+        let max_version_no: Option<i32> = conn.query_row(
             "SELECT MAX(version_no) FROM plan_version WHERE plan_id = ?",
             params![&version.plan_id],
             |row| row.get(0),
@@ -74,7 +114,7 @@ impl PlanVersionRepository {
 
         version.version_no = max_version_no.unwrap_or(0) + 1;
 
-        tx.execute(
+        conn.execute(
             r#"INSERT INTO plan_version (
                 version_id, plan_id, version_no, status,
                 frozen_from_date, recalc_window_days, config_snapshot_json,
@@ -96,8 +136,7 @@ impl PlanVersionRepository {
             ],
         )?;
 
-        tx.commit()?;
-        Ok(version.version_id.clone())
+        Ok(())
     }
 
     /// 按version_id查询版本
@@ -297,6 +336,73 @@ impl PlanVersionRepository {
         Ok(max_version_no.unwrap_or(0) + 1)
     }
 
+    // ==========================================
+    // 重算全量输入快照（用于确定性回放）
+    // ==========================================
+
+    fn ensure_input_snapshot_table(conn: &Connection) -> RepositoryResult<()> {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS plan_version_input_snapshot (
+              version_id TEXT PRIMARY KEY REFERENCES plan_version(version_id) ON DELETE CASCADE,
+              combined_hash TEXT NOT NULL,
+              inputs_json TEXT NOT NULL,
+              created_at TEXT NOT NULL
+            );
+            "#,
+        )?;
+        Ok(())
+    }
+
+    /// 保存重算全量输入（同一版本覆盖写入）
+    pub fn save_input_snapshot(
+        &self,
+        version_id: &str,
+        combined_hash: &str,
+        inputs_json: &str,
+    ) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        Self::ensure_input_snapshot_table(&conn)?;
+        conn.execute(
+            r#"INSERT INTO plan_version_input_snapshot (version_id, combined_hash, inputs_json, created_at)
+               VALUES (?1, ?2, ?3, ?4)
+               ON CONFLICT(version_id) DO UPDATE SET
+                 combined_hash = excluded.combined_hash,
+                 inputs_json = excluded.inputs_json,
+                 created_at = excluded.created_at"#,
+            params![
+                version_id,
+                combined_hash,
+                inputs_json,
+                chrono::Utc::now()
+                    .naive_utc()
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 查询重算全量输入
+    ///
+    /// # 返回
+    /// - Some((combined_hash, inputs_json))
+    pub fn find_input_snapshot(
+        &self,
+        version_id: &str,
+    ) -> RepositoryResult<Option<(String, String)>> {
+        let conn = self.get_conn()?;
+        Self::ensure_input_snapshot_table(&conn)?;
+        let row = conn
+            .query_row(
+                "SELECT combined_hash, inputs_json FROM plan_version_input_snapshot WHERE version_id = ?1",
+                params![version_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(row)
+    }
+
     /// 映射数据库行到PlanVersion对象
     fn map_row(&self, row: &rusqlite::Row) -> rusqlite::Result<PlanVersion> {
         let status_str: String = row.get(3)?;
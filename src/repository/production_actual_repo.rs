@@ -0,0 +1,457 @@
+// ==========================================
+// 热轧精整排产系统 - 生产实绩仓储
+// ==========================================
+// 职责:
+// - 管理 production_actual / plan_adherence_daily / plan_carry_over
+// - 实绩落库 + 材料完工状态 + 换辊累计吨位 在同一事务内完成
+// 说明:
+// - 换辊状态由 API 层经 RollCampaignEngine 计算后传入，仓储只负责写入
+// ==========================================
+
+use crate::db::open_sqlite_connection;
+use crate::domain::production::{CarryOverItem, PlanAdherenceRecord, ProductionActual};
+use crate::domain::types::RollStatus;
+use crate::repository::error::{RepositoryError, RepositoryResult};
+use chrono::{NaiveDate, NaiveDateTime};
+use rusqlite::{params, params_from_iter, Connection};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+const DATETIME_FMT: &str = "%Y-%m-%d %H:%M:%S";
+const DATE_FMT: &str = "%Y-%m-%d";
+
+/// 单次 IN 查询的参数上限（SQLite 默认 999）
+const IN_CHUNK_SIZE: usize = 500;
+
+/// 换辊累计吨位更新（由实绩吨位推进）
+#[derive(Debug, Clone)]
+pub struct CampaignTonnageUpdate {
+    pub version_id: String,
+    pub machine_code: String,
+    pub campaign_no: i32,
+    pub cum_weight_t: f64,
+    pub status: RollStatus,
+}
+
+pub struct ProductionActualRepository {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl ProductionActualRepository {
+    pub fn new(db_path: &str) -> RepositoryResult<Self> {
+        let conn = open_sqlite_connection(db_path)?;
+        let repo = Self {
+            conn: Arc::new(Mutex::new(conn)),
+        };
+        repo.ensure_tables()?;
+        Ok(repo)
+    }
+
+    pub fn from_connection(conn: Arc<Mutex<Connection>>) -> RepositoryResult<Self> {
+        let repo = Self { conn };
+        repo.ensure_tables()?;
+        Ok(repo)
+    }
+
+    fn get_conn(&self) -> RepositoryResult<std::sync::MutexGuard<Connection>> {
+        self.conn
+            .lock()
+            .map_err(|e| RepositoryError::LockError(e.to_string()))
+    }
+
+    fn ensure_tables(&self) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS production_actual (
+              material_id TEXT PRIMARY KEY,
+              batch_id TEXT NOT NULL,
+              version_id TEXT,
+              machine_code TEXT NOT NULL,
+              completed_at TEXT NOT NULL,
+              actual_weight_t REAL NOT NULL,
+              created_by TEXT,
+              created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_production_actual_machine_time
+              ON production_actual(machine_code, completed_at);
+
+            CREATE TABLE IF NOT EXISTS plan_adherence_daily (
+              version_id TEXT NOT NULL,
+              machine_code TEXT NOT NULL,
+              plan_date TEXT NOT NULL,
+              planned_count INTEGER NOT NULL DEFAULT 0,
+              planned_weight_t REAL NOT NULL DEFAULT 0,
+              actual_count INTEGER NOT NULL DEFAULT 0,
+              actual_weight_t REAL NOT NULL DEFAULT 0,
+              matched_count INTEGER NOT NULL DEFAULT 0,
+              matched_weight_t REAL NOT NULL DEFAULT 0,
+              unplanned_count INTEGER NOT NULL DEFAULT 0,
+              carry_over_count INTEGER NOT NULL DEFAULT 0,
+              sequence_deviation_count INTEGER NOT NULL DEFAULT 0,
+              max_sequence_displacement INTEGER NOT NULL DEFAULT 0,
+              avg_sequence_displacement REAL NOT NULL DEFAULT 0,
+              adherence_rate REAL,
+              batch_id TEXT,
+              generated_at TEXT NOT NULL,
+              PRIMARY KEY (version_id, machine_code, plan_date)
+            );
+
+            CREATE TABLE IF NOT EXISTS plan_carry_over (
+              version_id TEXT NOT NULL,
+              material_id TEXT NOT NULL,
+              machine_code TEXT NOT NULL,
+              plan_date TEXT NOT NULL,
+              seq_no INTEGER NOT NULL,
+              weight_t REAL NOT NULL,
+              detected_at TEXT NOT NULL,
+              resolved_at TEXT,
+              PRIMARY KEY (version_id, material_id)
+            );
+            "#,
+        )?;
+        Ok(())
+    }
+
+    // ==========================================
+    // 实绩写入
+    // ==========================================
+
+    /// 记录生产实绩（单事务）
+    ///
+    /// - 写入 production_actual
+    /// - material_state.sched_state → COMPLETED
+    /// - 推进换辊窗口累计吨位/状态
+    /// - 回填本批完工材料的顺延项 resolved_at
+    ///
+    /// # 返回
+    /// - 写入的实绩条数
+    pub fn record_actuals(
+        &self,
+        batch_id: &str,
+        version_id: Option<&str>,
+        actuals: &[ProductionActual],
+        campaign_updates: &[CampaignTonnageUpdate],
+        operator: &str,
+    ) -> RepositoryResult<usize> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        let now = chrono::Local::now()
+            .naive_local()
+            .format(DATETIME_FMT)
+            .to_string();
+        // material_state.updated_at 统一为 RFC3339 (UTC)
+        let state_updated_at = chrono::Utc::now().to_rfc3339();
+
+        for actual in actuals {
+            tx.execute(
+                r#"
+                INSERT INTO production_actual (
+                  material_id, batch_id, version_id, machine_code,
+                  completed_at, actual_weight_t, created_by, created_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                "#,
+                params![
+                    actual.material_id,
+                    batch_id,
+                    version_id,
+                    actual.machine_code,
+                    actual.completed_at.format(DATETIME_FMT).to_string(),
+                    actual.actual_weight_t,
+                    operator,
+                    now,
+                ],
+            )?;
+            tx.execute(
+                r#"
+                UPDATE material_state
+                SET sched_state = 'COMPLETED', updated_at = ?2, updated_by = ?3
+                WHERE material_id = ?1
+                "#,
+                params![actual.material_id, state_updated_at, operator],
+            )?;
+            tx.execute(
+                r#"
+                UPDATE plan_carry_over
+                SET resolved_at = ?2
+                WHERE material_id = ?1 AND resolved_at IS NULL
+                "#,
+                params![
+                    actual.material_id,
+                    actual.completed_at.format(DATETIME_FMT).to_string()
+                ],
+            )?;
+        }
+
+        for update in campaign_updates {
+            tx.execute(
+                r#"
+                UPDATE roller_campaign
+                SET cum_weight_t = ?4, status = ?5
+                WHERE version_id = ?1 AND machine_code = ?2 AND campaign_no = ?3
+                "#,
+                params![
+                    update.version_id,
+                    update.machine_code,
+                    update.campaign_no,
+                    update.cum_weight_t,
+                    format!("{:?}", update.status)
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(actuals.len())
+    }
+
+    /// 保存对账结果（覆盖同 版本×机组×日 的旧记录；顺延项保留最早发现时间）
+    pub fn save_reconciliation(
+        &self,
+        records: &[PlanAdherenceRecord],
+        carry_overs: &[CarryOverItem],
+    ) -> RepositoryResult<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        for r in records {
+            tx.execute(
+                r#"
+                INSERT OR REPLACE INTO plan_adherence_daily (
+                  version_id, machine_code, plan_date,
+                  planned_count, planned_weight_t, actual_count, actual_weight_t,
+                  matched_count, matched_weight_t, unplanned_count, carry_over_count,
+                  sequence_deviation_count, max_sequence_displacement, avg_sequence_displacement,
+                  adherence_rate, batch_id, generated_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+                "#,
+                params![
+                    r.version_id,
+                    r.machine_code,
+                    r.plan_date.format(DATE_FMT).to_string(),
+                    r.planned_count,
+                    r.planned_weight_t,
+                    r.actual_count,
+                    r.actual_weight_t,
+                    r.matched_count,
+                    r.matched_weight_t,
+                    r.unplanned_count,
+                    r.carry_over_count,
+                    r.sequence_deviation_count,
+                    r.max_sequence_displacement,
+                    r.avg_sequence_displacement,
+                    r.adherence_rate,
+                    r.batch_id,
+                    r.generated_at.format(DATETIME_FMT).to_string(),
+                ],
+            )?;
+        }
+
+        for c in carry_overs {
+            tx.execute(
+                r#"
+                INSERT INTO plan_carry_over (
+                  version_id, material_id, machine_code, plan_date, seq_no, weight_t,
+                  detected_at, resolved_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL)
+                ON CONFLICT(version_id, material_id) DO UPDATE SET
+                  machine_code = excluded.machine_code,
+                  plan_date = excluded.plan_date,
+                  seq_no = excluded.seq_no,
+                  weight_t = excluded.weight_t
+                "#,
+                params![
+                    c.version_id,
+                    c.material_id,
+                    c.machine_code,
+                    c.plan_date.format(DATE_FMT).to_string(),
+                    c.seq_no,
+                    c.weight_t,
+                    c.detected_at.format(DATETIME_FMT).to_string(),
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ==========================================
+    // 查询
+    // ==========================================
+
+    /// 查询已有实绩的材料号（用于重复导入校验）
+    pub fn find_completed_material_ids(
+        &self,
+        material_ids: &[String],
+    ) -> RepositoryResult<HashSet<String>> {
+        self.find_existing_ids("production_actual", material_ids)
+    }
+
+    /// 查询存在于材料主数据中的材料号
+    pub fn find_known_material_ids(
+        &self,
+        material_ids: &[String],
+    ) -> RepositoryResult<HashSet<String>> {
+        self.find_existing_ids("material_master", material_ids)
+    }
+
+    fn find_existing_ids(
+        &self,
+        table: &str,
+        material_ids: &[String],
+    ) -> RepositoryResult<HashSet<String>> {
+        let conn = self.get_conn()?;
+        let mut found = HashSet::new();
+        for chunk in material_ids.chunks(IN_CHUNK_SIZE) {
+            let placeholders = vec!["?"; chunk.len()].join(",");
+            let sql = format!(
+                "SELECT material_id FROM {} WHERE material_id IN ({})",
+                table, placeholders
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(chunk.iter()), |row| {
+                row.get::<_, String>(0)
+            })?;
+            for id in rows {
+                found.insert(id?);
+            }
+        }
+        Ok(found)
+    }
+
+    /// 按机组×完工日期区间查询实绩
+    pub fn find_actuals_by_machine_range(
+        &self,
+        machine_code: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> RepositoryResult<Vec<ProductionActual>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT material_id, machine_code, completed_at, actual_weight_t
+            FROM production_actual
+            WHERE machine_code = ?1 AND substr(completed_at, 1, 10) BETWEEN ?2 AND ?3
+            ORDER BY completed_at, material_id
+            "#,
+        )?;
+        let rows = stmt.query_map(
+            params![
+                machine_code,
+                from.format(DATE_FMT).to_string(),
+                to.format(DATE_FMT).to_string()
+            ],
+            |row| {
+                let completed_at: String = row.get(2)?;
+                Ok(ProductionActual {
+                    material_id: row.get(0)?,
+                    machine_code: row.get(1)?,
+                    completed_at: parse_datetime(&completed_at),
+                    actual_weight_t: row.get(3)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// 查询对账记录（按机组、日期排序）
+    pub fn list_adherence(
+        &self,
+        version_id: &str,
+        machine_code: Option<&str>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> RepositoryResult<Vec<PlanAdherenceRecord>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT version_id, machine_code, plan_date,
+                   planned_count, planned_weight_t, actual_count, actual_weight_t,
+                   matched_count, matched_weight_t, unplanned_count, carry_over_count,
+                   sequence_deviation_count, max_sequence_displacement, avg_sequence_displacement,
+                   adherence_rate, batch_id, generated_at
+            FROM plan_adherence_daily
+            WHERE version_id = ?1
+              AND (?2 IS NULL OR machine_code = ?2)
+              AND (?3 IS NULL OR plan_date >= ?3)
+              AND (?4 IS NULL OR plan_date <= ?4)
+            ORDER BY machine_code, plan_date
+            "#,
+        )?;
+        let rows = stmt.query_map(
+            params![
+                version_id,
+                machine_code,
+                from.map(|d| d.format(DATE_FMT).to_string()),
+                to.map(|d| d.format(DATE_FMT).to_string()),
+            ],
+            |row| {
+                let plan_date: String = row.get(2)?;
+                let generated_at: String = row.get(16)?;
+                Ok(PlanAdherenceRecord {
+                    version_id: row.get(0)?,
+                    machine_code: row.get(1)?,
+                    plan_date: parse_date(&plan_date),
+                    planned_count: row.get(3)?,
+                    planned_weight_t: row.get(4)?,
+                    actual_count: row.get(5)?,
+                    actual_weight_t: row.get(6)?,
+                    matched_count: row.get(7)?,
+                    matched_weight_t: row.get(8)?,
+                    unplanned_count: row.get(9)?,
+                    carry_over_count: row.get(10)?,
+                    sequence_deviation_count: row.get(11)?,
+                    max_sequence_displacement: row.get(12)?,
+                    avg_sequence_displacement: row.get(13)?,
+                    adherence_rate: row.get(14)?,
+                    batch_id: row.get(15)?,
+                    generated_at: parse_datetime(&generated_at),
+                })
+            },
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// 查询顺延项（open_only=true 时仅返回未完工项）
+    pub fn list_carry_overs(
+        &self,
+        version_id: &str,
+        open_only: bool,
+    ) -> RepositoryResult<Vec<CarryOverItem>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT version_id, material_id, machine_code, plan_date, seq_no, weight_t,
+                   detected_at, resolved_at
+            FROM plan_carry_over
+            WHERE version_id = ?1 AND (?2 = 0 OR resolved_at IS NULL)
+            ORDER BY plan_date, machine_code, seq_no
+            "#,
+        )?;
+        let rows = stmt.query_map(params![version_id, open_only as i32], |row| {
+            let plan_date: String = row.get(3)?;
+            let detected_at: String = row.get(6)?;
+            let resolved_at: Option<String> = row.get(7)?;
+            Ok(CarryOverItem {
+                version_id: row.get(0)?,
+                material_id: row.get(1)?,
+                machine_code: row.get(2)?,
+                plan_date: parse_date(&plan_date),
+                seq_no: row.get(4)?,
+                weight_t: row.get(5)?,
+                detected_at: parse_datetime(&detected_at),
+                resolved_at: resolved_at.as_deref().map(parse_datetime),
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+fn parse_date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, DATE_FMT).unwrap_or_default()
+}
+
+fn parse_datetime(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, DATETIME_FMT).unwrap_or_default()
+}
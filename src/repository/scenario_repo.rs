@@ -0,0 +1,209 @@
+// ==========================================
+// 热轧精整排产系统 - 场景/沙盘方案仓储
+// ==========================================
+// 职责:
+// - 管理 scenario_overlay (场景假设叠加层) / scenario_run (评估结果)
+// - 为隔离评估导出数据库快照 (VACUUM INTO)
+// 说明:
+// - 评估结果整体以 JSON 存储 (与策略草案 summary_json/diff_items_json 口径一致)
+// ==========================================
+
+use crate::db::open_sqlite_connection;
+use crate::domain::scenario::{ScenarioOverlay, ScenarioRun};
+use crate::repository::error::{RepositoryError, RepositoryResult};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const DATETIME_FMT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub struct ScenarioRepository {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl ScenarioRepository {
+    pub fn new(db_path: &str) -> RepositoryResult<Self> {
+        let conn = open_sqlite_connection(db_path)?;
+        let repo = Self {
+            conn: Arc::new(Mutex::new(conn)),
+        };
+        repo.ensure_tables()?;
+        Ok(repo)
+    }
+
+    pub fn from_connection(conn: Arc<Mutex<Connection>>) -> RepositoryResult<Self> {
+        let repo = Self { conn };
+        repo.ensure_tables()?;
+        Ok(repo)
+    }
+
+    fn get_conn(&self) -> RepositoryResult<std::sync::MutexGuard<Connection>> {
+        self.conn
+            .lock()
+            .map_err(|e| RepositoryError::LockError(e.to_string()))
+    }
+
+    fn ensure_tables(&self) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS scenario_overlay (
+              plan_id TEXT PRIMARY KEY REFERENCES plan(plan_id) ON DELETE CASCADE,
+              overlay_json TEXT NOT NULL,
+              updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+              updated_by TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS scenario_run (
+              run_id TEXT PRIMARY KEY,
+              plan_id TEXT NOT NULL REFERENCES plan(plan_id) ON DELETE CASCADE,
+              base_version_id TEXT NOT NULL,
+              run_json TEXT NOT NULL,
+              created_by TEXT NOT NULL,
+              created_at TEXT NOT NULL,
+              promoted_version_id TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_scenario_run_plan
+              ON scenario_run(plan_id, created_at);
+            "#,
+        )?;
+        Ok(())
+    }
+
+    // ==========================================
+    // 叠加层
+    // ==========================================
+
+    /// 保存场景叠加层（整体覆盖）
+    pub fn upsert_overlay(
+        &self,
+        plan_id: &str,
+        overlay: &ScenarioOverlay,
+        updated_by: &str,
+    ) -> RepositoryResult<()> {
+        let overlay_json = serde_json::to_string(overlay)
+            .map_err(|e| RepositoryError::InternalError(e.to_string()))?;
+        let conn = self.get_conn()?;
+        conn.execute(
+            r#"
+            INSERT INTO scenario_overlay (plan_id, overlay_json, updated_at, updated_by)
+            VALUES (?1, ?2, datetime('now'), ?3)
+            ON CONFLICT(plan_id) DO UPDATE SET
+              overlay_json = excluded.overlay_json,
+              updated_at = excluded.updated_at,
+              updated_by = excluded.updated_by
+            "#,
+            params![plan_id, overlay_json, updated_by],
+        )?;
+        Ok(())
+    }
+
+    /// 查询场景叠加层（未设置时返回 None）
+    pub fn find_overlay(&self, plan_id: &str) -> RepositoryResult<Option<ScenarioOverlay>> {
+        let conn = self.get_conn()?;
+        let raw: Option<String> = conn
+            .query_row(
+                "SELECT overlay_json FROM scenario_overlay WHERE plan_id = ?1",
+                params![plan_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        raw.map(|s| {
+            serde_json::from_str(&s).map_err(|e| RepositoryError::InternalError(e.to_string()))
+        })
+        .transpose()
+    }
+
+    // ==========================================
+    // 评估结果
+    // ==========================================
+
+    /// 写入评估结果
+    pub fn insert_run(&self, run: &ScenarioRun) -> RepositoryResult<()> {
+        let run_json = serde_json::to_string(run)
+            .map_err(|e| RepositoryError::InternalError(e.to_string()))?;
+        let conn = self.get_conn()?;
+        conn.execute(
+            r#"
+            INSERT INTO scenario_run (
+              run_id, plan_id, base_version_id, run_json, created_by, created_at, promoted_version_id
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+            params![
+                run.run_id,
+                run.plan_id,
+                run.base_version_id,
+                run_json,
+                run.created_by,
+                run.created_at.format(DATETIME_FMT).to_string(),
+                run.promoted_version_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 按ID查询评估结果
+    pub fn find_run(&self, run_id: &str) -> RepositoryResult<Option<ScenarioRun>> {
+        let conn = self.get_conn()?;
+        conn.query_row(
+            "SELECT run_json, promoted_version_id FROM scenario_run WHERE run_id = ?1",
+            params![run_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .optional()?
+        .map(Self::decode_run)
+        .transpose()
+    }
+
+    /// 查询场景方案最近一次评估结果
+    pub fn find_latest_run(&self, plan_id: &str) -> RepositoryResult<Option<ScenarioRun>> {
+        let conn = self.get_conn()?;
+        conn.query_row(
+            r#"
+            SELECT run_json, promoted_version_id FROM scenario_run
+            WHERE plan_id = ?1
+            ORDER BY created_at DESC, rowid DESC
+            LIMIT 1
+            "#,
+            params![plan_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .optional()?
+        .map(Self::decode_run)
+        .transpose()
+    }
+
+    /// 标记评估结果已提升为基准方案草稿版本
+    pub fn mark_promoted(&self, run_id: &str, version_id: &str) -> RepositoryResult<usize> {
+        let conn = self.get_conn()?;
+        let rows = conn.execute(
+            "UPDATE scenario_run SET promoted_version_id = ?2 WHERE run_id = ?1",
+            params![run_id, version_id],
+        )?;
+        Ok(rows)
+    }
+
+    fn decode_run(raw: (String, Option<String>)) -> RepositoryResult<ScenarioRun> {
+        let mut run: ScenarioRun = serde_json::from_str(&raw.0)
+            .map_err(|e| RepositoryError::InternalError(e.to_string()))?;
+        run.promoted_version_id = raw.1;
+        Ok(run)
+    }
+
+    // ==========================================
+    // 隔离快照
+    // ==========================================
+
+    /// 将当前数据库导出为独立快照文件（用于沙盘隔离评估）
+    ///
+    /// 说明: 目标文件必须不存在；快照与生产库无共享连接，写入互不影响。
+    pub fn snapshot_to(&self, target: &Path) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "VACUUM INTO ?1",
+            params![target.to_string_lossy().to_string()],
+        )?;
+        Ok(())
+    }
+}
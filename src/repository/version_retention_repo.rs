@@ -0,0 +1,155 @@
+// ==========================================
+// 热轧精整排产系统 - 版本保留/清理仓储
+// ==========================================
+// 职责:
+// - 查询曾激活过的版本（激活/回滚日志）
+// - 批量清理版本及其版本级数据（单事务）
+// 说明:
+// - 显式删除关联数据，不依赖 SQLite foreign_keys 配置
+// - action_log 仅解绑 version_id（审计记录保留），清理日志与删除同一事务写入
+// - production_actual 为实绩数据，不随版本清理
+// ==========================================
+
+use crate::db::open_sqlite_connection;
+use crate::domain::action_log::ActionLog;
+use crate::repository::action_log_repo::ActionLogRepository;
+use crate::repository::error::{RepositoryError, RepositoryResult};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// 以 version_id 关联的版本级数据表（按删除顺序）
+const VERSION_SCOPED_TABLES: &[&str] = &[
+    "plan_item",
+    "capacity_pool",
+    "capacity_reservation_usage",
+    "risk_snapshot",
+    "roller_campaign",
+    "path_override_pending",
+    "grade_batching_deferral",
+    "roll_campaign_plan",
+    "plan_rhythm_target",
+    "plan_adherence_daily",
+    "plan_carry_over",
+    "plan_version_review",
+    "plan_version_input_snapshot",
+    "machine_capacity_config",
+    "decision_day_summary",
+    "decision_order_failure_set",
+    "decision_cold_stock_profile",
+    "decision_machine_bottleneck",
+    "decision_roll_campaign_alert",
+    "decision_capacity_opportunity",
+    "decision_refresh_log",
+    "decision_refresh_queue",
+];
+
+pub struct VersionRetentionRepository {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl VersionRetentionRepository {
+    pub fn new(db_path: &str) -> RepositoryResult<Self> {
+        let conn = open_sqlite_connection(db_path)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    pub fn from_connection(conn: Arc<Mutex<Connection>>) -> RepositoryResult<Self> {
+        Ok(Self { conn })
+    }
+
+    fn get_conn(&self) -> RepositoryResult<std::sync::MutexGuard<Connection>> {
+        self.conn
+            .lock()
+            .map_err(|e| RepositoryError::LockError(e.to_string()))
+    }
+
+    /// 查询有激活/回滚记录的版本ID（曾激活过）
+    pub fn list_ever_active_version_ids(&self) -> RepositoryResult<Vec<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT DISTINCT version_id FROM action_log
+               WHERE version_id IS NOT NULL
+                 AND action_type IN ('ACTIVATE_VERSION', 'ROLLBACK_VERSION')"#,
+        )?;
+        let ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// 批量清理版本（单事务，任一失败整体回滚）
+    ///
+    /// # 参数
+    /// - build_log: 按清理结果生成审计日志，与删除在同一事务内写入
+    ///
+    /// # 返回
+    /// - (表名 → 删除行数, 解绑的 action_log 行数)
+    pub fn purge_versions(
+        &self,
+        version_ids: &[String],
+        build_log: impl FnOnce(&BTreeMap<String, usize>, usize) -> ActionLog,
+    ) -> RepositoryResult<(BTreeMap<String, usize>, usize)> {
+        let mut deleted_rows: BTreeMap<String, usize> = BTreeMap::new();
+        if version_ids.is_empty() {
+            return Ok((deleted_rows, 0));
+        }
+
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        // 仅处理当前库中存在的表（不同部署的表集合可能不同）
+        let mut existing_tables = Vec::new();
+        for table in VERSION_SCOPED_TABLES
+            .iter()
+            .chain(["decision_strategy_draft"].iter())
+        {
+            let exists = tx
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                    params![table],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some();
+            if exists {
+                existing_tables.push(*table);
+            }
+        }
+
+        let mut detached = 0usize;
+        for version_id in version_ids {
+            for table in &existing_tables {
+                // 策略草稿以 base_version_id 关联
+                let column = if *table == "decision_strategy_draft" {
+                    "base_version_id"
+                } else {
+                    "version_id"
+                };
+                let rows = tx.execute(
+                    &format!("DELETE FROM {} WHERE {} = ?1", table, column),
+                    params![version_id],
+                )?;
+                *deleted_rows.entry(table.to_string()).or_insert(0) += rows;
+            }
+
+            detached += tx.execute(
+                "UPDATE action_log SET version_id = NULL WHERE version_id = ?1",
+                params![version_id],
+            )?;
+
+            let rows = tx.execute(
+                "DELETE FROM plan_version WHERE version_id = ?1",
+                params![version_id],
+            )?;
+            *deleted_rows.entry("plan_version".to_string()).or_insert(0) += rows;
+        }
+
+        ActionLogRepository::insert_log(&tx, &build_log(&deleted_rows, detached))?;
+
+        tx.commit()?;
+        Ok((deleted_rows, detached))
+    }
+}
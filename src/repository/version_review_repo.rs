@@ -0,0 +1,189 @@
+// ==========================================
+// 热轧精整排产系统 - 版本审批仓储
+// ==========================================
+// 职责:
+// - 管理 plan_version_review (提交/通过/驳回 记录)
+// - 审批动作与 plan_version.status 流转在同一事务内完成
+// 说明:
+// - 状态流转以 "期望当前状态" 做条件更新，防止并发审批重复生效
+// ==========================================
+
+use crate::db::open_sqlite_connection;
+use crate::domain::types::PlanVersionStatus;
+use crate::domain::version_review::{VersionReview, VersionReviewAction};
+use crate::repository::error::{RepositoryError, RepositoryResult};
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection};
+use std::sync::{Arc, Mutex};
+
+const DATETIME_FMT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub struct VersionReviewRepository {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl VersionReviewRepository {
+    pub fn new(db_path: &str) -> RepositoryResult<Self> {
+        let conn = open_sqlite_connection(db_path)?;
+        let repo = Self {
+            conn: Arc::new(Mutex::new(conn)),
+        };
+        repo.ensure_tables()?;
+        Ok(repo)
+    }
+
+    pub fn from_connection(conn: Arc<Mutex<Connection>>) -> RepositoryResult<Self> {
+        let repo = Self { conn };
+        repo.ensure_tables()?;
+        Ok(repo)
+    }
+
+    fn get_conn(&self) -> RepositoryResult<std::sync::MutexGuard<Connection>> {
+        self.conn
+            .lock()
+            .map_err(|e| RepositoryError::LockError(e.to_string()))
+    }
+
+    fn ensure_tables(&self) -> RepositoryResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS plan_version_review (
+              review_id TEXT PRIMARY KEY,
+              version_id TEXT NOT NULL REFERENCES plan_version(version_id) ON DELETE CASCADE,
+              action TEXT NOT NULL,
+              actor TEXT NOT NULL,
+              comment TEXT,
+              checklist_json TEXT,
+              created_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_plan_version_review_version
+              ON plan_version_review(version_id, created_at);
+            "#,
+        )?;
+        Ok(())
+    }
+
+    /// 记录审批动作并流转版本状态（同一事务）
+    ///
+    /// # 错误
+    /// - `RepositoryError::VersionConflict`: 版本当前状态不是 `expected`（已被他人处理）
+    pub fn record_transition(
+        &self,
+        review: &VersionReview,
+        expected: PlanVersionStatus,
+        next: PlanVersionStatus,
+    ) -> RepositoryResult<()> {
+        let checklist_json = review
+            .checklist
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| RepositoryError::InternalError(e.to_string()))?;
+
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        let updated = tx.execute(
+            r#"UPDATE plan_version
+               SET status = ?1, revision = revision + 1
+               WHERE version_id = ?2 AND status = ?3"#,
+            params![next.to_db_str(), &review.version_id, expected.to_db_str()],
+        )?;
+        if updated == 0 {
+            return Err(RepositoryError::VersionConflict {
+                message: format!(
+                    "版本{}状态已变化(期望{})，请刷新后重试",
+                    review.version_id, expected
+                ),
+            });
+        }
+
+        tx.execute(
+            r#"INSERT INTO plan_version_review (
+                 review_id, version_id, action, actor, comment, checklist_json, created_at
+               ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+            params![
+                &review.review_id,
+                &review.version_id,
+                review.action.to_db_str(),
+                &review.actor,
+                &review.comment,
+                checklist_json,
+                review.created_at.format(DATETIME_FMT).to_string(),
+            ],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 查询版本的审批记录（按时间正序）
+    pub fn list_by_version(&self, version_id: &str) -> RepositoryResult<Vec<VersionReview>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT review_id, version_id, action, actor, comment, checklist_json, created_at
+               FROM plan_version_review
+               WHERE version_id = ?1
+               ORDER BY created_at ASC, rowid ASC"#,
+        )?;
+        let rows = stmt
+            .query_map(params![version_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(
+                |(review_id, version_id, action, actor, comment, checklist_json, created_at)| {
+                    let action = VersionReviewAction::parse(&action).ok_or_else(|| {
+                        RepositoryError::FieldValueError {
+                            field: "action".to_string(),
+                            message: format!("未知审批动作: {}", action),
+                        }
+                    })?;
+                    let checklist = checklist_json
+                        .map(|s| serde_json::from_str(&s))
+                        .transpose()
+                        .map_err(|e| RepositoryError::InternalError(e.to_string()))?;
+                    let created_at = NaiveDateTime::parse_from_str(&created_at, DATETIME_FMT)
+                        .map_err(|e| RepositoryError::FieldValueError {
+                            field: "created_at".to_string(),
+                            message: e.to_string(),
+                        })?;
+                    Ok(VersionReview {
+                        review_id,
+                        version_id,
+                        action,
+                        actor,
+                        comment,
+                        checklist,
+                        created_at,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// 查询版本最近一次指定动作的审批记录
+    pub fn find_latest(
+        &self,
+        version_id: &str,
+        action: VersionReviewAction,
+    ) -> RepositoryResult<Option<VersionReview>> {
+        Ok(self
+            .list_by_version(version_id)?
+            .into_iter()
+            .rev()
+            .find(|r| r.action == action))
+    }
+}
@@ -29,7 +29,7 @@ export interface PlanVersion {
   note?: string | null;
   created_at: string;
   created_by: string;
-  status: 'DRAFT' | 'ACTIVE' | 'ARCHIVED';
+  status: 'DRAFT' | 'SUBMITTED' | 'APPROVED' | 'REJECTED' | 'ACTIVE' | 'ARCHIVED';
 }
 
 // ==========================================
@@ -0,0 +1,77 @@
+/**
+ * 瓶颈优化建议（一键优化）类型定义
+ * 对应后端 BottleneckOptimizerApi
+ */
+
+export type BottleneckActionKind =
+  | 'MOVE_ITEM'
+  | 'FORCE_RELEASE'
+  | 'CONFIRM_PATH_OVERRIDE'
+  | 'SHIFT_ROLL_CHANGE';
+
+/** 单条调整建议（predicted_impact 为 ImpactSummaryEngine 输出） */
+export type BottleneckRecommendation = {
+  recommendation_id: string;
+  rank: number;
+  kind: BottleneckActionKind;
+  machine_code: string;
+  material_id?: string | null;
+  from_date?: string | null;
+  to_date?: string | null;
+  /** 平移换辊：原/新换辊时间 (YYYY-MM-DD HH:MM:SS) */
+  roll_change_from?: string | null;
+  roll_change_to?: string | null;
+  weight_t: number;
+  urgent_level?: string | null;
+  overload_relief_t: number;
+  slack_fill_t: number;
+  benefit_score: number;
+  reason: string;
+  /** 预计影响（JSON 结构，需运行时验证） */
+  predicted_impact?: Record<string, unknown> | null;
+};
+
+export type BottleneckRecommendationResponse = {
+  version_id: string;
+  plan_date_from: string;
+  plan_date_to: string;
+  machine_code?: string | null;
+  bottleneck_day_count: number;
+  overload_t: number;
+  /** 全部建议执行后的预计值 */
+  remaining_bottleneck_day_count: number;
+  remaining_overload_t: number;
+  recommendations: BottleneckRecommendation[];
+  message: string;
+};
+
+export type ApplyBottleneckRecommendationsResponse = {
+  version_id: string;
+  applied_ids: string[];
+  skipped: Array<{ recommendation_id: string; reason: string }>;
+  moved_count: number;
+  force_released_count: number;
+  path_override_confirmed_count: number;
+  roll_change_shifted_count: number;
+  message: string;
+};
+
+export const BOTTLENECK_ACTION_KIND_LABELS: Record<BottleneckActionKind, string> = {
+  MOVE_ITEM: '移动材料',
+  FORCE_RELEASE: '强制放行',
+  CONFIRM_PATH_OVERRIDE: '确认路径突破',
+  SHIFT_ROLL_CHANGE: '平移换辊',
+};
+
+/** 建议的一句话描述（列表展示用） */
+export function describeBottleneckRecommendation(rec: BottleneckRecommendation): string {
+  const label = BOTTLENECK_ACTION_KIND_LABELS[rec.kind] ?? rec.kind;
+  switch (rec.kind) {
+    case 'MOVE_ITEM':
+      return `${label} ${rec.material_id}：${rec.machine_code}/${rec.from_date} → ${rec.machine_code}/${rec.to_date}`;
+    case 'SHIFT_ROLL_CHANGE':
+      return `${label} ${rec.machine_code}：${rec.roll_change_from} → ${rec.roll_change_to}`;
+    default:
+      return `${label} ${rec.material_id}（${rec.machine_code}/${rec.to_date}）`;
+  }
+}
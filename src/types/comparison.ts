@@ -1,3 +1,4 @@
+import type { PlanObjectiveVector } from '../api/ipcSchemas';
 import type { StrategyType } from './preferences';
 
 export interface PlanItemSnapshot {
@@ -115,6 +116,9 @@ export interface BackendVersionKpiSummary {
   urgent_total_t: number | null;
   snapshot_date_from: string | null;
   snapshot_date_to: string | null;
+
+  /** 多目标评价（与策略草案、决策层同一口径） */
+  objectives?: PlanObjectiveVector | null;
 }
 
 export interface BackendVersionComparisonKpiResult {
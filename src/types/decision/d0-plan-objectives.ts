@@ -0,0 +1,61 @@
+// ==========================================
+// D0: 方案多目标评价 TypeScript 类型定义
+// ==========================================
+// 对应 Rust: src/engine/plan_evaluation.rs
+// PlanObjectiveVector（策略草案 / 版本对比 / 决策层 同一口径）
+// ==========================================
+
+/**
+ * D0 请求: 查询方案多目标评价
+ */
+export interface GetPlanObjectivesRequest {
+  /** 方案版本 ID */
+  versionId: string;
+
+  /** 期望计划修订号（可选，用于防陈旧读取） */
+  expectedPlanRev?: number;
+}
+
+/**
+ * D0 响应: 标准目标向量
+ */
+export interface PlanObjectives {
+  planItemsCount: number;
+  totalWeightT: number;
+
+  // ===== 交付 =====
+  /** 有交期的已排材料数 */
+  dueItemsCount: number;
+  tardyCount: number;
+  /** 拖期天数合计 = Σ max(0, 排产日期 - 交期) */
+  totalTardinessDays: number;
+  maxTardinessDays: number;
+  onTimeWeightT: number;
+  dueWeightT: number;
+  /** 准时吨位率 [0,1] */
+  onTimeWeightRate: number;
+
+  // ===== 产能 =====
+  capacityUsedT: number;
+  capacityTargetT: number;
+  capacityLimitT: number;
+  capacityUtilPct: number;
+  overflowT: number;
+  overflowMachineDays: number;
+
+  // ===== 结构 =====
+  /** 钢种配比偏差（吨位加权）[0,1] */
+  structureDeviation: number;
+  structureViolationDays: number;
+
+  // ===== 路径/换辊/换钢种 =====
+  pathOverrideCount: number;
+  /** 换辊次数（按累计吨位估算） */
+  rollChangeCount: number;
+  changeoverCount: number;
+
+  // ===== 冷料 =====
+  coldStockClearedCount: number;
+  coldStockClearedT: number;
+  coldStockAgeReductionDays: number;
+}
@@ -2,6 +2,9 @@
 // 决策层类型定义 - 统一导出
 // ==========================================
 
+// D0: 方案多目标评价
+export * from './d0-plan-objectives';
+
 // D1: 日期风险摘要
 export * from './d1-day-summary';
 
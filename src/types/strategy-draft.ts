@@ -4,7 +4,11 @@
  */
 
 import type { z } from 'zod';
-import type { MaterialMasterSchema, MaterialStateSchema } from '../api/ipcSchemas';
+import type {
+  MaterialMasterSchema,
+  MaterialStateSchema,
+  PlanObjectiveVector,
+} from '../api/ipcSchemas';
 
 export type StrategyKey = string;
 
@@ -44,6 +48,8 @@ export type StrategyDraftSummary = {
   removed_count: number;
   squeezed_out_count: number;
   message: string;
+  /** 多目标评价（旧草案摘要可能缺失） */
+  objectives?: PlanObjectiveVector | null;
 };
 
 export type GenerateStrategyDraftsResponse = {
@@ -52,10 +58,123 @@ export type GenerateStrategyDraftsResponse = {
   plan_date_to: string;
   drafts: StrategyDraftSummary[];
   message: string;
+  /** 本次生成任务ID（进度事件/取消使用） */
+  generation_id?: string;
+  /** 是否被取消（已完成的草案仍会返回） */
+  cancelled?: boolean;
+};
+
+export type StrategyDraftProgressStatus = 'STARTED' | 'COMPLETED' | 'FAILED' | 'CANCELLED';
+
+/** 草案生成进度事件（strategy_draft_progress，按策略推送） */
+export type StrategyDraftProgress = {
+  generation_id: string;
+  strategy: StrategyKey;
+  status: StrategyDraftProgressStatus;
+  finished_count: number;
+  total_count: number;
+  draft_id?: string | null;
+  message: string;
 };
 
 export type ListStrategyDraftsResponse = GenerateStrategyDraftsResponse;
 
+/** 参数扫描点（每个点对应一条草案，可发布或保存为自定义策略） */
+export type StrategySweepPoint = {
+  index: number;
+  draft_id: string;
+  strategy_key: StrategyKey;
+  parameters: Record<string, number | null | undefined>;
+  objectives: PlanObjectiveVector;
+  pareto_optimal: boolean;
+  dominated_by_count: number;
+};
+
+export type StrategySweepResponse = {
+  sweep_id: string;
+  base_version_id: string;
+  plan_date_from: string;
+  plan_date_to: string;
+  base_strategy: string;
+  points: StrategySweepPoint[];
+  pareto_count: number;
+  message: string;
+};
+
+/** 将扫描点转为可保存的自定义策略 */
+export function sweepPointToCustomStrategy(
+  point: StrategySweepPoint,
+  baseStrategy: string,
+  strategyId: string,
+  title: string
+): CustomStrategyProfile {
+  return {
+    strategy_id: strategyId,
+    title,
+    description: `参数扫描点 #${point.index}（${point.strategy_key}）`,
+    base_strategy: baseStrategy,
+    parameters: { ...point.parameters },
+  };
+}
+
+/** 一致率统计（计划员人工选择被策略复现的比例） */
+export type AgreementRate = {
+  agreed: number;
+  total: number;
+  rate: number;
+};
+
+/** 紧急阈值调整建议 */
+export type UrgencyRuleSuggestion = {
+  config_key: string;
+  current_value: number;
+  suggested_value: number;
+  evidence_count: number;
+  covered_before: number;
+  covered_after: number;
+  reason: string;
+};
+
+/** 人工调整反推策略权重报告 */
+export type StrategyWeightLearningResponse = {
+  analysis_from: string;
+  analysis_to: string;
+  strategy_key: StrategyKey;
+  base_strategy: string;
+  move_log_count: number;
+  legacy_move_log_count: number;
+  urgent_log_count: number;
+  force_release_log_count: number;
+  pair_count: number;
+  excluded_pair_count: number;
+  pairs_truncated: boolean;
+  baseline_agreement: AgreementRate;
+  learned_agreement: AgreementRate;
+  learned_parameters: Record<string, number | null | undefined>;
+  urgent_n1_days: number;
+  urgent_n2_days: number;
+  rule_suggestions: UrgencyRuleSuggestion[];
+  message: string;
+};
+
+/** 将学习结果转为可保存的自定义策略 */
+export function learnedWeightsToCustomStrategy(
+  report: StrategyWeightLearningResponse,
+  strategyId: string,
+  title: string
+): CustomStrategyProfile {
+  const pct = (r: AgreementRate) => `${(r.rate * 100).toFixed(1)}%`;
+  return {
+    strategy_id: strategyId,
+    title,
+    description: `人工调整学习（${report.analysis_from}~${report.analysis_to}，一致率 ${pct(
+      report.baseline_agreement
+    )} → ${pct(report.learned_agreement)}）`,
+    base_strategy: report.base_strategy,
+    parameters: { ...report.learned_parameters },
+  };
+}
+
 export type ApplyStrategyDraftResponse = {
   version_id: string;
   success: boolean;
@@ -144,9 +263,15 @@ export const FALLBACK_STRATEGIES: StrategyPreset[] = [
   { key: 'cold_stock_first', title: '冷料消化', description: '优先消化冷料/压库物料', kind: 'preset' },
 ];
 
-/** 最大计划天数限制 */
+/** 最大计划天数限制（默认值；实际以全局配置 strategy_draft_max_days 为准） */
 export const MAX_DAYS = 60;
 
+/** 草案时间跨度上限配置键 */
+export const STRATEGY_DRAFT_MAX_DAYS_CONFIG_KEY = 'strategy_draft_max_days';
+
+/** 草案生成进度事件名 */
+export const STRATEGY_DRAFT_PROGRESS_EVENT = 'strategy_draft_progress';
+
 /** 生成自定义策略的 key */
 export function makeCustomStrategyKey(strategyId: string): string {
   return `custom:${String(strategyId || '').trim()}`;
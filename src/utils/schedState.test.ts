@@ -53,6 +53,12 @@ describe('schedState 工具函数', () => {
       expect(normalizeSchedState('Scheduled')).toBe('SCHEDULED');
     });
 
+    it('应该规范化 COMPLETED 状态', () => {
+      expect(normalizeSchedState('COMPLETED')).toBe('COMPLETED');
+      expect(normalizeSchedState('completed')).toBe('COMPLETED');
+      expect(normalizeSchedState('Completed')).toBe('COMPLETED');
+    });
+
     it('应该处理未知状态', () => {
       expect(normalizeSchedState('INVALID_STATE')).toBe('UNKNOWN');
       expect(normalizeSchedState('random')).toBe('UNKNOWN');
@@ -131,6 +137,11 @@ describe('schedState 工具函数', () => {
       expect(getSchedStateLabel('Scheduled')).toBe('已排产');
     });
 
+    it('应该返回 COMPLETED 的中文标签', () => {
+      expect(getSchedStateLabel('COMPLETED')).toBe('已完工');
+      expect(getSchedStateLabel('completed')).toBe('已完工');
+    });
+
     it('应该返回未知状态的中文标签', () => {
       expect(getSchedStateLabel('INVALID')).toBe('未知');
       expect(getSchedStateLabel(null)).toBe('未知');
@@ -5,6 +5,7 @@ export type NormalizedSchedState =
   | 'FORCE_RELEASE'
   | 'BLOCKED'
   | 'SCHEDULED'
+  | 'COMPLETED'
   | 'UNKNOWN';
 
 // Normalize backend/legacy variants into a single canonical sched_state value.
@@ -29,6 +30,8 @@ export function normalizeSchedState(value: unknown): NormalizedSchedState {
       return 'BLOCKED';
     case 'SCHEDULED':
       return 'SCHEDULED';
+    case 'COMPLETED':
+      return 'COMPLETED';
     default:
       return 'UNKNOWN';
   }
@@ -52,6 +55,8 @@ export function getSchedStateLabel(value: unknown): string {
       return '阻断';
     case 'SCHEDULED':
       return '已排产';
+    case 'COMPLETED':
+      return '已完工';
     default:
       return '未知';
   }
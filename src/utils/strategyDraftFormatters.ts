@@ -11,7 +11,7 @@ import { MAX_DAYS } from '../types/strategy-draft';
 /**
  * 限制日期范围不超过最大天数
  */
-export function clampRange(range: [Dayjs, Dayjs]): [Dayjs, Dayjs] {
+export function clampRange(range: [Dayjs, Dayjs], maxDays: number = MAX_DAYS): [Dayjs, Dayjs] {
   let [start, end] = range;
   start = start.startOf('day');
   end = end.startOf('day');
@@ -21,8 +21,8 @@ export function clampRange(range: [Dayjs, Dayjs]): [Dayjs, Dayjs] {
     end = tmp;
   }
   const days = end.diff(start, 'day') + 1;
-  if (days > MAX_DAYS) {
-    end = start.add(MAX_DAYS - 1, 'day');
+  if (days > maxDays) {
+    end = start.add(maxDays - 1, 'day');
   }
   return [start, end];
 }
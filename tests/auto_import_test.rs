@@ -0,0 +1,163 @@
+// ==========================================
+// 监控目录自动导入 集成测试
+// ==========================================
+// 测试目标: 扫描目录 → 导入 → 归档 archive/failed → 批次记录 imported_by
+//           导入后重算只生成派生草稿，不改动激活版本
+// ==========================================
+
+mod test_helpers;
+
+use hot_rolling_aps::app::auto_import::{
+    AutoImportOutcome, ARCHIVE_DIR_NAME, AUTO_IMPORT_OPERATOR, FAILED_DIR_NAME,
+};
+use hot_rolling_aps::app::AppState;
+use hot_rolling_aps::domain::types::PlanVersionStatus;
+use test_helpers::{create_test_db, insert_test_config};
+
+fn set_config(conn: &rusqlite::Connection, key: &str, value: &str) {
+    conn.execute(
+        "INSERT OR REPLACE INTO config_kv (scope_id, key, value, updated_at) VALUES ('global', ?1, ?2, datetime('now'))",
+        rusqlite::params![key, value],
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_auto_import_scan_archives_and_records_batch() {
+    let (_temp_file, db_path) = create_test_db().expect("Failed to create test db");
+    let conn = test_helpers::open_test_connection(&db_path).expect("Failed to open db");
+    insert_test_config(&conn).expect("Failed to insert test config");
+
+    let watch_dir = tempfile::tempdir().unwrap();
+    std::fs::copy(
+        "tests/fixtures/test_materials.csv",
+        watch_dir.path().join("MES_snapshot.csv"),
+    )
+    .unwrap();
+    std::fs::write(watch_dir.path().join("broken.xlsx"), b"not an excel file").unwrap();
+    std::fs::write(watch_dir.path().join("readme.txt"), b"ignored").unwrap();
+
+    set_config(&conn, "auto_import_dir", watch_dir.path().to_str().unwrap());
+    set_config(&conn, "auto_import_stability_secs", "0");
+
+    let state = AppState::new(db_path.clone()).expect("Failed to init AppState");
+    let results = state.auto_import.scan_once().await.expect("scan failed");
+    let find = |name: &str| results.iter().find(|r| r.file_name == name).unwrap();
+
+    assert_eq!(results.len(), 2, "仅处理匹配模式的文件: {:?}", results);
+
+    // broken.xlsx → failed/ + 错误说明
+    let failed = find("broken.xlsx");
+    assert_eq!(failed.outcome, AutoImportOutcome::Failed);
+    assert!(failed.error.is_some());
+    let failed_dir = watch_dir.path().join(FAILED_DIR_NAME);
+    assert!(failed_dir.join("broken.xlsx").exists());
+    assert!(failed_dir.join("broken.xlsx.error.txt").exists());
+
+    // MES_snapshot.csv → archive/
+    let ok = find("MES_snapshot.csv");
+    assert_eq!(ok.outcome, AutoImportOutcome::Imported);
+    assert!(ok.imported > 0);
+    assert!(watch_dir
+        .path()
+        .join(ARCHIVE_DIR_NAME)
+        .join("MES_snapshot.csv")
+        .exists());
+    assert!(!watch_dir.path().join("MES_snapshot.csv").exists());
+    assert!(watch_dir.path().join("readme.txt").exists());
+
+    let imported_by: String = conn
+        .query_row(
+            "SELECT imported_by FROM import_batch WHERE batch_id = ?1",
+            [ok.import_batch_id.as_deref().unwrap()],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(imported_by, AUTO_IMPORT_OPERATOR);
+
+    // 再次扫描：目录已清空
+    let again = state.auto_import.scan_once().await.expect("scan failed");
+    assert!(again.is_empty());
+
+    let status = state.auto_import.status();
+    assert!(!status.running);
+    assert_eq!(status.recent_results.len(), 2);
+    assert!(status.last_scan_at.is_some());
+}
+
+#[tokio::test]
+async fn test_auto_import_scan_requires_watch_dir() {
+    let (_temp_file, db_path) = create_test_db().expect("Failed to create test db");
+    let state = AppState::new(db_path).expect("Failed to init AppState");
+
+    let err = state.auto_import.scan_once().await.unwrap_err();
+    assert!(err.contains("auto_import_dir"), "err = {}", err);
+    assert!(state.auto_import.status().last_scan_error.is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_auto_import_recalc_creates_draft_and_keeps_active_version() {
+    let (_temp_file, db_path) = create_test_db().expect("Failed to create test db");
+    let conn = test_helpers::open_test_connection(&db_path).expect("Failed to open db");
+    insert_test_config(&conn).expect("Failed to insert test config");
+
+    let watch_dir = tempfile::tempdir().unwrap();
+    std::fs::copy(
+        "tests/fixtures/test_materials.csv",
+        watch_dir.path().join("MES_snapshot.csv"),
+    )
+    .unwrap();
+    set_config(&conn, "auto_import_dir", watch_dir.path().to_str().unwrap());
+    set_config(&conn, "auto_import_stability_secs", "0");
+    set_config(&conn, "auto_import_trigger_recalc", "true");
+    set_config(&conn, "auto_import_recalc_days", "3");
+
+    let state = AppState::new(db_path.clone()).expect("Failed to init AppState");
+    let plan_id = state
+        .plan_api
+        .create_plan("自动导入方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let active = state
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    state
+        .version_approval_api
+        .submit_version(&active, None, "planner")
+        .expect("提交审批失败");
+    state
+        .version_approval_api
+        .approve_version(&active, None, "manager")
+        .expect("审批失败");
+    state
+        .plan_api
+        .activate_version(&active, "admin")
+        .expect("激活版本失败");
+
+    let results = state.auto_import.scan_once().await.expect("scan failed");
+    assert_eq!(results.len(), 1);
+    let result = &results[0];
+    assert_eq!(result.outcome, AutoImportOutcome::Imported);
+    assert!(result.recalc_error.is_none(), "{:?}", result.recalc_error);
+
+    // 重算结果为新的草稿版本，激活版本保持不变
+    let draft_id = result.recalc_version_id.clone().expect("应生成重算草稿");
+    assert_ne!(draft_id, active);
+    let draft = state
+        .plan_api
+        .get_version_detail(&draft_id)
+        .expect("查询草稿失败");
+    assert_eq!(draft.status, PlanVersionStatus::Draft);
+    assert_eq!(
+        state.plan_api.get_latest_active_version_id().unwrap(),
+        Some(active.clone())
+    );
+    assert_eq!(
+        state
+            .plan_api
+            .get_version_detail(&active)
+            .expect("查询激活版本失败")
+            .status,
+        PlanVersionStatus::Active
+    );
+}
@@ -0,0 +1,373 @@
+// ==========================================
+// 瓶颈优化建议（一键优化） 集成测试
+// ==========================================
+// 测试范围:
+// 1. 建议生成: 移动材料 / 平移换辊 / 确认路径突破 / 强制放行，按收益排序并附预计影响
+// 2. 一键执行: 移动走 move_items 校验与日志，其余走对应业务接口；执行后瓶颈消除
+// 3. 失效建议ID跳过、参数校验
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, Local, NaiveDate};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{
+    CapacityPoolBuilder, MaterialBuilder, MaterialStateBuilder, PlanItemBuilder,
+};
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::domain::types::{SchedState, UrgentLevel};
+use hot_rolling_aps::engine::BottleneckActionKind;
+use hot_rolling_aps::repository::path_override_pending_repo::PathOverridePendingRecord;
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn day(offset: i64) -> NaiveDate {
+    today() + Duration::days(offset)
+}
+
+/// 准备瓶颈场景，返回版本ID
+///
+/// H032 目标 100t / 上限 120t:
+/// - D1: A(60t, L0, 交期远) + B(50t, L2) + F(30t, 冻结) = 140t，超目标 40t
+/// - D2: C(90t, 冻结) + 换辊停机 240 分钟(折算 20t) = 110t，超目标 10t
+/// - D3/D4: 空
+/// - P: 路径突破待确认（D3, 30t, L1）
+/// - R: 未适温 L3 紧急材料（25t，还需 3 天适温）
+fn prepare_bottleneck(env: &ApiTestEnv) -> String {
+    // (材料, 重量, 紧急等级, 交期偏移)
+    let materials: [(&str, f64, UrgentLevel, i64); 6] = [
+        ("A", 60.0, UrgentLevel::L0, 30),
+        ("B", 50.0, UrgentLevel::L2, 2),
+        ("F", 30.0, UrgentLevel::L0, 10),
+        ("C", 90.0, UrgentLevel::L1, 10),
+        ("P", 30.0, UrgentLevel::L1, 10),
+        ("R", 25.0, UrgentLevel::L3, 5),
+    ];
+    let masters = materials
+        .iter()
+        .map(|(id, weight, _, due)| {
+            MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(*weight)
+                .due_date(day(*due))
+                .build()
+        })
+        .collect();
+    let states = materials
+        .iter()
+        .map(|(id, _, level, _)| {
+            let builder = MaterialStateBuilder::new(id).urgent_level(*level);
+            if *id == "R" {
+                let mut state = builder.sched_state(SchedState::PendingMature).build();
+                state.ready_in_days = 3;
+                state.earliest_sched_date = Some(day(3));
+                state
+            } else {
+                builder.sched_state(SchedState::Ready).build()
+            }
+        })
+        .collect();
+    env.prepare_materials(masters, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("瓶颈优化测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.prepare_capacity_pools(
+        (1..=4)
+            .map(|i| {
+                CapacityPoolBuilder::new("H032", day(i))
+                    .version_id(&version_id)
+                    .target(100.0)
+                    .limit(120.0)
+                    .build()
+            })
+            .collect(),
+    )
+    .expect("准备产能池失败");
+
+    let items = vec![
+        PlanItemBuilder::new(&version_id, "A", "H032", day(1))
+            .seq_no(1)
+            .weight(60.0)
+            .build(),
+        PlanItemBuilder::new(&version_id, "B", "H032", day(1))
+            .seq_no(2)
+            .weight(50.0)
+            .build(),
+        PlanItemBuilder::new(&version_id, "F", "H032", day(1))
+            .seq_no(3)
+            .weight(30.0)
+            .frozen()
+            .build(),
+        PlanItemBuilder::new(&version_id, "C", "H032", day(2))
+            .seq_no(1)
+            .weight(90.0)
+            .frozen()
+            .build(),
+    ];
+    env.plan_item_repo
+        .batch_insert(&items)
+        .expect("插入计划失败");
+
+    env.roller_api
+        .upsert_campaign_plan(
+            &version_id,
+            "H032",
+            &format!("{} 00:00:00", day(-10)),
+            Some(&format!("{} 08:00:00", day(2))),
+            Some(240),
+            "admin",
+            "计划换辊",
+        )
+        .expect("写入换辊计划失败");
+
+    env.path_override_pending_repo
+        .insert_ignore_many(&[PathOverridePendingRecord {
+            version_id: version_id.clone(),
+            machine_code: "H032".to_string(),
+            plan_date: day(3),
+            material_id: "P".to_string(),
+            violation_type: "WIDTH_JUMP".to_string(),
+            urgent_level: "L1".to_string(),
+            width_mm: 1500.0,
+            thickness_mm: 10.0,
+            anchor_width_mm: 1200.0,
+            anchor_thickness_mm: 10.0,
+            width_delta_mm: 300.0,
+            thickness_delta_mm: 0.0,
+        }])
+        .expect("写入路径待确认失败");
+
+    version_id
+}
+
+#[test]
+fn test_recommendations_ranked_with_predicted_impact() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_bottleneck(&env);
+
+    let resp = env
+        .bottleneck_optimizer_api
+        .list_bottleneck_recommendations(&version_id, day(1), day(4), None)
+        .expect("生成建议失败");
+
+    assert_eq!(resp.bottleneck_day_count, 2);
+    assert!((resp.overload_t - 50.0).abs() < 1e-6);
+    assert_eq!(resp.remaining_bottleneck_day_count, 0);
+
+    let summary: Vec<(BottleneckActionKind, Option<&str>, Option<NaiveDate>, f64)> = resp
+        .recommendations
+        .iter()
+        .map(|r| (r.kind, r.material_id.as_deref(), r.to_date, r.benefit_score))
+        .collect();
+    // A 移出 D1 消除 40t；R 放行 25t×1.0；P 确认 30t×0.5；换辊平移消除 10t
+    assert_eq!(
+        summary,
+        vec![
+            (
+                BottleneckActionKind::MoveItem,
+                Some("A"),
+                Some(day(3)),
+                40.0
+            ),
+            (
+                BottleneckActionKind::ForceRelease,
+                Some("R"),
+                Some(day(4)),
+                25.0
+            ),
+            (
+                BottleneckActionKind::ConfirmPathOverride,
+                Some("P"),
+                Some(day(3)),
+                15.0
+            ),
+            (
+                BottleneckActionKind::ShiftRollChange,
+                None,
+                Some(day(1)),
+                10.0
+            ),
+        ]
+    );
+    assert_eq!(
+        resp.recommendations
+            .iter()
+            .map(|r| r.rank)
+            .collect::<Vec<_>>(),
+        vec![1, 2, 3, 4]
+    );
+
+    // 预计影响: A 从 D1 移到 D3，D1 -60t / D3 +60t
+    let impact = resp.recommendations[0]
+        .predicted_impact
+        .as_ref()
+        .expect("缺少预计影响");
+    assert_eq!(impact.moved_count, 1);
+    let deltas: Vec<(NaiveDate, f64)> = impact
+        .capacity_changes
+        .iter()
+        .map(|c| (c.date, c.delta_t))
+        .collect();
+    assert!(deltas.contains(&(day(1), -60.0)));
+    assert!(deltas.contains(&(day(3), 60.0)));
+
+    let roll = &resp.recommendations[3];
+    let roll_impact = roll.predicted_impact.as_ref().unwrap();
+    assert!(roll_impact.roll_campaign_affected);
+    assert_eq!(
+        roll.roll_change_to.as_deref(),
+        Some(format!("{} 08:00:00", day(1)).as_str())
+    );
+
+    // 机组过滤：其他机组无建议
+    let other = env
+        .bottleneck_optimizer_api
+        .list_bottleneck_recommendations(&version_id, day(1), day(4), Some("H033"))
+        .expect("生成建议失败");
+    assert!(other.recommendations.is_empty());
+    assert_eq!(other.bottleneck_day_count, 0);
+}
+
+#[test]
+fn test_apply_recommendations_in_one_call() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_bottleneck(&env);
+
+    let resp = env
+        .bottleneck_optimizer_api
+        .list_bottleneck_recommendations(&version_id, day(1), day(4), None)
+        .expect("生成建议失败");
+    let mut ids: Vec<String> = resp
+        .recommendations
+        .iter()
+        .map(|r| r.recommendation_id.clone())
+        .collect();
+    ids.push("MOVE_ITEM:B:H032:2000-01-01".to_string());
+
+    let applied = env
+        .bottleneck_optimizer_api
+        .apply_bottleneck_recommendations(
+            &version_id,
+            day(1),
+            day(4),
+            None,
+            &ids,
+            "planner",
+            "一键优化",
+        )
+        .expect("执行建议失败");
+
+    assert_eq!(applied.applied_ids.len(), 4);
+    assert_eq!(
+        (
+            applied.moved_count,
+            applied.force_released_count,
+            applied.path_override_confirmed_count,
+            applied.roll_change_shifted_count
+        ),
+        (1, 1, 1, 1)
+    );
+    assert_eq!(applied.skipped.len(), 1);
+    assert_eq!(
+        applied.skipped[0].recommendation_id,
+        "MOVE_ITEM:B:H032:2000-01-01"
+    );
+
+    // 移动走 move_items：人工来源 + 追加到目标日末尾 + MOVE_ITEMS 日志
+    let items = env
+        .plan_item_repo
+        .find_by_version(&version_id)
+        .expect("查询计划失败");
+    let a = items.iter().find(|i| i.material_id == "A").unwrap();
+    assert_eq!((a.plan_date, a.source_type.as_str()), (day(3), "MANUAL"));
+    let logs = env
+        .action_log_repo
+        .find_by_version_id(&version_id)
+        .expect("查询日志失败");
+    let move_log = logs
+        .iter()
+        .find(|l| l.action_type == "MOVE_ITEMS")
+        .expect("缺少移动日志");
+    assert!(move_log.detail.as_deref().unwrap().contains("瓶颈优化建议"));
+
+    let r = env.material_state_repo.find_by_id("R").unwrap().unwrap();
+    assert_eq!(r.sched_state, SchedState::ForceRelease);
+    let p = env.material_state_repo.find_by_id("P").unwrap().unwrap();
+    assert!(p.user_confirmed);
+    let roll = env
+        .roll_plan_repo
+        .find_by_key(&version_id, "H032")
+        .unwrap()
+        .unwrap();
+    assert_eq!(roll.next_change_at, Some(format!("{} 08:00:00", day(1))));
+    assert_eq!(roll.downtime_minutes, Some(240));
+
+    // 执行后瓶颈消除，不再有建议
+    let after = env
+        .bottleneck_optimizer_api
+        .list_bottleneck_recommendations(&version_id, day(1), day(4), None)
+        .expect("生成建议失败");
+    assert_eq!(after.bottleneck_day_count, 0);
+    assert!(after.recommendations.is_empty());
+
+    // 重复执行：建议均已失效
+    let again = env
+        .bottleneck_optimizer_api
+        .apply_bottleneck_recommendations(
+            &version_id,
+            day(1),
+            day(4),
+            None,
+            &ids[..1],
+            "planner",
+            "一键优化",
+        )
+        .expect("执行建议失败");
+    assert!(again.applied_ids.is_empty());
+    assert_eq!(again.skipped.len(), 1);
+}
+
+#[test]
+fn test_bottleneck_recommendation_validation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_bottleneck(&env);
+    let api = &env.bottleneck_optimizer_api;
+
+    assert_invalid_input(api.list_bottleneck_recommendations(&version_id, day(2), day(1), None));
+    assert_invalid_input(api.list_bottleneck_recommendations(&version_id, day(1), day(61), None));
+    assert_invalid_input(api.list_bottleneck_recommendations(" ", day(1), day(2), None));
+    assert!(matches!(
+        api.list_bottleneck_recommendations("missing", day(1), day(2), None),
+        Err(ApiError::NotFound(_))
+    ));
+
+    let ids = vec!["MOVE_ITEM:A:H032:x".to_string()];
+    assert_invalid_input(api.apply_bottleneck_recommendations(
+        &version_id,
+        day(1),
+        day(4),
+        None,
+        &[],
+        "planner",
+        "一键优化",
+    ));
+    assert_invalid_input(api.apply_bottleneck_recommendations(
+        &version_id,
+        day(1),
+        day(4),
+        None,
+        &ids,
+        "planner",
+        " ",
+    ));
+}
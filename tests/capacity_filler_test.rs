@@ -2,15 +2,15 @@
 // CapacityFiller 引擎集成测试
 // ==========================================
 // 测试目标: 验证产能池填充逻辑
-// 覆盖范围: 产能约束、冻结区保护、锁定材料处理
+// 覆盖范围: 产能约束、冻结区保护、锁定材料处理、产能预留
 // ==========================================
 
 use chrono::{NaiveDate, Utc};
-use hot_rolling_aps::domain::capacity::CapacityPool;
+use hot_rolling_aps::domain::capacity::{CapacityPool, CapacityReservation};
 use hot_rolling_aps::domain::material::{MaterialMaster, MaterialState};
 use hot_rolling_aps::domain::plan::PlanItem;
 use hot_rolling_aps::domain::types::{RushLevel, SchedState, UrgentLevel};
-use hot_rolling_aps::engine::CapacityFiller;
+use hot_rolling_aps::engine::{CapacityFiller, ReservationHold};
 
 // ==========================================
 // 测试辅助函数
@@ -94,6 +94,38 @@ fn create_test_material(
     (master, state)
 }
 
+/// 创建测试用的产能预留（按合同号前缀 EX 预留）
+fn create_test_reservation(daily_reserved_t: f64) -> CapacityReservation {
+    CapacityReservation {
+        reservation_id: "RSV001".to_string(),
+        machine_code: "H032".to_string(),
+        date_from: NaiveDate::from_ymd_opt(2026, 1, 20).unwrap(),
+        date_to: NaiveDate::from_ymd_opt(2026, 1, 20).unwrap(),
+        daily_reserved_t,
+        contract_no: None,
+        contract_prefix: Some("EX".to_string()),
+        release_days_before: 0,
+        expires_on: None,
+        reason: None,
+        created_by: "TEST".to_string(),
+        created_at: NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap(),
+    }
+}
+
+/// 预留测试候选：普通合同 70+30 吨在前，出口合同 EX 40 吨在后
+fn create_reservation_candidates() -> Vec<(MaterialMaster, MaterialState)> {
+    let mut export = create_test_material("MAT003", 40.0, SchedState::Ready);
+    export.0.contract_no = Some("EX2026001".to_string());
+    vec![
+        create_test_material("MAT001", 70.0, SchedState::Ready),
+        create_test_material("MAT002", 30.0, SchedState::Ready),
+        export,
+    ]
+}
+
 // ==========================================
 // 测试用例 1: 基本填充功能
 // ==========================================
@@ -457,3 +489,87 @@ fn test_capacity_filler_empty_candidates() {
 
     println!("=== 测试通过 ===\n");
 }
+
+// ==========================================
+// 测试用例 8: 产能预留保留期内为匹配材料保留吨位
+// ==========================================
+
+#[test]
+fn test_capacity_filler_reservation_held_for_matching_contract() {
+    println!("\n=== 测试：产能预留保留期 ===");
+
+    let filler = CapacityFiller::new();
+    let mut capacity_pool = create_test_capacity_pool(100.0, 120.0);
+    let mut holds = vec![ReservationHold::new(create_test_reservation(50.0), true)];
+
+    let result = filler.fill_single_day_with_reservations(
+        &mut capacity_pool,
+        &create_reservation_candidates(),
+        vec![],
+        "V001",
+        None,
+        None,
+        None,
+        &mut holds,
+    );
+
+    // 一般需求可用 120 - 50 = 70 吨：MAT001 排入，MAT002 因预留被跳过；出口材料占用预留
+    let ids: Vec<&str> = result
+        .plan_items
+        .iter()
+        .map(|i| i.material_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["MAT001", "MAT003"]);
+    assert_eq!(result.skipped_materials.len(), 1);
+    assert_eq!(result.skipped_materials[0].0.material_id, "MAT002");
+    assert!(result.skipped_materials[0].2.contains("CAPACITY_RESERVED"));
+    assert_eq!(
+        result.plan_items[1].assign_reason.as_deref(),
+        Some("FILL_RESERVATION")
+    );
+    assert_eq!(holds[0].used_t, 40.0);
+    assert_eq!(holds[0].remaining_t(), 10.0);
+    assert_eq!(capacity_pool.used_capacity_t, 110.0);
+
+    println!("=== 测试通过 ===\n");
+}
+
+// ==========================================
+// 测试用例 9: 过释放点后未用预留释放给一般需求
+// ==========================================
+
+#[test]
+fn test_capacity_filler_reservation_released_after_release_point() {
+    println!("\n=== 测试：产能预留释放 ===");
+
+    let filler = CapacityFiller::new();
+    let mut capacity_pool = create_test_capacity_pool(100.0, 120.0);
+    let mut holds = vec![ReservationHold::new(create_test_reservation(50.0), false)];
+
+    let result = filler.fill_single_day_with_reservations(
+        &mut capacity_pool,
+        &create_reservation_candidates(),
+        vec![],
+        "V001",
+        None,
+        None,
+        None,
+        &mut holds,
+    );
+
+    // 预留已释放：按普通规则填充至 limit，出口材料超限被跳过
+    let ids: Vec<&str> = result
+        .plan_items
+        .iter()
+        .map(|i| i.material_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["MAT001", "MAT002"]);
+    assert_eq!(result.skipped_materials.len(), 1);
+    assert!(result.skipped_materials[0]
+        .2
+        .contains("CAPACITY_LIMIT_EXCEEDED"));
+    assert_eq!(holds[0].used_t, 0.0);
+    assert_eq!(capacity_pool.used_capacity_t, 100.0);
+
+    println!("=== 测试通过 ===\n");
+}
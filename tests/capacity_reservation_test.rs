@@ -0,0 +1,217 @@
+// ==========================================
+// 产能预留 集成测试
+// ==========================================
+// 测试范围:
+// 1. 重算时保留期内的预留吨位仅供匹配合同使用，一般需求被挤到后续日期
+// 2. 到达释放点的未用预留释放给一般需求，利用率按版本汇报
+// 3. 已失效的预留不参与重算
+// 4. 创建参数校验，取消后历史利用率仍可查询
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, NaiveDate};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::MaterialBuilder;
+use hot_rolling_aps::api::{ApiError, CapacityReservationApi, CreateCapacityReservationRequest};
+use hot_rolling_aps::domain::types::SchedState;
+
+fn base_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+}
+
+fn day(offset: i64) -> NaiveDate {
+    base_date() + Duration::days(offset)
+}
+
+fn build_api(env: &ApiTestEnv) -> CapacityReservationApi {
+    CapacityReservationApi::new(env.capacity_pool_repo.clone(), env.action_log_repo.clone())
+}
+
+fn request(
+    date_from: NaiveDate,
+    date_to: NaiveDate,
+    daily_reserved_t: f64,
+    contract_prefix: Option<&str>,
+) -> CreateCapacityReservationRequest {
+    CreateCapacityReservationRequest {
+        machine_code: "H032".to_string(),
+        date_from,
+        date_to,
+        daily_reserved_t,
+        contract_no: None,
+        contract_prefix: contract_prefix.map(str::to_string),
+        release_days_before: Some(0),
+        expires_on: None,
+        reason: Some("出口客户".to_string()),
+        operator: "sales".to_string(),
+    }
+}
+
+/// H032: 6 块紧急普通合同材料（各 600t）+ 1 块远期出口合同材料（400t），激活 V1
+fn prepare(env: &ApiTestEnv) -> String {
+    let mut masters: Vec<_> = (1..=6)
+        .map(|i| {
+            let mut m = MaterialBuilder::new(&format!("G{}", i))
+                .machine("H032")
+                .weight(600.0)
+                .output_age_days(30)
+                .due_date(base_date())
+                .build();
+            m.contract_no = Some(format!("DM2026{:03}", i));
+            m
+        })
+        .collect();
+    let mut export = MaterialBuilder::new("E1")
+        .machine("H032")
+        .weight(400.0)
+        .output_age_days(30)
+        .due_date(day(30))
+        .build();
+    export.contract_no = Some("EX2026001".to_string());
+    masters.push(export);
+
+    let states = masters
+        .iter()
+        .map(|m| create_test_state(&m.material_id, SchedState::Ready, 0))
+        .collect();
+    env.prepare_materials(masters, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("产能预留方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let v1 = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.approve_version(&v1).expect("审批失败");
+    env.plan_api
+        .activate_version(&v1, "admin")
+        .expect("激活版本失败");
+    v1
+}
+
+#[test]
+fn test_recalc_holds_reserved_capacity_and_reports_utilization() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let api = build_api(&env);
+    let v1 = prepare(&env);
+
+    // D0 已到释放点（释放给一般需求），D1 仍在保留期
+    let reservation = api
+        .create_reservation(request(day(0), day(1), 500.0, Some("EX")))
+        .expect("创建预留失败");
+    // 已失效的预留不参与重算
+    let mut expired = request(day(0), day(1), 800.0, Some("EX"));
+    expired.expires_on = Some(day(-1));
+    let expired = api.create_reservation(expired).expect("创建预留失败");
+
+    let v2 = env
+        .plan_api
+        .recalc_full(&v1, base_date(), None, "admin")
+        .expect("重算失败")
+        .version_id;
+
+    let items = env.plan_api.list_plan_items(&v2).expect("查询明细失败");
+    let on = |date: NaiveDate| -> Vec<&str> {
+        items
+            .iter()
+            .filter(|i| i.machine_code == "H032" && i.plan_date == date)
+            .map(|i| i.material_id.as_str())
+            .collect()
+    };
+
+    // D0: 紧急普通合同占满（3 x 600t），出口材料超上限未排
+    assert_eq!(on(day(0)).len(), 3);
+    assert!(!on(day(0)).contains(&"E1"));
+
+    // D1: 保留 500t，普通合同只能排 2 块；出口材料占用预留
+    let d1 = on(day(1));
+    assert!(d1.contains(&"E1"), "出口材料应占用D1预留: {:?}", d1);
+    assert_eq!(d1.iter().filter(|id| id.starts_with('G')).count(), 2);
+    let e1 = items.iter().find(|i| i.material_id == "E1").unwrap();
+    assert_eq!(e1.assign_reason.as_deref(), Some("FILL_RESERVATION"));
+
+    // 被预留挤出的普通材料顺延到 D2
+    assert_eq!(on(day(2)).len(), 1);
+
+    let report = api
+        .get_reservation_utilization(&v2)
+        .expect("查询利用率失败");
+    assert_eq!(report.reservations.len(), 1);
+    let r = &report.reservations[0];
+    assert_eq!(r.reservation_id, reservation.reservation_id);
+    assert_eq!(r.daily.len(), 2);
+    assert!(!r.daily[0].held && r.daily[1].held);
+    assert_eq!(r.reserved_t, 1000.0);
+    assert_eq!(r.used_t, 400.0);
+    assert_eq!(r.released_t, 500.0);
+    assert_eq!(r.held_unused_t, 100.0);
+    assert!((report.utilization_pct - 40.0).abs() < 1e-9);
+    assert!(report
+        .reservations
+        .iter()
+        .all(|r| r.reservation_id != expired.reservation_id));
+}
+
+#[test]
+fn test_reservation_validation_and_cancel() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let api = build_api(&env);
+    let v1 = prepare(&env);
+
+    let err = api
+        .create_reservation(request(day(0), day(1), 500.0, None))
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidInput(_)));
+    let err = api
+        .create_reservation(request(day(0), day(1), 0.0, Some("EX")))
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidInput(_)));
+    let err = api
+        .create_reservation(request(day(1), day(0), 500.0, Some("EX")))
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidInput(_)));
+
+    let reservation = api
+        .create_reservation(request(day(1), day(1), 500.0, Some("EX")))
+        .expect("创建预留失败");
+    assert_eq!(api.list_reservations(Some("H032")).unwrap().len(), 1);
+    assert!(api.list_reservations(Some("H033")).unwrap().is_empty());
+
+    let v2 = env
+        .plan_api
+        .recalc_full(&v1, base_date(), None, "admin")
+        .expect("重算失败")
+        .version_id;
+
+    api.cancel_reservation(&reservation.reservation_id, "sales")
+        .expect("取消预留失败");
+    assert!(api.list_reservations(None).unwrap().is_empty());
+    assert!(matches!(
+        api.cancel_reservation(&reservation.reservation_id, "sales"),
+        Err(ApiError::NotFound(_))
+    ));
+
+    // 取消后历史版本的利用率快照仍保留
+    let report = api
+        .get_reservation_utilization(&v2)
+        .expect("查询利用率失败");
+    assert_eq!(report.reservations.len(), 1);
+    assert!(report.reservations[0].reservation.is_none());
+    assert_eq!(report.total_used_t, 400.0);
+
+    let logs = env
+        .action_log_repo
+        .find_recent(20)
+        .expect("查询日志失败");
+    assert!(logs
+        .iter()
+        .any(|l| l.action_type == "CREATE_CAPACITY_RESERVATION"));
+    assert!(logs
+        .iter()
+        .any(|l| l.action_type == "CANCEL_CAPACITY_RESERVATION"));
+}
@@ -0,0 +1,224 @@
+// ==========================================
+// 现场变更通知 集成测试
+// ==========================================
+// 测试范围:
+// 1. 激活时自动生成变更通知并随 ACTIVATE_VERSION 日志保存
+// 2. 新增/移出/调整/换序 分类及原因
+// 3. 按机组筛选、导出 CSV / XLSX
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::PlanItemBuilder;
+use hot_rolling_aps::domain::types::SchedState;
+use hot_rolling_aps::engine::ChangeNoticeType;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2030, 6, day).unwrap()
+}
+
+const OVERRIDE: Option<&str> = Some("测试数据，红线校验另测");
+
+/// 返回 (V1, V2)，两版本均已激活（V2 为当前激活版本）
+///
+/// - V1: 1日 M1,M2,M5; 2日 M3
+/// - V2: 1日 M4(人工插单),M5,M1,M3; M2 已完工移出
+fn prepare_activated_versions(env: &ApiTestEnv) -> (String, String) {
+    let ids = ["M1", "M2", "M3", "M4", "M5"];
+    let materials = ids
+        .iter()
+        .map(|id| create_test_material(id, "H032", 10.0, None))
+        .collect();
+    let states = ids
+        .iter()
+        .map(|id| {
+            let state = if *id == "M2" {
+                SchedState::Completed
+            } else {
+                SchedState::Ready
+            };
+            create_test_state(id, state, 0)
+        })
+        .collect();
+    env.prepare_materials(materials, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("变更通知测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let create = || {
+        env.plan_api
+            .create_version(plan_id.clone(), 7, None, None, "admin".to_string())
+            .expect("创建版本失败")
+    };
+    let layout = |version: &str, rows: &[(&str, NaiveDate, i32, &str)]| {
+        let items: Vec<_> = rows
+            .iter()
+            .map(|(id, date, seq, source)| {
+                PlanItemBuilder::new(version, id, "H032", *date)
+                    .seq_no(*seq)
+                    .weight(10.0)
+                    .source_type(source)
+                    .build()
+            })
+            .collect();
+        env.plan_item_repo
+            .batch_insert(&items)
+            .expect("插入计划失败");
+    };
+
+    let v1 = create();
+    layout(
+        &v1,
+        &[
+            ("M1", d(1), 1, "CALC"),
+            ("M2", d(1), 2, "CALC"),
+            ("M5", d(1), 3, "CALC"),
+            ("M3", d(2), 1, "CALC"),
+        ],
+    );
+    env.approve_version(&v1).expect("审批失败");
+    env.plan_api
+        .activate_version_with_override(&v1, "manager", OVERRIDE)
+        .expect("激活V1失败");
+
+    let v2 = create();
+    layout(
+        &v2,
+        &[
+            ("M4", d(1), 1, "MANUAL"),
+            ("M5", d(1), 2, "CALC"),
+            ("M1", d(1), 3, "CALC"),
+            ("M3", d(1), 4, "CALC"),
+        ],
+    );
+    env.approve_version(&v2).expect("审批失败");
+    env.plan_api
+        .activate_version_with_override(&v2, "manager", OVERRIDE)
+        .expect("激活V2失败");
+
+    (v1, v2)
+}
+
+#[test]
+fn test_activation_stores_change_notice() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (v1, v2) = prepare_activated_versions(&env);
+
+    // 首次激活：全部视为新增
+    let first = env
+        .plan_api
+        .get_activation_change_notice(&v1)
+        .expect("查询V1变更通知失败");
+    assert!(first.from_version_id.is_none());
+    assert_eq!(first.added_count, 4);
+
+    let notice = env
+        .plan_api
+        .get_activation_change_notice(&v2)
+        .expect("查询V2变更通知失败");
+    assert_eq!(notice.from_version_id.as_deref(), Some(v1.as_str()));
+    assert_eq!(
+        (
+            notice.added_count,
+            notice.removed_count,
+            notice.moved_count,
+            notice.resequenced_count
+        ),
+        (1, 1, 1, 1)
+    );
+
+    let find = |material_id: &str| {
+        notice
+            .lines
+            .iter()
+            .find(|l| l.material_id == material_id)
+            .unwrap_or_else(|| panic!("缺少{}的变更", material_id))
+    };
+    assert_eq!(find("M4").change_type, ChangeNoticeType::Added);
+    assert!(find("M4").reason.starts_with("人工插单"));
+    assert_eq!(find("M2").change_type, ChangeNoticeType::Removed);
+    assert_eq!(find("M2").reason, "已完工（实绩回填）");
+    assert_eq!(find("M3").change_type, ChangeNoticeType::Moved);
+    assert_eq!(find("M3").from_plan_date, Some(d(2)));
+    assert!(find("M3").reason.starts_with("提前"));
+    assert_eq!(find("M1").change_type, ChangeNoticeType::Resequenced);
+    assert!(notice.lines.iter().all(|l| l.material_id != "M5"));
+
+    // 2日仅有 M3 移出
+    let day2 = notice
+        .machine_days
+        .iter()
+        .find(|m| m.plan_date == d(2))
+        .expect("缺少2日汇总");
+    assert_eq!(day2.moved_out_count, 1);
+
+    // 激活日志摘要
+    let logs = env
+        .action_log_repo
+        .find_by_action_type("ACTIVATE_VERSION", 10)
+        .expect("查询 action_log 失败");
+    let log = logs
+        .iter()
+        .find(|l| l.version_id.as_deref() == Some(v2.as_str()))
+        .expect("缺少V2激活日志");
+    let impact = log.impact_summary_json.as_ref().expect("缺少影响摘要");
+    assert_eq!(
+        impact["change_notice"]["resequenced_count"].as_u64(),
+        Some(1)
+    );
+
+    // 按机组筛选
+    let other = notice.filtered(Some("H033"), None, None);
+    assert!(other.lines.is_empty());
+    assert_eq!(other.added_count, 0);
+}
+
+#[test]
+fn test_export_change_notice_csv_and_xlsx() {
+    use calamine::{Reader, Xlsx};
+
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (_v1, v2) = prepare_activated_versions(&env);
+    let dir = tempfile::tempdir().expect("创建临时目录失败");
+
+    let csv_path = dir.path().join("notice.csv");
+    let resp = env
+        .plan_api
+        .export_change_notice(&v2, "csv", csv_path.to_str().unwrap(), None, None, None)
+        .expect("导出CSV失败");
+    assert_eq!(resp.line_count, 4);
+    let text = std::fs::read_to_string(&csv_path).expect("读取CSV失败");
+    assert!(text.starts_with('\u{feff}'));
+    assert!(text.contains("M2"));
+    assert!(text.contains("已完工（实绩回填）"));
+
+    // 仅导出 2日（M3 原日期）
+    let xlsx_path = dir.path().join("notice.xlsx");
+    let resp = env
+        .plan_api
+        .export_change_notice(
+            &v2,
+            "XLSX",
+            xlsx_path.to_str().unwrap(),
+            Some("H032"),
+            Some(d(2)),
+            Some(d(2)),
+        )
+        .expect("导出XLSX失败");
+    assert_eq!(resp.line_count, 1);
+    let mut workbook: Xlsx<_> = calamine::open_workbook(&xlsx_path).expect("打开XLSX失败");
+    let sheet = workbook.sheet_names()[0].clone();
+    let range = workbook.worksheet_range(&sheet).expect("读取工作表失败");
+    assert_eq!(range.get_size().0, 2);
+    assert_eq!(range.get_value((1, 3)).unwrap().to_string(), "M3");
+
+    assert!(env
+        .plan_api
+        .export_change_notice(&v2, "pdf", csv_path.to_str().unwrap(), None, None, None)
+        .is_err());
+}
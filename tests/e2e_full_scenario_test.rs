@@ -114,6 +114,14 @@ async fn test_full_scenario_workflow() {
 
     println!("版本创建成功：{}", version_id);
 
+    app_state
+        .version_approval_api
+        .submit_version(&version_id, None, &created_by)
+        .expect("提交审批失败");
+    app_state
+        .version_approval_api
+        .approve_version(&version_id, None, "manager")
+        .expect("审批失败");
     app_state
         .plan_api
         .activate_version(&version_id, &created_by)
@@ -11,7 +11,9 @@ mod test_helpers;
 #[cfg(test)]
 mod e2e_p0_p1_features_test {
     use chrono::{Duration, NaiveDate, Utc};
-    use hot_rolling_aps::api::{ConfigApi, MaterialApi, PlanApi, ValidationMode};
+    use hot_rolling_aps::api::{
+        ActivationRedLineValidator, ConfigApi, MaterialApi, PlanApi, ValidationMode,
+    };
     use hot_rolling_aps::config::config_manager::ConfigManager;
     use hot_rolling_aps::domain::material::{MaterialMaster, MaterialState};
     use hot_rolling_aps::domain::types::{RushLevel, SchedState, UrgentLevel};
@@ -69,6 +71,14 @@ mod e2e_p0_p1_features_test {
             Arc::new(CapacityPoolRepository::new(db_path.to_string()).unwrap());
         let roller_campaign_repo = Arc::new(RollerCampaignRepository::new(&db_path).unwrap());
         let path_override_pending_repo = Arc::new(PathOverridePendingRepository::new(conn.clone()));
+        let activation_validator = Arc::new(ActivationRedLineValidator::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            material_state_repo.clone(),
+            capacity_pool_repo.clone(),
+            roller_campaign_repo.clone(),
+            path_override_pending_repo.clone(),
+        ));
 
         // 创建engines
         let config_manager = Arc::new(ConfigManager::new(&db_path).unwrap());
@@ -127,6 +137,7 @@ mod e2e_p0_p1_features_test {
             config_manager.clone(),
             recalc_engine,
             risk_engine,
+            activation_validator,
             None, // refresh_queue (not needed in tests)
         ));
 
@@ -425,7 +436,7 @@ mod e2e_p0_p1_features_test {
 
     #[test]
     fn test_e2e_complete_scheduling_flow() {
-        let (_temp_file, _db_path, plan_api, material_api, config_api, _config_manager) =
+        let (_temp_file, db_path, plan_api, material_api, config_api, _config_manager) =
             setup_test_env();
 
         // 1. 配置系统参数
@@ -467,6 +478,7 @@ mod e2e_p0_p1_features_test {
         assert!(items_result.is_ok(), "查询排产明细应该成功");
 
         // 7. 激活版本
+        test_helpers::approve_version_for_activation(&db_path, &version_id).unwrap();
         let activate_result = plan_api.activate_version(&version_id, "test_user");
         assert!(activate_result.is_ok(), "激活版本应该成功");
 
@@ -553,6 +565,7 @@ mod e2e_p0_p1_features_test {
             )
             .unwrap();
 
+        test_helpers::approve_version_for_activation(&db_path, &version_id).unwrap();
         plan_api.activate_version(&version_id, "test_user").unwrap();
 
         // 2. 生成草案（dry-run，不落库）
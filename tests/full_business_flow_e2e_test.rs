@@ -13,7 +13,7 @@ mod full_business_flow_e2e_test {
     use crate::test_helpers;
     use crate::test_helpers::create_test_db;
     use chrono::{Duration, NaiveDate};
-    use hot_rolling_aps::api::{DashboardApi, MaterialApi, PlanApi};
+    use hot_rolling_aps::api::{ActivationRedLineValidator, DashboardApi, MaterialApi, PlanApi};
     use hot_rolling_aps::config::config_manager::ConfigManager;
     use hot_rolling_aps::decision::api::{DecisionApi, DecisionApiImpl};
     use hot_rolling_aps::decision::repository::{BottleneckRepository, DaySummaryRepository};
@@ -84,6 +84,14 @@ mod full_business_flow_e2e_test {
         let capacity_pool_repo = Arc::new(CapacityPoolRepository::new(db_path.clone()).unwrap());
         let roller_campaign_repo = Arc::new(RollerCampaignRepository::new(&db_path).unwrap());
         let path_override_pending_repo = Arc::new(PathOverridePendingRepository::new(conn.clone()));
+        let activation_validator = Arc::new(ActivationRedLineValidator::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            material_state_repo.clone(),
+            capacity_pool_repo.clone(),
+            roller_campaign_repo.clone(),
+            path_override_pending_repo.clone(),
+        ));
 
         // === Engine 层 ===
         let config_manager = Arc::new(ConfigManager::new(&db_path).unwrap());
@@ -165,6 +173,7 @@ mod full_business_flow_e2e_test {
             config_manager.clone(),
             recalc_engine,
             risk_engine,
+            activation_validator,
             None, // refresh_queue (not needed in tests)
         ));
 
@@ -0,0 +1,416 @@
+// ==========================================
+// 钢种族最小批量（集批）测试
+// ==========================================
+// 测试范围:
+// 1. 候选不足最小块数时整族顺延，跳过原因为 GRADE_BATCHING
+// 2. 同族材料聚集为连续批次入池
+// 3. 当日产能装不下最小批量时整族顺延，释放的产能按原始产能池重新填充
+// 4. 锁定/L3/强制放行材料不可顺延，但计入批量
+// 5. 重算落库顺延记录，D2 失败原因/阻塞因素包含批量原因
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::mock_config::MockConfig;
+use helpers::test_data_builder::*;
+use hot_rolling_aps::config::ConfigManager;
+use hot_rolling_aps::db::open_sqlite_connection;
+use hot_rolling_aps::decision::services::{DecisionRefreshService, RefreshScope, RefreshTrigger};
+use hot_rolling_aps::domain::batching::{GradeBatchingRule, GRADE_BATCHING_REASON};
+use hot_rolling_aps::domain::material::{MaterialMaster, MaterialState};
+use hot_rolling_aps::domain::types::{SchedState, UrgentLevel};
+use hot_rolling_aps::engine::{GradeBatchingEnforcer, ScheduleOrchestrator, ScheduleResult};
+use std::collections::HashMap;
+
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 1, 20).unwrap()
+}
+
+fn pickled_rule(min_run_t: Option<f64>, min_run_coils: Option<u32>) -> GradeBatchingRule {
+    GradeBatchingRule {
+        family: "PICKLED".to_string(),
+        steel_mark_prefixes: vec!["SPHC".to_string()],
+        min_run_t,
+        min_run_coils,
+        machine_codes: vec![],
+    }
+}
+
+/// (材料ID, 出钢记号, 吨位, 库存天数)：平衡策略下库存天数大的优先
+fn material(
+    id: &str,
+    steel_mark: &str,
+    weight: f64,
+    stock_age_days: i32,
+) -> (MaterialMaster, MaterialState) {
+    (
+        MaterialBuilder::new(id)
+            .steel_mark(steel_mark)
+            .weight(weight)
+            .output_age_days(5)
+            .machine("H032")
+            .due_date(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap())
+            .build(),
+        MaterialStateBuilder::new(id)
+            .stock_age_days(stock_age_days)
+            .build(),
+    )
+}
+
+async fn run(
+    rule: GradeBatchingRule,
+    materials: Vec<(MaterialMaster, MaterialState)>,
+    target: f64,
+    limit: f64,
+) -> ScheduleResult {
+    let orchestrator = ScheduleOrchestrator::new(Arc::new(MockConfig::default()))
+        .with_grade_batching_rules(vec![rule]);
+    let (masters, states): (Vec<_>, Vec<_>) = materials.into_iter().unzip();
+    let mut capacity_pool = create_capacity_pool("H032", today(), target, limit, 0.0);
+    orchestrator
+        .execute_single_day_schedule(
+            masters,
+            states,
+            &mut capacity_pool,
+            vec![],
+            &HashMap::new(),
+            0.15,
+            today(),
+            "v1",
+        )
+        .await
+        .expect("排产失败")
+}
+
+fn placed_ids(result: &ScheduleResult) -> Vec<&str> {
+    let mut items: Vec<_> = result.plan_items.iter().collect();
+    items.sort_by_key(|i| i.seq_no);
+    items.iter().map(|i| i.material_id.as_str()).collect()
+}
+
+fn batching_skips(result: &ScheduleResult) -> Vec<(&str, &str)> {
+    result
+        .skipped_materials
+        .iter()
+        .filter(|(_, _, reason)| reason.starts_with(GRADE_BATCHING_REASON))
+        .map(|(m, _, reason)| (m.material_id.as_str(), reason.as_str()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_family_short_of_material_is_deferred() {
+    let result = run(
+        pickled_rule(None, Some(3)),
+        vec![
+            material("P1", "SPHC", 100.0, 20),
+            material("P2", "SPHC-P", 100.0, 18),
+            material("Q1", "Q235", 100.0, 15),
+            material("Q2", "Q235", 100.0, 12),
+            material("Q3", "Q345", 100.0, 10),
+        ],
+        800.0,
+        900.0,
+    )
+    .await;
+
+    assert_eq!(placed_ids(&result), vec!["Q1", "Q2", "Q3"]);
+    let skips = batching_skips(&result);
+    assert_eq!(skips.len(), 2);
+    assert!(skips
+        .iter()
+        .all(|(_, r)| r.contains("family=PICKLED") && r.contains("INSUFFICIENT_MATERIAL")));
+    assert_eq!(result.batching_deferred.len(), 2);
+    assert!(result
+        .batching_deferred
+        .iter()
+        .all(|d| d.grade_family == "PICKLED"));
+    assert_eq!(result.updated_capacity_pool.used_capacity_t, 300.0);
+}
+
+#[tokio::test]
+async fn test_family_members_are_gathered_into_one_run() {
+    let result = run(
+        pickled_rule(None, Some(2)),
+        vec![
+            material("P1", "SPHC", 100.0, 20),
+            material("Q1", "Q235", 100.0, 15),
+            material("P2", "SPHC", 100.0, 10),
+            material("Q2", "Q235", 100.0, 5),
+        ],
+        800.0,
+        900.0,
+    )
+    .await;
+
+    assert_eq!(placed_ids(&result), vec!["P1", "P2", "Q1", "Q2"]);
+    assert!(batching_skips(&result).is_empty());
+    assert!(result.batching_deferred.is_empty());
+}
+
+#[tokio::test]
+async fn test_family_short_of_capacity_is_deferred_and_pool_refilled() {
+    // 材料 600t 足够，但当日只剩 300t：批次 200t < 500t，整族顺延
+    let result = run(
+        pickled_rule(Some(500.0), None),
+        vec![
+            material("Q1", "Q235", 400.0, 30),
+            material("P1", "SPHC", 200.0, 20),
+            material("P2", "SPHC", 200.0, 15),
+            material("P3", "SPHC", 200.0, 10),
+            material("Q2", "Q235", 100.0, 5),
+        ],
+        700.0,
+        700.0,
+    )
+    .await;
+
+    assert_eq!(placed_ids(&result), vec!["Q1", "Q2"]);
+    let skips = batching_skips(&result);
+    assert_eq!(skips.len(), 3);
+    assert!(skips
+        .iter()
+        .all(|(_, r)| r.contains("INSUFFICIENT_CAPACITY")));
+    // 产能池以最终一轮填充为准（不残留顺延前的占用）
+    assert_eq!(result.updated_capacity_pool.used_capacity_t, 500.0);
+}
+
+#[tokio::test]
+async fn test_locked_material_is_never_deferred() {
+    let (p1, _) = material("P1", "SPHC", 100.0, 20);
+    let locked = MaterialStateBuilder::new("P1").locked().build();
+    let result = run(
+        pickled_rule(None, Some(3)),
+        vec![
+            (p1, locked),
+            material("P2", "SPHC", 100.0, 15),
+            material("Q1", "Q235", 100.0, 10),
+        ],
+        800.0,
+        900.0,
+    )
+    .await;
+
+    let placed = placed_ids(&result);
+    assert!(placed.contains(&"P1"), "锁定材料必须入池: {:?}", placed);
+    assert!(!placed.contains(&"P2"));
+    let skipped: Vec<&str> = batching_skips(&result).iter().map(|(id, _)| *id).collect();
+    assert_eq!(skipped, vec!["P2"]);
+}
+
+#[tokio::test]
+async fn test_l3_material_is_never_deferred() {
+    let (p1, _) = material("P1", "SPHC", 100.0, 20);
+    // 人工红线 → L3
+    let mut urgent = MaterialStateBuilder::new("P1").build();
+    urgent.manual_urgent_flag = true;
+    let result = run(
+        pickled_rule(None, Some(3)),
+        vec![
+            (p1, urgent),
+            material("P2", "SPHC", 100.0, 15),
+            material("Q1", "Q235", 100.0, 10),
+        ],
+        800.0,
+        900.0,
+    )
+    .await;
+
+    let placed = placed_ids(&result);
+    assert!(placed.contains(&"P1"), "L3 材料必须入池: {:?}", placed);
+    assert!(!placed.contains(&"P2"));
+    let skipped: Vec<&str> = batching_skips(&result).iter().map(|(id, _)| *id).collect();
+    assert_eq!(skipped, vec!["P2"]);
+}
+
+#[tokio::test]
+async fn test_force_release_material_is_never_deferred() {
+    let (p1, _) = material("P1", "SPHC", 100.0, 20);
+    let released = MaterialStateBuilder::new("P1").force_release().build();
+    let result = run(
+        pickled_rule(None, Some(3)),
+        vec![
+            (p1, released),
+            material("P2", "SPHC", 100.0, 15),
+            material("Q1", "Q235", 100.0, 10),
+        ],
+        800.0,
+        900.0,
+    )
+    .await;
+
+    let placed = placed_ids(&result);
+    assert!(placed.contains(&"P1"), "强制放行材料必须入池: {:?}", placed);
+    assert!(!placed.contains(&"P2"));
+    let skipped: Vec<&str> = batching_skips(&result).iter().map(|(id, _)| *id).collect();
+    assert_eq!(skipped, vec!["P2"]);
+}
+
+#[test]
+fn test_capacity_deferral_requires_deferrable_member() {
+    let enforcer = GradeBatchingEnforcer::new(vec![pickled_rule(Some(500.0), None)]);
+    let (p1, _) = material("P1", "SPHC", 100.0, 20);
+    let (p2, _) = material("P2", "SPHC", 100.0, 15);
+    let mut candidates = vec![
+        (
+            p1,
+            MaterialStateBuilder::new("P1")
+                .urgent_level(UrgentLevel::L3)
+                .build(),
+        ),
+        (p2, MaterialStateBuilder::new("P2").force_release().build()),
+    ];
+    let placed = |candidates: &[(MaterialMaster, MaterialState)]| -> Vec<_> {
+        candidates
+            .iter()
+            .map(|(m, _)| {
+                PlanItemBuilder::new("v1", &m.material_id, "H032", today())
+                    .weight(100.0)
+                    .build()
+            })
+            .collect()
+    };
+
+    // 批次仅含 L3/强制放行材料：不判定产能顺延
+    let short = enforcer.families_short_of_capacity(
+        "H032",
+        &placed(&candidates),
+        &candidates,
+        &HashMap::new(),
+    );
+    assert!(short.is_empty(), "{:?}", short);
+
+    candidates.push(material("P3", "SPHC", 100.0, 10));
+    let short = enforcer.families_short_of_capacity(
+        "H032",
+        &placed(&candidates),
+        &candidates,
+        &HashMap::new(),
+    );
+    assert!(short["PICKLED"].contains("INSUFFICIENT_CAPACITY"));
+}
+
+// ==========================================
+// 重算集成：顺延落库 + D2 解释
+// ==========================================
+
+fn open_conn(env: &ApiTestEnv) -> Arc<Mutex<rusqlite::Connection>> {
+    Arc::new(Mutex::new(
+        open_sqlite_connection(&env.db_path).expect("打开数据库失败"),
+    ))
+}
+
+#[test]
+fn test_recalc_persists_deferrals_and_explains_in_d2() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let base_date = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+
+    let rules = serde_json::to_string(&vec![pickled_rule(None, Some(3))]).unwrap();
+    ConfigManager::from_connection(open_conn(&env))
+        .expect("创建配置失败")
+        .restore_config_from_snapshot(
+            &serde_json::json!({ "grade_batching_rules": rules }).to_string(),
+        )
+        .expect("写入配置失败");
+
+    let masters: Vec<_> = [
+        ("P1", "SPHC"),
+        ("P2", "SPHC"),
+        ("Q1", "Q235"),
+        ("Q2", "Q235"),
+    ]
+    .iter()
+    .map(|(id, mark)| {
+        let mut m = MaterialBuilder::new(id)
+            .machine("H032")
+            .steel_mark(mark)
+            .weight(100.0)
+            .output_age_days(30)
+            .due_date(base_date + chrono::Duration::days(30))
+            .build();
+        m.contract_no = Some(format!("C-{}", id));
+        m
+    })
+    .collect();
+    let states = masters
+        .iter()
+        .map(|m| create_test_state(&m.material_id, SchedState::Ready, 0))
+        .collect();
+    env.prepare_materials(masters, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("集批方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let v1 = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.approve_version(&v1).expect("审批失败");
+    env.plan_api
+        .activate_version(&v1, "admin")
+        .expect("激活版本失败");
+
+    let v2 = env
+        .plan_api
+        .recalc_full(&v1, base_date, None, "admin")
+        .expect("重算失败")
+        .version_id;
+
+    let items = env.plan_api.list_plan_items(&v2).expect("查询明细失败");
+    assert!(items.iter().all(|i| !i.material_id.starts_with('P')));
+    assert_eq!(items.len(), 2);
+
+    let deferrals = env
+        .plan_api
+        .list_batching_deferrals(&v2)
+        .expect("查询顺延失败");
+    let ids: Vec<&str> = deferrals.iter().map(|d| d.material_id.as_str()).collect();
+    assert_eq!(ids, vec!["P1", "P2"]);
+    assert!(deferrals.iter().all(|d| d.grade_family == "PICKLED"
+        && d.machine_code == "H032"
+        && d.reason.starts_with(GRADE_BATCHING_REASON)));
+
+    // D2：紧急合同因批量顺延无法完成
+    let conn = open_conn(&env);
+    conn.lock()
+        .unwrap()
+        .execute(
+            "UPDATE material_state SET contract_no = 'C-P', urgency_level = 'L2', due_date = '2026-06-01', weight_t = 100.0 WHERE material_id IN ('P1', 'P2')",
+            [],
+        )
+        .expect("更新材料状态失败");
+    DecisionRefreshService::new(conn.clone())
+        .refresh_all(
+            RefreshScope {
+                version_id: v2.clone(),
+                is_full_refresh: true,
+                affected_machines: None,
+                affected_date_range: None,
+            },
+            RefreshTrigger::ManualRefresh,
+            Some("test".to_string()),
+        )
+        .expect("刷新失败");
+
+    let (reasons, factors): (String, String) = conn
+        .lock()
+        .unwrap()
+        .query_row(
+            "SELECT failure_reasons, blocking_factors FROM decision_order_failure_set WHERE version_id = ?1 AND contract_no = 'C-P'",
+            [&v2],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("查询D2失败");
+    assert!(
+        reasons.contains("钢种族 PICKLED 未达最小批量，顺延 2 块"),
+        "{}",
+        reasons
+    );
+    assert!(factors.contains("GradeBatching"), "{}", factors);
+}
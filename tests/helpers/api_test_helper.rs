@@ -12,7 +12,9 @@ use std::sync::Arc;
 use tempfile::NamedTempFile;
 
 use hot_rolling_aps::api::{
-    ApiError, ConfigApi, DashboardApi, ManualOperationValidator, MaterialApi, PlanApi, RollerApi,
+    ActivationRedLineValidator, ApiError, BottleneckOptimizerApi, ConfigApi, DashboardApi,
+    InterventionExpiryApi, ManualOperationValidator, MaterialApi, PathRuleApi, PlanApi, RollerApi,
+    ScenarioApi, UndoApi, VersionApprovalApi, VersionLineageApi,
 };
 use hot_rolling_aps::config::config_manager::ConfigManager;
 use hot_rolling_aps::decision::api::{DecisionApi, DecisionApiImpl};
@@ -40,13 +42,17 @@ use hot_rolling_aps::repository::{
     action_log_repo::ActionLogRepository,
     capacity_repo::CapacityPoolRepository,
     decision_refresh_repo::DecisionRefreshRepository,
+    intervention_expiry_repo::InterventionExpiryRepository,
     material_repo::{MaterialMasterRepository, MaterialStateRepository},
     path_override_pending_repo::PathOverridePendingRepository,
     plan_repo::{PlanItemRepository, PlanRepository, PlanVersionRepository},
     risk_repo::RiskSnapshotRepository,
     roll_campaign_plan_repo::RollCampaignPlanRepository,
     roller_repo::RollerCampaignRepository,
+    scenario_repo::ScenarioRepository,
     strategy_draft_repo::StrategyDraftRepository,
+    version_retention_repo::VersionRetentionRepository,
+    version_review_repo::VersionReviewRepository,
 };
 
 use hot_rolling_aps::db::open_sqlite_connection;
@@ -67,6 +73,13 @@ pub struct ApiTestEnv {
     pub dashboard_api: Arc<DashboardApi>,
     pub config_api: Arc<ConfigApi>,
     pub roller_api: Arc<RollerApi>,
+    pub version_approval_api: Arc<VersionApprovalApi>,
+    pub version_lineage_api: Arc<VersionLineageApi>,
+    pub path_rule_api: Arc<PathRuleApi>,
+    pub bottleneck_optimizer_api: Arc<BottleneckOptimizerApi>,
+    pub undo_api: Arc<UndoApi>,
+    pub intervention_expiry_api: Arc<InterventionExpiryApi>,
+    pub scenario_api: Arc<ScenarioApi>,
 
     // Repository层（用于测试数据准备）
     pub material_master_repo: Arc<MaterialMasterRepository>,
@@ -76,6 +89,8 @@ pub struct ApiTestEnv {
     pub plan_item_repo: Arc<PlanItemRepository>,
     pub capacity_pool_repo: Arc<CapacityPoolRepository>,
     pub action_log_repo: Arc<ActionLogRepository>,
+    pub path_override_pending_repo: Arc<PathOverridePendingRepository>,
+    pub roll_plan_repo: Arc<RollCampaignPlanRepository>,
 
     // 临时文件（确保生命周期）
     _temp_file: NamedTempFile,
@@ -133,6 +148,14 @@ impl ApiTestEnv {
                 .map_err(|e| format!("无法创建RollerCampaignRepository: {}", e))?,
         );
         let path_override_pending_repo = Arc::new(PathOverridePendingRepository::new(conn.clone()));
+        let activation_validator = Arc::new(ActivationRedLineValidator::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            material_state_repo.clone(),
+            capacity_pool_repo.clone(),
+            roller_campaign_repo.clone(),
+            path_override_pending_repo.clone(),
+        ));
 
         // ==========================================
         // 初始化Engine层
@@ -211,6 +234,7 @@ impl ApiTestEnv {
             config_manager.clone(),
             recalc_engine,
             risk_engine,
+            activation_validator.clone(),
             event_publisher,
         ));
 
@@ -261,8 +285,99 @@ impl ApiTestEnv {
                 .map_err(|e| format!("无法创建RollCampaignPlanRepository: {}", e))?,
         );
         let roller_api = Arc::new(RollerApi::new(
+            roller_repo.clone(),
+            roll_plan_repo.clone(),
+            action_log_repo.clone(),
+            config_manager.clone(),
+        ));
+
+        // PathRuleApi
+        let path_rule_api = Arc::new(PathRuleApi::new(
+            conn.clone(),
+            config_manager.clone(),
+            plan_item_repo.clone(),
+            material_master_repo.clone(),
+            material_state_repo.clone(),
             roller_repo,
-            roll_plan_repo,
+            roll_plan_repo.clone(),
+            action_log_repo.clone(),
+            path_override_pending_repo.clone(),
+        ));
+
+        // BottleneckOptimizerApi
+        let bottleneck_optimizer_api = Arc::new(BottleneckOptimizerApi::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            capacity_pool_repo.clone(),
+            material_master_repo.clone(),
+            material_state_repo.clone(),
+            path_override_pending_repo.clone(),
+            roll_plan_repo.clone(),
+            config_manager.clone(),
+            plan_api.clone(),
+            material_api.clone(),
+            path_rule_api.clone(),
+            roller_api.clone(),
+        ));
+
+        // UndoApi
+        let undo_api = Arc::new(UndoApi::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            action_log_repo.clone(),
+            plan_api.clone(),
+            material_api.clone(),
+            path_rule_api.clone(),
+        ));
+
+        // InterventionExpiryApi
+        let intervention_expiry_repo = Arc::new(
+            InterventionExpiryRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建InterventionExpiryRepository: {}", e))?,
+        );
+        let intervention_expiry_api = Arc::new(InterventionExpiryApi::new(
+            material_state_repo.clone(),
+            intervention_expiry_repo,
+            action_log_repo.clone(),
+        ));
+
+        // VersionApprovalApi
+        let version_review_repo = Arc::new(
+            VersionReviewRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建VersionReviewRepository: {}", e))?,
+        );
+        let version_approval_api = Arc::new(VersionApprovalApi::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            capacity_pool_repo.clone(),
+            version_review_repo,
+            action_log_repo.clone(),
+            activation_validator.clone(),
+        ));
+
+        // VersionLineageApi
+        let version_retention_repo = Arc::new(
+            VersionRetentionRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建VersionRetentionRepository: {}", e))?,
+        );
+        let version_lineage_api = Arc::new(VersionLineageApi::new(
+            plan_repo.clone(),
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            version_retention_repo,
+        ));
+
+        // ScenarioApi
+        let scenario_repo = Arc::new(
+            ScenarioRepository::from_connection(conn.clone())
+                .map_err(|e| format!("无法创建ScenarioRepository: {}", e))?,
+        );
+        let scenario_api = Arc::new(ScenarioApi::new(
+            scenario_repo,
+            plan_repo.clone(),
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            capacity_pool_repo.clone(),
             action_log_repo.clone(),
             config_manager.clone(),
         ));
@@ -274,6 +389,13 @@ impl ApiTestEnv {
             dashboard_api,
             config_api,
             roller_api,
+            version_approval_api,
+            version_lineage_api,
+            path_rule_api,
+            bottleneck_optimizer_api,
+            undo_api,
+            intervention_expiry_api,
+            scenario_api,
             material_master_repo,
             material_state_repo,
             plan_repo,
@@ -281,10 +403,23 @@ impl ApiTestEnv {
             plan_item_repo,
             capacity_pool_repo,
             action_log_repo,
+            path_override_pending_repo,
+            roll_plan_repo,
             _temp_file: temp_file,
         })
     }
 
+    /// 走完审批流（planner 提交 → manager 审批通过），使版本可激活
+    pub fn approve_version(&self, version_id: &str) -> Result<(), String> {
+        self.version_approval_api
+            .submit_version(version_id, None, "planner")
+            .map_err(|e| format!("提交审批失败: {}", e))?;
+        self.version_approval_api
+            .approve_version(version_id, None, "manager")
+            .map_err(|e| format!("审批失败: {}", e))?;
+        Ok(())
+    }
+
     /// 准备测试材料数据
     ///
     /// # 参数
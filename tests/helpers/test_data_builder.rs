@@ -377,6 +377,11 @@ impl PlanItemBuilder {
         self
     }
 
+    pub fn assign_reason(mut self, reason: Option<&str>) -> Self {
+        self.assign_reason = reason.map(|r| r.to_string());
+        self
+    }
+
     pub fn width_mm(mut self, width: f64) -> Self {
         self.width_mm = Some(width);
         self
@@ -143,3 +143,271 @@ async fn test_import_csv_data_verification() {
         "Should have MAT001 in database"
     );
 }
+
+// ==========================================
+// 流式分块导入
+// ==========================================
+
+/// 写入临时 CSV（复用 fixture 表头）
+fn write_temp_csv(rows: &[&str]) -> tempfile::NamedTempFile {
+    use std::io::Write;
+
+    let fixture = std::fs::read_to_string("tests/fixtures/test_materials.csv").unwrap();
+    let header = fixture.lines().next().unwrap();
+    let mut file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+    writeln!(file, "{}", header).unwrap();
+    for row in rows {
+        writeln!(file, "{}", row).unwrap();
+    }
+    file.flush().unwrap();
+    file
+}
+
+#[tokio::test]
+async fn test_import_streaming_chunks_and_progress() {
+    use hot_rolling_aps::importer::{ImportProgress, ImportStage, StreamingImportOptions};
+    use std::sync::{Arc, Mutex};
+
+    logging::init_test();
+
+    let (_temp_file, db_path) = create_test_db().expect("Failed to create test db");
+    let conn = test_helpers::open_test_connection(&db_path).expect("Failed to open db");
+    insert_test_config(&conn).expect("Failed to insert test config");
+    let importer = create_test_importer(&db_path);
+
+    let seen: Arc<Mutex<Vec<ImportProgress>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    let options = StreamingImportOptions {
+        chunk_size: 2,
+        progress: Some(Arc::new(move |p: &ImportProgress| {
+            sink.lock().unwrap().push(p.clone());
+        })),
+        cancel_token: None,
+        imported_by: None,
+    };
+
+    let result = importer
+        .import_streaming("tests/fixtures/test_materials.csv", options)
+        .await
+        .expect("Streaming import should succeed");
+
+    assert!(!result.cancelled);
+    assert_eq!(result.summary.total_rows, 5);
+
+    let progress = seen.lock().unwrap();
+    let writing: Vec<&ImportProgress> = progress
+        .iter()
+        .filter(|p| p.stage == ImportStage::Writing)
+        .collect();
+    // 5 行 / 每块 2 行 → 3 个分块
+    assert_eq!(writing.len(), 3);
+    assert_eq!(
+        writing.iter().map(|p| p.processed_rows).collect::<Vec<_>>(),
+        vec![2, 4, 5]
+    );
+    assert_eq!(progress.last().unwrap().stage, ImportStage::Completed);
+    assert_eq!(progress.last().unwrap().chunks_committed, 3);
+
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM material_master", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count as usize, result.summary.success);
+}
+
+#[tokio::test]
+async fn test_import_streaming_duplicate_across_chunks() {
+    use hot_rolling_aps::importer::StreamingImportOptions;
+
+    logging::init_test();
+
+    let (_temp_file, db_path) = create_test_db().expect("Failed to create test db");
+    let conn = test_helpers::open_test_connection(&db_path).expect("Failed to open db");
+    insert_test_config(&conn).expect("Failed to insert test config");
+    let importer = create_test_importer(&db_path);
+
+    let csv = write_temp_csv(&[
+        "MATX01,MO001,READY,Q235B,SLAB001,H032,,1500.0,10.0,12.0,15.5,1480.0,2024-02-15,5,2,2024-01-10 10:00:00,CT001,NORMAL,Y,0",
+        "MATX02,MO002,READY,Q345B,SLAB002,H033,,1800.0,12.0,10.0,18.2,1780.0,2024-02-20,3,1,2024-01-12 14:30:00,CT002,URGENT,N,1",
+        "MATX01,MO003,READY,Q420C,SLAB003,H034,,2000.0,15.0,8.0,20.5,1980.0,2024-02-10,8,5,2024-01-05 09:15:00,CT003,NORMAL,Y,0",
+    ]);
+
+    let options = StreamingImportOptions {
+        chunk_size: 2,
+        ..Default::default()
+    };
+    let result = importer
+        .import_streaming(csv.path(), options)
+        .await
+        .expect("Streaming import should succeed");
+
+    // 第 3 行与第 1 分块重复，应进入冲突队列（同批次重复），而非覆盖
+    assert_eq!(result.summary.conflict, 1);
+    let conflict_row: i64 = conn
+        .query_row(
+            "SELECT row_number FROM import_conflict WHERE material_id = 'MATX01'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(conflict_row, 3);
+
+    let slab: String = conn
+        .query_row(
+            "SELECT slab_id FROM material_master WHERE material_id = 'MATX01'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(slab, "SLAB001");
+}
+
+#[tokio::test]
+async fn test_import_streaming_cancel_keeps_committed_chunks() {
+    use hot_rolling_aps::importer::{
+        ImportCancelToken, ImportProgress, ImportStage, StreamingImportOptions,
+    };
+    use std::sync::Arc;
+
+    logging::init_test();
+
+    let (_temp_file, db_path) = create_test_db().expect("Failed to create test db");
+    let conn = test_helpers::open_test_connection(&db_path).expect("Failed to open db");
+    insert_test_config(&conn).expect("Failed to insert test config");
+    let importer = create_test_importer(&db_path);
+
+    // 第一个分块提交后请求取消
+    let token = ImportCancelToken::new();
+    let trigger = token.clone();
+    let options = StreamingImportOptions {
+        chunk_size: 2,
+        progress: Some(Arc::new(move |p: &ImportProgress| {
+            if p.stage == ImportStage::Writing {
+                trigger.cancel();
+            }
+        })),
+        cancel_token: Some(token),
+        imported_by: None,
+    };
+
+    let result = importer
+        .import_streaming("tests/fixtures/test_materials.csv", options)
+        .await
+        .expect("Cancelled import should still return a result");
+
+    assert!(result.cancelled);
+    assert_eq!(result.summary.total_rows, 2);
+
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM material_master", [], |row| row.get(0))
+        .unwrap();
+    let states: i64 = conn
+        .query_row("SELECT COUNT(*) FROM material_state", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count as usize, result.summary.success);
+    assert_eq!(count, states, "master 与 state 同分块提交");
+
+    // 批次记录带取消标记
+    let report: String = conn
+        .query_row(
+            "SELECT dq_report_json FROM import_batch WHERE batch_id = ?1",
+            [&result.batch.batch_id],
+            |row| row.get(0),
+        )
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["streaming"]["cancelled"], true);
+    assert_eq!(report["streaming"]["chunks_committed"], 1);
+}
+
+#[tokio::test]
+async fn test_import_contracts_updates_linked_materials() {
+    use hot_rolling_aps::domain::material::DqLevel;
+    use hot_rolling_aps::importer::{ContractImporter, ParseOptions};
+    use std::io::Write;
+
+    logging::init_test();
+
+    let (_temp_file, db_path) = create_test_db().expect("Failed to create test db");
+    let conn = test_helpers::open_test_connection(&db_path).expect("Failed to open db");
+    insert_test_config(&conn).expect("Failed to insert test config");
+    create_test_importer(&db_path)
+        .import_from_csv("tests/fixtures/test_materials.csv")
+        .await
+        .expect("Material import should succeed");
+
+    let mut contract_file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+    writeln!(
+        contract_file,
+        "合同号,合同性质代码,按周交货标志,出口标记,合同交货期"
+    )
+    .unwrap();
+    writeln!(contract_file, "CT001,A1,D,0,20991231").unwrap();
+    writeln!(contract_file, "CT002,,,N,").unwrap();
+    writeln!(contract_file, "CT001,B1,A,1,20991231").unwrap();
+    writeln!(contract_file, "CT999,A1,D,0,20991231").unwrap();
+    writeln!(contract_file, ",A1,D,0,20991231").unwrap();
+    contract_file.flush().unwrap();
+
+    let importer = ContractImporter::new(
+        MaterialImportRepositoryImpl::new(&db_path).unwrap(),
+        ConfigManager::new(&db_path).unwrap(),
+    );
+    let result = importer
+        .import_from_file(contract_file.path(), ParseOptions::default(), "tester")
+        .await
+        .expect("Contract import should succeed");
+
+    assert_eq!(result.summary.total_rows, 5);
+    assert_eq!(result.matched_contracts, 2);
+    assert_eq!(result.unmatched_contracts, 1);
+    assert_eq!(result.updated_materials, 2);
+    assert_eq!(result.summary.blocked, 1);
+    assert_eq!(result.summary.conflict, 1);
+    assert!(result
+        .violations
+        .iter()
+        .any(|v| v.level == DqLevel::Warning && v.message.contains("CT999")));
+
+    // CT001: 影子字段更新 + 催料等级重算（A1 + D → L2）
+    let (nature, weekly, due): (String, String, String) = conn
+        .query_row(
+            "SELECT contract_nature, weekly_delivery_flag, due_date FROM material_master WHERE material_id = 'MAT001'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!((nature.as_str(), weekly.as_str()), ("A1", "D"));
+    assert_eq!(due, "2099-12-31");
+    let (rush, urgent, updated_by): (String, String, String) = conn
+        .query_row(
+            "SELECT rush_level, urgent_level, updated_by FROM material_state WHERE material_id = 'MAT001'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(rush, "L2");
+    assert_eq!(urgent, "L2");
+    assert_eq!(updated_by, "tester");
+
+    // CT002: 文件中为空的属性保留原值，仅出口标记被更新
+    let (nature, export): (String, String) = conn
+        .query_row(
+            "SELECT contract_nature, export_flag FROM material_master WHERE material_id = 'MAT002'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(nature, "URGENT");
+    assert_eq!(export, "0");
+
+    // 批次记录标注导入类型
+    let report: String = conn
+        .query_row(
+            "SELECT dq_report_json FROM import_batch WHERE batch_id = ?1",
+            [&result.batch.batch_id],
+            |row| row.get(0),
+        )
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["import_type"], "CONTRACT");
+}
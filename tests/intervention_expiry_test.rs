@@ -0,0 +1,395 @@
+// ==========================================
+// 人工干预时效 集成测试
+// ==========================================
+// 测试范围:
+// 1. 锁定按到期时间失效：清扫清除标志并记录 EXPIRE_INTERVENTION 日志
+// 2. 人工紧急按重算次数失效
+// 3. 优先级提升（路径突破拒绝）失效后清除拒绝标记
+// 4. 设置时效后再次手工调整同一标志，时效作废
+// 5. 按设置人列出即将失效的干预，参数与未生效干预校验
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{MaterialBuilder, MaterialStateBuilder, PlanItemBuilder};
+use hot_rolling_aps::api::intervention_expiry_api::INTERVENTION_EXPIRY_OPERATOR;
+use hot_rolling_aps::api::{ApiError, ValidationMode};
+use hot_rolling_aps::domain::action_log::ActionLog;
+use hot_rolling_aps::domain::types::SchedState;
+use hot_rolling_aps::domain::InterventionType;
+
+fn day(offset: i64) -> NaiveDate {
+    Local::now().date_naive() + Duration::days(offset)
+}
+
+fn hours_later(hours: i64) -> NaiveDateTime {
+    Local::now().naive_local() + Duration::hours(hours)
+}
+
+fn ids(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+/// 准备场景：H032 上 A/B/C 三个材料（D1 已排），返回版本ID
+fn prepare(env: &ApiTestEnv) -> String {
+    let masters = ["A", "B", "C"]
+        .iter()
+        .map(|id| {
+            MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(20.0)
+                .build()
+        })
+        .collect();
+    let states = ["A", "B", "C"]
+        .iter()
+        .map(|id| MaterialStateBuilder::new(id).build())
+        .collect();
+    env.prepare_materials(masters, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("时效测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    let items = ["A", "B", "C"]
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            PlanItemBuilder::new(&version_id, id, "H032", day(1))
+                .seq_no(i as i32 + 1)
+                .build()
+        })
+        .collect::<Vec<_>>();
+    env.plan_item_repo
+        .batch_insert(&items)
+        .expect("插入计划失败");
+
+    version_id
+}
+
+fn lock(env: &ApiTestEnv, list: &[&str], lock_flag: bool, operator: &str) {
+    env.material_api
+        .batch_lock_materials(
+            ids(list),
+            lock_flag,
+            operator,
+            "人工锁定",
+            ValidationMode::Strict,
+        )
+        .expect("锁定/解锁失败");
+}
+
+fn record_recalc(env: &ApiTestEnv) {
+    env.action_log_repo
+        .insert(&ActionLog::new(
+            uuid::Uuid::new_v4().to_string(),
+            None,
+            "RECALC_FULL",
+            "planner".to_string(),
+        ))
+        .expect("写入重算日志失败");
+}
+
+#[test]
+fn test_lock_expires_by_time_with_action_log() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    prepare(&env);
+    lock(&env, &["A", "B"], true, "planner");
+
+    let api = &env.intervention_expiry_api;
+    let entries = api
+        .set_intervention_expiry(
+            ids(&["A"]),
+            InterventionType::Lock,
+            Some(hours_later(2)),
+            None,
+            "planner",
+            Some("等待质检结论"),
+        )
+        .expect("设置时效失败");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].intervention_type, "LOCK");
+    assert!(!entries[0].expired);
+
+    // 未到期：不清除
+    let result = api.sweep_expired(hours_later(1)).unwrap();
+    assert!(result.expired.is_empty());
+    assert!(
+        env.material_state_repo
+            .find_by_id("A")
+            .unwrap()
+            .unwrap()
+            .lock_flag
+    );
+
+    let result = api.sweep_expired(hours_later(3)).unwrap();
+    assert_eq!(result.expired.len(), 1);
+    assert_eq!(result.expired[0].material_id, "A");
+    assert_eq!(result.expired[0].operator, "planner");
+    assert_eq!(result.expired[0].trigger, "TIME");
+    assert_eq!(result.action_ids.len(), 1);
+
+    let a = env.material_state_repo.find_by_id("A").unwrap().unwrap();
+    assert!(!a.lock_flag);
+    assert_eq!(a.sched_state, SchedState::Ready);
+    // 未设置时效的锁定保持不变
+    assert!(
+        env.material_state_repo
+            .find_by_id("B")
+            .unwrap()
+            .unwrap()
+            .lock_flag
+    );
+
+    let log = env
+        .action_log_repo
+        .find_by_id(&result.action_ids[0])
+        .unwrap()
+        .unwrap();
+    assert_eq!(log.action_type, "EXPIRE_INTERVENTION");
+    assert_eq!(log.actor, INTERVENTION_EXPIRY_OPERATOR);
+    let payload = log.payload_json.unwrap();
+    assert_eq!(payload["intervention_type"], "LOCK");
+    assert_eq!(payload["entries"][0]["set_by"], "planner");
+
+    // 清扫后时效记录删除，再次清扫无事可做
+    let result = api.sweep_expired(hours_later(4)).unwrap();
+    assert!(result.expired.is_empty());
+    assert_eq!(result.stale_removed, 0);
+}
+
+#[test]
+fn test_urgent_expires_after_recalcs() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    prepare(&env);
+    env.material_api
+        .batch_set_urgent(ids(&["A"]), true, "planner", "客户催货")
+        .expect("设置紧急失败");
+    // 设置时效之前的重算不计入
+    record_recalc(&env);
+
+    let api = &env.intervention_expiry_api;
+    api.set_intervention_expiry(
+        ids(&["A"]),
+        InterventionType::ManualUrgent,
+        None,
+        Some(2),
+        "planner",
+        None,
+    )
+    .expect("设置时效失败");
+
+    record_recalc(&env);
+    let now = Local::now().naive_local();
+    assert!(api.sweep_expired(now).unwrap().expired.is_empty());
+    let upcoming = api.list_upcoming_expirations(Some("planner"), 1).unwrap();
+    assert_eq!(upcoming.total, 1);
+    assert_eq!(upcoming.operators[0].entries[0].recalcs_remaining, Some(1));
+
+    record_recalc(&env);
+    let result = api.sweep_expired(now).unwrap();
+    assert_eq!(result.expired.len(), 1);
+    assert_eq!(result.expired[0].trigger, "RECALCS");
+    assert!(
+        !env.material_state_repo
+            .find_by_id("A")
+            .unwrap()
+            .unwrap()
+            .manual_urgent_flag
+    );
+}
+
+#[test]
+fn test_priority_boost_expiry_clears_rejection() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    prepare(&env);
+    // 直接写入拒绝标记（路径突破拒绝的前置条件不在本测试范围内）
+    env.material_state_repo
+        .update_path_override_rejection("A", "planner", "跳宽过大，延后一个换辊周期", 1, "READY")
+        .expect("写入拒绝标记失败");
+    assert_eq!(
+        env.material_state_repo
+            .find_path_override_rejections(&ids(&["A"]))
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let api = &env.intervention_expiry_api;
+    api.set_intervention_expiry(
+        ids(&["A"]),
+        InterventionType::PriorityBoost,
+        Some(hours_later(12)),
+        None,
+        "planner",
+        None,
+    )
+    .expect("设置时效失败");
+
+    let result = api.sweep_expired(hours_later(13)).unwrap();
+    assert_eq!(result.expired.len(), 1);
+    assert_eq!(result.expired[0].intervention_type, "PRIORITY_BOOST");
+    assert!(env
+        .material_state_repo
+        .find_path_override_rejections(&ids(&["A"]))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_manual_change_supersedes_expiry() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    prepare(&env);
+    lock(&env, &["A", "B"], true, "planner");
+
+    let api = &env.intervention_expiry_api;
+    api.set_intervention_expiry(
+        ids(&["A", "B"]),
+        InterventionType::Lock,
+        Some(hours_later(1)),
+        None,
+        "planner",
+        None,
+    )
+    .expect("设置时效失败");
+
+    // 值班长随后重新锁定 A（长期有效），B 被解锁
+    lock(&env, &["A"], false, "shift_lead");
+    lock(&env, &["A"], true, "shift_lead");
+    lock(&env, &["B"], false, "shift_lead");
+
+    let upcoming = api.list_upcoming_expirations(None, 24).unwrap();
+    assert_eq!(upcoming.total, 0);
+
+    let result = api.sweep_expired(hours_later(2)).unwrap();
+    assert!(result.expired.is_empty());
+    assert_eq!(result.stale_removed, 2);
+    let a = env.material_state_repo.find_by_id("A").unwrap().unwrap();
+    assert!(a.lock_flag);
+    assert_eq!(a.sched_state, SchedState::Locked);
+}
+
+#[test]
+fn test_list_upcoming_by_operator_and_validation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    prepare(&env);
+    lock(&env, &["A", "B", "C"], true, "planner");
+
+    let api = &env.intervention_expiry_api;
+    api.set_intervention_expiry(
+        ids(&["A"]),
+        InterventionType::Lock,
+        Some(hours_later(2)),
+        None,
+        "planner",
+        None,
+    )
+    .unwrap();
+    api.set_intervention_expiry(
+        ids(&["B"]),
+        InterventionType::Lock,
+        Some(hours_later(5)),
+        None,
+        "shift_lead",
+        None,
+    )
+    .unwrap();
+    // 窗口外
+    api.set_intervention_expiry(
+        ids(&["C"]),
+        InterventionType::Lock,
+        Some(hours_later(72)),
+        None,
+        "planner",
+        None,
+    )
+    .unwrap();
+
+    let upcoming = api.list_upcoming_expirations(None, 24).unwrap();
+    assert_eq!(upcoming.total, 2);
+    let operators: Vec<&str> = upcoming
+        .operators
+        .iter()
+        .map(|o| o.operator.as_str())
+        .collect();
+    assert_eq!(operators, vec!["planner", "shift_lead"]);
+    assert_eq!(upcoming.operators[0].entries[0].material_id, "A");
+    let hours = upcoming.operators[0].entries[0].hours_remaining.unwrap();
+    assert!(hours > 1.5 && hours <= 2.0);
+
+    let mine = api.list_upcoming_expirations(Some("planner"), 96).unwrap();
+    assert_eq!(mine.total, 2);
+    assert_eq!(mine.operators.len(), 1);
+
+    // 取消时效后不再列出
+    assert_eq!(
+        api.clear_intervention_expiry(ids(&["C"]), InterventionType::Lock, "planner")
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        api.list_upcoming_expirations(Some("planner"), 96)
+            .unwrap()
+            .total,
+        1
+    );
+
+    // 干预未生效
+    match api.set_intervention_expiry(
+        ids(&["A", "missing"]),
+        InterventionType::ForceRelease,
+        None,
+        Some(1),
+        "planner",
+        None,
+    ) {
+        Err(ApiError::ManualOperationValidationError { violations, .. }) => {
+            assert_eq!(violations.len(), 2);
+            assert_eq!(violations[0].violation_type, "INTERVENTION_NOT_ACTIVE");
+        }
+        other => panic!("期望干预未生效错误, 实际: {:?}", other),
+    }
+
+    // 参数校验
+    assert_invalid_input(api.set_intervention_expiry(
+        ids(&["A"]),
+        InterventionType::Lock,
+        None,
+        None,
+        "planner",
+        None,
+    ));
+    assert_invalid_input(api.set_intervention_expiry(
+        ids(&["A"]),
+        InterventionType::Lock,
+        Some(hours_later(-1)),
+        None,
+        "planner",
+        None,
+    ));
+    assert_invalid_input(api.set_intervention_expiry(
+        ids(&["A"]),
+        InterventionType::Lock,
+        None,
+        Some(0),
+        "planner",
+        None,
+    ));
+    assert_invalid_input(api.set_intervention_expiry(
+        vec![" ".to_string()],
+        InterventionType::Lock,
+        None,
+        Some(1),
+        "planner",
+        None,
+    ));
+    assert_invalid_input(api.list_upcoming_expirations(None, 0));
+}
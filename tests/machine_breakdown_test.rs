@@ -0,0 +1,158 @@
+// ==========================================
+// 机组故障 what-if 模拟 集成测试
+// ==========================================
+// 测试范围:
+// 1. 停机当日产能扣减 → 材料被延后/挤出
+// 2. 默认不生成版本,生产库不受影响
+// 3. create_version=true 时保存故障后草稿版本(含扣减后产能)
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::MaterialBuilder;
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::domain::scenario::MachineBreakdown;
+use hot_rolling_aps::domain::types::SchedState;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 6, day).unwrap()
+}
+
+/// 8 块 H033 材料(每块 500t),默认产能下 2 天可排完
+fn prepare_version(env: &ApiTestEnv) -> String {
+    let ids: Vec<String> = (1..=8).map(|i| format!("B{}", i)).collect();
+    env.prepare_materials(
+        ids.iter()
+            .map(|id| {
+                MaterialBuilder::new(id)
+                    .machine("H033")
+                    .weight(500.0)
+                    .output_age_days(30)
+                    .build()
+            })
+            .collect(),
+        ids.iter()
+            .map(|id| create_test_state(id, SchedState::Ready, 0))
+            .collect(),
+    )
+    .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("故障模拟方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.approve_version(&version_id).expect("审批失败");
+    env.plan_api
+        .activate_version(&version_id, "admin")
+        .expect("激活版本失败");
+    version_id
+}
+
+fn full_day_outage() -> MachineBreakdown {
+    MachineBreakdown {
+        machine_code: "H033".to_string(),
+        outage_start: d(1).and_hms_opt(0, 0, 0).unwrap(),
+        outage_hours: 24.0,
+        capacity_reduction_pct: None,
+    }
+}
+
+#[test]
+fn test_breakdown_delays_materials_without_creating_version() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let api = &env.scenario_api;
+    let version_id = prepare_version(&env);
+    let plan_id = env
+        .plan_version_repo
+        .find_by_id(&version_id)
+        .expect("查询版本失败")
+        .expect("版本不存在")
+        .plan_id;
+    let versions_before = env
+        .plan_version_repo
+        .find_by_plan_id(&plan_id)
+        .expect("查询版本失败")
+        .len();
+
+    let result = api
+        .simulate_machine_breakdown(&version_id, full_day_outage(), 3, None, false, "planner")
+        .expect("模拟失败");
+
+    assert_eq!(result.capacity_loss.len(), 1);
+    assert_eq!(result.capacity_loss[0].plan_date, d(1));
+    assert!(result.capacity_loss[0].lost_capacity_t > 0.0);
+    assert!(result.baseline_items > 0);
+    assert!(!result.delayed.is_empty() || !result.squeezed_out.is_empty());
+    assert!(result.delayed.iter().all(|m| m.delay_days > Some(0)));
+    assert!(result.draft_version_id.is_none());
+
+    // 生产库: 无新版本、产能池未扣减
+    assert_eq!(
+        env.plan_version_repo
+            .find_by_plan_id(&plan_id)
+            .expect("查询版本失败")
+            .len(),
+        versions_before
+    );
+    let pool = env
+        .capacity_pool_repo
+        .find_by_machine_and_date(&version_id, "H033", d(1))
+        .expect("查询产能池失败");
+    assert!(pool.map_or(true, |p| p.limit_capacity_t > 0.0));
+}
+
+#[test]
+fn test_breakdown_can_save_draft_version() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let api = &env.scenario_api;
+    let version_id = prepare_version(&env);
+
+    let err = api
+        .simulate_machine_breakdown(
+            &version_id,
+            MachineBreakdown {
+                machine_code: "X999".to_string(),
+                ..full_day_outage()
+            },
+            3,
+            None,
+            false,
+            "planner",
+        )
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidInput(_)));
+
+    let result = api
+        .simulate_machine_breakdown(&version_id, full_day_outage(), 3, None, true, "planner")
+        .expect("模拟失败");
+    let draft_id = result.draft_version_id.expect("应生成草稿版本");
+
+    let draft = env
+        .plan_api
+        .get_version_detail(&draft_id)
+        .expect("查询版本失败");
+    assert!(draft.is_draft());
+
+    let items = env
+        .plan_item_repo
+        .find_by_version(&draft_id)
+        .expect("查询明细失败");
+    assert_eq!(items.len(), result.whatif_items);
+    assert!(items
+        .iter()
+        .all(|i| !(i.machine_code == "H033" && i.plan_date == d(1))));
+
+    let pool = env
+        .capacity_pool_repo
+        .find_by_machine_and_date(&draft_id, "H033", d(1))
+        .expect("查询产能池失败")
+        .expect("草稿版本应带扣减后产能");
+    assert_eq!(pool.limit_capacity_t, 0.0);
+}
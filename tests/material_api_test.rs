@@ -304,6 +304,29 @@ fn test_batch_force_release_正常放行() {
     assert_action_logged(&env, "FORCE_RELEASE", 1).unwrap();
 }
 
+#[test]
+fn test_batch_force_release_返回失败材料() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+
+    let materials = vec![MaterialBuilder::new("M001").machine("M1").build()];
+    let states = vec![MaterialStateBuilder::new("M001").build()];
+    env.prepare_materials(materials, states).unwrap();
+
+    let result = env
+        .material_api
+        .batch_force_release(
+            vec!["M001".to_string(), "M404".to_string()],
+            "admin",
+            "紧急放行",
+            ValidationMode::AutoFix,
+        )
+        .expect("放行失败");
+
+    assert_eq!((result.success_count, result.fail_count), (1, 1));
+    let details = result.details.expect("缺少详细信息");
+    assert_eq!(details["failed_material_ids"], serde_json::json!(["M404"]));
+}
+
 #[test]
 fn test_batch_set_urgent_设置紧急标志() {
     let env = ApiTestEnv::new().expect("无法创建测试环境");
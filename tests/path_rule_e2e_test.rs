@@ -19,7 +19,7 @@ mod path_rule_e2e_test {
     use crate::test_helpers;
     use crate::test_helpers::create_test_db;
     use chrono::{Duration, NaiveDate};
-    use hot_rolling_aps::api::{PathRuleApi, PlanApi};
+    use hot_rolling_aps::api::{ActivationRedLineValidator, PathRuleApi, PlanApi};
     use hot_rolling_aps::config::config_manager::ConfigManager;
     use hot_rolling_aps::domain::types::{SchedState, UrgentLevel};
     use hot_rolling_aps::engine::strategy::ScheduleStrategy;
@@ -84,6 +84,14 @@ mod path_rule_e2e_test {
                 .expect("RollCampaignPlanRepository init failed"),
         );
         let path_override_pending_repo = Arc::new(PathOverridePendingRepository::new(conn.clone()));
+        let activation_validator = Arc::new(ActivationRedLineValidator::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            material_state_repo.clone(),
+            capacity_pool_repo.clone(),
+            roller_campaign_repo.clone(),
+            path_override_pending_repo.clone(),
+        ));
 
         // === Engine ===
         let config_manager =
@@ -127,6 +135,7 @@ mod path_rule_e2e_test {
             config_manager.clone(),
             recalc_engine.clone(),
             risk_engine,
+            activation_validator,
             None,
         ));
 
@@ -199,6 +199,7 @@ fn test_activate_version_激活版本() {
         .expect("创建失败");
 
     // 测试: 激活版本
+    env.approve_version(&version_id).expect("审批失败");
     env.plan_api
         .activate_version(&version_id, "admin")
         .expect("激活失败");
@@ -985,7 +986,12 @@ fn test_rollback_version_切换激活版本并恢复配置快照() {
         )
         .expect("创建版本B失败");
 
-    // 4) 先激活版本B（模拟“当前运行版本”）
+    // 4) 先后激活版本A、B（B 为“当前运行版本”，A 被归档）
+    env.approve_version(&version_a).expect("审批版本A失败");
+    env.plan_api
+        .activate_version(&version_a, "admin")
+        .expect("激活版本A失败");
+    env.approve_version(&version_b).expect("审批失败");
     env.plan_api
         .activate_version(&version_b, "admin")
         .expect("激活版本B失败");
@@ -0,0 +1,193 @@
+// ==========================================
+// 方案多目标评价 集成测试
+// ==========================================
+// 测试范围:
+// 1. 版本目标向量（拖期/准时率/产能/路径突破/冷料/换钢种）
+//    冷料阈值读取 cold_stock_age_threshold_days 配置
+// 2. 版本 KPI 对比复用同一评价结果
+// 3. 参数校验
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{
+    CapacityPoolBuilder, MaterialBuilder, MaterialStateBuilder, PlanItemBuilder,
+};
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::domain::types::SchedState;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2030, 7, day).unwrap()
+}
+
+/// 返回 (版本A, 版本B)
+///
+/// - A: 1日 M1(Q235),M2(Q345),M3(Q235); 2日 M4(Q235)
+/// - B: 1日 M1,M3; 2日 M2,M4（M2 推迟 1 天，超出交期）
+fn prepare_versions(env: &ApiTestEnv) -> (String, String) {
+    let spec = [
+        ("M1", "Q235", Some(d(2)), None),
+        ("M2", "Q345", Some(d(1)), Some(45)),
+        ("M3", "Q235", None, Some(10)),
+        ("M4", "Q235", None, None),
+    ];
+    let materials = spec
+        .iter()
+        .map(|(id, grade, due, age)| {
+            let mut builder = MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(400.0)
+                .steel_mark(grade);
+            if let Some(due) = due {
+                builder = builder.due_date(*due);
+            }
+            let mut master = builder.build();
+            master.stock_age_days = *age;
+            master
+        })
+        .collect();
+    let states = spec
+        .iter()
+        .map(|(id, _, _, _)| {
+            MaterialStateBuilder::new(id)
+                .sched_state(SchedState::Ready)
+                .build()
+        })
+        .collect();
+    env.prepare_materials(materials, states)
+        .expect("准备材料失败");
+    env.material_state_repo
+        .update_user_confirmation("M4", "admin", "宽度跳跃确认")
+        .expect("确认路径突破失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("多目标评价测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let layout = |rows: &[(&str, NaiveDate, i32)]| {
+        let version_id = env
+            .plan_api
+            .create_version(plan_id.clone(), 7, None, None, "admin".to_string())
+            .expect("创建版本失败");
+        let items: Vec<_> = rows
+            .iter()
+            .map(|(id, date, seq)| {
+                PlanItemBuilder::new(&version_id, id, "H032", *date)
+                    .seq_no(*seq)
+                    .weight(400.0)
+                    .build()
+            })
+            .collect();
+        env.plan_item_repo
+            .batch_insert(&items)
+            .expect("插入计划失败");
+        env.prepare_capacity_pools(
+            [d(1), d(2)]
+                .iter()
+                .map(|date| {
+                    CapacityPoolBuilder::new("H032", *date)
+                        .version_id(&version_id)
+                        .target(1000.0)
+                        .limit(1100.0)
+                        .build()
+                })
+                .collect(),
+        )
+        .expect("准备产能池失败");
+        version_id
+    };
+
+    let a = layout(&[
+        ("M1", d(1), 1),
+        ("M2", d(1), 2),
+        ("M3", d(1), 3),
+        ("M4", d(2), 1),
+    ]);
+    let b = layout(&[
+        ("M1", d(1), 1),
+        ("M3", d(1), 2),
+        ("M2", d(2), 1),
+        ("M4", d(2), 2),
+    ]);
+    (a, b)
+}
+
+#[test]
+fn test_evaluate_version_objectives() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (a, b) = prepare_versions(&env);
+
+    let va = env.plan_api.evaluate_version(&a).expect("评价失败");
+    assert_eq!(va.plan_items_count, 4);
+    assert_eq!((va.due_items_count, va.tardy_count), (2, 0));
+    assert_eq!(va.on_time_weight_rate, 1.0);
+    assert_eq!(va.capacity_used_t, 1600.0);
+    assert_eq!(va.capacity_target_t, 2000.0);
+    assert!((va.capacity_util_pct - 80.0).abs() < 1e-9);
+    // 1日 1200t > 上限 1100t
+    assert_eq!((va.overflow_t, va.overflow_machine_days), (100.0, 1));
+    assert_eq!(va.changeover_count, 2);
+    assert_eq!(va.path_override_count, 1);
+    assert_eq!(va.cold_stock_cleared_count, 1);
+    assert_eq!(va.cold_stock_age_reduction_days, 45);
+
+    let vb = env.plan_api.evaluate_version(&b).expect("评价失败");
+    assert_eq!((vb.tardy_count, vb.total_tardiness_days), (1, 1));
+    assert_eq!(vb.max_tardiness_days, 1);
+    assert!((vb.on_time_weight_rate - 0.5).abs() < 1e-9);
+    assert_eq!(vb.overflow_machine_days, 0);
+    assert_eq!(vb.changeover_count, 1);
+}
+
+#[test]
+fn test_evaluate_version_reads_cold_stock_threshold_config() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (a, _) = prepare_versions(&env);
+
+    // 阈值上调到 50 天后，库龄 45 天的 M2 不再计为冷料
+    env.config_api
+        .update_config(
+            "global",
+            "cold_stock_age_threshold_days",
+            "50",
+            "admin",
+            "测试",
+        )
+        .expect("更新配置失败");
+
+    let va = env.plan_api.evaluate_version(&a).expect("评价失败");
+    assert_eq!(va.cold_stock_cleared_count, 0);
+    assert_eq!(va.cold_stock_age_reduction_days, 0);
+}
+
+#[test]
+fn test_compare_versions_kpi_reports_same_objectives() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (a, b) = prepare_versions(&env);
+
+    let kpi = env
+        .plan_api
+        .compare_versions_kpi(&a, &b)
+        .expect("KPI 对比失败");
+    assert_eq!(
+        kpi.kpi_a.objectives,
+        Some(env.plan_api.evaluate_version(&a).expect("评价失败"))
+    );
+    assert_eq!(
+        kpi.kpi_b.objectives,
+        Some(env.plan_api.evaluate_version(&b).expect("评价失败"))
+    );
+}
+
+#[test]
+fn test_evaluate_version_validation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    assert_invalid_input(env.plan_api.evaluate_version(" "));
+    assert!(matches!(
+        env.plan_api.evaluate_version("NOT_EXISTS"),
+        Err(ApiError::NotFound(_))
+    ));
+}
@@ -0,0 +1,233 @@
+// ==========================================
+// ProductionApi 集成测试
+// ==========================================
+// 测试范围:
+// 1. 实绩导入: 材料完工、换辊累计吨位推进、DQ 校验
+// 2. 计划执行对账: 机组×日 执行率、顺延项、报告查询
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::PlanItemBuilder;
+use hot_rolling_aps::api::ProductionApi;
+use hot_rolling_aps::db::open_sqlite_connection;
+use hot_rolling_aps::domain::types::{RollStatus, SchedState};
+use hot_rolling_aps::domain::DqLevel;
+use hot_rolling_aps::importer::ParseOptions;
+use hot_rolling_aps::repository::{
+    ActionLogRepository, PlanItemRepository, PlanVersionRepository, ProductionActualRepository,
+    RollerCampaignRepository,
+};
+
+fn build_production_api(db_path: &str) -> ProductionApi {
+    let conn = Arc::new(Mutex::new(
+        open_sqlite_connection(db_path).expect("无法打开数据库"),
+    ));
+    ProductionApi::new(
+        Arc::new(
+            ProductionActualRepository::from_connection(conn.clone()).expect("仓储初始化失败"),
+        ),
+        Arc::new(PlanVersionRepository::new(conn.clone())),
+        Arc::new(PlanItemRepository::new(conn.clone())),
+        Arc::new(RollerCampaignRepository::from_connection(conn.clone())),
+        Arc::new(ActionLogRepository::new(conn)),
+    )
+}
+
+fn write_csv(content: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new()
+        .suffix(".csv")
+        .tempfile()
+        .expect("无法创建临时文件");
+    file.write_all(content.as_bytes()).expect("写入失败");
+    file
+}
+
+/// 激活版本 + H032 换辊窗口 + 2026-03-02 计划 (A1, A2, A3)
+fn prepare_active_plan(env: &ApiTestEnv) -> String {
+    let d = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+    let materials = ["A1", "A2", "A3", "B1"]
+        .iter()
+        .map(|id| create_test_material(id, "H032", 10.0, None))
+        .collect();
+    let states = ["A1", "A2", "A3", "B1"]
+        .iter()
+        .map(|id| create_test_state(id, SchedState::Scheduled, 0))
+        .collect();
+    env.prepare_materials(materials, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("实绩测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 30, None, None, "admin".to_string())
+        .expect("创建版本失败");
+
+    let items: Vec<_> = ["A1", "A2", "A3"]
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            PlanItemBuilder::new(&version_id, id, "H032", d)
+                .seq_no(i as i32 + 1)
+                .weight(10.0)
+                .build()
+        })
+        .collect();
+    env.plan_item_repo
+        .batch_insert(&items)
+        .expect("插入计划失败");
+    env.approve_version(&version_id).expect("审批失败");
+    env.plan_api
+        .activate_version(&version_id, "admin")
+        .expect("激活版本失败");
+
+    env.roller_api
+        .create_campaign(
+            &version_id,
+            "H032",
+            1,
+            d,
+            Some(30.0),
+            Some(100.0),
+            "admin",
+            "实绩测试",
+        )
+        .expect("创建换辊窗口失败");
+
+    version_id
+}
+
+#[test]
+fn test_import_actuals_completes_materials_and_reconciles() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_active_plan(&env);
+    let api = build_production_api(&env.db_path);
+
+    // A2 先于 A1 完工；A3 未完工；B1 计划外；X9 不存在；A1 文件内重复
+    let csv = write_csv(
+        "材料号,实际机组,完工时间,实际重量\n\
+         A2,H032,2026-03-02 08:00:00,10.5\n\
+         A1,H032,2026-03-02 09:30:00,9.8\n\
+         B1,H032,2026-03-02 11:00:00,12.0\n\
+         X9,H032,2026-03-02 12:00:00,5.0\n\
+         A1,H032,2026-03-02 13:00:00,9.8\n",
+    );
+    let result = api
+        .import_actuals(csv.path().to_str().unwrap(), ParseOptions::default(), "mes")
+        .expect("实绩导入失败");
+
+    assert_eq!(result.version_id.as_deref(), Some(version_id.as_str()));
+    assert_eq!(result.imported, 3);
+    assert_eq!(result.dq_summary.blocked, 1);
+    assert_eq!(result.dq_summary.conflict, 1);
+    assert!(result
+        .dq_violations
+        .iter()
+        .any(|v| v.level == DqLevel::Error && v.material_id.as_deref() == Some("X9")));
+
+    // 材料完工
+    let a1 = env
+        .material_state_repo
+        .find_by_id("A1")
+        .expect("查询失败")
+        .expect("A1 不存在");
+    assert_eq!(a1.sched_state, SchedState::Completed);
+
+    // 换辊累计吨位: 10.5 + 9.8 + 12.0 = 32.3 ≥ 建议阈值 30
+    assert_eq!(result.campaigns_updated, 1);
+    let campaign = env
+        .roller_api
+        .get_active_campaign(&version_id, "H032")
+        .expect("查询失败")
+        .expect("换辊窗口不存在");
+    assert!((campaign.cum_weight_t - 32.3).abs() < 1e-6);
+    assert_eq!(campaign.status, format!("{:?}", RollStatus::Suggest));
+
+    // 对账: 计划 30t, 按计划完工 A1/A2 = 20t, 顺序互换
+    assert_eq!(result.adherence.len(), 1);
+    let day = &result.adherence[0];
+    assert_eq!(day.planned_count, 3);
+    assert_eq!(day.matched_count, 2);
+    assert_eq!(day.unplanned_count, 1);
+    assert_eq!(day.carry_over_count, 1);
+    assert_eq!(day.sequence_deviation_count, 2);
+    assert!((day.adherence_rate.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+
+    let report = api
+        .get_plan_adherence_report(&version_id, Some("H032"), Some("2026-03-01"), None)
+        .expect("查询报告失败");
+    assert_eq!(report.rows.len(), 1);
+    assert!((report.total_actual_weight_t - 32.3).abs() < 1e-6);
+
+    let carry_overs = api
+        .list_carry_over_items(&version_id, true)
+        .expect("查询顺延项失败");
+    assert_eq!(carry_overs.len(), 1);
+    assert_eq!(carry_overs[0].material_id, "A3");
+}
+
+#[test]
+fn test_import_actuals_resolves_carry_over_and_rejects_duplicates() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_active_plan(&env);
+    let api = build_production_api(&env.db_path);
+
+    let first = write_csv(
+        "材料号,实际机组,完工时间,实际重量\n\
+         A1,H032,2026-03-02 08:00:00,10\n\
+         A2,H032,2026-03-02 09:00:00,10\n",
+    );
+    api.import_actuals(
+        first.path().to_str().unwrap(),
+        ParseOptions::default(),
+        "mes",
+    )
+    .expect("首批导入失败");
+    assert_eq!(
+        api.list_carry_over_items(&version_id, true).unwrap().len(),
+        1
+    );
+
+    // 次日补录 A3（顺延项回填），A1 重复回填记为冲突
+    let second = write_csv(
+        "材料号,实际机组,完工时间,实际重量\n\
+         A3,H032,2026-03-03 07:00:00,10\n\
+         A1,H032,2026-03-03 08:00:00,10\n",
+    );
+    let result = api
+        .import_actuals(
+            second.path().to_str().unwrap(),
+            ParseOptions::default(),
+            "mes",
+        )
+        .expect("次批导入失败");
+
+    assert_eq!(result.imported, 1);
+    assert_eq!(result.dq_summary.conflict, 1);
+    assert!(api
+        .list_carry_over_items(&version_id, true)
+        .unwrap()
+        .is_empty());
+
+    let all = api.list_carry_over_items(&version_id, false).unwrap();
+    assert_eq!(all.len(), 1);
+    assert!(all[0].resolved_at.is_some());
+
+    // 3 日无计划，A3 计为计划外
+    let day3 = result
+        .adherence
+        .iter()
+        .find(|r| r.plan_date == NaiveDate::from_ymd_opt(2026, 3, 3).unwrap())
+        .expect("缺少 3 日对账");
+    assert_eq!(day3.unplanned_count, 1);
+    assert_eq!(day3.adherence_rate, None);
+}
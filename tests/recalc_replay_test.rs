@@ -0,0 +1,205 @@
+// ==========================================
+// 历史重算确定性回放 集成测试
+// ==========================================
+// 测试范围:
+// 1. 一键重算在版本元信息中记录输入指纹
+// 2. 开启 recalc_persist_inputs 后，生产数据变化不影响回放（结果与已存明细一致）
+// 3. 未保存全量输入时按当前数据回放，并列出与原指纹不一致的部分
+// 4. 非重算版本无法回放
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::MaterialBuilder;
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::config::ConfigManager;
+use hot_rolling_aps::db::open_sqlite_connection;
+use hot_rolling_aps::domain::recalc_input::{RecalcInputFingerprint, ReplayInputSource};
+use hot_rolling_aps::domain::types::SchedState;
+
+fn base_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+}
+
+fn open_config(env: &ApiTestEnv) -> ConfigManager {
+    let conn = Arc::new(Mutex::new(
+        open_sqlite_connection(&env.db_path).expect("打开数据库失败"),
+    ));
+    ConfigManager::from_connection(conn).expect("创建配置失败")
+}
+
+/// H032/H033 各 6 块材料，激活 V1 后一键重算得到 V2
+///
+/// # 返回
+/// - (V1, V2)
+fn prepare_recalc(env: &ApiTestEnv, persist_inputs: bool) -> (String, String) {
+    if persist_inputs {
+        open_config(env)
+            .restore_config_from_snapshot(r#"{"recalc_persist_inputs":"true"}"#)
+            .expect("写入配置失败");
+    }
+
+    let ids: Vec<(String, &str)> = (1..=12)
+        .map(|i| {
+            let machine = if i % 2 == 0 { "H032" } else { "H033" };
+            (format!("R{:02}", i), machine)
+        })
+        .collect();
+    env.prepare_materials(
+        ids.iter()
+            .enumerate()
+            .map(|(i, (id, machine))| {
+                MaterialBuilder::new(id)
+                    .machine(machine)
+                    .weight(300.0 + i as f64 * 50.0)
+                    .output_age_days(30)
+                    .due_date(base_date() + chrono::Duration::days(i as i64 % 4))
+                    .build()
+            })
+            .collect(),
+        ids.iter()
+            .map(|(id, _)| create_test_state(id, SchedState::Ready, 0))
+            .collect(),
+    )
+    .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("重算回放方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let v1 = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.approve_version(&v1).expect("审批失败");
+    env.plan_api
+        .activate_version(&v1, "admin")
+        .expect("激活版本失败");
+
+    let v2 = env
+        .plan_api
+        .recalc_full(&v1, base_date(), None, "admin")
+        .expect("重算失败")
+        .version_id;
+    (v1, v2)
+}
+
+#[test]
+fn test_recalc_records_input_fingerprint() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (_, v2) = prepare_recalc(&env, false);
+
+    let version = env
+        .plan_version_repo
+        .find_by_id(&v2)
+        .expect("查询失败")
+        .expect("版本不存在");
+    let fingerprint =
+        RecalcInputFingerprint::from_snapshot(version.config_snapshot_json.as_deref())
+            .expect("缺少输入指纹");
+    assert_eq!(fingerprint.material_count, 12);
+    assert_eq!(fingerprint.run.base_date, base_date());
+    assert_eq!(fingerprint.run.strategy_key, "balanced");
+    assert_eq!(fingerprint.combined_hash.len(), 16);
+    assert!(!fingerprint.inputs_persisted);
+    assert!(env
+        .plan_version_repo
+        .find_input_snapshot(&v2)
+        .expect("查询失败")
+        .is_none());
+}
+
+#[test]
+fn test_replay_with_persisted_inputs_is_deterministic() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (_, v2) = prepare_recalc(&env, true);
+    let stored_count = env
+        .plan_item_repo
+        .find_by_version(&v2)
+        .expect("查询失败")
+        .len();
+    assert!(stored_count > 0);
+
+    // 重算之后生产数据变化：新增材料、修改配置
+    env.prepare_materials(
+        vec![MaterialBuilder::new("R99")
+            .machine("H032")
+            .weight(800.0)
+            .output_age_days(30)
+            .due_date(base_date())
+            .build()],
+        vec![create_test_state("R99", SchedState::Ready, 0)],
+    )
+    .expect("追加材料失败");
+    open_config(&env)
+        .restore_config_from_snapshot(r#"{"path_rule_enabled":"false"}"#)
+        .expect("写入配置失败");
+
+    let report = env.scenario_api.replay_recalc(&v2).expect("回放失败");
+    assert_eq!(report.input_source, ReplayInputSource::Persisted);
+    assert!(report.recorded_fingerprint.inputs_persisted);
+    assert!(
+        report.input_mismatches.is_empty(),
+        "{:?}",
+        report.input_mismatches
+    );
+    assert_eq!(
+        report.recorded_fingerprint.combined_hash,
+        report.replay_fingerprint.combined_hash
+    );
+    assert!(report.identical, "{:?}", report.diffs);
+    assert_eq!(report.stored_count, stored_count);
+    assert_eq!(report.replayed_count, stored_count);
+
+    // 回放不写生产库
+    assert!(env
+        .plan_item_repo
+        .find_by_version(&v2)
+        .expect("查询失败")
+        .iter()
+        .all(|i| i.material_id != "R99"));
+}
+
+#[test]
+fn test_replay_with_current_data_reports_mismatch() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (v1, v2) = prepare_recalc(&env, false);
+
+    // 重算之后新增一块最紧急材料
+    env.prepare_materials(
+        vec![MaterialBuilder::new("R99")
+            .machine("H032")
+            .weight(800.0)
+            .output_age_days(30)
+            .due_date(base_date())
+            .build()],
+        vec![create_test_state("R99", SchedState::Ready, 0)],
+    )
+    .expect("追加材料失败");
+
+    let scenario_api = &env.scenario_api;
+    let report = scenario_api.replay_recalc(&v2).expect("回放失败");
+    assert_eq!(report.input_source, ReplayInputSource::Current);
+    assert!(report.input_mismatches.contains(&"materials".to_string()));
+    assert!(!report.identical);
+    assert_eq!(report.added_count, 1);
+    assert!(report
+        .diffs
+        .iter()
+        .any(|d| d.material_id == "R99" && d.change_type == "ADDED"));
+
+    // 人工创建的版本没有输入指纹
+    assert!(matches!(
+        scenario_api.replay_recalc(&v1),
+        Err(ApiError::BusinessRuleViolation(_))
+    ));
+    assert!(matches!(
+        scenario_api.replay_recalc("NOT_EXISTS"),
+        Err(ApiError::NotFound(_))
+    ));
+}
@@ -0,0 +1,396 @@
+// ==========================================
+// 机组日内顺序调整 集成测试
+// ==========================================
+// 测试范围:
+// 1. 新顺序路径规则校验：初始锚点（上一排产日最后一块）+ 逐块推进
+// 2. OVERRIDE_REQUIRED 生成待确认并落库，HARD_VIOLATION 拒绝调整
+// 3. 换辊后锚点重置（取换辊周期锚点）；当日最后一块变化后复核下一排产日第一块
+// 4. 冻结材料保持原位、参数校验、LocalAdjust 日志
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, Local, NaiveDate};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{MaterialBuilder, MaterialStateBuilder, PlanItemBuilder};
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::domain::roller::RollerCampaign;
+use hot_rolling_aps::domain::types::{AnchorSource, UrgentLevel};
+use hot_rolling_aps::repository::roller_repo::RollerCampaignRepository;
+
+fn day(offset: i64) -> NaiveDate {
+    Local::now().date_naive() + Duration::days(offset)
+}
+
+fn ids(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+/// 准备场景，返回版本ID
+///
+/// H032（宽度容差 50mm，厚度容差 1mm）:
+/// - D1: P(1500x10)
+/// - D2: F(1500x10, 冻结) → A(1480x10, L2) → C(1450x9, L0) → B(1350x9, L2)
+fn prepare_resequence(env: &ApiTestEnv) -> String {
+    // (材料, 宽, 厚, 紧急等级)
+    let materials: [(&str, f64, f64, UrgentLevel); 5] = [
+        ("P", 1500.0, 10.0, UrgentLevel::L3),
+        ("F", 1500.0, 10.0, UrgentLevel::L3),
+        ("A", 1480.0, 10.0, UrgentLevel::L2),
+        ("C", 1450.0, 9.0, UrgentLevel::L0),
+        ("B", 1350.0, 9.0, UrgentLevel::L2),
+    ];
+    let masters = materials
+        .iter()
+        .map(|(id, width, thickness, _)| {
+            let mut master = MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(20.0)
+                .build();
+            master.width_mm = Some(*width);
+            master.thickness_mm = Some(*thickness);
+            master
+        })
+        .collect();
+    let states = materials
+        .iter()
+        .map(|(id, _, _, level)| MaterialStateBuilder::new(id).urgent_level(*level).build())
+        .collect();
+    env.prepare_materials(masters, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("日内调序测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+
+    let mut items = vec![PlanItemBuilder::new(&version_id, "P", "H032", day(1))
+        .seq_no(1)
+        .build()];
+    for (idx, id) in ["F", "A", "C", "B"].iter().enumerate() {
+        let builder = PlanItemBuilder::new(&version_id, id, "H032", day(2)).seq_no(idx as i32 + 1);
+        items.push(if *id == "F" {
+            builder.frozen().build()
+        } else {
+            builder.build()
+        });
+    }
+    env.plan_item_repo
+        .batch_insert(&items)
+        .expect("插入计划失败");
+
+    version_id
+}
+
+fn day_order(env: &ApiTestEnv, version_id: &str, date: NaiveDate) -> Vec<String> {
+    let mut items: Vec<_> = env
+        .plan_item_repo
+        .find_by_machine(version_id, "H032")
+        .unwrap()
+        .into_iter()
+        .filter(|i| i.plan_date == date)
+        .collect();
+    items.sort_by_key(|i| i.seq_no);
+    items.into_iter().map(|i| i.material_id).collect()
+}
+
+#[test]
+fn test_resequence_with_override_required() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_resequence(&env);
+    let new_order = ids(&["F", "B", "A", "C"]);
+
+    // 预览不落库
+    let preview = env
+        .path_rule_api
+        .preview_resequence_machine_day(&version_id, "H032", day(2), &new_order)
+        .expect("预览失败");
+    assert!(!preview.applied);
+    assert_eq!(preview.anchor_source, "PREVIOUS_DAY_LAST");
+    assert_eq!(preview.anchor_material_id.as_deref(), Some("P"));
+    assert_eq!(preview.moved_materials, ids(&["B", "A", "C"]));
+    // A 接在更窄的 B 之后，宽度突破（L2 允许人工确认）
+    assert_eq!(preview.override_required_count, 1);
+    assert_eq!(preview.hard_violation_count, 0);
+    let violation = &preview.violations[0];
+    assert_eq!(violation.material_id, "A");
+    assert_eq!(violation.status, "OVERRIDE_REQUIRED");
+    assert_eq!(violation.seq_no, 3);
+    assert_eq!(violation.anchor_material_id.as_deref(), Some("B"));
+    assert!((violation.width_delta_mm - 80.0).abs() < 1e-6);
+    assert_eq!(
+        day_order(&env, &version_id, day(2)),
+        ids(&["F", "A", "C", "B"])
+    );
+
+    // 执行：重写 seq_no + 生成待确认
+    let result = env
+        .path_rule_api
+        .resequence_machine_day(
+            &version_id,
+            "H032",
+            day(2),
+            &new_order,
+            "planner",
+            Some("拖拽调序"),
+        )
+        .expect("调序失败");
+    assert!(result.applied);
+    assert_eq!(day_order(&env, &version_id, day(2)), new_order);
+
+    let pending = env
+        .path_rule_api
+        .list_path_override_pending(&version_id, "H032", day(2))
+        .unwrap();
+    let pending_ids: Vec<&str> = pending.iter().map(|p| p.material_id.as_str()).collect();
+    assert_eq!(pending_ids, vec!["A"]);
+    assert!((pending[0].anchor_width_mm - 1350.0).abs() < 1e-6);
+
+    let logs = env.action_log_repo.find_by_version_id(&version_id).unwrap();
+    let log = logs
+        .iter()
+        .find(|l| l.action_type == "LocalAdjust")
+        .expect("缺少调序日志");
+    let payload = log.payload_json.as_ref().unwrap();
+    assert_eq!(
+        payload["old_order"],
+        serde_json::json!(["F", "A", "C", "B"])
+    );
+    assert_eq!(
+        payload["new_order"],
+        serde_json::json!(["F", "B", "A", "C"])
+    );
+    assert_eq!(
+        payload["moved_materials"],
+        serde_json::json!(["B", "A", "C"])
+    );
+    assert_eq!(log.machine_code.as_deref(), Some("H032"));
+
+    // 恢复原顺序后，待确认按新锚点刷新（A 不再违规）
+    let restored = env
+        .path_rule_api
+        .resequence_machine_day(
+            &version_id,
+            "H032",
+            day(2),
+            &ids(&["F", "A", "C", "B"]),
+            "planner",
+            None,
+        )
+        .expect("调序失败");
+    assert!(restored.applied);
+    assert!(restored.violations.is_empty());
+    assert!(env
+        .path_rule_api
+        .list_path_override_pending(&version_id, "H032", day(2))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_resequence_rejects_hard_violation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_resequence(&env);
+
+    // C(L0) 接在更窄的 B 之后：不允许人工突破
+    let result = env
+        .path_rule_api
+        .resequence_machine_day(
+            &version_id,
+            "H032",
+            day(2),
+            &ids(&["F", "B", "C", "A"]),
+            "planner",
+            None,
+        )
+        .expect("调序失败");
+    assert!(!result.applied);
+    assert_eq!(result.hard_violation_count, 1);
+    let hard = result
+        .violations
+        .iter()
+        .find(|v| v.status == "HARD_VIOLATION")
+        .unwrap();
+    assert_eq!(hard.material_id, "C");
+
+    assert_eq!(
+        day_order(&env, &version_id, day(2)),
+        ids(&["F", "A", "C", "B"])
+    );
+    assert!(env
+        .path_rule_api
+        .list_path_override_pending(&version_id, "H032", day(2))
+        .unwrap()
+        .is_empty());
+    let logs = env.action_log_repo.find_by_version_id(&version_id).unwrap();
+    assert!(logs.iter().all(|l| l.action_type != "LocalAdjust"));
+}
+
+#[test]
+fn test_resequence_anchor_resets_at_roll_change() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_resequence(&env);
+
+    // D2 换辊：新周期锚点由重算解析为 X(1350x9)，D1 的 P 不再作为锚点
+    let roller_repo = RollerCampaignRepository::new(&env.db_path).unwrap();
+    let mut first = RollerCampaign::new(
+        version_id.clone(),
+        "H032".to_string(),
+        1,
+        day(0),
+        None,
+        None,
+    );
+    first.end_date = Some(day(2));
+    roller_repo.create(&first).unwrap();
+    let mut second = RollerCampaign::new(
+        version_id.clone(),
+        "H032".to_string(),
+        2,
+        day(2),
+        None,
+        None,
+    );
+    second.update_anchor(Some("X".to_string()), 1350.0, 9.0, AnchorSource::FrozenLast);
+    roller_repo.create(&second).unwrap();
+
+    let preview = env
+        .path_rule_api
+        .preview_resequence_machine_day(&version_id, "H032", day(2), &ids(&["F", "B", "A", "C"]))
+        .expect("预览失败");
+    assert_eq!(preview.anchor_source, "ROLL_CYCLE");
+    assert_eq!(preview.anchor_material_id.as_deref(), Some("X"));
+    assert_eq!(preview.anchor_width_mm, Some(1350.0));
+    assert_eq!(preview.override_required_count, 1);
+}
+
+#[test]
+fn test_resequence_revalidates_next_day_first_coil() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_resequence(&env);
+
+    // D3: N(1450x9, L2)，接在 B(1350) 之后宽度突破，接在 C(1450) 之后正常
+    let mut master = MaterialBuilder::new("N")
+        .machine("H032")
+        .weight(20.0)
+        .build();
+    master.width_mm = Some(1450.0);
+    master.thickness_mm = Some(9.0);
+    env.prepare_materials(
+        vec![master],
+        vec![MaterialStateBuilder::new("N")
+            .urgent_level(UrgentLevel::L2)
+            .build()],
+    )
+    .expect("准备材料失败");
+    env.plan_item_repo
+        .batch_insert(&[PlanItemBuilder::new(&version_id, "N", "H032", day(3))
+            .seq_no(1)
+            .build()])
+        .expect("插入计划失败");
+
+    let resequence = |order: &[&str]| {
+        env.path_rule_api
+            .resequence_machine_day(&version_id, "H032", day(2), &ids(order), "planner", None)
+            .expect("调序失败")
+    };
+    let pending_next_day = || -> Vec<String> {
+        env.path_rule_api
+            .list_path_override_pending(&version_id, "H032", day(3))
+            .unwrap()
+            .into_iter()
+            .map(|p| p.material_id)
+            .collect()
+    };
+
+    // 最后一块 B → C：N 复核通过
+    let result = resequence(&["F", "B", "A", "C"]);
+    assert!(result.applied);
+    assert!(result.violations.iter().all(|v| v.material_id != "N"));
+    assert!(pending_next_day().is_empty());
+
+    // 最后一块 C → B：N 需人工确认，待确认记在 D3
+    let result = resequence(&["F", "A", "C", "B"]);
+    assert!(result.applied);
+    let violation = result
+        .violations
+        .iter()
+        .find(|v| v.material_id == "N")
+        .expect("缺少下一排产日复核结果");
+    assert_eq!(violation.status, "OVERRIDE_REQUIRED");
+    assert_eq!(violation.plan_date, day(3).format("%Y-%m-%d").to_string());
+    assert_eq!(violation.anchor_material_id.as_deref(), Some("B"));
+    assert_eq!(pending_next_day(), ids(&["N"]));
+
+    // 再次调回：N 的待确认随新锚点清除
+    resequence(&["F", "B", "A", "C"]);
+    assert!(pending_next_day().is_empty());
+
+    let logs = env.action_log_repo.find_by_version_id(&version_id).unwrap();
+    assert!(logs
+        .iter()
+        .filter(|l| l.action_type == "LocalAdjust")
+        .all(|l| l.payload_json.as_ref().unwrap()["revalidated_next_first"] == "N"));
+}
+
+#[test]
+fn test_resequence_validation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_resequence(&env);
+    let api = &env.path_rule_api;
+
+    // 冻结材料必须保持原位
+    assert!(matches!(
+        api.preview_resequence_machine_day(
+            &version_id,
+            "H032",
+            day(2),
+            &ids(&["A", "F", "C", "B"])
+        ),
+        Err(ApiError::FrozenZoneProtection(_))
+    ));
+
+    // 新顺序必须是当日明细的排列
+    for order in [
+        ids(&["F", "A", "C"]),
+        ids(&["F", "A", "C", "B", "P"]),
+        ids(&["F", "A", "A", "B"]),
+        Vec::new(),
+    ] {
+        assert_invalid_input(api.preview_resequence_machine_day(
+            &version_id,
+            "H032",
+            day(2),
+            &order,
+        ));
+    }
+    assert_invalid_input(api.resequence_machine_day(
+        &version_id,
+        "H032",
+        day(2),
+        &ids(&["F", "B", "A", "C"]),
+        " ",
+        None,
+    ));
+    assert!(matches!(
+        api.preview_resequence_machine_day("missing", "H032", day(2), &ids(&["F"])),
+        Err(ApiError::NotFound(_))
+    ));
+    assert!(matches!(
+        api.preview_resequence_machine_day(&version_id, "H032", day(5), &ids(&["F"])),
+        Err(ApiError::NotFound(_))
+    ));
+
+    // 顺序未变化：不落库、不记日志
+    let unchanged = api
+        .resequence_machine_day(&version_id, "H032", day(1), &ids(&["P"]), "planner", None)
+        .expect("调序失败");
+    assert!(!unchanged.applied);
+    assert!(unchanged.moved_materials.is_empty());
+    assert_ne!(unchanged.anchor_source, "PREVIOUS_DAY_LAST");
+}
@@ -0,0 +1,318 @@
+// ==========================================
+// 紧急插单 集成测试
+// ==========================================
+// 测试范围:
+// 1. 预览: 最早可行日 + 最小挤出链 + 拖期变化 + 影响摘要，不落库
+// 2. 执行: 生成派生草稿版本（谱系 RUSH_INSERT），基准版本不变
+// 3. 适温/锁定/不可排材料的处理、参数校验
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, Local, NaiveDate};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{
+    CapacityPoolBuilder, MaterialBuilder, MaterialStateBuilder, PlanItemBuilder,
+};
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::domain::types::{SchedState, UrgentLevel};
+use hot_rolling_aps::domain::version_lineage::{VersionLineageMeta, VersionTrigger};
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn day(offset: i64) -> NaiveDate {
+    today() + Duration::days(offset)
+}
+
+fn ids(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+/// 准备插单场景，返回版本ID
+///
+/// H032 上限 100t（D1~D3）:
+/// - D1: LK(30t, 冻结) + X1(50t, L0, 交期D2) + X2(20t, L2) = 100t
+/// - D2: Y1(80t, L0, 交期远)
+/// - 未排: U(30t, L3, 交期D1)、M(30t, 未适温至D3)、BK(阻断)
+fn prepare_rush(env: &ApiTestEnv) -> String {
+    // (材料, 重量, 紧急等级, 交期偏移, 排产状态)
+    let materials: [(&str, f64, UrgentLevel, i64, SchedState); 7] = [
+        ("LK", 30.0, UrgentLevel::L0, 10, SchedState::Locked),
+        ("X1", 50.0, UrgentLevel::L0, 2, SchedState::Scheduled),
+        ("X2", 20.0, UrgentLevel::L2, 1, SchedState::Scheduled),
+        ("Y1", 80.0, UrgentLevel::L0, 20, SchedState::Scheduled),
+        ("U", 30.0, UrgentLevel::L3, 1, SchedState::Ready),
+        ("M", 30.0, UrgentLevel::L2, 5, SchedState::PendingMature),
+        ("BK", 10.0, UrgentLevel::L3, 1, SchedState::Blocked),
+    ];
+    let masters = materials
+        .iter()
+        .map(|(id, weight, _, due, _)| {
+            MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(*weight)
+                .due_date(day(*due))
+                .build()
+        })
+        .collect();
+    let states = materials
+        .iter()
+        .map(|(id, _, level, _, sched_state)| {
+            let mut state = MaterialStateBuilder::new(id)
+                .urgent_level(*level)
+                .sched_state(*sched_state)
+                .build();
+            if *id == "M" {
+                state.ready_in_days = 3;
+                state.earliest_sched_date = Some(day(3));
+            }
+            state
+        })
+        .collect();
+    env.prepare_materials(masters, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("紧急插单测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.prepare_capacity_pools(
+        (1..=3)
+            .map(|i| {
+                CapacityPoolBuilder::new("H032", day(i))
+                    .version_id(&version_id)
+                    .target(90.0)
+                    .limit(100.0)
+                    .build()
+            })
+            .collect(),
+    )
+    .expect("准备产能池失败");
+
+    let items = vec![
+        PlanItemBuilder::new(&version_id, "LK", "H032", day(1))
+            .seq_no(1)
+            .weight(30.0)
+            .frozen()
+            .build(),
+        PlanItemBuilder::new(&version_id, "X1", "H032", day(1))
+            .seq_no(2)
+            .weight(50.0)
+            .urgent_level("L0")
+            .build(),
+        PlanItemBuilder::new(&version_id, "X2", "H032", day(1))
+            .seq_no(3)
+            .weight(20.0)
+            .urgent_level("L2")
+            .build(),
+        PlanItemBuilder::new(&version_id, "Y1", "H032", day(2))
+            .seq_no(1)
+            .weight(80.0)
+            .urgent_level("L0")
+            .build(),
+    ];
+    env.plan_item_repo
+        .batch_insert(&items)
+        .expect("插入计划失败");
+
+    version_id
+}
+
+fn position(items: &[hot_rolling_aps::domain::plan::PlanItem], id: &str) -> (NaiveDate, i32) {
+    let item = items
+        .iter()
+        .find(|i| i.material_id == id)
+        .unwrap_or_else(|| panic!("缺少材料{}", id));
+    (item.plan_date, item.seq_no)
+}
+
+#[test]
+fn test_preview_rush_insertion_with_displacement_chain() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_rush(&env);
+
+    let resp = env
+        .plan_api
+        .preview_rush_insertion(&version_id, &ids(&["U"]))
+        .expect("插单预览失败");
+
+    assert!(resp.derived_version_id.is_none());
+    assert_eq!(resp.first_open_date, today());
+    assert!(resp.infeasible.is_empty());
+    assert_eq!(resp.placements.len(), 1);
+    let placement = &resp.placements[0];
+    assert_eq!((placement.to_date, placement.seq_no), (day(1), 2));
+    assert_eq!(placement.from_date, None);
+    assert_eq!(placement.tardiness_days, 0);
+
+    // X1 被挤到 D2，D2 交期更远的 Y1 级联挤到 D3
+    let chain: Vec<(&str, NaiveDate, NaiveDate, usize)> = resp
+        .displaced
+        .iter()
+        .map(|d| (d.material_id.as_str(), d.from_date, d.to_date, d.chain_step))
+        .collect();
+    assert_eq!(
+        chain,
+        vec![("X1", day(1), day(2), 1), ("Y1", day(2), day(3), 2)]
+    );
+    assert_eq!(resp.added_tardiness_days, 0);
+    assert!((resp.displaced_weight_t - 130.0).abs() < 1e-6);
+
+    assert_eq!(resp.impact.added_count, 1);
+    assert_eq!(resp.impact.moved_count, 2);
+
+    // 预览不落库
+    let items = env.plan_item_repo.find_by_version(&version_id).unwrap();
+    assert_eq!(items.len(), 4);
+    assert_eq!(position(&items, "X1"), (day(1), 2));
+}
+
+#[test]
+fn test_apply_rush_insertion_creates_derived_version() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_rush(&env);
+
+    let resp = env
+        .plan_api
+        .apply_rush_insertion(&version_id, &ids(&["U"]), "planner", "客户催货")
+        .expect("插单失败");
+    let derived_id = resp.derived_version_id.clone().expect("缺少派生版本");
+    assert_ne!(derived_id, version_id);
+
+    let derived = env
+        .plan_version_repo
+        .find_by_id(&derived_id)
+        .unwrap()
+        .unwrap();
+    let meta = VersionLineageMeta::from_snapshot(derived.config_snapshot_json.as_deref());
+    assert_eq!(meta.trigger, Some(VersionTrigger::RushInsert));
+    assert_eq!(meta.parent_version_id.as_deref(), Some(version_id.as_str()));
+
+    // 派生版本：插单 + 挤出链落位
+    let items = env.plan_item_repo.find_by_version(&derived_id).unwrap();
+    assert_eq!(items.len(), 5);
+    assert_eq!(position(&items, "LK"), (day(1), 1));
+    assert_eq!(position(&items, "U"), (day(1), 2));
+    assert_eq!(position(&items, "X2"), (day(1), 3));
+    assert_eq!(position(&items, "X1"), (day(2), 1));
+    assert_eq!(position(&items, "Y1"), (day(3), 1));
+    let u = items.iter().find(|i| i.material_id == "U").unwrap();
+    assert_eq!(u.source_type, "MANUAL");
+    assert_eq!(u.assign_reason.as_deref(), Some("RUSH_INSERT"));
+    let y1 = items.iter().find(|i| i.material_id == "Y1").unwrap();
+    assert_eq!(y1.assign_reason.as_deref(), Some("RUSH_DISPLACED"));
+
+    // 产能池随派生版本复制，已用吨位按新明细重算
+    let pools = env
+        .capacity_pool_repo
+        .find_by_version_id(&derived_id)
+        .unwrap();
+    let used: Vec<(NaiveDate, f64)> = pools
+        .iter()
+        .map(|p| (p.plan_date, p.used_capacity_t))
+        .collect();
+    assert!(used.contains(&(day(1), 80.0)));
+    assert!(used.contains(&(day(2), 50.0)));
+    assert!(used.contains(&(day(3), 80.0)));
+
+    // 基准版本不变
+    let base_items = env.plan_item_repo.find_by_version(&version_id).unwrap();
+    assert_eq!(base_items.len(), 4);
+    assert_eq!(position(&base_items, "X1"), (day(1), 2));
+
+    // 派生版本与插单日志同一事务写入
+    let logs = env.action_log_repo.find_by_version_id(&derived_id).unwrap();
+    let mut actions: Vec<&str> = logs.iter().map(|l| l.action_type.as_str()).collect();
+    actions.sort();
+    assert_eq!(actions, vec!["CREATE_VERSION", "RUSH_INSERT"]);
+    let log = logs
+        .iter()
+        .find(|l| l.action_type == "RUSH_INSERT")
+        .expect("缺少插单日志");
+    assert!(log.detail.as_deref().unwrap().contains("客户催货"));
+    assert_eq!(log.impact_summary_json.as_ref().unwrap()["added_count"], 1);
+}
+
+#[test]
+fn test_rush_insertion_maturity_and_infeasible() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_rush(&env);
+
+    let resp = env
+        .plan_api
+        .preview_rush_insertion(&version_id, &ids(&["M", "BK", "LK", "NOPE", " "]))
+        .expect("插单预览失败");
+
+    // 未适温材料不早于最早可排日
+    assert_eq!(resp.placements.len(), 1);
+    assert_eq!(resp.placements[0].material_id, "M");
+    assert_eq!(resp.placements[0].to_date, day(3));
+    assert!(resp.displaced.is_empty());
+
+    let infeasible: Vec<&str> = resp
+        .infeasible
+        .iter()
+        .map(|i| i.material_id.as_str())
+        .collect();
+    assert_eq!(infeasible, vec!["BK", "LK", "NOPE"]);
+
+    // 全部不可插单时拒绝执行，不生成版本
+    let versions_before = env
+        .plan_api
+        .list_versions(&plan_id_of(&env, &version_id))
+        .unwrap();
+    let err = env
+        .plan_api
+        .apply_rush_insertion(&version_id, &ids(&["BK"]), "planner", "客户催货")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+    let versions_after = env
+        .plan_api
+        .list_versions(&plan_id_of(&env, &version_id))
+        .unwrap();
+    assert_eq!(versions_before.len(), versions_after.len());
+}
+
+#[test]
+fn test_rush_insertion_validation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_rush(&env);
+
+    assert_invalid_input(env.plan_api.preview_rush_insertion(&version_id, &[]));
+    assert_invalid_input(
+        env.plan_api
+            .preview_rush_insertion(&version_id, &ids(&[" "])),
+    );
+    assert_invalid_input(env.plan_api.preview_rush_insertion(" ", &ids(&["U"])));
+    assert!(matches!(
+        env.plan_api.preview_rush_insertion("missing", &ids(&["U"])),
+        Err(ApiError::NotFound(_))
+    ));
+    assert_invalid_input(env.plan_api.apply_rush_insertion(
+        &version_id,
+        &ids(&["U"]),
+        "planner",
+        " ",
+    ));
+    assert_invalid_input(env.plan_api.apply_rush_insertion(
+        &version_id,
+        &ids(&["U"]),
+        " ",
+        "客户催货",
+    ));
+}
+
+fn plan_id_of(env: &ApiTestEnv, version_id: &str) -> String {
+    env.plan_version_repo
+        .find_by_id(version_id)
+        .unwrap()
+        .unwrap()
+        .plan_id
+}
@@ -0,0 +1,239 @@
+// ==========================================
+// 场景/沙盘方案 集成测试
+// ==========================================
+// 测试范围:
+// 1. 场景评估在快照上进行,不写生产 material_state / risk_snapshot
+// 2. 机组停机/假设材料叠加层生效
+// 3. 评估结果提升为基准方案草稿版本(剔除假设材料)
+// 4. 场景方案禁止直接重算
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::MaterialBuilder;
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::db::open_sqlite_connection;
+use hot_rolling_aps::domain::scenario::{HypotheticalMaterial, MachineOutage, ScenarioOverlay};
+use hot_rolling_aps::domain::types::SchedState;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 6, day).unwrap()
+}
+
+fn count_rows(env: &ApiTestEnv, sql: &str) -> i64 {
+    let conn = open_sqlite_connection(&env.db_path).expect("打开数据库失败");
+    conn.query_row(sql, [], |row| row.get(0)).expect("查询失败")
+}
+
+/// 返回 (基准方案, 激活版本, 场景方案)
+fn prepare_scenario(env: &ApiTestEnv) -> (String, String, String) {
+    let ids = ["S1", "S2", "S3"];
+    env.prepare_materials(
+        ids.iter()
+            .map(|id| {
+                MaterialBuilder::new(id)
+                    .machine("H032")
+                    .weight(100.0)
+                    .output_age_days(30)
+                    .build()
+            })
+            .collect(),
+        ids.iter()
+            .map(|id| create_test_state(id, SchedState::Ready, 0))
+            .collect(),
+    )
+    .expect("准备材料失败");
+
+    let base_plan_id = env
+        .plan_api
+        .create_plan("基准方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(base_plan_id.clone(), 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.approve_version(&version_id).expect("审批失败");
+    env.plan_api
+        .activate_version(&version_id, "admin")
+        .expect("激活版本失败");
+
+    let scenario_plan_id = env
+        .scenario_api
+        .create_scenario_plan(&base_plan_id, "H032停机演练", "SCENARIO", "planner")
+        .expect("创建场景方案失败");
+    (base_plan_id, version_id, scenario_plan_id)
+}
+
+#[test]
+fn test_evaluate_scenario_is_isolated_from_production_state() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let api = &env.scenario_api;
+    let (_, version_id, scenario_plan_id) = prepare_scenario(&env);
+
+    let overlay = ScenarioOverlay {
+        machine_outages: vec![MachineOutage {
+            machine_code: "H032".to_string(),
+            date_from: d(1),
+            date_to: d(7),
+            reason: Some("检修".to_string()),
+        }],
+        hypothetical_materials: vec![HypotheticalMaterial {
+            material_id: "HYP1".to_string(),
+            machine_code: "H033".to_string(),
+            weight_t: 50.0,
+            width_mm: Some(1250.0),
+            thickness_mm: Some(2.5),
+            steel_mark: None,
+            due_date: Some(d(5)),
+        }],
+        ..Default::default()
+    };
+    api.save_scenario_overlay(&scenario_plan_id, overlay.clone(), "planner")
+        .expect("保存叠加层失败");
+    assert_eq!(
+        api.get_scenario_overlay(&scenario_plan_id)
+            .expect("查询叠加层失败"),
+        overlay
+    );
+
+    let state_before = count_rows(
+        &env,
+        "SELECT COUNT(*) FROM material_state WHERE scheduled_date IS NOT NULL",
+    );
+    let risk_before = count_rows(&env, "SELECT COUNT(*) FROM risk_snapshot");
+
+    let result = api
+        .evaluate_scenario(&scenario_plan_id, d(1), 7, None, "planner")
+        .expect("评估失败");
+    assert_eq!(result.base_version_id, version_id);
+
+    let run = api.get_scenario_run(&result.run_id).expect("查询评估失败");
+    assert!(run.items.iter().all(|i| i.machine_code != "H032"));
+    assert_eq!(run.hypothetical_material_ids, vec!["HYP1".to_string()]);
+    assert_eq!(result.hypothetical_scheduled, 1);
+
+    // 生产库未被触碰
+    assert_eq!(
+        count_rows(
+            &env,
+            "SELECT COUNT(*) FROM material_state WHERE scheduled_date IS NOT NULL"
+        ),
+        state_before
+    );
+    assert_eq!(
+        count_rows(&env, "SELECT COUNT(*) FROM risk_snapshot"),
+        risk_before
+    );
+    assert_eq!(
+        count_rows(
+            &env,
+            "SELECT COUNT(*) FROM material_master WHERE material_id = 'HYP1'"
+        ),
+        0
+    );
+    assert_eq!(
+        count_rows(
+            &env,
+            &format!(
+                "SELECT COUNT(*) FROM capacity_pool WHERE version_id = '{}' AND limit_capacity_t = 0",
+                version_id
+            )
+        ),
+        0
+    );
+}
+
+#[test]
+fn test_promote_scenario_creates_baseline_draft() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let api = &env.scenario_api;
+    let (base_plan_id, _, scenario_plan_id) = prepare_scenario(&env);
+
+    api.save_scenario_overlay(
+        &scenario_plan_id,
+        ScenarioOverlay {
+            hypothetical_materials: vec![HypotheticalMaterial {
+                material_id: "HYP1".to_string(),
+                machine_code: "H032".to_string(),
+                weight_t: 50.0,
+                width_mm: None,
+                thickness_mm: None,
+                steel_mark: None,
+                due_date: None,
+            }],
+            ..Default::default()
+        },
+        "planner",
+    )
+    .expect("保存叠加层失败");
+
+    let result = api
+        .evaluate_scenario(&scenario_plan_id, d(1), 7, None, "planner")
+        .expect("评估失败");
+    // 试算已排入基准材料,但生产状态不变
+    assert_eq!(
+        count_rows(
+            &env,
+            "SELECT COUNT(*) FROM material_state WHERE scheduled_date IS NOT NULL"
+        ),
+        0
+    );
+
+    let promoted = api
+        .promote_scenario_run(&result.run_id, "planner")
+        .expect("提升失败");
+    assert_eq!(promoted.plan_id, base_plan_id);
+    assert_eq!(promoted.skipped_hypothetical, 1);
+    assert!(promoted.items_count > 0);
+    assert_eq!(
+        promoted.items_count + promoted.skipped_hypothetical,
+        result.total_items
+    );
+
+    let version = env
+        .plan_api
+        .get_version_detail(&promoted.version_id)
+        .expect("查询版本失败");
+    assert!(version.is_draft());
+    let items = env
+        .plan_item_repo
+        .find_by_version(&promoted.version_id)
+        .expect("查询明细失败");
+    assert_eq!(items.len(), promoted.items_count);
+    assert!(items.iter().all(|i| i.material_id != "HYP1"));
+
+    // 同一评估结果不可重复提升
+    let err = api
+        .promote_scenario_run(&result.run_id, "planner")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+}
+
+#[test]
+fn test_scenario_plan_rejects_direct_recalc() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let api = &env.scenario_api;
+    let (base_plan_id, _, scenario_plan_id) = prepare_scenario(&env);
+
+    // 场景方案只能基于基准方案
+    let err = api
+        .create_scenario_plan(&scenario_plan_id, "嵌套场景", "SANDBOX", "planner")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+    assert!(api
+        .create_scenario_plan(&base_plan_id, "错误类型", "BASELINE", "planner")
+        .is_err());
+
+    let scenario_version = env
+        .plan_api
+        .create_version(scenario_plan_id, 7, None, None, "planner".to_string())
+        .expect("创建版本失败");
+    let err = env
+        .plan_api
+        .recalc_full(&scenario_version, d(1), None, "planner")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+}
@@ -0,0 +1,250 @@
+// ==========================================
+// 策略草案并发生成 集成测试
+// ==========================================
+// 测试范围:
+// 1. 多策略并发试算：结果顺序与输入一致，逐策略推送进度
+// 2. 取消进行中的生成：不再落库，response.cancelled = true
+// 3. 草案时间跨度上限可配置（strategy_draft_max_days）
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, Local, NaiveDate};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{CapacityPoolBuilder, MaterialBuilder, MaterialStateBuilder};
+use hot_rolling_aps::api::plan_api::{StrategyDraftProgress, StrategyDraftProgressStatus};
+use hot_rolling_aps::domain::types::SchedState;
+use std::sync::Mutex;
+
+const STRATEGIES: [&str; 4] = [
+    "balanced",
+    "urgent_first",
+    "capacity_first",
+    "cold_stock_first",
+];
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// 准备材料 + 激活基准版本，返回版本ID
+fn prepare_active_version(env: &ApiTestEnv) -> String {
+    let materials = (1..=6)
+        .map(|i| {
+            let mut master = MaterialBuilder::new(&format!("PD{}", i))
+                .machine("H032")
+                .weight(400.0)
+                .steel_mark(if i % 2 == 0 { "Q345" } else { "Q235" })
+                .due_date(today() + Duration::days(i))
+                .build();
+            master.stock_age_days = Some(10 * i as i32);
+            master
+        })
+        .collect();
+    let states = (1..=6)
+        .map(|i| {
+            MaterialStateBuilder::new(&format!("PD{}", i))
+                .sched_state(SchedState::Ready)
+                .stock_age_days(10 * i as i32)
+                .build()
+        })
+        .collect();
+    env.prepare_materials(materials, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("草案并发测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.prepare_capacity_pools(
+        (0..3)
+            .map(|i| {
+                CapacityPoolBuilder::new("H032", today() + Duration::days(i))
+                    .version_id(&version_id)
+                    .target(1000.0)
+                    .limit(1200.0)
+                    .build()
+            })
+            .collect(),
+    )
+    .expect("准备产能池失败");
+    env.approve_version(&version_id).expect("审批失败");
+    env.plan_api
+        .activate_version(&version_id, "admin")
+        .expect("激活版本失败");
+    version_id
+}
+
+fn strategies() -> Vec<String> {
+    STRATEGIES.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_parallel_generation_reports_progress_per_strategy() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_active_version(&env);
+    let (from, to) = (today(), today() + Duration::days(2));
+
+    let events: Mutex<Vec<StrategyDraftProgress>> = Mutex::new(Vec::new());
+    let resp = env
+        .plan_api
+        .generate_strategy_drafts_with_progress(
+            &version_id,
+            from,
+            to,
+            strategies(),
+            "admin",
+            Some("gen-progress"),
+            &|p| events.lock().unwrap().push(p),
+        )
+        .expect("生成草案失败");
+
+    assert!(!resp.cancelled);
+    assert_eq!(resp.generation_id, "gen-progress");
+    let order: Vec<&str> = resp.drafts.iter().map(|d| d.strategy.as_str()).collect();
+    assert_eq!(order, STRATEGIES.to_vec());
+
+    let events = events.into_inner().unwrap();
+    assert_eq!(events.len(), STRATEGIES.len() * 2);
+    assert!(events
+        .iter()
+        .all(|e| e.generation_id == "gen-progress" && e.total_count == 4));
+    for draft in &resp.drafts {
+        let completed = events
+            .iter()
+            .find(|e| {
+                e.strategy == draft.strategy && e.status == StrategyDraftProgressStatus::Completed
+            })
+            .expect("缺少完成事件");
+        assert_eq!(completed.draft_id.as_deref(), Some(draft.draft_id.as_str()));
+    }
+    let mut finished: Vec<usize> = events
+        .iter()
+        .filter(|e| e.status == StrategyDraftProgressStatus::Completed)
+        .map(|e| e.finished_count)
+        .collect();
+    finished.sort_unstable();
+    assert_eq!(finished, vec![1, 2, 3, 4]);
+
+    // 共享快照并发试算与单策略生成结果一致
+    let single = env
+        .plan_api
+        .generate_strategy_drafts(&version_id, from, to, vec!["balanced".to_string()], "admin")
+        .expect("生成草案失败");
+    assert_eq!(single.drafts[0].objectives, resp.drafts[0].objectives);
+    assert_eq!(
+        single.drafts[0].plan_items_count,
+        resp.drafts[0].plan_items_count
+    );
+}
+
+#[test]
+fn test_cancel_in_flight_generation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_active_version(&env);
+    let (from, to) = (today(), today() + Duration::days(2));
+
+    // 收到开始事件即取消：进行中的策略在排产日边界停止，不再落库
+    let events: Mutex<Vec<StrategyDraftProgress>> = Mutex::new(Vec::new());
+    let resp = env
+        .plan_api
+        .generate_strategy_drafts_with_progress(
+            &version_id,
+            from,
+            to,
+            strategies(),
+            "admin",
+            Some("gen-cancel"),
+            &|p| {
+                if p.status == StrategyDraftProgressStatus::Started {
+                    assert!(env
+                        .plan_api
+                        .cancel_strategy_draft_generation("gen-cancel")
+                        .unwrap());
+                }
+                events.lock().unwrap().push(p);
+            },
+        )
+        .expect("取消不应返回错误");
+
+    assert!(resp.cancelled);
+    assert!(resp.drafts.is_empty());
+    let events = events.into_inner().unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .filter(|e| e.status == StrategyDraftProgressStatus::Cancelled)
+            .count(),
+        STRATEGIES.len()
+    );
+
+    let listed = env
+        .plan_api
+        .list_strategy_drafts(&version_id, from, to, None, None)
+        .expect("查询草案失败");
+    assert!(listed.drafts.is_empty());
+
+    // 任务结束后取消返回 false
+    assert!(!env
+        .plan_api
+        .cancel_strategy_draft_generation("gen-cancel")
+        .unwrap());
+    assert_invalid_input(env.plan_api.cancel_strategy_draft_generation(" "));
+}
+
+#[test]
+fn test_configurable_horizon_limit() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_active_version(&env);
+    let from = today();
+
+    // 默认 60 天
+    assert_invalid_input(env.plan_api.generate_strategy_drafts(
+        &version_id,
+        from,
+        from + Duration::days(61),
+        vec!["balanced".to_string()],
+        "admin",
+    ));
+
+    env.config_api
+        .update_config(
+            "global",
+            "strategy_draft_max_days",
+            "90",
+            "admin",
+            "扩大草案跨度",
+        )
+        .expect("更新配置失败");
+    env.plan_api
+        .generate_strategy_drafts(
+            &version_id,
+            from,
+            from + Duration::days(61),
+            vec!["balanced".to_string()],
+            "admin",
+        )
+        .expect("配置放宽后应允许生成");
+
+    env.config_api
+        .update_config(
+            "global",
+            "strategy_draft_max_days",
+            "5",
+            "admin",
+            "收紧草案跨度",
+        )
+        .expect("更新配置失败");
+    assert_invalid_input(env.plan_api.generate_strategy_drafts(
+        &version_id,
+        from,
+        from + Duration::days(6),
+        vec!["balanced".to_string()],
+        "admin",
+    ));
+}
@@ -14,7 +14,7 @@ mod strategy_draft_persistence_test {
     use chrono::NaiveDate;
     use chrono::{Duration, NaiveDateTime};
     use hot_rolling_aps::api::plan_api::StrategyDraftSummary;
-    use hot_rolling_aps::api::PlanApi;
+    use hot_rolling_aps::api::{ActivationRedLineValidator, PlanApi};
     use hot_rolling_aps::config::config_manager::ConfigManager;
     use hot_rolling_aps::engine::{
         capacity_filler::CapacityFiller, eligibility::EligibilityEngine, priority::PrioritySorter,
@@ -52,6 +52,14 @@ mod strategy_draft_persistence_test {
             Arc::new(CapacityPoolRepository::new(db_path.to_string()).unwrap());
         let roller_campaign_repo = Arc::new(RollerCampaignRepository::new(db_path).unwrap());
         let path_override_pending_repo = Arc::new(PathOverridePendingRepository::new(conn.clone()));
+        let activation_validator = Arc::new(ActivationRedLineValidator::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            material_state_repo.clone(),
+            capacity_pool_repo.clone(),
+            roller_campaign_repo.clone(),
+            path_override_pending_repo.clone(),
+        ));
 
         let config_manager = Arc::new(ConfigManager::new(db_path).unwrap());
         let eligibility_engine = Arc::new(EligibilityEngine::new(config_manager.clone()));
@@ -92,6 +100,7 @@ mod strategy_draft_persistence_test {
             config_manager,
             recalc_engine,
             risk_engine,
+            activation_validator,
             None, // 测试环境不需要事件发布
         ))
     }
@@ -120,6 +129,7 @@ mod strategy_draft_persistence_test {
             )
             .unwrap();
 
+        test_helpers::approve_version_for_activation(&db_path, &base_version_id).unwrap();
         plan_api
             .activate_version(&base_version_id, operator)
             .unwrap();
@@ -221,6 +231,7 @@ mod strategy_draft_persistence_test {
             )
             .unwrap();
 
+        test_helpers::approve_version_for_activation(&db_path, &base_version_id).unwrap();
         plan_api
             .activate_version(&base_version_id, operator)
             .unwrap();
@@ -250,6 +261,7 @@ mod strategy_draft_persistence_test {
             removed_count: 0,
             squeezed_out_count: 0,
             message: "expired draft".to_string(),
+            objectives: None,
         };
 
         let draft = StrategyDraftEntity {
@@ -0,0 +1,242 @@
+// ==========================================
+// 自定义策略权重参数扫描 集成测试
+// ==========================================
+// 测试范围:
+// 1. 网格扫描: 每个组合生成一条草案，Pareto 标记与支配计数一致
+// 2. 扫描点保存为自定义策略
+// 3. 参数校验
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, Local, NaiveDate};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{CapacityPoolBuilder, MaterialBuilder, MaterialStateBuilder};
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::domain::types::SchedState;
+use hot_rolling_aps::engine::{StrategySweepSpec, SweepSampleMode, WeightRange};
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// 准备材料 + 激活基准版本，返回版本ID
+fn prepare_active_version(env: &ApiTestEnv) -> String {
+    let spec = [
+        ("SW1", "Q235", Some(1), 5),
+        ("SW2", "Q345", Some(2), 60),
+        ("SW3", "Q235", None, 45),
+        ("SW4", "Q345", Some(3), 10),
+        ("SW5", "Q235", None, 90),
+    ];
+    let materials = spec
+        .iter()
+        .map(|(id, grade, due, age)| {
+            let mut builder = MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(500.0)
+                .steel_mark(grade);
+            if let Some(days) = due {
+                builder = builder.due_date(today() + Duration::days(*days));
+            }
+            let mut master = builder.build();
+            master.stock_age_days = Some(*age);
+            master
+        })
+        .collect();
+    let states = spec
+        .iter()
+        .map(|(id, _, _, age)| {
+            MaterialStateBuilder::new(id)
+                .sched_state(SchedState::Ready)
+                .stock_age_days(*age)
+                .build()
+        })
+        .collect();
+    env.prepare_materials(materials, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("参数扫描测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    env.prepare_capacity_pools(
+        (0..3)
+            .map(|i| {
+                CapacityPoolBuilder::new("H032", today() + Duration::days(i))
+                    .version_id(&version_id)
+                    .target(1000.0)
+                    .limit(1200.0)
+                    .build()
+            })
+            .collect(),
+    )
+    .expect("准备产能池失败");
+    env.approve_version(&version_id).expect("审批失败");
+    env.plan_api
+        .activate_version(&version_id, "admin")
+        .expect("激活版本失败");
+    version_id
+}
+
+fn grid_spec() -> StrategySweepSpec {
+    StrategySweepSpec {
+        base_strategy: "balanced".to_string(),
+        mode: SweepSampleMode::Grid,
+        urgent_weight: Some(WeightRange {
+            min: 0.0,
+            max: 20.0,
+            steps: 2,
+        }),
+        capacity_weight: None,
+        cold_stock_weight: Some(WeightRange {
+            min: 0.0,
+            max: 50.0,
+            steps: 2,
+        }),
+        due_date_weight: None,
+        rolling_output_age_weight: None,
+        sample_count: None,
+        seed: None,
+        cold_stock_age_threshold_days: Some(30),
+        overflow_tolerance_pct: None,
+    }
+}
+
+#[test]
+fn test_grid_sweep_generates_drafts_and_pareto_set() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_active_version(&env);
+    let (from, to) = (today(), today() + Duration::days(2));
+
+    let resp = env
+        .plan_api
+        .run_strategy_sweep(&version_id, from, to, grid_spec(), "admin")
+        .expect("参数扫描失败");
+
+    assert_eq!(resp.base_strategy, "balanced");
+    assert_eq!(resp.points.len(), 4);
+    assert!(resp.pareto_count >= 1);
+    assert_eq!(
+        resp.pareto_count,
+        resp.points.iter().filter(|p| p.pareto_optimal).count()
+    );
+    for (i, point) in resp.points.iter().enumerate() {
+        assert_eq!(point.index, i + 1);
+        assert_eq!(point.pareto_optimal, point.dominated_by_count == 0);
+        assert_eq!(point.parameters.cold_stock_age_threshold_days, Some(30));
+        assert!(point.parameters.urgent_weight.is_some());
+        assert!(point.parameters.capacity_weight.is_none());
+    }
+
+    // 每个扫描点都是一条可恢复/可发布的草案
+    let drafts = env
+        .plan_api
+        .list_strategy_drafts(&version_id, from, to, None, None)
+        .expect("查询草案失败");
+    assert_eq!(drafts.drafts.len(), 4);
+    for point in &resp.points {
+        let draft = drafts
+            .drafts
+            .iter()
+            .find(|d| d.draft_id == point.draft_id)
+            .expect("扫描点草案未落库");
+        assert_eq!(draft.strategy, point.strategy_key);
+    }
+}
+
+#[test]
+fn test_sweep_point_can_be_saved_as_custom_strategy() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_active_version(&env);
+
+    let mut spec = grid_spec();
+    spec.mode = SweepSampleMode::Random;
+    spec.sample_count = Some(2);
+    spec.seed = Some(42);
+    let resp = env
+        .plan_api
+        .run_strategy_sweep(
+            &version_id,
+            today(),
+            today() + Duration::days(2),
+            spec,
+            "admin",
+        )
+        .expect("参数扫描失败");
+    assert_eq!(resp.points.len(), 2);
+
+    let point = resp
+        .points
+        .iter()
+        .find(|p| p.pareto_optimal)
+        .expect("至少一个 Pareto 最优点");
+    let profile = point.to_custom_profile(&resp.base_strategy, "sweep_best", "扫描最优");
+    env.config_api
+        .save_custom_strategy(profile, "admin", "参数扫描保存")
+        .expect("保存自定义策略失败");
+
+    let saved = env
+        .config_api
+        .list_custom_strategies()
+        .expect("查询自定义策略失败")
+        .into_iter()
+        .find(|p| p.strategy_id == "sweep_best")
+        .expect("自定义策略未保存");
+    assert_eq!(saved.base_strategy, "balanced");
+    assert_eq!(
+        saved.parameters.urgent_weight,
+        point.parameters.urgent_weight
+    );
+    assert_eq!(
+        saved.parameters.cold_stock_weight,
+        point.parameters.cold_stock_weight
+    );
+}
+
+#[test]
+fn test_sweep_validation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_active_version(&env);
+    let (from, to) = (today(), today() + Duration::days(2));
+
+    let mut no_axis = grid_spec();
+    no_axis.urgent_weight = None;
+    no_axis.cold_stock_weight = None;
+    assert_invalid_input(
+        env.plan_api
+            .run_strategy_sweep(&version_id, from, to, no_axis, "admin"),
+    );
+
+    let mut out_of_range = grid_spec();
+    out_of_range.urgent_weight = Some(WeightRange {
+        min: 0.0,
+        max: 150.0,
+        steps: 2,
+    });
+    assert_invalid_input(env.plan_api.run_strategy_sweep(
+        &version_id,
+        from,
+        to,
+        out_of_range,
+        "admin",
+    ));
+
+    let mut bad_base = grid_spec();
+    bad_base.base_strategy = "unknown".to_string();
+    assert_invalid_input(
+        env.plan_api
+            .run_strategy_sweep(&version_id, from, to, bad_base, "admin"),
+    );
+
+    assert!(matches!(
+        env.plan_api
+            .run_strategy_sweep("NOT_EXISTS", from, to, grid_spec(), "admin"),
+        Err(ApiError::NotFound(_))
+    ));
+}
@@ -0,0 +1,275 @@
+// ==========================================
+// 人工调整反推策略权重 集成测试
+// ==========================================
+// 测试范围:
+// 1. MOVE_ITEMS 日志记录原位置 -> 目标位置，构造偏好样本并拟合权重
+// 2. 锁定材料剔除、旧版日志跳过、设紧急证据给出阈值建议
+// 3. 学习结果保存为自定义策略后一致率复现
+// 4. 参数校验
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, Local, NaiveDate};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{MaterialBuilder, MaterialStateBuilder, PlanItemBuilder};
+use hot_rolling_aps::api::plan_api::MoveItemRequest;
+use hot_rolling_aps::api::ValidationMode;
+use hot_rolling_aps::domain::action_log::ActionLog;
+use hot_rolling_aps::domain::types::SchedState;
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn day(offset: i64) -> NaiveDate {
+    today() + Duration::days(offset)
+}
+
+/// (材料, 计划日偏移, 库龄, 交期偏移, 是否锁定)
+///
+/// - X*: 库龄大、交期远（均衡策略冷料优先会排在前面）
+/// - N*: 库龄小、交期近（计划员人工提前）
+/// - L1: 锁定材料（不参与拟合）
+/// - U*: 未排产，仅用于设紧急证据
+const MATERIALS: [(&str, Option<i64>, i32, i64, bool); 11] = [
+    ("X1", Some(1), 60, 30, false),
+    ("X2", Some(1), 50, 25, false),
+    ("X3", Some(2), 40, 20, false),
+    ("X4", Some(2), 35, 22, false),
+    ("L1", Some(2), 45, 28, true),
+    ("X5", Some(3), 30, 18, false),
+    ("N1", Some(3), 5, 2, false),
+    ("N2", Some(3), 3, 1, false),
+    ("U1", None, 10, 10, false),
+    ("U2", None, 10, 12, false),
+    ("U3", None, 10, 14, false),
+];
+
+/// 准备材料 + 版本计划，并执行两次人工移动，返回版本ID
+fn prepare_adjustments(env: &ApiTestEnv) -> String {
+    let masters = MATERIALS
+        .iter()
+        .map(|(id, _, age, due, _)| {
+            let mut master = MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(10.0)
+                .due_date(day(*due))
+                .build();
+            master.stock_age_days = Some(*age);
+            master
+        })
+        .collect();
+    let states = MATERIALS
+        .iter()
+        .map(|(id, _, age, _, locked)| {
+            let builder = MaterialStateBuilder::new(id).stock_age_days(*age);
+            if *locked {
+                builder.locked().build()
+            } else {
+                builder.sched_state(SchedState::Ready).build()
+            }
+        })
+        .collect();
+    env.prepare_materials(masters, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("权重学习测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+    let items: Vec<_> = MATERIALS
+        .iter()
+        .filter_map(|(id, offset, ..)| offset.map(|o| (id, o)))
+        .enumerate()
+        .map(|(i, (id, offset))| {
+            PlanItemBuilder::new(&version_id, id, "H032", day(offset))
+                .seq_no(i as i32 + 1)
+                .weight(10.0)
+                .build()
+        })
+        .collect();
+    env.plan_item_repo
+        .batch_insert(&items)
+        .expect("插入计划失败");
+
+    let move_to = |material_id: &str, offset: i64| MoveItemRequest {
+        material_id: material_id.to_string(),
+        to_date: day(offset).format("%Y-%m-%d").to_string(),
+        to_seq: 1,
+        to_machine: "H032".to_string(),
+    };
+    // 提前: N1 3日->1日, N2 3日->2日
+    env.plan_api
+        .move_items(
+            &version_id,
+            vec![move_to("N1", 1), move_to("N2", 2)],
+            ValidationMode::AutoFix,
+            "planner",
+            Some("临期订单提前"),
+        )
+        .expect("移动失败");
+    // 推后: X1 1日->3日
+    env.plan_api
+        .move_items(
+            &version_id,
+            vec![move_to("X1", 3)],
+            ValidationMode::AutoFix,
+            "planner",
+            Some("远交期冷料推后"),
+        )
+        .expect("移动失败");
+
+    version_id
+}
+
+#[test]
+fn test_move_log_records_from_and_to_positions() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_adjustments(&env);
+
+    let logs = env
+        .action_log_repo
+        .find_by_version_id(&version_id)
+        .expect("查询日志失败");
+    let log = logs
+        .iter()
+        .find(|l| {
+            l.action_type == "MOVE_ITEMS" && l.detail.as_deref().unwrap_or("").contains("临期")
+        })
+        .expect("缺少移动日志");
+    let moves = log.payload_json.as_ref().unwrap()["moves"]
+        .as_array()
+        .expect("缺少 moves 明细")
+        .clone();
+    assert_eq!(moves.len(), 2);
+    assert_eq!(moves[0]["material_id"], "N1");
+    assert_eq!(moves[0]["from_date"], day(3).format("%Y-%m-%d").to_string());
+    assert_eq!(moves[0]["from_machine"], "H032");
+    assert_eq!(moves[0]["to_date"], day(1).format("%Y-%m-%d").to_string());
+}
+
+#[test]
+fn test_learn_weights_from_manual_moves() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_adjustments(&env);
+
+    // 旧版日志（无 moves 明细）跳过
+    env.action_log_repo
+        .insert(&ActionLog {
+            action_id: "legacy-move".to_string(),
+            version_id: Some(version_id.clone()),
+            action_type: "MOVE_ITEMS".to_string(),
+            action_ts: Local::now().naive_local(),
+            actor: "planner".to_string(),
+            payload_json: Some(serde_json::json!({ "moved_materials": ["X2"] })),
+            impact_summary_json: None,
+            machine_code: None,
+            date_range_start: None,
+            date_range_end: None,
+            detail: None,
+        })
+        .expect("插入日志失败");
+    // 人工设紧急：距交期 10~14 天
+    env.material_api
+        .batch_set_urgent(
+            vec!["U1".to_string(), "U2".to_string(), "U3".to_string()],
+            true,
+            "planner",
+            "客户催货",
+        )
+        .expect("设紧急失败");
+    // 取消紧急不作为证据
+    env.material_api
+        .batch_set_urgent(vec!["X5".to_string()], false, "planner", "取消紧急")
+        .expect("取消紧急失败");
+
+    let report = env
+        .plan_api
+        .learn_strategy_weights(day(-1), today(), "balanced")
+        .expect("权重学习失败");
+
+    assert_eq!(report.strategy_key, "balanced");
+    assert_eq!(report.move_log_count, 3);
+    assert_eq!(report.legacy_move_log_count, 1);
+    assert_eq!(report.urgent_log_count, 1);
+    // N1 > {X3, X4, X5}, N2 > {X5}, {X2, X3, X4} > X1；L1 锁定剔除 2 条
+    assert_eq!(report.pair_count, 7);
+    assert_eq!(report.excluded_pair_count, 2);
+    assert!(!report.pairs_truncated);
+
+    // 均衡策略冷料优先，与人工选择完全相反；学习后交期权重主导
+    assert_eq!(report.baseline_agreement.agreed, 0);
+    assert_eq!(report.learned_agreement.agreed, 7);
+    assert!((report.learned_agreement.rate - 1.0).abs() < 1e-9);
+    assert!(report.learned_parameters.due_date_weight.unwrap_or(0.0) > 0.0);
+
+    // 默认 N1=3/N2=7 天，人工提级证据 10/12/14 天
+    assert_eq!((report.urgent_n1_days, report.urgent_n2_days), (3, 7));
+    let keys: Vec<(&str, i64)> = report
+        .rule_suggestions
+        .iter()
+        .map(|s| (s.config_key.as_str(), s.suggested_value))
+        .collect();
+    assert_eq!(keys, vec![("urgent_n1_days", 12), ("urgent_n2_days", 16)]);
+}
+
+#[test]
+fn test_learned_profile_reproduces_agreement() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    prepare_adjustments(&env);
+
+    let report = env
+        .plan_api
+        .learn_strategy_weights(day(-1), today(), "balanced")
+        .expect("权重学习失败");
+    let profile = report.to_custom_profile("learned", "人工调整学习");
+    assert_eq!(profile.base_strategy, "balanced");
+    env.config_api
+        .save_custom_strategy(profile, "admin", "采纳学习结果")
+        .expect("保存自定义策略失败");
+
+    let relearned = env
+        .plan_api
+        .learn_strategy_weights(day(-1), today(), "custom:learned")
+        .expect("权重学习失败");
+    assert_eq!(relearned.strategy_key, "custom:learned");
+    assert_eq!(relearned.baseline_agreement, report.learned_agreement);
+    assert_eq!(relearned.learned_agreement, report.learned_agreement);
+
+    // 窗口外没有样本：参数不变，一致率为 0/0
+    let empty = env
+        .plan_api
+        .learn_strategy_weights(day(-30), day(-10), "balanced")
+        .expect("权重学习失败");
+    assert_eq!(empty.pair_count, 0);
+    assert_eq!(empty.learned_agreement.total, 0);
+    assert!(empty.rule_suggestions.is_empty());
+}
+
+#[test]
+fn test_learn_weights_validation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+
+    assert_invalid_input(
+        env.plan_api
+            .learn_strategy_weights(today(), day(-1), "balanced"),
+    );
+    assert_invalid_input(
+        env.plan_api
+            .learn_strategy_weights(day(-400), today(), "balanced"),
+    );
+    assert_invalid_input(
+        env.plan_api
+            .learn_strategy_weights(day(-1), today(), "unknown"),
+    );
+    assert_invalid_input(
+        env.plan_api
+            .learn_strategy_weights(day(-1), today(), "custom:missing"),
+    );
+}
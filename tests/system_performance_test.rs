@@ -10,7 +10,7 @@ mod test_helpers;
 #[cfg(test)]
 mod system_performance_test {
     use chrono::{NaiveDate, Utc};
-    use hot_rolling_aps::api::PlanApi;
+    use hot_rolling_aps::api::{ActivationRedLineValidator, PlanApi};
     use hot_rolling_aps::config::config_manager::ConfigManager;
     use hot_rolling_aps::domain::{MaterialMaster, MaterialState};
     use hot_rolling_aps::engine::{
@@ -162,6 +162,14 @@ mod system_performance_test {
             Arc::new(CapacityPoolRepository::new(db_path.to_string()).unwrap());
         let roller_campaign_repo = Arc::new(RollerCampaignRepository::new(&db_path).unwrap());
         let path_override_pending_repo = Arc::new(PathOverridePendingRepository::new(conn.clone()));
+        let activation_validator = Arc::new(ActivationRedLineValidator::new(
+            plan_version_repo.clone(),
+            plan_item_repo.clone(),
+            material_state_repo.clone(),
+            capacity_pool_repo.clone(),
+            roller_campaign_repo.clone(),
+            path_override_pending_repo.clone(),
+        ));
 
         let config_manager = Arc::new(ConfigManager::new(&db_path).unwrap());
         let eligibility_engine = Arc::new(EligibilityEngine::new(config_manager.clone()));
@@ -202,6 +210,7 @@ mod system_performance_test {
             config_manager.clone(),
             recalc_engine,
             risk_engine,
+            activation_validator,
             None, // 测试环境不需要事件发布
         ));
 
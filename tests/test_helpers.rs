@@ -292,6 +292,7 @@ fn init_schema(conn: &Connection) -> Result<(), Box<dyn Error>> {
             locked_in_plan INTEGER NOT NULL DEFAULT 0,
             force_release_in_plan INTEGER NOT NULL DEFAULT 0,
             violation_flags TEXT,
+            assign_reason TEXT,
             PRIMARY KEY (version_id, material_id)
         )
         "#,
@@ -745,3 +746,43 @@ pub fn insert_test_config(conn: &Connection) -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+/// 走完版本审批流（planner 提交 → manager 审批通过），使版本可激活
+///
+/// 用于不经 ApiTestEnv 构建 PlanApi 的测试
+pub fn approve_version_for_activation(
+    db_path: &str,
+    version_id: &str,
+) -> Result<(), Box<dyn Error>> {
+    use hot_rolling_aps::api::{ActivationRedLineValidator, VersionApprovalApi};
+    use hot_rolling_aps::repository::{
+        ActionLogRepository, CapacityPoolRepository, MaterialStateRepository,
+        PathOverridePendingRepository, PlanItemRepository, PlanVersionRepository,
+        RollerCampaignRepository, VersionReviewRepository,
+    };
+    use std::sync::{Arc, Mutex};
+
+    let conn = Arc::new(Mutex::new(open_sqlite_connection(db_path)?));
+    let plan_version_repo = Arc::new(PlanVersionRepository::new(conn.clone()));
+    let plan_item_repo = Arc::new(PlanItemRepository::new(conn.clone()));
+    let capacity_pool_repo = Arc::new(CapacityPoolRepository::from_connection(conn.clone()));
+    let activation_validator = Arc::new(ActivationRedLineValidator::new(
+        plan_version_repo.clone(),
+        plan_item_repo.clone(),
+        Arc::new(MaterialStateRepository::from_connection(conn.clone())),
+        capacity_pool_repo.clone(),
+        Arc::new(RollerCampaignRepository::from_connection(conn.clone())),
+        Arc::new(PathOverridePendingRepository::new(conn.clone())),
+    ));
+    let api = VersionApprovalApi::new(
+        plan_version_repo,
+        plan_item_repo,
+        capacity_pool_repo,
+        Arc::new(VersionReviewRepository::from_connection(conn.clone())?),
+        Arc::new(ActionLogRepository::new(conn)),
+        activation_validator,
+    );
+    api.submit_version(version_id, None, "planner")?;
+    api.approve_version(version_id, None, "manager")?;
+    Ok(())
+}
@@ -0,0 +1,346 @@
+// ==========================================
+// 人工操作撤销/重做 集成测试
+// ==========================================
+// 测试范围:
+// 1. move_items 撤销/重做：位置恢复、UNDO/REDO 日志、新操作清空重做栈
+// 2. 锁定 / 人工紧急 撤销：材料标志恢复，材料级操作计入版本撤销栈
+// 3. 路径突破确认撤销/重做
+// 4. 后续操作冲突时拒绝撤销并说明冲突来源，参数与空栈校验
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::{Duration, Local, NaiveDate};
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{MaterialBuilder, MaterialStateBuilder, PlanItemBuilder};
+use hot_rolling_aps::api::plan_api::MoveItemRequest;
+use hot_rolling_aps::api::{ApiError, ValidationMode};
+use hot_rolling_aps::domain::types::SchedState;
+
+fn day(offset: i64) -> NaiveDate {
+    Local::now().date_naive() + Duration::days(offset)
+}
+
+fn ids(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+/// 准备场景：H032 上 A/B 两个材料（D1），返回版本ID
+fn prepare_undo(env: &ApiTestEnv) -> String {
+    let masters = ["A", "B"]
+        .iter()
+        .map(|id| {
+            MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(20.0)
+                .build()
+        })
+        .collect();
+    let states = ["A", "B"]
+        .iter()
+        .map(|id| MaterialStateBuilder::new(id).build())
+        .collect();
+    env.prepare_materials(masters, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("撤销测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id, 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+
+    let items = vec![
+        PlanItemBuilder::new(&version_id, "A", "H032", day(1))
+            .seq_no(1)
+            .build(),
+        PlanItemBuilder::new(&version_id, "B", "H032", day(1))
+            .seq_no(2)
+            .build(),
+    ];
+    env.plan_item_repo
+        .batch_insert(&items)
+        .expect("插入计划失败");
+
+    version_id
+}
+
+fn position(env: &ApiTestEnv, version_id: &str, material_id: &str) -> (NaiveDate, i32) {
+    let item = env
+        .plan_item_repo
+        .find_by_version(version_id)
+        .unwrap()
+        .into_iter()
+        .find(|i| i.material_id == material_id)
+        .expect("排产项不存在");
+    (item.plan_date, item.seq_no)
+}
+
+fn move_a(env: &ApiTestEnv, version_id: &str, operator: &str) {
+    let result = env
+        .plan_api
+        .move_items(
+            version_id,
+            vec![MoveItemRequest {
+                material_id: "A".to_string(),
+                to_date: day(3).format("%Y-%m-%d").to_string(),
+                to_seq: 5,
+                to_machine: "H032".to_string(),
+            }],
+            ValidationMode::Strict,
+            operator,
+            None,
+        )
+        .expect("移动失败");
+    assert_eq!(result.success_count, 1);
+}
+
+#[test]
+fn test_undo_redo_move_items() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_undo(&env);
+    move_a(&env, &version_id, "planner");
+    assert_eq!(position(&env, &version_id, "A"), (day(3), 5));
+
+    let status = env
+        .undo_api
+        .get_undo_status(&version_id, "planner")
+        .unwrap();
+    assert_eq!(status.undo_depth, 1);
+    assert_eq!(status.redo_depth, 0);
+    let next = status.next_undo.unwrap();
+    assert_eq!(next.source_action_type, "MOVE_ITEMS");
+    assert_eq!(next.material_ids, ids(&["A"]));
+    // 撤销栈按操作人隔离
+    assert_eq!(
+        env.undo_api
+            .get_undo_status(&version_id, "someone_else")
+            .unwrap()
+            .undo_depth,
+        0
+    );
+
+    let undone = env
+        .undo_api
+        .undo_last(&version_id, "planner", Some("误操作"))
+        .expect("撤销失败");
+    assert_eq!(undone.kind, "UNDO");
+    assert_eq!(undone.payload_kind, "MOVE_ITEMS");
+    assert_eq!(position(&env, &version_id, "A"), (day(1), 1));
+    assert_eq!(undone.status.undo_depth, 0);
+    assert_eq!(undone.status.redo_depth, 1);
+
+    let logs = env.action_log_repo.find_by_version_id(&version_id).unwrap();
+    let undo_log = logs.iter().find(|l| l.action_type == "UNDO").unwrap();
+    let payload = undo_log.payload_json.as_ref().unwrap();
+    assert_eq!(
+        payload["history"]["target_action_id"],
+        undone.target_action_id
+    );
+    assert_eq!(payload["moved_materials"], serde_json::json!(["A"]));
+
+    let redone = env
+        .undo_api
+        .redo_last(&version_id, "planner", None)
+        .expect("重做失败");
+    assert_eq!(redone.kind, "REDO");
+    assert_eq!(redone.target_action_id, undone.action_id);
+    assert_eq!(position(&env, &version_id, "A"), (day(3), 5));
+    assert_eq!(redone.status.undo_depth, 1);
+    assert_eq!(redone.status.redo_depth, 0);
+
+    // 重做后可再次撤销；新操作使重做失效
+    env.undo_api
+        .undo_last(&version_id, "planner", None)
+        .expect("再次撤销失败");
+    assert_eq!(position(&env, &version_id, "A"), (day(1), 1));
+    move_a(&env, &version_id, "planner");
+    let status = env
+        .undo_api
+        .get_undo_status(&version_id, "planner")
+        .unwrap();
+    assert_eq!(status.undo_depth, 1);
+    assert_eq!(status.redo_depth, 0);
+}
+
+#[test]
+fn test_undo_material_flags() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_undo(&env);
+
+    env.material_api
+        .batch_lock_materials(
+            ids(&["A", "B"]),
+            true,
+            "planner",
+            "锁定",
+            ValidationMode::Strict,
+        )
+        .expect("锁定失败");
+    env.material_api
+        .batch_set_urgent(ids(&["B"]), true, "planner", "客户催货")
+        .expect("设置紧急失败");
+
+    // 材料级操作不关联版本，但计入该操作人的版本撤销栈
+    let status = env
+        .undo_api
+        .get_undo_status(&version_id, "planner")
+        .unwrap();
+    assert_eq!(status.undo_depth, 2);
+    assert_eq!(status.next_undo.unwrap().source_action_type, "SET_URGENT");
+
+    let undone = env
+        .undo_api
+        .undo_last(&version_id, "planner", None)
+        .expect("撤销紧急失败");
+    assert_eq!(undone.payload_kind, "MATERIAL_FLAGS");
+    let b = env.material_state_repo.find_by_id("B").unwrap().unwrap();
+    assert!(!b.manual_urgent_flag);
+    assert!(b.lock_flag);
+
+    env.undo_api
+        .undo_last(&version_id, "planner", None)
+        .expect("撤销锁定失败");
+    for id in ["A", "B"] {
+        let state = env.material_state_repo.find_by_id(id).unwrap().unwrap();
+        assert!(!state.lock_flag);
+        assert_eq!(state.sched_state, SchedState::Ready);
+    }
+
+    let status = env
+        .undo_api
+        .get_undo_status(&version_id, "planner")
+        .unwrap();
+    assert_eq!(status.undo_depth, 0);
+    assert_eq!(status.redo_depth, 2);
+    assert_eq!(
+        status.next_redo.unwrap().source_action_type,
+        "LOCK_MATERIALS"
+    );
+}
+
+#[test]
+fn test_undo_redo_path_override_confirm() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_undo(&env);
+
+    env.path_rule_api
+        .confirm_path_override(&version_id, "A", "planner", "客户同意跳宽")
+        .expect("确认失败");
+    assert!(
+        env.material_state_repo
+            .find_by_id("A")
+            .unwrap()
+            .unwrap()
+            .user_confirmed
+    );
+
+    let undone = env
+        .undo_api
+        .undo_last(&version_id, "planner", None)
+        .expect("撤销确认失败");
+    assert_eq!(undone.payload_kind, "PATH_OVERRIDE");
+    assert_eq!(undone.source_action_type, "PathOverrideConfirm");
+    let state = env.material_state_repo.find_by_id("A").unwrap().unwrap();
+    assert!(!state.user_confirmed);
+    assert!(state.user_confirmed_by.is_none());
+
+    env.undo_api
+        .redo_last(&version_id, "planner", None)
+        .expect("重做确认失败");
+    let state = env.material_state_repo.find_by_id("A").unwrap().unwrap();
+    assert!(state.user_confirmed);
+    assert_eq!(state.user_confirmed_by.as_deref(), Some("planner"));
+    assert_eq!(state.user_confirmed_reason.as_deref(), Some("客户同意跳宽"));
+}
+
+#[test]
+fn test_undo_refuses_when_later_operation_conflicts() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_undo(&env);
+
+    env.material_api
+        .batch_lock_materials(ids(&["A"]), true, "planner", "锁定", ValidationMode::Strict)
+        .expect("锁定失败");
+    // 其他人随后解锁
+    env.material_api
+        .batch_lock_materials(
+            ids(&["A"]),
+            false,
+            "shift_lead",
+            "解锁",
+            ValidationMode::Strict,
+        )
+        .expect("解锁失败");
+
+    match env.undo_api.undo_last(&version_id, "planner", None) {
+        Err(ApiError::ManualOperationValidationError { violations, .. }) => {
+            assert_eq!(violations.len(), 1);
+            let v = &violations[0];
+            assert_eq!(v.violation_type, "UNDO_CONFLICT");
+            assert_eq!(v.material_id, "A");
+            assert!(v.reason.contains("UNLOCK_MATERIALS"));
+            assert!(v.reason.contains("shift_lead"));
+        }
+        other => panic!("期望撤销冲突, 实际: {:?}", other.map(|r| r.message)),
+    }
+    // 拒绝撤销时不落库、不写日志
+    let state = env.material_state_repo.find_by_id("A").unwrap().unwrap();
+    assert!(!state.lock_flag);
+    assert_eq!(
+        env.undo_api
+            .get_undo_status(&version_id, "planner")
+            .unwrap()
+            .undo_depth,
+        1
+    );
+
+    // 移动后材料被他人再次移动
+    move_a(&env, &version_id, "planner");
+    env.plan_api
+        .move_items(
+            &version_id,
+            vec![MoveItemRequest {
+                material_id: "A".to_string(),
+                to_date: day(4).format("%Y-%m-%d").to_string(),
+                to_seq: 1,
+                to_machine: "H032".to_string(),
+            }],
+            ValidationMode::Strict,
+            "shift_lead",
+            None,
+        )
+        .expect("移动失败");
+    match env.undo_api.undo_last(&version_id, "planner", None) {
+        Err(ApiError::ManualOperationValidationError { violations, .. }) => {
+            assert!(violations[0].reason.contains("MOVE_ITEMS"));
+        }
+        other => panic!("期望撤销冲突, 实际: {:?}", other.map(|r| r.message)),
+    }
+    assert_eq!(position(&env, &version_id, "A"), (day(4), 1));
+}
+
+#[test]
+fn test_undo_validation() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let version_id = prepare_undo(&env);
+    let api = &env.undo_api;
+
+    assert_invalid_input(api.get_undo_status(" ", "planner"));
+    assert_invalid_input(api.undo_last(&version_id, " ", None));
+    assert!(matches!(
+        api.undo_last("missing", "planner", None),
+        Err(ApiError::NotFound(_))
+    ));
+    assert!(matches!(
+        api.undo_last(&version_id, "planner", None),
+        Err(ApiError::BusinessRuleViolation(_))
+    ));
+    assert!(matches!(
+        api.redo_last(&version_id, "planner", None),
+        Err(ApiError::BusinessRuleViolation(_))
+    ));
+}
@@ -0,0 +1,235 @@
+// ==========================================
+// 版本激活审批流 集成测试
+// ==========================================
+// 测试范围:
+// 1. 未审批版本不可激活/回滚
+// 2. 提交 → 驳回 → 再提交 → 通过 → 激活 的完整流转
+// 3. 审批人不能与提交人相同，驳回必须填写意见
+// 4. 提交记录附带激活前检查单，所有动作写入 action_log
+// 5. 红线硬违规阻断激活，填写覆盖原因后可激活并留痕
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::PlanItemBuilder;
+use hot_rolling_aps::api::ApiError;
+use hot_rolling_aps::domain::red_line::RedLineSeverity;
+use hot_rolling_aps::domain::types::{PlanVersionStatus, SchedState};
+use hot_rolling_aps::domain::version_review::VersionReviewAction;
+
+fn create_draft_version(env: &ApiTestEnv) -> (String, String) {
+    let plan_id = env
+        .plan_api
+        .create_plan("审批测试方案".to_string(), "planner".to_string())
+        .expect("创建方案失败");
+    let version_id = env
+        .plan_api
+        .create_version(plan_id.clone(), 7, None, None, "planner".to_string())
+        .expect("创建版本失败");
+    (plan_id, version_id)
+}
+
+fn status_of(env: &ApiTestEnv, version_id: &str) -> PlanVersionStatus {
+    env.plan_version_repo
+        .find_by_id(version_id)
+        .expect("查询版本失败")
+        .expect("版本不存在")
+        .status
+}
+
+#[test]
+fn test_unapproved_version_cannot_be_activated() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (plan_id, version_id) = create_draft_version(&env);
+
+    let err = env
+        .plan_api
+        .activate_version(&version_id, "planner")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidStateTransition { .. }));
+
+    let err = env
+        .plan_api
+        .rollback_version(&plan_id, &version_id, "planner", "绕过审批")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+
+    env.version_approval_api
+        .submit_version(&version_id, None, "planner")
+        .expect("提交失败");
+    assert!(env
+        .plan_api
+        .activate_version(&version_id, "planner")
+        .is_err());
+    assert_eq!(status_of(&env, &version_id), PlanVersionStatus::Submitted);
+}
+
+#[test]
+fn test_submit_reject_resubmit_approve_activate() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (plan_id, version_id) = create_draft_version(&env);
+    let api = &env.version_approval_api;
+
+    // 提交：附检查单（空版本 → HAS_ITEMS 未通过，但不阻断提交）
+    let submit = api
+        .submit_version(&version_id, Some("早班计划".to_string()), "planner")
+        .expect("提交失败");
+    let checklist = submit.checklist.expect("提交记录应附带检查单");
+    assert!(checklist
+        .items
+        .iter()
+        .any(|i| i.code == "HAS_ITEMS" && !i.passed));
+
+    // 自审不允许；驳回必须填写意见
+    let err = api
+        .approve_version(&version_id, None, "planner")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+    let err = api
+        .reject_version(&version_id, "  ", "manager")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidInput(_)));
+
+    api.reject_version(&version_id, "H033 当日超产能", "manager")
+        .expect("驳回失败");
+    assert_eq!(status_of(&env, &version_id), PlanVersionStatus::Rejected);
+
+    // 已驳回不能直接审批，需重新提交
+    assert!(api.approve_version(&version_id, None, "manager").is_err());
+    api.submit_version(&version_id, Some("已调整".to_string()), "planner")
+        .expect("再次提交失败");
+    api.approve_version(&version_id, Some("同意".to_string()), "manager")
+        .expect("审批失败");
+    assert_eq!(status_of(&env, &version_id), PlanVersionStatus::Approved);
+
+    // 已审批未激活的版本不能经回滚上线（须走激活的红线校验）
+    let err = env
+        .plan_api
+        .rollback_version(&plan_id, &version_id, "manager", "绕过红线校验")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+    assert_eq!(status_of(&env, &version_id), PlanVersionStatus::Approved);
+
+    env.plan_api
+        .activate_version(&version_id, "manager")
+        .expect("激活失败");
+    assert_eq!(status_of(&env, &version_id), PlanVersionStatus::Active);
+    assert_eq!(
+        env.plan_version_repo
+            .find_active_version(&plan_id)
+            .expect("查询激活版本失败")
+            .map(|v| v.version_id),
+        Some(version_id.clone())
+    );
+
+    // 审批记录
+    let history = api.get_version_reviews(&version_id).expect("查询记录失败");
+    assert_eq!(history.status, PlanVersionStatus::Active);
+    let actions: Vec<VersionReviewAction> = history.reviews.iter().map(|r| r.action).collect();
+    assert_eq!(
+        actions,
+        vec![
+            VersionReviewAction::Submit,
+            VersionReviewAction::Reject,
+            VersionReviewAction::Submit,
+            VersionReviewAction::Approve,
+        ]
+    );
+    assert_eq!(
+        history.reviews[1].comment.as_deref(),
+        Some("H033 当日超产能")
+    );
+
+    // 审计日志
+    for (action_type, expected) in [
+        ("SUBMIT_VERSION", 2),
+        ("REJECT_VERSION", 1),
+        ("APPROVE_VERSION", 1),
+        ("ACTIVATE_VERSION", 1),
+    ] {
+        let logs = env
+            .action_log_repo
+            .find_by_action_type(action_type, 10)
+            .expect("查询 action_log 失败");
+        assert_eq!(logs.len(), expected, "{} 日志条数不符", action_type);
+    }
+}
+
+#[test]
+fn test_red_line_hard_violation_blocks_activation_without_override() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (_plan_id, version_id) = create_draft_version(&env);
+    let today = chrono::Local::now().date_naive();
+
+    // 未适温材料（3天后才可排）排在今天，且未强制放行
+    let material = create_test_material("RL001", "H032", 100.0, None);
+    let mut state = create_test_state("RL001", SchedState::PendingMature, 3);
+    state.ready_in_days = 3;
+    state.earliest_sched_date = Some(today + chrono::Duration::days(3));
+    env.prepare_materials(vec![material], vec![state])
+        .expect("准备材料失败");
+    env.plan_item_repo
+        .batch_insert(&[PlanItemBuilder::new(&version_id, "RL001", "H032", today)
+            .weight(100.0)
+            .source_type("MANUAL")
+            .assign_reason(None)
+            .build()])
+        .expect("插入明细失败");
+
+    let report = env
+        .plan_api
+        .get_red_line_report(&version_id)
+        .expect("生成红线报告失败");
+    assert_eq!(report.hard_count, 1);
+    assert_eq!(report.warning_count, 1);
+    let codes: Vec<(&str, RedLineSeverity)> = report
+        .violations
+        .iter()
+        .map(|v| (v.code.as_str(), v.severity))
+        .collect();
+    assert!(codes.contains(&("IMMATURE_NOT_RELEASED", RedLineSeverity::Hard)));
+    assert!(codes.contains(&("MISSING_ASSIGN_REASON", RedLineSeverity::Warning)));
+
+    // 检查单同步体现红线结果
+    let checklist = env
+        .version_approval_api
+        .build_activation_checklist(&version_id)
+        .expect("生成检查单失败");
+    assert!(checklist
+        .items
+        .iter()
+        .any(|i| i.code == "RED_LINES_CLEAR" && !i.passed));
+
+    env.approve_version(&version_id).expect("审批失败");
+    let err = env
+        .plan_api
+        .activate_version(&version_id, "manager")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+    assert!(env
+        .plan_api
+        .activate_version_with_override(&version_id, "manager", Some("   "))
+        .is_err());
+    assert_eq!(status_of(&env, &version_id), PlanVersionStatus::Approved);
+
+    env.plan_api
+        .activate_version_with_override(&version_id, "manager", Some("客户急单，已与质量部确认"))
+        .expect("覆盖激活失败");
+    assert_eq!(status_of(&env, &version_id), PlanVersionStatus::Active);
+
+    let logs = env
+        .action_log_repo
+        .find_by_action_type("ACTIVATE_VERSION", 10)
+        .expect("查询 action_log 失败");
+    assert_eq!(logs.len(), 1);
+    let payload = logs[0].payload_json.as_ref().expect("缺少 payload");
+    assert_eq!(
+        payload["red_line_override_reason"].as_str(),
+        Some("客户急单，已与质量部确认")
+    );
+    let impact = logs[0].impact_summary_json.as_ref().expect("缺少影响摘要");
+    assert_eq!(impact["red_line"]["hard_count"].as_u64(), Some(1));
+    assert_eq!(impact["red_line"]["overridden"].as_bool(), Some(true));
+}
@@ -0,0 +1,232 @@
+// ==========================================
+// 版本明细级对比 集成测试
+// ==========================================
+// 测试范围:
+// 1. 合同准时交付变化、钢种结构变化、同日顺序变化（Kendall-tau）
+// 2. 换辊次数变化
+// 3. 物料级差异分页查询（过滤/分页/参数校验）
+// 4. 路径突破确认数按版本日志统计（不受其他版本确认影响，撤销后回退）
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{MaterialBuilder, MaterialStateBuilder, PlanItemBuilder};
+use hot_rolling_aps::domain::roller::RollerCampaign;
+use hot_rolling_aps::domain::types::SchedState;
+use hot_rolling_aps::repository::roller_repo::RollerCampaignRepository;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2030, 6, day).unwrap()
+}
+
+/// 返回 (版本A, 版本B)
+///
+/// - A: 1日 M1,M2,M3; 2日 M4
+/// - B: 1日 M3,M1;    2日 M2,M4（M2 推迟，超出合同 C1 交期）
+fn prepare_versions(env: &ApiTestEnv) -> (String, String) {
+    let spec = [
+        ("M1", "Q235", Some(("C1", d(1)))),
+        ("M2", "Q345", Some(("C1", d(1)))),
+        ("M3", "Q235", Some(("C2", d(2)))),
+        ("M4", "Q345", None),
+    ];
+    let materials = spec
+        .iter()
+        .map(|(id, grade, contract)| {
+            let mut builder = MaterialBuilder::new(id)
+                .machine("H032")
+                .weight(10.0)
+                .steel_mark(grade);
+            if let Some((_, due)) = contract {
+                builder = builder.due_date(*due);
+            }
+            let mut master = builder.build();
+            master.contract_no = contract.map(|(c, _)| c.to_string());
+            master
+        })
+        .collect();
+    let states = spec
+        .iter()
+        .map(|(id, _, _)| {
+            MaterialStateBuilder::new(id)
+                .sched_state(SchedState::Ready)
+                .build()
+        })
+        .collect();
+    env.prepare_materials(materials, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("明细对比测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let layout = |rows: &[(&str, NaiveDate, i32)]| {
+        let version_id = env
+            .plan_api
+            .create_version(plan_id.clone(), 7, None, None, "admin".to_string())
+            .expect("创建版本失败");
+        let items: Vec<_> = rows
+            .iter()
+            .map(|(id, date, seq)| {
+                PlanItemBuilder::new(&version_id, id, "H032", *date)
+                    .seq_no(*seq)
+                    .weight(10.0)
+                    .build()
+            })
+            .collect();
+        env.plan_item_repo
+            .batch_insert(&items)
+            .expect("插入计划失败");
+        version_id
+    };
+
+    let a = layout(&[
+        ("M1", d(1), 1),
+        ("M2", d(1), 2),
+        ("M3", d(1), 3),
+        ("M4", d(2), 1),
+    ]);
+    let b = layout(&[
+        ("M3", d(1), 1),
+        ("M1", d(1), 2),
+        ("M2", d(2), 1),
+        ("M4", d(2), 2),
+    ]);
+    (a, b)
+}
+
+#[test]
+fn test_compare_versions_contract_grade_sequence_and_roll_delta() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (a, b) = prepare_versions(&env);
+
+    // 版本B 多一次换辊
+    let roller_repo = RollerCampaignRepository::new(&env.db_path).expect("创建换辊仓储失败");
+    for (version_id, campaign_no) in [(&a, 1), (&b, 1), (&b, 2)] {
+        roller_repo
+            .create(&RollerCampaign::new(
+                version_id.clone(),
+                "H032".to_string(),
+                campaign_no,
+                d(1),
+                None,
+                None,
+            ))
+            .expect("创建换辊周期失败");
+    }
+
+    let result = env.plan_api.compare_versions(&a, &b).expect("对比失败");
+    assert_eq!(result.moved_count, 1);
+
+    // 合同: 仅 C1 变化（M2 推迟超交期），C2 不变
+    let contracts = result.contract_delta.expect("缺少合同变化");
+    assert_eq!(contracts.len(), 1);
+    let c1 = &contracts[0];
+    assert_eq!(c1.contract_no, "C1");
+    assert_eq!((c1.on_time_count_a, c1.on_time_count_b), (2, 1));
+    assert!((c1.on_time_rate_delta + 0.5).abs() < 1e-9);
+    assert_eq!(c1.finish_date_b, Some(d(2)));
+
+    // 钢种: Q345 从1日移到2日
+    let mix = result.grade_mix_delta.expect("缺少钢种变化");
+    assert_eq!(mix.len(), 2);
+    assert!(mix.iter().all(|m| m.steel_grade == "Q345"));
+    assert_eq!(
+        mix.iter().map(|m| m.weight_delta).collect::<Vec<_>>(),
+        vec![-10.0, 10.0]
+    );
+
+    // 顺序: 1日 M1/M3 交换 → 1 对逆序；2日 仅序号顺延不计
+    let seq = result.sequence_delta.expect("缺少顺序变化");
+    assert_eq!(seq.len(), 1);
+    assert_eq!(seq[0].plan_date, d(1));
+    assert_eq!(seq[0].discordant_pairs, 1);
+    assert_eq!(seq[0].kendall_tau_distance, 1.0);
+
+    let roll = result.roll_change_delta.expect("缺少换辊变化");
+    assert_eq!(roll.len(), 1);
+    assert_eq!(roll[0].roll_change_delta, 1);
+    assert!(result.path_override_delta.is_none());
+}
+
+#[test]
+fn test_path_override_confirmed_count_is_per_version() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (a, b) = prepare_versions(&env);
+
+    // 仅在版本B确认 M1 的路径突破（material_state.user_confirmed 为全局标记）
+    env.path_rule_api
+        .confirm_path_override(&b, "M1", "planner", "紧急合同")
+        .expect("确认失败");
+
+    let delta = env
+        .plan_api
+        .compare_versions(&a, &b)
+        .expect("对比失败")
+        .path_override_delta
+        .expect("缺少路径突破变化");
+    assert_eq!((delta.confirmed_count_a, delta.confirmed_count_b), (0, 1));
+    assert_eq!(delta.confirmed_delta, 1);
+
+    // 撤销确认后两版本均无已确认突破
+    env.undo_api
+        .undo_last(&b, "planner", None)
+        .expect("撤销失败");
+    let result = env.plan_api.compare_versions(&a, &b).expect("对比失败");
+    assert!(result.path_override_delta.is_none());
+}
+
+#[test]
+fn test_list_version_diff_items_paged() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (a, b) = prepare_versions(&env);
+
+    // M2 移动，M1/M3/M4 序号变化
+    let page = env
+        .plan_api
+        .list_version_diff_items(&a, &b, None, None, 2, 0)
+        .expect("分页查询失败");
+    assert_eq!(page.total_count, 4);
+    assert_eq!(page.items.len(), 2);
+    let rest = env
+        .plan_api
+        .list_version_diff_items(&a, &b, None, None, 2, 2)
+        .expect("分页查询失败");
+    assert_eq!(rest.items.len(), 2);
+    assert!(page
+        .items
+        .iter()
+        .all(|i| rest.items.iter().all(|r| r.material_id != i.material_id)));
+
+    let moved = env
+        .plan_api
+        .list_version_diff_items(&a, &b, Some("moved"), Some("H032"), 100, 0)
+        .expect("过滤查询失败");
+    assert_eq!(moved.total_count, 1);
+    assert_eq!(moved.items[0].material_id, "M2");
+    assert_eq!(moved.items[0].plan_date_a, Some(d(1)));
+    assert_eq!(moved.items[0].plan_date_b, Some(d(2)));
+
+    // 反向对比: 无新增/删除
+    let added = env
+        .plan_api
+        .list_version_diff_items(&b, &a, Some("ADDED"), None, 100, 0)
+        .expect("过滤查询失败");
+    assert_eq!(added.total_count, 0);
+
+    assert_invalid_input(env.plan_api.list_version_diff_items(
+        &a,
+        &b,
+        Some("SWAPPED"),
+        None,
+        100,
+        0,
+    ));
+    assert_invalid_input(
+        env.plan_api
+            .list_version_diff_items(&a, &b, None, None, 0, 0),
+    );
+}
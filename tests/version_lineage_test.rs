@@ -0,0 +1,201 @@
+// ==========================================
+// 版本谱系与保留策略 集成测试
+// ==========================================
+// 测试范围:
+// 1. 人工创建/一键重算的版本记录父版本与触发方式
+// 2. 谱系树按父子关系挂载，并给出与父版本的差异
+// 3. 保留策略 dry-run 只预览不删除
+// 4. 清理删除版本及版本级数据，激活版本保留，写入 action_log
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{CapacityPoolBuilder, MaterialBuilder, MaterialStateBuilder};
+use hot_rolling_aps::domain::batching::BatchingDeferral;
+use hot_rolling_aps::domain::capacity::ReservationUsage;
+use hot_rolling_aps::domain::types::SchedState;
+use hot_rolling_aps::domain::version_lineage::{VersionRetentionPolicy, VersionTrigger};
+
+/// 返回 (方案ID, 激活版本V1, 重算版本V2)
+fn prepare_recalculated_plan(env: &ApiTestEnv) -> (String, String, String) {
+    let materials = vec![
+        MaterialBuilder::new("M001")
+            .machine("M1")
+            .weight(100.0)
+            .build(),
+        MaterialBuilder::new("M002")
+            .machine("M1")
+            .weight(150.0)
+            .build(),
+    ];
+    let states = vec![
+        MaterialStateBuilder::new("M001")
+            .sched_state(SchedState::Ready)
+            .build(),
+        MaterialStateBuilder::new("M002")
+            .sched_state(SchedState::Ready)
+            .build(),
+    ];
+    env.prepare_materials(materials, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("谱系测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let v1 = env
+        .plan_api
+        .create_version(plan_id.clone(), 7, None, None, "admin".to_string())
+        .expect("创建版本失败");
+
+    let base_date = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap();
+    let pools = (0..2)
+        .map(|offset| {
+            CapacityPoolBuilder::new("M1", base_date + chrono::Duration::days(offset))
+                .version_id(&v1)
+                .target(800.0)
+                .limit(900.0)
+                .build()
+        })
+        .collect();
+    env.prepare_capacity_pools(pools).expect("准备产能池失败");
+
+    env.approve_version(&v1).expect("审批失败");
+    env.plan_api
+        .activate_version(&v1, "admin")
+        .expect("激活失败");
+
+    let v2 = env
+        .plan_api
+        .recalc_full(&v1, base_date, None, "admin")
+        .expect("重算失败")
+        .version_id;
+    (plan_id, v1, v2)
+}
+
+#[test]
+fn test_lineage_tree_records_parent_and_trigger() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (plan_id, v1, v2) = prepare_recalculated_plan(&env);
+
+    let tree = env
+        .version_lineage_api
+        .get_version_lineage(&plan_id)
+        .expect("查询谱系失败");
+    assert_eq!(tree.version_count, 2);
+    assert_eq!(tree.roots.len(), 1);
+
+    let root = &tree.roots[0];
+    assert_eq!(root.version_id, v1);
+    assert_eq!(root.trigger, Some(VersionTrigger::Manual));
+    assert!(root.parent_version_id.is_none());
+
+    assert_eq!(root.children.len(), 1);
+    let child = &root.children[0];
+    assert_eq!(child.version_id, v2);
+    assert_eq!(child.parent_version_id.as_deref(), Some(v1.as_str()));
+    assert_eq!(child.trigger, Some(VersionTrigger::Recalc));
+    assert!(child.diff_from_parent.is_some(), "应给出与父版本的差异");
+}
+
+#[test]
+fn test_purge_versions_dry_run_then_execute() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (plan_id, v1, v2) = prepare_recalculated_plan(&env);
+    let policy = VersionRetentionPolicy {
+        keep_archived_per_plan: 0,
+        keep_ever_active: false,
+        max_age_days: Some(0),
+    };
+
+    // dry-run: 仅列出草稿 V2（V1 为激活版本，始终保留）
+    let preview = env
+        .version_lineage_api
+        .purge_versions(Some(&plan_id), policy.clone(), true, "admin")
+        .expect("预览失败");
+    assert!(preview.dry_run);
+    assert_eq!(preview.scanned_count, 2);
+    let ids: Vec<_> = preview
+        .candidates
+        .iter()
+        .map(|c| c.version_id.clone())
+        .collect();
+    assert_eq!(ids, vec![v2.clone()]);
+    assert!(preview.deleted_rows.is_empty());
+    assert!(env.plan_version_repo.find_by_id(&v2).unwrap().is_some());
+
+    // V2 的版本级数据：预留使用快照、集批顺延记录
+    let plan_date = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap();
+    env.capacity_pool_repo
+        .replace_reservation_usage(
+            &v2,
+            &["M1".to_string()],
+            plan_date,
+            plan_date,
+            &[ReservationUsage {
+                version_id: v2.clone(),
+                reservation_id: "R1".to_string(),
+                machine_code: "M1".to_string(),
+                plan_date,
+                reserved_t: 100.0,
+                used_t: 60.0,
+                released_t: 0.0,
+                held: true,
+            }],
+        )
+        .expect("写入预留使用失败");
+    env.plan_item_repo
+        .replace_batching_deferrals(
+            &v2,
+            &["M1".to_string()],
+            &[BatchingDeferral {
+                version_id: v2.clone(),
+                material_id: "M002".to_string(),
+                machine_code: "M1".to_string(),
+                plan_date,
+                grade_family: "PICKLED".to_string(),
+                reason: "GRADE_BATCHING".to_string(),
+            }],
+        )
+        .expect("写入顺延记录失败");
+
+    // 执行清理
+    let report = env
+        .version_lineage_api
+        .purge_versions(Some(&plan_id), policy, false, "admin")
+        .expect("清理失败");
+    assert_eq!(report.deleted_rows.get("plan_version"), Some(&1));
+    assert!(env.plan_version_repo.find_by_id(&v2).unwrap().is_none());
+    assert!(env.plan_version_repo.find_by_id(&v1).unwrap().is_some());
+    assert!(env
+        .plan_item_repo
+        .find_by_version(&v2)
+        .expect("查询明细失败")
+        .is_empty());
+    assert_eq!(
+        report.deleted_rows.get("capacity_reservation_usage"),
+        Some(&1)
+    );
+    assert!(env
+        .capacity_pool_repo
+        .find_reservation_usage_by_version(&v2)
+        .expect("查询预留使用失败")
+        .is_empty());
+    assert_eq!(report.deleted_rows.get("grade_batching_deferral"), Some(&1));
+    assert!(env
+        .plan_item_repo
+        .find_batching_deferrals_by_version(&v2)
+        .expect("查询顺延记录失败")
+        .is_empty());
+    assert_action_logged(&env, "PURGE_VERSIONS", 1).unwrap();
+
+    let tree = env
+        .version_lineage_api
+        .get_version_lineage(&plan_id)
+        .expect("查询谱系失败");
+    assert_eq!(tree.version_count, 1);
+    assert!(tree.roots[0].children.is_empty());
+}
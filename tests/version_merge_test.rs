@@ -0,0 +1,300 @@
+// ==========================================
+// 版本三方合并 集成测试
+// ==========================================
+// 测试范围:
+// 1. 人工调整识别: MANUAL 明细 + MOVE_ITEMS 日志
+// 2. 冲突识别: 材料不可排 / 产能超限
+// 3. 冲突处理后保存为草稿版本（明细/产能池/日志同一事务写入）
+// 4. 已撤销的移动不计入人工调整（撤销原样恢复来源字段）
+// ==========================================
+
+mod helpers;
+mod test_helpers;
+
+use chrono::NaiveDate;
+use helpers::api_test_helper::*;
+use helpers::test_data_builder::{CapacityPoolBuilder, PlanItemBuilder};
+use hot_rolling_aps::api::plan_api::{MergeConflictResolution, MoveItemRequest};
+use hot_rolling_aps::api::{ApiError, ValidationMode};
+use hot_rolling_aps::domain::types::SchedState;
+use hot_rolling_aps::engine::MergeConflictType;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 5, day).unwrap()
+}
+
+/// 返回 (祖先版本, 调整版本, 重算版本)
+///
+/// - 祖先: 1日 M1,M2; 2日 M3,C1
+/// - 调整: C1 人工提前到1日; M1/M2 经 move_items 移到2日
+/// - 重算: 1日 M1,M2; 2日 M3,M4; 2日产能上限 30t
+fn prepare_versions(env: &ApiTestEnv) -> (String, String, String) {
+    let ids = ["M1", "M2", "M3", "M4", "C1"];
+    let materials = ids
+        .iter()
+        .map(|id| create_test_material(id, "H032", 10.0, None))
+        .collect();
+    let states = ids
+        .iter()
+        .map(|id| {
+            let state = if *id == "C1" {
+                SchedState::Completed
+            } else {
+                SchedState::Ready
+            };
+            create_test_state(id, state, 0)
+        })
+        .collect();
+    env.prepare_materials(materials, states)
+        .expect("准备材料失败");
+
+    let plan_id = env
+        .plan_api
+        .create_plan("合并测试方案".to_string(), "admin".to_string())
+        .expect("创建方案失败");
+    let mut versions = Vec::new();
+    for _ in 0..3 {
+        versions.push(
+            env.plan_api
+                .create_version(plan_id.clone(), 7, None, None, "admin".to_string())
+                .expect("创建版本失败"),
+        );
+    }
+    let (ancestor, adjusted, recalculated) = (
+        versions[0].clone(),
+        versions[1].clone(),
+        versions[2].clone(),
+    );
+
+    let layout = |version: &str, rows: &[(&str, NaiveDate, i32)]| {
+        let items: Vec<_> = rows
+            .iter()
+            .map(|(id, date, seq)| {
+                PlanItemBuilder::new(version, id, "H032", *date)
+                    .seq_no(*seq)
+                    .weight(10.0)
+                    .build()
+            })
+            .collect();
+        env.plan_item_repo
+            .batch_insert(&items)
+            .expect("插入计划失败");
+    };
+    let base = [("M1", d(1), 1), ("M2", d(1), 2), ("M3", d(2), 1)];
+    layout(&ancestor, &[base[0], base[1], base[2], ("C1", d(2), 2)]);
+    layout(&adjusted, &base);
+    layout(&recalculated, &[base[0], base[1], base[2], ("M4", d(2), 2)]);
+
+    // 调整版本: C1 人工落位(无日志), M1/M2 通过 move_items 调整
+    let c1 = PlanItemBuilder::new(&adjusted, "C1", "H032", d(1))
+        .seq_no(1)
+        .weight(10.0)
+        .source_type("MANUAL")
+        .build();
+    env.plan_item_repo.batch_insert(&[c1]).expect("插入失败");
+    env.plan_api
+        .move_items(
+            &adjusted,
+            vec![
+                MoveItemRequest {
+                    material_id: "M1".to_string(),
+                    to_date: "2026-05-02".to_string(),
+                    to_seq: 1,
+                    to_machine: "H032".to_string(),
+                },
+                MoveItemRequest {
+                    material_id: "M2".to_string(),
+                    to_date: "2026-05-02".to_string(),
+                    to_seq: 3,
+                    to_machine: "H032".to_string(),
+                },
+            ],
+            ValidationMode::AutoFix,
+            "planner",
+            Some("人工调整"),
+        )
+        .expect("移动失败");
+
+    env.prepare_capacity_pools(vec![CapacityPoolBuilder::new("H032", d(2))
+        .version_id(&recalculated)
+        .limit(30.0)
+        .build()])
+        .expect("准备产能池失败");
+
+    (ancestor, adjusted, recalculated)
+}
+
+#[test]
+fn test_preview_version_merge_reports_conflicts() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (ancestor, adjusted, recalculated) = prepare_versions(&env);
+
+    let preview = env
+        .plan_api
+        .preview_version_merge(&ancestor, &adjusted, &recalculated)
+        .expect("预览失败");
+
+    assert_eq!(preview.manual_change_count, 3);
+    assert_eq!(preview.applied.len(), 1);
+    assert_eq!(preview.applied[0].material_id, "M1");
+
+    let conflicts: Vec<_> = preview
+        .conflicts
+        .iter()
+        .map(|c| (c.material_id.as_str(), c.conflict_type))
+        .collect();
+    assert_eq!(
+        conflicts,
+        vec![
+            ("C1", MergeConflictType::MaterialNotEligible),
+            ("M2", MergeConflictType::CapacityExceeded),
+        ]
+    );
+}
+
+#[test]
+fn test_apply_version_merge_requires_resolutions_and_saves_draft() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (ancestor, adjusted, recalculated) = prepare_versions(&env);
+
+    // 未处理冲突 → 拒绝保存
+    let err = env
+        .plan_api
+        .apply_version_merge(&ancestor, &adjusted, &recalculated, vec![], "planner")
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+
+    // 不可排材料不允许强制回放
+    let resolution = |id: &str, r: &str| MergeConflictResolution {
+        material_id: id.to_string(),
+        resolution: r.to_string(),
+    };
+    let err = env
+        .plan_api
+        .apply_version_merge(
+            &ancestor,
+            &adjusted,
+            &recalculated,
+            vec![
+                resolution("C1", "FORCE_MANUAL"),
+                resolution("M2", "FORCE_MANUAL"),
+            ],
+            "planner",
+        )
+        .unwrap_err();
+    assert!(matches!(err, ApiError::BusinessRuleViolation(_)));
+
+    let result = env
+        .plan_api
+        .apply_version_merge(
+            &ancestor,
+            &adjusted,
+            &recalculated,
+            vec![
+                resolution("C1", "KEEP_RECALC"),
+                resolution("M2", "FORCE_MANUAL"),
+            ],
+            "planner",
+        )
+        .expect("合并失败");
+    assert_eq!(result.applied_count, 2);
+    assert_eq!(result.forced_count, 1);
+    assert_eq!(result.dropped_count, 1);
+
+    let version = env
+        .plan_api
+        .get_version_detail(&result.merged_version_id)
+        .expect("查询版本失败");
+    assert!(version.is_draft());
+
+    // 产能池与日志随草稿版本一并写入
+    let pools = env
+        .capacity_pool_repo
+        .find_by_version_id(&result.merged_version_id)
+        .expect("查询产能池失败");
+    assert_eq!(pools.len(), 1);
+    let mut actions: Vec<_> = env
+        .action_log_repo
+        .find_by_version_id(&result.merged_version_id)
+        .expect("查询日志失败")
+        .into_iter()
+        .map(|log| log.action_type)
+        .collect();
+    actions.sort();
+    assert_eq!(actions, vec!["CREATE_VERSION", "MERGE_VERSIONS"]);
+
+    let mut day2: Vec<_> = env
+        .plan_item_repo
+        .find_by_version(&result.merged_version_id)
+        .expect("查询明细失败")
+        .into_iter()
+        .filter(|i| i.plan_date == d(2))
+        .collect();
+    day2.sort_by_key(|i| i.seq_no);
+    let order: Vec<_> = day2.iter().map(|i| i.material_id.as_str()).collect();
+    assert_eq!(order, vec!["M1", "M3", "M4", "M2"]);
+
+    // 重算/调整版本不受影响
+    let recalc_items = env
+        .plan_item_repo
+        .find_by_version(&recalculated)
+        .expect("查询明细失败");
+    assert!(recalc_items
+        .iter()
+        .any(|i| i.material_id == "M1" && i.plan_date == d(1)));
+}
+
+#[test]
+fn test_undone_move_is_not_treated_as_manual_change() {
+    let env = ApiTestEnv::new().expect("无法创建测试环境");
+    let (ancestor, adjusted, recalculated) = prepare_versions(&env);
+
+    // 调整版本上 M3 为引擎落位（位置与祖先不同，但非人工调整）
+    let m3 = PlanItemBuilder::new(&adjusted, "M3", "H032", d(2))
+        .seq_no(5)
+        .weight(10.0)
+        .build();
+    env.plan_item_repo.batch_upsert(&[m3]).expect("更新失败");
+
+    env.plan_api
+        .move_items(
+            &adjusted,
+            vec![MoveItemRequest {
+                material_id: "M3".to_string(),
+                to_date: "2026-05-01".to_string(),
+                to_seq: 3,
+                to_machine: "H032".to_string(),
+            }],
+            ValidationMode::AutoFix,
+            "planner",
+            None,
+        )
+        .expect("移动失败");
+    env.undo_api
+        .undo_last(&adjusted, "planner", None)
+        .expect("撤销失败");
+
+    // 撤销后来源与分配原因原样恢复
+    let restored = env
+        .plan_item_repo
+        .find_by_version(&adjusted)
+        .expect("查询明细失败")
+        .into_iter()
+        .find(|i| i.material_id == "M3")
+        .unwrap();
+    assert_eq!((restored.plan_date, restored.seq_no), (d(2), 5));
+    assert_eq!(restored.source_type, "CALC");
+    assert_eq!(restored.assign_reason.as_deref(), Some("TEST"));
+
+    // 已撤销的移动不计入人工调整
+    let preview = env
+        .plan_api
+        .preview_version_merge(&ancestor, &adjusted, &recalculated)
+        .expect("预览失败");
+    assert_eq!(preview.manual_change_count, 3);
+    assert!(preview
+        .applied
+        .iter()
+        .chain(preview.conflicts.iter().map(|c| &c.change))
+        .all(|c| c.material_id != "M3"));
+}